@@ -29,6 +29,28 @@ pub(crate) struct BackendError {
     pub kind: ErrorKind,
 }
 
+impl BackendError {
+    /// The category this error is counted under: the X11 error for errors reported by the X
+    /// server (e.g. `BadWindow`), the kind of the error otherwise.
+    pub fn category(&self) -> String {
+        let x11_error = self.src.as_deref().and_then(|src| {
+            match (
+                src.downcast_ref::<ReplyError>(),
+                src.downcast_ref::<ReplyOrIdError>(),
+            ) {
+                (Some(ReplyError::X11Error(e)), _) | (_, Some(ReplyOrIdError::X11Error(e))) => {
+                    Some(e)
+                }
+                _ => None,
+            }
+        });
+        match x11_error {
+            Some(e) => format!("{:?}", e.error_kind),
+            None => self.kind.to_string(),
+        }
+    }
+}
+
 impl std::error::Error for BackendError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self.src {
@@ -64,6 +86,7 @@ impl Display for BackendError {
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum ErrorKind {
     RootWindowNotFound,
+    PropertyNotFound,
     StringConversion,
     IntConversion,
 
@@ -76,6 +99,7 @@ impl Display for ErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
             ErrorKind::RootWindowNotFound => "RootWindowNotFound",
+            ErrorKind::PropertyNotFound => "PropertyNotFound",
             ErrorKind::StringConversion => "StringConversion",
             ErrorKind::IntConversion => "IntConversion",
             ErrorKind::XConnection => "XConnection",
@@ -2,7 +2,10 @@ use std::backtrace::BacktraceStatus;
 
 use leftwm_core::{
     models::{WindowChange, WindowHandle, WindowType, XyhwChange},
-    utils::modmask_lookup::{Button, ModMask},
+    utils::{
+        focus_stealing::{FocusLoopVerdict, FOCUS_LOOP_BACKOFF},
+        modmask_lookup::{Button, ModMask},
+    },
     DisplayEvent, Mode,
 };
 use x11rb::protocol::{xproto, Event};
@@ -13,10 +16,18 @@ use crate::{error::Result, X11rbWindowHandle};
 mod client_message;
 mod property_notify;
 
+pub(crate) use property_notify::from_settled_changes as settled_property_changes;
+
 /// Translate events from x11rb to leftwm's `DisplayEvent`
 pub(crate) fn translate(event: &Event, xw: &mut XWrap) -> Option<DisplayEvent<X11rbWindowHandle>> {
     let is_normal = xw.mode == Mode::Normal;
     let is_sloppy = xw.focus_behaviour.is_sloppy();
+    let _span = tracing::trace_span!(
+        "x_event",
+        kind = event_name(event),
+        window = event_window(event)
+    )
+    .entered();
 
     let res = match event {
         Event::MapRequest(e) => xw.setup_window(e.window),
@@ -28,8 +39,32 @@ pub(crate) fn translate(event: &Event, xw: &mut XWrap) -> Option<DisplayEvent<X1
         Event::ConfigureRequest(e) if is_normal => from_configure_request(e, xw),
         Event::EnterNotify(e) if is_normal && is_sloppy => Ok(from_enter_notify(e, xw)),
         Event::MotionNotify(e) => from_motion_notify(e, xw),
+        Event::ButtonPress(e) if is_normal && xw.is_frame(e.event) => {
+            Ok(Some(from_frame_button_press(e, xw)))
+        }
         Event::ButtonPress(e) => Ok(Some(from_button_press(e, xw))),
         Event::ButtonRelease(e) if !is_normal => from_button_release(e, xw),
+        Event::SelectionClear(e) if e.selection == xw.wm_selection => {
+            Ok(Some(DisplayEvent::WmReplaced))
+        }
+        Event::SelectionClear(e) => {
+            xw.clipboard_selection_clear(e);
+            Ok(None)
+        }
+        Event::SelectionRequest(e) => xw.clipboard_selection_request(e).map(|()| None),
+        Event::SelectionNotify(e) => xw.clipboard_selection_notify(e).map(|()| None),
+        Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
+            xw.screens_changed = true;
+            Ok(None)
+        }
+        Event::MappingNotify(e) if e.request != xproto::Mapping::POINTER => {
+            xw.refresh_lock_mask().map(|()| None)
+        }
+        Event::Expose(e) if e.count == 0 && xw.is_frame(e.window) => {
+            let window = xw.client_of(e.window);
+            xw.draw_title_bar(window, window == xw.focused_window)
+                .map(|()| None)
+        }
         _ => return None,
     };
     match res {
@@ -52,6 +87,53 @@ pub(crate) fn translate(event: &Event, xw: &mut XWrap) -> Option<DisplayEvent<X1
     }
 }
 
+/// Name of the events handled by [`translate`], for logging.
+fn event_name(event: &Event) -> &'static str {
+    match event {
+        Event::MapRequest(_) => "MapRequest",
+        Event::UnmapNotify(_) => "UnmapNotify",
+        Event::DestroyNotify(_) => "DestroyNotify",
+        Event::FocusIn(_) => "FocusIn",
+        Event::ClientMessage(_) => "ClientMessage",
+        Event::PropertyNotify(_) => "PropertyNotify",
+        Event::ConfigureRequest(_) => "ConfigureRequest",
+        Event::EnterNotify(_) => "EnterNotify",
+        Event::MotionNotify(_) => "MotionNotify",
+        Event::ButtonPress(_) => "ButtonPress",
+        Event::ButtonRelease(_) => "ButtonRelease",
+        Event::SelectionClear(_) => "SelectionClear",
+        Event::SelectionRequest(_) => "SelectionRequest",
+        Event::SelectionNotify(_) => "SelectionNotify",
+        Event::RandrScreenChangeNotify(_) => "RandrScreenChangeNotify",
+        Event::RandrNotify(_) => "RandrNotify",
+        Event::MappingNotify(_) => "MappingNotify",
+        Event::Expose(_) => "Expose",
+        _ => "Other",
+    }
+}
+
+/// Window the events handled by [`translate`] are about, for logging.
+fn event_window(event: &Event) -> Option<xproto::Window> {
+    match event {
+        Event::MapRequest(e) => Some(e.window),
+        Event::UnmapNotify(e) => Some(e.window),
+        Event::DestroyNotify(e) => Some(e.window),
+        Event::FocusIn(e) => Some(e.event),
+        Event::ClientMessage(e) => Some(e.window),
+        Event::PropertyNotify(e) => Some(e.window),
+        Event::ConfigureRequest(e) => Some(e.window),
+        Event::EnterNotify(e) => Some(e.event),
+        Event::MotionNotify(e) => Some(e.event),
+        Event::ButtonPress(e) | Event::ButtonRelease(e) => Some(e.event),
+        Event::SelectionClear(e) => Some(e.owner),
+        Event::SelectionRequest(e) => Some(e.requestor),
+        Event::SelectionNotify(e) => Some(e.requestor),
+        Event::RandrScreenChangeNotify(e) => Some(e.root),
+        Event::Expose(e) => Some(e.window),
+        _ => None,
+    }
+}
+
 fn from_unmap_event(
     event: &xproto::UnmapNotifyEvent,
     xw: &mut XWrap,
@@ -86,14 +168,35 @@ fn from_focus_in(
     xw: &mut XWrap,
 ) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
     // Check that if a window is taking focus, that it should be.
-    if xw.focused_window != event.event {
-        let never_focus = match xw.get_wmhints(xw.focused_window)? {
-            Some(hint) => !hint.input.unwrap_or(true),
-            None => false,
-        };
-        xw.focus(xw.focused_window, never_focus)?;
+    if xw.focused_window == event.event {
+        return Ok(None);
+    }
+    match xw.focus_loop.check(xw.focused_window, event.event) {
+        FocusLoopVerdict::Reassert => {
+            let never_focus = match xw.get_wmhints(xw.focused_window)? {
+                Some(hint) => !hint.input.unwrap_or(true),
+                None => false,
+            };
+            xw.focus(xw.focused_window, never_focus)?;
+            Ok(None)
+        }
+        FocusLoopVerdict::LoopDetected => {
+            tracing::warn!(
+                "Window {:#x} ({}) keeps taking the focus from window {:#x}, not taking it back for {:?}",
+                event.event,
+                xw.get_window_name(event.event).unwrap_or_default(),
+                xw.focused_window,
+                FOCUS_LOOP_BACKOFF
+            );
+            if !xw.mark_focus_stealers_never_focus {
+                return Ok(None);
+            }
+            let mut change = WindowChange::new(WindowHandle(X11rbWindowHandle(event.event)));
+            change.never_focus = Some(true);
+            Ok(Some(DisplayEvent::WindowChange(change)))
+        }
+        FocusLoopVerdict::BackingOff => Ok(None),
     }
-    Ok(None)
 }
 
 fn from_configure_request(
@@ -159,7 +262,7 @@ fn from_enter_notify(
         return None;
     }
 
-    let h = WindowHandle(X11rbWindowHandle(event.event));
+    let h = WindowHandle(X11rbWindowHandle(xw.client_of(event.event)));
     Some(DisplayEvent::WindowTakeFocus(h))
 }
 
@@ -195,15 +298,27 @@ fn from_motion_notify(
     Ok(None)
 }
 
+/// Clicks on a frame are on the title bar or the border of the window inside.
+fn from_frame_button_press(
+    event: &xproto::ButtonPressEvent,
+    xw: &mut XWrap,
+) -> DisplayEvent<X11rbWindowHandle> {
+    let window = xw.client_of(event.event);
+    let on_title_bar = u16::try_from(event.event_y).is_ok_and(|y| y < xw.title_bar_of(window));
+    if event.detail == 1 && on_title_bar {
+        return DisplayEvent::TitleBarPressed(WindowHandle(X11rbWindowHandle(window)));
+    }
+    from_button_press(event, xw)
+}
+
 fn from_button_press(
     event: &xproto::ButtonPressEvent,
-    _xw: &mut XWrap,
+    xw: &mut XWrap,
 ) -> DisplayEvent<X11rbWindowHandle> {
-    let h = WindowHandle(X11rbWindowHandle(event.event));
-    let mod_mask = event.state;
-    mod_mask.remove(xproto::KeyButMask::MOD2 | xproto::KeyButMask::LOCK);
+    let h = WindowHandle(X11rbWindowHandle(xw.client_of(event.event)));
+    let mod_mask = u16::from(event.state) & !xw.lock_mask();
     DisplayEvent::MouseCombo(
-        ModMask::from_bits_retain(mod_mask.bits()),
+        ModMask::from_bits_retain(mod_mask),
         Button::from(event.detail),
         h,
         i32::from(event.root_x),
@@ -212,9 +327,10 @@ fn from_button_press(
 }
 
 fn from_button_release(
-    _event: &xproto::ButtonReleaseEvent,
+    event: &xproto::ButtonReleaseEvent,
     xw: &mut XWrap,
 ) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
     xw.set_mode(Mode::Normal)?;
-    Ok(Some(DisplayEvent::ChangeToNormalMode))
+    let (x, y) = (i32::from(event.root_x), i32::from(event.root_y));
+    Ok(Some(DisplayEvent::ChangeToNormalMode(x, y)))
 }
@@ -1,6 +1,6 @@
 use leftwm_core::{
-    models::{WindowChange, WindowHandle},
-    Command, DisplayEvent,
+    models::{Mode, WindowChange, WindowHandle},
+    Command, DisplayEvent, WindowSelector,
 };
 use x11rb::protocol::xproto;
 
@@ -8,9 +8,20 @@ use crate::{xwrap::XWrap, X11rbWindowHandle};
 
 use crate::error::Result;
 
+/// Source indication of `_NET_ACTIVE_WINDOW` requests sent by pagers and taskbars.
+const SOURCE_PAGER: u32 = 2;
+
+/// Directions of `_NET_WM_MOVERESIZE` requests, from 0 up to `SIZE_LEFT` the window is resized
+/// from an edge or corner.
+const MOVERESIZE_SIZE_LEFT: u32 = 7;
+const MOVERESIZE_MOVE: u32 = 8;
+const MOVERESIZE_SIZE_KEYBOARD: u32 = 9;
+const MOVERESIZE_MOVE_KEYBOARD: u32 = 10;
+const MOVERESIZE_CANCEL: u32 = 11;
+
 pub(crate) fn from_event(
     event: &xproto::ClientMessageEvent,
-    xw: &XWrap,
+    xw: &mut XWrap,
 ) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
     if !xw.managed_windows.contains(&event.window) && event.window != xw.get_default_root() {
         return Ok(None);
@@ -18,6 +29,21 @@ pub(crate) fn from_event(
     let atom_name = xw.atoms.get_name(event.type_);
     tracing::trace!("ClientMessage: {} : {:?}", event.window, atom_name);
 
+    // Replies to `_NET_WM_PING`, sent back to the root window.
+    if event.type_ == xw.atoms.WMProtocols {
+        let data = event.data.as_data32();
+        if data[0] != xw.atoms.NetWMPing || !xw.ping_watch.pong(&data[2]) {
+            return Ok(None);
+        }
+        let handle = WindowHandle(X11rbWindowHandle(data[2]));
+        return Ok(Some(DisplayEvent::WindowResponding(handle, true)));
+    }
+
+    if event.type_ == xw.atoms.NetCloseWindow {
+        xw.kill_window(WindowHandle(X11rbWindowHandle(event.window)))?;
+        return Ok(None);
+    }
+
     if event.type_ == xw.atoms.NetCurrentDesktop {
         let value = event.data.as_data32();
         match usize::try_from(value[0]) {
@@ -61,8 +87,30 @@ pub(crate) fn from_event(
     }
 
     if event.type_ == xw.atoms.NetActiveWindow {
-        xw.set_window_urgency(event.window, true)?;
-        return Ok(None);
+        let data = event.data.as_data32();
+        // Applications may only move the focus away from their own focused window, other
+        // requests get the attention of the user instead of stealing the focus.
+        let granted =
+            data[0] == SOURCE_PAGER || (data[2] != x11rb::NONE && data[2] == xw.focused_window);
+        if !granted {
+            xw.set_window_urgency(event.window, true)?;
+            return Ok(None);
+        }
+        let handle = WindowHandle(X11rbWindowHandle(event.window));
+        let event =
+            DisplayEvent::SendCommand(Command::ActivateWindow(WindowSelector::Handle(handle)));
+        return Ok(Some(event));
+    }
+
+    if event.type_ == xw.atoms.NetWMMoveResize {
+        let handle = WindowHandle(X11rbWindowHandle(event.window));
+        let mode = match event.data.as_data32()[2] {
+            MOVERESIZE_MOVE | MOVERESIZE_MOVE_KEYBOARD => Mode::ReadyToMove(handle),
+            0..=MOVERESIZE_SIZE_LEFT | MOVERESIZE_SIZE_KEYBOARD => Mode::ReadyToResize(handle),
+            MOVERESIZE_CANCEL => Mode::Normal,
+            _ => return Ok(None),
+        };
+        return Ok(Some(DisplayEvent::MoveResizeRequest(mode)));
     }
 
     if event.type_ == xw.atoms.NetWMState {
@@ -9,7 +9,7 @@ use crate::{error::Result, X11rbWindowHandle};
 
 pub(crate) fn from_event(
     event: &xproto::PropertyNotifyEvent,
-    xw: &XWrap,
+    xw: &mut XWrap,
 ) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
     if event.window == xw.get_default_root()
         || event.state == xproto::Property::DELETE
@@ -17,7 +17,52 @@ pub(crate) fn from_event(
     {
         return Ok(None);
     }
+    // Further changes shortly after this one are handled once the property settled.
+    if !xw.property_throttle.allow((event.window, event.atom)) {
+        return Ok(None);
+    }
+    translate(event, xw)
+}
+
+/// Translate the property changes held back by the throttle that are due, and request a wakeup
+/// for the ones still waiting.
+pub(crate) fn from_settled_changes(xw: &mut XWrap) -> Vec<DisplayEvent<X11rbWindowHandle>> {
+    let mut events = vec![];
+    for (window, atom) in xw.property_throttle.take_due() {
+        if !xw.managed_windows.contains(&window) {
+            continue;
+        }
+        let event = xproto::PropertyNotifyEvent {
+            response_type: xproto::PROPERTY_NOTIFY_EVENT,
+            sequence: 0,
+            window,
+            atom,
+            time: x11rb::CURRENT_TIME,
+            state: xproto::Property::NEW_VALUE,
+        };
+        match translate(&event, xw) {
+            Ok(Some(event)) => events.push(event),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("Error when translating a settled property change: {}", e);
+                xw.count_error(e.category());
+            }
+        }
+    }
+    if let Some(delay) = xw.property_throttle.schedule_wakeup() {
+        let notify = xw.task_notify.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            notify.notify_one();
+        });
+    }
+    events
+}
 
+fn translate(
+    event: &xproto::PropertyNotifyEvent,
+    xw: &XWrap,
+) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
     let event_name = xw.get_xatom_name(event.atom)?;
     tracing::trace!("PropertyNotify: {} : {:?}", event_name, &event);
 
@@ -78,6 +123,19 @@ pub(crate) fn from_event(
                 }
             }
 
+            if event.atom == xw.atoms.MotifWMHints {
+                let mut change = WindowChange::new(WindowHandle(X11rbWindowHandle(event.window)));
+                change.undecorated = Some(xw.get_window_undecorated(event.window)?);
+                return Ok(Some(DisplayEvent::WindowChange(change)));
+            }
+
+            if event.atom == xw.atoms.WMColormapWindows {
+                if event.window == xw.focused_window {
+                    xw.install_colormaps(event.window)?;
+                }
+                return Ok(None);
+            }
+
             if event.atom == xw.atoms.NetWMState {
                 let handle = WindowHandle(X11rbWindowHandle(event.window));
                 let mut change = WindowChange::new(handle);
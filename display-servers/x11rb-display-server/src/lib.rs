@@ -1,10 +1,12 @@
 //! x11rb backend for leftwm
 
 use leftwm_core::{
-    models::{Handle, Screen, TagId, WindowHandle, WindowState},
+    config,
+    models::{BorderColors, Handle, Screen, TagId, WindowHandle, WindowState, WindowType},
     Config, DisplayAction, DisplayEvent, DisplayServer, Mode, Window, Workspace,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use x11rb::protocol::xproto;
 
 use crate::xwrap::XWrap;
@@ -24,6 +26,11 @@ pub struct X11rbDisplayServer {
     xw: XWrap,
     root: xproto::Window,
     initial_events: Vec<DisplayEvent<X11rbWindowHandle>>,
+    /// The workspaces of the config, and whether screens not in them get a workspace too.
+    workspaces: Option<Vec<config::Workspace>>,
+    auto_derive_workspaces: bool,
+    /// The screens the manager was told about.
+    screens: Vec<Screen<X11rbWindowHandle>>,
 }
 
 impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
@@ -38,6 +45,9 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
             xw: xwrap,
             root,
             initial_events: Vec::new(),
+            workspaces: config.workspaces(),
+            auto_derive_workspaces: config.auto_derive_workspaces(),
+            screens: Vec::new(),
         };
         instance.initial_events = instance.initial_events(config);
 
@@ -52,16 +62,25 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
     ) {
         if let Err(e) = self.xw.load_config(config) {
             tracing::error!("Error when loading config: {}", e);
+            self.xw.count_error(e.category());
         }
+        self.workspaces = config.workspaces();
+        self.auto_derive_workspaces = config.auto_derive_workspaces();
         if let Err(e) = self.xw.update_colors(focused, windows) {
             tracing::error!("Error when updating border colors: {}", e);
+            self.xw.count_error(e.category());
         }
     }
 
     fn update_windows(&self, windows: Vec<&Window<X11rbWindowHandle>>) {
-        for window in &windows {
-            if let Err(e) = self.xw.update_window(window) {
+        // Ask for all the states up front, the changes are then sent without waiting on the
+        // server and flushed together at the end of the loop iteration.
+        let handles: Vec<_> = windows.iter().map(|w| w.handle.0 .0).collect();
+        let states = self.xw.get_wm_states(&handles);
+        for (window, state) in windows.iter().zip(states) {
+            if let Err(e) = state.and_then(|state| self.xw.update_window(window, state)) {
                 tracing::error!("Error when updating window {:?}: {}", window, e);
+                self.xw.count_error(e.category());
             }
         }
     }
@@ -70,6 +89,7 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
         if let Some(focused) = focused {
             if let Err(e) = self.xw.set_current_desktop(focused.tag) {
                 tracing::error!("Error when setting current desktop to {:?}: {}", focused, e);
+                self.xw.count_error(e.category());
             }
         }
     }
@@ -79,6 +99,9 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
 
         loop {
             match self.xw.poll_next_event() {
+                Ok(Some(x11rb::protocol::Event::Error(e))) => {
+                    self.xw.report_x11_error(&e);
+                }
                 Ok(Some(ev)) => {
                     tracing::trace!("New event received: {:?}", ev);
                     if let Some(ev) = event_translate::translate(&ev, &mut self.xw) {
@@ -88,15 +111,28 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
                 Ok(None) => break,
                 Err(e) => {
                     tracing::error!("An error occurred when polling for events. {:?}", e);
+                    self.xw.count_error(e.category());
                     break;
                 }
             }
         }
 
+        if std::mem::take(&mut self.xw.screens_changed) {
+            events.append(&mut self.screen_changes());
+        }
+
+        if self.xw.mode == Mode::Normal {
+            events.append(&mut event_translate::settled_property_changes(&mut self.xw));
+        }
+
+        events.extend(self.idle_event());
+        events.append(&mut self.unresponsive_events());
+
         for event in &events {
             if let DisplayEvent::WindowDestroy(WindowHandle(X11rbWindowHandle(w))) = event {
                 if let Err(e) = self.xw.force_unmapped(*w) {
                     tracing::error!(error = ?e, "Error when forcing unmapping of window {}", w);
+                    self.xw.count_error(e.category());
                 };
             }
         }
@@ -112,6 +148,7 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
         let xw = &mut self.xw;
         let event: Result<Option<DisplayEvent<X11rbWindowHandle>>> = match act.clone() {
             DisplayAction::KillWindow(h) => from_kill_window(xw, h),
+            DisplayAction::ForceKillWindow(h) => from_force_kill_window(xw, h),
             DisplayAction::AddedWindow(h, f, fm) => xw.setup_managed_window(h, f, fm),
             DisplayAction::MoveMouseOver(h, f) => from_move_mouse_over(xw, h, f),
             DisplayAction::MoveMouseOverPoint(p) => from_move_mouse_over_point(xw, p),
@@ -124,8 +161,12 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
             DisplayAction::ReadyToMoveWindow(h) => from_ready_to_move_window(xw, h),
             DisplayAction::ReadyToResizeWindow(h) => from_ready_to_resize_window(xw, h),
             DisplayAction::SetCurrentTags(t) => from_set_current_tags(xw, t),
+            DisplayAction::SetDesktopNames(labels) => from_set_desktop_names(xw, &labels),
             DisplayAction::SetWindowTag(h, t) => from_set_window_tag(xw, h, t),
             DisplayAction::ConfigureXlibWindow(w) => from_configure_xlib_window(xw, &w),
+            DisplayAction::SetWindowOpacity(h, o) => from_set_window_opacity(xw, h, o),
+            DisplayAction::SetWindowBorderColors(h, c) => from_set_window_border_colors(xw, h, &c),
+            DisplayAction::SetWindowUrgency(h, u) => from_set_window_urgency(xw, h, u),
 
             DisplayAction::WindowTakeFocus {
                 window,
@@ -134,6 +175,8 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
 
             DisplayAction::FocusWindowUnderCursor => from_focus_window_under_cursor(xw),
             DisplayAction::NormalMode => from_normal_mode(xw),
+            DisplayAction::ReacquireWmSelection => from_reacquire_wm_selection(xw),
+            DisplayAction::ReloadKeyGrabs => from_reload_key_grabs(xw),
         };
         match event {
             Ok(ev) => {
@@ -148,6 +191,7 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
                     act,
                     e
                 );
+                self.xw.count_error(e.category());
                 None
             }
         }
@@ -163,9 +207,14 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
     fn flush(&self) {
         if let Err(e) = self.xw.flush() {
             tracing::error!(error = ?e, "Error when flushing the connection.");
+            self.xw.count_error(e.category());
         }
     }
 
+    fn error_counts(&self) -> BTreeMap<String, usize> {
+        self.xw.error_counts.borrow().clone()
+    }
+
     fn generate_verify_focus_event(&self) -> Option<leftwm_core::DisplayEvent<X11rbWindowHandle>> {
         let handle = self.xw.get_cursor_window().ok()?;
         Some(DisplayEvent::VerifyFocusedAt(handle))
@@ -173,78 +222,170 @@ impl DisplayServer<X11rbWindowHandle> for X11rbDisplayServer {
 }
 
 impl X11rbDisplayServer {
-    fn initial_events(&self, config: &impl Config) -> Vec<DisplayEvent<X11rbWindowHandle>> {
-        let mut events = vec![];
-        if let Some(workspaces) = config.workspaces() {
-            let screens = match self.xw.get_screens() {
-                Ok(s) => s,
-                Err(e) => {
-                    tracing::error!(error = ?e, "An error occurred when trying to get screens.");
-                    return events;
-                }
-            };
-
-            for (i, wsc) in workspaces.iter().enumerate() {
-                let mut screen = Screen::from(wsc);
-                screen.root = WindowHandle(X11rbWindowHandle(self.root));
-                // If there is a screen corresponding to the given output, create the workspace
-                match screens.iter().find(|i| i.output == wsc.output) {
-                    Some(output_match) => {
-                        if wsc.relative.unwrap_or(false) {
-                            screen.bbox.add(output_match.bbox);
-                        }
-                        screen.id = Some(i + 1);
-                    }
-                    None => continue,
-                }
-                let e = DisplayEvent::ScreenCreate(screen);
-                events.push(e);
+    /// Whether the user became idle or active again, waking up to look again later.
+    fn idle_event(&mut self) -> Option<DisplayEvent<X11rbWindowHandle>> {
+        if !self.xw.idle_watch.is_enabled() || !self.xw.idle_watch.is_due() {
+            return None;
+        }
+        let idle_for = match self.xw.get_idle_time() {
+            Ok(idle_for) => idle_for,
+            Err(e) => {
+                tracing::error!(error = ?e, "Error when querying the idle time.");
+                self.xw.count_error(e.category());
+                return None;
             }
-
-            let auto_derive_workspaces: bool = if config.auto_derive_workspaces() {
-                true
-            } else if events.is_empty() {
-                tracing::warn!("No Workspace in Workspace config matches connected screen. Falling back to \"auto_derive_workspaces: true\".");
-                true
+        };
+        let event = self.xw.idle_watch.update(idle_for).map(|idle| {
+            if idle {
+                DisplayEvent::UserIdle
             } else {
-                false
-            };
-
-            let mut next_id = workspaces.len() + 1;
-
-            // If there is no hardcoded workspace layout, add every screen not mentioned in the config.
-            if auto_derive_workspaces {
-                screens
-                    .iter()
-                    .filter(|screen| !workspaces.iter().any(|wsc| wsc.output == screen.output))
-                    .for_each(|screen| {
-                        let mut s = screen.clone();
-                        s.id = Some(next_id);
-                        next_id += 1;
-                        events.push(DisplayEvent::ScreenCreate(s));
-                    });
+                DisplayEvent::UserActive
             }
+        });
+        if let Some(delay) = self.xw.idle_watch.schedule_wakeup(idle_for) {
+            let notify = self.xw.task_notify.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                notify.notify_one();
+            });
+        }
+        event
+    }
+
+    /// The windows asked to close which did not answer a ping in time, waking up when the next
+    /// one is due.
+    fn unresponsive_events(&mut self) -> Vec<DisplayEvent<X11rbWindowHandle>> {
+        let events = self
+            .xw
+            .ping_watch
+            .take_unresponsive()
+            .into_iter()
+            .map(|w| DisplayEvent::WindowResponding(WindowHandle(X11rbWindowHandle(w)), false))
+            .collect();
+        if let Some(delay) = self.xw.ping_watch.schedule_wakeup() {
+            let notify = self.xw.task_notify.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                notify.notify_one();
+            });
+        }
+        events
+    }
+
+    fn initial_events(&mut self, config: &impl Config) -> Vec<DisplayEvent<X11rbWindowHandle>> {
+        let mut events = vec![];
+        if let Some(screens) = self.workspace_screens(true) {
+            events.extend(screens.iter().cloned().map(DisplayEvent::ScreenCreate));
+            self.screens = screens;
         }
 
         // Tell manager about existing windows.
-        events.append(&mut self.find_all_windows());
+        let tag_count = config.create_list_of_tag_labels().len();
+        events.append(&mut self.find_all_windows(tag_count));
+
+        events
+    }
+
+    /// The screens to create a workspace for: those of the config whose output is connected,
+    /// then the other ones when deriving workspaces. The derived screens are numbered at startup,
+    /// and left for the manager to number later on.
+    fn workspace_screens(&self, initial: bool) -> Option<Vec<Screen<X11rbWindowHandle>>> {
+        let workspaces = self.workspaces.as_ref()?;
+        let screens = match self.xw.get_screens() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::error!(error = ?e, "An error occurred when trying to get screens.");
+                self.xw.count_error(e.category());
+                return None;
+            }
+        };
+
+        let mut created = vec![];
+        for (i, wsc) in workspaces.iter().enumerate() {
+            let mut screen = Screen::from(wsc);
+            screen.root = WindowHandle(X11rbWindowHandle(self.root));
+            // If there is a screen corresponding to the given output, create the workspace
+            match screens.iter().find(|i| i.output == wsc.output) {
+                Some(output_match) => {
+                    screen.bbox = wsc.bbox_on(output_match.bbox);
+                    screen.id = Some(i + 1);
+                }
+                None => continue,
+            }
+            created.push(screen);
+        }
+
+        let auto_derive_workspaces: bool = if self.auto_derive_workspaces {
+            true
+        } else if created.is_empty() {
+            tracing::warn!("No Workspace in Workspace config matches connected screen. Falling back to \"auto_derive_workspaces: true\".");
+            true
+        } else {
+            false
+        };
+
+        let mut next_id = workspaces.len() + 1;
+
+        // If there is no hardcoded workspace layout, add every screen not mentioned in the config.
+        if auto_derive_workspaces {
+            screens
+                .iter()
+                .filter(|screen| !workspaces.iter().any(|wsc| wsc.output == screen.output))
+                .for_each(|screen| {
+                    let mut s = screen.clone();
+                    s.id = initial.then_some(next_id);
+                    next_id += 1;
+                    created.push(s);
+                });
+        }
+        Some(created)
+    }
 
+    /// Tell the manager about the screens which went away or appeared since it was last told,
+    /// after RandR reported a change. A screen which moved or was resized is replaced.
+    fn screen_changes(&mut self) -> Vec<DisplayEvent<X11rbWindowHandle>> {
+        let Some(screens) = self.workspace_screens(false) else {
+            return vec![];
+        };
+        let same = |a: &Screen<X11rbWindowHandle>, b: &Screen<X11rbWindowHandle>| {
+            a.output == b.output
+                && (a.bbox.x, a.bbox.y, a.bbox.width, a.bbox.height)
+                    == (b.bbox.x, b.bbox.y, b.bbox.width, b.bbox.height)
+        };
+        let mut events: Vec<_> = self
+            .screens
+            .iter()
+            .filter(|known| !screens.iter().any(|s| same(known, s)))
+            .map(|known| DisplayEvent::ScreenDestroy(known.output.clone()))
+            .collect();
+        events.extend(
+            screens
+                .iter()
+                .filter(|s| !self.screens.iter().any(|known| same(known, s)))
+                .cloned()
+                .map(DisplayEvent::ScreenCreate),
+        );
+        self.screens = screens;
         events
     }
 
-    fn find_all_windows(&self) -> Vec<DisplayEvent<X11rbWindowHandle>> {
+    /// Adopt the windows already mapped, normal windows back on the tag of their
+    /// `_NET_WM_DESKTOP` when it is one of the `tag_count` tags.
+    fn find_all_windows(&self, tag_count: usize) -> Vec<DisplayEvent<X11rbWindowHandle>> {
         let mut all: Vec<DisplayEvent<X11rbWindowHandle>> = Vec::new();
         match self.xw.get_all_windows() {
             Ok(handles) => handles.into_iter().for_each(|handle| {
                 let attrs = match self.xw.get_window_attrs(handle) {
                     Ok(a) => a,
                     Err(e) => {
+                        self.xw.count_error(e.category());
                         return tracing::error!(window_handle = handle, error = ?e, "Error when getting window attributes.");
                     }
                 };
                 let state = match self.xw.get_wm_state(handle) {
                     Ok((s, _)) => s,
                     Err(e) => {
+                        self.xw.count_error(e.category());
                         return tracing::error!(window_handle = handle, error = ?e, "Error when getting WM_STATE atom.");
                     }
                 };
@@ -252,16 +393,32 @@ impl X11rbDisplayServer {
                     || state == xatom::WMStateWindowState::Iconic
                 {
                     match self.xw.setup_window(handle) {
+                        Ok(Some(DisplayEvent::WindowCreate(mut window, x, y)))
+                            if window.r#type == WindowType::Normal =>
+                        {
+                            window.tag = match self.xw.get_window_desktop(handle) {
+                                Ok(desktop) => desktop.filter(|&d| d < tag_count).map(|d| d + 1),
+                                Err(e) => {
+                                    self.xw.count_error(e.category());
+                                    None
+                                }
+                            };
+                            all.push(DisplayEvent::WindowCreate(window, x, y));
+                        }
                         Ok(Some(event)) => {
                             all.push(event);
                         }
-                        Err(e) => tracing::error!(window_handle = handle, error = ?e, "Error when setting up window."),
+                        Err(e) => {
+                            tracing::error!(window_handle = handle, error = ?e, "Error when setting up window.");
+                            self.xw.count_error(e.category());
+                        }
                         _ => (),
                     }
                 }
             }),
             Err(err) => {
                 tracing::error!(error = ?err, "An error occurred.");
+                self.xw.count_error(err.category());
             }
         }
         all
@@ -277,6 +434,14 @@ fn from_kill_window(
     Ok(None)
 }
 
+fn from_force_kill_window(
+    xw: &mut XWrap,
+    handle: WindowHandle<X11rbWindowHandle>,
+) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    xw.force_kill_window(handle)?;
+    Ok(None)
+}
+
 fn from_move_mouse_over(
     xw: &mut XWrap,
     handle: WindowHandle<X11rbWindowHandle>,
@@ -366,7 +531,7 @@ fn from_set_window_order(
     let unmanaged: Vec<WindowHandle<X11rbWindowHandle>> = xw
         .get_all_windows()?
         .iter()
-        .filter(|&w| *w != xw.get_default_root())
+        .filter(|&w| *w != xw.get_default_root() && !xw.is_frame(*w))
         .map(|&w| WindowHandle(X11rbWindowHandle(w)))
         .filter(|h| !windows.iter().any(|&w| w == *h))
         .collect();
@@ -427,6 +592,44 @@ fn from_configure_xlib_window(
     Ok(None)
 }
 
+fn from_set_window_opacity(
+    xw: &mut XWrap,
+    handle: WindowHandle<X11rbWindowHandle>,
+    opacity: Option<f32>,
+) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    let WindowHandle(X11rbWindowHandle(window)) = handle;
+    xw.set_window_opacity(window, opacity)?;
+    Ok(None)
+}
+
+fn from_set_window_border_colors(
+    xw: &mut XWrap,
+    handle: WindowHandle<X11rbWindowHandle>,
+    colors: &BorderColors,
+) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    let WindowHandle(X11rbWindowHandle(window)) = handle;
+    xw.set_window_border_colors(window, colors)?;
+    Ok(None)
+}
+
+fn from_set_desktop_names(
+    xw: &mut XWrap,
+    labels: &[String],
+) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    xw.set_desktop_names(labels)?;
+    Ok(None)
+}
+
+fn from_set_window_urgency(
+    xw: &mut XWrap,
+    handle: WindowHandle<X11rbWindowHandle>,
+    urgent: bool,
+) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    let WindowHandle(X11rbWindowHandle(window)) = handle;
+    xw.set_window_urgency(window, urgent)?;
+    Ok(None)
+}
+
 fn from_window_take_focus(
     xw: &mut XWrap,
     window: &Window<X11rbWindowHandle>,
@@ -453,3 +656,13 @@ fn from_normal_mode(xw: &mut XWrap) -> Result<Option<DisplayEvent<X11rbWindowHan
     xw.set_mode(Mode::Normal)?;
     Ok(None)
 }
+
+fn from_reacquire_wm_selection(xw: &mut XWrap) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    xw.reacquire_wm_selection()?;
+    Ok(None)
+}
+
+fn from_reload_key_grabs(xw: &mut XWrap) -> Result<Option<DisplayEvent<X11rbWindowHandle>>> {
+    xw.regrab_mouse_clicks()?;
+    Ok(None)
+}
@@ -1,3 +1,4 @@
+use leftwm_core::utils::text_property::TextEncoding;
 use x11rb::{atom_manager, protocol::xproto};
 
 atom_manager! {
@@ -11,6 +12,8 @@ atom_manager! {
         WMState: b"WM_STATE",
         WMClass: b"WM_CLASS",
         WMTakeFocus: b"WM_TAKE_FOCUS",
+        WMColormapWindows: b"WM_COLORMAP_WINDOWS",
+        MotifWMHints: b"_MOTIF_WM_HINTS",
         NetActiveWindow: b"_NET_ACTIVE_WINDOW",
         NetSupported: b"_NET_SUPPORTED",
         NetWMName: b"_NET_WM_NAME",
@@ -50,6 +53,10 @@ atom_manager! {
         NetWMWindowTypeUtility: b"_NET_WM_WINDOW_TYPE_UTILITY",
         NetWMWindowTypeSplash: b"_NET_WM_WINDOW_TYPE_SPLASH",
         NetWMWindowTypeDialog: b"_NET_WM_WINDOW_TYPE_DIALOG",
+        NetWMWindowTypeNotification: b"_NET_WM_WINDOW_TYPE_NOTIFICATION",
+        NetWMWindowTypePopupMenu: b"_NET_WM_WINDOW_TYPE_POPUP_MENU",
+        NetWMWindowTypeDropdownMenu: b"_NET_WM_WINDOW_TYPE_DROPDOWN_MENU",
+        NetWMWindowTypeTooltip: b"_NET_WM_WINDOW_TYPE_TOOLTIP",
         NetSupportingWmCheck: b"_NET_SUPPORTING_WM_CHECK",
 
         NetClientList: b"_NET_CLIENT_LIST",
@@ -58,10 +65,26 @@ atom_manager! {
         NetCurrentDesktop: b"_NET_CURRENT_DESKTOP",
         NetDesktopNames: b"_NET_DESKTOP_NAMES",
         NetWMDesktop: b"_NET_WM_DESKTOP",
+        NetWMMoveResize: b"_NET_WM_MOVERESIZE",
+        NetCloseWindow: b"_NET_CLOSE_WINDOW",
+        NetWMPing: b"_NET_WM_PING",
         NetWMStrutPartial: b"_NET_WM_STRUT_PARTIAL",
         NetWMStrut: b"_NET_WM_STRUT",
+        NetWMWindowOpacity: b"_NET_WM_WINDOW_OPACITY",
 
         UTF8String: b"UTF8_STRING",
+        CompoundText: b"COMPOUND_TEXT",
+
+        Clipboard: b"CLIPBOARD",
+        ClipboardManager: b"CLIPBOARD_MANAGER",
+        Manager: b"MANAGER",
+        SaveTargets: b"SAVE_TARGETS",
+        Targets: b"TARGETS",
+        Multiple: b"MULTIPLE",
+        Timestamp: b"TIMESTAMP",
+        Delete: b"DELETE",
+        Incr: b"INCR",
+        LeftwmClipboard: b"_LEFTWM_CLIPBOARD",
 
         WMNormalHints: b"WM_NORMAL_HINTS",
         WMSizeHints: b"WM_SIZE_HINTS",
@@ -107,6 +130,10 @@ impl AtomCollection {
             self.NetWMWindowTypeUtility,
             self.NetWMWindowTypeSplash,
             self.NetWMWindowTypeDialog,
+            self.NetWMWindowTypeNotification,
+            self.NetWMWindowTypePopupMenu,
+            self.NetWMWindowTypeDropdownMenu,
+            self.NetWMWindowTypeTooltip,
             self.NetSupportingWmCheck,
             self.NetClientList,
             self.NetDesktopViewport,
@@ -114,6 +141,9 @@ impl AtomCollection {
             self.NetCurrentDesktop,
             self.NetDesktopNames,
             self.NetWMDesktop,
+            self.NetWMMoveResize,
+            self.NetCloseWindow,
+            self.NetWMPing,
             self.NetWMStrutPartial,
             self.NetWMStrut,
         ]
@@ -128,6 +158,7 @@ impl AtomCollection {
             x if x == self.WMState => "WM_STATE",
             x if x == self.WMClass => "WM_CLASS",
             x if x == self.WMTakeFocus => "WM_TAKE_FOCUS",
+            x if x == self.MotifWMHints => "_MOTIF_WM_HINTS",
             x if x == self.NetActiveWindow => "_NET_ACTIVE_WINDOW",
             x if x == self.NetSupported => "_NET_SUPPORTED",
             x if x == self.NetWMName => "_NET_WM_NAME",
@@ -171,14 +202,38 @@ impl AtomCollection {
             x if x == self.NetCurrentDesktop => "_NET_CURRENT_DESKTOP",
             x if x == self.NetDesktopNames => "_NET_DESKTOP_NAMES",
             x if x == self.NetWMDesktop => "_NET_WM_DESKTOP",
+            x if x == self.NetWMMoveResize => "_NET_WM_MOVERESIZE",
+            x if x == self.NetCloseWindow => "_NET_CLOSE_WINDOW",
+            x if x == self.NetWMPing => "_NET_WM_PING",
             x if x == self.NetWMStrutPartial => "_NET_WM_STRUT_PARTIAL",
             x if x == self.NetWMStrut => "_NET_WM_STRUT",
+            x if x == self.NetWMWindowOpacity => "_NET_WM_WINDOW_OPACITY",
             x if x == self.WMNormalHints => "WM_NORMAL_HINTS",
             x if x == self.WMSizeHints => "WM_SIZE_HINTS",
             x if x == self.UTF8String => "UTF8_STRING",
+            x if x == self.CompoundText => "COMPOUND_TEXT",
+            x if x == self.Clipboard => "CLIPBOARD",
+            x if x == self.ClipboardManager => "CLIPBOARD_MANAGER",
+            x if x == self.Manager => "MANAGER",
+            x if x == self.SaveTargets => "SAVE_TARGETS",
+            x if x == self.Targets => "TARGETS",
+            x if x == self.Multiple => "MULTIPLE",
+            x if x == self.Timestamp => "TIMESTAMP",
+            x if x == self.Delete => "DELETE",
+            x if x == self.Incr => "INCR",
+            x if x == self.LeftwmClipboard => "_LEFTWM_CLIPBOARD",
             _ => "(UNKNOWN)",
         }
     }
+
+    /// The encoding of a text property of the given type, UTF-8 if unknown.
+    pub fn text_encoding(&self, r#type: xproto::Atom) -> TextEncoding {
+        match r#type {
+            x if x == self.CompoundText => TextEncoding::CompoundText,
+            x if x == u32::from(xproto::AtomEnum::STRING) => TextEncoding::Latin1,
+            _ => TextEncoding::Utf8,
+        }
+    }
 }
 
 /// Possible values of the `state` field of `WM_STATE`
@@ -1,27 +1,48 @@
-use std::{io::IoSlice, os::fd::AsRawFd, sync::Arc, time::Duration};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    io::IoSlice,
+    os::fd::AsRawFd,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use leftwm_core::{
-    models::{FocusBehaviour, WindowHandle},
-    utils::{self, modmask_lookup::ModMask},
+    models::{BorderColors, FocusBehaviour, WindowHandle},
+    utils::{
+        self,
+        clipboard_manager::ClipboardStore,
+        color::{Rgba, VisualFormat},
+        focus_stealing::FocusLoopDetector,
+        idle_watch::IdleWatch,
+        modmask_lookup::ModMask,
+        ping_watch::PingWatch,
+        property_throttle::PropertyThrottle,
+    },
     Config, Mode, Window,
 };
 use tokio::sync::{oneshot, Notify};
 use x11rb::{
     connection::{Connection, RequestConnection},
     protocol::{
-        randr,
-        xproto::{self, ChangeWindowAttributesAux},
+        randr, screensaver,
+        xproto::{self, ChangeWindowAttributesAux, PropMode},
+        ErrorKind as X11ErrorKind, Event,
     },
     resource_manager::Database,
     rust_connection::RustConnection,
     wrapper::ConnectionExt,
-    x11_utils::Serialize,
+    x11_utils::{Serialize, X11Error},
 };
 
 use crate::{error::ErrorKind, xatom::AtomCollection, xcursors::XCursor, X11rbWindowHandle};
 
 use crate::error::Result;
 
+use frame::{Frame, TitleBarStyle};
+
+mod clipboard;
+mod frame;
 mod getters;
 mod mouse;
 mod setters;
@@ -29,6 +50,25 @@ mod window;
 
 const MAX_PROPERTY_VALUE_LEN: u32 = 4096;
 
+/// How long the window manager being replaced is given to step down.
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What the `bad_value` of an X11 error of the given kind refers to.
+const fn bad_resource_name(kind: X11ErrorKind) -> &'static str {
+    match kind {
+        X11ErrorKind::Window => "window",
+        X11ErrorKind::Drawable => "drawable",
+        X11ErrorKind::Pixmap => "pixmap",
+        X11ErrorKind::Cursor => "cursor",
+        X11ErrorKind::Font => "font",
+        X11ErrorKind::Colormap => "colormap",
+        X11ErrorKind::GContext => "graphics context",
+        X11ErrorKind::IDChoice => "resource id",
+        X11ErrorKind::Atom => "atom",
+        _ => "value",
+    }
+}
+
 #[inline]
 pub fn root_event_mask() -> xproto::EventMask {
     xproto::EventMask::SUBSTRUCTURE_REDIRECT
@@ -51,11 +91,23 @@ pub fn mouse_event_mask() -> xproto::EventMask {
 }
 
 /// IDs of colors used across `LeftWM`
+#[derive(Clone, Copy)]
 pub struct Colors {
-    normal: u32,
-    floating: u32,
-    active: u32,
-    background: u32,
+    normal: Rgba,
+    floating: Rgba,
+    active: Rgba,
+    selected: Rgba,
+    urgent: Rgba,
+    background: Rgba,
+}
+
+/// Border colors of a window replacing the configured ones, from the overrides of its tag.
+#[derive(Default)]
+struct WindowColors {
+    normal: Option<Rgba>,
+    floating: Option<Rgba>,
+    active: Option<Rgba>,
+    selected: Option<Rgba>,
 }
 
 /// Contains Xserver information and origins.
@@ -67,12 +119,44 @@ pub(crate) struct XWrap {
     pub atoms: AtomCollection,
 
     colors: Colors,
+    window_colors: HashMap<xproto::Window, WindowColors>,
+    /// The pixel layout of the visuals of managed windows, their borders are drawn in it.
+    visuals: HashMap<xproto::Window, VisualFormat>,
     pub managed_windows: Vec<xproto::Window>,
     pub focused_window: xproto::Window,
     pub tag_labels: Vec<String>,
     pub mode: Mode<X11rbWindowHandle>,
     pub focus_behaviour: FocusBehaviour,
-    pub mouse_key_mask: ModMask,
+    pub focus_loop: FocusLoopDetector<xproto::Window>,
+    pub property_throttle: PropertyThrottle<(xproto::Window, xproto::Atom)>,
+    pub mark_focus_stealers_never_focus: bool,
+    /// Whether windows managed from now on are wrapped in a frame.
+    pub reparent_windows: bool,
+    /// The frames wrapping managed windows, by window.
+    frames: HashMap<xproto::Window, Frame>,
+    /// The border color last set on the top level window of managed windows.
+    border_colors: RefCell<HashMap<xproto::Window, Rgba>>,
+    /// How title bars are drawn on frames, when they have one.
+    title_bar: Option<TitleBarStyle>,
+    pub idle_watch: IdleWatch,
+    /// The `_NET_WM_WINDOW_OPACITY` of dimmed windows from before they were dimmed.
+    own_opacities: HashMap<xproto::Window, Option<u32>>,
+    /// The windows asked to close which did not answer `_NET_WM_PING` yet.
+    pub ping_watch: PingWatch<xproto::Window>,
+    /// Whether RandR reported a change of the screens since they were last read.
+    pub screens_changed: bool,
+    /// The `WM_Sn` manager selection of the screen and the window owning it.
+    pub wm_selection: xproto::Atom,
+    wm_selection_owner: xproto::Window,
+    /// Events received while waiting for a specific one, handed out before newer events.
+    held_events: VecDeque<Event>,
+    /// The clipboard saved when the window owning it exited.
+    clipboard: ClipboardStore<xproto::Atom, xproto::Window>,
+    /// The modifiers and buttons of the mousebinds, grabbed on windows.
+    pub mousebinds: Vec<(ModMask, u8)>,
+    /// The modifiers of the lock keys, which mousebinds work with and without: `Lock` and those
+    /// `Num_Lock` and `Scroll_Lock` are on.
+    lock_mask: u16,
     pub mode_origin: (i32, i32),
 
     #[allow(unused)]
@@ -80,6 +164,8 @@ pub(crate) struct XWrap {
     pub task_notify: Arc<Notify>,
     pub motion_event_limiter: u32,
     pub refresh_rate: u32,
+    /// Number of errors met so far, by category.
+    pub error_counts: RefCell<BTreeMap<String, usize>>,
 }
 
 impl XWrap {
@@ -123,7 +209,7 @@ impl XWrap {
         let atoms = AtomCollection::new(&conn)
             .expect("Unable to load atoms")
             .reply()
-            .unwrap();
+            .expect("Unable to intern atoms");
         let root = &conn.setup().roots[display];
         let root_handle = root.root;
         let mut req = Database::GET_RESOURCE_DATABASE;
@@ -139,11 +225,14 @@ impl XWrap {
         let db = Database::new_from_default(&reply, "localhost".into());
         let cursors = XCursor::new(&conn, display, &db).expect("Unable to load cursors");
 
+        let black = Rgba::opaque(0, 0, 0);
         let colors = Colors {
-            normal: 0,
-            floating: 0,
-            active: 0,
-            background: 0,
+            normal: black,
+            floating: black,
+            active: black,
+            selected: black,
+            urgent: black,
+            background: black,
         };
 
         let refresh_rate = get_refresh_rate(&conn, root.root).unwrap_or(60);
@@ -157,18 +246,37 @@ impl XWrap {
             atoms,
 
             colors,
+            window_colors: HashMap::new(),
+            visuals: HashMap::new(),
             managed_windows: vec![],
             focused_window: root_handle,
             tag_labels: vec![],
             mode: Mode::Normal,
             focus_behaviour: FocusBehaviour::Sloppy,
-            mouse_key_mask: ModMask::Zero,
+            focus_loop: FocusLoopDetector::default(),
+            property_throttle: PropertyThrottle::default(),
+            mark_focus_stealers_never_focus: false,
+            reparent_windows: false,
+            frames: HashMap::new(),
+            border_colors: RefCell::new(HashMap::new()),
+            title_bar: None,
+            idle_watch: IdleWatch::default(),
+            own_opacities: HashMap::new(),
+            ping_watch: PingWatch::default(),
+            screens_changed: false,
+            wm_selection: x11rb::NONE,
+            wm_selection_owner: x11rb::NONE,
+            held_events: VecDeque::new(),
+            clipboard: ClipboardStore::default(),
+            mousebinds: vec![],
+            lock_mask: u16::from(xproto::ModMask::LOCK | xproto::ModMask::M2),
             mode_origin: (0, 0),
 
             task_guard,
             task_notify,
             motion_event_limiter: 0,
             refresh_rate,
+            error_counts: RefCell::default(),
         };
 
         //TODO: Do we need to check if another WM is running ?
@@ -178,22 +286,86 @@ impl XWrap {
             &xproto::ChangeWindowAttributesAux::new()
                 .event_mask(xproto::EventMask::PROPERTY_CHANGE),
         )
-        .unwrap();
+        .expect("Unable to listen to property changes on the root window");
         xw.sync().expect("Unable to sync the connection");
 
         xw
     }
 
+    /// Count an error under the given category, see [`BackendError::category`].
+    ///
+    /// [`BackendError::category`]: crate::error::BackendError::category
+    pub fn count_error(&self, category: String) {
+        *self.error_counts.borrow_mut().entry(category).or_default() += 1;
+    }
+
+    /// Log an error event sent by the X server together with the request that caused it and the
+    /// resource it concerned, and count it.
+    pub fn report_x11_error(&self, error: &X11Error) {
+        let request = error.request_name.unwrap_or("UnknownRequest");
+        let resource = bad_resource_name(error.error_kind);
+        let opcode = format!("{}.{}", error.major_opcode, error.minor_opcode);
+        // Requests on windows destroyed in the meantime fail routinely, they are only noise.
+        if error.error_kind == X11ErrorKind::Window {
+            tracing::debug!(
+                window = error.bad_value,
+                "X11 error {:?} in request {} ({}) for window {:#x}, it is probably already destroyed",
+                error.error_kind,
+                request,
+                opcode,
+                error.bad_value
+            );
+        } else {
+            tracing::warn!(
+                sequence = error.sequence,
+                "X11 error {:?} in request {} ({}) for {} {:#x}",
+                error.error_kind,
+                request,
+                opcode,
+                resource,
+                error.bad_value
+            );
+        }
+        self.count_error(format!("{:?}", error.error_kind));
+    }
+
     pub fn load_config(&mut self, config: &impl Config) -> Result<()> {
         self.focus_behaviour = config.focus_behaviour();
-        self.mouse_key_mask = utils::modmask_lookup::into_modmask(&config.mousekey());
+        self.mark_focus_stealers_never_focus = config.mark_focus_stealers_never_focus();
+        self.reparent_windows = config.reparent_windows();
+        self.mousebinds = config
+            .mousebinds()
+            .into_iter()
+            .map(|bind| {
+                (
+                    utils::modmask_lookup::into_modmask(&bind.modifier),
+                    bind.button.into(),
+                )
+            })
+            // Button 0 would grab any button.
+            .filter(|(_, button)| *button != 0)
+            .collect();
         self.tag_labels = config.create_list_of_tag_labels();
+        let mut idle_timeout = config.idle().timeout();
+        if idle_timeout.is_some()
+            && self
+                .conn
+                .extension_information(screensaver::X11_EXTENSION_NAME)?
+                .is_none()
+        {
+            tracing::warn!("Idle detection is unavailable: no screen saver extension");
+            idle_timeout = None;
+        }
+        self.idle_watch = IdleWatch::new(idle_timeout);
         self.colors = Colors {
-            normal: self.get_color(&config.default_border_color())?,
-            floating: self.get_color(&config.floating_border_color())?,
-            active: self.get_color(&config.focused_border_color())?,
-            background: self.get_color(&config.background_color())?,
+            normal: self.parse_color(&config.default_border_color())?,
+            floating: self.parse_color(&config.floating_border_color())?,
+            active: self.parse_color(&config.focused_border_color())?,
+            selected: self.parse_color(&config.selected_border_color())?,
+            urgent: self.parse_color(&config.urgent_border_color())?,
+            background: self.parse_color(&config.background_color())?,
         };
+        self.load_title_bar(config.title_bar().filter(|_| self.reparent_windows))?;
         Ok(())
     }
 
@@ -206,22 +378,76 @@ impl XWrap {
         // Update all the windows with the new colors.
         for window in windows {
             let WindowHandle(X11rbWindowHandle(handle)) = window.handle;
-            let color: u32 = if focused == Some(window.handle) {
-                self.colors.active
-            } else if window.floating() {
-                self.colors.floating
+            let color = if focused == Some(window.handle) {
+                self.colors_of(handle).active
             } else {
-                self.colors.normal
+                self.unfocused_border_color(window)
             };
             self.set_window_border_color(handle, color)?;
         }
-        self.set_background_color(self.colors.background)?;
+        self.set_background_color(self.get_pixel(self.root, self.colors.background)?)?;
+        Ok(())
+    }
+
+    /// Border color of a window that does not have focus.
+    pub fn unfocused_border_color(&self, window: &Window<X11rbWindowHandle>) -> Rgba {
+        let WindowHandle(X11rbWindowHandle(handle)) = window.handle;
+        let colors = self.colors_of(handle);
+        if window.shows_urgency() {
+            colors.urgent
+        } else if window.selected {
+            colors.selected
+        } else if window.floating() {
+            colors.floating
+        } else {
+            colors.normal
+        }
+    }
+
+    /// Border colors of a window, the configured ones unless replaced for the window.
+    pub fn colors_of(&self, window: xproto::Window) -> Colors {
+        let mut colors = self.colors;
+        if let Some(custom) = self.window_colors.get(&window) {
+            colors.normal = custom.normal.unwrap_or(colors.normal);
+            colors.floating = custom.floating.unwrap_or(colors.floating);
+            colors.active = custom.active.unwrap_or(colors.active);
+            colors.selected = custom.selected.unwrap_or(colors.selected);
+        }
+        colors
+    }
+
+    /// Replace the configured border colors of a window, or go back to them when none are set.
+    pub fn set_window_border_colors(
+        &mut self,
+        window: xproto::Window,
+        colors: &BorderColors,
+    ) -> Result<()> {
+        if colors.is_empty() {
+            self.window_colors.remove(&window);
+            return Ok(());
+        }
+        let color = |color: &Option<String>| {
+            color
+                .as_deref()
+                .map(|color| self.parse_color(color))
+                .transpose()
+        };
+        let custom = WindowColors {
+            normal: color(&colors.default)?,
+            floating: color(&colors.floating)?,
+            active: color(&colors.focused)?,
+            selected: color(&colors.selected)?,
+        };
+        self.window_colors.insert(window, custom);
         Ok(())
     }
 
     pub fn init(&mut self) -> Result<()> {
         let root = self.root;
 
+        // The window manager being replaced has to let go of the root window first.
+        self.acquire_wm_selection()?;
+
         xproto::change_window_attributes(
             &self.conn,
             root,
@@ -243,47 +469,176 @@ impl XWrap {
         // EWMH compliance for desktops.
         self.init_desktops_hints()?;
 
+        // Be told about monitors being plugged in, unplugged or rearranged.
+        if self
+            .conn
+            .extension_information(randr::X11_EXTENSION_NAME)?
+            .is_some()
+        {
+            // Outputs can be plugged, unplugged or swapped without the screen changing size.
+            let mask = randr::NotifyMask::SCREEN_CHANGE
+                | randr::NotifyMask::OUTPUT_CHANGE
+                | randr::NotifyMask::CRTC_CHANGE;
+            randr::select_input(&self.conn, root, mask)?;
+        }
+
+        self.acquire_clipboard_manager()?;
+        self.lock_mask = self.get_lock_mask()?;
+
         self.sync()?;
         Ok(())
     }
 
-    /// EWMH support used for bars such as polybar.
-    pub fn init_desktops_hints(&self) -> Result<()> {
-        let tag_labels = &self.tag_labels;
-        let tag_length = tag_labels.len();
-
-        // Set the number of desktop.
-        self.set_desktop_prop(
-            &[u32::try_from(tag_length)?],
-            self.atoms.NetNumberOfDesktops,
-        )?;
+    /// Take the `WM_Sn` manager selection of the screen (ICCCM 2.8), waiting for the window
+    /// manager owning it to step down. A window manager started later takes it over to ask
+    /// leftwm to step down, which is reported by a `SelectionClear`.
+    pub fn acquire_wm_selection(&mut self) -> Result<()> {
+        if self.wm_selection_owner == x11rb::NONE {
+            let name = format!("WM_S{}", self.display);
+            self.wm_selection = xproto::intern_atom(&self.conn, false, name.as_bytes())?
+                .reply()?
+                .atom;
+            let owner = self.conn.generate_id()?;
+            xproto::create_window(
+                &self.conn,
+                x11rb::COPY_DEPTH_FROM_PARENT,
+                owner,
+                self.root,
+                -1,
+                -1,
+                1,
+                1,
+                0,
+                xproto::WindowClass::INPUT_ONLY,
+                x11rb::COPY_FROM_PARENT,
+                &xproto::CreateWindowAux::new(),
+            )?;
+            self.wm_selection_owner = owner;
+        }
+        let previous = xproto::get_selection_owner(&self.conn, self.wm_selection)?
+            .reply()?
+            .owner;
+        if previous != x11rb::NONE {
+            tracing::info!("Another window manager is running, asking it to step down");
+            // Be told when the previous owner is gone.
+            xproto::change_window_attributes(
+                &self.conn,
+                previous,
+                &ChangeWindowAttributesAux::new().event_mask(xproto::EventMask::STRUCTURE_NOTIFY),
+            )?;
+        }
+        let time = self.server_time()?;
+        if !self.take_manager_selection(self.wm_selection, time)? {
+            tracing::error!("Unable to take the window manager selection");
+            return Ok(());
+        }
+        if previous != x11rb::NONE {
+            let destroyed = self.wait_for_event(
+                REPLACE_TIMEOUT,
+                |event| matches!(event, Event::DestroyNotify(e) if e.window == previous),
+            )?;
+            if destroyed.is_none() {
+                tracing::warn!("The previous window manager did not step down in time");
+            }
+        }
+        Ok(())
+    }
 
-        // Set a current desktop.
-        self.set_desktop_prop(&[0_u32, x11rb::CURRENT_TIME], self.atoms.NetCurrentDesktop)?;
+    /// Take the `WM_Sn` selection back from a window manager which just took it, without
+    /// waiting for it to step down.
+    pub fn reacquire_wm_selection(&mut self) -> Result<()> {
+        let time = self.server_time()?;
+        if !self.take_manager_selection(self.wm_selection, time)? {
+            tracing::error!("Unable to take the window manager selection back");
+        }
+        Ok(())
+    }
 
-        // Set desktop names.
-        //
-        // Convert the list of tag names string into a valid list of strings for an atom,
-        // which is a null terminated string containing null terminated strings for each value.
-        // This essecially replicates what this function does:
-        // `Xutf8TextListToTextProperty`: https://linux.die.net/man/3/xutf8textlisttotextproperty
-        let concat_str = tag_labels
-            .iter()
-            .fold(String::default(), |acc, x| format!("{acc}{x}\0"));
-        let bytes = concat_str.as_bytes();
+    /// Take a manager selection with the window owning `WM_Sn` and announce it with a `MANAGER`
+    /// client message on the root window (ICCCM 2.8). Returns whether the selection is owned.
+    fn take_manager_selection(
+        &self,
+        selection: xproto::Atom,
+        time: xproto::Timestamp,
+    ) -> Result<bool> {
+        let owner = self.wm_selection_owner;
+        xproto::set_selection_owner(&self.conn, owner, selection, time)?;
+        let current = xproto::get_selection_owner(&self.conn, selection)?
+            .reply()?
+            .owner;
+        if current != owner {
+            return Ok(false);
+        }
+        let event = xproto::ClientMessageEvent::new(
+            32,
+            self.root,
+            self.atoms.Manager,
+            [time, selection, owner, 0, 0],
+        );
+        xproto::send_event(
+            &self.conn,
+            false,
+            self.root,
+            xproto::EventMask::STRUCTURE_NOTIFY,
+            event,
+        )?;
+        Ok(true)
+    }
 
+    /// Returns the current server time, read from the `PropertyNotify` of a zero-length change
+    /// to a property of the window owning `WM_Sn`.
+    fn server_time(&mut self) -> Result<xproto::Timestamp> {
+        let owner = self.wm_selection_owner;
+        let property = self.wm_selection;
+        let mask = ChangeWindowAttributesAux::new().event_mask(xproto::EventMask::PROPERTY_CHANGE);
+        xproto::change_window_attributes(&self.conn, owner, &mask)?;
         xproto::change_property(
             &self.conn,
-            xproto::PropMode::REPLACE,
-            self.root,
-            self.atoms.NetDesktopNames,
-            self.atoms.UTF8String,
+            PropMode::APPEND,
+            owner,
+            property,
+            xproto::AtomEnum::STRING,
             8,
-            // Removing the last null byte because `CString::from_vec_unchecked` adds a trailing
-            // null byte
-            u32::try_from(bytes.len())? - 1,
-            &bytes[..bytes.len() - 1],
+            0,
+            &[],
         )?;
+        let notify = self.wait_for_event(REPLACE_TIMEOUT, |event| {
+            matches!(event, Event::PropertyNotify(e) if e.window == owner && e.atom == property)
+        })?;
+        let mask = ChangeWindowAttributesAux::new().event_mask(xproto::EventMask::NO_EVENT);
+        xproto::change_window_attributes(&self.conn, owner, &mask)?;
+        match notify {
+            Some(Event::PropertyNotify(e)) => Ok(e.time),
+            _ => Ok(x11rb::CURRENT_TIME),
+        }
+    }
+
+    /// Waits up to `timeout` for an event matching `wanted`. The other events received meanwhile
+    /// are held for the event loop.
+    fn wait_for_event(
+        &mut self,
+        timeout: Duration,
+        wanted: impl Fn(&Event) -> bool,
+    ) -> Result<Option<Event>> {
+        self.conn.flush()?;
+        let started = Instant::now();
+        loop {
+            match self.conn.poll_for_event()? {
+                Some(event) if wanted(&event) => return Ok(Some(event)),
+                Some(event) => self.held_events.push_back(event),
+                None if started.elapsed() >= timeout => return Ok(None),
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+
+    /// EWMH support used for bars such as polybar.
+    pub fn init_desktops_hints(&self) -> Result<()> {
+        // Set the number and names of the desktops.
+        self.set_desktop_names(&self.tag_labels)?;
+
+        // Set a current desktop.
+        self.set_desktop_prop(&[0_u32, x11rb::CURRENT_TIME], self.atoms.NetCurrentDesktop)?;
 
         // Set the WM NAME.
         self.set_desktop_prop_string("LeftWM", self.atoms.NetWMName, self.atoms.UTF8String)?;
@@ -317,6 +672,8 @@ impl XWrap {
             let mut data = [0u32; 5];
             data[0] = atom;
             data[1] = x11rb::CURRENT_TIME;
+            // `_NET_WM_PING` replies tell which window they are about.
+            data[2] = window;
             msg.data = data.into();
 
             self.send_xevent(window, false, xproto::EventMask::NO_EVENT, &msg.serialize())?;
@@ -0,0 +1,276 @@
+//! `XWrap` clipboard manager, see `leftwm_core::utils::clipboard_manager`.
+use leftwm_core::utils::clipboard_manager::{ClipboardData, SaveRequest};
+use x11rb::{
+    connection::RequestConnection,
+    protocol::xproto::{self, PropMode},
+};
+
+use crate::error::Result;
+
+use super::XWrap;
+
+/// Bytes taken by the header of a `ChangeProperty` request.
+const CHANGE_PROPERTY_HEADER_LEN: usize = 24;
+
+impl XWrap {
+    /// Take the `CLIPBOARD_MANAGER` selection with the window owning `WM_Sn`, unless another
+    /// clipboard manager is running.
+    pub fn acquire_clipboard_manager(&mut self) -> Result<()> {
+        let selection = self.atoms.ClipboardManager;
+        let owner = xproto::get_selection_owner(&self.conn, selection)?
+            .reply()?
+            .owner;
+        if owner != x11rb::NONE {
+            tracing::info!("Another clipboard manager is running, not saving the clipboard");
+            return Ok(());
+        }
+        let time = self.server_time()?;
+        if !self.take_manager_selection(selection, time)? {
+            tracing::warn!("Unable to take the clipboard manager selection");
+        }
+        Ok(())
+    }
+
+    /// A selection leftwm owned was taken by another window.
+    pub fn clipboard_selection_clear(&mut self, event: &xproto::SelectionClearEvent) {
+        if event.selection == self.atoms.Clipboard {
+            self.clipboard.clear();
+        } else if event.selection == self.atoms.ClipboardManager {
+            tracing::info!("Another clipboard manager took over");
+            self.clipboard.clear();
+        }
+    }
+
+    /// A client asked for a selection leftwm owns: to save the clipboard or for its contents.
+    pub fn clipboard_selection_request(
+        &mut self,
+        event: &xproto::SelectionRequestEvent,
+    ) -> Result<()> {
+        // Obsolete clients leave it to the owner to choose the property.
+        let property = if event.property == x11rb::NONE {
+            event.target
+        } else {
+            event.property
+        };
+        let converted = if event.selection == self.atoms.ClipboardManager {
+            self.convert_clipboard_manager(event, property)?
+        } else if event.selection == self.atoms.Clipboard {
+            self.convert_clipboard(event, property)?
+        } else {
+            false
+        };
+        if !converted {
+            self.send_selection_notify(event, x11rb::NONE)?;
+        } else if event.target != self.atoms.SaveTargets {
+            self.send_selection_notify(event, property)?;
+        }
+        Ok(())
+    }
+
+    /// The clipboard owner answered a conversion leftwm asked for while saving the clipboard.
+    pub fn clipboard_selection_notify(
+        &mut self,
+        event: &xproto::SelectionNotifyEvent,
+    ) -> Result<()> {
+        if event.selection != self.atoms.Clipboard || !self.clipboard.is_saving() {
+            return Ok(());
+        }
+        let next = if event.property == x11rb::NONE {
+            if event.target == self.atoms.Targets {
+                None
+            } else {
+                self.clipboard.target_fetched(None)
+            }
+        } else {
+            let prop = xproto::get_property(
+                &self.conn,
+                true,
+                self.wm_selection_owner,
+                event.property,
+                xproto::AtomEnum::ANY,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()?;
+            if event.target == self.atoms.Targets {
+                let offered = self.savable_targets(&prop);
+                self.clipboard.targets_offered(offered)
+            } else {
+                // Contents sent incrementally are too large to be worth keeping around.
+                let data = (prop.type_ != self.atoms.Incr).then_some(ClipboardData {
+                    target: event.target,
+                    r#type: prop.type_,
+                    format: prop.format,
+                    value: prop.value,
+                });
+                self.clipboard.target_fetched(data)
+            }
+        };
+
+        if let Some(target) = next {
+            xproto::convert_selection(
+                &self.conn,
+                self.wm_selection_owner,
+                self.atoms.Clipboard,
+                target,
+                self.atoms.LeftwmClipboard,
+                event.time,
+            )?;
+            return Ok(());
+        }
+        let Some((request, saved)) = self.clipboard.finish_save() else {
+            return Ok(());
+        };
+        if saved {
+            xproto::set_selection_owner(
+                &self.conn,
+                self.wm_selection_owner,
+                self.atoms.Clipboard,
+                request.time,
+            )?;
+        }
+        let reply = xproto::SelectionRequestEvent {
+            requestor: request.requestor,
+            selection: self.atoms.ClipboardManager,
+            target: self.atoms.SaveTargets,
+            time: request.time,
+            ..Default::default()
+        };
+        let property = if saved { request.property } else { x11rb::NONE };
+        self.send_selection_notify(&reply, property)
+    }
+
+    /// Converts `CLIPBOARD_MANAGER`, starting to save the clipboard for `SAVE_TARGETS`. The
+    /// requestor is answered once the clipboard is saved.
+    fn convert_clipboard_manager(
+        &mut self,
+        event: &xproto::SelectionRequestEvent,
+        property: xproto::Atom,
+    ) -> Result<bool> {
+        if event.target == self.atoms.Targets {
+            let targets = [self.atoms.Targets, self.atoms.SaveTargets];
+            self.replace_property_u32(
+                event.requestor,
+                property,
+                xproto::AtomEnum::ATOM.into(),
+                &targets,
+            )?;
+            return Ok(true);
+        }
+        if event.target != self.atoms.SaveTargets {
+            return Ok(false);
+        }
+        // The requestor may list the targets to save, otherwise all those offered are.
+        let requested = if event.property == x11rb::NONE {
+            vec![]
+        } else {
+            let prop = xproto::get_property(
+                &self.conn,
+                false,
+                event.requestor,
+                property,
+                xproto::AtomEnum::ATOM,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()?;
+            self.savable_targets(&prop)
+        };
+        self.clipboard.begin_save(SaveRequest {
+            requestor: event.requestor,
+            property,
+            time: event.time,
+        });
+        let target = self
+            .clipboard
+            .targets_offered(requested)
+            .unwrap_or(self.atoms.Targets);
+        xproto::convert_selection(
+            &self.conn,
+            self.wm_selection_owner,
+            self.atoms.Clipboard,
+            target,
+            self.atoms.LeftwmClipboard,
+            event.time,
+        )?;
+        Ok(true)
+    }
+
+    /// The targets listed in a property of type `ATOM`, without those which are not contents.
+    fn savable_targets(&self, prop: &xproto::GetPropertyReply) -> Vec<xproto::Atom> {
+        let special = [
+            self.atoms.Targets,
+            self.atoms.Multiple,
+            self.atoms.Timestamp,
+            self.atoms.SaveTargets,
+            self.atoms.Delete,
+        ];
+        prop.value32()
+            .into_iter()
+            .flatten()
+            .filter(|t| !special.contains(t))
+            .collect()
+    }
+
+    /// Converts the saved clipboard.
+    fn convert_clipboard(
+        &self,
+        event: &xproto::SelectionRequestEvent,
+        property: xproto::Atom,
+    ) -> Result<bool> {
+        if event.target == self.atoms.Targets {
+            let mut targets = self.clipboard.targets();
+            targets.push(self.atoms.Targets);
+            self.replace_property_u32(
+                event.requestor,
+                property,
+                xproto::AtomEnum::ATOM.into(),
+                &targets,
+            )?;
+            return Ok(true);
+        }
+        let Some(data) = self.clipboard.get(event.target) else {
+            return Ok(false);
+        };
+        let max_len = self.conn.maximum_request_bytes() - CHANGE_PROPERTY_HEADER_LEN;
+        if data.value.len() > max_len || data.format == 0 {
+            return Ok(false);
+        }
+        xproto::change_property(
+            &self.conn,
+            PropMode::REPLACE,
+            event.requestor,
+            property,
+            data.r#type,
+            data.format,
+            u32::try_from(data.value.len() / usize::from(data.format / 8))?,
+            &data.value,
+        )?;
+        Ok(true)
+    }
+
+    /// Answer a selection request, `property` is `NONE` if it was refused.
+    fn send_selection_notify(
+        &self,
+        request: &xproto::SelectionRequestEvent,
+        property: xproto::Atom,
+    ) -> Result<()> {
+        let reply = xproto::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: request.time,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property,
+        };
+        xproto::send_event(
+            &self.conn,
+            false,
+            request.requestor,
+            xproto::EventMask::NO_EVENT,
+            reply,
+        )?;
+        Ok(())
+    }
+}
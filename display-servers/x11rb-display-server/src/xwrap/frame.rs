@@ -0,0 +1,248 @@
+//! Frame windows wrapping managed windows, which own their border.
+//!
+//! Borders drawn on the windows themselves are in their visual, which may have an alpha channel.
+//! Frames use the visual of the root window instead, and draw the title bars above the windows
+//! when they are configured.
+use std::cell::Cell;
+
+use leftwm_core::{config::TitleBar, Window};
+use x11rb::{connection::Connection, protocol::xproto};
+
+use crate::{error::Result, X11rbWindowHandle};
+
+use super::{root_event_mask, XWrap};
+
+/// Events of frames, the windows inside them are managed through them like through the root.
+fn frame_event_mask() -> xproto::EventMask {
+    xproto::EventMask::SUBSTRUCTURE_REDIRECT
+        | xproto::EventMask::SUBSTRUCTURE_NOTIFY
+        | xproto::EventMask::ENTER_WINDOW
+        | xproto::EventMask::EXPOSURE
+        | xproto::EventMask::BUTTON_PRESS
+}
+
+/// Space left of the title in title bars.
+const TITLE_PADDING: i16 = 4;
+
+/// A frame wrapping a managed window.
+pub struct Frame {
+    window: xproto::Window,
+    /// Height of the title bar shown above the window, none while it is fullscreen.
+    title_bar: Cell<u16>,
+}
+
+/// The font, graphics context and colors title bars are drawn with.
+pub struct TitleBarStyle {
+    height: u16,
+    font: xproto::Font,
+    gc: xproto::Gcontext,
+    color: u32,
+    focused_color: u32,
+    text_color: u32,
+    ascent: i16,
+    descent: i16,
+}
+
+impl XWrap {
+    /// The frame of a window, or the window itself when it has none.
+    #[must_use]
+    pub fn frame_of(&self, window: xproto::Window) -> xproto::Window {
+        self.frames
+            .get(&window)
+            .map_or(window, |frame| frame.window)
+    }
+
+    /// The window inside a frame, or the window itself when it is not a frame.
+    #[must_use]
+    pub fn client_of(&self, window: xproto::Window) -> xproto::Window {
+        self.frames
+            .iter()
+            .find(|(_, frame)| frame.window == window)
+            .map_or(window, |(client, _)| *client)
+    }
+
+    /// Whether a window is the frame of a managed window.
+    #[must_use]
+    pub fn is_frame(&self, window: xproto::Window) -> bool {
+        self.frames.values().any(|frame| frame.window == window)
+    }
+
+    /// Height of the title bar shown above a window, 0 when it has none.
+    #[must_use]
+    pub fn title_bar_of(&self, window: xproto::Window) -> u16 {
+        self.frames
+            .get(&window)
+            .map_or(0, |frame| frame.title_bar.get())
+    }
+
+    /// Height of the title bar a window should have, fullscreen windows have none.
+    pub(super) fn title_bar_height(&self, window: &Window<X11rbWindowHandle>) -> u16 {
+        match &self.title_bar {
+            Some(style) if !window.is_fullscreen() => style.height,
+            _ => 0,
+        }
+    }
+
+    /// Record the height of the title bar shown above a framed window.
+    pub(super) fn set_title_bar_of(&self, window: xproto::Window, height: u16) {
+        if let Some(frame) = self.frames.get(&window) {
+            frame.title_bar.set(height);
+        }
+    }
+
+    /// Load the font and colors of title bars, freeing the previous ones.
+    pub fn load_title_bar(&mut self, config: Option<TitleBar>) -> Result<()> {
+        if let Some(style) = self.title_bar.take() {
+            xproto::free_gc(&self.conn, style.gc)?;
+            xproto::close_font(&self.conn, style.font)?;
+        }
+        let Some(config) = config else {
+            return Ok(());
+        };
+        let font = self.conn.generate_id()?;
+        if xproto::open_font(&self.conn, font, config.font.as_bytes())?
+            .check()
+            .is_err()
+        {
+            tracing::warn!(
+                "Unable to load the title bar font {}, using fixed",
+                config.font
+            );
+            xproto::open_font(&self.conn, font, b"fixed")?.check()?;
+        }
+        let info = xproto::query_font(&self.conn, font)?.reply()?;
+        let gc = self.conn.generate_id()?;
+        let values = xproto::CreateGCAux::new().font(font);
+        xproto::create_gc(&self.conn, gc, self.root, &values)?;
+        self.title_bar = Some(TitleBarStyle {
+            height: config.height,
+            font,
+            gc,
+            color: self.get_color(&config.color)?,
+            focused_color: self.get_color(&config.focused_color)?,
+            text_color: self.get_color(&config.text_color)?,
+            ascent: info.font_ascent,
+            descent: info.font_descent,
+        });
+        Ok(())
+    }
+
+    /// Draw the title bar of a framed window, with its `_NET_WM_NAME` written on it.
+    pub fn draw_title_bar(&self, window: xproto::Window, focused: bool) -> Result<()> {
+        let (Some(style), Some(frame)) = (&self.title_bar, self.frames.get(&window)) else {
+            return Ok(());
+        };
+        let height = frame.title_bar.get();
+        if height == 0 {
+            return Ok(());
+        }
+        let background = if focused {
+            style.focused_color
+        } else {
+            style.color
+        };
+        let fill = xproto::ChangeGCAux::new().foreground(background | 0xff00_0000);
+        xproto::change_gc(&self.conn, style.gc, &fill)?;
+        // The server clips the bar to the width of the frame.
+        let bar = xproto::Rectangle {
+            x: 0,
+            y: 0,
+            width: u16::MAX,
+            height,
+        };
+        xproto::poly_fill_rectangle(&self.conn, frame.window, style.gc, &[bar])?;
+
+        // Core fonts are Latin-1, and requests take at most 255 characters.
+        let name = self.get_window_name(window).unwrap_or_default();
+        let text: Vec<u8> = name
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+            .take(255)
+            .collect();
+        let text_colors = xproto::ChangeGCAux::new()
+            .foreground(style.text_color | 0xff00_0000)
+            .background(background | 0xff00_0000);
+        xproto::change_gc(&self.conn, style.gc, &text_colors)?;
+        let baseline = (i16::try_from(height)? + style.ascent - style.descent) / 2;
+        xproto::image_text8(
+            &self.conn,
+            frame.window,
+            style.gc,
+            TITLE_PADDING,
+            baseline,
+            &text,
+        )?;
+        Ok(())
+    }
+
+    /// Wrap a window in a mapped frame taking its place.
+    pub fn frame_window(&mut self, window: xproto::Window) -> Result<()> {
+        if self.frames.contains_key(&window) {
+            return Ok(());
+        }
+        let geo = xproto::get_geometry(&self.conn, window)?.reply()?;
+        let frame = self.conn.generate_id()?;
+        let attrs = xproto::CreateWindowAux::new()
+            .border_pixel(self.get_pixel(self.root, self.colors.normal)?)
+            .event_mask(frame_event_mask());
+        xproto::create_window(
+            &self.conn,
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            frame,
+            self.root,
+            geo.x,
+            geo.y,
+            geo.width.max(1),
+            geo.height.max(1),
+            geo.border_width,
+            xproto::WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &attrs,
+        )?;
+        // Keep the window around if we exit without giving it back.
+        xproto::change_save_set(&self.conn, xproto::SetMode::INSERT, window)?;
+        let no_border = xproto::ConfigureWindowAux::new().border_width(0);
+        xproto::configure_window(&self.conn, window, &no_border)?;
+        // The window is unmapped from the root when it leaves it, which is not a withdrawal.
+        self.set_root_notify(false)?;
+        xproto::reparent_window(&self.conn, window, frame, 0, 0)?;
+        xproto::map_window(&self.conn, window)?;
+        xproto::map_window(&self.conn, frame)?;
+        self.set_root_notify(true)?;
+        let frame = Frame {
+            window: frame,
+            title_bar: Cell::new(0),
+        };
+        self.frames.insert(window, frame);
+        Ok(())
+    }
+
+    /// Destroy the frame of a window, giving the window back to the root unless it is destroyed.
+    pub fn unframe_window(&mut self, window: xproto::Window, destroyed: bool) -> Result<()> {
+        let Some(frame) = self.frames.remove(&window) else {
+            return Ok(());
+        };
+        if !destroyed {
+            let geo = xproto::get_geometry(&self.conn, frame.window)?.reply()?;
+            xproto::reparent_window(&self.conn, window, self.root, geo.x, geo.y)?;
+            xproto::change_save_set(&self.conn, xproto::SetMode::DELETE, window)?;
+        }
+        xproto::destroy_window(&self.conn, frame.window)?;
+        Ok(())
+    }
+
+    /// Whether the root reports the windows on it being mapped, unmapped and destroyed.
+    pub fn set_root_notify(&self, enabled: bool) -> Result<()> {
+        let mask = if enabled {
+            root_event_mask()
+        } else {
+            root_event_mask().remove(xproto::EventMask::SUBSTRUCTURE_NOTIFY)
+        };
+        let attrs = xproto::ChangeWindowAttributesAux {
+            event_mask: Some(mask),
+            ..Default::default()
+        };
+        xproto::change_window_attributes(&self.conn, self.root, &attrs)?;
+        Ok(())
+    }
+}
@@ -1,12 +1,20 @@
-use std::{backtrace::Backtrace, ffi::CStr};
-
-use leftwm_core::models::{
-    BBox, DockArea, Screen, WindowHandle, WindowState, WindowType, XyhwChange,
+use std::{backtrace::Backtrace, time::Duration};
+
+use leftwm_core::{
+    models::{
+        sort_screens, BBox, DockArea, Screen, WindowHandle, WindowState, WindowType, XyhwChange,
+    },
+    utils::{
+        color::{Rgba, VisualFormat},
+        motif_hints, text_property,
+    },
 };
 use x11rb::{
-    connection::Connection,
-    properties::{WmClass, WmHints, WmSizeHints},
-    protocol::{randr, xinerama, xproto},
+    connection::{Connection, RequestConnection},
+    cookie::Cookie,
+    properties::{AspectRatio, WmHints, WmSizeHints},
+    protocol::{randr, screensaver, xinerama, xproto},
+    rust_connection::RustConnection,
 };
 
 use crate::{
@@ -17,6 +25,9 @@ use crate::{
 
 use super::{XWrap, MAX_PROPERTY_VALUE_LEN};
 
+/// The `Num_Lock` and `Scroll_Lock` keysyms.
+const LOCK_KEYSYMS: [xproto::Keysym; 2] = [0xff7f, 0xff14];
+
 impl XWrap {
     // Public functions.
 
@@ -36,16 +47,79 @@ impl XWrap {
         Ok(all)
     }
 
-    /// Returns a `XColor` for a color.
+    /// Returns the pixel of a color for the root visual.
     pub fn get_color(&self, color: &str) -> Result<u32> {
+        self.get_pixel(self.root, self.parse_color(color)?)
+    }
+
+    /// Parses a color. Hex colors, with or without alpha, are parsed here. Other colors, such as
+    /// X11 color names, are looked up by the X server.
+    pub fn parse_color(&self, color: &str) -> Result<Rgba> {
+        if let Some(rgba) = Rgba::from_hex(color) {
+            return Ok(rgba);
+        }
         let screen = &self.conn.setup().roots[self.display];
-        let (red, green, blue) = parse_color_string(color)?;
+        let name = color.trim().as_bytes();
+        let reply = xproto::lookup_color(&self.conn, screen.default_colormap, name)?.reply()?;
+        Ok(Rgba::from_x_channels(
+            reply.exact_red,
+            reply.exact_green,
+            reply.exact_blue,
+        ))
+    }
 
+    /// Returns the pixel of a color for a window: in its visual for managed windows, in the
+    /// visual of the root for the others.
+    pub fn get_pixel(&self, window: xproto::Window, color: Rgba) -> Result<u32> {
+        let screen = &self.conn.setup().roots[self.display];
+        let format = self.visuals.get(&window).copied();
+        if let Some(format) = format.or_else(|| visual_format(screen, screen.root_visual)) {
+            return Ok(color.pixel(format));
+        }
+        // Visuals using a colormap still need a color cell.
+        let channel = |channel: u8| u16::from(channel) * 0x101;
+        let (red, green, blue) = (
+            channel(color.red),
+            channel(color.green),
+            channel(color.blue),
+        );
         let reply =
             xproto::alloc_color(&self.conn, screen.default_colormap, red, green, blue)?.reply()?;
         Ok(reply.pixel)
     }
 
+    /// Returns the pixel layout of the visual of a window, if it is a true color visual.
+    pub fn get_visual_format(&self, window: xproto::Window) -> Result<Option<VisualFormat>> {
+        let visual = self.get_window_attrs(window)?.visual;
+        let screen = &self.conn.setup().roots[self.display];
+        Ok(visual_format(screen, visual))
+    }
+
+    /// Returns the modifiers of the lock keys: `Lock`, and those `Num_Lock` and `Scroll_Lock`
+    /// are on in the modifier mapping.
+    pub fn get_lock_mask(&self) -> Result<u16> {
+        let (min, max) = (self.conn.setup().min_keycode, self.conn.setup().max_keycode);
+        let modifiers = xproto::get_modifier_mapping(&self.conn)?;
+        let keyboard = xproto::get_keyboard_mapping(&self.conn, min, max - min + 1)?;
+        let (modifiers, keyboard) = (modifiers.reply()?, keyboard.reply()?);
+        let keysyms_per_keycode = usize::from(keyboard.keysyms_per_keycode).max(1);
+        let lock_keys: Vec<xproto::Keycode> = keyboard
+            .keysyms
+            .chunks(keysyms_per_keycode)
+            .zip(min..=max)
+            .filter(|(keysyms, _)| keysyms.iter().any(|k| LOCK_KEYSYMS.contains(k)))
+            .map(|(_, keycode)| keycode)
+            .collect();
+        let keycodes_per_modifier = usize::from(modifiers.keycodes_per_modifier()).max(1);
+        let mut mask = u16::from(xproto::ModMask::LOCK);
+        for (i, keycodes) in modifiers.keycodes.chunks(keycodes_per_modifier).enumerate() {
+            if keycodes.iter().any(|k| lock_keys.contains(k)) {
+                mask |= 1 << i;
+            }
+        }
+        Ok(mask)
+    }
+
     /// Returns the current position of the cursor.
     /// # Errors
     ///
@@ -75,7 +149,7 @@ impl XWrap {
         for w in roots {
             let reply = xproto::query_pointer(&self.conn, w)?.reply();
             if let Ok(reply) = reply {
-                return Ok(WindowHandle(X11rbWindowHandle(reply.child)));
+                return Ok(WindowHandle(X11rbWindowHandle(self.client_of(reply.child))));
             }
         }
         Err(BackendError {
@@ -100,62 +174,36 @@ impl XWrap {
 
     /// Returns the `WM_SIZE_HINTS`/`WM_NORMAL_HINTS` of a window as a `XyhwChange`.
     pub fn get_hint_sizing_as_xyhw(&self, window: xproto::Window) -> Result<Option<XyhwChange>> {
-        let hints = self.get_hint_sizing(window)?;
-        if let Some(size) = hints {
-            let mut xyhw = XyhwChange::default();
-
-            if let Some((_specification, width, height)) = size.size {
-                xyhw.w = Some(width);
-                xyhw.h = Some(height);
-            } else if let Some((width, height)) = size.base_size {
-                xyhw.w = Some(width);
-                xyhw.h = Some(height);
-            }
-
-            if let Some((width, height)) = size.size_increment {
-                xyhw.w = Some(width);
-                xyhw.h = Some(height);
-            }
-
-            if let Some((max_width, max_height)) = size.max_size {
-                xyhw.maxw = Some(max_width);
-                xyhw.maxh = Some(max_height);
-            }
-
-            if let Some((min_width, min_height)) = size.min_size {
-                xyhw.minw = Some(min_width);
-                xyhw.minh = Some(min_height);
-            }
-            // Make sure that width and height are not smaller than the min values.
-            xyhw.w = std::cmp::max(xyhw.w, xyhw.minw);
-            xyhw.h = std::cmp::max(xyhw.h, xyhw.minh);
-            // Ignore the sizing if the sizing is set to 0.
-            xyhw.w = xyhw.w.filter(|&w| w != 0);
-            xyhw.h = xyhw.h.filter(|&h| h != 0);
-
-            if let Some((_specification, x, y)) = size.position {
-                xyhw.x = Some(x);
-                xyhw.y = Some(y);
-            }
-            // TODO: support min/max aspect
-            // if size.flags & xlib::PAspect != 0 {
-            //     //c->mina = (float)size.min_aspect.y / size.min_aspect.x;
-            //     //c->maxa = (float)size.max_aspect.x / size.max_aspect.y;
-            // }
+        Ok(self.get_hint_sizing(window)?.map(sizing_as_xyhw))
+    }
 
-            return Ok(Some(xyhw));
-        }
-        Ok(None)
+    /// Returns how long the user has been idle.
+    pub fn get_idle_time(&self) -> Result<Duration> {
+        let info = screensaver::query_info(&self.conn, self.root)?.reply()?;
+        Ok(Duration::from_millis(info.ms_since_user_input.into()))
     }
 
-    /// Returns the next `Xevent` of the xserver.
-    pub fn poll_next_event(&self) -> Result<Option<x11rb::protocol::Event>> {
+    /// Returns the next `Xevent` of the xserver, starting with those held while waiting for
+    /// another one.
+    pub fn poll_next_event(&mut self) -> Result<Option<x11rb::protocol::Event>> {
+        if let Some(event) = self.held_events.pop_front() {
+            return Ok(Some(event));
+        }
         Ok(self.conn.poll_for_event()?)
     }
 
-    /// Returns all the screens of the display.
-    // TODO: Check if this is working, because it's most likely not
+    /// Returns all the screens of the display, sorted left to right and without clones.
     pub fn get_screens(&self) -> Result<Vec<Screen<X11rbWindowHandle>>> {
+        let mut screens = self.query_screens()?;
+        sort_screens(&mut screens);
+        Ok(screens)
+    }
+
+    /// The screens from RandR, or else from Xinerama, or else the roots, in server order.
+    fn query_screens(&self) -> Result<Vec<Screen<X11rbWindowHandle>>> {
+        if let Some(screens) = self.get_monitors() {
+            return Ok(screens);
+        }
         if let Ok(screen_resources) = randr::get_screen_resources(&self.conn, self.root)?.reply() {
             return Ok(screen_resources
                 .outputs
@@ -166,11 +214,7 @@ impl XWrap {
                 })
                 .filter_map(|res| res.reply().ok())
                 .filter_map(|output_info| {
-                    //FIX: This always fails
-                    let name = match CStr::from_bytes_with_nul(&output_info.name) {
-                        Ok(name) => name.to_str().unwrap(),
-                        Err(_) => "output_name",
-                    };
+                    let name = String::from_utf8_lossy(&output_info.name);
                     Some((
                         randr::get_crtc_info(
                             &self.conn,
@@ -199,9 +243,7 @@ impl XWrap {
                 .collect());
         }
 
-        let is_active = xinerama::is_active(&self.conn)?.reply()?;
-
-        if is_active.state == 0 {
+        if !self.xinerama_is_active()? {
             // NON-XINERAMA
             return Ok(self
                 .get_roots()
@@ -242,6 +284,71 @@ impl XWrap {
             .collect())
     }
 
+    /// Whether the server has the Xinerama extension, and it is active.
+    fn xinerama_is_active(&self) -> Result<bool> {
+        if self
+            .conn
+            .extension_information(xinerama::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        Ok(xinerama::is_active(&self.conn)?.reply()?.state != 0)
+    }
+
+    /// Returns the monitors of RandR 1.5, named after their first output so workspaces can be
+    /// pinned to it, or `None` when the server does not support them.
+    fn get_monitors(&self) -> Option<Vec<Screen<X11rbWindowHandle>>> {
+        let monitors = randr::get_monitors(&self.conn, self.root, true)
+            .ok()?
+            .reply()
+            .ok()?
+            .monitors;
+        if monitors.is_empty() {
+            return None;
+        }
+        let root = self.get_default_root_handle();
+        let screens = monitors
+            .iter()
+            .map(|monitor| {
+                // Monitors set up by hand may have their own name, or no output.
+                let name = monitor
+                    .outputs
+                    .first()
+                    .and_then(|&output| self.get_output_name(output))
+                    .or_else(|| {
+                        let reply = xproto::get_atom_name(&self.conn, monitor.name)
+                            .ok()?
+                            .reply()
+                            .ok()?;
+                        Some(String::from_utf8_lossy(&reply.name).into_owned())
+                    })
+                    .unwrap_or_default();
+                let mut s = Screen::new(
+                    BBox {
+                        x: i32::from(monitor.x),
+                        y: i32::from(monitor.y),
+                        width: i32::from(monitor.width),
+                        height: i32::from(monitor.height),
+                    },
+                    name,
+                );
+                s.root = root;
+                s
+            })
+            .collect();
+        Some(screens)
+    }
+
+    /// The name of a RandR output, e.g. `DP-1`.
+    fn get_output_name(&self, output: randr::Output) -> Option<String> {
+        let info = randr::get_output_info(&self.conn, output, x11rb::CURRENT_TIME)
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(String::from_utf8_lossy(&info.name).into_owned())
+    }
+
     /// Returns the dimensions of the screens.
     pub fn get_screens_area_dimensions(&self) -> Result<(i32, i32)> {
         let mut height = 0;
@@ -272,20 +379,22 @@ impl XWrap {
         }
     }
 
-    /// Returns the atom actions of a window.
-    pub fn get_window_actions_atoms(&self, window: xproto::Window) -> Result<Vec<xproto::Atom>> {
+    /// Returns the index of the desktop a window is on, as set by a previous window manager.
+    pub fn get_window_desktop(&self, window: xproto::Window) -> Result<Option<usize>> {
         let reply = xproto::get_property(
             &self.conn,
             false,
             window,
-            self.atoms.NetWMAction,
-            xproto::AtomEnum::ATOM,
-            0,
+            self.atoms.NetWMDesktop,
+            xproto::AtomEnum::CARDINAL,
             0,
+            1,
         )?
         .reply()?;
-
-        Ok(reply.value32().map_or(Vec::new(), Iterator::collect))
+        Ok(reply
+            .value32()
+            .and_then(|mut i| i.next())
+            .and_then(|desktop| usize::try_from(desktop).ok()))
     }
 
     /// Returns the attributes of a window.
@@ -296,11 +405,6 @@ impl XWrap {
         Ok(xproto::get_window_attributes(&self.conn, window)?.reply()?)
     }
 
-    /// Returns a windows class `WM_CLASS`
-    pub fn get_window_class(&self, window: xproto::Window) -> Result<Option<WmClass>> {
-        Ok(WmClass::get(&self.conn, window)?.reply()?)
-    }
-
     /// Returns the geometry of a window as a `XyhwChange` struct.
     pub fn get_window_geometry(&self, window: xproto::Window) -> Result<XyhwChange> {
         let geo = xproto::get_geometry(&self.conn, window)?.reply()?;
@@ -327,23 +431,34 @@ impl XWrap {
         self.get_text_prop(window, xproto::AtomEnum::WM_NAME.into())
     }
 
-    /// Returns a windows `_NET_WM_PID`.
-    pub fn get_window_pid(&self, window: xproto::Window) -> Result<u32> {
-        let prop = self.get_property(
+    /// Returns a windows `_NET_WM_WINDOW_OPACITY`, if it has one.
+    pub fn get_window_opacity(&self, window: xproto::Window) -> Result<Option<u32>> {
+        let opacity = self.request_property(
             window,
-            self.atoms.NetWMPid,
-            xproto::AtomEnum::CARDINAL.into(),
+            self.atoms.NetWMWindowOpacity,
+            xproto::AtomEnum::CARDINAL,
+            1,
         )?;
-        if prop.is_empty() {
-            return Ok(x11rb::NONE);
-        }
-        Ok(prop[0])
+        Ok(opacity.reply()?.value32().and_then(|mut v| v.next()))
+    }
+
+    /// Returns whether a window asks for no border through `_MOTIF_WM_HINTS`.
+    pub fn get_window_undecorated(&self, window: xproto::Window) -> Result<bool> {
+        let hints =
+            self.request_property(window, self.atoms.MotifWMHints, self.atoms.MotifWMHints, 5)?;
+        Ok(undecorated_from_reply(&hints.reply()?))
     }
 
     /// Returns the states of a window.
     pub fn get_window_states(&self, window: xproto::Window) -> Result<Vec<WindowState>> {
-        let window_states_atoms = self.get_window_states_atoms(window)?;
+        Ok(self.window_states_from_atoms(&self.get_window_states_atoms(window)?))
+    }
 
+    /// Reads the states of a window from its `_NET_WM_STATE` atoms.
+    pub(super) fn window_states_from_atoms(
+        &self,
+        window_states_atoms: &[xproto::Atom],
+    ) -> Vec<WindowState> {
         // if window is maximized both horizontally and vertically
         // `WindowState::Maximized` is used
         // instead of `WindowState::MaximizedVert` and `WindowState::MaximizedHorz`
@@ -376,7 +491,7 @@ impl XWrap {
             window_states.push(WindowState::Maximized);
         }
 
-        Ok(window_states)
+        window_states
     }
 
     /// Returns the atom states of a window.
@@ -422,12 +537,16 @@ impl XWrap {
             1,
         )?
         .reply()?;
+        Ok(self.window_type_from_reply(&reply))
+    }
 
+    /// Reads the type of a window from its `_NET_WM_WINDOW_TYPE`.
+    pub(super) fn window_type_from_reply(&self, reply: &xproto::GetPropertyReply) -> WindowType {
         let Some(mut val) = reply.value32() else {
-            return Ok(WindowType::Normal);
+            return WindowType::Normal;
         };
 
-        Ok(match val.next() {
+        match val.next() {
             x if x == Some(self.atoms.NetWMWindowTypeDesktop) => WindowType::Desktop,
             x if x == Some(self.atoms.NetWMWindowTypeDock) => WindowType::Dock,
             x if x == Some(self.atoms.NetWMWindowTypeToolbar) => WindowType::Toolbar,
@@ -435,8 +554,12 @@ impl XWrap {
             x if x == Some(self.atoms.NetWMWindowTypeUtility) => WindowType::Utility,
             x if x == Some(self.atoms.NetWMWindowTypeSplash) => WindowType::Splash,
             x if x == Some(self.atoms.NetWMWindowTypeDialog) => WindowType::Dialog,
+            x if x == Some(self.atoms.NetWMWindowTypeNotification) => WindowType::Notification,
+            x if x == Some(self.atoms.NetWMWindowTypePopupMenu) => WindowType::PopupMenu,
+            x if x == Some(self.atoms.NetWMWindowTypeDropdownMenu) => WindowType::DropdownMenu,
+            x if x == Some(self.atoms.NetWMWindowTypeTooltip) => WindowType::Tooltip,
             _ => WindowType::Normal,
-        })
+        }
     }
 
     /// Returns the `WM_HINTS` of a window.
@@ -449,30 +572,18 @@ impl XWrap {
         &self,
         window: xproto::Window,
     ) -> Result<(WMStateWindowState, Option<xproto::Window>)> {
-        // `WM_STATE` contains 2 properties:
-        //   - state (CARD32)
-        //   - icon (WINDOW)
-        let rep = xproto::get_property(
-            &self.conn,
-            false,
-            window,
-            self.atoms.WMState,
-            self.atoms.WMState,
-            0,
-            2,
-        )?
-        .reply()?;
+        let rep = self.request_wm_state(window)?.reply()?;
+        Ok(wm_state_from_reply(&rep))
+    }
 
-        let Some(values) = rep.value32().map(Iterator::collect::<Vec<u32>>) else {
-            return Ok((WMStateWindowState::Normal, None));
-        };
-        Ok((
-            values
-                .first()
-                .and_then(|v| v.try_into().ok())
-                .unwrap_or(WMStateWindowState::Normal),
-            values.get(1).copied(),
-        ))
+    /// Returns the `WM_STATE` of each window. All the requests are sent before waiting for the
+    /// first reply, so this costs a single round trip however many windows there are.
+    pub fn get_wm_states(&self, windows: &[xproto::Window]) -> Vec<Result<WMStateWindowState>> {
+        let cookies: Vec<_> = windows.iter().map(|w| self.request_wm_state(*w)).collect();
+        cookies
+            .into_iter()
+            .map(|cookie| Ok(wm_state_from_reply(&cookie?.reply()?).0))
+            .collect()
     }
 
     /// Returns the name of a `XAtom`.
@@ -488,23 +599,6 @@ impl XWrap {
         Ok(WmSizeHints::get(&self.conn, window, self.atoms.WMNormalHints)?.reply()?)
     }
 
-    /// Returns a cardinal property of a window.
-    fn get_property(
-        &self,
-        window: xproto::Window,
-        property: xproto::Atom,
-        r#type: xproto::Atom,
-    ) -> Result<Vec<xproto::Atom>> {
-        let res =
-            xproto::get_property(&self.conn, false, window, property, r#type, 0, 0)?.reply()?;
-
-        let rt = match res.value32() {
-            Some(props) => props.collect(),
-            None => vec![],
-        };
-        Ok(rt)
-    }
-
     /// Returns all the roots of the display.
     fn get_roots(&self) -> impl Iterator<Item = xproto::Window> + '_ {
         self.conn.setup().roots.iter().map(|screen| screen.root)
@@ -517,14 +611,26 @@ impl XWrap {
             false,
             window,
             atom,
-            // Not sure for the type atom here...
             xproto::AtomEnum::ANY,
             0,
             MAX_PROPERTY_VALUE_LEN,
         )?
         .reply()?;
-        let s = String::from_utf8(prop.value)?;
-        Ok(s)
+        self.text_from_reply(&prop).ok_or_else(|| BackendError {
+            src: None,
+            msg: "Text property not set",
+            backtrace: Backtrace::capture(),
+            kind: ErrorKind::PropertyNotFound,
+        })
+    }
+
+    /// Reads a text property, `None` when it is not set.
+    pub(super) fn text_from_reply(&self, prop: &xproto::GetPropertyReply) -> Option<String> {
+        if prop.type_ == x11rb::NONE {
+            return None;
+        }
+        let encoding = self.atoms.text_encoding(prop.type_);
+        Some(text_property::decode(&prop.value, encoding))
     }
 
     /// Returns the child windows of a root.
@@ -533,6 +639,36 @@ impl XWrap {
         Ok(oui.children)
     }
 
+    /// Asks for a property of a window, without waiting for the reply.
+    pub(super) fn request_property(
+        &self,
+        window: xproto::Window,
+        property: impl Into<xproto::Atom>,
+        r#type: impl Into<xproto::Atom>,
+        long_length: u32,
+    ) -> Result<Cookie<'_, RustConnection, xproto::GetPropertyReply>> {
+        Ok(xproto::get_property(
+            &self.conn,
+            false,
+            window,
+            property,
+            r#type,
+            0,
+            long_length,
+        )?)
+    }
+
+    /// Asks for the `WM_STATE` of a window, without waiting for the reply.
+    fn request_wm_state(
+        &self,
+        window: xproto::Window,
+    ) -> Result<Cookie<'_, RustConnection, xproto::GetPropertyReply>> {
+        // `WM_STATE` contains 2 properties:
+        //   - state (CARD32)
+        //   - icon (WINDOW)
+        self.request_property(window, self.atoms.WMState, self.atoms.WMState, 2)
+    }
+
     /// Returns the `_NET_WM_STRUT` as a `DockArea`.
     fn get_window_strut_array_strut(&self, window: xproto::Window) -> Result<Option<DockArea>> {
         let res = xproto::get_property(
@@ -590,31 +726,114 @@ impl XWrap {
 /// Parses a color string written in the hex format #RRGGBB to a tuple of u16.
 /// Since colors in hex format are represented using 8 bits, we need to adjust them to represent
 /// the right proportion of color on a 16 bits value by multiplying by 256
-fn parse_color_string(color: &str) -> Result<(u16, u16, u16)> {
-    Ok((
-        u16::from_str_radix(&color[1..3], 16)? * 256,
-        u16::from_str_radix(&color[3..5], 16)? * 256,
-        u16::from_str_radix(&color[5..7], 16)? * 256,
-    ))
+/// Reads `_MOTIF_WM_HINTS` as whether the window asks for no border.
+pub(super) fn undecorated_from_reply(reply: &xproto::GetPropertyReply) -> bool {
+    reply
+        .value32()
+        .is_some_and(|hints| motif_hints::wants_no_border(&hints.collect::<Vec<_>>()))
+}
+
+/// The pixel layout of a visual of a screen, if it is a true color visual.
+fn visual_format(screen: &xproto::Screen, visual: xproto::Visualid) -> Option<VisualFormat> {
+    screen.allowed_depths.iter().find_map(|depth| {
+        depth
+            .visuals
+            .iter()
+            .find(|candidate| candidate.visual_id == visual)
+            .filter(|visual| visual.class == xproto::VisualClass::TRUE_COLOR)
+            .map(|visual| VisualFormat {
+                red_mask: visual.red_mask,
+                green_mask: visual.green_mask,
+                blue_mask: visual.blue_mask,
+                depth: depth.depth,
+            })
+    })
+}
+
+/// Reads `WM_SIZE_HINTS`/`WM_NORMAL_HINTS` as a `XyhwChange`.
+pub(super) fn sizing_as_xyhw(size: WmSizeHints) -> XyhwChange {
+    let mut xyhw = XyhwChange::default();
+
+    if let Some((_specification, width, height)) = size.size {
+        xyhw.w = Some(width);
+        xyhw.h = Some(height);
+    } else if let Some((width, height)) = size.base_size {
+        xyhw.w = Some(width);
+        xyhw.h = Some(height);
+    }
+
+    if let Some((width, height)) = size.size_increment {
+        xyhw.w = Some(width);
+        xyhw.h = Some(height);
+    }
+
+    if let Some((max_width, max_height)) = size.max_size {
+        xyhw.maxw = Some(max_width);
+        xyhw.maxh = Some(max_height);
+    }
+
+    if let Some((min_width, min_height)) = size.min_size {
+        xyhw.minw = Some(min_width);
+        xyhw.minh = Some(min_height);
+    }
+    // Make sure that width and height are not smaller than the min values.
+    xyhw.w = std::cmp::max(xyhw.w, xyhw.minw);
+    xyhw.h = std::cmp::max(xyhw.h, xyhw.minh);
+    // Ignore the sizing if the sizing is set to 0.
+    xyhw.w = xyhw.w.filter(|&w| w != 0);
+    xyhw.h = xyhw.h.filter(|&h| h != 0);
+
+    if let Some((_specification, x, y)) = size.position {
+        xyhw.x = Some(x);
+        xyhw.y = Some(y);
+    }
+
+    if let Some((min, max)) = size.aspect {
+        let ratio = |r: AspectRatio| {
+            (r.numerator > 0 && r.denominator > 0).then_some((r.numerator, r.denominator))
+        };
+        xyhw.min_aspect = ratio(min);
+        xyhw.max_aspect = ratio(max);
+    }
+
+    xyhw
+}
+
+/// Reads the state and icon window of a `WM_STATE` property.
+fn wm_state_from_reply(
+    rep: &xproto::GetPropertyReply,
+) -> (WMStateWindowState, Option<xproto::Window>) {
+    let Some(values) = rep.value32().map(Iterator::collect::<Vec<u32>>) else {
+        return (WMStateWindowState::Normal, None);
+    };
+    (
+        values
+            .first()
+            .and_then(|v| v.try_into().ok())
+            .unwrap_or(WMStateWindowState::Normal),
+        values.get(1).copied(),
+    )
 }
 
 struct IntoDockArea<'a>(&'a [i32]);
 
 impl From<IntoDockArea<'_>> for DockArea {
     fn from(val: IntoDockArea<'_>) -> Self {
+        // `_NET_WM_STRUT` only has the first four values, the start and ends are left at zero.
+        let get = |i: usize| val.0.get(i).copied().unwrap_or_default();
         DockArea {
-            left: val.0[0],
-            right: val.0[1],
-            top: val.0[2],
-            bottom: val.0[3],
-            left_start_y: val.0[4],
-            left_end_y: val.0[5],
-            right_start_y: val.0[6],
-            right_end_y: val.0[7],
-            top_start_x: val.0[8],
-            top_end_x: val.0[9],
-            bottom_start_x: val.0[10],
-            bottom_end_x: val.0[11],
+            left: get(0),
+            right: get(1),
+            top: get(2),
+            bottom: get(3),
+            left_start_y: get(4),
+            left_end_y: get(5),
+            right_start_y: get(6),
+            right_end_y: get(7),
+            top_start_x: get(8),
+            top_end_x: get(9),
+            bottom_start_x: get(10),
+            bottom_end_x: get(11),
         }
     }
 }
@@ -1,4 +1,5 @@
 //! Xlib calls related to a mouse.
+use leftwm_core::{models::WindowType, utils::modmask_lookup::with_lock_combinations};
 use x11rb::{protocol::xproto, x11_utils::Serialize};
 
 use super::{button_event_mask, mouse_event_mask, XWrap};
@@ -13,19 +14,10 @@ impl XWrap {
             self.grab_buttons(handle, xproto::ButtonIndex::M1, xproto::ModMask::ANY)?;
             self.grab_buttons(handle, xproto::ButtonIndex::M3, xproto::ModMask::ANY)?;
         }
-        let mouse_key_mask = xproto::ModMask::from(self.mouse_key_mask.bits());
-        self.grab_buttons(handle, xproto::ButtonIndex::M1, mouse_key_mask)?;
-        self.grab_buttons(
-            handle,
-            xproto::ButtonIndex::M1,
-            mouse_key_mask | xproto::ModMask::SHIFT,
-        )?;
-        self.grab_buttons(handle, xproto::ButtonIndex::M3, mouse_key_mask)?;
-        self.grab_buttons(
-            handle,
-            xproto::ButtonIndex::M3,
-            mouse_key_mask | xproto::ModMask::SHIFT,
-        )?;
+        for (modifiers, button) in &self.mousebinds {
+            let modifiers = xproto::ModMask::from(modifiers.bits());
+            self.grab_buttons(handle, xproto::ButtonIndex::from(*button), modifiers)?;
+        }
         Ok(())
     }
 
@@ -36,12 +28,8 @@ impl XWrap {
         button: xproto::ButtonIndex,
         modifiers: xproto::ModMask,
     ) -> Result<()> {
-        let mods: Vec<xproto::ModMask> = vec![
-            modifiers,
-            modifiers | xproto::ModMask::M2,
-            modifiers | xproto::ModMask::LOCK,
-        ];
-        for m in mods {
+        // Grab the buttons whichever locks are on.
+        for m in with_lock_combinations(modifiers.into(), self.lock_mask) {
             xproto::grab_button(
                 &self.conn,
                 false,
@@ -52,12 +40,41 @@ impl XWrap {
                 x11rb::NONE,
                 x11rb::NONE,
                 button,
-                m,
+                xproto::ModMask::from(m),
             )?;
         }
         Ok(())
     }
 
+    /// Detect the lock modifiers again after the keyboard mapping changed, grabbing the buttons
+    /// of the windows again when they moved.
+    pub fn refresh_lock_mask(&mut self) -> Result<()> {
+        let lock_mask = self.get_lock_mask()?;
+        if lock_mask == self.lock_mask {
+            return Ok(());
+        }
+        self.lock_mask = lock_mask;
+        self.regrab_mouse_clicks()
+    }
+
+    /// Grabs the buttons of all managed windows again, e.g. after the mousebinds changed.
+    pub fn regrab_mouse_clicks(&self) -> Result<()> {
+        for &window in &self.managed_windows {
+            // Docks do not get clicks grabbed to focus them, see `toggle_window_visibility`.
+            let focus_on_click = self.focus_behaviour.is_clickto()
+                && window != self.focused_window
+                && self.get_window_type(window)? != WindowType::Dock;
+            self.grab_mouse_clicks(window, !focus_on_click)?;
+        }
+        Ok(())
+    }
+
+    /// The modifiers of the lock keys, which mousebinds ignore.
+    #[must_use]
+    pub const fn lock_mask(&self) -> u16 {
+        self.lock_mask
+    }
+
     /// Cleans all currently grabbed buttons of a window.
     pub fn ungrab_buttons(&self, handle: xproto::Window) -> Result<()> {
         xproto::ungrab_button(
@@ -93,7 +110,7 @@ impl XWrap {
 
     /// Move the cursor to a window.
     pub fn move_cursor_to_window(&self, window: xproto::Window) -> Result<()> {
-        let geo = xproto::get_geometry(&self.conn, window)?.reply()?;
+        let geo = xproto::get_geometry(&self.conn, self.frame_of(window))?.reply()?;
         let point = (
             i32::from(geo.x) + (i32::from(geo.width) / 2),
             i32::from(geo.y) + (i32::from(geo.height) / 2),
@@ -1,6 +1,9 @@
 use std::ffi::CString;
 
-use leftwm_core::models::{TagId, WindowHandle};
+use leftwm_core::{
+    models::{TagId, WindowHandle},
+    utils::color::Rgba,
+};
 use x11rb::protocol::xproto::{self, ChangeWindowAttributesAux, PropMode};
 
 use crate::{error::Result, xatom, X11rbWindowHandle};
@@ -68,6 +71,29 @@ impl XWrap {
         Ok(())
     }
 
+    /// Sets the number of desktops and their names.
+    pub fn set_desktop_names(&self, labels: &[String]) -> Result<()> {
+        self.set_desktop_prop(
+            &[u32::try_from(labels.len())?],
+            self.atoms.NetNumberOfDesktops,
+        )?;
+
+        // A list of strings is written as the strings separated by null bytes, the way
+        // `Xutf8TextListToTextProperty` does: https://linux.die.net/man/3/xutf8textlisttotextproperty
+        let bytes = labels.join("\0").into_bytes();
+        xproto::change_property(
+            &self.conn,
+            xproto::PropMode::REPLACE,
+            self.root,
+            self.atoms.NetDesktopNames,
+            self.atoms.UTF8String,
+            8,
+            u32::try_from(bytes.len())?,
+            &bytes,
+        )?;
+        Ok(())
+    }
+
     /// Sets the current desktop.
     pub fn set_current_desktop(&self, current_tag: Option<TagId>) -> Result<()> {
         let indexes: Vec<u32> = match current_tag {
@@ -139,14 +165,17 @@ impl XWrap {
         Ok(())
     }
 
-    /// Sets a windows border color.
-    pub fn set_window_border_color(&self, window: xproto::Window, mut color: u32) -> Result<()> {
-        // Force border opacity to 0xff. (color is <aarrggbb> in hex format)
-        color |= 0xff00_0000;
+    /// Sets a windows border color, in the visual of the window owning the border.
+    pub fn set_window_border_color(&self, window: xproto::Window, color: Rgba) -> Result<()> {
+        let window = self.frame_of(window);
+        if self.border_colors.borrow_mut().insert(window, color) == Some(color) {
+            return Ok(());
+        }
+        let pixel = self.get_pixel(window, color)?;
         xproto::change_window_attributes(
             &self.conn,
             window,
-            &ChangeWindowAttributesAux::new().border_pixel(color),
+            &ChangeWindowAttributesAux::new().border_pixel(pixel),
         )?;
         Ok(())
     }
@@ -188,6 +217,37 @@ impl XWrap {
         )
     }
 
+    /// Sets the opacity compositors draw a window with, or lets them use the default.
+    pub fn set_window_opacity(
+        &mut self,
+        window: xproto::Window,
+        opacity: Option<f32>,
+    ) -> Result<()> {
+        // Compositors read it from the top level window.
+        let top = self.frame_of(window);
+        let opacity = match opacity {
+            Some(opacity) => {
+                if !self.own_opacities.contains_key(&window) {
+                    let own = self.get_window_opacity(top)?;
+                    self.own_opacities.entry(window).or_insert(own);
+                }
+                Some((opacity.clamp(0.0, 1.0) * u32::MAX as f32) as u32)
+            }
+            // Give the window back the opacity it had before.
+            None => self.own_opacities.remove(&window).flatten(),
+        };
+        let Some(opacity) = opacity else {
+            xproto::delete_property(&self.conn, top, self.atoms.NetWMWindowOpacity)?;
+            return Ok(());
+        };
+        self.replace_property_u32(
+            top,
+            self.atoms.NetWMWindowOpacity,
+            xproto::AtomEnum::CARDINAL.into(),
+            &[opacity],
+        )
+    }
+
     /// Sets the atom states of a window.
     pub fn set_window_states_atoms(
         &self,
@@ -4,12 +4,20 @@ use leftwm_core::{
     models::{WindowChange, WindowHandle, WindowType, Xyhw},
     DisplayEvent, Window,
 };
-use x11rb::{protocol::xproto, x11_utils::Serialize};
+use x11rb::{
+    connection::Connection,
+    properties::{WmClass, WmHints, WmSizeHints},
+    protocol::xproto::{self, AtomEnum},
+    x11_utils::Serialize,
+};
 
 use crate::xatom::WMStateWindowState;
 use crate::{error::Result, X11rbWindowHandle};
 
-use super::{root_event_mask, XWrap};
+use super::{
+    getters::{sizing_as_xyhw, undecorated_from_reply},
+    XWrap, MAX_PROPERTY_VALUE_LEN,
+};
 
 impl XWrap {
     /// Sets up a window before we manage it.
@@ -23,18 +31,56 @@ impl XWrap {
             return Ok(None);
         }
         let handle = WindowHandle(X11rbWindowHandle(window));
-        // Gather info about the window from xlib.
-        let name = self.get_window_name(window)?;
-        let legacy_name = self.get_window_legacy_name(window)?;
-        let class = self.get_window_class(window)?;
-        let pid = self.get_window_pid(window)?;
-        let r#type = self.get_window_type(window)?;
-        let states = self.get_window_states(window)?;
-        let actions = self.get_window_actions_atoms(window)?;
+        // Gather info about the window. All the requests are sent before waiting for the first
+        // reply, so they only cost a single round trip.
+        let text_len = MAX_PROPERTY_VALUE_LEN;
+        let list_len = MAX_PROPERTY_VALUE_LEN / 4;
+        let net_name =
+            self.request_property(window, self.atoms.NetWMName, AtomEnum::ANY, text_len)?;
+        let legacy_name =
+            self.request_property(window, AtomEnum::WM_NAME, AtomEnum::ANY, text_len)?;
+        let class = WmClass::get(&self.conn, window)?;
+        let pid = self.request_property(window, self.atoms.NetWMPid, AtomEnum::CARDINAL, 1)?;
+        let r#type =
+            self.request_property(window, self.atoms.NetWMWindowType, AtomEnum::ATOM, 1)?;
+        let states =
+            self.request_property(window, self.atoms.NetWMState, AtomEnum::ATOM, list_len)?;
+        let actions =
+            self.request_property(window, self.atoms.NetWMAction, AtomEnum::ATOM, list_len)?;
+        let trans =
+            self.request_property(window, AtomEnum::WM_TRANSIENT_FOR, AtomEnum::WINDOW, 1)?;
+        let sizing_hint = WmSizeHints::get(&self.conn, window, self.atoms.WMNormalHints)?;
+        let wm_hint = WmHints::get(&self.conn, window)?;
+        let motif_hints =
+            self.request_property(window, self.atoms.MotifWMHints, self.atoms.MotifWMHints, 5)?;
+
+        let net_name = self.text_from_reply(&net_name.reply()?);
+        // Windows without a legacy name are managed all the same.
+        let legacy_name = legacy_name
+            .reply()
+            .ok()
+            .and_then(|reply| self.text_from_reply(&reply))
+            .unwrap_or_default();
+        // Fall back to the legacy name.
+        let name = net_name.unwrap_or_else(|| legacy_name.clone());
+        let class = class.reply()?;
+        let pid = pid.reply()?.value32().and_then(|mut v| v.next());
+        let pid = pid.unwrap_or(x11rb::NONE);
+        let r#type = self.window_type_from_reply(&r#type.reply()?);
+        let states = states
+            .reply()?
+            .value32()
+            .map_or(Vec::new(), Iterator::collect::<Vec<_>>);
+        let states = self.window_states_from_atoms(&states);
+        let actions = actions
+            .reply()?
+            .value32()
+            .map_or(Vec::new(), Iterator::collect::<Vec<_>>);
         let mut can_resize = actions.contains(&self.atoms.NetWMActionResize);
-        let trans = self.get_transient_for(window)?;
-        let sizing_hint = self.get_hint_sizing_as_xyhw(window)?;
-        let wm_hint = self.get_wmhints(window)?;
+        let trans = trans.reply()?.value32().and_then(|mut v| v.next());
+        let sizing_hint = sizing_hint.reply()?.map(sizing_as_xyhw);
+        let wm_hint = wm_hint.reply()?;
+        let undecorated = undecorated_from_reply(&motif_hints.reply()?);
 
         // Build the new window, and fill in info about it.
         let mut w = Window::new(handle, Some(name), Some(pid));
@@ -46,6 +92,7 @@ impl XWrap {
         w.r#type = r#type.clone();
         w.states = states;
         w.transient = trans.map(|h| WindowHandle(X11rbWindowHandle(h)));
+        w.undecorated = undecorated;
 
         // Initialise the windows floating with the pre-mapped settings.
         sizing_hint
@@ -147,13 +194,20 @@ impl XWrap {
                 change.r#type = Some(r#type);
                 return Ok(Some(DisplayEvent::WindowChange(change)));
             }
-        } else {
+        } else if !r#type.is_popup() {
+            // Windows with an alpha channel need their border pixel in their own visual.
+            if let Some(format) = self.get_visual_format(handle)? {
+                self.visuals.insert(handle, format);
+            }
             let color = if floating {
                 self.colors.floating
             } else {
                 self.colors.normal
             };
             self.set_window_border_color(handle, color)?;
+            if self.reparent_windows {
+                self.frame_window(handle)?;
+            }
 
             if follow_mouse {
                 self.move_cursor_to_window(handle)?;
@@ -173,6 +227,15 @@ impl XWrap {
     ) -> Result<()> {
         let WindowHandle(X11rbWindowHandle(handle)) = h;
         self.managed_windows.retain(|x| *x != handle);
+        self.window_colors.remove(&handle);
+        self.visuals.remove(&handle);
+        self.own_opacities.remove(&handle);
+        let frame = self.frame_of(handle);
+        let border_colors = self.border_colors.get_mut();
+        border_colors.remove(&handle);
+        border_colors.remove(&frame);
+        self.ping_watch.forget(&handle);
+        self.unframe_window(handle, destroyed)?;
         if !destroyed {
             xproto::grab_server(&self.conn)?;
             self.ungrab_buttons(handle)?;
@@ -184,8 +247,12 @@ impl XWrap {
         Ok(())
     }
 
-    /// Updates a window.
-    pub fn update_window(&self, window: &Window<X11rbWindowHandle>) -> Result<()> {
+    /// Updates a window, whose `WM_STATE` is `state`.
+    pub fn update_window(
+        &self,
+        window: &Window<X11rbWindowHandle>,
+        state: WMStateWindowState,
+    ) -> Result<()> {
         let WindowHandle(X11rbWindowHandle(handle)) = window.handle;
         if window.visible() {
             let changes = xproto::ConfigureWindowAux {
@@ -196,10 +263,33 @@ impl XWrap {
                 border_width: Some(u32::try_from(window.border())?),
                 ..Default::default()
             };
-            self.set_window_config(handle, &changes)?;
+            if self.frames.contains_key(&handle) {
+                self.set_window_config(self.frame_of(handle), &changes)?;
+                // The title bar takes the top of the frame.
+                let bar = self.title_bar_height(window);
+                self.set_title_bar_of(handle, bar);
+                let inside = xproto::ConfigureWindowAux {
+                    x: Some(0),
+                    y: Some(bar.into()),
+                    height: Some(
+                        u32::try_from(window.height())?
+                            .saturating_sub(bar.into())
+                            .max(1),
+                    ),
+                    border_width: Some(0),
+                    ..changes
+                };
+                self.set_window_config(handle, &inside)?;
+                self.draw_title_bar(handle, handle == self.focused_window)?;
+            } else {
+                self.set_window_config(handle, &changes)?;
+            }
             self.configure_window(window)?;
+            // Keep the selection visible on windows without focus.
+            if handle != self.focused_window {
+                self.set_window_border_color(handle, self.unfocused_border_color(window))?;
+            }
         }
-        let (state, _) = self.get_wm_state(handle)?;
         // Only change when needed. This prevents task bar icons flashing (especially with steam).
         if window.visible() && state != WMStateWindowState::Normal {
             self.toggle_window_visibility(handle, true)?;
@@ -212,17 +302,14 @@ impl XWrap {
     /// Maps and unmaps a window depending on it is visible.
     pub fn toggle_window_visibility(&self, window: xproto::Window, visible: bool) -> Result<()> {
         // We don't want to receive this map or unmap event.
-        let mask_off = root_event_mask().remove(xproto::EventMask::SUBSTRUCTURE_NOTIFY);
-        let mut attrs = xproto::ChangeWindowAttributesAux {
-            event_mask: Some(mask_off),
-            ..Default::default()
-        };
-        xproto::change_window_attributes(&self.conn, self.root, &attrs)?;
+        self.set_root_notify(false)?;
+        // Framed windows stay mapped inside their frame.
+        let frame = self.frame_of(window);
         if visible {
             // Set WM_STATE to normal state.
             self.set_wm_state(window, WMStateWindowState::Normal)?;
             // Make sure the window is mapped.
-            xproto::map_window(&self.conn, window)?;
+            xproto::map_window(&self.conn, frame)?;
             // Regrab the mouse clicks but ignore `dock` windows as some don't handle click events put on them
             if self.focus_behaviour.is_clickto()
                 && self.get_window_type(window)? != WindowType::Dock
@@ -233,13 +320,11 @@ impl XWrap {
             // Ungrab the mouse clicks.
             self.ungrab_buttons(window)?;
             // Make sure the window is unmapped.
-            xproto::unmap_window(&self.conn, window)?;
+            xproto::unmap_window(&self.conn, frame)?;
             // Set WM_STATE to iconic state.
             self.set_wm_state(window, WMStateWindowState::Iconic)?;
         }
-        attrs.event_mask = Some(root_event_mask());
-        xproto::change_window_attributes(&self.conn, self.root, &attrs)?;
-        Ok(())
+        self.set_root_notify(true)
     }
 
     /// Makes a window take focus.
@@ -252,12 +337,9 @@ impl XWrap {
         // Update previous window.
         if let Some(previous) = previous {
             let WindowHandle(X11rbWindowHandle(previous_handle)) = previous.handle;
-            let color = if previous.floating() {
-                self.colors.floating
-            } else {
-                self.colors.normal
-            };
+            let color = self.unfocused_border_color(previous);
             self.set_window_border_color(previous_handle, color)?;
+            self.draw_title_bar(previous_handle, false)?;
             // Open up button1 clicking on the previously focused window.
             if self.focus_behaviour.is_clickto() {
                 self.grab_mouse_clicks(previous_handle, false)?;
@@ -265,8 +347,8 @@ impl XWrap {
         }
         self.focused_window = handle;
         self.grab_mouse_clicks(handle, true)?;
-        self.set_window_urgency(handle, false)?;
-        self.set_window_border_color(handle, self.colors.active)?;
+        self.set_window_border_color(handle, self.colors_of(handle).active)?;
+        self.draw_title_bar(handle, true)?;
         self.focus(handle, window.never_focus)?;
         self.sync()?;
         Ok(())
@@ -274,6 +356,7 @@ impl XWrap {
 
     /// Focuses a window.
     pub fn focus(&mut self, window: xproto::Window, never_focus: bool) -> Result<()> {
+        self.install_colormaps(window)?;
         if !never_focus {
             xproto::set_input_focus(
                 &self.conn,
@@ -300,15 +383,18 @@ impl XWrap {
         floating: bool,
     ) -> Result<()> {
         if let Some(WindowHandle(X11rbWindowHandle(handle))) = handle {
+            let colors = self.colors_of(handle);
             let color = if floating {
-                self.colors.floating
+                colors.floating
             } else {
-                self.colors.normal
+                colors.normal
             };
             self.set_window_border_color(handle, color)?;
+            self.draw_title_bar(handle, false)?;
 
             self.grab_mouse_clicks(handle, false)?;
         }
+        self.install_default_colormap()?;
         xproto::set_input_focus(
             &self.conn,
             xproto::InputFocus::POINTER_ROOT,
@@ -323,17 +409,64 @@ impl XWrap {
         )
     }
 
+    /// Installs the colormaps a window needs: those of the windows in its `WM_COLORMAP_WINDOWS`,
+    /// and its own before them unless it is listed.
+    pub fn install_colormaps(&self, window: xproto::Window) -> Result<()> {
+        let listed = self.request_property(
+            window,
+            self.atoms.WMColormapWindows,
+            AtomEnum::WINDOW,
+            MAX_PROPERTY_VALUE_LEN / 4,
+        )?;
+        let mut windows: Vec<_> = listed
+            .reply()?
+            .value32()
+            .map_or(Vec::new(), Iterator::collect);
+        if !windows.contains(&window) {
+            windows.insert(0, window);
+        }
+        // The first window has the highest priority, so its colormap is installed last.
+        let attrs = windows
+            .into_iter()
+            .rev()
+            .map(|w| xproto::get_window_attributes(&self.conn, w))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        // Listed windows which are gone are skipped.
+        for attrs in attrs.into_iter().filter_map(|cookie| cookie.reply().ok()) {
+            if attrs.colormap != x11rb::NONE {
+                xproto::install_colormap(&self.conn, attrs.colormap)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Installs the default colormap of the screen again.
+    pub fn install_default_colormap(&self) -> Result<()> {
+        let screen = self.conn.setup().roots.iter().find(|s| s.root == self.root);
+        if let Some(screen) = screen {
+            xproto::install_colormap(&self.conn, screen.default_colormap)?;
+        }
+        Ok(())
+    }
+
     /// Send a `XConfigureEvent` for a window to X.
     pub fn configure_window(&self, window: &Window<X11rbWindowHandle>) -> Result<()> {
         let WindowHandle(X11rbWindowHandle(handle)) = window.handle;
+        // Framed windows are told where their inside is, and that they have no border.
+        let (offset, border) = if self.frames.contains_key(&handle) {
+            (window.border(), 0)
+        } else {
+            (0, window.border())
+        };
+        let bar = i32::from(self.title_bar_of(handle));
         let configure_event = xproto::ConfigureNotifyEvent {
             event: handle,
             window: handle,
-            x: i16::try_from(window.x())?,
-            y: i16::try_from(window.y())?,
+            x: i16::try_from(window.x() + offset)?,
+            y: i16::try_from(window.y() + offset + bar)?,
             width: u16::try_from(window.width())?,
-            height: u16::try_from(window.height())?,
-            border_width: u16::try_from(window.border())?,
+            height: u16::try_from((window.height() - bar).max(1))?,
+            border_width: u16::try_from(border)?,
             above_sibling: x11rb::NONE,
             override_redirect: false,
             ..Default::default()
@@ -358,9 +491,9 @@ impl XWrap {
             conf.stack_mode = Some(xproto::StackMode::BELOW);
             conf.sibling = handles.get(i - 1).copied().map(|h| {
                 let WindowHandle(X11rbWindowHandle(w)) = h;
-                w
+                self.frame_of(w)
             });
-            xproto::configure_window(&self.conn, *window, &conf)?;
+            xproto::configure_window(&self.conn, self.frame_of(*window), &conf)?;
         }
         Ok(())
     }
@@ -391,21 +524,32 @@ impl XWrap {
             stack_mode: Some(xproto::StackMode::ABOVE),
             ..Default::default()
         };
-        xproto::configure_window(&self.conn, window, &attrs)?;
+        xproto::configure_window(&self.conn, self.frame_of(window), &attrs)?;
         Ok(())
     }
 
     /// Kills a window.
-    pub fn kill_window(&self, h: WindowHandle<X11rbWindowHandle>) -> Result<()> {
+    pub fn kill_window(&mut self, h: WindowHandle<X11rbWindowHandle>) -> Result<()> {
         let WindowHandle(X11rbWindowHandle(handle)) = h;
         // Nicely ask the window to close.
         if !self.send_xevent_atom(handle, self.atoms.WMDelete)? {
-            // Force kill the window.
-            xproto::grab_server(&self.conn)?;
-            xproto::set_close_down_mode(&self.conn, xproto::CloseDown::DESTROY_ALL)?;
-            xproto::kill_client(&self.conn, handle)?;
-            xproto::ungrab_server(&self.conn)?;
+            return self.force_kill_window(h);
         }
+        // Find out whether the client is still alive to do so.
+        if self.send_xevent_atom(handle, self.atoms.NetWMPing)? {
+            self.ping_watch.ping(handle);
+        }
+        Ok(())
+    }
+
+    /// Kills the client of a window without asking it.
+    pub fn force_kill_window(&mut self, h: WindowHandle<X11rbWindowHandle>) -> Result<()> {
+        let WindowHandle(X11rbWindowHandle(handle)) = h;
+        self.ping_watch.forget(&handle);
+        xproto::grab_server(&self.conn)?;
+        xproto::set_close_down_mode(&self.conn, xproto::CloseDown::DESTROY_ALL)?;
+        xproto::kill_client(&self.conn, handle)?;
+        xproto::ungrab_server(&self.conn)?;
         Ok(())
     }
 
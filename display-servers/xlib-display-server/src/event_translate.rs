@@ -6,7 +6,10 @@ use super::{
 };
 use leftwm_core::{
     models::{Mode, WindowChange, WindowHandle, WindowType, XyhwChange},
-    utils::modmask_lookup::{Button, ModMask},
+    utils::{
+        focus_stealing::{FocusLoopVerdict, FOCUS_LOOP_BACKOFF},
+        modmask_lookup::{Button, ModMask},
+    },
 };
 use std::os::raw::c_ulong;
 use x11_dl::xlib;
@@ -18,6 +21,13 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent<XlibWindowHandle>> {
         let raw_event = x_event.1;
         let normal_mode = x_event.0.mode == Mode::Normal;
         let sloppy_behaviour = x_event.0.focus_behaviour.is_sloppy();
+        let on_frame = x_event.0.is_frame(xlib::XAnyEvent::from(raw_event).window);
+        let _span = tracing::trace_span!(
+            "x_event",
+            kind = raw_event.get_type(),
+            window = xlib::XAnyEvent::from(raw_event).window
+        )
+        .entered();
 
         match raw_event.get_type() {
             // New window is mapped.
@@ -29,9 +39,9 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent<XlibWindowHandle>> {
             // Window is taking focus.
             xlib::FocusIn => from_focus_in(x_event),
             // Window client message.
-            xlib::ClientMessage if normal_mode => from_client_message(&x_event),
+            xlib::ClientMessage if normal_mode => from_client_message(x_event),
             // Window property notify.
-            xlib::PropertyNotify if normal_mode => from_property_notify(&x_event),
+            xlib::PropertyNotify if normal_mode => from_property_notify(x_event),
             // Window configure request.
             xlib::ConfigureRequest if normal_mode => from_configure_request(x_event),
             // Mouse entered notify.
@@ -39,14 +49,46 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent<XlibWindowHandle>> {
             // Mouse motion notify.
             xlib::MotionNotify => from_motion_notify(x_event),
             // Mouse button pressed.
-            xlib::ButtonPress => Some(from_button_press(raw_event)),
+            xlib::ButtonPress if normal_mode && on_frame => Some(from_frame_button_press(&x_event)),
+            xlib::ButtonPress => Some(from_button_press(x_event.0, raw_event)),
             // Mouse button released.
             xlib::ButtonRelease if !normal_mode => Some(from_button_release(x_event)),
+            // Another window manager took the window manager selection, or a window the
+            // clipboard.
+            xlib::SelectionClear => from_selection_clear(x_event),
+            // Selections leftwm owns, for the clipboard manager.
+            xlib::SelectionRequest => from_selection_request(x_event),
+            xlib::SelectionNotify => from_selection_notify(x_event),
+            // The keyboard mapping changed, the lock keys may be on other modifiers.
+            xlib::MappingNotify => from_mapping_notify(x_event),
+            // Part of a frame needs to be drawn again.
+            xlib::Expose => from_expose(&x_event),
             _other => None,
         }
     }
 }
 
+fn from_selection_clear(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let event = xlib::XSelectionClearEvent::from(x_event.1);
+    if event.selection == x_event.0.wm_selection {
+        return Some(DisplayEvent::WmReplaced);
+    }
+    x_event.0.clipboard_selection_clear(&event);
+    None
+}
+
+fn from_selection_request(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let event = xlib::XSelectionRequestEvent::from(x_event.1);
+    x_event.0.clipboard_selection_request(&event);
+    None
+}
+
+fn from_selection_notify(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let event = xlib::XSelectionEvent::from(x_event.1);
+    x_event.0.clipboard_selection_notify(&event);
+    None
+}
+
 fn from_map_request(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
     let xw = x_event.0;
     let event = xlib::XMapRequestEvent::from(x_event.1);
@@ -83,22 +125,43 @@ fn from_focus_in(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
     let xw = x_event.0;
     let event = xlib::XFocusChangeEvent::from(x_event.1);
     // Check that if a window is taking focus, that it should be.
-    if xw.focused_window != event.window {
-        let never_focus = match xw.get_wmhints(xw.focused_window) {
-            Some(hint) => hint.flags & xlib::InputHint != 0 && hint.input == 0,
-            None => false,
-        };
-        xw.focus(xw.focused_window, never_focus);
+    if xw.focused_window == event.window {
+        return None;
+    }
+    match xw.focus_loop.check(xw.focused_window, event.window) {
+        FocusLoopVerdict::Reassert => {
+            let never_focus = match xw.get_wmhints(xw.focused_window) {
+                Some(hint) => hint.flags & xlib::InputHint != 0 && hint.input == 0,
+                None => false,
+            };
+            xw.focus(xw.focused_window, never_focus);
+            None
+        }
+        FocusLoopVerdict::LoopDetected => {
+            tracing::warn!(
+                "Window {:#x} ({}) keeps taking the focus from window {:#x}, not taking it back for {:?}",
+                event.window,
+                xw.get_window_name(event.window).unwrap_or_default(),
+                xw.focused_window,
+                FOCUS_LOOP_BACKOFF
+            );
+            if !xw.mark_focus_stealers_never_focus {
+                return None;
+            }
+            let mut change = WindowChange::new(WindowHandle(XlibWindowHandle(event.window)));
+            change.never_focus = Some(true);
+            Some(DisplayEvent::WindowChange(change))
+        }
+        FocusLoopVerdict::BackingOff => None,
     }
-    None
 }
 
-fn from_client_message(x_event: &XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+fn from_client_message(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
     let event = xlib::XClientMessageEvent::from(x_event.1);
     event_translate_client_message::from_event(x_event.0, event)
 }
 
-fn from_property_notify(x_event: &XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+fn from_property_notify(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
     let event = xlib::XPropertyEvent::from(x_event.1);
     event_translate_property_notify::from_event(x_event.0, event)
 }
@@ -170,7 +233,7 @@ fn from_enter_notify(x_event: &XEvent) -> Option<DisplayEvent<XlibWindowHandle>>
         return None;
     }
 
-    let h = WindowHandle(XlibWindowHandle(event.window));
+    let h = WindowHandle(XlibWindowHandle(x_event.0.client_of(event.window)));
     Some(DisplayEvent::WindowTakeFocus(h))
 }
 
@@ -208,22 +271,52 @@ fn from_motion_notify(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>>
     None
 }
 
-fn from_button_press(raw_event: xlib::XEvent) -> DisplayEvent<XlibWindowHandle> {
+/// Clicks on a frame are on the title bar or the border of the window inside.
+fn from_frame_button_press(x_event: &XEvent) -> DisplayEvent<XlibWindowHandle> {
+    let xw = &x_event.0;
+    let event = xlib::XButtonPressedEvent::from(x_event.1);
+    let window = xw.client_of(event.window);
+    if event.button == xlib::Button1 && event.y >= 0 && event.y < xw.title_bar_of(window) {
+        return DisplayEvent::TitleBarPressed(WindowHandle(XlibWindowHandle(window)));
+    }
+    from_button_press(xw, x_event.1)
+}
+
+fn from_button_press(xw: &XWrap, raw_event: xlib::XEvent) -> DisplayEvent<XlibWindowHandle> {
     let event = xlib::XButtonPressedEvent::from(raw_event);
-    let h = WindowHandle(XlibWindowHandle(event.window));
+    let h = WindowHandle(XlibWindowHandle(xw.client_of(event.window)));
     let mut mod_mask = event.state;
-    mod_mask &= !(xlib::Mod2Mask | xlib::LockMask);
+    mod_mask &= !u32::from(xw.lock_mask());
     DisplayEvent::MouseCombo(
         ModMask::from_bits_retain(mod_mask as u16),
         Button::from(event.button as u8),
         h,
-        event.x,
-        event.y,
+        event.x_root,
+        event.y_root,
     )
 }
 
+fn from_mapping_notify(x_event: XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let mut event = xlib::XMappingEvent::from(x_event.1);
+    if event.request != xlib::MappingPointer {
+        x_event.0.refresh_lock_mask(&mut event);
+    }
+    None
+}
+
+fn from_expose(x_event: &XEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let xw = &x_event.0;
+    let event = xlib::XExposeEvent::from(x_event.1);
+    if event.count == 0 && xw.is_frame(event.window) {
+        let window = xw.client_of(event.window);
+        xw.draw_title_bar(window, window == xw.focused_window);
+    }
+    None
+}
+
 fn from_button_release(x_event: XEvent) -> DisplayEvent<XlibWindowHandle> {
     let xw = x_event.0;
+    let event = xlib::XButtonReleasedEvent::from(x_event.1);
     xw.set_mode(Mode::Normal);
-    DisplayEvent::ChangeToNormalMode
+    DisplayEvent::ChangeToNormalMode(event.x_root, event.y_root)
 }
@@ -1,15 +1,26 @@
 use crate::XlibWindowHandle;
 
 use super::{DisplayEvent, XWrap};
-use leftwm_core::models::WindowHandle;
-use leftwm_core::{models::WindowChange, Command};
+use leftwm_core::models::{Mode, WindowHandle};
+use leftwm_core::{models::WindowChange, Command, WindowSelector};
 use std::convert::TryFrom;
 use std::os::raw::c_long;
 
 use x11_dl::xlib;
 
+/// Source indication of `_NET_ACTIVE_WINDOW` requests sent by pagers and taskbars.
+const SOURCE_PAGER: c_long = 2;
+
+/// Directions of `_NET_WM_MOVERESIZE` requests, from 0 up to `SIZE_LEFT` the window is resized
+/// from an edge or corner.
+const MOVERESIZE_SIZE_LEFT: c_long = 7;
+const MOVERESIZE_MOVE: c_long = 8;
+const MOVERESIZE_SIZE_KEYBOARD: c_long = 9;
+const MOVERESIZE_MOVE_KEYBOARD: c_long = 10;
+const MOVERESIZE_CANCEL: c_long = 11;
+
 pub fn from_event(
-    xw: &XWrap,
+    xw: &mut XWrap,
     event: xlib::XClientMessageEvent,
 ) -> Option<DisplayEvent<XlibWindowHandle>> {
     if !xw.managed_windows.contains(&event.window) && event.window != xw.get_default_root() {
@@ -18,6 +29,21 @@ pub fn from_event(
     let atom_name = xw.atoms.get_name(event.message_type);
     tracing::trace!("ClientMessage: {} : {:?}", event.window, atom_name);
 
+    // Replies to `_NET_WM_PING`, sent back to the root window.
+    if event.message_type == xw.atoms.WMProtocols {
+        let window = event.data.get_long(2) as xlib::Window;
+        if event.data.get_long(0) != xw.atoms.NetWMPing as c_long || !xw.ping_watch.pong(&window) {
+            return None;
+        }
+        let handle = WindowHandle(XlibWindowHandle(window));
+        return Some(DisplayEvent::WindowResponding(handle, true));
+    }
+
+    if event.message_type == xw.atoms.NetCloseWindow {
+        xw.kill_window(&WindowHandle(XlibWindowHandle(event.window)));
+        return None;
+    }
+
     if event.message_type == xw.atoms.NetCurrentDesktop {
         let value = event.data.get_long(0);
         match usize::try_from(value) {
@@ -59,8 +85,30 @@ pub fn from_event(
         }
     }
     if event.message_type == xw.atoms.NetActiveWindow {
-        xw.set_window_urgency(event.window, true);
-        return None;
+        let requestor_focus = event.data.get_long(2) as xlib::Window;
+        // Applications may only move the focus away from their own focused window, other
+        // requests get the attention of the user instead of stealing the focus.
+        let granted = event.data.get_long(0) == SOURCE_PAGER
+            || (requestor_focus != 0 && requestor_focus == xw.focused_window);
+        if !granted {
+            xw.set_window_urgency(event.window, true);
+            return None;
+        }
+        let handle = WindowHandle(XlibWindowHandle(event.window));
+        let event =
+            DisplayEvent::SendCommand(Command::ActivateWindow(WindowSelector::Handle(handle)));
+        return Some(event);
+    }
+
+    if event.message_type == xw.atoms.NetWMMoveResize {
+        let handle = WindowHandle(XlibWindowHandle(event.window));
+        let mode = match event.data.get_long(2) {
+            MOVERESIZE_MOVE | MOVERESIZE_MOVE_KEYBOARD => Mode::ReadyToMove(handle),
+            0..=MOVERESIZE_SIZE_LEFT | MOVERESIZE_SIZE_KEYBOARD => Mode::ReadyToResize(handle),
+            MOVERESIZE_CANCEL => Mode::Normal,
+            _ => return None,
+        };
+        return Some(DisplayEvent::MoveResizeRequest(mode));
     }
 
     // if the client is trying to toggle fullscreen without changing the window state, change it too
@@ -6,7 +6,7 @@ use leftwm_core::models::{WindowChange, WindowHandle, WindowType, Xyhw};
 use x11_dl::xlib;
 
 pub fn from_event(
-    xw: &XWrap,
+    xw: &mut XWrap,
     event: xlib::XPropertyEvent,
 ) -> Option<DisplayEvent<XlibWindowHandle>> {
     if event.window == xw.get_default_root()
@@ -15,7 +15,44 @@ pub fn from_event(
     {
         return None;
     }
+    // Further changes shortly after this one are handled once the property settled.
+    if !xw.property_throttle.allow((event.window, event.atom)) {
+        return None;
+    }
+    translate(xw, event)
+}
 
+/// Translate the property changes held back by the throttle that are due, and request a wakeup
+/// for the ones still waiting.
+pub fn from_settled_changes(xw: &mut XWrap) -> Vec<DisplayEvent<XlibWindowHandle>> {
+    let mut events = vec![];
+    for (window, atom) in xw.property_throttle.take_due() {
+        if !xw.managed_windows.contains(&window) {
+            continue;
+        }
+        let event = xlib::XPropertyEvent {
+            type_: xlib::PropertyNotify,
+            serial: 0,
+            send_event: xlib::False,
+            display: xw.display,
+            window,
+            atom,
+            time: xlib::CurrentTime,
+            state: xlib::PropertyNewValue,
+        };
+        events.extend(translate(xw, event));
+    }
+    if let Some(delay) = xw.property_throttle.schedule_wakeup() {
+        let notify = xw.task_notify.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            notify.notify_one();
+        });
+    }
+    events
+}
+
+fn translate(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEvent<XlibWindowHandle>> {
     let event_name = xw.get_xatom_name(event.atom).ok()?;
     tracing::trace!("PropertyNotify: {} : {:?}", event_name, &event);
 
@@ -57,6 +94,19 @@ pub fn from_event(
                 }
             }
 
+            if event.atom == xw.atoms.MotifWMHints {
+                let mut change = WindowChange::new(WindowHandle(XlibWindowHandle(event.window)));
+                change.undecorated = Some(xw.get_window_undecorated(event.window));
+                return Some(DisplayEvent::WindowChange(change));
+            }
+
+            if event.atom == xw.atoms.WMColormapWindows {
+                if event.window == xw.focused_window {
+                    xw.install_colormaps(event.window);
+                }
+                return None;
+            }
+
             if event.atom == xw.atoms.NetWMState {
                 let handle = WindowHandle(XlibWindowHandle(event.window));
                 let mut change = WindowChange::new(handle);
@@ -9,6 +9,7 @@ mod event_translate_client_message;
 mod event_translate_property_notify;
 mod xatom;
 mod xcursor;
+mod xerror;
 mod xwrap;
 
 use serde::{Deserialize, Serialize};
@@ -19,10 +20,11 @@ use event_translate::XEvent;
 use futures::prelude::*;
 use leftwm_core::config::Config;
 use leftwm_core::models::{
-    Handle, Mode, Screen, TagId, Window, WindowHandle, WindowState, Workspace,
+    BorderColors, Handle, Mode, Screen, TagId, Window, WindowHandle, WindowState, Workspace,
 };
 use leftwm_core::utils;
 use leftwm_core::{DisplayAction, DisplayEvent, DisplayServer};
+use std::collections::BTreeMap;
 use std::pin::Pin;
 
 use x11_dl::xlib;
@@ -93,6 +95,15 @@ impl DisplayServer<XlibWindowHandle> for XlibDisplayServer {
             }
         }
 
+        if self.xw.mode == Mode::Normal {
+            events.append(&mut event_translate_property_notify::from_settled_changes(
+                &mut self.xw,
+            ));
+        }
+
+        events.extend(self.idle_event());
+        events.append(&mut self.unresponsive_events());
+
         for event in &events {
             if let DisplayEvent::WindowDestroy(WindowHandle(XlibWindowHandle(w))) = event {
                 self.xw.force_unmapped(*w);
@@ -110,6 +121,7 @@ impl DisplayServer<XlibWindowHandle> for XlibDisplayServer {
         let xw = &mut self.xw;
         let event: Option<DisplayEvent<XlibWindowHandle>> = match act {
             DisplayAction::KillWindow(h) => from_kill_window(xw, h),
+            DisplayAction::ForceKillWindow(h) => from_force_kill_window(xw, h),
             DisplayAction::AddedWindow(h, f, fm) => from_added_window(xw, h, f, fm),
             DisplayAction::MoveMouseOver(h, f) => from_move_mouse_over(xw, h, f),
             DisplayAction::MoveMouseOverPoint(p) => from_move_mouse_over_point(xw, p),
@@ -122,8 +134,12 @@ impl DisplayServer<XlibWindowHandle> for XlibDisplayServer {
             DisplayAction::ReadyToMoveWindow(h) => from_ready_to_move_window(xw, h),
             DisplayAction::ReadyToResizeWindow(h) => from_ready_to_resize_window(xw, h),
             DisplayAction::SetCurrentTags(t) => from_set_current_tags(xw, t),
+            DisplayAction::SetDesktopNames(labels) => from_set_desktop_names(xw, &labels),
             DisplayAction::SetWindowTag(h, t) => from_set_window_tag(xw, h, t),
             DisplayAction::ConfigureXlibWindow(w) => from_configure_xlib_window(xw, &w),
+            DisplayAction::SetWindowOpacity(h, o) => from_set_window_opacity(xw, h, o),
+            DisplayAction::SetWindowBorderColors(h, c) => from_set_window_border_colors(xw, h, &c),
+            DisplayAction::SetWindowUrgency(h, u) => from_set_window_urgency(xw, h, u),
 
             DisplayAction::WindowTakeFocus {
                 window,
@@ -132,6 +148,8 @@ impl DisplayServer<XlibWindowHandle> for XlibDisplayServer {
 
             DisplayAction::FocusWindowUnderCursor => from_focus_window_under_cursor(xw),
             DisplayAction::NormalMode => from_normal_mode(xw),
+            DisplayAction::ReacquireWmSelection => from_reacquire_wm_selection(xw),
+            DisplayAction::ReloadKeyGrabs => from_reload_key_grabs(xw),
         };
         if event.is_some() {
             tracing::trace!("DisplayEvent: {:?}", event);
@@ -150,6 +168,10 @@ impl DisplayServer<XlibWindowHandle> for XlibDisplayServer {
         self.xw.flush();
     }
 
+    fn error_counts(&self) -> BTreeMap<String, usize> {
+        xerror::counts()
+    }
+
     /// Creates a verify focus event for the cursors current window.
     fn generate_verify_focus_event(&self) -> Option<DisplayEvent<XlibWindowHandle>> {
         let handle = self.xw.get_cursor_window().ok()?;
@@ -158,6 +180,49 @@ impl DisplayServer<XlibWindowHandle> for XlibDisplayServer {
 }
 
 impl XlibDisplayServer {
+    /// Whether the user became idle or active again, waking up to look again later.
+    fn idle_event(&mut self) -> Option<DisplayEvent<XlibWindowHandle>> {
+        if !self.xw.idle_watch.is_enabled() || !self.xw.idle_watch.is_due() {
+            return None;
+        }
+        let idle_for = self.xw.get_idle_time()?;
+        let event = self.xw.idle_watch.update(idle_for).map(|idle| {
+            if idle {
+                DisplayEvent::UserIdle
+            } else {
+                DisplayEvent::UserActive
+            }
+        });
+        if let Some(delay) = self.xw.idle_watch.schedule_wakeup(idle_for) {
+            let notify = self.xw.task_notify.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                notify.notify_one();
+            });
+        }
+        event
+    }
+
+    /// The windows asked to close which did not answer a ping in time, waking up when the next
+    /// one is due.
+    fn unresponsive_events(&mut self) -> Vec<DisplayEvent<XlibWindowHandle>> {
+        let events = self
+            .xw
+            .ping_watch
+            .take_unresponsive()
+            .into_iter()
+            .map(|w| DisplayEvent::WindowResponding(WindowHandle(XlibWindowHandle(w)), false))
+            .collect();
+        if let Some(delay) = self.xw.ping_watch.schedule_wakeup() {
+            let notify = self.xw.task_notify.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                notify.notify_one();
+            });
+        }
+        events
+    }
+
     /// Return a vec of events for setting up state of WM.
     fn initial_events(&self, config: &impl Config) -> Vec<DisplayEvent<XlibWindowHandle>> {
         let mut events = vec![];
@@ -169,9 +234,7 @@ impl XlibDisplayServer {
                 // If there is a screen corresponding to the given output, create the workspace
                 match screens.iter().find(|i| i.output == wsc.output) {
                     Some(output_match) => {
-                        if wsc.relative.unwrap_or(false) {
-                            screen.bbox.add(output_match.bbox);
-                        }
+                        screen.bbox = wsc.bbox_on(output_match.bbox);
                         screen.id = Some(i + 1);
                     }
                     None => continue,
@@ -244,6 +307,14 @@ fn from_kill_window(
     None
 }
 
+fn from_force_kill_window(
+    xw: &mut XWrap,
+    handle: WindowHandle<XlibWindowHandle>,
+) -> Option<DisplayEvent<XlibWindowHandle>> {
+    xw.force_kill_window(&handle);
+    None
+}
+
 fn from_added_window(
     xw: &mut XWrap,
     handle: WindowHandle<XlibWindowHandle>,
@@ -341,7 +412,7 @@ fn from_set_window_order(
         .get_all_windows()
         .unwrap_or_default()
         .iter()
-        .filter(|&w| *w != xw.get_default_root())
+        .filter(|&w| *w != xw.get_default_root() && !xw.is_frame(*w))
         .map(|&w| WindowHandle(XlibWindowHandle(w)))
         .filter(|h| !windows.iter().any(|w| w == h))
         .collect();
@@ -401,6 +472,44 @@ fn from_configure_xlib_window(
     None
 }
 
+fn from_set_window_opacity(
+    xw: &mut XWrap,
+    handle: WindowHandle<XlibWindowHandle>,
+    opacity: Option<f32>,
+) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let WindowHandle(XlibWindowHandle(window)) = handle;
+    xw.set_window_opacity(window, opacity);
+    None
+}
+
+fn from_set_window_border_colors(
+    xw: &mut XWrap,
+    handle: WindowHandle<XlibWindowHandle>,
+    colors: &BorderColors,
+) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let WindowHandle(XlibWindowHandle(window)) = handle;
+    xw.set_window_border_colors(window, colors);
+    None
+}
+
+fn from_set_desktop_names(
+    xw: &mut XWrap,
+    labels: &[String],
+) -> Option<DisplayEvent<XlibWindowHandle>> {
+    xw.set_desktop_names(labels);
+    None
+}
+
+fn from_set_window_urgency(
+    xw: &mut XWrap,
+    handle: WindowHandle<XlibWindowHandle>,
+    urgent: bool,
+) -> Option<DisplayEvent<XlibWindowHandle>> {
+    let WindowHandle(XlibWindowHandle(window)) = handle;
+    xw.set_window_urgency(window, urgent);
+    None
+}
+
 fn from_window_take_focus(
     xw: &mut XWrap,
     window: &Window<XlibWindowHandle>,
@@ -426,3 +535,13 @@ fn from_normal_mode(xw: &mut XWrap) -> Option<DisplayEvent<XlibWindowHandle>> {
     xw.set_mode(Mode::Normal);
     None
 }
+
+fn from_reacquire_wm_selection(xw: &mut XWrap) -> Option<DisplayEvent<XlibWindowHandle>> {
+    xw.reacquire_wm_selection();
+    None
+}
+
+fn from_reload_key_grabs(xw: &mut XWrap) -> Option<DisplayEvent<XlibWindowHandle>> {
+    xw.regrab_mouse_clicks();
+    None
+}
@@ -1,3 +1,4 @@
+use leftwm_core::utils::text_property::TextEncoding;
 use std::ffi::CString;
 use x11_dl::xlib;
 
@@ -12,6 +13,8 @@ pub struct XAtom {
     pub WMState: xlib::Atom,
     pub WMClass: xlib::Atom,
     pub WMTakeFocus: xlib::Atom,
+    pub WMColormapWindows: xlib::Atom,
+    pub MotifWMHints: xlib::Atom,
     pub NetActiveWindow: xlib::Atom,
     pub NetSupported: xlib::Atom,
     pub NetWMName: xlib::Atom,
@@ -51,6 +54,10 @@ pub struct XAtom {
     pub NetWMWindowTypeUtility: xlib::Atom,
     pub NetWMWindowTypeSplash: xlib::Atom,
     pub NetWMWindowTypeDialog: xlib::Atom,
+    pub NetWMWindowTypeNotification: xlib::Atom,
+    pub NetWMWindowTypePopupMenu: xlib::Atom,
+    pub NetWMWindowTypeDropdownMenu: xlib::Atom,
+    pub NetWMWindowTypeTooltip: xlib::Atom,
 
     pub NetSupportingWmCheck: xlib::Atom,
     pub NetClientList: xlib::Atom,
@@ -59,10 +66,26 @@ pub struct XAtom {
     pub NetCurrentDesktop: xlib::Atom,
     pub NetDesktopNames: xlib::Atom,
     pub NetWMDesktop: xlib::Atom,
+    pub NetWMMoveResize: xlib::Atom,
+    pub NetCloseWindow: xlib::Atom,
+    pub NetWMPing: xlib::Atom,
     pub NetWMStrutPartial: xlib::Atom, // net version - Reserve Screen Space
     pub NetWMStrut: xlib::Atom,        // old version
+    pub NetWMWindowOpacity: xlib::Atom,
 
     pub UTF8String: xlib::Atom,
+    pub CompoundText: xlib::Atom,
+
+    pub Clipboard: xlib::Atom,
+    pub ClipboardManager: xlib::Atom,
+    pub Manager: xlib::Atom,
+    pub SaveTargets: xlib::Atom,
+    pub Targets: xlib::Atom,
+    pub Multiple: xlib::Atom,
+    pub Timestamp: xlib::Atom,
+    pub Delete: xlib::Atom,
+    pub Incr: xlib::Atom,
+    pub LeftwmClipboard: xlib::Atom,
 }
 
 impl XAtom {
@@ -104,6 +127,10 @@ impl XAtom {
             self.NetWMWindowTypeUtility,
             self.NetWMWindowTypeSplash,
             self.NetWMWindowTypeDialog,
+            self.NetWMWindowTypeNotification,
+            self.NetWMWindowTypePopupMenu,
+            self.NetWMWindowTypeDropdownMenu,
+            self.NetWMWindowTypeTooltip,
             self.NetSupportingWmCheck,
             self.NetClientList,
             self.NetDesktopViewport,
@@ -111,6 +138,9 @@ impl XAtom {
             self.NetCurrentDesktop,
             self.NetDesktopNames,
             self.NetWMDesktop,
+            self.NetWMMoveResize,
+            self.NetCloseWindow,
+            self.NetWMPing,
             self.NetWMStrutPartial,
             self.NetWMStrut,
         ]
@@ -123,6 +153,7 @@ impl XAtom {
             a if a == self.WMState => "WM_STATE",
             a if a == self.WMClass => "WM_CLASS",
             a if a == self.WMTakeFocus => "WM_TAKE_FOCUS",
+            a if a == self.MotifWMHints => "_MOTIF_WM_HINTS",
             a if a == self.NetActiveWindow => "_NET_ACTIVE_WINDOW",
             a if a == self.NetSupported => "_NET_SUPPORTED",
             a if a == self.NetWMName => "_NET_WM_NAME",
@@ -162,14 +193,38 @@ impl XAtom {
             a if a == self.NetCurrentDesktop => "_NET_CURRENT_DESKTOP",
             a if a == self.NetDesktopNames => "_NET_DESKTOP_NAMES",
             a if a == self.NetWMDesktop => "_NET_WM_DESKTOP",
+            a if a == self.NetWMMoveResize => "_NET_WM_MOVERESIZE",
+            a if a == self.NetCloseWindow => "_NET_CLOSE_WINDOW",
+            a if a == self.NetWMPing => "_NET_WM_PING",
             a if a == self.NetWMStrutPartial => "_NET_WM_STRUT_PARTIAL",
             a if a == self.NetWMStrut => "_NET_WM_STRUT",
+            a if a == self.NetWMWindowOpacity => "_NET_WM_WINDOW_OPACITY",
 
             a if a == self.UTF8String => "UTF8_STRING",
+            a if a == self.CompoundText => "COMPOUND_TEXT",
+            a if a == self.Clipboard => "CLIPBOARD",
+            a if a == self.ClipboardManager => "CLIPBOARD_MANAGER",
+            a if a == self.Manager => "MANAGER",
+            a if a == self.SaveTargets => "SAVE_TARGETS",
+            a if a == self.Targets => "TARGETS",
+            a if a == self.Multiple => "MULTIPLE",
+            a if a == self.Timestamp => "TIMESTAMP",
+            a if a == self.Delete => "DELETE",
+            a if a == self.Incr => "INCR",
+            a if a == self.LeftwmClipboard => "_LEFTWM_CLIPBOARD",
             _ => "(UNKNOWN)",
         }
     }
 
+    /// The encoding of a text property of the given type, UTF-8 if unknown.
+    pub fn text_encoding(&self, r#type: xlib::Atom) -> TextEncoding {
+        match r#type {
+            a if a == self.CompoundText => TextEncoding::CompoundText,
+            xlib::XA_STRING => TextEncoding::Latin1,
+            _ => TextEncoding::Utf8,
+        }
+    }
+
     pub fn new(xlib: &xlib::Xlib, dpy: *mut xlib::Display) -> Self {
         Self {
             WMProtocols: from(xlib, dpy, "WM_PROTOCOLS"),
@@ -177,6 +232,8 @@ impl XAtom {
             WMState: from(xlib, dpy, "WM_STATE"),
             WMClass: from(xlib, dpy, "WM_CLASS"),
             WMTakeFocus: from(xlib, dpy, "WM_TAKE_FOCUS"),
+            WMColormapWindows: from(xlib, dpy, "WM_COLORMAP_WINDOWS"),
+            MotifWMHints: from(xlib, dpy, "_MOTIF_WM_HINTS"),
             NetActiveWindow: from(xlib, dpy, "_NET_ACTIVE_WINDOW"),
             NetSupported: from(xlib, dpy, "_NET_SUPPORTED"),
             NetWMName: from(xlib, dpy, "_NET_WM_NAME"),
@@ -216,6 +273,10 @@ impl XAtom {
             NetWMWindowTypeUtility: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_UTILITY"),
             NetWMWindowTypeSplash: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_SPLASH"),
             NetWMWindowTypeDialog: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_DIALOG"),
+            NetWMWindowTypeNotification: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_NOTIFICATION"),
+            NetWMWindowTypePopupMenu: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_POPUP_MENU"),
+            NetWMWindowTypeDropdownMenu: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_DROPDOWN_MENU"),
+            NetWMWindowTypeTooltip: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_TOOLTIP"),
             NetSupportingWmCheck: from(xlib, dpy, "_NET_SUPPORTING_WM_CHECK"),
 
             NetClientList: from(xlib, dpy, "_NET_CLIENT_LIST"),
@@ -224,10 +285,26 @@ impl XAtom {
             NetCurrentDesktop: from(xlib, dpy, "_NET_CURRENT_DESKTOP"),
             NetDesktopNames: from(xlib, dpy, "_NET_DESKTOP_NAMES"),
             NetWMDesktop: from(xlib, dpy, "_NET_WM_DESKTOP"),
+            NetWMMoveResize: from(xlib, dpy, "_NET_WM_MOVERESIZE"),
+            NetCloseWindow: from(xlib, dpy, "_NET_CLOSE_WINDOW"),
+            NetWMPing: from(xlib, dpy, "_NET_WM_PING"),
             NetWMStrutPartial: from(xlib, dpy, "_NET_WM_STRUT_PARTIAL"),
             NetWMStrut: from(xlib, dpy, "_NET_WM_STRUT"),
+            NetWMWindowOpacity: from(xlib, dpy, "_NET_WM_WINDOW_OPACITY"),
 
             UTF8String: from(xlib, dpy, "UTF8_STRING"),
+            CompoundText: from(xlib, dpy, "COMPOUND_TEXT"),
+
+            Clipboard: from(xlib, dpy, "CLIPBOARD"),
+            ClipboardManager: from(xlib, dpy, "CLIPBOARD_MANAGER"),
+            Manager: from(xlib, dpy, "MANAGER"),
+            SaveTargets: from(xlib, dpy, "SAVE_TARGETS"),
+            Targets: from(xlib, dpy, "TARGETS"),
+            Multiple: from(xlib, dpy, "MULTIPLE"),
+            Timestamp: from(xlib, dpy, "TIMESTAMP"),
+            Delete: from(xlib, dpy, "DELETE"),
+            Incr: from(xlib, dpy, "INCR"),
+            LeftwmClipboard: from(xlib, dpy, "_LEFTWM_CLIPBOARD"),
         }
     }
 }
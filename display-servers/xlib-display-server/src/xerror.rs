@@ -0,0 +1,213 @@
+//! Decoding and counting of the protocol errors reported by the X server.
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use x11_dl::xlib;
+
+/// Number of protocol errors received so far, by error name.
+///
+/// Xlib reports errors through a C callback without any user data, hence the global.
+static ERROR_COUNTS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+/// Log a protocol error with the names of the error and the request that caused it, and count it.
+///
+/// `expected` errors, like requests on windows that were destroyed in the meantime, are only
+/// logged at debug level.
+pub fn report(err: &xlib::XErrorEvent, expected: bool) {
+    let error = error_name(err.error_code);
+    let request = request_name(err.request_code);
+    let opcode = format!("{}.{}", err.request_code, err.minor_code);
+    if expected {
+        tracing::debug!(
+            window = err.resourceid,
+            "X11 error {} in request {} ({}) for resource {:#x}, it is probably already destroyed",
+            error,
+            request,
+            opcode,
+            err.resourceid
+        );
+    } else {
+        tracing::warn!(
+            serial = err.serial,
+            "X11 error {} in request {} ({}) for resource {:#x}",
+            error,
+            request,
+            opcode,
+            err.resourceid
+        );
+    }
+    if let Ok(mut counts) = ERROR_COUNTS.lock() {
+        *counts.entry(error.to_string()).or_default() += 1;
+    }
+}
+
+/// Number of protocol errors received so far, by error name.
+pub fn counts() -> BTreeMap<String, usize> {
+    ERROR_COUNTS
+        .lock()
+        .map(|counts| counts.clone())
+        .unwrap_or_default()
+}
+
+/// The name of a core protocol error, as used by the x11rb backend.
+const fn error_name(code: u8) -> &'static str {
+    match code {
+        xlib::BadRequest => "Request",
+        xlib::BadValue => "Value",
+        xlib::BadWindow => "Window",
+        xlib::BadPixmap => "Pixmap",
+        xlib::BadAtom => "Atom",
+        xlib::BadCursor => "Cursor",
+        xlib::BadFont => "Font",
+        xlib::BadMatch => "Match",
+        xlib::BadDrawable => "Drawable",
+        xlib::BadAccess => "Access",
+        xlib::BadAlloc => "Alloc",
+        xlib::BadColor => "Colormap",
+        xlib::BadGC => "GContext",
+        xlib::BadIDChoice => "IDChoice",
+        xlib::BadName => "Name",
+        xlib::BadLength => "Length",
+        xlib::BadImplementation => "Implementation",
+        _ => "Unknown",
+    }
+}
+
+/// The name of a core protocol request, see `X11/Xproto.h`.
+const fn request_name(code: u8) -> &'static str {
+    const NAMES: [&str; 128] = [
+        "UnknownRequest",
+        "CreateWindow",
+        "ChangeWindowAttributes",
+        "GetWindowAttributes",
+        "DestroyWindow",
+        "DestroySubwindows",
+        "ChangeSaveSet",
+        "ReparentWindow",
+        "MapWindow",
+        "MapSubwindows",
+        "UnmapWindow",
+        "UnmapSubwindows",
+        "ConfigureWindow",
+        "CirculateWindow",
+        "GetGeometry",
+        "QueryTree",
+        "InternAtom",
+        "GetAtomName",
+        "ChangeProperty",
+        "DeleteProperty",
+        "GetProperty",
+        "ListProperties",
+        "SetSelectionOwner",
+        "GetSelectionOwner",
+        "ConvertSelection",
+        "SendEvent",
+        "GrabPointer",
+        "UngrabPointer",
+        "GrabButton",
+        "UngrabButton",
+        "ChangeActivePointerGrab",
+        "GrabKeyboard",
+        "UngrabKeyboard",
+        "GrabKey",
+        "UngrabKey",
+        "AllowEvents",
+        "GrabServer",
+        "UngrabServer",
+        "QueryPointer",
+        "GetMotionEvents",
+        "TranslateCoords",
+        "WarpPointer",
+        "SetInputFocus",
+        "GetInputFocus",
+        "QueryKeymap",
+        "OpenFont",
+        "CloseFont",
+        "QueryFont",
+        "QueryTextExtents",
+        "ListFonts",
+        "ListFontsWithInfo",
+        "SetFontPath",
+        "GetFontPath",
+        "CreatePixmap",
+        "FreePixmap",
+        "CreateGC",
+        "ChangeGC",
+        "CopyGC",
+        "SetDashes",
+        "SetClipRectangles",
+        "FreeGC",
+        "ClearArea",
+        "CopyArea",
+        "CopyPlane",
+        "PolyPoint",
+        "PolyLine",
+        "PolySegment",
+        "PolyRectangle",
+        "PolyArc",
+        "FillPoly",
+        "PolyFillRectangle",
+        "PolyFillArc",
+        "PutImage",
+        "GetImage",
+        "PolyText8",
+        "PolyText16",
+        "ImageText8",
+        "ImageText16",
+        "CreateColormap",
+        "FreeColormap",
+        "CopyColormapAndFree",
+        "InstallColormap",
+        "UninstallColormap",
+        "ListInstalledColormaps",
+        "AllocColor",
+        "AllocNamedColor",
+        "AllocColorCells",
+        "AllocColorPlanes",
+        "FreeColors",
+        "StoreColors",
+        "StoreNamedColor",
+        "QueryColors",
+        "LookupColor",
+        "CreateCursor",
+        "CreateGlyphCursor",
+        "FreeCursor",
+        "RecolorCursor",
+        "QueryBestSize",
+        "QueryExtension",
+        "ListExtensions",
+        "ChangeKeyboardMapping",
+        "GetKeyboardMapping",
+        "ChangeKeyboardControl",
+        "GetKeyboardControl",
+        "Bell",
+        "ChangePointerControl",
+        "GetPointerControl",
+        "SetScreenSaver",
+        "GetScreenSaver",
+        "ChangeHosts",
+        "ListHosts",
+        "SetAccessControl",
+        "SetCloseDownMode",
+        "KillClient",
+        "RotateProperties",
+        "ForceScreenSaver",
+        "SetPointerMapping",
+        "GetPointerMapping",
+        "SetModifierMapping",
+        "GetModifierMapping",
+        "UnknownRequest",
+        "UnknownRequest",
+        "UnknownRequest",
+        "UnknownRequest",
+        "UnknownRequest",
+        "UnknownRequest",
+        "UnknownRequest",
+        "NoOperation",
+    ];
+    if code < 128 {
+        NAMES[code as usize]
+    } else {
+        "ExtensionRequest"
+    }
+}
@@ -12,18 +12,32 @@ use super::xatom::XAtom;
 use super::xcursor::XCursor;
 use super::{utils, Screen, Window, WindowHandle};
 use leftwm_core::config::Config;
-use leftwm_core::models::{FocusBehaviour, Mode};
+use leftwm_core::models::{BorderColors, FocusBehaviour, Mode};
+use leftwm_core::utils::clipboard_manager::ClipboardStore;
+use leftwm_core::utils::color::{Rgba, VisualFormat};
+use leftwm_core::utils::focus_stealing::FocusLoopDetector;
+use leftwm_core::utils::idle_watch::IdleWatch;
 use leftwm_core::utils::modmask_lookup::ModMask;
+use leftwm_core::utils::ping_watch::PingWatch;
+use leftwm_core::utils::property_throttle::PropertyThrottle;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::CString;
-use std::os::raw::{c_char, c_double, c_int, c_long, c_short, c_ulong};
+use std::os::raw::{c_double, c_int, c_long, c_short, c_ulong};
 use std::sync::Arc;
+use std::time::Instant;
 use std::{ptr, slice};
 use tokio::sync::{oneshot, Notify};
 use tokio::time::Duration;
 
 use x11_dl::xlib;
 use x11_dl::xrandr::Xrandr;
+use x11_dl::xss::Xss;
 
+use frame::{Frame, TitleBarStyle};
+
+mod clipboard;
+mod frame;
 mod getters;
 mod mouse;
 mod setters;
@@ -35,6 +49,9 @@ pub const NORMAL_STATE: WindowStateConst = 1;
 pub const ICONIC_STATE: WindowStateConst = 2;
 const MAX_PROPERTY_VALUE_LEN: c_long = 4096;
 
+/// How long the window manager being replaced is given to step down.
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(3);
+
 pub const ROOT_EVENT_MASK: c_long = xlib::SubstructureRedirectMask
     | xlib::SubstructureNotifyMask
     | xlib::ButtonPressMask
@@ -75,8 +92,10 @@ pub extern "C" fn on_error_from_xlib(_: *mut xlib::Display, er: *mut xlib::XErro
         || (rc == X_POLYFILLRECTANGLE && bd)
         || (rc == X_POLYTEXT8 && bd)
     {
+        crate::xerror::report(&err, true);
         return 0;
     }
+    crate::xerror::report(&err, false);
     1
 }
 
@@ -87,11 +106,23 @@ pub extern "C" fn on_error_from_xlib_dummy(
     1
 }
 
+#[derive(Clone, Copy)]
 pub struct Colors {
-    normal: c_ulong,
-    floating: c_ulong,
-    active: c_ulong,
-    background: c_ulong,
+    normal: Rgba,
+    floating: Rgba,
+    active: Rgba,
+    selected: Rgba,
+    urgent: Rgba,
+    background: Rgba,
+}
+
+/// Border colors of a window replacing the configured ones, from the overrides of its tag.
+#[derive(Default)]
+struct WindowColors {
+    normal: Option<Rgba>,
+    floating: Option<Rgba>,
+    active: Option<Rgba>,
+    selected: Option<Rgba>,
 }
 
 #[derive(Debug, Clone)]
@@ -104,17 +135,49 @@ pub enum XlibError {
 /// Contains Xserver information and origins.
 pub struct XWrap {
     xlib: xlib::Xlib,
-    display: *mut xlib::Display,
+    pub(crate) display: *mut xlib::Display,
     root: xlib::Window,
     pub atoms: XAtom,
     cursors: XCursor,
     colors: Colors,
+    window_colors: HashMap<xlib::Window, WindowColors>,
+    /// The pixel layout of the visuals of managed windows, their borders are drawn in it.
+    visuals: HashMap<xlib::Window, VisualFormat>,
     pub managed_windows: Vec<xlib::Window>,
     pub focused_window: xlib::Window,
     pub tag_labels: Vec<String>,
     pub mode: Mode<XlibWindowHandle>,
     pub focus_behaviour: FocusBehaviour,
-    pub mouse_key_mask: ModMask,
+    pub focus_loop: FocusLoopDetector<xlib::Window>,
+    pub property_throttle: PropertyThrottle<(xlib::Window, xlib::Atom)>,
+    pub mark_focus_stealers_never_focus: bool,
+    /// Whether windows managed from now on are wrapped in a frame.
+    pub reparent_windows: bool,
+    /// The frames wrapping managed windows, by window.
+    frames: HashMap<xlib::Window, Frame>,
+    /// The border color last set on the top level window of managed windows.
+    border_colors: RefCell<HashMap<xlib::Window, Rgba>>,
+    /// The `WM_STATE` last set on managed windows, so updating them needs no round trip to read it.
+    wm_states: RefCell<HashMap<xlib::Window, c_long>>,
+    /// How title bars are drawn on frames, when they have one.
+    title_bar: Option<TitleBarStyle>,
+    pub idle_watch: IdleWatch,
+    /// The `_NET_WM_WINDOW_OPACITY` of dimmed windows from before they were dimmed.
+    own_opacities: HashMap<xlib::Window, Option<u32>>,
+    /// The windows asked to close which did not answer `_NET_WM_PING` yet.
+    pub ping_watch: PingWatch<xlib::Window>,
+    /// The screen saver extension, loaded once idle detection is enabled.
+    xss: Option<Xss>,
+    /// The `WM_Sn` manager selection of the screen and the window owning it.
+    pub wm_selection: xlib::Atom,
+    wm_selection_owner: xlib::Window,
+    /// The clipboard saved when the window owning it exited.
+    clipboard: ClipboardStore<xlib::Atom, xlib::Window>,
+    /// The modifiers and buttons of the mousebinds, grabbed on windows.
+    pub mousebinds: Vec<(ModMask, u8)>,
+    /// The modifiers of the lock keys, which mousebinds work with and without: `Lock` and those
+    /// `Num_Lock` and `Scroll_Lock` are on.
+    lock_mask: u16,
     pub mode_origin: (i32, i32),
     _task_guard: oneshot::Receiver<()>,
     pub task_notify: Arc<Notify>,
@@ -182,11 +245,14 @@ impl XWrap {
         let cursors = XCursor::new(&xlib, display);
         let root = unsafe { (xlib.XDefaultRootWindow)(display) };
 
+        let black = Rgba::opaque(0, 0, 0);
         let colors = Colors {
-            normal: 0,
-            floating: 0,
-            active: 0,
-            background: 0,
+            normal: black,
+            floating: black,
+            active: black,
+            selected: black,
+            urgent: black,
+            background: black,
         };
 
         let refresh_rate = match Xrandr::open() {
@@ -223,19 +289,37 @@ impl XWrap {
 
         tracing::debug!("Refresh Rate: {}", refresh_rate);
 
-        let xw = Self {
+        let mut xw = Self {
             xlib,
             display,
             root,
             atoms,
             cursors,
             colors,
+            window_colors: HashMap::new(),
+            visuals: HashMap::new(),
             managed_windows: vec![],
             focused_window: root,
             tag_labels: vec![],
             mode: Mode::Normal,
             focus_behaviour: FocusBehaviour::Sloppy,
-            mouse_key_mask: ModMask::Zero,
+            focus_loop: FocusLoopDetector::default(),
+            property_throttle: PropertyThrottle::default(),
+            mark_focus_stealers_never_focus: false,
+            reparent_windows: false,
+            frames: HashMap::new(),
+            border_colors: RefCell::new(HashMap::new()),
+            wm_states: RefCell::new(HashMap::new()),
+            title_bar: None,
+            idle_watch: IdleWatch::default(),
+            own_opacities: HashMap::new(),
+            ping_watch: PingWatch::default(),
+            xss: None,
+            wm_selection: 0,
+            wm_selection_owner: 0,
+            clipboard: ClipboardStore::default(),
+            mousebinds: vec![],
+            lock_mask: (xlib::LockMask | xlib::Mod2Mask) as u16,
             mode_origin: (0, 0),
             _task_guard,
             task_notify,
@@ -243,6 +327,10 @@ impl XWrap {
             refresh_rate,
         };
 
+        // The window manager being replaced has to let go of the root window first.
+        unsafe { (xw.xlib.XSetErrorHandler)(Some(on_error_from_xlib)) };
+        xw.acquire_wm_selection();
+
         // Check that another WM is not running.
         extern "C" fn startup_check_for_other_wm(
             _: *mut xlib::Display,
@@ -264,14 +352,37 @@ impl XWrap {
 
     pub fn load_config(&mut self, config: &impl Config) {
         self.focus_behaviour = config.focus_behaviour();
-        self.mouse_key_mask = utils::modmask_lookup::into_modmask(&config.mousekey());
+        self.mark_focus_stealers_never_focus = config.mark_focus_stealers_never_focus();
+        self.reparent_windows = config.reparent_windows();
+        self.mousebinds = config
+            .mousebinds()
+            .into_iter()
+            .map(|bind| {
+                (
+                    utils::modmask_lookup::into_modmask(&bind.modifier),
+                    bind.button.into(),
+                )
+            })
+            // Button 0 would grab any button.
+            .filter(|(_, button)| *button != 0)
+            .collect();
         self.tag_labels = config.create_list_of_tag_labels();
+        self.idle_watch = IdleWatch::new(config.idle().timeout());
+        if self.idle_watch.is_enabled() && self.xss.is_none() {
+            match Xss::open() {
+                Ok(xss) => self.xss = Some(xss),
+                Err(err) => tracing::warn!("Idle detection is unavailable: {}", err),
+            }
+        }
         self.colors = Colors {
-            normal: self.get_color(config.default_border_color()),
-            floating: self.get_color(config.floating_border_color()),
-            active: self.get_color(config.focused_border_color()),
-            background: self.get_color(config.background_color()),
+            normal: self.parse_color(&config.default_border_color()),
+            floating: self.parse_color(&config.floating_border_color()),
+            active: self.parse_color(&config.focused_border_color()),
+            selected: self.parse_color(&config.selected_border_color()),
+            urgent: self.parse_color(&config.urgent_border_color()),
+            background: self.parse_color(&config.background_color()),
         };
+        self.load_title_bar(config.title_bar().filter(|_| self.reparent_windows));
     }
 
     /// Initialize the xwrapper.
@@ -313,47 +424,141 @@ impl XWrap {
         // EWMH compliance for desktops.
         self.init_desktops_hints();
 
+        self.acquire_clipboard_manager();
+        self.lock_mask = self.get_lock_mask();
+
         self.sync();
     }
 
+    /// Take the `WM_Sn` manager selection of the screen (ICCCM 2.8), waiting for the window
+    /// manager owning it to step down. A window manager started later takes it over to ask
+    /// leftwm to step down, which is reported by a `SelectionClear`.
+    // `XInternAtom`: https://tronche.com/gui/x/xlib/window-information/XInternAtom.html
+    // `XCreateSimpleWindow`: https://tronche.com/gui/x/xlib/window/XCreateSimpleWindow.html
+    // `XGetSelectionOwner`: https://tronche.com/gui/x/xlib/window-information/XGetSelectionOwner.html
+    pub fn acquire_wm_selection(&mut self) {
+        let previous = unsafe {
+            if self.wm_selection_owner == 0 {
+                let screen = (self.xlib.XDefaultScreen)(self.display);
+                let name = CString::new(format!("WM_S{screen}")).unwrap_or_default();
+                self.wm_selection =
+                    (self.xlib.XInternAtom)(self.display, name.as_ptr(), xlib::False);
+                self.wm_selection_owner =
+                    (self.xlib.XCreateSimpleWindow)(self.display, self.root, -1, -1, 1, 1, 0, 0, 0);
+            }
+            (self.xlib.XGetSelectionOwner)(self.display, self.wm_selection)
+        };
+        if previous != 0 {
+            tracing::info!("Another window manager is running, asking it to step down");
+            // Be told when the previous owner is gone.
+            self.subscribe_to_event(previous, xlib::StructureNotifyMask);
+        }
+        let time = self.server_time();
+        if !self.take_manager_selection(self.wm_selection, time) {
+            tracing::error!("Unable to take the window manager selection");
+            return;
+        }
+        if previous != 0
+            && self
+                .wait_for_event(previous, xlib::DestroyNotify, REPLACE_TIMEOUT)
+                .is_none()
+        {
+            tracing::warn!("The previous window manager did not step down in time");
+        }
+    }
+
+    /// Take the `WM_Sn` selection back from a window manager which just took it, without
+    /// waiting for it to step down.
+    pub fn reacquire_wm_selection(&mut self) {
+        let time = self.server_time();
+        if !self.take_manager_selection(self.wm_selection, time) {
+            tracing::error!("Unable to take the window manager selection back");
+        }
+    }
+
+    /// Take a manager selection with the window owning `WM_Sn` and announce it with a `MANAGER`
+    /// client message on the root window (ICCCM 2.8). Returns whether the selection is owned.
+    // `XSetSelectionOwner`: https://tronche.com/gui/x/xlib/window-information/XSetSelectionOwner.html
+    // `XGetSelectionOwner`: https://tronche.com/gui/x/xlib/window-information/XGetSelectionOwner.html
+    fn take_manager_selection(&self, selection: xlib::Atom, time: xlib::Time) -> bool {
+        let owner = self.wm_selection_owner;
+        unsafe {
+            (self.xlib.XSetSelectionOwner)(self.display, selection, owner, time);
+            if (self.xlib.XGetSelectionOwner)(self.display, selection) != owner {
+                return false;
+            }
+        }
+        let mut msg: xlib::XClientMessageEvent = unsafe { std::mem::zeroed() };
+        msg.type_ = xlib::ClientMessage;
+        msg.window = self.root;
+        msg.message_type = self.atoms.Manager;
+        msg.format = 32;
+        msg.data.set_long(0, time as c_long);
+        msg.data.set_long(1, selection as c_long);
+        msg.data.set_long(2, owner as c_long);
+        let mut ev: xlib::XEvent = msg.into();
+        self.send_xevent(self.root, 0, xlib::StructureNotifyMask, &mut ev);
+        true
+    }
+
+    /// Returns the current server time, read from the `PropertyNotify` of a zero-length change
+    /// to a property of the window owning `WM_Sn`.
+    // `XChangeProperty`: https://tronche.com/gui/x/xlib/window-information/XChangeProperty.html
+    fn server_time(&self) -> xlib::Time {
+        let owner = self.wm_selection_owner;
+        self.subscribe_to_event(owner, xlib::PropertyChangeMask);
+        unsafe {
+            (self.xlib.XChangeProperty)(
+                self.display,
+                owner,
+                self.wm_selection,
+                xlib::XA_STRING,
+                8,
+                xlib::PropModeAppend,
+                ptr::null(),
+                0,
+            );
+        }
+        let notify = self.wait_for_event(owner, xlib::PropertyNotify, REPLACE_TIMEOUT);
+        self.subscribe_to_event(owner, xlib::NoEventMask);
+        notify.map_or(xlib::CurrentTime, |event| {
+            xlib::XPropertyEvent::from(event).time
+        })
+    }
+
+    /// Waits up to `timeout` for an event of the given type on a window. The other events are
+    /// left queued for the event loop.
+    // `XCheckTypedWindowEvent`: https://tronche.com/gui/x/xlib/event-handling/manipulating-event-queue/XCheckTypedWindowEvent.html
+    fn wait_for_event(
+        &self,
+        window: xlib::Window,
+        event_type: c_int,
+        timeout: Duration,
+    ) -> Option<xlib::XEvent> {
+        self.flush();
+        let started = Instant::now();
+        loop {
+            let mut event: xlib::XEvent = unsafe { std::mem::zeroed() };
+            let found = unsafe {
+                (self.xlib.XCheckTypedWindowEvent)(self.display, window, event_type, &mut event)
+            };
+            if found != 0 {
+                return Some(event);
+            }
+            if started.elapsed() >= timeout {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
     /// EWMH support used for bars such as polybar.
-    ///  # Panics
-    ///
-    ///  Panics if a new Cstring cannot be formed
-    // `Xutf8TextListToTextProperty`: https://linux.die.net/man/3/xutf8textlisttotextproperty
-    // `XSetTextProperty`: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/XSetTextProperty.html
     pub fn init_desktops_hints(&self) {
-        let tag_labels = &self.tag_labels;
-        let tag_length = tag_labels.len();
-        // Set the number of desktop.
-        let data = vec![tag_length as u32];
-        self.set_desktop_prop(&data, self.atoms.NetNumberOfDesktops);
+        // Set the number and names of the desktops.
+        self.set_desktop_names(&self.tag_labels);
         // Set a current desktop.
         let data = vec![0_u32, xlib::CurrentTime as u32];
         self.set_desktop_prop(&data, self.atoms.NetCurrentDesktop);
-        // Set desktop names.
-        let mut text: xlib::XTextProperty = unsafe { std::mem::zeroed() };
-        unsafe {
-            let mut clist_tags: Vec<*mut c_char> = tag_labels
-                .iter()
-                .map(|x| CString::new(x.clone()).unwrap_or_default().into_raw())
-                .collect();
-            let ptr = clist_tags.as_mut_ptr();
-            (self.xlib.Xutf8TextListToTextProperty)(
-                self.display,
-                ptr,
-                clist_tags.len() as i32,
-                xlib::XUTF8StringStyle,
-                &mut text,
-            );
-            std::mem::forget(clist_tags);
-            (self.xlib.XSetTextProperty)(
-                self.display,
-                self.root,
-                &mut text,
-                self.atoms.NetDesktopNames,
-            );
-        }
 
         // Set the WM NAME.
         self.set_desktop_prop_string("LeftWM", self.atoms.NetWMName, self.atoms.UTF8String);
@@ -382,6 +587,8 @@ impl XWrap {
             msg.format = 32;
             msg.data.set_long(0, atom as c_long);
             msg.data.set_long(1, xlib::CurrentTime as c_long);
+            // `_NET_WM_PING` replies tell which window they are about.
+            msg.data.set_long(2, window as c_long);
             let mut ev: xlib::XEvent = msg.into();
             self.send_xevent(window, 0, xlib::NoEventMask, &mut ev);
             return true;
@@ -423,16 +630,57 @@ impl XWrap {
     ) {
         for window in windows {
             let WindowHandle(XlibWindowHandle(handle)) = window.handle;
-            let color: c_ulong = if focused == Some(window.handle) {
-                self.colors.active
-            } else if window.floating() {
-                self.colors.floating
+            let color = if focused == Some(window.handle) {
+                self.colors_of(handle).active
             } else {
-                self.colors.normal
+                self.unfocused_border_color(window)
             };
             self.set_window_border_color(handle, color);
         }
-        self.set_background_color(self.colors.background);
+        self.set_background_color(self.get_pixel(self.root, self.colors.background));
+    }
+
+    /// Border color of a window that does not have focus.
+    pub fn unfocused_border_color(&self, window: &Window<XlibWindowHandle>) -> Rgba {
+        let WindowHandle(XlibWindowHandle(handle)) = window.handle;
+        let colors = self.colors_of(handle);
+        if window.shows_urgency() {
+            colors.urgent
+        } else if window.selected {
+            colors.selected
+        } else if window.floating() {
+            colors.floating
+        } else {
+            colors.normal
+        }
+    }
+
+    /// Border colors of a window, the configured ones unless replaced for the window.
+    pub fn colors_of(&self, window: xlib::Window) -> Colors {
+        let mut colors = self.colors;
+        if let Some(custom) = self.window_colors.get(&window) {
+            colors.normal = custom.normal.unwrap_or(colors.normal);
+            colors.floating = custom.floating.unwrap_or(colors.floating);
+            colors.active = custom.active.unwrap_or(colors.active);
+            colors.selected = custom.selected.unwrap_or(colors.selected);
+        }
+        colors
+    }
+
+    /// Replace the configured border colors of a window, or go back to them when none are set.
+    pub fn set_window_border_colors(&mut self, window: xlib::Window, colors: &BorderColors) {
+        if colors.is_empty() {
+            self.window_colors.remove(&window);
+            return;
+        }
+        let color = |color: &Option<String>| color.as_deref().map(|color| self.parse_color(color));
+        let custom = WindowColors {
+            normal: color(&colors.default),
+            floating: color(&colors.floating),
+            active: color(&colors.focused),
+            selected: color(&colors.selected),
+        };
+        self.window_colors.insert(window, custom);
     }
 
     /// Sets the mode within our xwrapper.
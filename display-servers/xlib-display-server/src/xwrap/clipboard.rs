@@ -0,0 +1,299 @@
+//! `XWrap` clipboard manager, see `leftwm_core::utils::clipboard_manager`.
+use super::XWrap;
+use leftwm_core::utils::clipboard_manager::{ClipboardData, SaveRequest};
+use std::os::raw::{c_int, c_long, c_uchar, c_ulong};
+use std::{mem, slice};
+use x11_dl::xlib;
+
+/// Bytes taken by the header of a `ChangeProperty` request.
+const CHANGE_PROPERTY_HEADER_LEN: usize = 24;
+
+impl XWrap {
+    /// Take the `CLIPBOARD_MANAGER` selection with the window owning `WM_Sn`, unless another
+    /// clipboard manager is running.
+    // `XGetSelectionOwner`: https://tronche.com/gui/x/xlib/window-information/XGetSelectionOwner.html
+    pub fn acquire_clipboard_manager(&mut self) {
+        let selection = self.atoms.ClipboardManager;
+        if unsafe { (self.xlib.XGetSelectionOwner)(self.display, selection) } != 0 {
+            tracing::info!("Another clipboard manager is running, not saving the clipboard");
+            return;
+        }
+        let time = self.server_time();
+        if !self.take_manager_selection(selection, time) {
+            tracing::warn!("Unable to take the clipboard manager selection");
+        }
+    }
+
+    /// A selection leftwm owned was taken by another window.
+    pub fn clipboard_selection_clear(&mut self, event: &xlib::XSelectionClearEvent) {
+        if event.selection == self.atoms.Clipboard {
+            self.clipboard.clear();
+        } else if event.selection == self.atoms.ClipboardManager {
+            tracing::info!("Another clipboard manager took over");
+            self.clipboard.clear();
+        }
+    }
+
+    /// A client asked for a selection leftwm owns: to save the clipboard or for its contents.
+    pub fn clipboard_selection_request(&mut self, event: &xlib::XSelectionRequestEvent) {
+        // Obsolete clients leave it to the owner to choose the property.
+        let property = if event.property == 0 {
+            event.target
+        } else {
+            event.property
+        };
+        let converted = if event.selection == self.atoms.ClipboardManager {
+            self.convert_clipboard_manager(event, property)
+        } else if event.selection == self.atoms.Clipboard {
+            self.convert_clipboard(event, property)
+        } else {
+            false
+        };
+        if !converted {
+            self.send_selection_notify(event, 0);
+        } else if event.target != self.atoms.SaveTargets {
+            self.send_selection_notify(event, property);
+        }
+    }
+
+    /// The clipboard owner answered a conversion leftwm asked for while saving the clipboard.
+    pub fn clipboard_selection_notify(&mut self, event: &xlib::XSelectionEvent) {
+        if event.selection != self.atoms.Clipboard || !self.clipboard.is_saving() {
+            return;
+        }
+        let next = if event.property == 0 {
+            if event.target == self.atoms.Targets {
+                None
+            } else {
+                self.clipboard.target_fetched(None)
+            }
+        } else if event.target == self.atoms.Targets {
+            let offered =
+                self.read_selection_property(self.wm_selection_owner, event.property, true);
+            self.clipboard
+                .targets_offered(self.savable_targets(offered))
+        } else {
+            // Contents sent incrementally are too large to be worth keeping around.
+            let data = self
+                .read_selection_property(self.wm_selection_owner, event.property, true)
+                .filter(|(r#type, _, _)| *r#type != self.atoms.Incr)
+                .map(|(r#type, format, value)| ClipboardData {
+                    target: event.target,
+                    r#type,
+                    format,
+                    value,
+                });
+            self.clipboard.target_fetched(data)
+        };
+
+        if let Some(target) = next {
+            self.convert_clipboard_to(target, event.time);
+            return;
+        }
+        let Some((request, saved)) = self.clipboard.finish_save() else {
+            return;
+        };
+        if saved {
+            unsafe {
+                (self.xlib.XSetSelectionOwner)(
+                    self.display,
+                    self.atoms.Clipboard,
+                    self.wm_selection_owner,
+                    c_ulong::from(request.time),
+                );
+            }
+        }
+        let mut reply: xlib::XSelectionRequestEvent = unsafe { mem::zeroed() };
+        reply.requestor = request.requestor;
+        reply.selection = self.atoms.ClipboardManager;
+        reply.target = self.atoms.SaveTargets;
+        reply.time = c_ulong::from(request.time);
+        self.send_selection_notify(&reply, if saved { request.property } else { 0 });
+    }
+
+    /// Converts `CLIPBOARD_MANAGER`, starting to save the clipboard for `SAVE_TARGETS`. The
+    /// requestor is answered once the clipboard is saved.
+    fn convert_clipboard_manager(
+        &mut self,
+        event: &xlib::XSelectionRequestEvent,
+        property: xlib::Atom,
+    ) -> bool {
+        if event.target == self.atoms.Targets {
+            let targets = [self.atoms.Targets, self.atoms.SaveTargets].map(|a| a as c_long);
+            self.replace_property_long(event.requestor, property, xlib::XA_ATOM, &targets);
+            return true;
+        }
+        if event.target != self.atoms.SaveTargets {
+            return false;
+        }
+        // The requestor may list the targets to save, otherwise all those offered are.
+        let requested = if event.property == 0 {
+            vec![]
+        } else {
+            self.savable_targets(self.read_selection_property(event.requestor, property, false))
+        };
+        self.clipboard.begin_save(SaveRequest {
+            requestor: event.requestor,
+            property,
+            // Server timestamps are 32 bits.
+            time: event.time as u32,
+        });
+        let target = self
+            .clipboard
+            .targets_offered(requested)
+            .unwrap_or(self.atoms.Targets);
+        self.convert_clipboard_to(target, event.time);
+        true
+    }
+
+    /// The targets listed in a property of type `ATOM`, without those which are not contents.
+    fn savable_targets(&self, property: Option<(xlib::Atom, u8, Vec<u8>)>) -> Vec<xlib::Atom> {
+        let special = [
+            self.atoms.Targets,
+            self.atoms.Multiple,
+            self.atoms.Timestamp,
+            self.atoms.SaveTargets,
+            self.atoms.Delete,
+        ];
+        property
+            .filter(|(r#type, _, _)| *r#type == xlib::XA_ATOM)
+            .map(|(_, _, value)| {
+                value
+                    .chunks_exact(mem::size_of::<xlib::Atom>())
+                    .filter_map(|atom| atom.try_into().ok().map(xlib::Atom::from_ne_bytes))
+                    .filter(|atom| !special.contains(atom))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Converts the saved clipboard.
+    // `XChangeProperty`: https://tronche.com/gui/x/xlib/window-information/XChangeProperty.html
+    fn convert_clipboard(
+        &self,
+        event: &xlib::XSelectionRequestEvent,
+        property: xlib::Atom,
+    ) -> bool {
+        if event.target == self.atoms.Targets {
+            let mut targets = self.clipboard.targets();
+            targets.push(self.atoms.Targets);
+            let targets: Vec<c_long> = targets.into_iter().map(|a| a as c_long).collect();
+            self.replace_property_long(event.requestor, property, xlib::XA_ATOM, &targets);
+            return true;
+        }
+        let Some(data) = self.clipboard.get(event.target) else {
+            return false;
+        };
+        // Xlib holds 32 bit items in longs.
+        let item_len = match data.format {
+            8 => 1,
+            16 => 2,
+            32 => mem::size_of::<c_long>(),
+            _ => return false,
+        };
+        let nitems = data.value.len() / item_len;
+        if nitems * usize::from(data.format / 8) > self.max_request_len() {
+            return false;
+        }
+        let Ok(nitems) = c_int::try_from(nitems) else {
+            return false;
+        };
+        unsafe {
+            (self.xlib.XChangeProperty)(
+                self.display,
+                event.requestor,
+                property,
+                data.r#type,
+                c_int::from(data.format),
+                xlib::PropModeReplace,
+                data.value.as_ptr(),
+                nitems,
+            );
+        }
+        true
+    }
+
+    /// The most bytes of property data a `ChangeProperty` request can carry.
+    // `XMaxRequestSize`: https://tronche.com/gui/x/xlib/display/display-macros.html#MaxRequestSize
+    fn max_request_len(&self) -> usize {
+        let units = unsafe {
+            match (self.xlib.XExtendedMaxRequestSize)(self.display) {
+                0 => (self.xlib.XMaxRequestSize)(self.display),
+                extended => extended,
+            }
+        };
+        (units as usize * 4).saturating_sub(CHANGE_PROPERTY_HEADER_LEN)
+    }
+
+    /// Ask the clipboard owner to convert the clipboard to `target`.
+    // `XConvertSelection`: https://tronche.com/gui/x/xlib/window-information/XConvertSelection.html
+    fn convert_clipboard_to(&self, target: xlib::Atom, time: xlib::Time) {
+        unsafe {
+            (self.xlib.XConvertSelection)(
+                self.display,
+                self.atoms.Clipboard,
+                target,
+                self.atoms.LeftwmClipboard,
+                self.wm_selection_owner,
+                time,
+            );
+        }
+    }
+
+    /// Read a property of a selection transfer, deleting it if asked to. Returns its type, format
+    /// and value as returned by Xlib.
+    // `XGetWindowProperty`: https://tronche.com/gui/x/xlib/window-information/XGetWindowProperty.html
+    fn read_selection_property(
+        &self,
+        window: xlib::Window,
+        property: xlib::Atom,
+        delete: bool,
+    ) -> Option<(xlib::Atom, u8, Vec<u8>)> {
+        let mut type_return: xlib::Atom = 0;
+        let mut format_return: c_int = 0;
+        let mut nitems_return: c_ulong = 0;
+        let mut bytes_after_return: c_ulong = 0;
+        let mut prop_return: *mut c_uchar = std::ptr::null_mut();
+        unsafe {
+            let status = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                property,
+                0,
+                c_long::MAX / 4,
+                xlib::Bool::from(delete),
+                xlib::AnyPropertyType as xlib::Atom,
+                &mut type_return,
+                &mut format_return,
+                &mut nitems_return,
+                &mut bytes_after_return,
+                &mut prop_return,
+            );
+            if status != i32::from(xlib::Success) || prop_return.is_null() {
+                return None;
+            }
+            let item_len = match format_return {
+                16 => 2,
+                32 => mem::size_of::<c_long>(),
+                _ => 1,
+            };
+            let value = slice::from_raw_parts(prop_return, nitems_return as usize * item_len);
+            let value = value.to_vec();
+            (self.xlib.XFree)(prop_return.cast());
+            Some((type_return, u8::try_from(format_return).ok()?, value))
+        }
+    }
+
+    /// Answer a selection request, `property` is `None` (0) if it was refused.
+    fn send_selection_notify(&self, request: &xlib::XSelectionRequestEvent, property: xlib::Atom) {
+        let mut msg: xlib::XSelectionEvent = unsafe { mem::zeroed() };
+        msg.type_ = xlib::SelectionNotify;
+        msg.requestor = request.requestor;
+        msg.selection = request.selection;
+        msg.target = request.target;
+        msg.property = property;
+        msg.time = request.time;
+        let mut ev: xlib::XEvent = msg.into();
+        self.send_xevent(request.requestor, 0, xlib::NoEventMask, &mut ev);
+    }
+}
@@ -0,0 +1,253 @@
+//! Frame windows wrapping managed windows, which own their border.
+//!
+//! Borders drawn on the windows themselves are in their visual, which may have an alpha channel.
+//! Frames use the visual of the root window instead, and draw the title bars above the windows
+//! when they are configured.
+use super::{XWrap, ROOT_EVENT_MASK};
+use crate::XlibWindowHandle;
+use leftwm_core::{config::TitleBar, Window};
+use std::cell::Cell;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_long, c_uint, c_ulong};
+use x11_dl::xlib;
+
+/// Events of frames, the windows inside them are managed through them like through the root.
+const FRAME_EVENT_MASK: c_long = xlib::SubstructureRedirectMask
+    | xlib::SubstructureNotifyMask
+    | xlib::EnterWindowMask
+    | xlib::ExposureMask
+    | xlib::ButtonPressMask;
+
+/// Space left of the title in title bars.
+const TITLE_PADDING: c_int = 4;
+
+/// A frame wrapping a managed window.
+pub struct Frame {
+    window: xlib::Window,
+    /// Height of the title bar shown above the window, none while it is fullscreen.
+    title_bar: Cell<c_int>,
+}
+
+/// The font, graphics context and colors title bars are drawn with.
+pub struct TitleBarStyle {
+    height: c_int,
+    font: *mut xlib::XFontStruct,
+    gc: xlib::GC,
+    color: c_ulong,
+    focused_color: c_ulong,
+    text_color: c_ulong,
+}
+
+impl XWrap {
+    /// The frame of a window, or the window itself when it has none.
+    #[must_use]
+    pub fn frame_of(&self, window: xlib::Window) -> xlib::Window {
+        self.frames
+            .get(&window)
+            .map_or(window, |frame| frame.window)
+    }
+
+    /// The window inside a frame, or the window itself when it is not a frame.
+    #[must_use]
+    pub fn client_of(&self, window: xlib::Window) -> xlib::Window {
+        self.frames
+            .iter()
+            .find(|(_, frame)| frame.window == window)
+            .map_or(window, |(client, _)| *client)
+    }
+
+    /// Whether a window is the frame of a managed window.
+    #[must_use]
+    pub fn is_frame(&self, window: xlib::Window) -> bool {
+        self.frames.values().any(|frame| frame.window == window)
+    }
+
+    /// Height of the title bar shown above a window, 0 when it has none.
+    #[must_use]
+    pub fn title_bar_of(&self, window: xlib::Window) -> c_int {
+        self.frames
+            .get(&window)
+            .map_or(0, |frame| frame.title_bar.get())
+    }
+
+    /// Height of the title bar a window should have, fullscreen windows have none.
+    pub(super) fn title_bar_height(&self, window: &Window<XlibWindowHandle>) -> c_int {
+        match &self.title_bar {
+            Some(style) if !window.is_fullscreen() => style.height,
+            _ => 0,
+        }
+    }
+
+    /// Record the height of the title bar shown above a framed window.
+    pub(super) fn set_title_bar_of(&self, window: xlib::Window, height: c_int) {
+        if let Some(frame) = self.frames.get(&window) {
+            frame.title_bar.set(height);
+        }
+    }
+
+    /// Load the font and colors of title bars, freeing the previous ones.
+    // `XLoadQueryFont`: https://tronche.com/gui/x/xlib/graphics/font-metrics/XLoadQueryFont.html
+    // `XCreateGC`: https://tronche.com/gui/x/xlib/GC/XCreateGC.html
+    pub fn load_title_bar(&mut self, config: Option<TitleBar>) {
+        if let Some(style) = self.title_bar.take() {
+            unsafe {
+                (self.xlib.XFreeGC)(self.display, style.gc);
+                (self.xlib.XFreeFont)(self.display, style.font);
+            }
+        }
+        let Some(config) = config else {
+            return;
+        };
+        let load = |name: &str| {
+            let name = CString::new(name).unwrap_or_default();
+            unsafe { (self.xlib.XLoadQueryFont)(self.display, name.as_ptr()) }
+        };
+        let mut font = load(&config.font);
+        if font.is_null() {
+            tracing::warn!(
+                "Unable to load the title bar font {}, using fixed",
+                config.font
+            );
+            font = load("fixed");
+        }
+        if font.is_null() {
+            return;
+        }
+        let gc = unsafe {
+            let gc = (self.xlib.XCreateGC)(self.display, self.root, 0, std::ptr::null_mut());
+            (self.xlib.XSetFont)(self.display, gc, (*font).fid);
+            gc
+        };
+        self.title_bar = Some(TitleBarStyle {
+            height: config.height.into(),
+            font,
+            gc,
+            color: self.get_color(config.color),
+            focused_color: self.get_color(config.focused_color),
+            text_color: self.get_color(config.text_color),
+        });
+    }
+
+    /// Draw the title bar of a framed window, with its `_NET_WM_NAME` written on it.
+    // `XFillRectangle`: https://tronche.com/gui/x/xlib/graphics/filling-areas/XFillRectangle.html
+    // `XDrawImageString`: https://tronche.com/gui/x/xlib/graphics/drawing-text/XDrawImageString.html
+    pub fn draw_title_bar(&self, window: xlib::Window, focused: bool) {
+        let (Some(style), Some(frame)) = (&self.title_bar, self.frames.get(&window)) else {
+            return;
+        };
+        let height = frame.title_bar.get();
+        if height == 0 {
+            return;
+        }
+        let background = if focused {
+            style.focused_color
+        } else {
+            style.color
+        } | 0xff00_0000;
+
+        // Core fonts are Latin-1.
+        let name = self.get_window_name(window).unwrap_or_default();
+        let text: Vec<c_char> = name
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?') as c_char)
+            .collect();
+        let (ascent, descent) = unsafe { ((*style.font).ascent, (*style.font).descent) };
+        let baseline = (height + ascent - descent) / 2;
+        unsafe {
+            (self.xlib.XSetForeground)(self.display, style.gc, background);
+            // The server clips the bar to the width of the frame.
+            (self.xlib.XFillRectangle)(
+                self.display,
+                frame.window,
+                style.gc,
+                0,
+                0,
+                c_uint::from(u16::MAX),
+                height as c_uint,
+            );
+            (self.xlib.XSetForeground)(self.display, style.gc, style.text_color | 0xff00_0000);
+            (self.xlib.XSetBackground)(self.display, style.gc, background);
+            (self.xlib.XDrawImageString)(
+                self.display,
+                frame.window,
+                style.gc,
+                TITLE_PADDING,
+                baseline,
+                text.as_ptr(),
+                text.len().min(255) as c_int,
+            );
+        }
+    }
+
+    /// Wrap a window in a mapped frame taking its place.
+    // `XCreateSimpleWindow`: https://tronche.com/gui/x/xlib/window/XCreateSimpleWindow.html
+    // `XAddToSaveSet`: https://tronche.com/gui/x/xlib/window-and-session-manager/XAddToSaveSet.html
+    // `XReparentWindow`: https://tronche.com/gui/x/xlib/window-and-session-manager/XReparentWindow.html
+    pub fn frame_window(&mut self, window: xlib::Window) {
+        if self.frames.contains_key(&window) {
+            return;
+        }
+        let Ok(attrs) = self.get_window_attrs(window) else {
+            return;
+        };
+        let frame = unsafe {
+            (self.xlib.XCreateSimpleWindow)(
+                self.display,
+                self.root,
+                attrs.x,
+                attrs.y,
+                attrs.width.max(1) as c_uint,
+                attrs.height.max(1) as c_uint,
+                attrs.border_width as c_uint,
+                self.get_pixel(self.root, self.colors.normal),
+                0,
+            )
+        };
+        self.subscribe_to_event(frame, FRAME_EVENT_MASK);
+        // The window is unmapped from the root when it leaves it, which is not a withdrawal.
+        self.set_root_notify(false);
+        unsafe {
+            // Keep the window around if we exit without giving it back.
+            (self.xlib.XAddToSaveSet)(self.display, window);
+            (self.xlib.XSetWindowBorderWidth)(self.display, window, 0);
+            (self.xlib.XReparentWindow)(self.display, window, frame, 0, 0);
+            (self.xlib.XMapWindow)(self.display, window);
+            (self.xlib.XMapWindow)(self.display, frame);
+        }
+        self.set_root_notify(true);
+        let frame = Frame {
+            window: frame,
+            title_bar: Cell::new(0),
+        };
+        self.frames.insert(window, frame);
+    }
+
+    /// Destroy the frame of a window, giving the window back to the root unless it is destroyed.
+    // `XRemoveFromSaveSet`: https://tronche.com/gui/x/xlib/window-and-session-manager/XRemoveFromSaveSet.html
+    // `XDestroyWindow`: https://tronche.com/gui/x/xlib/window/XDestroyWindow.html
+    pub fn unframe_window(&mut self, window: xlib::Window, destroyed: bool) {
+        let Some(frame) = self.frames.remove(&window) else {
+            return;
+        };
+        if !destroyed {
+            if let Ok(attrs) = self.get_window_attrs(frame.window) {
+                unsafe {
+                    (self.xlib.XReparentWindow)(self.display, window, self.root, attrs.x, attrs.y);
+                    (self.xlib.XRemoveFromSaveSet)(self.display, window);
+                }
+            }
+        }
+        unsafe { (self.xlib.XDestroyWindow)(self.display, frame.window) };
+    }
+
+    /// Whether the root reports the windows on it being mapped, unmapped and destroyed.
+    pub fn set_root_notify(&self, enabled: bool) {
+        let mut attrs: xlib::XSetWindowAttributes = unsafe { std::mem::zeroed() };
+        attrs.event_mask = if enabled {
+            ROOT_EVENT_MASK
+        } else {
+            ROOT_EVENT_MASK & !(xlib::SubstructureNotifyMask)
+        };
+        self.change_window_attributes(self.root, xlib::CWEventMask, attrs);
+    }
+}
@@ -1,10 +1,14 @@
 //! `XWrap` getters.
 use super::{Screen, WindowHandle, XlibError, MAX_PROPERTY_VALUE_LEN, MOUSEMASK};
 use crate::{XWrap, XlibWindowHandle};
-use leftwm_core::models::{BBox, DockArea, WindowState, WindowType, XyhwChange};
+use leftwm_core::models::{sort_screens, BBox, DockArea, WindowState, WindowType, XyhwChange};
+use leftwm_core::utils::color::{Rgba, VisualFormat};
+use leftwm_core::utils::{motif_hints, text_property};
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
 use std::slice;
+use std::time::Duration;
+use x11_dl::keysym;
 use x11_dl::xinerama::XineramaScreenInfo;
 use x11_dl::xlib::{self, XWindowAttributes};
 use x11_dl::xrandr::XRRCrtcInfo;
@@ -32,20 +36,105 @@ impl XWrap {
         Ok(all)
     }
 
-    /// Returns a `XColor` for a color.
+    /// Returns the pixel of a color for the root visual.
+    #[must_use]
+    pub fn get_color(&self, color: String) -> c_ulong {
+        self.get_pixel(self.root, self.parse_color(&color))
+    }
+
+    /// Parses a color. Hex colors, with or without alpha, are parsed here. Other colors, such as
+    /// X11 color names, are looked up by the X server. Unknown colors are black.
     // `XDefaultScreen`: https://tronche.com/gui/x/xlib/display/display-macros.html#DefaultScreen
     // `XDefaultColormap`: https://tronche.com/gui/x/xlib/display/display-macros.html#DefaultColormap
-    // `XAllocNamedColor`: https://tronche.com/gui/x/xlib/color/XAllocNamedColor.html
+    // `XLookupColor`: https://tronche.com/gui/x/xlib/color/XLookupColor.html
     #[must_use]
-    pub fn get_color(&self, color: String) -> c_ulong {
+    pub fn parse_color(&self, color: &str) -> Rgba {
+        if let Some(rgba) = Rgba::from_hex(color) {
+            return rgba;
+        }
+        let name = CString::new(color.trim()).unwrap_or_default();
+        let mut exact: xlib::XColor = unsafe { std::mem::zeroed() };
+        let mut screen_color: xlib::XColor = unsafe { std::mem::zeroed() };
+        let status = unsafe {
+            let screen = (self.xlib.XDefaultScreen)(self.display);
+            let cmap = (self.xlib.XDefaultColormap)(self.display, screen);
+            (self.xlib.XLookupColor)(
+                self.display,
+                cmap,
+                name.as_ptr(),
+                &mut exact,
+                &mut screen_color,
+            )
+        };
+        if status == 0 {
+            tracing::warn!("Unknown color: {}", color);
+            return Rgba::opaque(0, 0, 0);
+        }
+        Rgba::from_x_channels(exact.red, exact.green, exact.blue)
+    }
+
+    /// Returns the pixel of a color for a window: in its visual for managed windows, in the
+    /// visual of the root for the others.
+    // `XDefaultVisual`: https://tronche.com/gui/x/xlib/display/display-macros.html#DefaultVisual
+    // `XDefaultDepth`: https://tronche.com/gui/x/xlib/display/display-macros.html#DefaultDepth
+    // `XAllocColor`: https://tronche.com/gui/x/xlib/color/XAllocColor.html
+    #[must_use]
+    pub fn get_pixel(&self, window: xlib::Window, color: Rgba) -> c_ulong {
+        let format = self.visuals.get(&window).copied().or_else(|| unsafe {
+            let screen = (self.xlib.XDefaultScreen)(self.display);
+            let visual = (self.xlib.XDefaultVisual)(self.display, screen).as_ref()?;
+            visual_format(visual, (self.xlib.XDefaultDepth)(self.display, screen))
+        });
+        if let Some(format) = format {
+            return c_ulong::from(color.pixel(format));
+        }
+        // Visuals using a colormap still need a color cell.
+        let channel = |channel: u8| u16::from(channel) * 0x101;
+        let mut xcolor: xlib::XColor = unsafe { std::mem::zeroed() };
+        xcolor.red = channel(color.red);
+        xcolor.green = channel(color.green);
+        xcolor.blue = channel(color.blue);
         unsafe {
             let screen = (self.xlib.XDefaultScreen)(self.display);
-            let cmap: xlib::Colormap = (self.xlib.XDefaultColormap)(self.display, screen);
-            let color_cstr = CString::new(color).unwrap_or_default().into_raw();
-            let mut color: xlib::XColor = std::mem::zeroed();
-            (self.xlib.XAllocNamedColor)(self.display, cmap, color_cstr, &mut color, &mut color);
-            color.pixel
+            let cmap = (self.xlib.XDefaultColormap)(self.display, screen);
+            (self.xlib.XAllocColor)(self.display, cmap, &mut xcolor);
         }
+        xcolor.pixel
+    }
+
+    /// Returns the pixel layout of the visual of a window, if it is a true color visual.
+    #[must_use]
+    pub fn get_visual_format(&self, window: xlib::Window) -> Option<VisualFormat> {
+        let attrs = self.get_window_attrs(window).ok()?;
+        let visual = unsafe { attrs.visual.as_ref()? };
+        visual_format(visual, attrs.depth)
+    }
+
+    /// Returns the modifiers of the lock keys: `Lock`, and those `Num_Lock` and `Scroll_Lock`
+    /// are on in the modifier mapping.
+    // `XGetModifierMapping`: https://tronche.com/gui/x/xlib/input/XGetModifierMapping.html
+    // `XKeysymToKeycode`: https://tronche.com/gui/x/xlib/utilities/keyboard/XKeysymToKeycode.html
+    #[must_use]
+    pub fn get_lock_mask(&self) -> u16 {
+        let mut mask = xlib::LockMask as u16;
+        let lock_keys = [keysym::XK_Num_Lock, keysym::XK_Scroll_Lock]
+            .map(|sym| unsafe { (self.xlib.XKeysymToKeycode)(self.display, c_ulong::from(sym)) });
+        unsafe {
+            let map = (self.xlib.XGetModifierMapping)(self.display);
+            let Some(modifiers) = map.as_ref() else {
+                return mask;
+            };
+            let keycodes_per_modifier = usize::try_from(modifiers.max_keypermod).unwrap_or(0);
+            let keycodes = slice::from_raw_parts(modifiers.modifiermap, 8 * keycodes_per_modifier);
+            for (i, keycodes) in keycodes.chunks(keycodes_per_modifier.max(1)).enumerate() {
+                // Unused entries are 0, as are lock keys missing from the keyboard.
+                if keycodes.iter().any(|k| *k != 0 && lock_keys.contains(k)) {
+                    mask |= 1 << i;
+                }
+            }
+            (self.xlib.XFreeModifiermap)(map);
+        }
+        mask
     }
 
     /// Returns the current position of the cursor.
@@ -112,7 +201,7 @@ impl XWrap {
                 )
             };
             if success > 0 {
-                return Ok(WindowHandle(XlibWindowHandle(child_return)));
+                return Ok(WindowHandle(XlibWindowHandle(self.client_of(child_return))));
             }
         }
         Err(XlibError::RootWindowNotFound)
@@ -172,17 +261,35 @@ impl XWrap {
                 xyhw.x = Some(size.x);
                 xyhw.y = Some(size.y);
             }
-            // TODO: support min/max aspect
-            // if size.flags & xlib::PAspect != 0 {
-            //     //c->mina = (float)size.min_aspect.y / size.min_aspect.x;
-            //     //c->maxa = (float)size.max_aspect.x / size.max_aspect.y;
-            // }
+
+            if (size.flags & xlib::PAspect) != 0 {
+                let ratio = |r: xlib::AspectRatio| (r.x > 0 && r.y > 0).then_some((r.x, r.y));
+                xyhw.min_aspect = ratio(size.min_aspect);
+                xyhw.max_aspect = ratio(size.max_aspect);
+            }
 
             return Some(xyhw);
         }
         None
     }
 
+    /// Returns how long the user has been idle, if the screen saver extension is loaded.
+    // `XScreenSaverQueryInfo`: https://www.x.org/releases/current/doc/man/man3/Xss.3.xhtml
+    #[must_use]
+    pub fn get_idle_time(&self) -> Option<Duration> {
+        let xss = self.xss.as_ref()?;
+        unsafe {
+            let info = (xss.XScreenSaverAllocInfo)();
+            if info.is_null() {
+                return None;
+            }
+            let status = (xss.XScreenSaverQueryInfo)(self.display, self.root, info);
+            let idle = (*info).idle;
+            (self.xlib.XFree)(info.cast());
+            (status != 0).then(|| Duration::from_millis(idle))
+        }
+    }
+
     /// Returns the next `Xevent` that matches the mask of the xserver.
     // `XMaskEvent`: https://tronche.com/gui/x/xlib/event-handling/manipulating-event-queue/XMaskEvent.html
     #[must_use]
@@ -216,9 +323,14 @@ impl XWrap {
     /// Also panics if window attrs cannot be obtained.
     #[must_use]
     pub fn get_screens(&self) -> Vec<Screen<XlibWindowHandle>> {
-        use x11_dl::xinerama::Xlib;
+        let mut screens = self.query_screens();
+        sort_screens(&mut screens);
+        screens
+    }
+
+    /// The screens from RandR, or else from Xinerama, or else the roots, in server order.
+    fn query_screens(&self) -> Vec<Screen<XlibWindowHandle>> {
         use x11_dl::xrandr::Xrandr;
-        let xlib = Xlib::open().expect("Couldn't not connect to Xorg Server");
 
         // Use randr for screen detection if possible, otherwise fall back to Xinerama.
         // Only randr supports screen names.
@@ -254,33 +366,49 @@ impl XWrap {
             }
         }
 
-        let xinerama = unsafe { (xlib.XineramaIsActive)(self.display) } > 0;
-        if xinerama {
-            let root = self.get_default_root_handle();
-            let mut screen_count = 0;
-            let info_array_raw =
-                unsafe { (xlib.XineramaQueryScreens)(self.display, &mut screen_count) };
-            // Take ownership of the array.
-            let xinerama_infos: &[XineramaScreenInfo] =
-                unsafe { slice::from_raw_parts(info_array_raw, screen_count as usize) };
-            xinerama_infos
-                .iter()
-                .map(|i| {
-                    let mut s: Screen<XlibWindowHandle> = XineramaScreenInfoIntoScreen(i).into();
-                    s.root = root;
-                    s
-                })
-                .collect()
-        } else {
-            // NON-XINERAMA
-            let roots: Result<Vec<xlib::XWindowAttributes>, _> =
-                self.get_roots().map(|w| self.get_window_attrs(w)).collect();
-            let roots = roots.expect("Error: No screen were detected");
-            roots
-                .iter()
-                .map(|attrs| XWindowAttributesIntoScreen(attrs).into())
-                .collect()
+        if let Some(screens) = self.get_xinerama_screens() {
+            return screens;
         }
+        // NON-XINERAMA
+        let roots: Result<Vec<xlib::XWindowAttributes>, _> =
+            self.get_roots().map(|w| self.get_window_attrs(w)).collect();
+        let roots = roots.expect("Error: No screen were detected");
+        roots
+            .iter()
+            .map(|attrs| XWindowAttributesIntoScreen(attrs).into())
+            .collect()
+    }
+
+    /// The screens of Xinerama, or `None` when the extension is missing or inactive.
+    // `XineramaQueryScreens`: https://www.x.org/releases/current/doc/man/man3/Xinerama.3.xhtml
+    fn get_xinerama_screens(&self) -> Option<Vec<Screen<XlibWindowHandle>>> {
+        let xinerama = x11_dl::xinerama::Xlib::open().ok()?;
+        let (mut event_base, mut error_base) = (0, 0);
+        let present = unsafe {
+            (xinerama.XineramaQueryExtension)(self.display, &mut event_base, &mut error_base)
+        };
+        if present == 0 || unsafe { (xinerama.XineramaIsActive)(self.display) } == 0 {
+            return None;
+        }
+        let root = self.get_default_root_handle();
+        let mut screen_count = 0;
+        let info_array_raw =
+            unsafe { (xinerama.XineramaQueryScreens)(self.display, &mut screen_count) };
+        if info_array_raw.is_null() {
+            return None;
+        }
+        let xinerama_infos: &[XineramaScreenInfo] =
+            unsafe { slice::from_raw_parts(info_array_raw, screen_count as usize) };
+        let screens = xinerama_infos
+            .iter()
+            .map(|i| {
+                let mut s: Screen<XlibWindowHandle> = XineramaScreenInfoIntoScreen(i).into();
+                s.root = root;
+                s
+            })
+            .collect();
+        unsafe { (self.xlib.XFree)(info_array_raw.cast()) };
+        Some(screens)
     }
 
     /// Returns the dimensions of the screens.
@@ -458,6 +586,41 @@ impl XWrap {
         }
     }
 
+    /// Returns a windows `_NET_WM_WINDOW_OPACITY`, if it has one.
+    // `XFree`: https://tronche.com/gui/x/xlib/display/XFree.html
+    #[must_use]
+    pub fn get_window_opacity(&self, window: xlib::Window) -> Option<u32> {
+        let (prop_return, _) = self
+            .get_property(window, self.atoms.NetWMWindowOpacity, xlib::XA_CARDINAL)
+            .ok()?;
+        unsafe {
+            // Format 32 properties are returned as longs.
+            #[allow(clippy::cast_ptr_alignment)]
+            let opacity = *prop_return.cast::<c_ulong>() as u32;
+            (self.xlib.XFree)(prop_return.cast_mut().cast());
+            Some(opacity)
+        }
+    }
+
+    /// Returns whether a window asks for no border through `_MOTIF_WM_HINTS`.
+    // `XFree`: https://tronche.com/gui/x/xlib/display/XFree.html
+    #[must_use]
+    pub fn get_window_undecorated(&self, window: xlib::Window) -> bool {
+        let atom = self.atoms.MotifWMHints;
+        let Ok((prop_return, nitems)) = self.get_property(window, atom, atom) else {
+            return false;
+        };
+        // Format 32 properties are returned as longs.
+        let hints: Vec<u32> = unsafe {
+            #[allow(clippy::cast_ptr_alignment)]
+            let hints = slice::from_raw_parts(prop_return.cast::<c_ulong>(), nitems as usize);
+            let hints = hints.iter().map(|&hint| hint as u32).collect();
+            (self.xlib.XFree)(prop_return.cast_mut().cast());
+            hints
+        };
+        motif_hints::wants_no_border(&hints)
+    }
+
     /// Returns the states of a window.
     #[must_use]
     pub fn get_window_states(&self, window: xlib::Window) -> Vec<WindowState> {
@@ -567,6 +730,10 @@ impl XWrap {
             x if x == Some(self.atoms.NetWMWindowTypeUtility) => WindowType::Utility,
             x if x == Some(self.atoms.NetWMWindowTypeSplash) => WindowType::Splash,
             x if x == Some(self.atoms.NetWMWindowTypeDialog) => WindowType::Dialog,
+            x if x == Some(self.atoms.NetWMWindowTypeNotification) => WindowType::Notification,
+            x if x == Some(self.atoms.NetWMWindowTypePopupMenu) => WindowType::PopupMenu,
+            x if x == Some(self.atoms.NetWMWindowTypeDropdownMenu) => WindowType::DropdownMenu,
+            x if x == Some(self.atoms.NetWMWindowTypeTooltip) => WindowType::Tooltip,
             _ => WindowType::Normal,
         }
     }
@@ -585,9 +752,12 @@ impl XWrap {
         }
     }
 
-    /// Returns the `WM_STATE` of a window.
+    /// Returns the `WM_STATE` of a window, the one last set without a round trip for managed ones.
     #[must_use]
     pub fn get_wm_state(&self, window: xlib::Window) -> Option<c_long> {
+        if let Some(&state) = self.wm_states.borrow().get(&window) {
+            return Some(state);
+        }
         let (prop_return, nitems_return) = self
             .get_property(window, self.atoms.WMState, self.atoms.WMState)
             .ok()?;
@@ -686,14 +856,14 @@ impl XWrap {
             let mut text_prop: xlib::XTextProperty = std::mem::zeroed();
             let status: c_int =
                 (self.xlib.XGetTextProperty)(self.display, window, &mut text_prop, atom);
-            if status == 0 {
+            if status == 0 || text_prop.value.is_null() {
                 return Err(XlibError::FailedStatus);
             }
-            if let Ok(s) = CString::from_raw(text_prop.value.cast::<c_char>()).into_string() {
-                return Ok(s);
-            }
-        };
-        Err(XlibError::FailedStatus)
+            let bytes = slice::from_raw_parts(text_prop.value, text_prop.nitems as usize);
+            let text = text_property::decode(bytes, self.atoms.text_encoding(text_prop.encoding));
+            (self.xlib.XFree)(text_prop.value.cast());
+            Ok(text)
+        }
     }
 
     /// Returns the child windows of a root.
@@ -733,7 +903,8 @@ impl XWrap {
             #[allow(clippy::cast_ptr_alignment)]
             let array_ptr = prop_return.cast::<c_long>();
             let slice = slice::from_raw_parts(array_ptr, nitems_return as usize);
-            if slice.len() == 12 {
+            // Only the four widths, without the start and end of `_NET_WM_STRUT_PARTIAL`.
+            if slice.len() == 4 {
                 return Some(SliceIntoDockArea(slice).into());
             }
             None
@@ -822,19 +993,34 @@ struct SliceIntoDockArea<'a>(&'a [i64]);
 
 impl From<SliceIntoDockArea<'_>> for DockArea {
     fn from(val: SliceIntoDockArea<'_>) -> Self {
+        // `_NET_WM_STRUT` only has the first four values, the start and ends are left at zero.
+        let get = |i: usize| val.0.get(i).map_or(0, |v| *v as i32);
         DockArea {
-            left: val.0[0] as i32,
-            right: val.0[1] as i32,
-            top: val.0[2] as i32,
-            bottom: val.0[3] as i32,
-            left_start_y: val.0[4] as i32,
-            left_end_y: val.0[5] as i32,
-            right_start_y: val.0[6] as i32,
-            right_end_y: val.0[7] as i32,
-            top_start_x: val.0[8] as i32,
-            top_end_x: val.0[9] as i32,
-            bottom_start_x: val.0[10] as i32,
-            bottom_end_x: val.0[11] as i32,
+            left: get(0),
+            right: get(1),
+            top: get(2),
+            bottom: get(3),
+            left_start_y: get(4),
+            left_end_y: get(5),
+            right_start_y: get(6),
+            right_end_y: get(7),
+            top_start_x: get(8),
+            top_end_x: get(9),
+            bottom_start_x: get(10),
+            bottom_end_x: get(11),
         }
     }
 }
+
+/// The pixel layout of a visual, if it is a true color visual.
+fn visual_format(visual: &xlib::Visual, depth: c_int) -> Option<VisualFormat> {
+    if visual.class != xlib::TrueColor {
+        return None;
+    }
+    Some(VisualFormat {
+        red_mask: u32::try_from(visual.red_mask).ok()?,
+        green_mask: u32::try_from(visual.green_mask).ok()?,
+        blue_mask: u32::try_from(visual.blue_mask).ok()?,
+        depth: u8::try_from(depth).ok()?,
+    })
+}
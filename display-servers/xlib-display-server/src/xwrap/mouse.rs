@@ -2,6 +2,8 @@
 use super::{XlibError, MOUSEMASK};
 use crate::xwrap::BUTTONMASK;
 use crate::XWrap;
+use leftwm_core::models::WindowType;
+use leftwm_core::utils::modmask_lookup::{with_lock_combinations, ModMask};
 use std::os::raw::{c_int, c_uint, c_ulong};
 use x11_dl::xlib;
 
@@ -13,35 +15,22 @@ impl XWrap {
             self.grab_buttons(handle, xlib::Button1, xlib::AnyModifier);
             self.grab_buttons(handle, xlib::Button3, xlib::AnyModifier);
         }
-        self.grab_buttons(handle, xlib::Button1, u32::from(self.mouse_key_mask.bits()));
-        self.grab_buttons(
-            handle,
-            xlib::Button1,
-            u32::from(self.mouse_key_mask.bits()) | xlib::ShiftMask,
-        );
-        self.grab_buttons(handle, xlib::Button3, u32::from(self.mouse_key_mask.bits()));
-        self.grab_buttons(
-            handle,
-            xlib::Button3,
-            u32::from(self.mouse_key_mask.bits()) | xlib::ShiftMask,
-        );
+        for (modifiers, button) in &self.mousebinds {
+            self.grab_buttons(handle, u32::from(*button), u32::from(modifiers.bits()));
+        }
     }
 
     /// Grabs the button with the modifier for a window.
     // `XGrabButton`: https://tronche.com/gui/x/xlib/input/XGrabButton.html
     pub fn grab_buttons(&self, window: xlib::Window, button: u32, modifiers: u32) {
-        // Grab the buttons with and without numlock (Mod2).
-        let mods: Vec<u32> = vec![
-            modifiers,
-            modifiers | xlib::Mod2Mask,
-            modifiers | xlib::LockMask,
-        ];
-        for m in mods {
+        // Grab the buttons whichever locks are on.
+        let modifiers = u16::try_from(modifiers).unwrap_or(ModMask::Any.bits());
+        for m in with_lock_combinations(modifiers, self.lock_mask) {
             unsafe {
                 (self.xlib.XGrabButton)(
                     self.display,
                     button,
-                    m,
+                    u32::from(m),
                     window,
                     0,
                     BUTTONMASK as u32,
@@ -54,6 +43,37 @@ impl XWrap {
         }
     }
 
+    /// Detect the lock modifiers again after the keyboard mapping changed, grabbing the buttons
+    /// of the windows again when they moved.
+    // `XRefreshKeyboardMapping`: https://tronche.com/gui/x/xlib/utilities/keyboard/XRefreshKeyboardMapping.html
+    pub fn refresh_lock_mask(&mut self, event: &mut xlib::XMappingEvent) {
+        // Lookups of keycodes use the mapping cached by Xlib.
+        unsafe { (self.xlib.XRefreshKeyboardMapping)(event) };
+        let lock_mask = self.get_lock_mask();
+        if lock_mask == self.lock_mask {
+            return;
+        }
+        self.lock_mask = lock_mask;
+        self.regrab_mouse_clicks();
+    }
+
+    /// Grabs the buttons of all managed windows again, e.g. after the mousebinds changed.
+    pub fn regrab_mouse_clicks(&self) {
+        for &window in &self.managed_windows {
+            // Docks do not get clicks grabbed to focus them, see `toggle_window_visibility`.
+            let focus_on_click = self.focus_behaviour.is_clickto()
+                && window != self.focused_window
+                && self.get_window_type(window) != WindowType::Dock;
+            self.grab_mouse_clicks(window, !focus_on_click);
+        }
+    }
+
+    /// The modifiers of the lock keys, which mousebinds ignore.
+    #[must_use]
+    pub const fn lock_mask(&self) -> u16 {
+        self.lock_mask
+    }
+
     /// Cleans all currently grabbed buttons of a window.
     // `XUngrabButton`: https://tronche.com/gui/x/xlib/input/XUngrabButton.html
     pub fn ungrab_buttons(&self, handle: xlib::Window) {
@@ -100,7 +120,7 @@ impl XWrap {
     ///
     /// Will error if unable to obtain window attributes. See `get_window_attrs`.
     pub fn move_cursor_to_window(&self, window: xlib::Window) -> Result<(), XlibError> {
-        let attrs = self.get_window_attrs(window)?;
+        let attrs = self.get_window_attrs(self.frame_of(window))?;
         let point = (attrs.x + (attrs.width / 2), attrs.y + (attrs.height / 2));
         self.move_cursor_to_point(point)
     }
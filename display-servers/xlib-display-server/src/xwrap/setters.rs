@@ -2,8 +2,9 @@
 use super::WindowHandle;
 use crate::{XWrap, XlibWindowHandle};
 use leftwm_core::models::TagId;
+use leftwm_core::utils::color::Rgba;
 use std::ffi::CString;
-use std::os::raw::{c_long, c_ulong};
+use std::os::raw::{c_char, c_long, c_ulong};
 use x11_dl::xlib;
 
 impl XWrap {
@@ -67,6 +68,38 @@ impl XWrap {
         }
     }
 
+    /// Sets the number of desktops and their names. Labels with null bytes are left empty.
+    // `Xutf8TextListToTextProperty`: https://linux.die.net/man/3/xutf8textlisttotextproperty
+    // `XSetTextProperty`: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/XSetTextProperty.html
+    pub fn set_desktop_names(&self, labels: &[String]) {
+        let data = vec![labels.len() as u32];
+        self.set_desktop_prop(&data, self.atoms.NetNumberOfDesktops);
+        let labels: Vec<CString> = labels
+            .iter()
+            .map(|label| CString::new(label.as_str()).unwrap_or_default())
+            .collect();
+        let mut list: Vec<*mut c_char> = labels.iter().map(|l| l.as_ptr().cast_mut()).collect();
+        let mut text: xlib::XTextProperty = unsafe { std::mem::zeroed() };
+        unsafe {
+            (self.xlib.Xutf8TextListToTextProperty)(
+                self.display,
+                list.as_mut_ptr(),
+                list.len() as i32,
+                xlib::XUTF8StringStyle,
+                &mut text,
+            );
+            (self.xlib.XSetTextProperty)(
+                self.display,
+                self.root,
+                &mut text,
+                self.atoms.NetDesktopNames,
+            );
+            if !text.value.is_null() {
+                (self.xlib.XFree)(text.value.cast());
+            }
+        }
+    }
+
     /// Sets the current desktop.
     pub fn set_current_desktop(&self, current_tag: Option<TagId>) {
         let indexes: Vec<u32> = match current_tag {
@@ -151,14 +184,15 @@ impl XWrap {
         self.set_window_states_atoms(h, &states);
     }
 
-    /// Sets a windows border color.
+    /// Sets a windows border color, in the visual of the window owning the border.
     // `XSetWindowBorder`: https://tronche.com/gui/x/xlib/window/XSetWindowBorder.html
-    pub fn set_window_border_color(&self, window: xlib::Window, mut color: c_ulong) {
-        unsafe {
-            // Force border opacity to 0xff. (color is <aarrggbb> in hex format)
-            color |= 0xff00_0000;
-            (self.xlib.XSetWindowBorder)(self.display, window, color);
+    pub fn set_window_border_color(&self, window: xlib::Window, color: Rgba) {
+        let window = self.frame_of(window);
+        if self.border_colors.borrow_mut().insert(window, color) == Some(color) {
+            return;
         }
+        let pixel = self.get_pixel(window, color);
+        unsafe { (self.xlib.XSetWindowBorder)(self.display, window, pixel) };
     }
 
     pub fn set_background_color(&self, mut color: c_ulong) {
@@ -172,7 +206,8 @@ impl XWrap {
         }
     }
 
-    /// Sets a windows configuration.
+    /// Sets a windows configuration. The request is only sent with the next flush, which the
+    /// event loop does once per iteration.
     pub fn set_window_config(
         &self,
         window: xlib::Window,
@@ -180,7 +215,6 @@ impl XWrap {
         unlock: u32,
     ) {
         unsafe { (self.xlib.XConfigureWindow)(self.display, window, unlock, &mut window_changes) };
-        self.sync();
     }
 
     /// Sets what desktop a window is on.
@@ -192,6 +226,36 @@ impl XWrap {
         self.replace_property_long(window, self.atoms.NetWMDesktop, xlib::XA_CARDINAL, &indexes);
     }
 
+    /// Sets the opacity compositors draw a window with, or lets them use the default.
+    // `XDeleteProperty`: https://tronche.com/gui/x/xlib/window-information/XDeleteProperty.html
+    pub fn set_window_opacity(&mut self, window: xlib::Window, opacity: Option<f32>) {
+        // Compositors read it from the top level window.
+        let top = self.frame_of(window);
+        let opacity = match opacity {
+            Some(opacity) => {
+                if !self.own_opacities.contains_key(&window) {
+                    let own = self.get_window_opacity(top);
+                    self.own_opacities.entry(window).or_insert(own);
+                }
+                Some((opacity.clamp(0.0, 1.0) * u32::MAX as f32) as u32)
+            }
+            // Give the window back the opacity it had before.
+            None => self.own_opacities.remove(&window).flatten(),
+        };
+        let Some(opacity) = opacity else {
+            unsafe {
+                (self.xlib.XDeleteProperty)(self.display, top, self.atoms.NetWMWindowOpacity);
+            }
+            return;
+        };
+        self.replace_property_long(
+            top,
+            self.atoms.NetWMWindowOpacity,
+            xlib::XA_CARDINAL,
+            &[opacity as c_long],
+        );
+    }
+
     /// Sets the atom states of a window.
     pub fn set_window_states_atoms(&self, window: xlib::Window, states: &[xlib::Atom]) {
         let data: Vec<c_long> = states.iter().map(|x| *x as c_long).collect();
@@ -220,5 +284,10 @@ impl XWrap {
     /// Sets the `WM_STATE` of a window.
     pub fn set_wm_states(&self, window: xlib::Window, states: &[c_long]) {
         self.replace_property_long(window, self.atoms.WMState, self.atoms.WMState, states);
+        if let Some(&state) = states.first() {
+            if self.managed_windows.contains(&window) {
+                self.wm_states.borrow_mut().insert(window, state);
+            }
+        }
     }
 }
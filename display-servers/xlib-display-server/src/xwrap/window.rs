@@ -1,7 +1,7 @@
 //! Xlib calls related to a window.
 use super::{
     on_error_from_xlib, on_error_from_xlib_dummy, Window, WindowHandle, ICONIC_STATE, NORMAL_STATE,
-    ROOT_EVENT_MASK, WITHDRAWN_STATE,
+    WITHDRAWN_STATE,
 };
 use crate::{XWrap, XlibWindowHandle};
 use leftwm_core::models::{WindowChange, WindowType, Xyhw, XyhwChange};
@@ -33,6 +33,7 @@ impl XWrap {
         let trans = self.get_transient_for(window);
         let sizing_hint = self.get_hint_sizing_as_xyhw(window);
         let wm_hint = self.get_wmhints(window);
+        let undecorated = self.get_window_undecorated(window);
 
         // Build the new window, and fill in info about it.
         let mut w = Window::new(handle, name, pid);
@@ -43,6 +44,7 @@ impl XWrap {
         w.legacy_name = legacy_name;
         w.r#type = r#type.clone();
         w.states = states;
+        w.undecorated = undecorated;
         if let Some(trans) = trans {
             w.transient = Some(WindowHandle(XlibWindowHandle(trans)));
         }
@@ -142,13 +144,20 @@ impl XWrap {
                 change.r#type = Some(r#type);
                 return Some(DisplayEvent::WindowChange(change));
             }
-        } else {
+        } else if !r#type.is_popup() {
+            // Windows with an alpha channel need their border pixel in their own visual.
+            if let Some(format) = self.get_visual_format(handle) {
+                self.visuals.insert(handle, format);
+            }
             let color = if floating {
                 self.colors.floating
             } else {
                 self.colors.normal
             };
             self.set_window_border_color(handle, color);
+            if self.reparent_windows {
+                self.frame_window(handle);
+            }
 
             if follow_mouse {
                 _ = self.move_cursor_to_window(handle);
@@ -166,6 +175,16 @@ impl XWrap {
     pub fn teardown_managed_window(&mut self, h: &WindowHandle<XlibWindowHandle>, destroyed: bool) {
         let WindowHandle(XlibWindowHandle(handle)) = h;
         self.managed_windows.retain(|x| *x != *handle);
+        self.window_colors.remove(handle);
+        self.visuals.remove(handle);
+        self.own_opacities.remove(handle);
+        self.wm_states.get_mut().remove(handle);
+        let frame = self.frame_of(*handle);
+        let border_colors = self.border_colors.get_mut();
+        border_colors.remove(handle);
+        border_colors.remove(&frame);
+        self.ping_watch.forget(handle);
+        self.unframe_window(*handle, destroyed);
         if !destroyed {
             unsafe {
                 (self.xlib.XGrabServer)(self.display);
@@ -195,8 +214,28 @@ impl XWrap {
             };
             let unlock =
                 xlib::CWX | xlib::CWY | xlib::CWWidth | xlib::CWHeight | xlib::CWBorderWidth;
-            self.set_window_config(handle, changes, u32::from(unlock));
+            if self.frames.contains_key(&handle) {
+                self.set_window_config(self.frame_of(handle), changes, u32::from(unlock));
+                // The title bar takes the top of the frame.
+                let bar = self.title_bar_height(window);
+                self.set_title_bar_of(handle, bar);
+                let inside = xlib::XWindowChanges {
+                    x: 0,
+                    y: bar,
+                    height: (window.height() - bar).max(1),
+                    border_width: 0,
+                    ..changes
+                };
+                self.set_window_config(handle, inside, u32::from(unlock));
+                self.draw_title_bar(handle, handle == self.focused_window);
+            } else {
+                self.set_window_config(handle, changes, u32::from(unlock));
+            }
             self.configure_window(window);
+            // Keep the selection visible on windows without focus.
+            if handle != self.focused_window {
+                self.set_window_border_color(handle, self.unfocused_border_color(window));
+            }
         }
         let Some(state) = self.get_wm_state(handle) else {
             return;
@@ -212,15 +251,14 @@ impl XWrap {
     /// Maps and unmaps a window depending on it is visible.
     pub fn toggle_window_visibility(&self, window: xlib::Window, visible: bool) {
         // We don't want to receive this map or unmap event.
-        let mask_off = ROOT_EVENT_MASK & !(xlib::SubstructureNotifyMask);
-        let mut attrs: xlib::XSetWindowAttributes = unsafe { std::mem::zeroed() };
-        attrs.event_mask = mask_off;
-        self.change_window_attributes(self.root, xlib::CWEventMask, attrs);
+        self.set_root_notify(false);
+        // Framed windows stay mapped inside their frame.
+        let frame = self.frame_of(window);
         if visible {
             // Set WM_STATE to normal state.
             self.set_wm_states(window, &[NORMAL_STATE]);
             // Make sure the window is mapped.
-            unsafe { (self.xlib.XMapWindow)(self.display, window) };
+            unsafe { (self.xlib.XMapWindow)(self.display, frame) };
             // Regrab the mouse clicks but ignore `dock` windows as some don't handle click events put on them
             if self.focus_behaviour.is_clickto() && self.get_window_type(window) != WindowType::Dock
             {
@@ -230,12 +268,11 @@ impl XWrap {
             // Ungrab the mouse clicks.
             self.ungrab_buttons(window);
             // Make sure the window is unmapped.
-            unsafe { (self.xlib.XUnmapWindow)(self.display, window) };
+            unsafe { (self.xlib.XUnmapWindow)(self.display, frame) };
             // Set WM_STATE to iconic state.
             self.set_wm_states(window, &[ICONIC_STATE]);
         }
-        attrs.event_mask = ROOT_EVENT_MASK;
-        self.change_window_attributes(self.root, xlib::CWEventMask, attrs);
+        self.set_root_notify(true);
     }
 
     /// Makes a window take focus.
@@ -248,12 +285,9 @@ impl XWrap {
         // Update previous window.
         if let Some(previous) = previous {
             let WindowHandle(XlibWindowHandle(previous_handle)) = previous.handle;
-            let color = if previous.floating() {
-                self.colors.floating
-            } else {
-                self.colors.normal
-            };
+            let color = self.unfocused_border_color(previous);
             self.set_window_border_color(previous_handle, color);
+            self.draw_title_bar(previous_handle, false);
             // Open up button1 clicking on the previously focused window.
             if self.focus_behaviour.is_clickto() {
                 self.grab_mouse_clicks(previous_handle, false);
@@ -261,8 +295,8 @@ impl XWrap {
         }
         self.focused_window = handle;
         self.grab_mouse_clicks(handle, true);
-        self.set_window_urgency(handle, false);
-        self.set_window_border_color(handle, self.colors.active);
+        self.set_window_border_color(handle, self.colors_of(handle).active);
+        self.draw_title_bar(handle, true);
         self.focus(handle, window.never_focus);
         self.sync();
     }
@@ -270,6 +304,7 @@ impl XWrap {
     /// Focuses a window.
     // `XSetInputFocus`: https://tronche.com/gui/x/xlib/input/XSetInputFocus.html
     pub fn focus(&mut self, window: xlib::Window, never_focus: bool) {
+        self.install_colormaps(window);
         if !never_focus {
             unsafe {
                 (self.xlib.XSetInputFocus)(
@@ -297,15 +332,18 @@ impl XWrap {
     // `XSetInputFocus`: https://tronche.com/gui/x/xlib/input/XSetInputFocus.html
     pub fn unfocus(&self, handle: Option<WindowHandle<XlibWindowHandle>>, floating: bool) {
         if let Some(WindowHandle(XlibWindowHandle(handle))) = handle {
+            let colors = self.colors_of(handle);
             let color = if floating {
-                self.colors.floating
+                colors.floating
             } else {
-                self.colors.normal
+                colors.normal
             };
             self.set_window_border_color(handle, color);
+            self.draw_title_bar(handle, false);
 
             self.grab_mouse_clicks(handle, false);
         }
+        self.install_default_colormap();
         unsafe {
             (self.xlib.XSetInputFocus)(
                 self.display,
@@ -322,6 +360,47 @@ impl XWrap {
         }
     }
 
+    /// Installs the colormaps a window needs: those of the windows in its `WM_COLORMAP_WINDOWS`,
+    /// and its own before them unless it is listed.
+    // `XGetWMColormapWindows`: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/XGetWMColormapWindows.html
+    // `XInstallColormap`: https://tronche.com/gui/x/xlib/color/XInstallColormap.html
+    pub fn install_colormaps(&self, window: xlib::Window) {
+        let mut windows = vec![];
+        unsafe {
+            let mut list: *mut xlib::Window = std::ptr::null_mut();
+            let mut count = 0;
+            let status =
+                (self.xlib.XGetWMColormapWindows)(self.display, window, &mut list, &mut count);
+            if status != 0 && !list.is_null() {
+                let count = usize::try_from(count).unwrap_or_default();
+                windows = std::slice::from_raw_parts(list, count).to_vec();
+                (self.xlib.XFree)(list.cast());
+            }
+        }
+        if !windows.contains(&window) {
+            windows.insert(0, window);
+        }
+        // The first window has the highest priority, so its colormap is installed last.
+        for window in windows.into_iter().rev() {
+            match self.get_window_attrs(window) {
+                Ok(attrs) if attrs.colormap != 0 => unsafe {
+                    (self.xlib.XInstallColormap)(self.display, attrs.colormap);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Installs the default colormap of the screen again.
+    // `XDefaultColormap`: https://tronche.com/gui/x/xlib/display/display-macros.html#DefaultColormap
+    pub fn install_default_colormap(&self) {
+        unsafe {
+            let screen = (self.xlib.XDefaultScreen)(self.display);
+            let colormap = (self.xlib.XDefaultColormap)(self.display, screen);
+            (self.xlib.XInstallColormap)(self.display, colormap);
+        }
+    }
+
     /// Send a `XConfigureEvent` for a window to X.
     pub fn configure_window(&self, window: &Window<XlibWindowHandle>) {
         let WindowHandle(XlibWindowHandle(handle)) = window.handle;
@@ -330,11 +409,18 @@ impl XWrap {
         configure_event.display = self.display;
         configure_event.event = handle;
         configure_event.window = handle;
-        configure_event.x = window.x();
-        configure_event.y = window.y();
+        // Framed windows are told where their inside is, and that they have no border.
+        let (offset, border) = if self.frames.contains_key(&handle) {
+            (window.border(), 0)
+        } else {
+            (0, window.border)
+        };
+        let bar = self.title_bar_of(handle);
+        configure_event.x = window.x() + offset;
+        configure_event.y = window.y() + offset + bar;
         configure_event.width = window.width();
-        configure_event.height = window.height();
-        configure_event.border_width = window.border;
+        configure_event.height = (window.height() - bar).max(1);
+        configure_event.border_width = border;
         configure_event.above = 0;
         configure_event.override_redirect = 0;
         self.send_xevent(
@@ -364,7 +450,7 @@ impl XWrap {
         let mut windows = vec![];
         for handle in handles {
             let WindowHandle(XlibWindowHandle(window)) = handle;
-            windows.push(window);
+            windows.push(self.frame_of(window));
         }
         let size = windows.len();
         let ptr = windows.as_mut_ptr();
@@ -384,7 +470,7 @@ impl XWrap {
     pub fn move_to_top(&self, handle: &WindowHandle<XlibWindowHandle>) {
         let WindowHandle(XlibWindowHandle(window)) = handle;
         unsafe {
-            (self.xlib.XRaiseWindow)(self.display, *window);
+            (self.xlib.XRaiseWindow)(self.display, self.frame_of(*window));
         }
     }
 
@@ -393,20 +479,31 @@ impl XWrap {
     // `XSetCloseDownMode`: https://tronche.com/gui/x/xlib/display/XSetCloseDownMode.html
     // `XKillClient`: https://tronche.com/gui/x/xlib/window-and-session-manager/XKillClient.html
     // `XUngrabServer`: https://tronche.com/gui/x/xlib/window-and-session-manager/XUngrabServer.html
-    pub fn kill_window(&self, h: &WindowHandle<XlibWindowHandle>) {
+    pub fn kill_window(&mut self, h: &WindowHandle<XlibWindowHandle>) {
         let WindowHandle(XlibWindowHandle(handle)) = h;
         // Nicely ask the window to close.
         if !self.send_xevent_atom(*handle, self.atoms.WMDelete) {
-            // Force kill the window.
-            unsafe {
-                (self.xlib.XGrabServer)(self.display);
-                (self.xlib.XSetErrorHandler)(Some(on_error_from_xlib_dummy));
-                (self.xlib.XSetCloseDownMode)(self.display, xlib::DestroyAll);
-                (self.xlib.XKillClient)(self.display, *handle);
-                self.sync();
-                (self.xlib.XSetErrorHandler)(Some(on_error_from_xlib));
-                (self.xlib.XUngrabServer)(self.display);
-            }
+            self.force_kill_window(h);
+            return;
+        }
+        // Find out whether the client is still alive to do so.
+        if self.send_xevent_atom(*handle, self.atoms.NetWMPing) {
+            self.ping_watch.ping(*handle);
+        }
+    }
+
+    /// Kills the client of a window without asking it.
+    pub fn force_kill_window(&mut self, h: &WindowHandle<XlibWindowHandle>) {
+        let WindowHandle(XlibWindowHandle(handle)) = h;
+        self.ping_watch.forget(handle);
+        unsafe {
+            (self.xlib.XGrabServer)(self.display);
+            (self.xlib.XSetErrorHandler)(Some(on_error_from_xlib_dummy));
+            (self.xlib.XSetCloseDownMode)(self.display, xlib::DestroyAll);
+            (self.xlib.XKillClient)(self.display, *handle);
+            self.sync();
+            (self.xlib.XSetErrorHandler)(Some(on_error_from_xlib));
+            (self.xlib.XUngrabServer)(self.display);
         }
     }
 
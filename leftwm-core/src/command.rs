@@ -1,5 +1,6 @@
+use crate::config::{InsertBehavior, Schedule};
 pub use crate::handlers::command_handler::ReleaseScratchPadOption;
-use crate::models::{Handle, ScratchPadName, TagId, WindowHandle};
+use crate::models::{Handle, ScratchPadName, TagId, Window, WindowHandle};
 use leftwm_layouts::geometry::Direction as FocusDirection;
 use serde::{Deserialize, Serialize};
 
@@ -29,6 +30,8 @@ pub enum Command<H: Handle> {
     },
     ToggleScratchPad(ScratchPadName),
     ToggleFullScreen,
+    ToggleFakeFullScreen,
+    TogglePictureInPicture,
     ToggleMaximized,
     ToggleSticky,
     ToggleAbove,
@@ -37,6 +40,8 @@ pub enum Command<H: Handle> {
         swap: bool,
     },
     ReturnToLastTag,
+    /// Show a tag on the focused workspace along with its tag, or stop showing it there.
+    ToggleTag(TagId),
     FloatingToTile,
     TileToFloating,
     ToggleFloating,
@@ -56,12 +61,15 @@ pub enum Command<H: Handle> {
         behavior: FocusDeltaBehavior,
     },
     FocusWindow(String),
+    #[serde(bound = "")]
+    ActivateWindow(WindowSelector<H>),
     FocusWindowUp,
     FocusWindowDown,
     FocusWindowTop {
         swap: bool,
     },
     FocusWindowAt(FocusDirection),
+    FocusPrevious(usize),
     FocusWorkspaceNext,
     FocusWorkspacePrevious,
     SendWindowToTag {
@@ -76,8 +84,12 @@ pub enum Command<H: Handle> {
         follow: bool,
     },
     MoveWindowToLastWorkspace,
-    MoveWindowToNextWorkspace,
-    MoveWindowToPreviousWorkspace,
+    MoveWindowToNextWorkspace {
+        follow: bool,
+    },
+    MoveWindowToPreviousWorkspace {
+        follow: bool,
+    },
     NextLayout,
     PreviousLayout,
     SetLayout(String),
@@ -89,11 +101,173 @@ pub enum Command<H: Handle> {
     IncreaseMainCount(),
     DecreaseMainCount(),
     SetMarginMultiplier(f32),
+    /// Use another modifier as `modkey` until the config is reloaded.
+    SetModKey(String),
+    /// Insert new windows the given way until the config is reloaded.
+    SetInsertBehavior(InsertBehavior),
+    /// Leave out the gaps of all workspaces, or bring them back.
+    ToggleGaps,
     SendWorkspaceToTag(usize, usize),
     CloseAllOtherWindows,
+    /// Kill the client of the focused window without asking it to close.
+    ForceCloseWindow,
+    ToggleSelectWindow,
+    ClearSelection,
+    SendSelectionToTag(TagId),
+    FloatSelection,
+    CloseSelection,
+    GroupWindows,
+    UngroupWindow,
+    NextTab,
+    PreviousTab,
+    MinimizeWindow,
+    RestoreLastMinimized,
+    RestoreAll,
+    StashWindows,
+    UnstashWindows,
+    Schedule {
+        schedule: Schedule,
+        #[serde(bound = "")]
+        command: Box<Command<H>>,
+    },
+    CancelSchedules,
+    /// Clear the urgency of a window, the focused one if none is given.
+    ClearUrgent {
+        #[serde(bound = "")]
+        window: Option<WindowHandle<H>>,
+    },
+    /// Show and focus the window which most recently became urgent.
+    FocusNextUrgent,
+    TogglePresentationMode,
+    /// Move the focused floating window by `floating_step` pixels.
+    MoveFloatingWindow(FocusDirection),
+    ResizeWindowWider,
+    ResizeWindowNarrower,
+    ResizeWindowTaller,
+    ResizeWindowShorter,
+    Execute(String),
     Other(String),
 }
 
+impl<H: Handle> Command<H> {
+    /// The name of the command, without its arguments.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::CloseWindow => "CloseWindow",
+            Self::SwapScreens => "SwapScreens",
+            Self::SoftReload => "SoftReload",
+            Self::HardReload => "HardReload",
+            Self::AttachScratchPad { .. } => "AttachScratchPad",
+            Self::ReleaseScratchPad { .. } => "ReleaseScratchPad",
+            Self::PrevScratchPadWindow { .. } => "PrevScratchPadWindow",
+            Self::NextScratchPadWindow { .. } => "NextScratchPadWindow",
+            Self::ToggleScratchPad(_) => "ToggleScratchPad",
+            Self::ToggleFullScreen => "ToggleFullScreen",
+            Self::ToggleFakeFullScreen => "ToggleFakeFullScreen",
+            Self::TogglePictureInPicture => "TogglePictureInPicture",
+            Self::ToggleMaximized => "ToggleMaximized",
+            Self::ToggleSticky => "ToggleSticky",
+            Self::ToggleAbove => "ToggleAbove",
+            Self::GoToTag { .. } => "GoToTag",
+            Self::ReturnToLastTag => "ReturnToLastTag",
+            Self::ToggleTag(_) => "ToggleTag",
+            Self::FloatingToTile => "FloatingToTile",
+            Self::TileToFloating => "TileToFloating",
+            Self::ToggleFloating => "ToggleFloating",
+            Self::MoveWindowUp => "MoveWindowUp",
+            Self::MoveWindowDown => "MoveWindowDown",
+            Self::MoveWindowTop { .. } => "MoveWindowTop",
+            Self::MoveWindowAt(_) => "MoveWindowAt",
+            Self::SwapWindowTop { .. } => "SwapWindowTop",
+            Self::FocusNextTag { .. } => "FocusNextTag",
+            Self::FocusPreviousTag { .. } => "FocusPreviousTag",
+            Self::FocusWindow(_) => "FocusWindow",
+            Self::ActivateWindow(_) => "ActivateWindow",
+            Self::FocusWindowUp => "FocusWindowUp",
+            Self::FocusWindowDown => "FocusWindowDown",
+            Self::FocusWindowTop { .. } => "FocusWindowTop",
+            Self::FocusWindowAt(_) => "FocusWindowAt",
+            Self::FocusPrevious(_) => "FocusPrevious",
+            Self::FocusWorkspaceNext => "FocusWorkspaceNext",
+            Self::FocusWorkspacePrevious => "FocusWorkspacePrevious",
+            Self::SendWindowToTag { .. } => "SendWindowToTag",
+            Self::MoveWindowToNextTag { .. } => "MoveWindowToNextTag",
+            Self::MoveWindowToPreviousTag { .. } => "MoveWindowToPreviousTag",
+            Self::MoveWindowToLastWorkspace => "MoveWindowToLastWorkspace",
+            Self::MoveWindowToNextWorkspace { .. } => "MoveWindowToNextWorkspace",
+            Self::MoveWindowToPreviousWorkspace { .. } => "MoveWindowToPreviousWorkspace",
+            Self::NextLayout => "NextLayout",
+            Self::PreviousLayout => "PreviousLayout",
+            Self::SetLayout(_) => "SetLayout",
+            Self::RotateTag => "RotateTag",
+            Self::IncreaseMainWidth(_) => "IncreaseMainWidth",
+            Self::DecreaseMainWidth(_) => "DecreaseMainWidth",
+            Self::IncreaseMainSize(_) => "IncreaseMainSize",
+            Self::DecreaseMainSize(_) => "DecreaseMainSize",
+            Self::IncreaseMainCount() => "IncreaseMainCount",
+            Self::DecreaseMainCount() => "DecreaseMainCount",
+            Self::SetMarginMultiplier(_) => "SetMarginMultiplier",
+            Self::SetModKey(_) => "SetModKey",
+            Self::SetInsertBehavior(_) => "SetInsertBehavior",
+            Self::ToggleGaps => "ToggleGaps",
+            Self::SendWorkspaceToTag(..) => "SendWorkspaceToTag",
+            Self::CloseAllOtherWindows => "CloseAllOtherWindows",
+            Self::ForceCloseWindow => "ForceCloseWindow",
+            Self::ToggleSelectWindow => "ToggleSelectWindow",
+            Self::ClearSelection => "ClearSelection",
+            Self::SendSelectionToTag(_) => "SendSelectionToTag",
+            Self::FloatSelection => "FloatSelection",
+            Self::CloseSelection => "CloseSelection",
+            Self::GroupWindows => "GroupWindows",
+            Self::UngroupWindow => "UngroupWindow",
+            Self::NextTab => "NextTab",
+            Self::PreviousTab => "PreviousTab",
+            Self::MinimizeWindow => "MinimizeWindow",
+            Self::RestoreLastMinimized => "RestoreLastMinimized",
+            Self::RestoreAll => "RestoreAll",
+            Self::StashWindows => "StashWindows",
+            Self::UnstashWindows => "UnstashWindows",
+            Self::Schedule { .. } => "Schedule",
+            Self::CancelSchedules => "CancelSchedules",
+            Self::ClearUrgent { .. } => "ClearUrgent",
+            Self::FocusNextUrgent => "FocusNextUrgent",
+            Self::TogglePresentationMode => "TogglePresentationMode",
+            Self::MoveFloatingWindow(_) => "MoveFloatingWindow",
+            Self::ResizeWindowWider => "ResizeWindowWider",
+            Self::ResizeWindowNarrower => "ResizeWindowNarrower",
+            Self::ResizeWindowTaller => "ResizeWindowTaller",
+            Self::ResizeWindowShorter => "ResizeWindowShorter",
+            Self::Execute(_) => "Execute",
+            Self::Other(_) => "Other",
+        }
+    }
+}
+
+/// Identifies the window a command acts on.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum WindowSelector<H: Handle> {
+    Handle(#[serde(bound = "")] WindowHandle<H>),
+    /// Matches either part of `WM_CLASS`.
+    Class(String),
+    Title(String),
+}
+
+impl<H: Handle> WindowSelector<H> {
+    #[must_use]
+    pub fn matches(&self, window: &Window<H>) -> bool {
+        match self {
+            Self::Handle(handle) => &window.handle == handle,
+            Self::Class(class) => {
+                window.res_name.as_ref() == Some(class) || window.res_class.as_ref() == Some(class)
+            }
+            Self::Title(title) => {
+                window.name.as_ref() == Some(title) || window.legacy_name.as_ref() == Some(title)
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub enum FocusDeltaBehavior {
     Default,
@@ -1,14 +1,42 @@
+mod empty_tag_behavior;
+mod floating_placement_behavior;
+mod idle;
 mod insert_behavior;
+mod mousebind;
+mod picture_in_picture;
+mod signal;
+mod tag_overflow_behavior;
+mod tag_overrides;
+mod timer;
+mod title_bar;
+mod urgency_behavior;
+mod wm_replaced_behavior;
 mod workspace_config;
 
 use crate::display_servers::DisplayServer;
-use crate::layouts::LayoutMode;
+use crate::hook::Hook;
+use crate::layouts::{LayoutArranger, LayoutMode};
 pub use crate::models::ScratchPad;
-pub use crate::models::{FocusBehaviour, Gutter, Margins, Size};
-use crate::models::{Handle, Manager, Window, WindowType};
+use crate::models::{FloatingMemory, Handle, Manager, Window, WindowType};
+pub use crate::models::{FocusBehaviour, FocusFollowsWorkspace, Gutter, Margins, Size};
 use crate::state::State;
+use crate::DisplayEvent;
+pub use empty_tag_behavior::EmptyTagBehavior;
+pub use floating_placement_behavior::FloatingPlacementBehavior;
+pub use idle::Idle;
 pub use insert_behavior::InsertBehavior;
 use leftwm_layouts::Layout;
+pub use mousebind::{MouseCommand, Mousebind};
+pub use picture_in_picture::{Corner, PictureInPicture};
+pub use signal::Signal;
+use std::collections::HashMap;
+use std::sync::Arc;
+pub use tag_overflow_behavior::TagOverflowBehavior;
+pub use tag_overrides::TagOverrides;
+pub use timer::{Schedule, Timer};
+pub use title_bar::TitleBar;
+pub use urgency_behavior::UrgencyBehavior;
+pub use wm_replaced_behavior::WmReplacedBehavior;
 pub use workspace_config::Workspace;
 
 pub trait Config {
@@ -20,6 +48,9 @@ pub trait Config {
 
     fn mousekey(&self) -> Vec<String>;
 
+    /// What mouse buttons pressed on windows with modifiers do.
+    fn mousebinds(&self) -> Vec<Mousebind>;
+
     fn create_list_of_scratchpads(&self) -> Vec<ScratchPad>;
 
     fn layouts(&self) -> Vec<String>;
@@ -28,12 +59,90 @@ pub trait Config {
 
     fn layout_mode(&self) -> LayoutMode;
 
+    /// The most windows shown in the stack of the layout named `layout`, the others hiding behind
+    /// the last of them, when limited.
+    fn max_visible_in_stack(&self, layout: &str) -> Option<usize>;
+
+    /// Places windows for the layouts `leftwm_layouts` does not know about.
+    fn layout_arranger(&self) -> Option<Arc<dyn LayoutArranger>> {
+        None
+    }
+
     fn insert_behavior(&self) -> InsertBehavior;
 
+    fn max_windows_per_tag(&self) -> Option<usize>;
+
+    fn tag_overflow_behavior(&self) -> TagOverflowBehavior;
+
+    fn floating_placement_behavior(&self) -> FloatingPlacementBehavior;
+
+    fn picture_in_picture(&self) -> PictureInPicture;
+
+    /// New windows no wider than this are floated, if they are also within `auto_float_max_height`.
+    fn auto_float_max_width(&self) -> Option<i32>;
+
+    /// New windows no higher than this are floated, if they are also within `auto_float_max_width`.
+    fn auto_float_max_height(&self) -> Option<i32>;
+
+    /// Whether resizing a tiled window with the mouse resizes the main column of its layout,
+    /// instead of floating the window.
+    fn resize_tiled_splits(&self) -> bool;
+
+    /// Classes of the windows `StashWindows` hides when no window is selected.
+    fn stash_classes(&self) -> Vec<String>;
+
+    /// What to do when the tag labelled `tag` is shown and its last window goes away.
+    fn empty_tag_behavior(&self, tag: &str) -> EmptyTagBehavior;
+
+    /// Settings replacing the configured ones on the tag labelled `tag`.
+    fn tag_overrides(&self, tag: &str) -> TagOverrides;
+
+    /// When the user is idle, and what to do then.
+    fn idle(&self) -> Idle;
+
+    /// Commands run after a delay or on a schedule.
+    fn timers(&self) -> Vec<Timer>;
+
+    /// How the urgency of windows is cleared.
+    fn urgency_behavior(&self) -> UrgencyBehavior;
+
+    /// Commands, as written for `leftwm-command`, run when the window manager receives a signal.
+    fn signals(&self) -> HashMap<Signal, String>;
+
+    /// Whether docks are hidden and their struts ignored in presentation mode.
+    fn presentation_mode_hides_docks(&self) -> bool;
+
+    /// Pixels floating windows are moved or resized by with the keyboard.
+    fn floating_step(&self) -> i32;
+
+    /// Whether gaps are left out when a workspace shows a single tiled window.
+    fn smart_gaps(&self) -> bool;
+
+    /// Whether windows launched from a terminal hide it until they close.
+    fn swallow(&self) -> bool;
+
     fn single_window_border(&self) -> bool;
 
     fn focus_new_windows(&self) -> bool;
 
+    /// Whether a window caught in a focus loop is marked as never taking focus.
+    fn mark_focus_stealers_never_focus(&self) -> bool;
+
+    /// Whether managed windows are wrapped in a frame window drawing their border.
+    fn reparent_windows(&self) -> bool;
+
+    /// Title bars drawn on the frames of windows, when they are reparented.
+    fn title_bar(&self) -> Option<TitleBar>;
+
+    fn wm_replaced_behavior(&self) -> WmReplacedBehavior;
+
+    /// A TCP address, or a unix socket as `unix:<path>` or an absolute path, to serve runtime
+    /// metrics on.
+    fn metrics_endpoint(&self) -> Option<String>;
+
+    /// Whether to serve an i3 IPC compatible socket, for tools written for i3.
+    fn i3_ipc(&self) -> bool;
+
     fn command_handler<H: Handle, SERVER>(
         command: &str,
         manager: &mut Manager<H, Self, SERVER>,
@@ -42,24 +151,52 @@ pub trait Config {
         SERVER: DisplayServer<H>,
         Self: Sized;
 
+    /// React to a change in the state. Returns whether the display needs a refresh.
+    fn hook_handler<H: Handle, SERVER>(
+        _hook: &Hook<H>,
+        _manager: &mut Manager<H, Self, SERVER>,
+    ) -> bool
+    where
+        SERVER: DisplayServer<H>,
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Whether the event loop should handle `event`, or drop it.
+    fn filter_event<H: Handle>(&self, _event: &DisplayEvent<H>) -> bool {
+        true
+    }
+
     fn always_float(&self) -> bool;
     fn default_width(&self) -> i32;
     fn default_height(&self) -> i32;
     fn border_width(&self) -> i32;
+    /// Border width of urgent windows, the one of other windows without it.
+    fn urgent_border_width(&self) -> Option<i32>;
     fn margin(&self) -> Margins;
     fn workspace_margin(&self) -> Option<Margins>;
+    /// Space reserved around windows for an external decorator, see [`crate::models::dto::Decorations`].
+    fn decoration_frame(&self) -> Option<Margins>;
     fn gutter(&self) -> Option<Vec<Gutter>>;
     fn default_border_color(&self) -> String;
     fn floating_border_color(&self) -> String;
     fn focused_border_color(&self) -> String;
+    fn selected_border_color(&self) -> String;
+    fn urgent_border_color(&self) -> String;
     fn background_color(&self) -> String;
     fn on_new_window_cmd(&self) -> Option<String>;
     fn get_list_of_gutters(&self) -> Vec<Gutter>;
     fn auto_derive_workspaces(&self) -> bool;
     fn disable_tile_drag(&self) -> bool;
     fn disable_window_snap(&self) -> bool;
+    /// Distance in pixels within which the edges of a floating window being dragged stick to the
+    /// edges of workspaces and other floating windows, 0 to disable it.
+    fn floating_snap_distance(&self) -> i32;
     fn sloppy_mouse_follows_focus(&self) -> bool;
     fn create_follows_cursor(&self) -> bool;
+    /// What moves along with the focus when a command focuses another workspace.
+    fn focus_follows_workspace(&self) -> FocusFollowsWorkspace;
     fn reposition_cursor_on_resize(&self) -> bool;
 
     /// Attempt to write current state to a file.
@@ -72,6 +209,29 @@ pub trait Config {
     /// Load saved state if it exists.
     fn load_state<H: Handle>(&self, state: &mut State<H>);
 
+    /// Read the config file again in place, keeping the current theme.
+    ///
+    /// Returns `false` when the config cannot be reloaded in place, for `SoftReload` to restart
+    /// the worker instead.
+    fn reload(&mut self) -> bool {
+        false
+    }
+
+    /// Use `modkey` in place of the configured modkey until the config is read again.
+    ///
+    /// Returns `false` when `modkey` is not a modifier, or the modkey cannot be changed.
+    fn set_modkey(&mut self, _modkey: &str) -> bool {
+        false
+    }
+
+    /// Load the remembered floating geometries, or `None` to not remember them at all.
+    fn load_floating_memory(&self) -> Option<FloatingMemory> {
+        None
+    }
+
+    /// Write the remembered floating geometries, so they outlive the session.
+    fn save_floating_memory(&self, _memory: &FloatingMemory) {}
+
     /// Handle window placement based on `WM_CLASS`
     fn setup_predefined_window<H: Handle>(
         &self,
@@ -82,10 +242,12 @@ pub trait Config {
     fn load_window<H: Handle>(&self, window: &mut Window<H>) {
         if window.r#type == WindowType::Normal {
             window.margin = self.margin();
+            window.frame = self.decoration_frame().unwrap_or_else(|| Margins::new(0));
             window.border = self.border_width();
             window.must_float = self.always_float();
         } else {
             window.margin = Margins::new(0);
+            window.frame = Margins::new(0);
             window.border = 0;
         }
     }
@@ -105,10 +267,30 @@ pub(crate) mod tests {
         pub tags: Vec<String>,
         pub layouts: Vec<String>,
         pub layout_definitions: Vec<Layout>,
+        pub max_visible_in_stack: HashMap<String, usize>,
         pub workspaces: Option<Vec<Workspace>>,
+        /// The workspaces read from the config file on the next reload, when they change.
+        pub reloaded_workspaces: Option<Vec<Workspace>>,
         pub insert_behavior: InsertBehavior,
+        pub max_windows_per_tag: Option<usize>,
+        pub tag_overflow_behavior: TagOverflowBehavior,
+        pub floating_placement_behavior: FloatingPlacementBehavior,
+        pub auto_float_max_width: Option<i32>,
+        pub auto_float_max_height: Option<i32>,
+        pub resize_tiled_splits: bool,
+        pub empty_tag_behavior: EmptyTagBehavior,
+        pub tag_overrides: HashMap<String, TagOverrides>,
+        pub idle: Idle,
+        pub urgency_behavior: UrgencyBehavior,
+        pub presentation_mode_hides_docks: bool,
+        pub floating_step: i32,
         pub border_width: i32,
+        pub urgent_border_width: Option<i32>,
         pub single_window_border: bool,
+        pub wm_replaced_behavior: WmReplacedBehavior,
+        pub mousebinds: Option<Vec<Mousebind>>,
+        pub floating_snap_distance: i32,
+        pub modkey: Option<String>,
     }
 
     impl Config for TestConfig {
@@ -122,7 +304,12 @@ pub(crate) mod tests {
             FocusBehaviour::ClickTo
         }
         fn mousekey(&self) -> Vec<String> {
-            vec!["Mod4".to_owned()]
+            vec![self.modkey.clone().unwrap_or_else(|| "Mod4".to_owned())]
+        }
+        fn mousebinds(&self) -> Vec<Mousebind> {
+            self.mousebinds
+                .clone()
+                .unwrap_or_else(|| Mousebind::defaults(&self.mousekey()))
         }
         fn create_list_of_scratchpads(&self) -> Vec<ScratchPad> {
             vec![]
@@ -136,11 +323,84 @@ pub(crate) mod tests {
         fn layout_mode(&self) -> LayoutMode {
             LayoutMode::Workspace
         }
+        fn max_visible_in_stack(&self, layout: &str) -> Option<usize> {
+            self.max_visible_in_stack.get(layout).copied()
+        }
 
         fn insert_behavior(&self) -> InsertBehavior {
             self.insert_behavior
         }
 
+        fn max_windows_per_tag(&self) -> Option<usize> {
+            self.max_windows_per_tag
+        }
+
+        fn tag_overflow_behavior(&self) -> TagOverflowBehavior {
+            self.tag_overflow_behavior
+        }
+
+        fn floating_placement_behavior(&self) -> FloatingPlacementBehavior {
+            self.floating_placement_behavior
+        }
+
+        fn picture_in_picture(&self) -> PictureInPicture {
+            PictureInPicture::default()
+        }
+
+        fn auto_float_max_width(&self) -> Option<i32> {
+            self.auto_float_max_width
+        }
+
+        fn auto_float_max_height(&self) -> Option<i32> {
+            self.auto_float_max_height
+        }
+
+        fn resize_tiled_splits(&self) -> bool {
+            self.resize_tiled_splits
+        }
+
+        fn stash_classes(&self) -> Vec<String> {
+            vec![]
+        }
+
+        fn empty_tag_behavior(&self, _tag: &str) -> EmptyTagBehavior {
+            self.empty_tag_behavior.clone()
+        }
+
+        fn tag_overrides(&self, tag: &str) -> TagOverrides {
+            self.tag_overrides.get(tag).cloned().unwrap_or_default()
+        }
+
+        fn idle(&self) -> Idle {
+            self.idle.clone()
+        }
+
+        fn timers(&self) -> Vec<Timer> {
+            vec![]
+        }
+
+        fn urgency_behavior(&self) -> UrgencyBehavior {
+            self.urgency_behavior
+        }
+
+        fn signals(&self) -> HashMap<Signal, String> {
+            HashMap::new()
+        }
+
+        fn presentation_mode_hides_docks(&self) -> bool {
+            self.presentation_mode_hides_docks
+        }
+
+        fn floating_step(&self) -> i32 {
+            self.floating_step
+        }
+        fn smart_gaps(&self) -> bool {
+            false
+        }
+        fn swallow(&self) -> bool {
+            false
+        }
+
         fn single_window_border(&self) -> bool {
             self.single_window_border
         }
@@ -148,6 +408,29 @@ pub(crate) mod tests {
         fn focus_new_windows(&self) -> bool {
             false
         }
+
+        fn mark_focus_stealers_never_focus(&self) -> bool {
+            false
+        }
+
+        fn reparent_windows(&self) -> bool {
+            false
+        }
+
+        fn title_bar(&self) -> Option<TitleBar> {
+            None
+        }
+
+        fn wm_replaced_behavior(&self) -> WmReplacedBehavior {
+            self.wm_replaced_behavior
+        }
+
+        fn metrics_endpoint(&self) -> Option<String> {
+            None
+        }
+        fn i3_ipc(&self) -> bool {
+            false
+        }
         fn command_handler<H: Handle, SERVER>(
             command: &str,
             manager: &mut Manager<H, Self, SERVER>,
@@ -175,12 +458,18 @@ pub(crate) mod tests {
         fn border_width(&self) -> i32 {
             self.border_width
         }
+        fn urgent_border_width(&self) -> Option<i32> {
+            self.urgent_border_width
+        }
         fn margin(&self) -> Margins {
             Margins::new(0)
         }
         fn workspace_margin(&self) -> Option<Margins> {
             None
         }
+        fn decoration_frame(&self) -> Option<Margins> {
+            None
+        }
         fn gutter(&self) -> Option<Vec<Gutter>> {
             unimplemented!()
         }
@@ -193,6 +482,12 @@ pub(crate) mod tests {
         fn focused_border_color(&self) -> String {
             unimplemented!()
         }
+        fn selected_border_color(&self) -> String {
+            unimplemented!()
+        }
+        fn urgent_border_color(&self) -> String {
+            unimplemented!()
+        }
         fn background_color(&self) -> String {
             unimplemented!()
         }
@@ -208,12 +503,27 @@ pub(crate) mod tests {
         fn disable_window_snap(&self) -> bool {
             false
         }
-        fn save_state<H: Handle>(&self, _state: &State<H>) {
-            unimplemented!()
+        fn floating_snap_distance(&self) -> i32 {
+            self.floating_snap_distance
         }
+        fn save_state<H: Handle>(&self, _state: &State<H>) {}
         fn load_state<H: Handle>(&self, _state: &mut State<H>) {
             unimplemented!()
         }
+        fn reload(&mut self) -> bool {
+            if let Some(workspaces) = self.reloaded_workspaces.take() {
+                self.workspaces = Some(workspaces);
+            }
+            true
+        }
+        fn set_modkey(&mut self, modkey: &str) -> bool {
+            let valid = crate::utils::modmask_lookup::into_modmask(&[modkey.to_owned()]);
+            if valid.is_empty() {
+                return false;
+            }
+            self.modkey = Some(modkey.to_owned());
+            true
+        }
         fn setup_predefined_window<H: Handle>(
             &self,
             _: &mut State<H>,
@@ -241,6 +551,10 @@ pub(crate) mod tests {
         fn create_follows_cursor(&self) -> bool {
             false
         }
+
+        fn focus_follows_workspace(&self) -> FocusFollowsWorkspace {
+            FocusFollowsWorkspace::Nothing
+        }
     }
 
     #[test]
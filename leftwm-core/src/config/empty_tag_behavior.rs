@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// What to do when the last window of a tag shown on a workspace goes away.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub enum EmptyTagBehavior {
+    /// Keep showing the empty tag.
+    #[default]
+    Nothing,
+    /// Run a shell command.
+    Execute(String),
+    /// Show the most recently shown tag which still has windows, on the focused workspace.
+    GoToLastTag,
+    /// Reset the layout of the tag to the first configured one, undoing any changes to it.
+    ResetLayout,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Where new floating windows without a transient parent are placed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FloatingPlacementBehavior {
+    /// Center new floating windows on the workspace.
+    #[default]
+    Center,
+    /// Place new floating windows in the largest area of the workspace not covered by other
+    /// floating windows, cascading them from the top left when they fit nowhere.
+    Smart,
+}
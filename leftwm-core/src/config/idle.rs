@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// What to do once the user did not touch the keyboard or mouse for a while.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct Idle {
+    /// Seconds without input after which the user is idle. Idle detection is off without it.
+    pub timeout: Option<u32>,
+    /// Shell command run when the user becomes idle, e.g. a screen locker.
+    pub command: Option<String>,
+    /// Shell command run when the user is active again.
+    pub active_command: Option<String>,
+    /// Opacity, from 0 to 1, windows are dimmed to while the user is idle.
+    pub dim_opacity: Option<f32>,
+}
+
+impl Idle {
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+            .filter(|secs| *secs > 0)
+            .map(|secs| Duration::from_secs(secs.into()))
+    }
+
+    #[must_use]
+    pub fn dim_opacity(&self) -> Option<f32> {
+        self.dim_opacity.map(|opacity| opacity.clamp(0.0, 1.0))
+    }
+}
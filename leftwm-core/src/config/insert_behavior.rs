@@ -2,9 +2,15 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum InsertBehavior {
+    /// Insert new windows as the main window.
     Top,
+    /// Append new windows to the end of the stack.
     #[default]
     Bottom,
+    /// Insert new windows before the focused window.
     BeforeCurrent,
+    /// Insert new windows after the focused window.
     AfterCurrent,
+    /// Insert new windows after the tiled window occupying the smallest region.
+    AfterSmallest,
 }
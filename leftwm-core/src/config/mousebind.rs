@@ -0,0 +1,50 @@
+use crate::models::TagId;
+use crate::utils::modmask_lookup::Button;
+use serde::{Deserialize, Serialize};
+
+/// What pressing the button of a [`Mousebind`] on a window does.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseCommand {
+    /// Drag the window, floating it unless `disable_tile_drag` is set.
+    Move,
+    /// Resize the window by dragging its bottom right corner.
+    Resize,
+    /// Ask the window to close, like `CloseWindow`.
+    Close,
+    /// Send the window to a tag, like `SendWindowToTag`.
+    SendToTag(TagId),
+}
+
+/// A mouse button pressed on a window while holding modifiers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Mousebind {
+    /// Modifiers held, e.g. `["Mod4", "Shift"]`.
+    #[serde(default)]
+    pub modifier: Vec<String>,
+    pub button: Button,
+    pub command: MouseCommand,
+}
+
+impl Mousebind {
+    /// Move windows with `mousekey` and the main button and resize them with the secondary one,
+    /// with or without Shift.
+    #[must_use]
+    pub fn defaults(mousekey: &[String]) -> Vec<Self> {
+        let with_shift = || {
+            let mut modifier = mousekey.to_vec();
+            modifier.push("Shift".to_owned());
+            modifier
+        };
+        let bind = |modifier, button, command| Self {
+            modifier,
+            button,
+            command,
+        };
+        vec![
+            bind(mousekey.to_vec(), Button::Main, MouseCommand::Move),
+            bind(with_shift(), Button::Main, MouseCommand::Move),
+            bind(mousekey.to_vec(), Button::Secondary, MouseCommand::Resize),
+            bind(with_shift(), Button::Secondary, MouseCommand::Resize),
+        ]
+    }
+}
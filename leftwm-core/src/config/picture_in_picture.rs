@@ -0,0 +1,56 @@
+use crate::models::{Size, Xyhw, XyhwBuilder};
+use serde::{Deserialize, Serialize};
+
+/// The corner of the workspace windows are pinned to.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Where `TogglePictureInPicture` pins windows, and their size.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PictureInPicture {
+    pub corner: Corner,
+    pub width: Size,
+    pub height: Size,
+}
+
+impl Default for PictureInPicture {
+    fn default() -> Self {
+        Self {
+            corner: Corner::BottomRight,
+            width: Size::Ratio(0.25),
+            height: Size::Ratio(0.25),
+        }
+    }
+}
+
+impl PictureInPicture {
+    /// The geometry of a window pinned to the corner of `area`.
+    #[must_use]
+    pub fn xyhw(&self, area: &Xyhw) -> Xyhw {
+        let w = self.width.into_absolute(area.w());
+        let h = self.height.into_absolute(area.h());
+        let x = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => area.x(),
+            Corner::TopRight | Corner::BottomRight => area.x() + area.w() - w,
+        };
+        let y = match self.corner {
+            Corner::TopLeft | Corner::TopRight => area.y(),
+            Corner::BottomLeft | Corner::BottomRight => area.y() + area.h() - h,
+        };
+        XyhwBuilder {
+            x,
+            y,
+            h,
+            w,
+            ..XyhwBuilder::default()
+        }
+        .into()
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use tokio::signal::unix::SignalKind;
+
+/// A signal the window manager runs a command on, e.g. sent with `pkill -USR1 leftwm-worker`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// `SIGHUP`, running `SoftReload` unless configured otherwise.
+    Hup,
+    /// `SIGUSR1`.
+    Usr1,
+    /// `SIGUSR2`.
+    Usr2,
+}
+
+impl Signal {
+    #[must_use]
+    pub const fn kind(self) -> SignalKind {
+        match self {
+            Self::Hup => SignalKind::hangup(),
+            Self::Usr1 => SignalKind::user_defined1(),
+            Self::Usr2 => SignalKind::user_defined2(),
+        }
+    }
+}
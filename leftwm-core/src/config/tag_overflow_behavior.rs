@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// What to do with a new tiled window once its tag holds `max_windows_per_tag` tiled windows.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TagOverflowBehavior {
+    /// Put the new window on the next tag without windows.
+    #[default]
+    SpillToNextTag,
+    /// Keep the new window on its tag and switch the tag to the `Monocle` layout.
+    Monocle,
+}
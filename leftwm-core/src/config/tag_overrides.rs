@@ -0,0 +1,17 @@
+use crate::models::{BorderColors, Margins};
+use serde::{Deserialize, Serialize};
+
+/// Settings of a tag replacing the configured ones while its windows are arranged, e.g. no gaps
+/// on a tag for videos. Unset settings are left as configured.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct TagOverrides {
+    /// Gaps around the windows.
+    pub margin: Option<Margins>,
+    /// Gaps around the workspace showing the tag.
+    pub workspace_margin: Option<Margins>,
+    pub border_width: Option<i32>,
+    pub border_colors: BorderColors,
+    /// The layout the tag starts with, when layouts are kept per tag.
+    pub layout: Option<String>,
+}
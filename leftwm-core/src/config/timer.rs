@@ -0,0 +1,38 @@
+use crate::utils::scheduler::Cron;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// When a scheduled command runs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// Once, this many seconds later.
+    After(u64),
+    /// Every this many seconds.
+    Every(u64),
+    /// Whenever the local time matches a cron expression: minute, hour, day of the month, month
+    /// and day of the week, e.g. `"0 9-17 * * 1-5"`.
+    Cron(String),
+}
+
+impl Schedule {
+    /// Check the schedule can be followed.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the interval is zero or the cron expression is invalid.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Every(0) => Err("the interval must be at least a second".into()),
+            Self::After(_) | Self::Every(_) => Ok(()),
+            Self::Cron(expression) => Cron::from_str(expression).map(|_| ()),
+        }
+    }
+}
+
+/// A command run on a schedule, from the config.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Timer {
+    /// The command, as written for `leftwm-command`, e.g. `"GoToTag 9"`.
+    pub command: String,
+    pub schedule: Schedule,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Title bars drawn at the top of the frames of windows, showing their title.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TitleBar {
+    /// Height of the bars in pixels.
+    pub height: u16,
+    /// Core X font the title is written with, e.g. `fixed` or an XLFD name.
+    pub font: String,
+    /// Background of the bars of unfocused windows.
+    pub color: String,
+    /// Background of the bar of the focused window.
+    pub focused_color: String,
+    /// Color of the title text.
+    pub text_color: String,
+}
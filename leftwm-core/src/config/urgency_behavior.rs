@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// How the urgency of a window is cleared.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UrgencyBehavior {
+    /// When the window is focused.
+    #[default]
+    ClearOnFocus,
+    /// This many seconds after the window became urgent.
+    ClearAfter(u64),
+    /// Only with the `ClearUrgent` command.
+    Manual,
+}
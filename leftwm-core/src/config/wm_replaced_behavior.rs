@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// What to do when another window manager asks leftwm to step down.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WmReplacedBehavior {
+    /// Exit, leaving the screen to the other window manager.
+    #[default]
+    Exit,
+    /// Take the window manager selection back and keep running.
+    Reacquire,
+}
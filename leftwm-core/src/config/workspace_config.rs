@@ -1,12 +1,71 @@
+use crate::models::BBox;
 use serde::{Deserialize, Serialize};
 
+/// A workspace of the config, on the screen of the `RandR` output it names.
 #[derive(Serialize, Default, Deserialize, Debug, Clone, PartialEq)]
 pub struct Workspace {
+    #[serde(default)]
     pub x: i32,
+    #[serde(default)]
     pub y: i32,
+    #[serde(default)]
     pub height: i32,
+    #[serde(default)]
     pub width: i32,
     pub output: String,
     pub relative: Option<bool>,
     pub layouts: Option<Vec<String>>,
 }
+
+impl Workspace {
+    /// The area of the workspace, given the one of the screen of its output. Workspaces without
+    /// a size cover the whole screen, others are placed relative to it when `relative` is set.
+    #[must_use]
+    pub fn bbox_on(&self, screen: BBox) -> BBox {
+        if self.width <= 0 || self.height <= 0 {
+            return screen;
+        }
+        let mut bbox = BBox {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        };
+        if self.relative.unwrap_or(false) {
+            bbox.add(screen);
+        }
+        bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspaces_without_a_size_should_cover_their_output() {
+        let screen = BBox {
+            x: 1920,
+            y: 0,
+            width: 2560,
+            height: 1440,
+        };
+        let pinned: Workspace = serde_json::from_str(r#"{"output": "DP-1"}"#).unwrap();
+        assert_eq!(pinned.bbox_on(screen), screen);
+
+        let right_half = Workspace {
+            x: 1280,
+            width: 1280,
+            height: 1440,
+            output: "DP-1".to_string(),
+            ..Workspace::default()
+        };
+        let area = BBox {
+            x: 1280,
+            y: 0,
+            width: 1280,
+            height: 1440,
+        };
+        assert_eq!(right_half.bbox_on(screen), area);
+    }
+}
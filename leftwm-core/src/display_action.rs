@@ -1,3 +1,4 @@
+use crate::models::BorderColors;
 use crate::models::Handle;
 use crate::models::TagId;
 use crate::models::Window;
@@ -15,6 +16,10 @@ pub enum DisplayAction<H: Handle> {
     #[serde(bound = "")]
     KillWindow(WindowHandle<H>),
 
+    /// Kill the client of a window which would not close.
+    #[serde(bound = "")]
+    ForceKillWindow(WindowHandle<H>),
+
     /// Get triggered after a new window is discovered and WE are
     /// managing it.
     #[serde(bound = "")]
@@ -84,4 +89,93 @@ pub enum DisplayAction<H: Handle> {
     /// Configure a xlib window.
     #[serde(bound = "")]
     ConfigureXlibWindow(Window<H>),
+
+    /// Take the window manager selection back after another window manager took it.
+    ReacquireWmSelection,
+
+    /// Ask the compositor to show a window with the given opacity, from 0 to 1, or the opacity
+    /// the window asks for.
+    #[serde(bound = "")]
+    SetWindowOpacity(WindowHandle<H>, Option<f32>),
+
+    /// Border colors of a window replacing the configured ones, from the overrides of its tag.
+    #[serde(bound = "")]
+    SetWindowBorderColors(WindowHandle<H>, BorderColors),
+
+    /// Set or clear the urgency hint of a window.
+    #[serde(bound = "")]
+    SetWindowUrgency(WindowHandle<H>, bool),
+
+    /// Set the number and names of the desktops to the labels of the tags.
+    SetDesktopNames(Vec<String>),
+
+    /// Grab the mousebinds of the windows again, after the config changed them.
+    ReloadKeyGrabs,
+}
+
+impl<H: Handle> DisplayAction<H> {
+    /// The name of the action, for logging.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::KillWindow(_) => "KillWindow",
+            Self::ForceKillWindow(_) => "ForceKillWindow",
+            Self::AddedWindow(..) => "AddedWindow",
+            Self::MoveMouseOver(..) => "MoveMouseOver",
+            Self::MoveMouseOverPoint(_) => "MoveMouseOverPoint",
+            Self::SetState(..) => "SetState",
+            Self::SetWindowOrder(_) => "SetWindowOrder",
+            Self::MoveToTop(_) => "MoveToTop",
+            Self::DestroyedWindow(_) => "DestroyedWindow",
+            Self::WindowTakeFocus { .. } => "WindowTakeFocus",
+            Self::Unfocus(..) => "Unfocus",
+            Self::FocusWindowUnderCursor => "FocusWindowUnderCursor",
+            Self::ReplayClick(..) => "ReplayClick",
+            Self::ReadyToResizeWindow(_) => "ReadyToResizeWindow",
+            Self::ReadyToMoveWindow(_) => "ReadyToMoveWindow",
+            Self::SetCurrentTags(_) => "SetCurrentTags",
+            Self::SetWindowTag(..) => "SetWindowTag",
+            Self::NormalMode => "NormalMode",
+            Self::ConfigureXlibWindow(_) => "ConfigureXlibWindow",
+            Self::ReacquireWmSelection => "ReacquireWmSelection",
+            Self::SetWindowOpacity(..) => "SetWindowOpacity",
+            Self::SetWindowBorderColors(..) => "SetWindowBorderColors",
+            Self::SetWindowUrgency(..) => "SetWindowUrgency",
+            Self::SetDesktopNames(_) => "SetDesktopNames",
+            Self::ReloadKeyGrabs => "ReloadKeyGrabs",
+        }
+    }
+
+    /// The window the action is about, if any.
+    #[must_use]
+    pub const fn window(&self) -> Option<&WindowHandle<H>> {
+        match self {
+            Self::KillWindow(h)
+            | Self::ForceKillWindow(h)
+            | Self::AddedWindow(h, ..)
+            | Self::MoveMouseOver(h, _)
+            | Self::SetState(h, ..)
+            | Self::MoveToTop(h)
+            | Self::DestroyedWindow(h)
+            | Self::ReplayClick(h, _)
+            | Self::ReadyToResizeWindow(h)
+            | Self::ReadyToMoveWindow(h)
+            | Self::SetWindowTag(h, _)
+            | Self::SetWindowOpacity(h, _)
+            | Self::SetWindowBorderColors(h, _)
+            | Self::SetWindowUrgency(h, _) => Some(h),
+            Self::WindowTakeFocus { window, .. } | Self::ConfigureXlibWindow(window) => {
+                Some(&window.handle)
+            }
+            Self::Unfocus(h, _) => h.as_ref(),
+            Self::MoveMouseOverPoint(_)
+            | Self::SetWindowOrder(_)
+            | Self::FocusWindowUnderCursor
+            | Self::SetCurrentTags(_)
+            | Self::SetDesktopNames(_)
+            | Self::NormalMode
+            | Self::ReacquireWmSelection
+            | Self::ReloadKeyGrabs => None,
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use super::{models::Screen, models::Window, models::WindowHandle, Button, ModMask};
-use crate::models::{Handle, WindowChange};
+use crate::models::{Handle, Mode, WindowChange};
 use crate::Command;
 
 #[allow(clippy::large_enum_variant)]
@@ -17,7 +17,108 @@ pub enum DisplayEvent<H: Handle> {
     MoveWindow(WindowHandle<H>, i32, i32),
     ResizeWindow(WindowHandle<H>, i32, i32),
     ScreenCreate(Screen<H>),
+    ScreenDestroy(String), // The screen of this output went away.
     SendCommand(Command<H>),
     ConfigureXlibWindow(WindowHandle<H>), // TODO: check if this has backend specific code
-    ChangeToNormalMode,
+    ChangeToNormalMode(i32, i32),         // The pointer was released at this point.
+    MoveResizeRequest(Mode<H>), // A client asked to be moved or resized with the pointer, or to stop.
+    WmReplaced,                 // Another window manager asked to take over.
+    UserIdle,                   // No input for the configured idle timeout.
+    UserActive,                 // Input again after being idle.
+    WindowResponding(WindowHandle<H>, bool), // A client answered a ping late, or not in time.
+    TitleBarPressed(WindowHandle<H>), // The title bar of this window was clicked.
+}
+
+impl<H: Handle> DisplayEvent<H> {
+    /// The name of the event, for logging.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Movement(..) => "Movement",
+            Self::MouseCombo(..) => "MouseCombo",
+            Self::WindowCreate(..) => "WindowCreate",
+            Self::WindowChange(_) => "WindowChange",
+            Self::WindowDestroy(_) => "WindowDestroy",
+            Self::WindowTakeFocus(_) => "WindowTakeFocus",
+            Self::HandleWindowFocus(_) => "HandleWindowFocus",
+            Self::VerifyFocusedAt(_) => "VerifyFocusedAt",
+            Self::MoveFocusTo(..) => "MoveFocusTo",
+            Self::MoveWindow(..) => "MoveWindow",
+            Self::ResizeWindow(..) => "ResizeWindow",
+            Self::ScreenCreate(_) => "ScreenCreate",
+            Self::ScreenDestroy(_) => "ScreenDestroy",
+            Self::SendCommand(_) => "SendCommand",
+            Self::ConfigureXlibWindow(_) => "ConfigureXlibWindow",
+            Self::ChangeToNormalMode(..) => "ChangeToNormalMode",
+            Self::MoveResizeRequest(_) => "MoveResizeRequest",
+            Self::WmReplaced => "WmReplaced",
+            Self::UserIdle => "UserIdle",
+            Self::UserActive => "UserActive",
+            Self::WindowResponding(..) => "WindowResponding",
+            Self::TitleBarPressed(_) => "TitleBarPressed",
+        }
+    }
+
+    /// The window the event is about, if any.
+    #[must_use]
+    pub const fn window(&self) -> Option<&WindowHandle<H>> {
+        match self {
+            Self::Movement(h, ..)
+            | Self::MouseCombo(_, _, h, ..)
+            | Self::WindowDestroy(h)
+            | Self::WindowTakeFocus(h)
+            | Self::HandleWindowFocus(h)
+            | Self::VerifyFocusedAt(h)
+            | Self::MoveWindow(h, ..)
+            | Self::ResizeWindow(h, ..)
+            | Self::ConfigureXlibWindow(h)
+            | Self::WindowResponding(h, _)
+            | Self::TitleBarPressed(h)
+            | Self::MoveResizeRequest(
+                Mode::ReadyToMove(h)
+                | Mode::ReadyToResize(h)
+                | Mode::MovingWindow(h)
+                | Mode::ResizingWindow(h),
+            ) => Some(h),
+            Self::WindowCreate(window, ..) => Some(&window.handle),
+            Self::WindowChange(change) => Some(&change.handle),
+            Self::MoveFocusTo(..)
+            | Self::ScreenCreate(_)
+            | Self::ScreenDestroy(_)
+            | Self::SendCommand(_)
+            | Self::ChangeToNormalMode(..)
+            | Self::MoveResizeRequest(Mode::Normal)
+            | Self::WmReplaced
+            | Self::UserIdle
+            | Self::UserActive => None,
+        }
+    }
+
+    /// Whether the event is one sent many times a second, as the pointer moves or drags windows
+    /// and clients configure themselves.
+    #[must_use]
+    pub const fn is_frequent(&self) -> bool {
+        matches!(
+            self,
+            Self::Movement(..)
+                | Self::MoveFocusTo(..)
+                | Self::VerifyFocusedAt(_)
+                | Self::MoveWindow(..)
+                | Self::ResizeWindow(..)
+                | Self::ConfigureXlibWindow(_)
+        )
+    }
+
+    /// Whether the event can be dropped without leaving the state out of sync with the display
+    /// server: key and mouse bindings, and changes to the properties of windows.
+    #[must_use]
+    pub const fn is_droppable(&self) -> bool {
+        matches!(
+            self,
+            Self::SendCommand(_)
+                | Self::MouseCombo(..)
+                | Self::TitleBarPressed(_)
+                | Self::WindowChange(_)
+        )
+    }
 }
@@ -10,6 +10,7 @@ use crate::models::Workspace;
 use crate::DisplayEvent;
 
 use futures::prelude::*;
+use std::collections::BTreeMap;
 use std::pin::Pin;
 
 #[cfg(test)]
@@ -39,5 +40,10 @@ pub trait DisplayServer<H: Handle> {
 
     fn flush(&self);
 
+    /// Number of errors the display server ran into so far, by category.
+    fn error_counts(&self) -> BTreeMap<String, usize> {
+        BTreeMap::new()
+    }
+
     fn generate_verify_focus_event(&self) -> Option<DisplayEvent<H>>;
 }
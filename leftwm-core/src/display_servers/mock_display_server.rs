@@ -37,6 +37,5 @@ impl<H: Handle> DisplayServer<H> for MockDisplayServer<H> {
         _focused: Option<crate::models::WindowHandle<H>>,
         _windows: &[crate::Window<H>],
     ) {
-        unimplemented!()
     }
 }
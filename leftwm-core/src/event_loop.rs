@@ -1,12 +1,21 @@
-use crate::models::Handle;
-use crate::{child_process::Nanny, config::Config};
+use crate::hook::{Hook, Snapshot};
+use crate::models::dto::Decorations;
+use crate::models::{Handle, WindowHandle};
+use crate::utils::command_pipe;
+use crate::utils::i3_ipc;
+use crate::utils::loop_watchdog::{LoopWatchdog, Summary, Task, SLOW_THRESHOLD, STALL_THRESHOLD};
+use crate::utils::metrics::{self, Metrics};
+use crate::utils::sd_notify;
+use crate::utils::signals::Signals;
+use crate::{
+    child_process::{self, Nanny},
+    config::Config,
+};
 use crate::{
     Command, CommandPipe, DisplayEvent, DisplayServer, Manager, Mode, StateSocket, Window,
 };
 use std::path::{Path, PathBuf};
-use std::sync::{atomic::Ordering, Once};
-
-use tracing::error;
+use std::sync::{atomic::Ordering, Arc, Mutex, Once};
 
 /// Errors which can appear while running the event loop.
 #[derive(thiserror::Error, Debug, Clone, PartialEq, Eq, Hash)]
@@ -16,6 +25,9 @@ pub enum Error {
 
     #[error("Couldn't connect to file: '{0}'")]
     ConnectToFile(PathBuf),
+
+    #[error("Another window manager took over")]
+    Replaced,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -32,23 +44,56 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     pub async fn start_event_loop(mut self) -> Result<(), Error> {
         let state_socket = get_state_socket().await?;
         let command_pipe = get_command_pipe().await?;
+        let signals = Signals::new(self.config.signals());
+        let mut metrics_endpoint =
+            get_metrics_endpoint(self.config.metrics_endpoint(), self.metrics.clone()).await;
+        if self.config.i3_ipc() {
+            self.i3_ipc = get_i3_ipc_server().await;
+        }
 
         self.call_up_scripts();
+        self.start_timers();
         tracing::info!("LeftWM-core booted!");
-        self.event_loop(state_socket, command_pipe).await
+        sd_notify::notify("READY=1");
+        let result = self
+            .event_loop(state_socket, None, command_pipe, signals)
+            .await;
+        sd_notify::notify("STOPPING=1");
+        if let Some(endpoint) = &mut metrics_endpoint {
+            endpoint.shutdown().await;
+        }
+        if let Some(server) = &mut self.i3_ipc {
+            server.shutdown().await;
+        }
+        result
     }
 
     async fn event_loop(
         &mut self,
         mut state_socket: StateSocket,
+        mut decoration_socket: Option<StateSocket>,
         mut command_pipe: CommandPipe<H>,
+        mut signals: Signals<H>,
     ) -> Result<(), Error> {
         let after_first_loop: Once = Once::new();
         let mut event_buffer: Vec<DisplayEvent<H>> = vec![];
-        while self.should_keep_running(&mut state_socket).await {
-            self.update_manager_state(&mut state_socket).await;
+        let watchdog = LoopWatchdog::spawn(STALL_THRESHOLD);
+        // Pinged from the loop itself, so systemd notices when it is stuck.
+        let mut systemd_watchdog = sd_notify::watchdog_interval().map(tokio::time::interval);
+        while self
+            .should_keep_running(&mut state_socket, &mut decoration_socket)
+            .await
+        {
+            let task = loop_task("manager", "state update");
+            watchdog.begin(task.clone());
+            self.update_manager_state(&mut state_socket, &mut decoration_socket)
+                .await;
+            self.report_slow_work(watchdog.end(), &task);
             self.display_server.flush();
+            watchdog.set_summary(self.state_summary());
 
+            // Only frequent events that changed nothing on screen leave the hooks out.
+            let mut hooks_due = true;
             let response: EventResponse = tokio::select! {
                 () = self.display_server.wait_readable(), if event_buffer.is_empty() => {
                     self.add_events(&mut event_buffer);
@@ -63,16 +108,43 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
                         self.refresh_focus(&mut event_buffer);
                         continue;
                     }
-                Some::<Command<H>>(cmd) = command_pipe.read_command(), if event_buffer.is_empty() => self.execute_command(&cmd),
-                else => self.execute_display_events(&mut event_buffer),
+                () = systemd_watchdog_tick(&mut systemd_watchdog) => {
+                    sd_notify::notify("WATCHDOG=1");
+                    continue;
+                }
+                Some::<Command<H>>(cmd) = command_pipe.read_command(), if event_buffer.is_empty() => self.execute_command(&cmd, &watchdog),
+                Some(request) = next_i3_request(&mut self.i3_ipc), if event_buffer.is_empty() => self.execute_i3_request(request, &watchdog),
+                Some(cmd) = self.scheduler.next_command(), if event_buffer.is_empty() => self.execute_command(&cmd, &watchdog),
+                Some(cmd) = signals.next_command(), if event_buffer.is_empty() => self.execute_command(&cmd, &watchdog),
+                else => {
+                    hooks_due = !event_buffer.iter().all(DisplayEvent::is_frequent);
+                    self.execute_display_events(&mut event_buffer, &watchdog)
+                }
             };
+            hooks_due = hooks_due || response == EventResponse::DisplayRefreshNeeded;
 
             match response {
                 EventResponse::None => (),
-                EventResponse::DisplayRefreshNeeded => self.refresh_display(),
-            };
+                EventResponse::DisplayRefreshNeeded => {
+                    self.watched(
+                        &watchdog,
+                        &loop_task("display", "refresh"),
+                        Self::refresh_display,
+                    );
+                }
+            }
 
-            self.execute_actions(&mut event_buffer);
+            self.execute_actions(&mut event_buffer, &watchdog);
+
+            if hooks_due && self.watched(&watchdog, &loop_task("config", "hooks"), Self::run_hooks)
+            {
+                self.watched(
+                    &watchdog,
+                    &loop_task("display", "refresh"),
+                    Self::refresh_display,
+                );
+                self.execute_actions(&mut event_buffer, &watchdog);
+            }
 
             // We need to run once through all of the loop to properly initialize the state
             // before we can restore the previous state
@@ -83,32 +155,99 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             if self.reap_requested.swap(false, Ordering::SeqCst) {
                 self.children.remove_finished_children();
             }
+
+            self.save_floating_memory();
         }
 
+        // Windows still floating are remembered where they are now.
+        let floating: Vec<_> = self
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.floating())
+            .map(|w| w.handle)
+            .collect();
+        for handle in &floating {
+            self.state.remember_floating(handle);
+        }
+        self.save_floating_memory();
+
+        if self.replaced {
+            return Err(Error::Replaced);
+        }
         Ok(())
     }
 
-    async fn update_manager_state(&self, state_socket: &mut StateSocket) {
+    async fn update_manager_state(
+        &mut self,
+        state_socket: &mut StateSocket,
+        decoration_socket: &mut Option<StateSocket>,
+    ) {
+        // Window frames are only served while the theme leaves room for decorations.
+        if self.state.decorated != decoration_socket.is_some() {
+            match decoration_socket.take() {
+                Some(mut socket) => socket.shutdown().await,
+                None => *decoration_socket = Some(get_decoration_socket().await),
+            }
+        }
         if self.state.mode == Mode::Normal {
+            self.state.display_server_errors = self.display_server.error_counts();
             state_socket.write_manager_state(&self.state).await.ok();
+            if let Some(socket) = decoration_socket {
+                let decorations: Decorations<H> = (&self.state).into();
+                socket.write(&decorations).await.ok();
+            }
         }
+        let (windows, errors) = (&self.state.windows, &self.state.display_server_errors);
+        self.record_metrics(|metrics| metrics.set_state(windows.len(), errors));
     }
 
-    async fn should_keep_running(&self, state_socket: &mut StateSocket) -> bool {
-        if self.reload_requested {
+    async fn should_keep_running(
+        &self,
+        state_socket: &mut StateSocket,
+        decoration_socket: &mut Option<StateSocket>,
+    ) -> bool {
+        if self.reload_requested || self.replaced {
             state_socket.shutdown().await;
+            if let Some(socket) = decoration_socket {
+                socket.shutdown().await;
+            }
             false
         } else {
             true
         }
     }
 
-    fn execute_display_events(&mut self, event_buffer: &mut Vec<DisplayEvent<H>>) -> EventResponse {
+    fn execute_display_events(
+        &mut self,
+        event_buffer: &mut Vec<DisplayEvent<H>>,
+        watchdog: &LoopWatchdog<H>,
+    ) -> EventResponse {
         let mut display_needs_refresh = false;
 
-        event_buffer.drain(..).for_each(|event: DisplayEvent<H>| {
+        for event in prioritize_events(std::mem::take(event_buffer)) {
+            if !self.config.filter_event(&event) {
+                continue;
+            }
+            let window = event.window().copied();
+            let _span = tracing::debug_span!(
+                "display_event",
+                kind = event.name(),
+                ?window,
+                tag = ?window.and_then(|h| self.state.windows.iter().find(|w| w.handle == h)?.tag),
+            )
+            .entered();
+            let kind = event.name();
+            self.record_metrics(|metrics| metrics.record_event(kind));
+            let task = Task {
+                what: "event",
+                kind: kind.into(),
+                window,
+            };
+            watchdog.begin(task.clone());
             display_needs_refresh = self.display_event_handler(event) || display_needs_refresh;
-        });
+            self.report_slow_work(watchdog.end(), &task);
+        }
 
         if display_needs_refresh {
             EventResponse::DisplayRefreshNeeded
@@ -118,30 +257,110 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     }
 
     fn refresh_display(&mut self) {
+        self.record_metrics(Metrics::record_relayout);
         self.update_windows();
 
         match self.state.mode {
-            // When (resizing / moving) only deal with the single window.
-            Mode::ResizingWindow(h) | Mode::MovingWindow(h) => {
-                if let Some(window) = self.state.windows.iter().find(|w| w.handle == h) {
+            // When (resizing / moving) only deal with the single window, unless resizing the
+            // main column moves all windows of the layout.
+            Mode::ResizingWindow(h) | Mode::MovingWindow(h)
+                if self.state.split_resize.is_none() =>
+            {
+                if let Some(window) = self.state.windows.iter_mut().find(|w| w.handle == h) {
+                    window.mark_applied();
                     self.display_server.update_windows(vec![window]);
                 }
             }
             _ => {
-                let windows: Vec<&Window<H>> = self.state.windows.iter().collect();
+                // Leave out the windows which did not change since they were last sent.
+                let changed: Vec<bool> = self
+                    .state
+                    .windows
+                    .iter_mut()
+                    .map(Window::mark_applied)
+                    .collect();
+                let windows: Vec<&Window<H>> = self
+                    .state
+                    .windows
+                    .iter()
+                    .zip(changed)
+                    .filter_map(|(window, changed)| changed.then_some(window))
+                    .collect();
                 self.display_server.update_windows(windows);
             }
         }
     }
 
-    fn execute_command(&mut self, command: &Command<H>) -> EventResponse {
-        if self.command_handler(command) {
+    fn execute_command(
+        &mut self,
+        command: &Command<H>,
+        watchdog: &LoopWatchdog<H>,
+    ) -> EventResponse {
+        let _span = tracing::debug_span!("command", ?command).entered();
+        let task = Task {
+            what: "command",
+            kind: command.name().into(),
+            window: None,
+        };
+        watchdog.begin(task.clone());
+        let needs_refresh = self.command_handler(command);
+        let elapsed = watchdog.end();
+        self.record_metrics(|metrics| metrics.record_command(elapsed));
+        self.report_slow_work(elapsed, &task);
+        if needs_refresh {
             EventResponse::DisplayRefreshNeeded
         } else {
             EventResponse::None
         }
     }
 
+    fn execute_i3_request(
+        &mut self,
+        request: i3_ipc::Request,
+        watchdog: &LoopWatchdog<H>,
+    ) -> EventResponse {
+        let _span = tracing::debug_span!("i3_ipc", kind = request.kind).entered();
+        let task = Task {
+            what: "i3 IPC request",
+            kind: request.kind.to_string().into(),
+            window: None,
+        };
+        watchdog.begin(task.clone());
+        let needs_refresh = self.i3_ipc_handler(request);
+        self.report_slow_work(watchdog.end(), &task);
+        if needs_refresh {
+            EventResponse::DisplayRefreshNeeded
+        } else {
+            EventResponse::None
+        }
+    }
+
+    /// Call the config's hook handler for the changes since the last time.
+    fn run_hooks(&mut self) -> bool {
+        let snapshot = Snapshot::capture(&self.state);
+        let hooks = self
+            .hook_snapshot
+            .replace(snapshot.clone())
+            .map(|last| last.changes(&snapshot))
+            .unwrap_or_default();
+        self.send_i3_events(&hooks);
+        let mut needs_refresh = false;
+        for hook in hooks {
+            let _span = tracing::debug_span!("hook", ?hook).entered();
+            match hook {
+                Hook::TagEmptied { workspace, tag } => {
+                    needs_refresh = self.empty_tag_handler(workspace, tag) || needs_refresh;
+                }
+                Hook::UserIdle | Hook::UserActive => {
+                    needs_refresh = self.idle_handler(hook == Hook::UserIdle) || needs_refresh;
+                }
+                _ => {}
+            }
+            needs_refresh = C::hook_handler(&hook, self) || needs_refresh;
+        }
+        needs_refresh
+    }
+
     fn add_events(&mut self, event_buffer: &mut Vec<DisplayEvent<H>>) -> EventResponse {
         event_buffer.append(&mut self.display_server.get_next_events());
         EventResponse::None
@@ -155,12 +374,91 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     }
 
     // Perform any actions requested by the handler.
-    fn execute_actions(&mut self, event_buffer: &mut Vec<DisplayEvent<H>>) {
+    fn execute_actions(
+        &mut self,
+        event_buffer: &mut Vec<DisplayEvent<H>>,
+        watchdog: &LoopWatchdog<H>,
+    ) {
         while !self.state.actions.is_empty() {
             if let Some(act) = self.state.actions.pop_front() {
+                let window = act.window().copied();
+                let _span =
+                    tracing::debug_span!("display_action", kind = act.name(), ?window).entered();
+                let task = Task {
+                    what: "action",
+                    kind: act.name().into(),
+                    window,
+                };
+                watchdog.begin(task.clone());
                 if let Some(event) = self.display_server.execute_action(act) {
                     event_buffer.push(event);
                 }
+                self.report_slow_work(watchdog.end(), &task);
+            }
+        }
+    }
+
+    /// Write the remembered floating geometries, if they changed.
+    fn save_floating_memory(&mut self) {
+        if let Some(memory) = &mut self.state.floating_memory {
+            if memory.take_changed() {
+                self.config.save_floating_memory(memory);
+            }
+        }
+    }
+
+    fn record_metrics(&self, record: impl FnOnce(&mut Metrics)) {
+        if let Ok(mut metrics) = self.metrics.lock() {
+            record(&mut metrics);
+        }
+    }
+
+    /// Run `work` as a task of the watchdog.
+    fn watched<T>(
+        &mut self,
+        watchdog: &LoopWatchdog<H>,
+        task: &Task<H>,
+        work: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        watchdog.begin(task.clone());
+        let result = work(self);
+        self.report_slow_work(watchdog.end(), task);
+        result
+    }
+
+    fn report_slow_work(&self, elapsed: std::time::Duration, task: &Task<H>) {
+        if elapsed >= SLOW_THRESHOLD {
+            tracing::warn!(
+                "Handling {} took {:?} ({})",
+                task,
+                elapsed,
+                self.state_summary()
+            );
+        }
+    }
+
+    /// A short description of the state, for diagnostics.
+    fn state_summary(&self) -> Summary<H> {
+        Summary {
+            windows: self.state.windows.len(),
+            workspaces: self.state.workspaces.len(),
+            focused: self
+                .state
+                .focus_manager
+                .window(&self.state.windows)
+                .map(|w| w.handle),
+            mode: self.state.mode,
+        }
+    }
+
+    /// Schedule the commands of the configured timers.
+    fn start_timers(&mut self) {
+        for timer in self.config.timers() {
+            let scheduled = command_pipe::parse_command(&timer.command)
+                .map_err(|err| err.to_string())
+                .and_then(|command| self.scheduler.schedule(&timer.schedule, command, true));
+            if let Err(err) = scheduled {
+                tracing::error!("Unable to schedule `{}`: {}", timer.command, err);
             }
         }
     }
@@ -196,6 +494,40 @@ async fn get_state_socket() -> Result<StateSocket, Error> {
     Ok(state_socket)
 }
 
+/// Serves the window frames to external decorators. Failing to do so is not fatal.
+async fn get_decoration_socket() -> StateSocket {
+    let mut decoration_socket = StateSocket::default();
+    let socket_filename = Path::new("current_decorations.sock");
+    match place_runtime_file(socket_filename) {
+        Ok(socket_file) => {
+            if let Err(err) = decoration_socket.listen(socket_file).await {
+                tracing::warn!("Unable to serve window decorations: {}", err);
+            }
+        }
+        Err(err) => tracing::warn!("Unable to serve window decorations: {}", err),
+    }
+    decoration_socket
+}
+
+/// Serves i3 IPC clients, who find the socket through `I3SOCK`, set for the programs leftwm
+/// runs. Failing to do so is not fatal.
+async fn get_i3_ipc_server() -> Option<i3_ipc::Server> {
+    let socket_file = place_runtime_file("i3-ipc.sock")
+        .map_err(|err| tracing::warn!("Unable to serve i3 IPC: {}", err))
+        .ok()?;
+    match i3_ipc::Server::listen(socket_file).await {
+        Ok(server) => {
+            let socket_file = server.socket_file().into();
+            child_process::set_child_env("I3SOCK", Some(socket_file));
+            Some(server)
+        }
+        Err(err) => {
+            tracing::warn!("Unable to serve i3 IPC: {}", err);
+            None
+        }
+    }
+}
+
 async fn get_command_pipe<H: Handle>() -> Result<CommandPipe<H>, Error> {
     let file_name = crate::pipe_name();
 
@@ -207,6 +539,21 @@ async fn get_command_pipe<H: Handle>() -> Result<CommandPipe<H>, Error> {
         .map_err(|_| Error::ConnectToFile(file_name))
 }
 
+/// Serves the metrics if an endpoint is configured. Failing to do so is not fatal.
+async fn get_metrics_endpoint(
+    address: Option<String>,
+    metrics: Arc<Mutex<Metrics>>,
+) -> Option<metrics::Endpoint> {
+    let address = address?;
+    match metrics::Endpoint::listen(&address, metrics).await {
+        Ok(endpoint) => Some(endpoint),
+        Err(err) => {
+            tracing::warn!("Unable to serve metrics on {}: {}", address, err);
+            None
+        }
+    }
+}
+
 fn place_runtime_file<P>(path: P) -> std::io::Result<PathBuf>
 where
     P: AsRef<Path>,
@@ -218,3 +565,152 @@ async fn timeout(mills: u64) {
     use tokio::time::{sleep, Duration};
     sleep(Duration::from_millis(mills)).await;
 }
+
+/// The next request of an i3 IPC client, forever without a server.
+async fn next_i3_request(server: &mut Option<i3_ipc::Server>) -> Option<i3_ipc::Request> {
+    match server {
+        Some(server) => server.next_request().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for the next systemd watchdog ping, forever if the service has no watchdog.
+async fn systemd_watchdog_tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Reorders a batch of display events so screen changes and window destructions are handled
+/// before the bulk of property updates, and merges consecutive changes to the same window.
+///
+/// Events of a window created within the batch keep their order, so it is never destroyed
+/// before it is created.
+fn prioritize_events<H: Handle>(events: Vec<DisplayEvent<H>>) -> Vec<DisplayEvent<H>> {
+    let created: Vec<WindowHandle<H>> = events
+        .iter()
+        .filter_map(|e| match e {
+            DisplayEvent::WindowCreate(window, _, _) => Some(window.handle),
+            _ => None,
+        })
+        .collect();
+    let destroyed: Vec<WindowHandle<H>> = events
+        .iter()
+        .filter_map(|e| match e {
+            DisplayEvent::WindowDestroy(handle) if !created.contains(handle) => Some(*handle),
+            _ => None,
+        })
+        .collect();
+
+    let mut screens = vec![];
+    let mut destroys = vec![];
+    let mut rest: Vec<DisplayEvent<H>> = vec![];
+    for event in events {
+        match event {
+            DisplayEvent::ScreenCreate(_) | DisplayEvent::ScreenDestroy(_) => screens.push(event),
+            DisplayEvent::WindowDestroy(handle) if destroyed.contains(&handle) => {
+                destroys.push(event);
+            }
+            // Updates to a window that is about to be destroyed are of no use.
+            DisplayEvent::WindowChange(change) if destroyed.contains(&change.handle) => {}
+            DisplayEvent::WindowChange(change) => match rest.last_mut() {
+                Some(DisplayEvent::WindowChange(last)) if last.handle == change.handle => {
+                    last.merge(change);
+                }
+                _ => rest.push(DisplayEvent::WindowChange(change)),
+            },
+            _ => rest.push(event),
+        }
+    }
+    screens.append(&mut destroys);
+    screens.append(&mut rest);
+    screens
+}
+
+/// A task of the event loop itself, such as `display refresh`.
+fn loop_task<H: Handle>(what: &'static str, kind: &'static str) -> Task<H> {
+    Task {
+        what,
+        kind: kind.into(),
+        window: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MockHandle, Screen, WindowChange};
+
+    fn change(
+        handle: MockHandle,
+        urgent: Option<bool>,
+        name: Option<&str>,
+    ) -> DisplayEvent<MockHandle> {
+        let mut change = WindowChange::new(WindowHandle(handle));
+        change.urgent = urgent;
+        change.name = name.map(|n| Some(n.to_string()));
+        DisplayEvent::WindowChange(change)
+    }
+
+    #[test]
+    fn screen_and_destroy_events_should_preempt_window_changes() {
+        let events = vec![
+            change(1, Some(true), None),
+            DisplayEvent::WindowDestroy(WindowHandle(2)),
+            DisplayEvent::ScreenCreate(Screen::default()),
+        ];
+        let events = prioritize_events(events);
+        assert!(matches!(events[0], DisplayEvent::ScreenCreate(_)));
+        assert!(matches!(
+            events[1],
+            DisplayEvent::WindowDestroy(WindowHandle(2))
+        ));
+        assert!(matches!(events[2], DisplayEvent::WindowChange(_)));
+    }
+
+    #[test]
+    fn consecutive_changes_to_a_window_should_be_merged() {
+        let events = vec![
+            change(1, Some(true), Some("old")),
+            change(1, None, Some("new")),
+            change(2, Some(true), None),
+            change(1, Some(false), None),
+        ];
+        let events = prioritize_events(events);
+        assert_eq!(events.len(), 3);
+        let DisplayEvent::WindowChange(merged) = &events[0] else {
+            panic!("expected a window change");
+        };
+        assert_eq!(merged.urgent, Some(true));
+        assert_eq!(merged.name, Some(Some("new".to_string())));
+    }
+
+    #[test]
+    fn changes_to_destroyed_windows_should_be_dropped() {
+        let events = vec![
+            change(1, Some(true), None),
+            change(2, Some(true), None),
+            DisplayEvent::WindowDestroy(WindowHandle(1)),
+        ];
+        let events = prioritize_events(events);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0],
+            DisplayEvent::WindowDestroy(WindowHandle(1))
+        ));
+    }
+
+    #[test]
+    fn events_of_windows_created_in_the_batch_should_keep_their_order() {
+        let events = vec![
+            DisplayEvent::WindowCreate(Window::new(WindowHandle(1), None, None), 0, 0),
+            DisplayEvent::WindowDestroy(WindowHandle(1)),
+        ];
+        let events = prioritize_events(events);
+        assert!(matches!(events[0], DisplayEvent::WindowCreate(..)));
+        assert!(matches!(events[1], DisplayEvent::WindowDestroy(_)));
+    }
+}
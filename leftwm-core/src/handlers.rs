@@ -1,13 +1,20 @@
 pub mod command_handler;
 pub mod display_event_handler;
+mod empty_tag_handler;
 mod focus_handler;
 mod goto_tag_handler;
+mod i3_ipc_handler;
+mod idle_handler;
 mod mouse_combo_handler;
 mod screen_create_handler;
+mod swallow_handler;
+mod urgency_handler;
 mod window_handler;
 mod window_move_handler;
 mod window_resize_handler;
 
+pub(crate) use swallow_handler::{ProcessLookup, SWALLOWED_TAG};
+
 use super::command::Command;
 use super::config::Config;
 use super::models::{
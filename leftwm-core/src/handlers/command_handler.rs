@@ -1,18 +1,27 @@
 #![allow(clippy::wildcard_imports)]
 
+mod container_handler;
+mod minimize_handler;
 mod scratchpad_handler;
+mod selection_handler;
+mod stash_handler;
 
 use leftwm_layouts::geometry::{Direction as FocusDirection, Rect};
 // Make public to the rest of the crate without exposing other internal
 // details of the scratchpad handling code
+pub(crate) use container_handler::prune_containers;
+pub(crate) use minimize_handler::MINIMIZED_TAG;
 pub use scratchpad_handler::{Direction, ReleaseScratchPadOption};
+pub(crate) use stash_handler::STASHED_TAG;
 
 use super::*;
-use crate::command::FocusDeltaBehavior;
+use crate::child_process::exec_shell;
+use crate::command::{FocusDeltaBehavior, WindowSelector};
+use crate::config::Schedule;
 use crate::display_action::DisplayAction;
 use crate::display_servers::DisplayServer;
 use crate::layouts::{self, MAIN_AND_DECK, MONOCLE};
-use crate::models::{Handle, TagId, WindowState};
+use crate::models::{FocusFollowsWorkspace, Handle, PinnedFrom, TagId, WindowState};
 use crate::state::State;
 use crate::utils::helpers;
 use crate::utils::helpers::relative_find;
@@ -29,7 +38,12 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
      *  */
     /// Processes a command and invokes the associated function.
     pub fn command_handler(&mut self, command: &Command<H>) -> bool {
-        process_internal(self, command).unwrap_or(false)
+        let needs_refresh = process_internal(self, command).unwrap_or(false);
+        // Which tags are shown and their layouts only change by commands, and with them borders.
+        if needs_refresh {
+            self.state.handle_single_border();
+        }
+        needs_refresh
     }
 }
 
@@ -48,6 +62,7 @@ macro_rules! move_focus_common_vars {
     }};
 }
 
+#[allow(clippy::too_many_lines)]
 fn process_internal<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     manager: &mut Manager<H, C, SERVER>,
     command: &Command<H>,
@@ -71,6 +86,8 @@ fn process_internal<H: Handle, C: Config, SERVER: DisplayServer<H>>(
 
         Command::ToggleMaximized => toggle_state(state, WindowState::Maximized),
         Command::ToggleFullScreen => toggle_state(state, WindowState::Fullscreen),
+        Command::ToggleFakeFullScreen => toggle_fake_fullscreen(state),
+        Command::TogglePictureInPicture => toggle_picture_in_picture(state),
         Command::ToggleSticky => toggle_state(state, WindowState::Sticky),
         Command::ToggleAbove => toggle_state(state, WindowState::Above),
 
@@ -78,8 +95,12 @@ fn process_internal<H: Handle, C: Config, SERVER: DisplayServer<H>>(
         Command::MoveWindowToNextTag { follow } => move_to_tag_relative(manager, *follow, 1),
         Command::MoveWindowToPreviousTag { follow } => move_to_tag_relative(manager, *follow, -1),
         Command::MoveWindowToLastWorkspace => move_to_last_workspace(state),
-        Command::MoveWindowToNextWorkspace => move_window_to_workspace_change(manager, 1),
-        Command::MoveWindowToPreviousWorkspace => move_window_to_workspace_change(manager, -1),
+        Command::MoveWindowToNextWorkspace { follow } => {
+            move_window_to_workspace_change(manager, *follow, 1)
+        }
+        Command::MoveWindowToPreviousWorkspace { follow } => {
+            move_window_to_workspace_change(manager, *follow, -1)
+        }
         Command::MoveWindowUp => move_focus_common_vars!(move_window_change(state, -1)),
         Command::MoveWindowDown => move_focus_common_vars!(move_window_change(state, 1)),
         Command::MoveWindowTop { swap } => move_focus_common_vars!(move_window_top(state, *swap)),
@@ -90,6 +111,7 @@ fn process_internal<H: Handle, C: Config, SERVER: DisplayServer<H>>(
 
         Command::GoToTag { tag, swap } => goto_tag(state, *tag, *swap),
         Command::ReturnToLastTag => return_to_last_tag(state),
+        Command::ToggleTag(tag) => toggle_tag(manager, *tag),
 
         Command::CloseWindow => close_window(state),
         Command::SwapScreens => swap_tags(state),
@@ -113,32 +135,72 @@ fn process_internal<H: Handle, C: Config, SERVER: DisplayServer<H>>(
             FocusDeltaBehavior::IgnoreUsed => focus_previous_empty_tag(state),
         },
         Command::FocusWindow(param) => focus_window(state, param),
+        Command::ActivateWindow(selector) => activate_window(manager, selector),
         Command::FocusWindowUp => move_focus_common_vars!(focus_window_change(state, -1)),
         Command::FocusWindowDown => move_focus_common_vars!(focus_window_change(state, 1)),
         Command::FocusWindowTop { swap } => focus_window_top(state, *swap),
         Command::FocusWindowAt(param) => focus_window_direction(state, *param),
+        Command::FocusPrevious(steps) => focus_previous(state, *steps),
         Command::FocusWorkspaceNext => focus_workspace_change(state, 1),
         Command::FocusWorkspacePrevious => focus_workspace_change(state, -1),
 
+        Command::ToggleSelectWindow => selection_handler::toggle_select_window(state),
+        Command::ClearSelection => Some(selection_handler::clear_selection(state)),
+        Command::SendSelectionToTag(tag) => selection_handler::send_selection_to_tag(manager, *tag),
+        Command::FloatSelection => selection_handler::float_selection(state),
+        Command::CloseSelection => selection_handler::close_selection(state),
+
+        Command::GroupWindows => container_handler::group_windows(state),
+        Command::UngroupWindow => container_handler::ungroup_window(state),
+        Command::NextTab => container_handler::cycle_tab(state, 1),
+        Command::PreviousTab => container_handler::cycle_tab(state, -1),
+
+        Command::MinimizeWindow => minimize_handler::minimize_window(manager),
+        Command::RestoreLastMinimized => minimize_handler::restore_last_minimized(manager),
+        Command::RestoreAll => minimize_handler::restore_all(manager),
+
+        Command::StashWindows => stash_handler::stash_windows(manager),
+        Command::UnstashWindows => stash_handler::unstash_windows(manager),
+
+        Command::Schedule { schedule, command } => schedule_command(manager, schedule, command),
+        Command::CancelSchedules => cancel_schedules(manager),
+        Command::ClearUrgent { window } => clear_urgent(state, *window),
+        Command::FocusNextUrgent => focus_next_urgent(manager),
+        Command::TogglePresentationMode => {
+            toggle_presentation_mode(state);
+            Some(true)
+        }
+        Command::MoveFloatingWindow(direction) => {
+            let step = state.floating_step;
+            let (dx, dy) = match direction {
+                FocusDirection::North => (0, -step),
+                FocusDirection::East => (step, 0),
+                FocusDirection::South => (0, step),
+                FocusDirection::West => (-step, 0),
+            };
+            change_floating_geometry(state, dx, dy, 0, 0)
+        }
+        Command::ResizeWindowWider => change_floating_geometry(state, 0, 0, state.floating_step, 0),
+        Command::ResizeWindowNarrower => {
+            change_floating_geometry(state, 0, 0, -state.floating_step, 0)
+        }
+        Command::ResizeWindowTaller => {
+            change_floating_geometry(state, 0, 0, 0, state.floating_step)
+        }
+        Command::ResizeWindowShorter => {
+            change_floating_geometry(state, 0, 0, 0, -state.floating_step)
+        }
+
         Command::SoftReload => {
-            // Make sure the currently focused window is saved for the tag.
-            if let Some((handle, Some(tag))) = state
-                .focus_manager
-                .window(&state.windows)
-                .map(|w| (w.handle, w.tag))
-            {
-                let old_handle = state
-                    .focus_manager
-                    .tags_last_window
-                    .entry(tag)
-                    .or_insert(handle);
-                *old_handle = handle;
+            if manager.reload_config() {
+                return Some(true);
             }
-            manager.config.save_state(&manager.state);
+            save_state(manager);
             manager.hard_reload();
             None
         }
         Command::HardReload => {
+            save_state(manager);
             manager.hard_reload();
             None
         }
@@ -154,14 +216,44 @@ fn process_internal<H: Handle, C: Config, SERVER: DisplayServer<H>>(
         Command::IncreaseMainCount() => change_main_count(state, 1),
         Command::DecreaseMainCount() => change_main_count(state, -1),
         Command::SetMarginMultiplier(multiplier) => set_margin_multiplier(state, *multiplier),
+        Command::SetModKey(modkey) => Some(manager.set_modkey(modkey)),
+        Command::SetInsertBehavior(behavior) => {
+            state.insert_behavior = *behavior;
+            Some(false)
+        }
+        Command::ToggleGaps => {
+            state.gaps_hidden = !state.gaps_hidden;
+            Some(true)
+        }
         Command::SendWorkspaceToTag(ws_index, tag_index) => {
             Some(send_workspace_to_tag(state, *ws_index, *tag_index))
         }
         Command::CloseAllOtherWindows => close_all_other_windows(state),
+        Command::ForceCloseWindow => force_close_window(state),
+        Command::Execute(command) => execute(manager, command),
         Command::Other(cmd) => Some(C::command_handler(cmd, manager)),
     }
 }
 
+/// Write the state for the next worker to restore, see `Config::load_state`.
+fn save_state<H: Handle, C: Config, SERVER: DisplayServer<H>>(manager: &mut Manager<H, C, SERVER>) {
+    let state = &mut manager.state;
+    // Make sure the currently focused window is saved for the tag.
+    if let Some((handle, Some(tag))) = state
+        .focus_manager
+        .window(&state.windows)
+        .map(|w| (w.handle, w.tag))
+    {
+        let old_handle = state
+            .focus_manager
+            .tags_last_window
+            .entry(tag)
+            .or_insert(handle);
+        *old_handle = handle;
+    }
+    manager.config.save_state(&manager.state);
+}
+
 fn focus_next_empty_tag<H: Handle>(state: &mut State<H>) -> Option<bool> {
     let used_tags: Vec<usize> = state.windows.iter().filter_map(|w| w.tag).collect();
     let unused_tags: Vec<usize> = state
@@ -252,6 +344,54 @@ fn toggle_state<H: Handle>(state: &mut State<H>, window_state: WindowState) -> O
     }
 }
 
+/// Tell the focused window it is fullscreen, or not anymore, while it stays in its tile. Until
+/// toggled back, it also stays in its tile when it asks to be fullscreen itself.
+fn toggle_fake_fullscreen<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let window = state.focus_manager.window_mut(&mut state.windows)?;
+    let handle = window.handle;
+    window.fake_fullscreen = !window.fake_fullscreen;
+    let act = DisplayAction::SetState(handle, window.fake_fullscreen, WindowState::Fullscreen);
+    state.actions.push_back(act);
+    state.handle_window_focus(&handle);
+    Some(true)
+}
+
+/// Pin the focused window floating to a corner of the workspace, sticky and above other windows,
+/// or restore it the way it was.
+fn toggle_picture_in_picture<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let area = state
+        .focus_manager
+        .workspace(&state.workspaces)?
+        .xyhw_avoided;
+    let pinned = state.picture_in_picture.xyhw(&area);
+    let window = state.focus_manager.window_mut(&mut state.windows)?;
+    let handle = window.handle;
+    let (sticky, above) = if let Some(from) = window.pinned_from.take() {
+        window.set_floating_offsets(from.offsets);
+        window.set_floating(from.floating);
+        (from.sticky, from.above)
+    } else {
+        window.pinned_from = Some(PinnedFrom {
+            floating: window.floating(),
+            offsets: window.get_floating_offsets(),
+            sticky: window.is_sticky(),
+            above: window.states.contains(&WindowState::Above),
+        });
+        window.set_floating(true);
+        window.set_floating_exact(pinned);
+        (true, true)
+    };
+    for (window_state, on) in [(WindowState::Sticky, sticky), (WindowState::Above, above)] {
+        if window.states.contains(&window_state) != on {
+            let act = DisplayAction::SetState(handle, on, window_state);
+            state.actions.push_back(act);
+        }
+    }
+    state.move_to_top(&handle);
+    state.handle_window_focus(&handle);
+    Some(true)
+}
+
 fn move_to_tag<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     window: Option<WindowHandle<H>>,
     tag_id: TagId,
@@ -276,6 +416,17 @@ fn move_to_tag<H: Handle, C: Config, SERVER: DisplayServer<H>>(
         None
     };
 
+    // Keep the window's place in the stack of the tag it leaves.
+    let old_tag = manager
+        .state
+        .windows
+        .iter()
+        .find(|w| w.handle == handle)?
+        .tag;
+    if let Some(old_tag) = old_tag {
+        manager.state.remember_tag_position(&handle, old_tag);
+    }
+
     let window = manager
         .state
         .windows
@@ -289,17 +440,18 @@ fn move_to_tag<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     let act = DisplayAction::SetWindowTag(window.handle, Some(tag.id));
     manager.state.actions.push_back(act);
 
+    // Put the window back where it was if it has been on the target tag before.
+    manager.state.restore_tag_position(&handle, tag.id);
+
     manager.state.sort_windows();
-    manager
-        .state
-        .handle_single_border(manager.config.border_width());
+    manager.state.handle_single_border();
     if handle_focus {
         if let Some(new_handle) = new_handle {
             manager.state.focus_window(&new_handle);
         } else {
             let act = DisplayAction::Unfocus(Some(handle), false);
             manager.state.actions.push_back(act);
-            manager.state.focus_manager.window_history.push_front(None);
+            manager.state.focus_manager.push_window_history(None);
         }
     }
     Some(true)
@@ -330,19 +482,26 @@ fn move_to_tag_relative<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     Some(true)
 }
 
+/// Move the focused window to the tag of the next or previous workspace. Following it focuses it
+/// there and moves the cursor over it.
 fn move_window_to_workspace_change<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     manager: &mut Manager<H, C, SERVER>,
+    follow: bool,
     delta: i32,
 ) -> Option<bool> {
-    let current = manager
-        .state
-        .focus_manager
-        .workspace(&manager.state.workspaces)?;
-    let workspace =
-        helpers::relative_find(&manager.state.workspaces, |w| w == current, delta, true)?.clone();
-
+    let state = &manager.state;
+    let current = state.focus_manager.workspace(&state.workspaces)?;
+    let workspace = helpers::relative_find(&state.workspaces, |w| w == current, delta, true)?;
     let tag_id = workspace.tag?;
-    move_to_tag(None, tag_id, manager)
+    let handle = state.focus_manager.window(&state.windows)?.handle;
+
+    move_to_tag(None, tag_id, manager)?;
+    if follow {
+        manager.state.focus_window(&handle);
+        let act = DisplayAction::MoveMouseOver(handle, true);
+        manager.state.actions.push_back(act);
+    }
+    Some(true)
 }
 
 fn goto_tag<H: Handle>(
@@ -350,10 +509,9 @@ fn goto_tag<H: Handle>(
     input_tag: TagId,
     current_tag_swap: bool,
 ) -> Option<bool> {
-    let current_tag = state.focus_manager.tag(0).unwrap_or_default();
-    let previous_tag = state.focus_manager.tag(1).unwrap_or_default();
-    let destination_tag = if current_tag_swap && current_tag == input_tag {
-        previous_tag
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let destination_tag = if current_tag_swap && workspace.tag == Some(input_tag) {
+        workspace.last_tag().unwrap_or_default()
     } else {
         input_tag
     };
@@ -361,8 +519,33 @@ fn goto_tag<H: Handle>(
 }
 
 fn return_to_last_tag<H: Handle>(state: &mut State<H>) -> Option<bool> {
-    let previous_tag = state.focus_manager.tag(1).unwrap_or_default();
-    state.goto_tag_handler(previous_tag)
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    state.goto_tag_handler(workspace.last_tag().unwrap_or_default())
+}
+
+/// Show `tag` on the focused workspace along with its tag, or stop showing it there. It is taken
+/// from the other workspaces showing it along with theirs, but not from one showing it as its tag.
+fn toggle_tag<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    tag: TagId,
+) -> Option<bool> {
+    let state = &mut manager.state;
+    if tag < 1 || tag > state.tags.len_normal() {
+        return None;
+    }
+    if state.workspaces.iter().any(|ws| ws.tag == Some(tag)) {
+        return None;
+    }
+    let focused = state.focus_manager.workspace(&state.workspaces)?.id;
+    for ws in &mut state.workspaces {
+        let shown = ws.extra_tags.contains(&tag);
+        ws.extra_tags.retain(|&t| t != tag);
+        if ws.id == focused && !shown {
+            ws.extra_tags.push(tag);
+        }
+    }
+    stash_handler::refocus(manager);
+    Some(true)
 }
 
 fn focus_window<H: Handle>(state: &mut State<H>, param: &str) -> Option<bool> {
@@ -406,20 +589,56 @@ fn focus_window_direction<H: Handle>(state: &mut State<H>, dir: FocusDirection)
 
     let next_window = FocusDirection::find_neighbor(&rects, cur?, dir, &workspace);
 
-    match next_window {
+    let handle = match next_window {
         Some(next) => {
-            // update current focussed window
-            let handle = state
+            state
                 .windows
                 .iter()
                 .filter(|w| w.visible())
                 .nth(next)?
-                .handle;
-            state.handle_window_focus(&handle);
-            Some(true)
+                .handle
         }
-        None => None,
-    }
+        // Continue on the screen in that direction.
+        None => closest_window_on_other_screens(state, dir)?,
+    };
+    // update current focussed window
+    state.handle_window_focus(&handle);
+    Some(true)
+}
+
+/// The closest visible window in a direction on the other screens, floating or tiled. Windows
+/// beside the focused one, overlapping it across the direction, are preferred over others.
+fn closest_window_on_other_screens<H: Handle>(
+    state: &State<H>,
+    dir: FocusDirection,
+) -> Option<WindowHandle<H>> {
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let current = state.focus_manager.window(&state.windows)?;
+    let from = current.calculated_xyhw();
+    let (cx, cy) = from.center();
+    let closest = state
+        .windows
+        .iter()
+        .filter(|w| w.visible() && w.is_managed() && !workspace.is_displaying(w))
+        .filter_map(|w| {
+            let to = w.calculated_xyhw();
+            let (x, y) = to.center();
+            // Distance along the direction and across it, and whether the windows are beside.
+            let (along, across, beside) = match dir {
+                FocusDirection::North => (cy - y, x - cx, to.x() < from.x() + from.w()),
+                FocusDirection::South => (y - cy, x - cx, to.x() < from.x() + from.w()),
+                FocusDirection::East => (x - cx, y - cy, to.y() < from.y() + from.h()),
+                FocusDirection::West => (cx - x, y - cy, to.y() < from.y() + from.h()),
+            };
+            let beside = beside
+                && match dir {
+                    FocusDirection::North | FocusDirection::South => from.x() < to.x() + to.w(),
+                    FocusDirection::East | FocusDirection::West => from.y() < to.y() + to.h(),
+                };
+            (along > 0).then_some((!beside, along + across.abs(), w.handle))
+        })
+        .min_by_key(|(apart, distance, _)| (*apart, *distance))?;
+    Some(closest.2)
 }
 
 fn focus_window_by_class<H: Handle>(state: &mut State<H>, window_class: &str) -> Option<bool> {
@@ -453,7 +672,44 @@ fn focus_window_by_class<H: Handle>(state: &mut State<H>, window_class: &str) ->
 
     let tag_id = target_window.tag?;
     state.goto_tag_handler(tag_id)?;
+    bring_to_front(state, handle, tag_id)
+}
+
+/// Switch to the tag of the selected window if it isn't shown, raise it and focus it. Minimized
+/// windows are restored first.
+fn activate_window<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    selector: &WindowSelector<H>,
+) -> Option<bool> {
+    let handle = manager
+        .state
+        .windows
+        .iter()
+        .find(|w| selector.matches(w))?
+        .handle;
+    minimize_handler::restore_window(manager, handle);
+
+    let state = &mut manager.state;
+    let window = state.windows.iter().find(|w| w.handle == handle)?;
+    let (tag_id, floating) = (window.tag?, window.floating());
+    if !state.workspaces.iter().any(|ws| ws.has_tag(&tag_id)) {
+        state.goto_tag_handler(tag_id)?;
+    }
+    if floating {
+        state.move_to_top(&handle);
+        state.handle_window_focus(&handle);
+        return Some(true);
+    }
+    bring_to_front(state, handle, tag_id)
+}
 
+/// Bring a tiled window of a shown tag to the front of layouts only showing one window at a time,
+/// and focus it.
+fn bring_to_front<H: Handle>(
+    state: &mut State<H>,
+    handle: WindowHandle<H>,
+    tag_id: TagId,
+) -> Option<bool> {
     match state
         .focus_manager
         .workspace(&state.workspaces)
@@ -506,16 +762,16 @@ fn swap_tags<H: Handle>(state: &mut State<H>) -> Option<bool> {
         let hist_a = *state.focus_manager.workspace_history.front()?;
         let hist_b = *state.focus_manager.workspace_history.get(1)?;
         // Update workspace tags
-        let mut temp = None;
-        std::mem::swap(&mut state.workspaces.get_mut(hist_a)?.tag, &mut temp);
-        std::mem::swap(&mut state.workspaces.get_mut(hist_b)?.tag, &mut temp);
-        std::mem::swap(&mut state.workspaces.get_mut(hist_a)?.tag, &mut temp);
+        let tag_a = state.workspaces.get(hist_a)?.tag?;
+        let tag_b = state.workspaces.get(hist_b)?.tag?;
+        state.workspaces.get_mut(hist_a)?.show_tag(&tag_b);
+        state.workspaces.get_mut(hist_b)?.show_tag(&tag_a);
         // Update dock tags and layouts.
         state.update_static();
         return Some(true);
     }
     if state.workspaces.len() == 1 {
-        let last = *state.focus_manager.tag_history.get(1)?;
+        let last = state.workspaces.first()?.last_tag()?;
         return state.goto_tag_handler(last);
     }
     None
@@ -525,7 +781,21 @@ fn swap_tags<H: Handle>(state: &mut State<H>) -> Option<bool> {
 fn close_window<H: Handle>(state: &mut State<H>) -> Option<bool> {
     let window = state.focus_manager.window(&state.windows)?;
     if window.is_managed() {
-        let act = DisplayAction::KillWindow(window.handle);
+        // Closing a window which did not answer the last request kills it.
+        let act = if window.unresponsive {
+            DisplayAction::ForceKillWindow(window.handle)
+        } else {
+            DisplayAction::KillWindow(window.handle)
+        };
+        state.actions.push_back(act);
+    }
+    None
+}
+
+fn force_close_window<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let window = state.focus_manager.window(&state.windows)?;
+    if window.is_managed() {
+        let act = DisplayAction::ForceKillWindow(window.handle);
         state.actions.push_back(act);
     }
     None
@@ -614,8 +884,7 @@ fn set_layout<H: Handle>(layout: &str, state: &mut State<H>) -> Option<bool> {
 }
 
 fn floating_to_tile<H: Handle>(state: &mut State<H>) -> Option<bool> {
-    let workspace = state.focus_manager.workspace(&state.workspaces)?;
-    let window = state.focus_manager.window_mut(&mut state.windows)?;
+    let window = state.focus_manager.window(&state.windows)?;
     if window.must_float() {
         return None;
     }
@@ -625,6 +894,9 @@ fn floating_to_tile<H: Handle>(state: &mut State<H>) -> Option<bool> {
         return None;
     }
     let handle = window.handle;
+    state.remember_floating(&handle);
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let window = state.focus_manager.window_mut(&mut state.windows)?;
     if window.snap_to_workspace(workspace) {
         state.sort_windows();
     }
@@ -633,13 +905,22 @@ fn floating_to_tile<H: Handle>(state: &mut State<H>) -> Option<bool> {
 }
 
 fn tile_to_floating<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let handle = state.focus_manager.window(&state.windows)?.handle;
+    window_to_floating(state, handle)
+}
+
+fn window_to_floating<H: Handle>(state: &mut State<H>, handle: WindowHandle<H>) -> Option<bool> {
     let width = state.default_width;
     let height = state.default_height;
-    let window = state.focus_manager.window_mut(&mut state.windows)?;
-
+    let window = state.windows.iter().find(|w| w.handle == handle)?;
     if window.floating() {
         return None;
     }
+    let remembered = state
+        .focus_manager
+        .workspace(&state.workspaces)
+        .and_then(|ws| state.remembered_floating(window, ws));
+    let window = state.windows.iter_mut().find(|w| w.handle == handle)?;
 
     let mut normal = window.normal;
     let offset = window.container_size.unwrap_or_default();
@@ -654,7 +935,11 @@ fn tile_to_floating<H: Handle>(state: &mut State<H>) -> Option<bool> {
     window.start_loc = Some(floating);
     window.set_floating(true);
 
-    let handle = window.handle;
+    if let Some(xyhw) = remembered {
+        window.set_floating_exact(xyhw);
+        window.start_loc = window.get_floating_offsets();
+    }
+
     state.move_to_top(&handle);
 
     Some(true)
@@ -855,6 +1140,102 @@ fn focus_window_top<H: Handle>(state: &mut State<H>, swap: bool) -> Option<bool>
     None
 }
 
+/// Focus the window that had focus `steps` focus changes ago on the current tag.
+fn focus_previous<H: Handle>(state: &mut State<H>, steps: usize) -> Option<bool> {
+    if steps == 0 {
+        return None;
+    }
+    let tag = state.focus_manager.tag(0);
+    let history = state
+        .focus_manager
+        .window_history_for_tag(tag, &state.windows);
+    let handle = *history.get(steps)?;
+    state.handle_window_focus(&handle);
+    None
+}
+
+fn execute<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    command: &str,
+) -> Option<bool> {
+    exec_shell(command, &mut manager.children);
+    None
+}
+
+fn schedule_command<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    schedule: &Schedule,
+    command: &Command<H>,
+) -> Option<bool> {
+    if let Err(err) = manager.scheduler.schedule(schedule, command.clone(), false) {
+        tracing::error!("Unable to schedule {:?}: {}", command, err);
+    }
+    None
+}
+
+fn cancel_schedules<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    manager.scheduler.cancel();
+    None
+}
+
+fn clear_urgent<H: Handle>(state: &mut State<H>, window: Option<WindowHandle<H>>) -> Option<bool> {
+    let handle = window.or_else(|| state.focus_manager.window(&state.windows).map(|w| w.handle))?;
+    Some(state.clear_urgency(&handle))
+}
+
+/// Show and focus the urgent window which became urgent last, or else any urgent window, such as
+/// one which was urgent from the start.
+fn focus_next_urgent<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    let state = &manager.state;
+    let is_urgent = |handle: &WindowHandle<H>| {
+        state
+            .windows
+            .iter()
+            .any(|w| &w.handle == handle && w.urgent)
+    };
+    let handle = match state.urgent_order.iter().rev().find(|h| is_urgent(h)) {
+        Some(handle) => *handle,
+        None => state.windows.iter().find(|w| w.urgent)?.handle,
+    };
+    activate_window(manager, &WindowSelector::Handle(handle))
+}
+
+/// Leave out borders, gaps and the urgency of shown windows, or bring them back. Windows stay
+/// urgent meanwhile, their urgency is only not shown.
+fn toggle_presentation_mode<H: Handle>(state: &mut State<H>) {
+    state.presentation_mode = !state.presentation_mode;
+}
+
+/// Move and resize the focused floating window, which is configured right away.
+fn change_floating_geometry<H: Handle>(
+    state: &mut State<H>,
+    dx: i32,
+    dy: i32,
+    dw: i32,
+    dh: i32,
+) -> Option<bool> {
+    let handle = state.focus_manager.window(&state.windows)?.handle;
+    let window = state.windows.iter_mut().find(|w| w.handle == handle)?;
+    if !window.floating() || window.is_fullscreen() || window.is_maximized() {
+        return None;
+    }
+    let mut xyhw = window.exact_xyhw();
+    xyhw.set_x(xyhw.x() + dx);
+    xyhw.set_y(xyhw.y() + dy);
+    // Shrinking stops at the smallest size the window is shown at, borders included.
+    let border = window.border * 2;
+    xyhw.set_w(std::cmp::max(xyhw.w() + dw, window.min_width() + border));
+    xyhw.set_h(std::cmp::max(xyhw.h() + dh, window.min_height() + border));
+    window.set_floating_exact(xyhw);
+    let act = DisplayAction::ConfigureXlibWindow(window.clone());
+    state.actions.push_back(act);
+    Some(false)
+}
+
 fn close_all_other_windows<H: Handle>(state: &mut State<H>) -> Option<bool> {
     let current_window: Option<WindowHandle<H>> =
         state.focus_manager.window(&state.windows).map(|w| w.handle);
@@ -876,7 +1257,11 @@ fn focus_workspace_change<H: Handle>(state: &mut State<H>, val: i32) -> Option<b
     let current = state.focus_manager.workspace(&state.workspaces)?;
     let workspace = helpers::relative_find(&state.workspaces, |w| w == current, val, true)?.clone();
 
-    if state.focus_manager.behaviour.is_sloppy() && state.focus_manager.sloppy_mouse_follows_focus {
+    let follows = state.focus_manager.focus_follows_workspace;
+    if follows == FocusFollowsWorkspace::Nothing
+        && state.focus_manager.behaviour.is_sloppy()
+        && state.focus_manager.sloppy_mouse_follows_focus
+    {
         let action = workspace
             .tag
             .as_ref()
@@ -888,6 +1273,7 @@ fn focus_workspace_change<H: Handle>(state: &mut State<H>, val: i32) -> Option<b
         state.actions.push_back(action);
     }
     state.focus_workspace(&workspace);
+    state.focus_follow_workspace();
     None
 }
 
@@ -951,6 +1337,7 @@ fn send_workspace_to_tag<H: Handle>(
         let workspace = &state.workspaces[ws_index].clone();
         state.focus_workspace(workspace);
         state.goto_tag_handler(tag_index + 1);
+        state.focus_follow_workspace();
         return true;
     }
     false
@@ -959,7 +1346,7 @@ fn send_workspace_to_tag<H: Handle>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{MockHandle, Tags};
+    use crate::models::{BBox, MockHandle, Tags, TestManager, FOCUS_HISTORY_LENGTH};
 
     fn mock_update(
         manager: &mut Manager<
@@ -1048,6 +1435,187 @@ mod tests {
             .any(|w| w.states.contains(&WindowState::Maximized)));
     }
 
+    #[test]
+    fn toggle_maximized_fills_the_workspace_and_restores_the_window() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.focus_window(&WindowHandle(1));
+        manager.command_handler(&Command::TileToFloating);
+        manager.update_windows();
+        let xyhw = |manager: &Manager<_, _, _>, i| {
+            let window = manager
+                .state
+                .windows
+                .iter()
+                .find(|w| w.handle == WindowHandle(i));
+            window.unwrap().exact_xyhw()
+        };
+        let (floating, tile) = (xyhw(&manager, 1), xyhw(&manager, 2));
+
+        manager.command_handler(&Command::ToggleMaximized);
+        mock_update(&mut manager);
+        manager.update_windows();
+        let window = manager
+            .state
+            .focus_manager
+            .window(&manager.state.windows)
+            .unwrap();
+        assert!(window.is_maximized());
+        assert!(!window.states.contains(&WindowState::Fullscreen));
+        assert_eq!(
+            Rect::from(window.calculated_xyhw()),
+            manager.state.workspaces[0].rect()
+        );
+
+        manager.command_handler(&Command::ToggleMaximized);
+        mock_update(&mut manager);
+        manager.update_windows();
+        assert_eq!(xyhw(&manager, 1), floating);
+        assert_eq!(xyhw(&manager, 2), tile);
+    }
+
+    #[test]
+    fn focus_should_follow_the_workspace_when_configured() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        for x in [0, 1000] {
+            manager.screen_create_handler(Screen::new(
+                BBox {
+                    x,
+                    y: 0,
+                    width: 1000,
+                    height: 800,
+                },
+                String::new(),
+            ));
+        }
+        for (i, ws) in [(1, 0), (2, 1), (3, 1)] {
+            let workspace = manager.state.workspaces[ws].clone();
+            manager.state.focus_workspace(&workspace);
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let focus_first_workspace = |manager: &mut Manager<_, _, _>| {
+            manager.state.focus_window(&WindowHandle(1));
+            manager.state.focus_manager.tags_last_window.clear();
+            manager.state.actions.clear();
+        };
+        let focused = |manager: &Manager<_, _, _>| {
+            let window = manager.state.focus_manager.window(&manager.state.windows);
+            window.map(|w| w.handle.0)
+        };
+
+        focus_first_workspace(&mut manager);
+        manager.command_handler(&Command::FocusWorkspaceNext);
+        assert_eq!(focused(&manager), None);
+
+        manager.state.focus_manager.focus_follows_workspace = FocusFollowsWorkspace::Keyboard;
+        focus_first_workspace(&mut manager);
+        manager.command_handler(&Command::FocusWorkspaceNext);
+        assert_eq!(focused(&manager), Some(2));
+
+        manager.state.focus_manager.focus_follows_workspace =
+            FocusFollowsWorkspace::KeyboardAndCursor;
+        focus_first_workspace(&mut manager);
+        manager.command_handler(&Command::FocusWorkspaceNext);
+        assert_eq!(focused(&manager), Some(2));
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .any(|act| matches!(act, DisplayAction::MoveMouseOver(WindowHandle(2), true))));
+    }
+
+    #[test]
+    fn toggle_fake_fullscreen_keeps_the_window_in_its_tile() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        let tile = manager.state.windows[0].calculated_xyhw();
+
+        manager.command_handler(&Command::ToggleFakeFullScreen);
+        mock_update(&mut manager);
+        manager.update_windows();
+        let window = &manager.state.windows[0];
+        assert!(window.states.contains(&WindowState::Fullscreen));
+        assert!(!window.is_fullscreen());
+        assert_eq!(window.calculated_xyhw(), tile);
+
+        manager.command_handler(&Command::ToggleFakeFullScreen);
+        mock_update(&mut manager);
+        let window = &manager.state.windows[0];
+        assert!(!window.states.contains(&WindowState::Fullscreen));
+        assert!(!window.fake_fullscreen);
+    }
+
+    #[test]
+    fn toggle_picture_in_picture_pins_and_restores_the_window() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.focus_window(&WindowHandle(2));
+        manager.state.actions.clear();
+        let area = manager.state.workspaces[0].xyhw_avoided;
+
+        manager.command_handler(&Command::TogglePictureInPicture);
+        let window = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle(2));
+        let window = window.unwrap();
+        assert!(window.floating());
+        assert_eq!(
+            window.exact_xyhw(),
+            manager.state.picture_in_picture.xyhw(&area)
+        );
+        for window_state in [WindowState::Sticky, WindowState::Above] {
+            assert!(manager.state.actions.iter().any(|act| matches!(
+                act,
+                DisplayAction::SetState(WindowHandle(2), true, s) if *s == window_state
+            )));
+        }
+
+        manager.command_handler(&Command::TogglePictureInPicture);
+        let window = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle(2));
+        assert!(!window.unwrap().floating());
+        assert!(window.unwrap().pinned_from.is_none());
+    }
+
     #[test]
     fn fullscreen_window_sorting() {
         let mut manager = Manager::new_test(vec!["1".to_string()]);
@@ -1312,6 +1880,160 @@ mod tests {
         assert_eq!(initial.handle, actual);
     }
 
+    #[test]
+    fn focus_previous_should_walk_back_the_window_history() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let handles: Vec<_> = manager.state.windows.iter().map(|w| w.handle).collect();
+        for handle in &handles {
+            manager.state.focus_window(handle);
+        }
+        // Refocusing an older window should not leave duplicates in the history.
+        manager.state.focus_window(&handles[1]);
+        manager.state.focus_window(&handles[2]);
+
+        let history = manager
+            .state
+            .focus_manager
+            .window_history_for_tag(manager.state.focus_manager.tag(0), &manager.state.windows);
+        assert_eq!(history, vec![handles[2], handles[1], handles[0]]);
+
+        manager.command_handler(&Command::FocusPrevious(2));
+        let actual = manager
+            .state
+            .focus_manager
+            .window(&manager.state.windows)
+            .unwrap()
+            .handle;
+        assert_eq!(handles[0], actual);
+
+        manager.command_handler(&Command::FocusPrevious(1));
+        let actual = manager
+            .state
+            .focus_manager
+            .window(&manager.state.windows)
+            .unwrap()
+            .handle;
+        assert_eq!(handles[2], actual);
+
+        // Stepping past the end of the history does nothing.
+        manager.command_handler(&Command::FocusPrevious(5));
+        let actual = manager
+            .state
+            .focus_manager
+            .window(&manager.state.windows)
+            .unwrap()
+            .handle;
+        assert_eq!(handles[2], actual);
+    }
+
+    #[test]
+    fn focus_history_of_a_tag_should_outlive_focus_changes_on_others() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        let create_and_focus = |manager: &mut TestManager, i| {
+            let handle = WindowHandle::<MockHandle>(i);
+            manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+            manager.state.focus_window(&handle);
+        };
+        for i in 1..=2 {
+            create_and_focus(&mut manager, i);
+        }
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        for i in 3..=3 + FOCUS_HISTORY_LENGTH as i32 {
+            create_and_focus(&mut manager, i);
+        }
+
+        let history = manager
+            .state
+            .focus_manager
+            .window_history_for_tag(Some(1), &manager.state.windows);
+        assert_eq!(history, vec![WindowHandle(2), WindowHandle(1)]);
+    }
+
+    #[test]
+    fn moving_a_window_back_to_a_tag_should_restore_its_position() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+
+        manager.command_handler(&Command::SendWindowToTag {
+            window: Some(WindowHandle::<MockHandle>(2)),
+            tag: 2,
+        });
+        // Reordering tag 1 moves its windows behind the one that left.
+        manager.state.focus_window(&WindowHandle::<MockHandle>(3));
+        manager.command_handler(&Command::MoveWindowUp);
+
+        manager.command_handler(&Command::SendWindowToTag {
+            window: Some(WindowHandle::<MockHandle>(2)),
+            tag: 1,
+        });
+
+        let expected = vec![
+            WindowHandle::<MockHandle>(3),
+            WindowHandle::<MockHandle>(2),
+            WindowHandle::<MockHandle>(1),
+        ];
+        let actual: Vec<WindowHandle<MockHandle>> = manager
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.has_tag(&1))
+            .map(|w| w.handle)
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn activate_window_should_switch_to_its_tag_and_focus_it() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            let mut window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            window.res_class = Some(format!("class{i}"));
+            manager.window_created_handler(window, -1, -1);
+        }
+        manager.command_handler(&Command::SendWindowToTag {
+            window: Some(WindowHandle::<MockHandle>(2)),
+            tag: 2,
+        });
+        manager.state.focus_window(&WindowHandle::<MockHandle>(1));
+
+        let selector = WindowSelector::Class("class2".to_string());
+        assert!(manager.command_handler(&Command::ActivateWindow(selector)));
+        assert_eq!(manager.state.focus_manager.tag(0), Some(2));
+        assert_eq!(
+            manager
+                .state
+                .focus_manager
+                .window(&manager.state.windows)
+                .map(|w| w.handle),
+            Some(WindowHandle::<MockHandle>(2))
+        );
+
+        let selector = WindowSelector::Title("unknown".to_string());
+        assert!(!manager.command_handler(&Command::ActivateWindow(selector)));
+    }
+
     #[test]
     fn move_window_top() {
         let mut manager = Manager::new_test(vec![]);
@@ -1905,4 +2627,263 @@ mod tests {
 
         assert_eq!(manager.state.focus_manager.tag(0).unwrap(), 3);
     }
+    #[test]
+    fn floating_windows_should_move_and_resize_by_the_floating_step() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.floating_step = 10;
+        manager.state.actions.clear();
+
+        // Tiled windows are left alone.
+        assert!(!manager.command_handler(&Command::ResizeWindowWider));
+        assert!(manager.state.actions.is_empty());
+
+        manager.command_handler(&Command::ToggleFloating);
+        let start = manager.state.windows[0].exact_xyhw();
+        manager.state.actions.clear();
+        manager.command_handler(&Command::MoveFloatingWindow(FocusDirection::West));
+        manager.command_handler(&Command::ResizeWindowTaller);
+        let end = manager.state.windows[0].exact_xyhw();
+        assert_eq!(
+            (end.x(), end.y(), end.w(), end.h()),
+            (start.x() - 10, start.y(), start.w(), start.h() + 10)
+        );
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .all(|act| matches!(act, DisplayAction::ConfigureXlibWindow(_))));
+    }
+    #[test]
+    fn floating_windows_should_not_shrink_past_their_minimum_size() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        manager.command_handler(&Command::ToggleFloating);
+        manager.state.floating_step = 1000;
+
+        manager.command_handler(&Command::ResizeWindowNarrower);
+        manager.command_handler(&Command::ResizeWindowNarrower);
+        manager.command_handler(&Command::ResizeWindowShorter);
+        let window = &manager.state.windows[0];
+        let border = window.border * 2;
+        assert_eq!(window.exact_xyhw().w(), window.min_width() + border);
+        assert_eq!(window.exact_xyhw().h(), window.min_height() + border);
+
+        manager.state.floating_step = 10;
+        manager.command_handler(&Command::ResizeWindowWider);
+        manager.command_handler(&Command::ResizeWindowTaller);
+        let window = &manager.state.windows[0];
+        assert_eq!(window.width(), window.min_width() + 10);
+        assert_eq!(window.height(), window.min_height() + 10);
+    }
+    #[test]
+    fn toggled_tags_should_be_shown_along_with_the_tag_of_the_workspace() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for (i, tag) in [(1, 1), (2, 2)] {
+            let mut window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            window.tag(&tag);
+            manager.window_created_handler(window, -1, -1);
+        }
+        let visible = |manager: &Manager<_, _, _>| {
+            manager
+                .state
+                .windows
+                .iter()
+                .filter(|w| w.visible())
+                .map(|w| w.handle.0)
+                .collect::<Vec<_>>()
+        };
+
+        // The tag of the workspace cannot be toggled.
+        assert!(!manager.command_handler(&Command::ToggleTag(1)));
+        assert!(manager.command_handler(&Command::ToggleTag(2)));
+        manager.update_windows();
+        assert_eq!(visible(&manager), vec![1, 2]);
+        let tiles: Vec<_> = manager.state.windows.iter().map(|w| w.normal).collect();
+        assert_ne!(tiles[0], tiles[1]);
+
+        assert!(manager.command_handler(&Command::ToggleTag(2)));
+        manager.update_windows();
+        assert_eq!(visible(&manager), vec![1]);
+
+        manager.command_handler(&Command::ToggleTag(2));
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        assert!(manager.state.workspaces[0].extra_tags.is_empty());
+    }
+
+    #[test]
+    fn soft_reload_should_apply_the_config_without_losing_windows() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.actions.clear();
+
+        manager.config.tags = vec!["web".to_string(), "code".to_string(), "chat".to_string()];
+        manager.config.floating_step = 42;
+        assert!(manager.command_handler(&Command::SoftReload));
+        assert!(!manager.reload_requested);
+
+        let labels: Vec<_> = manager
+            .state
+            .tags
+            .normal()
+            .iter()
+            .map(|t| &t.label)
+            .collect();
+        assert_eq!(labels, vec!["web", "code", "chat"]);
+        assert!(manager.state.actions.iter().any(|act| matches!(
+            act,
+            DisplayAction::SetDesktopNames(names) if names.len() == 3
+        )));
+        assert_eq!(manager.state.floating_step, 42);
+        assert_eq!(manager.state.windows.len(), 2);
+        assert!(manager.state.windows.iter().all(|w| w.tag == Some(1)));
+    }
+
+    #[test]
+    fn soft_reload_should_restart_the_worker_when_the_workspaces_changed() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.config.reloaded_workspaces = Some(vec![crate::config::Workspace {
+            output: "HDMI-1".to_string(),
+            ..Default::default()
+        }]);
+
+        manager.command_handler(&Command::SoftReload);
+        assert!(manager.reload_requested);
+    }
+
+    #[test]
+    fn set_modkey_should_grab_the_mousebinds_again_with_the_new_modkey() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.state.actions.clear();
+
+        assert!(!manager.command_handler(&Command::SetModKey("Hyper".to_string())));
+        assert!(manager.state.actions.is_empty());
+
+        assert!(manager.command_handler(&Command::SetModKey("Alt".to_string())));
+        assert!(manager
+            .state
+            .mousebinds
+            .iter()
+            .all(|bind| bind.modifier.contains(&"Alt".to_string())));
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .any(|act| matches!(act, DisplayAction::ReloadKeyGrabs)));
+    }
+
+    #[test]
+    fn focus_window_at_should_continue_on_the_screen_in_that_direction() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        for x in [0, 1000] {
+            manager.screen_create_handler(Screen::new(
+                BBox {
+                    x,
+                    y: 0,
+                    width: 1000,
+                    height: 800,
+                },
+                String::new(),
+            ));
+        }
+        for (i, ws) in [(1, 0), (2, 1), (3, 1)] {
+            let workspace = manager.state.workspaces[ws].clone();
+            manager.state.focus_workspace(&workspace);
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        let focused = |manager: &Manager<_, _, _>| {
+            let window = manager.state.focus_manager.window(&manager.state.windows);
+            window.map(|w| w.handle.0)
+        };
+        manager.state.focus_window(&WindowHandle(1));
+
+        assert!(!manager.command_handler(&Command::FocusWindowAt(FocusDirection::West)));
+        assert_eq!(focused(&manager), Some(1));
+        manager.command_handler(&Command::FocusWindowAt(FocusDirection::East));
+        assert_eq!(focused(&manager), Some(2));
+        assert_eq!(
+            manager.state.focus_manager.workspace_history.front(),
+            Some(&1)
+        );
+        manager.command_handler(&Command::FocusWindowAt(FocusDirection::East));
+        assert_eq!(focused(&manager), Some(3));
+        manager.command_handler(&Command::FocusWindowAt(FocusDirection::West));
+        assert_eq!(focused(&manager), Some(2));
+        manager.command_handler(&Command::FocusWindowAt(FocusDirection::West));
+        assert_eq!(focused(&manager), Some(1));
+    }
+
+    #[test]
+    fn move_window_to_next_workspace_should_follow_the_window_when_asked() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        for x in [0, 1000] {
+            manager.screen_create_handler(Screen::new(
+                BBox {
+                    x,
+                    y: 0,
+                    width: 1000,
+                    height: 800,
+                },
+                String::new(),
+            ));
+        }
+        let workspace = manager.state.workspaces[0].clone();
+        manager.state.focus_workspace(&workspace);
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let focused = |manager: &Manager<_, _, _>| {
+            let window = manager.state.focus_manager.window(&manager.state.windows);
+            window.map(|w| (w.handle.0, w.tag))
+        };
+        manager.state.focus_window(&WindowHandle(2));
+
+        manager.command_handler(&Command::MoveWindowToNextWorkspace { follow: false });
+        assert_eq!(focused(&manager), Some((1, Some(1))));
+
+        manager.state.actions.clear();
+        manager.command_handler(&Command::MoveWindowToNextWorkspace { follow: true });
+        assert_eq!(focused(&manager), Some((1, Some(2))));
+        assert_eq!(
+            manager.state.focus_manager.workspace_history.front(),
+            Some(&1)
+        );
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .any(|act| matches!(act, DisplayAction::MoveMouseOver(WindowHandle(1), true))));
+    }
 }
@@ -0,0 +1,268 @@
+//! # Module for handling the tabbed container related commands
+//! Windows sharing a container occupy a single tile of the layout. Only the first of them in the
+//! window stack, the active tab, is shown; cycling the tabs rotates the members of the container.
+use crate::{
+    models::{ContainerId, Handle, TagId, WindowHandle},
+    state::State,
+    utils::helpers,
+    Window,
+};
+
+/// Group the focused window and the selected windows of its tag into a tabbed container.
+/// The focused window becomes the active tab.
+pub fn group_windows<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let focused = state.focus_manager.window(&state.windows)?;
+    if !can_be_grouped(focused) {
+        return None;
+    }
+    let (handle, tag) = (focused.handle, focused.tag);
+    let members: Vec<WindowHandle<H>> = state
+        .windows
+        .iter()
+        .filter(|w| w.tag == tag && can_be_grouped(w) && (w.selected || w.handle == handle))
+        .map(|w| w.handle)
+        .collect();
+    if members.len() < 2 {
+        return None;
+    }
+
+    let container = state
+        .windows
+        .iter()
+        .filter(|w| members.contains(&w.handle))
+        .find_map(|w| w.container)
+        .or_else(|| {
+            state
+                .windows
+                .iter()
+                .filter_map(|w| w.container)
+                .max()
+                .map(|c| c + 1)
+        })
+        .unwrap_or(1);
+    for window in state
+        .windows
+        .iter_mut()
+        .filter(|w| members.contains(&w.handle) || w.container == Some(container))
+    {
+        window.container = Some(container);
+        window.selected = false;
+    }
+
+    // Put the focused window in front of the container so it is the visible tab.
+    let first = state
+        .windows
+        .iter()
+        .position(|w| w.container == Some(container))?;
+    let current = state.windows.iter().position(|w| w.handle == handle)?;
+    let window = state.windows.remove(current);
+    state.windows.insert(first, window);
+    Some(true)
+}
+
+/// Take the focused window out of its container.
+/// A container left with a single window is dissolved.
+pub fn ungroup_window<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let window = state.focus_manager.window_mut(&mut state.windows)?;
+    let container = window.container.take()?;
+    let remaining: Vec<&mut Window<H>> = state
+        .windows
+        .iter_mut()
+        .filter(|w| w.container == Some(container))
+        .collect();
+    if remaining.len() == 1 {
+        for window in remaining {
+            window.container = None;
+        }
+    }
+    Some(true)
+}
+
+/// Make the next (`delta` > 0) or previous (`delta` < 0) tab of the focused container active,
+/// and focus it.
+pub fn cycle_tab<H: Handle>(state: &mut State<H>, delta: i32) -> Option<bool> {
+    let container = state.focus_manager.window(&state.windows)?.container?;
+    let indexes: Vec<usize> = state
+        .windows
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| w.container == Some(container))
+        .map(|(i, _)| i)
+        .collect();
+    let mut tabs: Vec<Window<H>> = indexes.iter().map(|&i| state.windows[i].clone()).collect();
+    helpers::cycle_vec(&mut tabs, -delta)?;
+    let active = tabs.first()?.handle;
+    for (index, tab) in indexes.into_iter().zip(tabs) {
+        state.windows[index] = tab;
+    }
+    state.handle_window_focus(&active);
+    Some(true)
+}
+
+/// Take floating and untagged windows out of their container, give the windows of a container
+/// moved to another tag a container of their own there, and dissolve the containers left with a
+/// single window.
+pub(crate) fn prune_containers<H: Handle>(windows: &mut [Window<H>]) {
+    for window in windows
+        .iter_mut()
+        .filter(|w| !can_be_grouped(w) || w.tag.is_none())
+    {
+        window.container = None;
+    }
+
+    // The container of each group of windows sharing a container and a tag, and its size.
+    let mut groups: Vec<((ContainerId, Option<TagId>), ContainerId, usize)> = vec![];
+    let mut next = windows
+        .iter()
+        .filter_map(|w| w.container)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    for window in windows.iter() {
+        let Some(container) = window.container else {
+            continue;
+        };
+        let key = (container, window.tag);
+        if let Some(group) = groups.iter_mut().find(|(k, ..)| *k == key) {
+            group.2 += 1;
+        } else if groups.iter().any(|((c, _), ..)| *c == container) {
+            groups.push((key, next, 1));
+            next += 1;
+        } else {
+            groups.push((key, container, 1));
+        }
+    }
+    for window in windows.iter_mut() {
+        let Some(container) = window.container else {
+            continue;
+        };
+        let key = (container, window.tag);
+        window.container = groups
+            .iter()
+            .find(|(k, ..)| *k == key)
+            .and_then(|&(_, id, size)| (size > 1).then_some(id));
+    }
+}
+
+fn can_be_grouped<H: Handle>(window: &Window<H>) -> bool {
+    window.is_managed() && !window.floating()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{MockHandle, TestManager, WindowHandle};
+    use crate::{Command, Manager};
+
+    fn manager_with_group() -> TestManager {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 4);
+        for i in [2, 4] {
+            manager.state.focus_window(&WindowHandle::<MockHandle>(i));
+            manager.command_handler(&Command::ToggleSelectWindow);
+        }
+        manager.state.focus_window(&WindowHandle::<MockHandle>(3));
+        manager.command_handler(&Command::GroupWindows);
+        manager.update_windows();
+        manager
+    }
+
+    fn visible(manager: &TestManager) -> Vec<WindowHandle<MockHandle>> {
+        manager
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.visible())
+            .map(|w| w.handle)
+            .collect()
+    }
+
+    #[test]
+    fn grouped_windows_should_share_a_single_tile() {
+        let manager = manager_with_group();
+        assert_eq!(
+            visible(&manager),
+            vec![WindowHandle::<MockHandle>(1), WindowHandle::<MockHandle>(3)]
+        );
+        let tile = |h: i32| {
+            manager
+                .state
+                .windows
+                .iter()
+                .find(|w| w.handle == WindowHandle::<MockHandle>(h))
+                .unwrap()
+                .normal
+        };
+        assert_eq!(tile(2), tile(3));
+        assert_eq!(tile(4), tile(3));
+        assert_ne!(tile(1), tile(3));
+    }
+
+    #[test]
+    fn cycling_tabs_should_show_and_focus_the_next_window() {
+        let mut manager = manager_with_group();
+        manager.command_handler(&Command::NextTab);
+        manager.update_windows();
+        assert_eq!(
+            visible(&manager),
+            vec![WindowHandle::<MockHandle>(1), WindowHandle::<MockHandle>(2)]
+        );
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.unwrap().handle, WindowHandle::<MockHandle>(2));
+
+        manager.command_handler(&Command::PreviousTab);
+        manager.update_windows();
+        assert_eq!(
+            visible(&manager),
+            vec![WindowHandle::<MockHandle>(1), WindowHandle::<MockHandle>(3)]
+        );
+    }
+
+    #[test]
+    fn ungrouping_the_last_two_windows_should_dissolve_the_container() {
+        let mut manager = manager_with_group();
+        manager.command_handler(&Command::UngroupWindow);
+        manager.state.focus_window(&WindowHandle::<MockHandle>(2));
+        manager.command_handler(&Command::UngroupWindow);
+        manager.update_windows();
+        assert!(manager.state.windows.iter().all(|w| w.container.is_none()));
+        assert_eq!(visible(&manager).len(), 4);
+    }
+
+    #[test]
+    fn floated_and_moved_windows_should_leave_their_container() {
+        let mut manager = manager_with_group();
+        manager.state.focus_window(&WindowHandle::<MockHandle>(4));
+        manager.command_handler(&Command::ToggleFloating);
+        manager.update_windows();
+        let grouped: Vec<_> = manager
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.container.is_some())
+            .map(|w| w.handle)
+            .collect();
+        assert_eq!(
+            grouped,
+            vec![WindowHandle::<MockHandle>(3), WindowHandle::<MockHandle>(2)]
+        );
+
+        manager.state.focus_window(&WindowHandle::<MockHandle>(2));
+        manager.command_handler(&Command::SendWindowToTag {
+            window: None,
+            tag: 2,
+        });
+        manager.update_windows();
+        assert!(manager.state.windows.iter().all(|w| w.container.is_none()));
+    }
+
+    #[test]
+    fn closing_all_but_one_window_of_a_container_should_dissolve_it() {
+        let mut manager = manager_with_group();
+        manager.window_destroyed_handler(&WindowHandle::<MockHandle>(3));
+        manager.update_windows();
+        assert!(manager.state.windows.iter().any(|w| w.container.is_some()));
+
+        manager.window_destroyed_handler(&WindowHandle::<MockHandle>(4));
+        manager.update_windows();
+        assert!(manager.state.windows.iter().all(|w| w.container.is_none()));
+    }
+}
@@ -0,0 +1,178 @@
+//! # Module for handling the minimize related commands
+//! Minimized windows are moved to the hidden "MIN" tag, which keeps them out of every layout, and
+//! are remembered together with the tag they came from so they can be restored there.
+use crate::{
+    models::{Handle, TagId, WindowHandle, WindowState},
+    Config, DisplayAction, DisplayServer, Manager,
+};
+
+/// Label of the hidden tag minimized windows are stored on.
+pub const MINIMIZED_TAG: &str = "MIN";
+
+/// Minimize the focused window and focus the next window of the workspace.
+pub fn minimize_window<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    let minimized_tag = manager.state.tags.get_hidden_by_label(MINIMIZED_TAG)?.id;
+    let window = manager.state.focus_manager.window(&manager.state.windows)?;
+    if !window.is_managed() {
+        return None;
+    }
+    let (handle, tag) = (window.handle, window.tag?);
+    if tag == minimized_tag {
+        return None;
+    }
+    let new_handle = manager.get_next_or_previous_handle(&handle);
+
+    retag(manager, handle, minimized_tag)?;
+    let act = DisplayAction::SetState(handle, true, WindowState::Hidden);
+    manager.state.actions.push_back(act);
+    manager.state.minimized.push((handle, tag));
+
+    if let Some(new_handle) = new_handle {
+        manager.state.focus_window(&new_handle);
+    } else {
+        let act = DisplayAction::Unfocus(Some(handle), false);
+        manager.state.actions.push_back(act);
+        manager.state.focus_manager.push_window_history(None);
+    }
+    Some(true)
+}
+
+/// Restore the most recently minimized window to its tag and focus it.
+pub fn restore_last_minimized<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    let (handle, tag) = manager.state.minimized.pop()?;
+    restore(manager, handle, tag)
+}
+
+/// Restore all minimized windows to their tags.
+pub fn restore_all<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    if manager.state.minimized.is_empty() {
+        return None;
+    }
+    let minimized: Vec<_> = manager.state.minimized.drain(..).collect();
+    for (handle, tag) in minimized {
+        restore(manager, handle, tag);
+    }
+    Some(true)
+}
+
+/// Restore a window to its tag and focus it, if it is minimized.
+pub fn restore_window<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    handle: WindowHandle<H>,
+) -> Option<bool> {
+    let index = manager
+        .state
+        .minimized
+        .iter()
+        .position(|(h, _)| *h == handle)?;
+    let (handle, tag) = manager.state.minimized.remove(index);
+    restore(manager, handle, tag)
+}
+
+fn restore<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    handle: WindowHandle<H>,
+    tag: TagId,
+) -> Option<bool> {
+    // The tag may be gone after a config change.
+    let tag = manager.state.tags.get(tag).map_or(1, |t| t.id);
+    retag(manager, handle, tag)?;
+    let act = DisplayAction::SetState(handle, false, WindowState::Hidden);
+    manager.state.actions.push_back(act);
+    manager.state.focus_window(&handle);
+    Some(true)
+}
+
+pub(super) fn retag<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    handle: WindowHandle<H>,
+    tag: TagId,
+) -> Option<()> {
+    let window = manager
+        .state
+        .windows
+        .iter_mut()
+        .find(|w| w.handle == handle)?;
+    window.untag();
+    window.tag(&tag);
+    let act = DisplayAction::SetWindowTag(handle, Some(tag));
+    manager.state.actions.push_back(act);
+    manager.state.handle_single_border();
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MINIMIZED_TAG;
+    use crate::models::{MockHandle, TestManager, WindowHandle};
+    use crate::{Command, Manager};
+
+    fn tag_of(manager: &TestManager, handle: i32) -> Option<usize> {
+        manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle::<MockHandle>(handle))
+            .and_then(|w| w.tag)
+    }
+
+    #[test]
+    fn minimize_window_should_hide_the_window_and_focus_another() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        let minimized_tag = manager
+            .state
+            .tags
+            .get_hidden_by_label(MINIMIZED_TAG)
+            .unwrap()
+            .id;
+        manager.state.focus_window(&WindowHandle::<MockHandle>(2));
+        manager.command_handler(&Command::MinimizeWindow);
+        manager.update_windows();
+
+        assert_eq!(tag_of(&manager, 2), Some(minimized_tag));
+        assert_eq!(
+            manager.state.minimized,
+            vec![(WindowHandle::<MockHandle>(2), 1)]
+        );
+        let window = manager.state.windows.iter().find(|w| w.handle.0 == 2);
+        assert!(!window.unwrap().visible());
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_ne!(focused.unwrap().handle, WindowHandle::<MockHandle>(2));
+    }
+
+    #[test]
+    fn restore_last_minimized_should_return_the_window_to_its_tag() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        manager.state.focus_window(&WindowHandle::<MockHandle>(1));
+        manager.command_handler(&Command::MinimizeWindow);
+        manager.state.focus_window(&WindowHandle::<MockHandle>(3));
+        manager.command_handler(&Command::MinimizeWindow);
+
+        manager.command_handler(&Command::RestoreLastMinimized);
+        assert_eq!(tag_of(&manager, 3), Some(1));
+        assert_eq!(
+            manager.state.minimized,
+            vec![(WindowHandle::<MockHandle>(1), 1)]
+        );
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.unwrap().handle, WindowHandle::<MockHandle>(3));
+    }
+
+    #[test]
+    fn restore_all_should_restore_every_minimized_window() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        for i in [1, 2] {
+            manager.state.focus_window(&WindowHandle::<MockHandle>(i));
+            manager.command_handler(&Command::MinimizeWindow);
+        }
+        manager.command_handler(&Command::RestoreAll);
+        assert!(manager.state.minimized.is_empty());
+        assert!((1..=3).all(|i| tag_of(&manager, i) == Some(1)));
+    }
+}
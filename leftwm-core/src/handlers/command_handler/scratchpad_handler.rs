@@ -54,9 +54,7 @@ fn hide_scratchpad<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     let act = DisplayAction::SetWindowTag(*scratchpad_window, window.tag);
     manager.state.actions.push_back(act);
     manager.state.sort_windows();
-    manager
-        .state
-        .handle_single_border(manager.config.border_width());
+    manager.state.handle_single_border();
 
     // Will ignore current window handler because we just set it invisible
     let last_focused_still_visible = manager
@@ -136,9 +134,7 @@ fn show_scratchpad<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     let act = DisplayAction::SetWindowTag(*scratchpad_window, window.tag);
     manager.state.actions.push_back(act);
     manager.state.sort_windows();
-    manager
-        .state
-        .handle_single_border(manager.config.border_width());
+    manager.state.handle_single_border();
     manager.state.handle_window_focus(scratchpad_window);
     manager.state.move_to_top(scratchpad_window);
 
@@ -0,0 +1,169 @@
+//! # Module for handling the window selection related commands
+//! Windows are added to or removed from the selection one by one, the batch commands then act on
+//! every selected window at once and clear the selection afterwards.
+use crate::{
+    models::{Handle, TagId, WindowHandle},
+    state::State,
+    Config, DisplayAction, DisplayServer, Manager,
+};
+
+/// Add the focused window to the selection, or remove it if it already is selected.
+pub fn toggle_select_window<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let window = state.focus_manager.window_mut(&mut state.windows)?;
+    if !window.is_managed() {
+        return None;
+    }
+    window.selected = !window.selected;
+    Some(true)
+}
+
+/// Deselect all windows.
+pub fn clear_selection<H: Handle>(state: &mut State<H>) -> bool {
+    let mut changed = false;
+    for window in state.windows.iter_mut().filter(|w| w.selected) {
+        window.selected = false;
+        changed = true;
+    }
+    changed
+}
+
+/// Move all selected windows to the given tag.
+pub fn send_selection_to_tag<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+    tag: TagId,
+) -> Option<bool> {
+    let selection = selected_handles(&manager.state);
+    if selection.is_empty() {
+        return None;
+    }
+    for handle in selection {
+        super::move_to_tag(Some(handle), tag, manager);
+    }
+    Some(clear_selection(&mut manager.state))
+}
+
+/// Make all selected windows floating.
+pub fn float_selection<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let selection = selected_handles(state);
+    if selection.is_empty() {
+        return None;
+    }
+    for handle in selection {
+        super::window_to_floating(state, handle);
+    }
+    Some(clear_selection(state))
+}
+
+/// Close all selected windows.
+pub fn close_selection<H: Handle>(state: &mut State<H>) -> Option<bool> {
+    let selection = selected_handles(state);
+    if selection.is_empty() {
+        return None;
+    }
+    for handle in selection {
+        state.actions.push_back(DisplayAction::KillWindow(handle));
+    }
+    Some(clear_selection(state))
+}
+
+fn selected_handles<H: Handle>(state: &State<H>) -> Vec<WindowHandle<H>> {
+    state
+        .windows
+        .iter()
+        .filter(|w| w.selected && w.is_managed())
+        .map(|w| w.handle)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{MockHandle, TestManager, WindowHandle};
+    use crate::{Command, DisplayAction, Manager};
+
+    fn manager_with_selection() -> TestManager {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        for i in [1, 3] {
+            manager.state.focus_window(&WindowHandle::<MockHandle>(i));
+            manager.command_handler(&Command::ToggleSelectWindow);
+        }
+        manager
+    }
+
+    fn selected(manager: &TestManager) -> Vec<WindowHandle<MockHandle>> {
+        manager
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.selected)
+            .map(|w| w.handle)
+            .collect()
+    }
+
+    #[test]
+    fn toggle_select_window_should_add_and_remove_the_focused_window() {
+        let mut manager = manager_with_selection();
+        assert_eq!(
+            selected(&manager),
+            vec![WindowHandle::<MockHandle>(1), WindowHandle::<MockHandle>(3)]
+        );
+
+        manager.command_handler(&Command::ToggleSelectWindow);
+        assert_eq!(selected(&manager), vec![WindowHandle::<MockHandle>(1)]);
+
+        manager.command_handler(&Command::ClearSelection);
+        assert!(selected(&manager).is_empty());
+    }
+
+    #[test]
+    fn send_selection_to_tag_should_move_only_selected_windows() {
+        let mut manager = manager_with_selection();
+        manager.command_handler(&Command::SendSelectionToTag(2));
+
+        let tags: Vec<_> = manager
+            .state
+            .windows
+            .iter()
+            .map(|w| (w.handle, w.tag))
+            .collect();
+        assert!(tags.contains(&(WindowHandle::<MockHandle>(1), Some(2))));
+        assert!(tags.contains(&(WindowHandle::<MockHandle>(2), Some(1))));
+        assert!(tags.contains(&(WindowHandle::<MockHandle>(3), Some(2))));
+        assert!(selected(&manager).is_empty());
+    }
+
+    #[test]
+    fn float_selection_should_float_only_selected_windows() {
+        let mut manager = manager_with_selection();
+        manager.command_handler(&Command::FloatSelection);
+
+        let floating: Vec<_> = manager
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.floating())
+            .map(|w| w.handle)
+            .collect();
+        assert_eq!(floating.len(), 2);
+        assert!(!floating.contains(&WindowHandle::<MockHandle>(2)));
+        assert!(selected(&manager).is_empty());
+    }
+
+    #[test]
+    fn close_selection_should_kill_only_selected_windows() {
+        let mut manager = manager_with_selection();
+        manager.state.actions.clear();
+        manager.command_handler(&Command::CloseSelection);
+
+        let killed: Vec<_> = manager
+            .state
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                DisplayAction::KillWindow(h) => Some(*h),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(killed.len(), 2);
+        assert!(!killed.contains(&WindowHandle::<MockHandle>(2)));
+    }
+}
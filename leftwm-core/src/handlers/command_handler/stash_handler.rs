@@ -0,0 +1,207 @@
+//! # Module for handling the window stash commands
+//! Stashed windows are moved to the hidden "STASH" tag all at once, like minimized windows, and
+//! are remembered together with their tag and position in its stack, so unstashing them puts them
+//! back exactly where they were, whatever tag is shown by then.
+use super::minimize_handler::retag;
+use crate::{
+    models::{Handle, WindowHandle, WindowState},
+    Config, DisplayAction, DisplayServer, Manager,
+};
+
+/// Label of the hidden tag stashed windows are stored on.
+pub const STASHED_TAG: &str = "STASH";
+
+/// Stash the selected windows, or else the windows of the classes configured in
+/// `stash_classes`.
+pub fn stash_windows<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    let state = &mut manager.state;
+    let stashed_tag = state.tags.get_hidden_by_label(STASHED_TAG)?.id;
+    let stashable = |selected: bool| {
+        let stash_classes = &state.stash_classes;
+        state
+            .windows
+            .iter()
+            .filter(|w| w.is_managed() && w.tag.is_some() && w.tag != Some(stashed_tag))
+            .filter(|w| {
+                if selected {
+                    return w.selected;
+                }
+                [&w.res_class, &w.res_name]
+                    .into_iter()
+                    .flatten()
+                    .any(|class| stash_classes.contains(class))
+            })
+            .filter_map(|w| Some((w.handle, w.tag?)))
+            .collect::<Vec<_>>()
+    };
+    let mut windows = stashable(true);
+    if windows.is_empty() {
+        windows = stashable(false);
+    }
+    if windows.is_empty() {
+        return None;
+    }
+
+    // All positions are taken before any window leaves its stack.
+    for (handle, tag) in &windows {
+        manager.state.remember_tag_position(handle, *tag);
+    }
+    for (handle, tag) in windows {
+        if retag(manager, handle, stashed_tag).is_none() {
+            continue;
+        }
+        manager.state.stashed.push((handle, tag));
+        let act = DisplayAction::SetState(handle, true, WindowState::Hidden);
+        manager.state.actions.push_back(act);
+        if let Some(window) = manager
+            .state
+            .windows
+            .iter_mut()
+            .find(|w| w.handle == handle)
+        {
+            window.selected = false;
+        }
+    }
+    refocus(manager);
+    Some(true)
+}
+
+/// Bring all stashed windows back to their tags and positions.
+pub fn unstash_windows<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) -> Option<bool> {
+    if manager.state.stashed.is_empty() {
+        return None;
+    }
+    let stashed: Vec<_> = manager.state.stashed.drain(..).collect();
+    let focused_tag = manager.state.focus_manager.tag(0).unwrap_or(1);
+    for &(handle, tag) in &stashed {
+        // The tag may be gone after a config change.
+        let tag = manager.state.tags.get(tag).map_or(focused_tag, |t| t.id);
+        if retag(manager, handle, tag).is_none() {
+            continue;
+        }
+        manager.state.restore_tag_position(&handle, tag);
+        let act = DisplayAction::SetState(handle, false, WindowState::Hidden);
+        manager.state.actions.push_back(act);
+    }
+    refocus(manager);
+    Some(true)
+}
+
+/// Focus the first window of the focused workspace if the focused window is not shown there.
+pub(super) fn refocus<H: Handle, C: Config, SERVER: DisplayServer<H>>(
+    manager: &mut Manager<H, C, SERVER>,
+) {
+    let state = &mut manager.state;
+    let Some(workspace) = state.focus_manager.workspace(&state.workspaces) else {
+        return;
+    };
+    let focused = state.focus_manager.window(&state.windows);
+    if focused.is_some_and(|w| workspace.is_managed(w)) {
+        return;
+    }
+    let first: Option<WindowHandle<H>> = state
+        .windows
+        .iter()
+        .find(|w| workspace.is_managed(w))
+        .map(|w| w.handle);
+    if let Some(handle) = first {
+        state.focus_window(&handle);
+    } else {
+        let act = DisplayAction::Unfocus(focused.map(|w| w.handle), false);
+        state.actions.push_back(act);
+        state.focus_manager.push_window_history(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::STASHED_TAG;
+    use crate::models::{MockHandle, TestManager, WindowHandle};
+    use crate::{Command, Manager};
+
+    fn manager_with_windows() -> TestManager {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 4);
+        for window in &mut manager.state.windows {
+            let class = if window.handle.0 % 2 == 0 {
+                "Steam"
+            } else {
+                "Alacritty"
+            };
+            window.res_class = Some(class.to_string());
+        }
+        manager.state.stash_classes = vec!["Steam".to_string()];
+        manager
+    }
+
+    fn handles(manager: &TestManager) -> Vec<i32> {
+        manager.state.windows.iter().map(|w| w.handle.0).collect()
+    }
+
+    #[test]
+    fn stashed_windows_should_come_back_where_they_were() {
+        let mut manager = manager_with_windows();
+        let order = handles(&manager);
+        let stashed_tag = manager
+            .state
+            .tags
+            .get_hidden_by_label(STASHED_TAG)
+            .unwrap()
+            .id;
+
+        manager.command_handler(&Command::StashWindows);
+        manager.update_windows();
+        for window in &manager.state.windows {
+            let stashed = window.handle.0 % 2 == 0;
+            assert_eq!(window.tag == Some(stashed_tag), stashed);
+            assert_eq!(window.visible(), !stashed);
+        }
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.unwrap().handle.0 % 2, 1);
+
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        manager.command_handler(&Command::UnstashWindows);
+        assert!(manager.state.stashed.is_empty());
+        assert!(manager.state.windows.iter().all(|w| w.tag == Some(1)));
+        assert_eq!(handles(&manager), order);
+    }
+
+    #[test]
+    fn selected_windows_should_be_stashed_instead_of_configured_ones() {
+        let mut manager = manager_with_windows();
+        manager.state.focus_window(&WindowHandle::<MockHandle>(3));
+        manager.command_handler(&Command::ToggleSelectWindow);
+
+        manager.command_handler(&Command::StashWindows);
+        assert_eq!(
+            manager.state.stashed,
+            vec![(WindowHandle::<MockHandle>(3), 1)]
+        );
+        assert!(manager.state.windows.iter().all(|w| !w.selected));
+    }
+
+    #[test]
+    fn windows_of_a_removed_tag_should_be_unstashed_to_the_focused_one() {
+        let mut manager = manager_with_windows();
+        manager.command_handler(&Command::StashWindows);
+        for (_, tag) in &mut manager.state.stashed {
+            *tag = 99;
+        }
+
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        manager.command_handler(&Command::UnstashWindows);
+        for window in &manager.state.windows {
+            let stashed = window.handle.0 % 2 == 0;
+            assert_eq!(window.tag == Some(2), stashed);
+        }
+    }
+}
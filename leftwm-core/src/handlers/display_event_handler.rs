@@ -1,4 +1,6 @@
+use super::window_resize_handler::start_split_resize;
 use super::{Config, DisplayEvent, Manager, Mode};
+use crate::config::{Corner, WmReplacedBehavior};
 use crate::display_action::DisplayAction;
 use crate::display_servers::DisplayServer;
 use crate::models::{Handle, WindowHandle, WindowState};
@@ -9,27 +11,92 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     /// Returns true if changes need to be rendered.
     pub fn display_event_handler(&mut self, event: DisplayEvent<H>) -> bool {
         let state = &mut self.state;
+        // Windows changed by their client are sent again, even when they stay where leftwm put
+        // them.
+        if let DisplayEvent::WindowChange(_) | DisplayEvent::ConfigureXlibWindow(_) = &event {
+            let handle = event.window();
+            if let Some(window) = state.windows.iter_mut().find(|w| Some(&w.handle) == handle) {
+                window.forget_applied();
+            }
+        }
         match event {
             DisplayEvent::ScreenCreate(s) => self.screen_create_handler(s),
+            DisplayEvent::ScreenDestroy(output) => self.screen_destroy_handler(&output),
             DisplayEvent::WindowCreate(w, x, y) => self.window_created_handler(w, x, y),
             DisplayEvent::WindowChange(w) => self.window_changed_handler(w),
             DisplayEvent::WindowDestroy(handle) => self.window_destroyed_handler(&handle),
             DisplayEvent::SendCommand(command) => self.command_handler(&command),
-            DisplayEvent::MouseCombo(mod_mask, button, handle, x, y) => self
-                .state
-                .mouse_combo_handler(&mod_mask, button, handle, x, y),
+            DisplayEvent::MouseCombo(mod_mask, button, handle, x, y) => {
+                self.mouse_combo_handler(&mod_mask, button, handle, x, y)
+            }
 
             DisplayEvent::WindowTakeFocus(handle) => from_window_take_focus(state, handle),
             DisplayEvent::HandleWindowFocus(handle) => from_handle_window_focus(state, handle),
             DisplayEvent::MoveFocusTo(x, y) => from_move_focus_to(state, x, y),
             DisplayEvent::VerifyFocusedAt(handle) => from_verify_focus_at(state, handle),
-            DisplayEvent::ChangeToNormalMode => from_change_to_normal_mode(state),
+            DisplayEvent::ChangeToNormalMode(x, y) => {
+                from_change_to_normal_mode(state, Some((x, y)))
+            }
+            DisplayEvent::MoveResizeRequest(mode) => from_move_resize_request(state, mode),
             DisplayEvent::Movement(handle, x, y) => from_movement(state, handle, x, y),
             DisplayEvent::MoveWindow(handle, x, y) => from_move_window(self, handle, x, y),
             DisplayEvent::ResizeWindow(handle, x, y) => from_resize_window(self, handle, x, y),
             DisplayEvent::ConfigureXlibWindow(handle) => from_configure_xlib_window(state, handle),
+            DisplayEvent::WmReplaced => self.wm_replaced_handler(),
+            DisplayEvent::UserIdle => {
+                state.user_idle = true;
+                false
+            }
+            DisplayEvent::UserActive => {
+                state.user_idle = false;
+                false
+            }
+            DisplayEvent::WindowResponding(handle, responding) => {
+                from_window_responding(state, handle, responding)
+            }
+            DisplayEvent::TitleBarPressed(handle) => from_title_bar_pressed(state, handle),
         }
     }
+
+    /// Another window manager asked to take over, step down or take the selection back.
+    fn wm_replaced_handler(&mut self) -> bool {
+        match self.config.wm_replaced_behavior() {
+            WmReplacedBehavior::Exit => {
+                tracing::warn!("Another window manager is taking over, exiting.");
+                self.replaced = true;
+            }
+            WmReplacedBehavior::Reacquire => {
+                tracing::warn!("Another window manager tried to take over, staying in charge.");
+                let act = DisplayAction::ReacquireWmSelection;
+                self.state.actions.push_back(act);
+            }
+        }
+        false
+    }
+}
+
+fn from_window_responding<H: Handle>(
+    state: &mut State<H>,
+    handle: WindowHandle<H>,
+    responding: bool,
+) -> bool {
+    if let Some(window) = state.windows.iter_mut().find(|w| w.handle == handle) {
+        if !responding {
+            tracing::warn!(
+                "{:?} ({:?}) is not responding, closing it again kills it.",
+                window.name,
+                handle
+            );
+        }
+        window.unresponsive = !responding;
+    }
+    false
+}
+
+/// Focus a window whose title bar was clicked, and drag it around like with the mouse key.
+fn from_title_bar_pressed<H: Handle>(state: &mut State<H>, handle: WindowHandle<H>) -> bool {
+    state.focus_window(&handle);
+    from_move_resize_request(state, Mode::ReadyToMove(handle))
 }
 
 fn from_window_take_focus<H: Handle>(state: &mut State<H>, handle: WindowHandle<H>) -> bool {
@@ -54,8 +121,16 @@ fn from_verify_focus_at<H: Handle>(state: &mut State<H>, handle: WindowHandle<H>
     false
 }
 
-fn from_change_to_normal_mode<H: Handle>(state: &mut State<H>) -> bool {
+/// Leave moving or resizing a window, `released_at` being where the pointer was released if it was.
+fn from_change_to_normal_mode<H: Handle>(
+    state: &mut State<H>,
+    released_at: Option<(i32, i32)>,
+) -> bool {
+    // Windows dragged by the pointer go to the workspace it was released over.
+    let dropped_at = released_at.filter(|_| matches!(state.mode, Mode::MovingWindow(_)));
     match state.mode {
+        // Tiled windows resizing the main column stay where they are.
+        Mode::ResizingWindow(h) if state.split_resize.take().is_some() => state.focus_window(&h),
         Mode::MovingWindow(h) | Mode::ResizingWindow(h) => {
             // We want to update the windows tag once it is done moving. This means
             // when the window is re-tiled it is on the correct workspace. This also
@@ -64,25 +139,22 @@ fn from_change_to_normal_mode<H: Handle>(state: &mut State<H>) -> bool {
             // it is only called once the window has stopped moving.
             if let Some(window) = state.windows.iter_mut().find(|w| w.handle == h) {
                 let loc = window.calculated_xyhw();
-                let (center_x, center_y) = loc.center();
-                let (margin_multiplier, tag, normal) = if let Some(ws) = state
-                    .workspaces
-                    .iter()
-                    .find(|ws| ws.contains_point(center_x, center_y))
-                {
-                    (ws.margin_multiplier(), ws.tag, ws.xyhw)
-                } else if let Some(ws) = state.workspaces.iter().find(|ws| {
-                    let dx =
-                        ws.xyhw.x() <= loc.x() + loc.w() && ws.xyhw.x() + ws.xyhw.w() >= loc.x();
-                    let dy =
-                        ws.xyhw.y() <= loc.y() + loc.h() && ws.xyhw.y() + ws.xyhw.h() >= loc.y();
-                    dx && dy
-                }) {
-                    (ws.margin_multiplier(), ws.tag, ws.xyhw)
-                } else {
-                    tracing::debug!("No matching workspace found for {:?}", window.handle);
-                    (1.0, Some(1), window.normal)
-                };
+                let (x, y) = dropped_at.unwrap_or_else(|| loc.center());
+                let (margin_multiplier, tag, normal) =
+                    if let Some(ws) = state.workspaces.iter().find(|ws| ws.contains_point(x, y)) {
+                        (ws.margin_multiplier(), ws.tag, ws.xyhw)
+                    } else if let Some(ws) = state.workspaces.iter().find(|ws| {
+                        let dx = ws.xyhw.x() <= loc.x() + loc.w()
+                            && ws.xyhw.x() + ws.xyhw.w() >= loc.x();
+                        let dy = ws.xyhw.y() <= loc.y() + loc.h()
+                            && ws.xyhw.y() + ws.xyhw.h() >= loc.y();
+                        dx && dy
+                    }) {
+                        (ws.margin_multiplier(), ws.tag, ws.xyhw)
+                    } else {
+                        tracing::debug!("No matching workspace found for {:?}", window.handle);
+                        (1.0, Some(1), window.normal)
+                    };
                 let mut offset = window.get_floating_offsets().unwrap_or_default();
                 // Re-adjust the floating offsets to the new workspace.
                 let exact = window.normal + offset;
@@ -101,6 +173,46 @@ fn from_change_to_normal_mode<H: Handle>(state: &mut State<H>) -> bool {
     true
 }
 
+/// Start moving or resizing a window with the pointer as if dragged with the mouse key, when a
+/// client asks to through `_NET_WM_MOVERESIZE`, or stop where it is when asked to cancel.
+fn from_move_resize_request<H: Handle>(state: &mut State<H>, mode: Mode<H>) -> bool {
+    let act = match mode {
+        Mode::Normal if state.mode != Mode::Normal => {
+            state.actions.push_back(DisplayAction::NormalMode);
+            return from_change_to_normal_mode(state, None);
+        }
+        Mode::ReadyToMove(h) | Mode::ReadyToResize(h) if state.mode == Mode::Normal => {
+            let Some(window) = state.windows.iter().find(|w| w.handle == h) else {
+                return false;
+            };
+            if state.disable_tile_drag && !window.floating() {
+                return false;
+            }
+            if let Mode::ReadyToMove(_) = mode {
+                if !window.can_move() {
+                    return false;
+                }
+                DisplayAction::ReadyToMoveWindow(h)
+            } else {
+                if !window.can_resize() {
+                    return false;
+                }
+                state.resize_corner = Corner::BottomRight;
+                if state.reposition_cursor_on_resize {
+                    let bottom_right = (window.x() + window.width(), window.y() + window.height());
+                    let act = DisplayAction::MoveMouseOverPoint(bottom_right);
+                    state.actions.push_back(act);
+                }
+                DisplayAction::ReadyToResizeWindow(h)
+            }
+        }
+        _ => return false,
+    };
+    state.mode = mode;
+    state.actions.push_back(act);
+    false
+}
+
 fn from_movement<H: Handle>(state: &mut State<H>, handle: WindowHandle<H>, x: i32, y: i32) -> bool {
     if state.screens.iter().any(|s| s.root == handle) {
         state.focus_workspace_with_point(x, y);
@@ -135,7 +247,10 @@ fn from_resize_window<H: Handle, C: Config, SERVER: DisplayServer<H>>(
     // Setup for when window first resizes.
     if let Mode::ReadyToResize(h) = manager.state.mode {
         manager.state.mode = Mode::ResizingWindow(h);
-        prepare_window(&mut manager.state, h);
+        manager.state.split_resize = start_split_resize(&mut manager.state, h);
+        if manager.state.split_resize.is_none() {
+            prepare_window(&mut manager.state, h);
+        }
     }
     manager.window_resize_handler(&handle, x, y)
 }
@@ -194,3 +309,207 @@ fn prepare_window<H: Handle>(state: &mut State<H>, handle: WindowHandle<H>) {
     }
     state.move_to_top(&handle);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{tests::TestConfig, WmReplacedBehavior};
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::{BBox, MockHandle, Mode, Screen, Window, WindowHandle, XyhwBuilder};
+    use crate::{Command, DisplayAction, DisplayEvent, Manager};
+    use leftwm_layouts::geometry::Size;
+
+    #[test]
+    fn being_replaced_should_stop_the_manager_by_default() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.display_event_handler(DisplayEvent::WmReplaced);
+        assert!(manager.replaced);
+    }
+
+    #[test]
+    fn being_replaced_should_reacquire_the_selection_when_configured() {
+        let mut manager: Manager<MockHandle, TestConfig, MockDisplayServer<MockHandle>> =
+            Manager::new(TestConfig {
+                wm_replaced_behavior: WmReplacedBehavior::Reacquire,
+                ..TestConfig::default()
+            });
+        manager.display_event_handler(DisplayEvent::WmReplaced);
+        assert!(!manager.replaced);
+        assert!(matches!(
+            manager.state.actions.back(),
+            Some(DisplayAction::ReacquireWmSelection)
+        ));
+    }
+
+    #[test]
+    fn resizing_tiled_windows_should_resize_the_main_column() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.resize_tiled_splits = true;
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        for i in 1..=2 {
+            let window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        let ws = &manager.state.workspaces[0];
+        let (id, tag) = (ws.id, ws.tag.unwrap());
+        manager
+            .state
+            .layout_manager
+            .set_layout(id, tag, "MainAndVertStack");
+        let main_size = |manager: &mut Manager<_, _, _>| {
+            manager.state.layout_manager.layout(id, tag).main_size()
+        };
+        let Some(Size::Ratio(start)) = main_size(&mut manager) else {
+            panic!("MainAndVertStack should have a main column sized by ratio");
+        };
+
+        // The second window created is the main one, and grows with the drag.
+        let main = manager.state.windows[0].handle;
+        manager.state.mode = Mode::ReadyToResize(main);
+        manager.display_event_handler(DisplayEvent::ResizeWindow(main, 100, 50));
+        assert_eq!(main_size(&mut manager), Some(Size::Ratio(start + 0.1)));
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode(0, 0));
+
+        // The stack shrinks the main column as it grows.
+        let stack = manager.state.windows[1].handle;
+        manager.state.mode = Mode::ReadyToResize(stack);
+        manager.display_event_handler(DisplayEvent::ResizeWindow(stack, 200, 0));
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode(0, 0));
+        assert_eq!(
+            main_size(&mut manager),
+            Some(Size::Ratio(start + 0.1 - 0.2))
+        );
+
+        assert!(manager.state.windows.iter().all(|w| !w.floating()));
+        assert!(manager.state.split_resize.is_none());
+        assert_eq!(manager.state.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn move_resize_requests_should_drag_windows_like_the_mouse_key() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        let handle = WindowHandle::<MockHandle>(1);
+        manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+        manager.state.actions.clear();
+
+        manager.display_event_handler(DisplayEvent::MoveResizeRequest(Mode::ReadyToMove(handle)));
+        assert_eq!(manager.state.mode, Mode::ReadyToMove(handle));
+        assert!(matches!(
+            manager.state.actions.pop_front(),
+            Some(DisplayAction::ReadyToMoveWindow(h)) if h == handle
+        ));
+
+        // Only one drag at a time.
+        let resize = DisplayEvent::MoveResizeRequest(Mode::ReadyToResize(handle));
+        manager.display_event_handler(resize.clone());
+        assert_eq!(manager.state.mode, Mode::ReadyToMove(handle));
+        assert!(manager.state.actions.is_empty());
+
+        manager.display_event_handler(DisplayEvent::MoveResizeRequest(Mode::Normal));
+        assert_eq!(manager.state.mode, Mode::Normal);
+        assert!(matches!(
+            manager.state.actions.pop_front(),
+            Some(DisplayAction::NormalMode)
+        ));
+
+        manager.state.disable_tile_drag = true;
+        manager.display_event_handler(resize);
+        assert_eq!(manager.state.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn closing_an_unresponsive_window_should_kill_it() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        let handle = WindowHandle::<MockHandle>(1);
+        manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+        manager.state.actions.clear();
+
+        manager.display_event_handler(DisplayEvent::WindowResponding(handle, false));
+        manager.command_handler(&Command::CloseWindow);
+        assert!(matches!(
+            manager.state.actions.pop_front(),
+            Some(DisplayAction::ForceKillWindow(h)) if h == handle
+        ));
+
+        manager.display_event_handler(DisplayEvent::WindowResponding(handle, true));
+        manager.command_handler(&Command::CloseWindow);
+        assert!(matches!(
+            manager.state.actions.pop_front(),
+            Some(DisplayAction::KillWindow(h)) if h == handle
+        ));
+    }
+
+    #[test]
+    fn pressing_a_title_bar_should_focus_and_move_the_window() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            let window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        let handle = WindowHandle::<MockHandle>(1);
+        manager.state.focus_window(&WindowHandle::<MockHandle>(2));
+
+        manager.display_event_handler(DisplayEvent::TitleBarPressed(handle));
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.map(|w| w.handle), Some(handle));
+        assert_eq!(manager.state.mode, Mode::ReadyToMove(handle));
+        assert!(matches!(
+            manager.state.actions.pop_back(),
+            Some(DisplayAction::ReadyToMoveWindow(h)) if h == handle
+        ));
+    }
+
+    #[test]
+    fn dropping_a_window_should_move_it_to_the_workspace_under_the_pointer() {
+        let mut manager: Manager<MockHandle, TestConfig, MockDisplayServer<MockHandle>> =
+            Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        for x in [0, 1000] {
+            manager.screen_create_handler(Screen::new(
+                BBox {
+                    x,
+                    y: 0,
+                    width: 1000,
+                    height: 800,
+                },
+                String::new(),
+            ));
+        }
+        let workspace = manager.state.workspaces[0].clone();
+        manager.state.focus_workspace(&workspace);
+        let handle = WindowHandle::<MockHandle>(1);
+        manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+        let window = &mut manager.state.windows[0];
+        window.set_floating(true);
+        window.set_floating_exact(
+            XyhwBuilder {
+                x: 700,
+                y: 100,
+                w: 200,
+                h: 200,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        let tag = |manager: &Manager<_, _, _>| manager.state.windows[0].tag;
+
+        // Cancelled, the window stays on the workspace its center is on.
+        manager.state.mode = Mode::MovingWindow(handle);
+        manager.display_event_handler(DisplayEvent::MoveResizeRequest(Mode::Normal));
+        assert_eq!(tag(&manager), Some(1));
+
+        manager.state.mode = Mode::MovingWindow(handle);
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode(1100, 150));
+        assert_eq!(tag(&manager), Some(2));
+        assert_eq!(manager.state.mode, Mode::Normal);
+    }
+}
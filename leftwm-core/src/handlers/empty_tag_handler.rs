@@ -0,0 +1,94 @@
+use super::{Config, Manager};
+use crate::child_process::exec_shell;
+use crate::config::EmptyTagBehavior;
+use crate::display_servers::DisplayServer;
+use crate::models::{Handle, TagId, WorkspaceId};
+
+impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
+    /// React the configured way to the last window of `tag` going away while it is shown on
+    /// `workspace`.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn empty_tag_handler(&mut self, workspace: WorkspaceId, tag: TagId) -> bool {
+        let Some(label) = self.state.tags.get(tag).map(|t| t.label.clone()) else {
+            return false;
+        };
+        match self.config.empty_tag_behavior(&label) {
+            EmptyTagBehavior::Nothing => false,
+            EmptyTagBehavior::Execute(command) => {
+                exec_shell(&command, &mut self.children);
+                false
+            }
+            EmptyTagBehavior::GoToLastTag => self.goto_last_occupied_tag(workspace, tag),
+            EmptyTagBehavior::ResetLayout => {
+                self.state.layout_manager.reset(workspace, tag);
+                true
+            }
+        }
+    }
+
+    /// Show the most recently shown tag with windows in place of `tag`, if `workspace` is focused.
+    fn goto_last_occupied_tag(&mut self, workspace: WorkspaceId, tag: TagId) -> bool {
+        let state = &mut self.state;
+        let focused = state.focus_manager.workspace(&state.workspaces);
+        if focused.map(|ws| ws.id) != Some(workspace) {
+            return false;
+        }
+        let last = state.focus_manager.tag_history.iter().copied().find(|&t| {
+            t != tag
+                && state
+                    .windows
+                    .iter()
+                    .any(|w| w.has_tag(&t) && w.is_managed())
+        });
+        last.and_then(|t| state.goto_tag_handler(t))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::EmptyTagBehavior;
+    use crate::models::{MockHandle, TestManager, WindowHandle};
+    use crate::{Manager, Window};
+
+    fn manager(empty_tag_behavior: EmptyTagBehavior) -> TestManager {
+        let tags = vec!["1".to_string(), "2".to_string(), "3".to_string()];
+        let mut manager = Manager::new_test_with_windows(tags, 0);
+        manager.config.empty_tag_behavior = empty_tag_behavior;
+        manager
+    }
+
+    #[test]
+    fn emptied_tags_should_switch_to_the_last_tag_with_windows() {
+        let mut manager = manager(EmptyTagBehavior::GoToLastTag);
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.goto_tag_handler(3);
+        manager.state.goto_tag_handler(2);
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(2), None, None),
+            -1,
+            -1,
+        );
+        manager.window_destroyed_handler(&WindowHandle(2));
+
+        assert!(manager.empty_tag_handler(1, 2));
+        assert_eq!(manager.state.workspaces[0].tag, Some(1));
+    }
+
+    #[test]
+    fn emptied_tags_should_get_their_layout_reset() {
+        let mut manager = manager(EmptyTagBehavior::ResetLayout);
+        let layout = |manager: &mut TestManager| manager.state.layout_manager.layout(1, 1).clone();
+        let default = layout(&mut manager);
+        manager.state.layout_manager.cycle_next_layout(1, 1);
+        assert_ne!(layout(&mut manager), default);
+
+        assert!(manager.empty_tag_handler(1, 1));
+        assert_eq!(layout(&mut manager), default);
+    }
+}
@@ -1,7 +1,8 @@
 #![allow(clippy::wildcard_imports)]
 
 use super::*;
-use crate::models::{Handle, TagId};
+use crate::config::UrgencyBehavior;
+use crate::models::{FocusFollowsWorkspace, Handle, TagId, FOCUS_HISTORY_LENGTH};
 use crate::state::State;
 use crate::{display_action::DisplayAction, models::FocusBehaviour};
 
@@ -56,6 +57,49 @@ impl<H: Handle> State<H> {
         }
     }
 
+    /// Moves input onto the focused workspace after a command focused it, as asked by
+    /// `focus_follows_workspace`: to its focused window when shown there, or else its top window.
+    pub fn focus_follow_workspace(&mut self) {
+        let follows = self.focus_manager.focus_follows_workspace;
+        if follows == FocusFollowsWorkspace::Nothing {
+            return;
+        }
+        let Some(ws) = self.focus_manager.workspace(&self.workspaces).cloned() else {
+            return;
+        };
+        // The window under the cursor is likely on another workspace.
+        self.actions
+            .retain(|act| !matches!(act, DisplayAction::FocusWindowUnderCursor));
+        let move_cursor = follows == FocusFollowsWorkspace::KeyboardAndCursor
+            || (self.focus_manager.behaviour.is_sloppy()
+                && self.focus_manager.sloppy_mouse_follows_focus);
+
+        let focused = self
+            .focus_manager
+            .window(&self.windows)
+            .filter(|w| ws.is_managed(w))
+            .map(|w| w.handle);
+        let handle = focused.or_else(|| {
+            self.windows
+                .iter()
+                .find(|w| ws.is_managed(w) && !w.never_focus)
+                .map(|w| w.handle)
+        });
+        let Some(handle) = handle else {
+            self.unfocus_current_window();
+            if move_cursor {
+                let act = DisplayAction::MoveMouseOverPoint(ws.xyhw.center());
+                self.actions.push_back(act);
+            }
+            return;
+        };
+        self.focus_window(&handle);
+        if move_cursor {
+            self.actions
+                .push_back(DisplayAction::MoveMouseOver(handle, true));
+        }
+    }
+
     /// Focuses the given tag.
     // NOTE: Should only be called externally from this file.
     pub fn focus_tag(&mut self, tag: &TagId) {
@@ -177,7 +221,9 @@ impl<H: Handle> State<H> {
             }
         };
         // Clean old history.
-        self.focus_manager.tag_history.truncate(10);
+        self.focus_manager
+            .tag_history
+            .truncate(FOCUS_HISTORY_LENGTH - 1);
         // Add this focus to the history.
         self.focus_manager.tag_history.push_front(tag);
 
@@ -190,7 +236,7 @@ impl<H: Handle> State<H> {
         if self.screens.iter().any(|s| &s.root == handle) {
             let act = DisplayAction::Unfocus(None, false);
             self.actions.push_back(act);
-            self.focus_manager.window_history.push_front(None);
+            self.focus_manager.push_window_history(None);
             return None;
         }
         // Find the handle in our managed windows.
@@ -213,10 +259,11 @@ impl<H: Handle> State<H> {
             }
         }
 
-        // Clean old history.
-        self.focus_manager.window_history.truncate(10);
         // Add this focus change to the history.
-        self.focus_manager.window_history.push_front(Some(*handle));
+        self.focus_manager.push_window_history(Some(*handle));
+        if let Some(tag) = found.tag {
+            self.focus_manager.push_tag_window_history(tag, *handle);
+        }
 
         let act = DisplayAction::WindowTakeFocus {
             window: found.clone(),
@@ -224,7 +271,12 @@ impl<H: Handle> State<H> {
         };
         self.actions.push_back(act);
 
-        Some(found.clone())
+        let found = found.clone();
+        if self.urgency_behavior == UrgencyBehavior::ClearOnFocus {
+            self.clear_urgency(handle);
+        }
+
+        Some(found)
     }
 
     fn focus_workspace_work(&mut self, ws_id: usize) -> bool {
@@ -235,7 +287,9 @@ impl<H: Handle> State<H> {
             }
         }
         // Clean old history.
-        self.focus_manager.workspace_history.truncate(10);
+        self.focus_manager
+            .workspace_history
+            .truncate(FOCUS_HISTORY_LENGTH - 1);
         // Add this focus to the history.
         if let Some(index) = self.workspaces.iter().position(|x| x.id == ws_id) {
             self.focus_manager.workspace_history.push_front(index);
@@ -250,7 +304,7 @@ impl<H: Handle> State<H> {
                 Some(window.handle),
                 window.floating(),
             ));
-            self.focus_manager.window_history.push_front(None);
+            self.focus_manager.push_window_history(None);
             if let Some(tag_id) = &window.tag {
                 self.focus_manager
                     .tags_last_window
@@ -28,10 +28,15 @@ impl<H: Handle> State<H> {
         }
 
         if let Some(ws) = self.workspaces.iter_mut().find(|ws| ws.tag == new_tag) {
-            ws.tag = Some(old_tag);
+            ws.show_tag(&old_tag);
+        }
+        for ws in &mut self.workspaces {
+            ws.extra_tags.retain(|&tag| tag != tag_id);
         }
 
-        self.focus_manager.workspace_mut(&mut self.workspaces)?.tag = new_tag;
+        let ws = self.focus_manager.workspace_mut(&mut self.workspaces)?;
+        ws.show_tag(&tag_id);
+        ws.extra_tags.clear();
         self.focus_tag(&tag_id);
         self.update_static();
 
@@ -42,7 +47,7 @@ impl<H: Handle> State<H> {
 #[cfg(test)]
 mod tests {
     use crate::models::Screen;
-    use crate::Manager;
+    use crate::{Command, Manager};
 
     #[test]
     fn going_to_a_workspace_that_is_already_visible_should_not_duplicate_the_workspace() {
@@ -53,4 +58,33 @@ mod tests {
         assert_eq!(manager.state.workspaces[0].tag, Some(2));
         assert_eq!(manager.state.workspaces[1].tag, Some(1));
     }
+
+    #[test]
+    fn going_back_should_show_the_last_tag_of_the_focused_workspace() {
+        let tags = ["1", "2", "3", "4"].map(String::from).to_vec();
+        let mut manager = Manager::new_test(tags);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        let focus_workspace = |manager: &mut Manager<_, _, _>, index: usize| {
+            let workspace = manager.state.workspaces[index].clone();
+            manager.state.focus_workspace(&workspace);
+        };
+        focus_workspace(&mut manager, 0);
+        manager.state.goto_tag_handler(3);
+        focus_workspace(&mut manager, 1);
+        manager.state.goto_tag_handler(4);
+        focus_workspace(&mut manager, 0);
+
+        manager.command_handler(&Command::ReturnToLastTag);
+        assert_eq!(manager.state.workspaces[0].tag, Some(1));
+        assert_eq!(manager.state.workspaces[1].tag, Some(4));
+        assert_eq!(manager.state.workspaces[0].tag_history, vec![3]);
+
+        manager.command_handler(&Command::GoToTag { tag: 1, swap: true });
+        assert_eq!(manager.state.workspaces[0].tag, Some(3));
+        manager.command_handler(&Command::GoToTag { tag: 4, swap: true });
+        assert_eq!(manager.state.workspaces[0].tag, Some(4));
+        assert_eq!(manager.state.workspaces[1].tag, Some(3));
+        assert_eq!(manager.state.workspaces[1].tag_history, vec![4, 2]);
+    }
 }
@@ -0,0 +1,559 @@
+//! Answers i3 IPC requests from the state: i3 workspaces are leftwm tags, shown on the output
+//! of the workspace showing them.
+use super::{Command, Config, Manager, Window, WindowHandle};
+use crate::command::FocusDeltaBehavior;
+use crate::display_servers::DisplayServer;
+use crate::hook::Hook;
+use crate::models::{Handle, TagId, Xyhw};
+use crate::state::State;
+use crate::utils::command_pipe::parse_command;
+use crate::utils::i3_ipc::{self, Request};
+use crate::WindowSelector;
+use leftwm_layouts::geometry::Direction;
+use serde_json::{json, Value};
+
+/// The first node ids of outputs, their content and workspaces, above the ids of X windows.
+const OUTPUT_IDS: u64 = 1 << 32;
+const CONTENT_IDS: u64 = 2 << 32;
+const WORKSPACE_IDS: u64 = 3 << 32;
+
+impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
+    /// Answer a request of an i3 IPC client. Returns whether the display needs a refresh.
+    pub fn i3_ipc_handler(&mut self, request: Request) -> bool {
+        let mut needs_refresh = false;
+        let reply = match request.kind {
+            i3_ipc::RUN_COMMAND => {
+                let (results, refresh) = self.run_i3_commands(&request.payload);
+                needs_refresh = refresh;
+                results
+            }
+            i3_ipc::GET_WORKSPACES => Value::Array(workspaces(&self.state)),
+            i3_ipc::GET_OUTPUTS => outputs(&self.state),
+            i3_ipc::GET_TREE => tree(&self.state),
+            i3_ipc::GET_VERSION => json!({
+                "major": 4,
+                "minor": 0,
+                "patch": 0,
+                "human_readable": format!("leftwm {}", env!("CARGO_PKG_VERSION")),
+                "loaded_config_file_name": "",
+            }),
+            _ => json!({"success": false, "error": "unsupported message type"}),
+        };
+        request.reply(reply.to_string());
+        needs_refresh
+    }
+
+    /// Run the commands of a `RUN_COMMAND` request, returning the result of each.
+    fn run_i3_commands(&mut self, payload: &str) -> (Value, bool) {
+        let mut needs_refresh = false;
+        let results = split_commands(payload)
+            .into_iter()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| match translate(text, &self.state) {
+                Ok(commands) => {
+                    for command in &commands {
+                        needs_refresh = self.command_handler(command) || needs_refresh;
+                    }
+                    json!({"success": true})
+                }
+                Err(error) => json!({"success": false, "error": error}),
+            })
+            .collect();
+        (Value::Array(results), needs_refresh)
+    }
+
+    /// Send the events for `hooks` to the subscribed i3 IPC clients.
+    pub(crate) fn send_i3_events(&self, hooks: &[Hook<H>]) {
+        let Some(server) = &self.i3_ipc else {
+            return;
+        };
+        for hook in hooks {
+            let (kind, event) = match hook {
+                Hook::WindowCreated(handle) => (
+                    i3_ipc::EVENT_WINDOW,
+                    json!({"change": "new", "container": container(&self.state, *handle)}),
+                ),
+                Hook::WindowDestroyed(handle) => (
+                    i3_ipc::EVENT_WINDOW,
+                    json!({"change": "close", "container": container(&self.state, *handle)}),
+                ),
+                Hook::FocusChanged(Some(handle)) => (
+                    i3_ipc::EVENT_WINDOW,
+                    json!({"change": "focus", "container": container(&self.state, *handle)}),
+                ),
+                Hook::FocusChanged(None) | Hook::UserIdle | Hook::UserActive => continue,
+                Hook::TagChanged { tag, .. } => (
+                    i3_ipc::EVENT_WORKSPACE,
+                    json!({
+                        "change": "focus",
+                        "current": tag.and_then(|tag| workspace(&self.state, tag)),
+                        "old": null,
+                    }),
+                ),
+                Hook::TagEmptied { tag, .. } => (
+                    i3_ipc::EVENT_WORKSPACE,
+                    json!({
+                        "change": "empty",
+                        "current": workspace(&self.state, *tag),
+                        "old": null,
+                    }),
+                ),
+            };
+            server.send_event(kind, event.to_string());
+        }
+    }
+}
+
+/// Split the commands of a `RUN_COMMAND` payload, separated by `;` or `,`. An `exec` takes the
+/// rest of the payload, for the command line it runs to hold them.
+fn split_commands(payload: &str) -> Vec<&str> {
+    let mut commands = vec![];
+    let mut rest = payload;
+    loop {
+        let command = rest.trim_start();
+        let action = command
+            .strip_prefix('[')
+            .and_then(|text| text.split_once(']'))
+            .map_or(command, |(_, action)| action.trim_start());
+        let is_exec = action.split_whitespace().next() == Some("exec");
+        match command.split_once([';', ',']) {
+            Some((first, tail)) if !is_exec => {
+                commands.push(first);
+                rest = tail;
+            }
+            _ => {
+                commands.push(command);
+                return commands;
+            }
+        }
+    }
+}
+
+/// The leftwm commands for an i3 command.
+fn translate<H: Handle>(text: &str, state: &State<H>) -> Result<Vec<Command<H>>, String> {
+    let (window, text) = criteria(text)?;
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let commands = match words.as_slice() {
+        ["nop", ..] => vec![],
+        ["focus"] => match window {
+            Some(handle) => {
+                let command = Command::ActivateWindow(WindowSelector::Handle(handle));
+                return Ok(vec![command]);
+            }
+            None => return Err("nothing to focus".to_string()),
+        },
+        ["focus", direction] => vec![Command::FocusWindowAt(direction_of(direction)?)],
+        ["workspace", "next" | "next_on_output"] => vec![Command::FocusNextTag {
+            behavior: FocusDeltaBehavior::Default,
+        }],
+        ["workspace", "prev" | "prev_on_output"] => vec![Command::FocusPreviousTag {
+            behavior: FocusDeltaBehavior::Default,
+        }],
+        ["workspace", "back_and_forth"] => vec![Command::ReturnToLastTag],
+        ["workspace", name @ ..] => vec![Command::GoToTag {
+            tag: tag_named(name, state)?,
+            swap: false,
+        }],
+        ["move", rest @ ..] => {
+            let rest = rest
+                .strip_prefix(&["container"])
+                .or_else(|| rest.strip_prefix(&["window"]))
+                .unwrap_or(rest);
+            let rest = rest.strip_prefix(&["to"]).unwrap_or(rest);
+            match rest {
+                ["workspace", name @ ..] => vec![Command::SendWindowToTag {
+                    window: None,
+                    tag: tag_named(name, state)?,
+                }],
+                [direction] => vec![Command::MoveWindowAt(direction_of(direction)?)],
+                _ => return Err(format!("unsupported command: {text}")),
+            }
+        }
+        ["kill"] => vec![Command::CloseWindow],
+        ["floating", "toggle"] => vec![Command::ToggleFloating],
+        ["floating", "enable"] => vec![Command::TileToFloating],
+        ["floating", "disable"] => vec![Command::FloatingToTile],
+        ["fullscreen", ..] => vec![Command::ToggleFullScreen],
+        ["exec", ..] => {
+            let command = text
+                .trim_start_matches("exec")
+                .trim_start()
+                .trim_start_matches("--no-startup-id")
+                .trim();
+            vec![Command::Execute(command.to_string())]
+        }
+        // Commands of leftwm itself, as for `leftwm-command`.
+        _ => match parse_command(text) {
+            Ok(Command::Other(_)) | Err(_) => return Err(format!("unsupported command: {text}")),
+            Ok(command) => vec![command],
+        },
+    };
+    match window {
+        Some(window) => commands
+            .into_iter()
+            .map(|command| {
+                on_window(command, window)
+                    .ok_or_else(|| format!("criteria are not supported for: {text}"))
+            })
+            .collect(),
+        None => Ok(commands),
+    }
+}
+
+/// `command` acting on `window` in place of the focused window, if it can.
+fn on_window<H: Handle>(command: Command<H>, window: WindowHandle<H>) -> Option<Command<H>> {
+    let window = Some(window);
+    match command {
+        Command::AttachScratchPad { scratchpad, .. } => {
+            Some(Command::AttachScratchPad { window, scratchpad })
+        }
+        Command::SendWindowToTag { tag, .. } => Some(Command::SendWindowToTag { window, tag }),
+        Command::ClearUrgent { .. } => Some(Command::ClearUrgent { window }),
+        _ => None,
+    }
+}
+
+/// Split the `[con_id=...]` or `[id=...]` criteria off a command.
+fn criteria<H: Handle>(text: &str) -> Result<(Option<WindowHandle<H>>, &str), String> {
+    let Some(rest) = text.strip_prefix('[') else {
+        return Ok((None, text));
+    };
+    let (criteria, command) = rest
+        .split_once(']')
+        .ok_or_else(|| format!("unterminated criteria: {text}"))?;
+    let id = criteria
+        .trim()
+        .strip_prefix("con_id=")
+        .or_else(|| criteria.trim().strip_prefix("id="))
+        .ok_or_else(|| format!("unsupported criteria: {criteria}"))?;
+    let id: u64 = id
+        .trim_matches('"')
+        .parse()
+        .map_err(|_| format!("invalid window id: {id}"))?;
+    let handle =
+        serde_json::from_value(json!(id)).map_err(|_| format!("invalid window id: {id}"))?;
+    Ok((Some(handle), command.trim()))
+}
+
+fn direction_of(direction: &str) -> Result<Direction, String> {
+    match direction {
+        "left" => Ok(Direction::West),
+        "right" => Ok(Direction::East),
+        "up" => Ok(Direction::North),
+        "down" => Ok(Direction::South),
+        _ => Err(format!("unsupported direction: {direction}")),
+    }
+}
+
+/// The tag of a workspace name, or of its number with `number`.
+fn tag_named<H: Handle>(name: &[&str], state: &State<H>) -> Result<TagId, String> {
+    let name = name.strip_prefix(&["number"]).unwrap_or(name).join(" ");
+    let tags = state.tags.normal();
+    tags.iter()
+        .find(|tag| tag.label == name)
+        .map(|tag| tag.id)
+        .or_else(|| {
+            let number: String = name.chars().take_while(char::is_ascii_digit).collect();
+            number
+                .parse()
+                .ok()
+                .filter(|id| tags.iter().any(|tag| tag.id == *id))
+        })
+        .ok_or_else(|| format!("no such workspace: {name}"))
+}
+
+fn rect(xyhw: &Xyhw) -> Value {
+    json!({"x": xyhw.x(), "y": xyhw.y(), "width": xyhw.w(), "height": xyhw.h()})
+}
+
+fn window_id<H: Handle>(handle: WindowHandle<H>) -> Value {
+    serde_json::to_value(handle).unwrap_or(Value::Null)
+}
+
+/// The output and area of the workspace showing `tag`, or of the first workspace.
+fn placement<H: Handle>(state: &State<H>, tag: TagId) -> (String, Value) {
+    let ws = state
+        .workspaces
+        .iter()
+        .find(|ws| ws.tag == Some(tag))
+        .or_else(|| state.workspaces.first());
+    let output = ws
+        .and_then(|ws| state.screens.iter().find(|s| s.id == Some(ws.id)))
+        .map(|s| s.output.clone())
+        .unwrap_or_default();
+    (output, ws.map_or(Value::Null, |ws| rect(&ws.xyhw)))
+}
+
+/// The tags i3 would have workspaces for: the visible ones and those with windows.
+fn existing_tags<H: Handle>(state: &State<H>) -> Vec<TagId> {
+    state
+        .tags
+        .normal()
+        .iter()
+        .map(|tag| tag.id)
+        .filter(|id| {
+            state.workspaces.iter().any(|ws| ws.tag == Some(*id))
+                || state.windows.iter().any(|w| w.has_tag(id))
+        })
+        .collect()
+}
+
+/// The `GET_WORKSPACES` entry of a tag.
+fn workspace<H: Handle>(state: &State<H>, tag: TagId) -> Option<Value> {
+    let label = &state.tags.get(tag)?.label;
+    let focused = state
+        .focus_manager
+        .workspace(&state.workspaces)
+        .and_then(|ws| ws.tag);
+    let (output, rect) = placement(state, tag);
+    Some(json!({
+        "id": WORKSPACE_IDS + tag as u64,
+        "num": tag,
+        "name": label,
+        "visible": state.workspaces.iter().any(|ws| ws.tag == Some(tag)),
+        "focused": focused == Some(tag),
+        "urgent": state.windows.iter().any(|w| w.has_tag(&tag) && w.urgent),
+        "rect": rect,
+        "output": output,
+    }))
+}
+
+fn workspaces<H: Handle>(state: &State<H>) -> Vec<Value> {
+    existing_tags(state)
+        .into_iter()
+        .filter_map(|tag| workspace(state, tag))
+        .collect()
+}
+
+fn outputs<H: Handle>(state: &State<H>) -> Value {
+    state
+        .workspaces
+        .iter()
+        .map(|ws| {
+            let output = state
+                .screens
+                .iter()
+                .find(|s| s.id == Some(ws.id))
+                .map(|s| s.output.clone())
+                .unwrap_or_default();
+            let current = ws.tag.and_then(|tag| state.tags.get(tag));
+            json!({
+                "name": output,
+                "active": true,
+                "primary": ws.id == 1,
+                "current_workspace": current.map(|tag| tag.label.clone()),
+                "rect": rect(&ws.xyhw),
+            })
+        })
+        .collect()
+}
+
+/// The tree node of a window.
+fn window_node<H: Handle>(window: &Window<H>, focused: bool) -> Value {
+    let id = window_id(window.handle);
+    json!({
+        "id": id,
+        "type": "con",
+        "name": window.name,
+        "window": id,
+        "focused": focused,
+        "urgent": window.urgent,
+        "floating": if window.floating() { "user_on" } else { "user_off" },
+        "fullscreen_mode": u8::from(window.is_fullscreen()),
+        "rect": rect(&window.calculated_xyhw()),
+        "window_properties": {
+            "class": window.res_class,
+            "instance": window.res_name,
+            "title": window.name,
+        },
+        "nodes": [],
+        "floating_nodes": [],
+    })
+}
+
+/// The node of a window for an event, just its ids once it is gone.
+fn container<H: Handle>(state: &State<H>, handle: WindowHandle<H>) -> Value {
+    let focused = state.focus_manager.window(&state.windows).map(|w| w.handle);
+    state
+        .windows
+        .iter()
+        .find(|w| w.handle == handle)
+        .map_or_else(
+            || json!({"id": window_id(handle), "type": "con", "window": window_id(handle)}),
+            |w| window_node(w, focused == Some(handle)),
+        )
+}
+
+fn tree<H: Handle>(state: &State<H>) -> Value {
+    let focused = state.focus_manager.window(&state.windows).map(|w| w.handle);
+    let tags = existing_tags(state);
+    let outputs: Vec<Value> = state
+        .workspaces
+        .iter()
+        .enumerate()
+        .map(|(i, ws)| {
+            let output = state
+                .screens
+                .iter()
+                .find(|s| s.id == Some(ws.id))
+                .map(|s| s.output.clone())
+                .unwrap_or_default();
+            // Tags not shown anywhere go to the first output.
+            let workspaces: Vec<Value> = tags
+                .iter()
+                .filter(|tag| {
+                    ws.tag == Some(**tag)
+                        || (i == 0 && !state.workspaces.iter().any(|ws| ws.tag == Some(**tag)))
+                })
+                .filter_map(|tag| {
+                    let mut node = workspace(state, *tag)?;
+                    let windows = state.windows.iter().filter(|w| w.has_tag(tag));
+                    let (floating, tiled): (Vec<_>, Vec<_>) = windows
+                        .map(|w| (w.floating(), window_node(w, focused == Some(w.handle))))
+                        .partition(|(floating, _)| *floating);
+                    node["type"] = json!("workspace");
+                    node["nodes"] = tiled.into_iter().map(|(_, node)| node).collect();
+                    node["floating_nodes"] = floating.into_iter().map(|(_, node)| node).collect();
+                    Some(node)
+                })
+                .collect();
+            json!({
+                "id": OUTPUT_IDS + ws.id as u64,
+                "type": "output",
+                "name": output,
+                "rect": rect(&ws.xyhw),
+                "nodes": [{
+                    "id": CONTENT_IDS + ws.id as u64,
+                    "type": "con",
+                    "name": "content",
+                    "rect": rect(&ws.xyhw),
+                    "nodes": workspaces,
+                }],
+            })
+        })
+        .collect();
+    json!({"id": 0, "type": "root", "name": "root", "nodes": outputs})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MockHandle;
+
+    #[test]
+    fn i3_commands_should_be_translated() {
+        let mut manager = Manager::new_test(vec!["web".to_string(), "code".to_string()]);
+        manager.screen_create_handler(Default::default());
+        let state = &manager.state;
+
+        assert_eq!(
+            translate::<MockHandle>("workspace number 2", state),
+            Ok(vec![Command::GoToTag {
+                tag: 2,
+                swap: false
+            }])
+        );
+        assert_eq!(
+            translate::<MockHandle>("[con_id=7] move container to workspace web", state),
+            Ok(vec![Command::SendWindowToTag {
+                window: Some(WindowHandle(7)),
+                tag: 1
+            }])
+        );
+        assert_eq!(
+            translate::<MockHandle>("[con_id=7] ClearUrgent", state),
+            Ok(vec![Command::ClearUrgent {
+                window: Some(WindowHandle(7))
+            }])
+        );
+        assert!(translate::<MockHandle>("[con_id=7] kill", state).is_err());
+        assert_eq!(
+            translate::<MockHandle>("focus left", state),
+            Ok(vec![Command::FocusWindowAt(Direction::West)])
+        );
+        assert_eq!(
+            translate::<MockHandle>("exec --no-startup-id rofi -show run", state),
+            Ok(vec![Command::Execute("rofi -show run".to_string())])
+        );
+        assert_eq!(
+            translate::<MockHandle>("NextLayout", state),
+            Ok(vec![Command::NextLayout])
+        );
+        assert!(translate::<MockHandle>("split h", state).is_err());
+        assert!(translate::<MockHandle>("workspace nope", state).is_err());
+    }
+
+    #[test]
+    fn exec_should_take_the_rest_of_the_commands() {
+        assert_eq!(
+            split_commands("workspace 2; exec sh -c 'a; b', c"),
+            vec!["workspace 2", "exec sh -c 'a; b', c"]
+        );
+        assert_eq!(
+            split_commands("[con_id=1] kill, focus left"),
+            vec!["[con_id=1] kill", "focus left"]
+        );
+    }
+
+    #[test]
+    fn commands_for_a_window_should_leave_the_focus_alone() {
+        let mut manager = Manager::new_test(vec!["web".to_string(), "code".to_string()]);
+        manager.screen_create_handler(Default::default());
+        for i in 1..=2 {
+            manager.window_created_handler(Window::new(WindowHandle(i), None, None), -1, -1);
+        }
+        manager.state.focus_window(&WindowHandle::<MockHandle>(2));
+
+        manager.run_i3_commands("[con_id=1] move container to workspace code");
+        for window in &manager.state.windows {
+            assert_eq!(window.has_tag(&2), window.handle == WindowHandle(1));
+        }
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.map(|w| w.handle), Some(WindowHandle(2)));
+    }
+
+    #[test]
+    fn tags_should_be_listed_as_workspaces() {
+        let mut manager = Manager::new_test(vec!["web".to_string(), "code".to_string()]);
+        manager.screen_create_handler(Default::default());
+        manager.window_created_handler(Window::new(WindowHandle(1), None, None), -1, -1);
+
+        let workspaces = workspaces(&manager.state);
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0]["name"], "web");
+        assert_eq!(workspaces[0]["focused"], true);
+
+        let tree = tree(&manager.state);
+        let web = &tree["nodes"][0]["nodes"][0]["nodes"][0];
+        assert_eq!(web["type"], "workspace");
+        assert_eq!(web["nodes"][0]["window"], 1);
+    }
+
+    #[test]
+    fn tree_nodes_should_have_unique_ids() {
+        fn ids(node: &Value, found: &mut Vec<Value>) {
+            found.push(node["id"].clone());
+            for child in node["nodes"].as_array().into_iter().flatten() {
+                ids(child, found);
+            }
+            for child in node["floating_nodes"].as_array().into_iter().flatten() {
+                ids(child, found);
+            }
+        }
+
+        let mut manager = Manager::new_test(vec!["web".to_string(), "code".to_string()]);
+        manager.screen_create_handler(Default::default());
+        for i in 1..=2 {
+            manager.window_created_handler(Window::new(WindowHandle(i), None, None), -1, -1);
+        }
+
+        let mut found = vec![];
+        ids(&tree(&manager.state), &mut found);
+        assert!(found.iter().all(Value::is_u64));
+        let count = found.len();
+        found.sort_by_key(Value::as_u64);
+        found.dedup();
+        assert_eq!(found.len(), count);
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Config, Manager};
+use crate::child_process::exec_shell;
+use crate::display_action::DisplayAction;
+use crate::display_servers::DisplayServer;
+use crate::models::Handle;
+
+impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
+    /// Run the configured command, and dim or undim the windows, when the user became `idle` or
+    /// active again.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn idle_handler(&mut self, idle: bool) -> bool {
+        let config = self.config.idle();
+        let command = if idle {
+            &config.command
+        } else {
+            &config.active_command
+        };
+        if let Some(command) = command {
+            exec_shell(command, &mut self.children);
+        }
+
+        let Some(dim_opacity) = config.dim_opacity() else {
+            return false;
+        };
+        let opacity = idle.then_some(dim_opacity);
+        for window in self.state.windows.iter().filter(|w| w.is_managed()) {
+            let act = DisplayAction::SetWindowOpacity(window.handle, opacity);
+            self.state.actions.push_back(act);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Idle;
+    use crate::models::{MockHandle, Screen, WindowHandle};
+    use crate::{DisplayAction, Manager, Window};
+
+    #[test]
+    fn idle_users_should_get_their_windows_dimmed() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.config.idle = Idle {
+            dim_opacity: Some(0.5),
+            ..Idle::default()
+        };
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        let opacities = |manager: &mut Manager<_, _, _>| -> Vec<_> {
+            manager
+                .state
+                .actions
+                .drain(..)
+                .filter_map(|act| match act {
+                    DisplayAction::SetWindowOpacity(handle, opacity) => Some((handle, opacity)),
+                    _ => None,
+                })
+                .collect()
+        };
+        opacities(&mut manager);
+
+        assert!(manager.idle_handler(true));
+        assert_eq!(opacities(&mut manager), vec![(WindowHandle(1), Some(0.5))]);
+        assert!(manager.idle_handler(false));
+        assert_eq!(opacities(&mut manager), vec![(WindowHandle(1), None)]);
+
+        manager.config.idle = Idle::default();
+        assert!(!manager.idle_handler(true));
+        assert!(opacities(&mut manager).is_empty());
+    }
+
+    #[test]
+    fn windows_created_while_idle_should_be_dimmed() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.config.idle = Idle {
+            dim_opacity: Some(0.5),
+            ..Idle::default()
+        };
+        manager.screen_create_handler(Screen::default());
+        manager.state.user_idle = true;
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        let opacities: Vec<_> = manager
+            .state
+            .actions
+            .iter()
+            .filter_map(|act| match act {
+                DisplayAction::SetWindowOpacity(handle, opacity) => Some((*handle, *opacity)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(opacities, vec![(WindowHandle(1), Some(0.5))]);
+    }
+}
@@ -1,17 +1,54 @@
+use super::window_resize_handler::corner_at;
+use crate::config::{Config, Corner, MouseCommand};
 use crate::display_action::DisplayAction;
+use crate::display_servers::DisplayServer;
 use crate::models::Handle;
+use crate::models::Manager;
 use crate::models::Mode;
 use crate::models::WindowHandle;
 use crate::state::State;
 use crate::utils;
 use crate::utils::modmask_lookup::Button;
 use crate::utils::modmask_lookup::ModMask;
+use crate::Command;
 
-impl<H: Handle> State<H> {
+impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     /// `mouse_combo_handler` is called when the display server sends
     /// `DisplayEvent::MouseCombo(modmask, button, handle, x, y)`
     ///
     /// Returns `true` if changes need to be rendered.
+    pub fn mouse_combo_handler(
+        &mut self,
+        modmask: &ModMask,
+        button: Button,
+        handle: WindowHandle<H>,
+        x: i32,
+        y: i32,
+    ) -> bool {
+        let is_window = self.state.windows.iter().any(|w| w.handle == handle);
+        let command = match self.state.mousebind(modmask, &button) {
+            Some(MouseCommand::Close) if is_window => {
+                self.state.focus_window(&handle);
+                Command::CloseWindow
+            }
+            Some(MouseCommand::SendToTag(tag)) if is_window => Command::SendWindowToTag {
+                window: Some(handle),
+                tag,
+            },
+            _ => {
+                return self
+                    .state
+                    .mouse_combo_handler(modmask, button, handle, x, y)
+            }
+        };
+        self.command_handler(&command)
+    }
+}
+
+impl<H: Handle> State<H> {
+    /// Move, resize or focus a window clicked on, see [`Manager::mouse_combo_handler`].
+    ///
+    /// Returns `true` if changes need to be rendered.
     pub fn mouse_combo_handler(
         &mut self,
         modmask: &ModMask,
@@ -22,13 +59,19 @@ impl<H: Handle> State<H> {
     ) -> bool {
         if let Some(window) = self.windows.iter().find(|w| w.handle == handle) {
             if !self.disable_tile_drag || window.floating() {
-                let modifier = utils::modmask_lookup::into_modmask(&self.mousekey);
                 let bottom_right = (window.x() + window.width(), window.y() + window.height());
+                // Resize from the corner clicked, unless the cursor is moved to the bottom right.
+                let corner = if self.reposition_cursor_on_resize {
+                    Corner::BottomRight
+                } else {
+                    corner_at(window, x, y)
+                };
                 // Build the display to say whether we are ready to move/resize.
-                let act = self.build_action(modmask, button, handle, modifier);
+                let act = self.build_action(modmask, button, handle);
                 if let Some(act) = act {
-                    if self.reposition_cursor_on_resize {
-                        if let DisplayAction::ReadyToResizeWindow(_) = act {
+                    if let DisplayAction::ReadyToResizeWindow(_) = act {
+                        self.resize_corner = corner;
+                        if self.reposition_cursor_on_resize {
                             let move_act = DisplayAction::MoveMouseOverPoint(bottom_right);
                             self.actions.push_back(move_act);
                         }
@@ -47,17 +90,26 @@ impl<H: Handle> State<H> {
         true
     }
 
+    /// The command of the mousebind for a button pressed with modifiers.
+    fn mousebind(&self, modmask: &ModMask, button: &Button) -> Option<MouseCommand> {
+        self.mousebinds
+            .iter()
+            .find(|bind| {
+                &bind.button == button
+                    && &utils::modmask_lookup::into_modmask(&bind.modifier) == modmask
+            })
+            .map(|bind| bind.command)
+    }
+
     // private helper function
     fn build_action(
         &mut self,
         mod_mask: &ModMask,
         button: Button,
         window: WindowHandle<H>,
-        modifier: ModMask,
     ) -> Option<DisplayAction<H>> {
-        let is_mouse_key = *mod_mask == modifier || *mod_mask == (modifier | ModMask::Shift);
-        match button {
-            Button::Main if is_mouse_key => {
+        match self.mousebind(mod_mask, &button) {
+            Some(MouseCommand::Move) => {
                 _ = self
                     .windows
                     .iter()
@@ -65,7 +117,7 @@ impl<H: Handle> State<H> {
                 self.mode = Mode::ReadyToMove(window);
                 Some(DisplayAction::ReadyToMoveWindow(window))
             }
-            Button::Secondary if is_mouse_key => {
+            Some(MouseCommand::Resize) => {
                 _ = self
                     .windows
                     .iter()
@@ -73,7 +125,9 @@ impl<H: Handle> State<H> {
                 self.mode = Mode::ReadyToResize(window);
                 Some(DisplayAction::ReadyToResizeWindow(window))
             }
-            Button::Main | Button::Secondary if self.focus_manager.behaviour.is_clickto() => {
+            _ if (button == Button::Main || button == Button::Secondary)
+                && self.focus_manager.behaviour.is_clickto() =>
+            {
                 self.focus_window(&window);
                 Some(DisplayAction::ReplayClick(window, button))
             }
@@ -81,3 +135,60 @@ impl<H: Handle> State<H> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{tests::TestConfig, MouseCommand, Mousebind};
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::{MockHandle, Screen, WindowHandle};
+    use crate::utils::modmask_lookup::{Button, ModMask};
+    use crate::{DisplayAction, Manager, Window};
+
+    #[test]
+    fn mousebinds_should_run_their_command_on_the_clicked_window() {
+        let mut manager: Manager<MockHandle, TestConfig, MockDisplayServer<MockHandle>> =
+            Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            let window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        manager.state.mousebinds.extend([
+            Mousebind {
+                modifier: vec!["Mod4".to_owned(), "Control".to_owned()],
+                button: Button::Middle,
+                command: MouseCommand::SendToTag(2),
+            },
+            Mousebind {
+                modifier: vec![],
+                button: Button::Middle,
+                command: MouseCommand::Close,
+            },
+        ]);
+        manager.state.actions.clear();
+        let (first, second) = (WindowHandle::<MockHandle>(1), WindowHandle::<MockHandle>(2));
+
+        let resize = ModMask::Super | ModMask::Shift;
+        manager.mouse_combo_handler(&resize, Button::Secondary, first, 0, 0);
+        assert!(matches!(
+            manager.state.actions.back(),
+            Some(DisplayAction::ReadyToResizeWindow(h)) if *h == first
+        ));
+
+        let send = ModMask::Super | ModMask::Control;
+        manager.mouse_combo_handler(&send, Button::Middle, second, 0, 0);
+        let tag_of = |manager: &Manager<_, _, _>, handle| {
+            let window = manager.state.windows.iter().find(|w| w.handle == handle);
+            window.and_then(|w| w.tag)
+        };
+        assert_eq!(tag_of(&manager, second), Some(2));
+
+        manager.state.actions.clear();
+        manager.mouse_combo_handler(&ModMask::Zero, Button::Middle, first, 0, 0);
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .any(|act| matches!(act, DisplayAction::KillWindow(h) if *h == first)));
+    }
+}
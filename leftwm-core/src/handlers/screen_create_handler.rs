@@ -2,24 +2,34 @@ use super::{Manager, Screen, Workspace};
 use crate::config::Config;
 use crate::display_servers::DisplayServer;
 use crate::models::Handle;
+use crate::DisplayAction;
 
 impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     /// `screen_create_handler` is called when the display server sends a
-    /// `DisplayEvent::ScreenCreate(screen)` event. This happens at initialization.
+    /// `DisplayEvent::ScreenCreate(screen)` event. This happens at initialization, and when a
+    /// monitor is plugged in.
     ///
     /// Returns `true` if changes need to be rendered.
-    pub fn screen_create_handler(&mut self, screen: Screen<H>) -> bool {
+    pub fn screen_create_handler(&mut self, mut screen: Screen<H>) -> bool {
         tracing::trace!("Screen create: {:?}", screen);
 
-        let tag_index = self.state.workspaces.len();
         let tag_len = self.state.tags.len_normal();
 
-        // Only used in tests, where there are multiple screens being created by `Screen::default()`
-        // The screen passed to this function should normally already have it's id given in the config serialization.
+        // Screens of the configured workspaces come with their id, others are numbered after the
+        // existing workspaces.
         let workspace_id = match screen.id {
-            None => self.state.workspaces.last().map_or(0, |ws| ws.id) + 1,
+            None => {
+                self.state
+                    .workspaces
+                    .iter()
+                    .map(|ws| ws.id)
+                    .max()
+                    .unwrap_or(0)
+                    + 1
+            }
             Some(set_id) => set_id,
         };
+        screen.id = Some(workspace_id);
 
         let mut new_workspace = Workspace::new(screen.bbox, workspace_id);
         if self.state.workspaces.len() >= tag_len {
@@ -27,12 +37,18 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
         }
         new_workspace.load_config(&self.config);
 
-        // Make sure there are enough tags for this new screen.
-        let next_id = if tag_len > tag_index {
-            tag_index + 1
+        // Show the first tag not shown yet, making sure there are enough tags for this new screen.
+        let unshown =
+            (1..=tag_len).find(|id| !self.state.workspaces.iter().any(|ws| ws.has_tag(id)));
+        let next_id = if let Some(id) = unshown {
+            id
         } else {
             // Add a new tag for the workspace.
-            self.state.tags.add_new_unlabeled()
+            let id = self.state.tags.add_new_unlabeled();
+            let labels = self.state.tags.normal().iter().map(|t| t.label.clone());
+            let act = DisplayAction::SetDesktopNames(labels.collect());
+            self.state.actions.push_back(act);
+            id
         };
 
         self.state.focus_workspace(&new_workspace); // focus_workspace is called.
@@ -43,6 +59,42 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
         self.state.focus_workspace(&new_workspace); // focus_workspace is called again.
         false
     }
+
+    /// `screen_destroy_handler` is called when the display server sends a
+    /// `DisplayEvent::ScreenDestroy(output)` event, when a monitor is unplugged. The workspace of
+    /// the screen goes away with it, its tag and windows are kept.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn screen_destroy_handler(&mut self, output: &str) -> bool {
+        tracing::trace!("Screen destroy: {:?}", output);
+        let Some(screen_index) = self.state.screens.iter().position(|s| s.output == output) else {
+            return false;
+        };
+        let screen = self.state.screens.remove(screen_index);
+        let Some(index) = self
+            .state
+            .workspaces
+            .iter()
+            .position(|ws| Some(ws.id) == screen.id)
+        else {
+            return true;
+        };
+        self.state.workspaces.remove(index);
+
+        // The focus history holds the index of workspaces.
+        let history = &mut self.state.focus_manager.workspace_history;
+        history.retain(|&i| i != index);
+        history
+            .iter_mut()
+            .filter(|i| **i > index)
+            .for_each(|i| *i -= 1);
+        if history.is_empty() {
+            if let Some(first) = self.state.workspaces.first().cloned() {
+                self.state.focus_workspace(&first);
+            }
+        }
+        true
+    }
 }
 
 #[cfg(test)]
@@ -95,5 +147,52 @@ mod tests {
         assert!(manager.state.workspaces[1].has_tag(&2));
         assert!(manager.state.workspaces[2].has_tag(&3));
         assert!(manager.state.workspaces[3].has_tag(&4));
+
+        // the desktops should be renamed for the new tags
+        let names = manager
+            .state
+            .actions
+            .iter()
+            .rev()
+            .find_map(|act| match act {
+                DisplayAction::SetDesktopNames(names) => Some(names.clone()),
+                _ => None,
+            });
+        assert_eq!(
+            names,
+            Some(
+                vec!["web", "console", "3", "4"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+    }
+    #[test]
+    fn unplugged_screens_should_give_their_tag_to_the_next_screen() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        let bbox = Screen::<crate::models::MockHandle>::default().bbox;
+        for output in ["A", "B", "C"] {
+            manager.screen_create_handler(Screen::new(bbox, output.to_string()));
+        }
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[1].clone());
+
+        assert!(manager.screen_destroy_handler("B"));
+        assert!(!manager.screen_destroy_handler("B"));
+        let ids: Vec<usize> = manager.state.workspaces.iter().map(|ws| ws.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        let focused = manager
+            .state
+            .focus_manager
+            .workspace(&manager.state.workspaces);
+        assert!(focused.is_some());
+
+        manager.screen_create_handler(Screen::new(bbox, "D".to_string()));
+        let workspace = manager.state.workspaces.last().unwrap();
+        assert_eq!(workspace.id, 4);
+        assert!(workspace.has_tag(&2));
     }
 }
@@ -0,0 +1,236 @@
+//! Windows launched from a terminal swallow it, when `swallow` is set: the terminal is moved to
+//! the hidden "SWALLOW" tag, like a minimized window, and the window takes its place in the stack.
+//! The terminal is put back in the place of the window when it closes.
+use super::{Config, Manager};
+use crate::display_action::DisplayAction;
+use crate::display_servers::DisplayServer;
+use crate::models::{Handle, WindowHandle, WindowState};
+use std::env;
+use std::str::FromStr;
+
+/// Label of the hidden tag swallowed terminals are stored on.
+pub const SWALLOWED_TAG: &str = "SWALLOW";
+
+/// A process, as far as finding the terminal a window was launched from goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub name: String,
+    pub parent: u32,
+}
+
+/// How the terminal a window was launched from is found: from `$SHELL` and `/proc`, unless
+/// faked in tests.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessLookup {
+    /// The name of the shell of the user, e.g. `zsh`.
+    pub shell: fn() -> Option<String>,
+    /// The name and parent of a process.
+    pub process: fn(u32) -> Option<Process>,
+}
+
+impl Default for ProcessLookup {
+    fn default() -> Self {
+        Self {
+            shell: shell_from_env,
+            process: process_from_proc,
+        }
+    }
+}
+
+fn shell_from_env() -> Option<String> {
+    // Get $SHELL, e.g. /bin/zsh, and remove /bin/.
+    let shell_path = env::var("SHELL").ok()?;
+    shell_path.rsplit('/').next().map(str::to_owned)
+}
+
+fn process_from_proc(pid: u32) -> Option<Process> {
+    let comm = std::fs::read(format!("/proc/{pid}/comm")).ok()?;
+    let name_bytes = comm.split(|&c| c == b' ').next()?;
+    let name = std::str::from_utf8(name_bytes).ok()?.strip_suffix('\n')?;
+    let stat = std::fs::read(format!("/proc/{pid}/stat")).ok()?;
+    let ppid_bytes = stat.split(|&c| c == b' ').nth(3)?;
+    let ppid_str = std::str::from_utf8(ppid_bytes).ok()?;
+    Some(Process {
+        name: name.to_owned(),
+        parent: u32::from_str(ppid_str).ok()?,
+    })
+}
+
+impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
+    /// Hide `terminal` until `handle`, the window launched from it, is destroyed.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn swallow_handler(&mut self, handle: WindowHandle<H>, terminal: WindowHandle<H>) -> bool {
+        let state = &mut self.state;
+        let Some(swallowed_tag) = state.tags.get_hidden_by_label(SWALLOWED_TAG).map(|t| t.id)
+        else {
+            return false;
+        };
+        let Some(current) = state.windows.iter().position(|w| w.handle == handle) else {
+            return false;
+        };
+        let swallowable = |handle: &WindowHandle<H>| {
+            state.windows.iter().any(|w| {
+                &w.handle == handle
+                    && w.is_managed()
+                    && w.tag.is_some()
+                    && w.tag != Some(swallowed_tag)
+            })
+        };
+        if handle == terminal || !swallowable(&terminal) {
+            return false;
+        }
+
+        // The window takes the place of the terminal in the stack.
+        let window = state.windows.remove(current);
+        let index = state
+            .windows
+            .iter()
+            .position(|w| w.handle == terminal)
+            .unwrap_or_default();
+        state.windows.insert(index, window);
+        if let Some(window) = state.windows.iter_mut().find(|w| w.handle == terminal) {
+            window.untag();
+            window.tag(&swallowed_tag);
+        }
+        let act = DisplayAction::SetWindowTag(terminal, Some(swallowed_tag));
+        state.actions.push_back(act);
+        let act = DisplayAction::SetState(terminal, true, WindowState::Hidden);
+        state.actions.push_back(act);
+        state.swallowed.push((handle, terminal));
+
+        let focused = state.focus_manager.window(&state.windows);
+        if focused.is_some_and(|w| w.handle == terminal) {
+            state.focus_window(&handle);
+        }
+        state.handle_single_border();
+        true
+    }
+
+    /// Put the terminal swallowed by `handle` back in its place, as `handle` is going away.
+    ///
+    /// Returns the terminal, if `handle` swallowed one.
+    pub(super) fn unswallow(&mut self, handle: &WindowHandle<H>) -> Option<WindowHandle<H>> {
+        let state = &mut self.state;
+        // A terminal may be killed while it is swallowed.
+        state.swallowed.retain(|(_, terminal)| terminal != handle);
+        let index = state.swallowed.iter().position(|(h, _)| h == handle)?;
+        let (_, terminal) = state.swallowed.remove(index);
+
+        let position =
+            |handle: &WindowHandle<H>| state.windows.iter().position(|w| &w.handle == handle);
+        let (Some(current), Some(_)) = (position(&terminal), position(handle)) else {
+            return None;
+        };
+        let mut window = state.windows.remove(current);
+        let (index, tag) = state
+            .windows
+            .iter()
+            .enumerate()
+            .find(|(_, w)| &w.handle == handle)
+            .map(|(i, w)| (i, w.tag))?;
+        window.untag();
+        if let Some(tag) = &tag {
+            window.tag(tag);
+        }
+        state.windows.insert(index, window);
+        let act = DisplayAction::SetWindowTag(terminal, tag);
+        state.actions.push_back(act);
+        let act = DisplayAction::SetState(terminal, false, WindowState::Hidden);
+        state.actions.push_back(act);
+        Some(terminal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Process, ProcessLookup, SWALLOWED_TAG};
+    use crate::models::{MockHandle, TestManager, WindowHandle};
+    use crate::{Manager, Window};
+
+    fn handles(manager: &TestManager) -> Vec<i32> {
+        manager.state.windows.iter().map(|w| w.handle.0).collect()
+    }
+
+    #[test]
+    fn swallowed_terminals_should_come_back_in_the_place_of_the_window() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        let order = handles(&manager);
+        let swallowed_tag = manager
+            .state
+            .tags
+            .get_hidden_by_label(SWALLOWED_TAG)
+            .unwrap()
+            .id;
+        let terminal = WindowHandle::<MockHandle>(order[1]);
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(4), None, None),
+            -1,
+            -1,
+        );
+        assert!(manager.swallow_handler(WindowHandle::<MockHandle>(4), terminal));
+
+        let mut swallowing = order.clone();
+        swallowing.insert(1, 4);
+        assert_eq!(handles(&manager), swallowing);
+        let hidden = manager.state.windows.iter().find(|w| w.handle == terminal);
+        assert_eq!(hidden.unwrap().tag, Some(swallowed_tag));
+        assert!(!manager.swallow_handler(WindowHandle::<MockHandle>(2), terminal));
+
+        manager.window_destroyed_handler(&WindowHandle::<MockHandle>(4));
+        assert_eq!(handles(&manager), order);
+        let restored = manager.state.windows.iter().find(|w| w.handle == terminal);
+        assert_eq!(restored.unwrap().tag, Some(1));
+        assert!(manager.state.swallowed.is_empty());
+    }
+
+    #[test]
+    fn closed_swallowed_terminals_should_be_forgotten() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        let terminal = WindowHandle::<MockHandle>(1);
+        manager.swallow_handler(WindowHandle::<MockHandle>(2), terminal);
+        manager.window_destroyed_handler(&terminal);
+        assert!(manager.state.swallowed.is_empty());
+        manager.window_destroyed_handler(&WindowHandle::<MockHandle>(2));
+        assert_eq!(handles(&manager), vec![3]);
+    }
+
+    #[test]
+    fn windows_launched_from_a_terminal_should_swallow_it_when_enabled() {
+        // A terminal (10) runs a shell (20), which runs the window (30).
+        let process = |pid| match pid {
+            30 => Some(Process {
+                name: "app".to_owned(),
+                parent: 20,
+            }),
+            20 => Some(Process {
+                name: "sh".to_owned(),
+                parent: 10,
+            }),
+            _ => None,
+        };
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string(), "2".to_string()], 3);
+        manager.process_lookup = ProcessLookup {
+            shell: || Some("sh".to_owned()),
+            process,
+        };
+        manager.state.windows[0].pid = Some(10);
+        let pid = Some(30);
+        let terminal = WindowHandle::<MockHandle>(1);
+
+        let window = Window::new(WindowHandle::<MockHandle>(4), None, pid);
+        manager.window_created_handler(window, -1, -1);
+        assert!(manager.state.swallowed.is_empty());
+
+        manager.state.swallow = true;
+        let window = Window::new(WindowHandle::<MockHandle>(5), None, pid);
+        manager.window_created_handler(window, -1, -1);
+        assert_eq!(
+            manager.state.swallowed,
+            vec![(WindowHandle::<MockHandle>(5), terminal)]
+        );
+        let hidden = manager.state.windows.iter().find(|w| w.handle == terminal);
+        let swallowed_tag = manager.state.tags.get_hidden_by_label(SWALLOWED_TAG);
+        assert_eq!(hidden.unwrap().tag, swallowed_tag.map(|t| t.id));
+    }
+}
@@ -0,0 +1,149 @@
+use super::{Command, Config, Manager, WindowHandle};
+use crate::config::UrgencyBehavior;
+use crate::display_action::DisplayAction;
+use crate::display_servers::DisplayServer;
+use crate::models::Handle;
+use crate::state::State;
+use std::time::Duration;
+
+impl<H: Handle> State<H> {
+    /// Clear the urgency of a window, also its urgency hint. Returns whether it was urgent.
+    pub fn clear_urgency(&mut self, handle: &WindowHandle<H>) -> bool {
+        let act = DisplayAction::SetWindowUrgency(*handle, false);
+        self.actions.push_back(act);
+        let was_urgent = self
+            .windows
+            .iter_mut()
+            .find(|w| &w.handle == handle)
+            .is_some_and(|w| std::mem::replace(&mut w.urgent, false));
+        if was_urgent && self.urgent_border_width.is_some() {
+            self.handle_single_border();
+        }
+        was_urgent
+    }
+}
+
+impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
+    /// Clear the urgency of a window which became urgent later, if configured to.
+    pub(crate) fn urgency_handler(&mut self, handle: WindowHandle<H>) {
+        self.state.urgent_order.retain(|h| h != &handle);
+        self.state.urgent_order.push(handle);
+        if self.state.urgent_border_width.is_some() {
+            self.state.handle_single_border();
+        }
+        if let UrgencyBehavior::ClearAfter(secs) = self.state.urgency_behavior {
+            let command = Command::ClearUrgent {
+                window: Some(handle),
+            };
+            self.scheduler
+                .send_after(Duration::from_secs(secs), command);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::UrgencyBehavior;
+    use crate::models::{MockHandle, Screen, WindowChange, WindowHandle};
+    use crate::{Command, DisplayAction, Manager, Window};
+    use std::time::Duration;
+
+    fn urgent_after_focus(behavior: UrgencyBehavior) -> bool {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.state.urgency_behavior = behavior;
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            let window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        manager.state.focus_window(&WindowHandle(2));
+        let mut change = WindowChange::new(WindowHandle(1));
+        change.urgent = Some(true);
+        manager.window_changed_handler(change);
+        manager.state.actions.clear();
+
+        manager.state.focus_window(&WindowHandle(1));
+        let cleared = manager
+            .state
+            .actions
+            .iter()
+            .any(|act| matches!(act, DisplayAction::SetWindowUrgency(WindowHandle(1), false)));
+        let window = manager.state.windows.iter().find(|w| w.handle.0 == 1);
+        let urgent = window.unwrap().urgent;
+        assert_eq!(cleared, !urgent);
+        urgent
+    }
+
+    #[test]
+    fn urgency_should_only_be_cleared_on_focus_when_configured() {
+        assert!(!urgent_after_focus(UrgencyBehavior::ClearOnFocus));
+        assert!(urgent_after_focus(UrgencyBehavior::Manual));
+    }
+
+    #[test]
+    fn urgency_should_be_cleared_by_command() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.state.urgency_behavior = UrgencyBehavior::Manual;
+        manager.screen_create_handler(Screen::default());
+        let mut window = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        window.urgent = true;
+        manager.window_created_handler(window, -1, -1);
+
+        assert!(manager.command_handler(&Command::ClearUrgent { window: None }));
+        assert!(!manager.state.windows[0].urgent);
+        assert!(!manager.command_handler(&Command::ClearUrgent { window: None }));
+    }
+
+    #[test]
+    fn focus_next_urgent_should_go_to_the_latest_urgent_window() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.state.urgency_behavior = UrgencyBehavior::ClearOnFocus;
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            let window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        for i in 2..=3 {
+            manager.command_handler(&Command::SendWindowToTag {
+                window: Some(WindowHandle(i)),
+                tag: 2,
+            });
+        }
+        for i in [3, 2] {
+            let mut change = WindowChange::new(WindowHandle(i));
+            change.urgent = Some(true);
+            manager.window_changed_handler(change);
+        }
+        manager.state.focus_window(&WindowHandle(1));
+
+        for expected in [2, 3] {
+            assert!(manager.command_handler(&Command::FocusNextUrgent));
+            assert_eq!(manager.state.focus_manager.tag(0), Some(2));
+            let focused = manager.state.focus_manager.window(&manager.state.windows);
+            assert_eq!(focused.map(|w| w.handle.0), Some(expected));
+        }
+        assert!(!manager.command_handler(&Command::FocusNextUrgent));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn urgency_should_be_cleared_after_the_timeout() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.state.urgency_behavior = UrgencyBehavior::ClearAfter(5);
+        manager.screen_create_handler(Screen::default());
+        let window = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        manager.window_created_handler(window, -1, -1);
+        let mut change = WindowChange::new(WindowHandle(1));
+        change.urgent = Some(true);
+        manager.window_changed_handler(change);
+
+        let start = tokio::time::Instant::now();
+        let command = manager.scheduler.next_command().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+        assert_eq!(
+            command,
+            Command::ClearUrgent {
+                window: Some(WindowHandle(1))
+            }
+        );
+    }
+}
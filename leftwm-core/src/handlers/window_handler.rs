@@ -1,14 +1,13 @@
+use super::swallow_handler::ProcessLookup;
 use super::{Manager, Window, WindowChange, WindowType, Workspace};
 use crate::child_process::exec_shell;
-use crate::config::{Config, InsertBehavior};
+use crate::config::{Config, FloatingPlacementBehavior, InsertBehavior, TagOverflowBehavior};
 use crate::display_action::DisplayAction;
 use crate::display_servers::DisplayServer;
 use crate::layouts::{self, MAIN_AND_VERT_STACK};
-use crate::models::{Handle, WindowHandle, WindowState, Xyhw};
+use crate::models::{Handle, WindowHandle, WindowState, Xyhw, XyhwBuilder};
 use crate::state::State;
 use crate::utils::helpers;
-use std::env;
-use std::str::FromStr;
 
 impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     /// `window_created_handler` is called when the display server sends
@@ -21,9 +20,14 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             return false;
         }
 
-        // Setup any predefined hooks.
-        self.config
-            .setup_predefined_window(&mut self.state, &mut window);
+        // Setup any predefined hooks. Windows without one are floated when they are small.
+        if !self
+            .config
+            .setup_predefined_window(&mut self.state, &mut window)
+            && is_below_auto_float_size(&self.state, &window)
+        {
+            window.set_floating(true);
+        }
 
         // TODO: this seems very janky.
         let mut is_first = false;
@@ -34,13 +38,26 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             &mut self.state,
             &mut window,
             (x, y),
+            &self.process_lookup,
             &mut layout,
             &mut is_first,
             &mut on_same_tag,
         );
         self.config.load_window(&mut window);
+        if apply_tag_capacity(&mut self.state, &mut window, &mut layout) {
+            on_same_tag = false;
+        }
         insert_window(&mut self.state, &mut window, &layout);
 
+        // A window launched from a terminal takes its place, see `swallow_handler`.
+        if self.state.swallow && window.r#type == WindowType::Normal && window.transient.is_none() {
+            if let Some(terminal) =
+                find_terminal(&self.state, window.pid, &self.process_lookup).map(|t| t.handle)
+            {
+                self.swallow_handler(window.handle, terminal);
+            }
+        }
+
         let follow_mouse = self.state.focus_manager.focus_new_windows
             && self.state.focus_manager.behaviour.is_sloppy()
             && self.state.focus_manager.sloppy_mouse_follows_focus
@@ -56,11 +73,19 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             self.state.actions.push_back(act);
         }
 
+        // Windows created while the user is idle are dimmed like the others, see `idle_handler`.
+        if self.state.user_idle && window.is_managed() {
+            if let Some(dim_opacity) = self.config.idle().dim_opacity() {
+                let act = DisplayAction::SetWindowOpacity(window.handle, Some(dim_opacity));
+                self.state.actions.push_back(act);
+            }
+        }
+
         // Tell the WM to reevaluate the stacking order, so the new window is put in the correct layer
         self.state.sort_windows();
 
         // if `single_window_border` is `false`, remove borders if there is a single visible window
-        self.state.handle_single_border(self.config.border_width());
+        self.state.handle_single_border();
 
         // `is_first` and `on_same_tag` are set by `setup_window`
         // TODO: remove focus_new_windows variable from focus_manager,
@@ -96,13 +121,27 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
                 Some(window) => (window.transient, window.floating(), window.visible()),
                 None => return false,
             };
+        let terminal = self.unswallow(handle);
         self.state
             .focus_manager
             .tags_last_window
             .retain(|_, h| h != handle);
+        self.state
+            .focus_manager
+            .tags_window_history
+            .values_mut()
+            .for_each(|history| history.retain(|h| h != handle));
+        self.state
+            .tag_stack_positions
+            .values_mut()
+            .for_each(|positions| positions.retain(|(h, _)| h != handle));
+        self.state.minimized.retain(|(h, _)| h != handle);
+        self.state.stashed.retain(|(h, _)| h != handle);
+        self.state.urgent_order.retain(|h| h != handle);
+        self.state.remember_floating(handle);
         self.state.windows.retain(|w| &w.handle != handle);
 
-        self.state.handle_single_border(self.config.border_width());
+        self.state.handle_single_border();
 
         // Make sure the workspaces do not draw on the docks.
         update_workspace_avoid_list(&mut self.state);
@@ -110,7 +149,9 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
         let focused = self.state.focus_manager.window_history.front();
         // Make sure focus is recalculated if we closed the currently focused window
         if focused == Some(&Some(*handle)) {
-            if self.state.focus_manager.behaviour.is_sloppy()
+            if let Some(terminal) = terminal {
+                self.state.focus_window(&terminal);
+            } else if self.state.focus_manager.behaviour.is_sloppy()
                 && self.state.focus_manager.sloppy_mouse_follows_focus
             {
                 let act = DisplayAction::FocusWindowUnderCursor;
@@ -124,12 +165,12 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             } else {
                 let act = DisplayAction::Unfocus(Some(*handle), floating);
                 self.state.actions.push_back(act);
-                self.state.focus_manager.window_history.push_front(None);
+                self.state.focus_manager.push_window_history(None);
             }
         }
 
         // Only update windows if this window is visible.
-        visible
+        visible || terminal.is_some()
     }
 
     /// `window_changed_handler` is called when the display server sends
@@ -139,8 +180,10 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     pub fn window_changed_handler(&mut self, change: WindowChange<H>) -> bool {
         let mut changed = false;
         let mut fullscreen_changed = false;
-        let mut above_changed = false;
+        let mut layer_changed = false;
         let strut_changed = change.strut.is_some();
+        let mut became_urgent = false;
+        let handle = change.handle;
         let windows = self.state.windows.clone();
         if let Some(window) = self
             .state
@@ -149,10 +192,11 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             .find(|w| w.handle == change.handle)
         {
             if let Some(states) = &change.states {
-                fullscreen_changed =
-                    states.contains(&WindowState::Fullscreen) != window.is_fullscreen();
-                above_changed = states.contains(&WindowState::Above)
-                    != window.states.contains(&WindowState::Above);
+                fullscreen_changed = states.contains(&WindowState::Fullscreen)
+                    != window.states.contains(&WindowState::Fullscreen);
+                layer_changed = [WindowState::Above, WindowState::Below]
+                    .iter()
+                    .any(|s| states.contains(s) != window.states.contains(s));
             }
             let container = match find_transient_parent(&windows, window.transient) {
                 Some(parent) => Some(parent.exact_xyhw()),
@@ -165,6 +209,7 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
                 _ => None,
             };
 
+            became_urgent = change.urgent == Some(true) && !window.urgent;
             changed = change.update(window, container);
             if window.r#type == WindowType::Dock {
                 update_workspace_avoid_list(&mut self.state);
@@ -172,6 +217,9 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
                 // infinite loop. Just be patient a rerender will occur.
             }
         }
+        if became_urgent {
+            self.urgency_handler(handle);
+        }
         if fullscreen_changed {
             // Update `dock` windows once, so they can recieve mouse click events again.
             // This is necessary, since we exclude them from the general update loop above.
@@ -185,7 +233,7 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
             }
         }
 
-        if fullscreen_changed || above_changed {
+        if fullscreen_changed || layer_changed {
             // Reorder windows.
             self.state.sort_windows();
         }
@@ -218,32 +266,21 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
 
 // Private helper functions.
 
-fn find_terminal<H: Handle>(state: &State<H>, pid: Option<u32>) -> Option<&Window<H>> {
-    // Get $SHELL, e.g. /bin/zsh
-    let shell_path = env::var("SHELL").ok()?;
-    // Remove /bin/
-    let shell = shell_path.split('/').last()?;
+fn find_terminal<'a, H: Handle>(
+    state: &'a State<H>,
+    pid: Option<u32>,
+    lookup: &ProcessLookup,
+) -> Option<&'a Window<H>> {
+    // The name of the shell, e.g. zsh.
+    let shell = (lookup.shell)()?;
     // Try and find the shell that launched this app, if such a thing exists.
-    let is_terminal = |pid: u32| -> Option<bool> {
-        let parent = std::fs::read(format!("/proc/{pid}/comm")).ok()?;
-        let parent_bytes = parent.split(|&c| c == b' ').next()?;
-        let parent_str = std::str::from_utf8(parent_bytes).ok()?.strip_suffix('\n')?;
-        Some(parent_str == shell)
-    };
-
-    let get_parent = |pid: u32| -> Option<u32> {
-        let stat = std::fs::read(format!("/proc/{pid}/stat")).ok()?;
-        let ppid_bytes = stat.split(|&c| c == b' ').nth(3)?;
-        let ppid_str = std::str::from_utf8(ppid_bytes).ok()?;
-        let ppid_u32 = u32::from_str(ppid_str).ok()?;
-        Some(ppid_u32)
-    };
-
-    let pid = pid?;
-    let shell_id = get_parent(pid)?;
-    if is_terminal(shell_id)? {
-        let terminal = get_parent(shell_id)?;
-        return state.windows.iter().find(|w| w.pid == Some(terminal));
+    let shell_pid = (lookup.process)(pid?)?.parent;
+    let shell_process = (lookup.process)(shell_pid)?;
+    if shell_process.name == shell {
+        return state
+            .windows
+            .iter()
+            .find(|w| w.pid == Some(shell_process.parent));
     }
 
     None
@@ -357,6 +394,21 @@ fn insert_window<H: Handle>(state: &mut State<H>, window: &mut Window<H>, layout
         InsertBehavior::AfterCurrent | InsertBehavior::BeforeCurrent => {
             state.windows.insert(current_index, window.clone());
         }
+        InsertBehavior::AfterSmallest => {
+            // On ties the last window wins, so evenly sized stacks keep growing at the end.
+            let smallest_index = state
+                .windows
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, w)| w.tag == window.tag && w.is_managed() && !w.floating())
+                .min_by_key(|(_, w)| w.width() * w.height())
+                .map(|(i, _)| i);
+            match smallest_index {
+                Some(i) => state.windows.insert(i + 1, window.clone()),
+                None => state.windows.push(window.clone()),
+            }
+        }
     }
 }
 
@@ -367,21 +419,42 @@ fn is_scratchpad<H: Handle>(state: &State<H>, window: &Window<H>) -> bool {
         .any(|(_, id)| id.iter().any(|id| window.pid == Some(*id)))
 }
 
-// Tries to position a window according to the requested sizes.
-// When no size was requested, defaults to `ws.center_halfed()`
-fn set_relative_floating<H: Handle>(window: &mut Window<H>, ws: &Workspace, outer: Xyhw) {
+/// Whether a new window requested a size within `auto_float_max_width` and
+/// `auto_float_max_height`. Windows are not floated when neither is set.
+fn is_below_auto_float_size<H: Handle>(state: &State<H>, window: &Window<H>) -> bool {
+    let (max_w, max_h) = (state.auto_float_max_width, state.auto_float_max_height);
+    let Some(requested) = window.requested else {
+        return false;
+    };
+    window.r#type == WindowType::Normal
+        && (max_w.is_some() || max_h.is_some())
+        && requested.w() > 0
+        && requested.h() > 0
+        && max_w.map_or(true, |max| requested.w() <= max)
+        && max_h.map_or(true, |max| requested.h() <= max)
+}
+
+// Tries to position a window according to its window rule, where it floated the last time, or
+// else centers it over `outer` with the requested size, kept within the workspace. When no size
+// was requested, defaults to half the size of the workspace.
+fn set_relative_floating<H: Handle>(
+    window: &mut Window<H>,
+    ws: &Workspace,
+    outer: Xyhw,
+    remembered: Option<Xyhw>,
+) {
     window.set_floating(true);
     window.normal = ws.xyhw;
-    let xyhw = window.requested.map_or_else(
-        || ws.center_halfed(),
-        |mut requested| {
-            requested.center_relative(outer, window.border);
-            if !ws.xyhw_avoided.contains_xyhw(&requested) {
-                requested.center_relative(ws.xyhw_avoided, window.border);
-            }
-            requested
-        },
-    );
+    if let (None, Some(xyhw)) = (window.placement, remembered) {
+        window.set_floating_exact(xyhw);
+        return;
+    }
+    let mut xyhw = window.requested.unwrap_or_else(|| ws.center_halfed());
+    xyhw.center_relative(outer, window.border);
+    xyhw.clamp_within(ws.xyhw_avoided);
+    if let Some(placement) = window.placement {
+        placement.apply(&mut xyhw, &ws.xyhw_avoided);
+    }
     window.set_floating_exact(xyhw);
 }
 
@@ -389,6 +462,7 @@ fn setup_window<H: Handle>(
     state: &mut State<H>,
     window: &mut Window<H>,
     xy: (i32, i32),
+    lookup: &ProcessLookup,
     layout: &mut String,
     is_first: &mut bool,
     on_same_tag: &mut bool,
@@ -423,10 +497,11 @@ fn setup_window<H: Handle>(
     // Setup basic variables.
     let for_active_workspace = |x: &Window<H>| -> bool { ws.tag == x.tag && x.is_managed() };
     *is_first = !state.windows.iter().any(for_active_workspace);
-    // May have been set by a predefined tag.
+    // May have been set by a predefined tag. Dialogs stay with their parent.
     if window.tag.is_none() {
-        window.tag =
-            find_terminal(state, window.pid).map_or_else(|| ws.tag, |terminal| terminal.tag);
+        window.tag = find_transient_parent(&state.windows, window.transient)
+            .or_else(|| find_terminal(state, window.pid, lookup))
+            .map_or_else(|| ws.tag, |parent| parent.tag);
     }
     *on_same_tag = ws.tag == window.tag;
     *layout = state
@@ -454,28 +529,181 @@ fn setup_window<H: Handle>(
         }
     }
 
+    let remembered = state.remembered_floating(window, ws);
+
     // Setup a child window.
     if let Some(parent) = find_transient_parent(&state.windows, window.transient) {
         // This is currently for vlc, this probably will need to be more general if another
         // case comes up where we don't want to move the window.
         if window.r#type != WindowType::Utility {
-            set_relative_floating(window, ws, parent.exact_xyhw());
+            let parent_ws = state
+                .workspaces
+                .iter()
+                .find(|ws| ws.tag == parent.tag)
+                .unwrap_or(ws);
+            set_relative_floating(window, parent_ws, parent.exact_xyhw(), remembered);
             return;
         }
     }
 
     // Setup window based on type.
-    match window.r#type {
+    let floats = match window.r#type {
         WindowType::Normal => {
             window.apply_margin_multiplier(ws.margin_multiplier);
-            if window.floating() {
-                set_relative_floating(window, ws, ws.xyhw_avoided);
+            window.floating()
+        }
+        WindowType::Dialog | WindowType::Splash => true,
+        _ => false,
+    };
+    if floats {
+        set_relative_floating(window, ws, ws.xyhw_avoided, remembered);
+        if state.floating_placement_behavior == FloatingPlacementBehavior::Smart
+            && window.placement.is_none()
+            && remembered.is_none()
+        {
+            place_in_free_area(&state.windows, window, ws.xyhw_avoided);
+        }
+    }
+}
+
+/// Offset between cascaded floating windows.
+const CASCADE_STEP: i32 = 32;
+
+/// Move a new floating window to the middle of the largest rectangle of `area` it fits in without
+/// covering other floating windows of its tag. When there is no such rectangle, cascade it from
+/// the top left of `area`.
+fn place_in_free_area<H: Handle>(windows: &[Window<H>], window: &mut Window<H>, area: Xyhw) {
+    let others: Vec<Xyhw> = windows
+        .iter()
+        .filter(|w| w.handle != window.handle && w.tag == window.tag)
+        .filter(|w| w.floating() && w.is_managed())
+        .map(Window::exact_xyhw)
+        .collect();
+    let mut xyhw = window.exact_xyhw();
+    let (w, h) = (xyhw.w(), xyhw.h());
+    let (x, y) = largest_free_rect(&others, area, w, h).map_or_else(
+        || {
+            let step = CASCADE_STEP * others.len() as i32;
+            let room = (area.w() - w).min(area.h() - h).max(1);
+            (area.x() + step % room, area.y() + step % room)
+        },
+        |free| (free.x() + (free.w() - w) / 2, free.y() + (free.h() - h) / 2),
+    );
+    xyhw.set_x(x);
+    xyhw.set_y(y);
+    window.set_floating_exact(xyhw);
+}
+
+/// The largest rectangle of `area` at least `w` by `h` covering none of `taken`.
+fn largest_free_rect(taken: &[Xyhw], area: Xyhw, w: i32, h: i32) -> Option<Xyhw> {
+    // The sides of a largest free rectangle lie on the sides of `area` or of a taken rectangle.
+    let edges = |start: fn(&Xyhw) -> i32, len: fn(&Xyhw) -> i32| {
+        let (min, max) = (start(&area), start(&area) + len(&area));
+        let mut edges: Vec<i32> = taken
+            .iter()
+            .flat_map(|t| [start(t), start(t) + len(t)])
+            .map(|edge| edge.clamp(min, max))
+            .chain([min, max])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    };
+    let (xs, ys) = (edges(Xyhw::x, Xyhw::w), edges(Xyhw::y, Xyhw::h));
+
+    let mut best: Option<Xyhw> = None;
+    for (i, &left) in xs.iter().enumerate() {
+        for &right in xs[i + 1..].iter().filter(|&&right| right - left >= w) {
+            for (j, &top) in ys.iter().enumerate() {
+                for &bottom in ys[j + 1..].iter().filter(|&&bottom| bottom - top >= h) {
+                    let covers = |t: &Xyhw| {
+                        t.x() < right
+                            && t.x() + t.w() > left
+                            && t.y() < bottom
+                            && t.y() + t.h() > top
+                    };
+                    let size = i64::from(right - left) * i64::from(bottom - top);
+                    let best_size = best.map_or(0, |b| i64::from(b.w()) * i64::from(b.h()));
+                    if size > best_size && !taken.iter().any(covers) {
+                        best = Some(
+                            XyhwBuilder {
+                                x: left,
+                                y: top,
+                                w: right - left,
+                                h: bottom - top,
+                                ..XyhwBuilder::default()
+                            }
+                            .into(),
+                        );
+                    }
+                }
             }
         }
-        WindowType::Dialog | WindowType::Splash => {
-            set_relative_floating(window, ws, ws.xyhw_avoided);
+    }
+    best
+}
+
+/// Keep the tag of a new tiled window within `max_windows_per_tag` according to the
+/// configured `tag_overflow_behavior`.
+///
+/// Returns true if the window was moved to another tag.
+fn apply_tag_capacity<H: Handle>(
+    state: &mut State<H>,
+    window: &mut Window<H>,
+    layout: &mut String,
+) -> bool {
+    let (Some(capacity), Some(tag)) = (state.max_windows_per_tag, window.tag) else {
+        return false;
+    };
+    if window.r#type != WindowType::Normal
+        || window.floating()
+        || state.tags.get(tag).map_or(true, |t| t.hidden)
+    {
+        return false;
+    }
+    let tiled = state
+        .windows
+        .iter()
+        .filter(|w| w.has_tag(&tag) && w.is_managed() && !w.floating())
+        .count();
+    if tiled < capacity {
+        return false;
+    }
+
+    let ws_id = state
+        .workspaces
+        .iter()
+        .find(|ws| ws.tag == Some(tag))
+        .or_else(|| state.focus_manager.workspace(&state.workspaces))
+        .map_or(1, |ws| ws.id);
+    match state.tag_overflow_behavior {
+        TagOverflowBehavior::SpillToNextTag => {
+            let tags = state.tags.normal();
+            let Some(index) = tags.iter().position(|t| t.id == tag) else {
+                return false;
+            };
+            let Some(empty) = tags
+                .iter()
+                .cycle()
+                .skip(index + 1)
+                .take(tags.len() - 1)
+                .map(|t| t.id)
+                .find(|&id| !state.windows.iter().any(|w| w.has_tag(&id)))
+            else {
+                return false;
+            };
+            window.untag();
+            window.tag(&empty);
+            layout.clone_from(&state.layout_manager.layout(ws_id, empty).name);
+            true
+        }
+        TagOverflowBehavior::Monocle => {
+            state
+                .layout_manager
+                .set_layout(ws_id, tag, layouts::MONOCLE);
+            layout.clone_from(&state.layout_manager.layout(ws_id, tag).name);
+            false
         }
-        _ => {}
     }
 }
 
@@ -508,7 +736,7 @@ fn update_workspace_avoid_list<H: Handle>(state: &mut State<H>) {
 mod tests {
     use super::*;
     use crate::layouts::MONOCLE;
-    use crate::models::{MockHandle, Screen};
+    use crate::models::{BBox, FloatingMemory, FloatingPlacement, MockHandle, Screen, Size};
     use crate::Manager;
 
     #[test]
@@ -627,6 +855,41 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn insert_behavior_after_smallest_add_window_after_the_smallest_window() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.insert_behavior = InsertBehavior::AfterSmallest;
+
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+            manager.update_windows();
+        }
+        // Make the main window the smallest one.
+        manager.state.windows[0].normal.set_w(10);
+
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(4), None, None),
+            -1,
+            -1,
+        );
+
+        let expected = vec![
+            WindowHandle::<MockHandle>(1),
+            WindowHandle::<MockHandle>(4),
+            WindowHandle::<MockHandle>(2),
+            WindowHandle::<MockHandle>(3),
+        ];
+        let actual: Vec<WindowHandle<MockHandle>> =
+            manager.state.windows.iter().map(|w| w.handle).collect();
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn single_window_has_no_border() {
         let mut manager = Manager::new_test_with_border(vec![], 1);
@@ -763,4 +1026,288 @@ mod tests {
         assert_eq!((manager.state.windows[0]).border(), 0);
         assert_eq!((manager.state.windows[1]).border(), 0);
     }
+
+    #[test]
+    fn windows_over_the_tag_capacity_should_spill_to_the_next_empty_tag() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.state.max_windows_per_tag = Some(2);
+        manager.state.tag_overflow_behavior = TagOverflowBehavior::SpillToNextTag;
+        for i in 1..=4 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let tags: Vec<_> = manager.state.windows.iter().map(|w| w.tag).collect();
+        assert_eq!(tags, vec![Some(1), Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn windows_over_the_tag_capacity_should_switch_the_tag_to_monocle() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.state.max_windows_per_tag = Some(2);
+        manager.state.tag_overflow_behavior = TagOverflowBehavior::Monocle;
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::<MockHandle>(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        assert!(manager.state.windows.iter().all(|w| w.tag == Some(1)));
+        assert_eq!(manager.state.layout_manager.layout(1, 1).name, MONOCLE);
+    }
+
+    #[test]
+    fn notifications_should_float_above_without_taking_the_focus() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        let mut notification = Window::new(WindowHandle::<MockHandle>(2), None, None);
+        notification.r#type = WindowType::Notification;
+        manager.window_created_handler(notification, -1, -1);
+
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(
+            focused.map(|w| w.handle),
+            Some(WindowHandle::<MockHandle>(1))
+        );
+        let notification = &manager.state.windows[0];
+        assert_eq!(notification.handle, WindowHandle::<MockHandle>(2));
+        assert!(notification.floating() && !notification.is_managed());
+        let Some(DisplayAction::SetWindowOrder(order)) = manager.state.actions.back() else {
+            panic!("creating a window should restack them");
+        };
+        assert_eq!(order[0], WindowHandle::<MockHandle>(2));
+    }
+
+    #[test]
+    fn window_rule_placement_should_position_floating_windows() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 100,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        let mut window = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        window.r#type = WindowType::Dialog;
+        window.placement = Some(FloatingPlacement {
+            position: Some((Size::Ratio(0.5), Size::Pixel(40))),
+            size: Some((Size::Pixel(300), Size::Ratio(0.25))),
+        });
+        manager.window_created_handler(window, -1, -1);
+
+        let xyhw = manager.state.windows[0].exact_xyhw();
+        assert_eq!(
+            (xyhw.x(), xyhw.y(), xyhw.w(), xyhw.h()),
+            (600, 40, 300, 200)
+        );
+    }
+
+    #[test]
+    fn destroyed_floating_windows_should_float_there_again() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.floating_memory = Some(FloatingMemory::default());
+        manager.screen_create_handler(Screen::default());
+        let dialog = |handle| {
+            let mut window = Window::new(WindowHandle::<MockHandle>(handle), None, None);
+            window.r#type = WindowType::Dialog;
+            window.res_class = Some("Pavucontrol".to_string());
+            window
+        };
+        manager.window_created_handler(dialog(1), -1, -1);
+        let mut moved = manager.state.windows[0].exact_xyhw();
+        moved.set_x(moved.x() + 20);
+        manager.state.windows[0].set_floating_exact(moved);
+        manager.window_destroyed_handler(&WindowHandle(1));
+
+        manager.window_created_handler(dialog(2), -1, -1);
+        assert_eq!(manager.state.windows[0].exact_xyhw().x(), moved.x());
+    }
+
+    #[test]
+    fn largest_free_rect_should_avoid_taken_areas() {
+        let xyhw = |x, y, w, h| -> Xyhw {
+            XyhwBuilder {
+                x,
+                y,
+                h,
+                w,
+                ..XyhwBuilder::default()
+            }
+            .into()
+        };
+        let area = xyhw(0, 0, 1000, 800);
+        let taken = [xyhw(0, 0, 600, 800)];
+        assert_eq!(
+            largest_free_rect(&taken, area, 300, 300),
+            Some(xyhw(600, 0, 400, 800))
+        );
+        assert_eq!(largest_free_rect(&taken, area, 500, 300), None);
+    }
+
+    #[test]
+    fn smart_placement_should_not_stack_floating_windows() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.floating_placement_behavior = FloatingPlacementBehavior::Smart;
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        for i in 1..=2 {
+            let mut window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            window.r#type = WindowType::Dialog;
+            window.requested = Some(
+                XyhwBuilder {
+                    h: 300,
+                    w: 300,
+                    ..XyhwBuilder::default()
+                }
+                .into(),
+            );
+            manager.window_created_handler(window, -1, -1);
+        }
+
+        let first = manager.state.windows[1].exact_xyhw();
+        let second = manager.state.windows[0].exact_xyhw();
+        let apart = |a: Xyhw, b: Xyhw| a.x() + a.w() <= b.x() || a.y() + a.h() <= b.y();
+        assert!(apart(first, second) || apart(second, first));
+    }
+
+    #[test]
+    fn dialogs_should_be_centered_over_their_parent_on_its_tag() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        let mut parent = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        parent.tag = Some(2);
+        parent.set_floating(true);
+        manager.window_created_handler(parent, -1, -1);
+        let parent = &mut manager.state.windows[0];
+        parent.set_floating_exact(
+            XyhwBuilder {
+                x: 700,
+                y: 500,
+                h: 200,
+                w: 200,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+
+        let mut dialog = Window::new(WindowHandle::<MockHandle>(2), None, None);
+        dialog.transient = Some(WindowHandle(1));
+        dialog.r#type = WindowType::Dialog;
+        dialog.requested = Some(
+            XyhwBuilder {
+                h: 100,
+                w: 100,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        manager.window_created_handler(dialog, -1, -1);
+        let dialog = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle(2));
+        let xyhw = dialog.unwrap().exact_xyhw();
+        assert_eq!(dialog.unwrap().tag, Some(2));
+        assert_eq!(
+            (xyhw.x() + xyhw.w() / 2, xyhw.y() + xyhw.h() / 2),
+            (799, 599)
+        );
+
+        // Dialogs larger than the room around their parent stay on the workspace.
+        let mut dialog = Window::new(WindowHandle::<MockHandle>(3), None, None);
+        dialog.transient = Some(WindowHandle(1));
+        dialog.r#type = WindowType::Dialog;
+        dialog.requested = Some(
+            XyhwBuilder {
+                h: 600,
+                w: 600,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        manager.window_created_handler(dialog, -1, -1);
+        let dialog = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle(3));
+        let xyhw = dialog.unwrap().exact_xyhw();
+        assert_eq!((xyhw.x(), xyhw.y()), (400, 200));
+    }
+
+    #[test]
+    fn small_windows_should_float_centered() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.auto_float_max_width = Some(300);
+        manager.state.auto_float_max_height = Some(300);
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        for (i, w) in [(1, 200), (2, 400)] {
+            let mut window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            window.requested = Some(
+                XyhwBuilder {
+                    h: 100,
+                    w,
+                    ..XyhwBuilder::default()
+                }
+                .into(),
+            );
+            manager.window_created_handler(window, -1, -1);
+        }
+
+        let small = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle(1));
+        let xyhw = small.unwrap().exact_xyhw();
+        assert!(small.unwrap().floating());
+        assert_eq!(
+            (xyhw.x(), xyhw.y(), xyhw.w(), xyhw.h()),
+            (399, 349, 200, 100)
+        );
+        let large = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle(2));
+        assert!(!large.unwrap().floating());
+    }
 }
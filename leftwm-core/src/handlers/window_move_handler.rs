@@ -15,11 +15,19 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
         offset_y: i32,
     ) -> bool {
         let disable_snap = &self.config.disable_window_snap();
+        let snap_distance = self.config.floating_snap_distance();
+        let areas = if snap_distance > 0 {
+            snap_areas(&self.state.workspaces, &self.state.windows, handle)
+        } else {
+            vec![]
+        };
         match self.state.windows.iter_mut().find(|w| w.handle == *handle) {
             Some(w) => {
                 process_window(w, offset_x, offset_y);
                 if !disable_snap && snap_to_workspace(w, &self.state.workspaces) {
                     self.state.sort_windows();
+                } else if w.floating() {
+                    stick_to_edges(w, &areas, snap_distance);
                 }
                 true
             }
@@ -37,6 +45,65 @@ fn process_window<H: Handle>(window: &mut Window<H>, offset_x: i32, offset_y: i3
     window.set_floating_offsets(Some(offset));
 }
 
+// The areas a dragged floating window sticks to the edges of: workspaces, with and without the
+// space taken by docks, and the other visible floating windows.
+fn snap_areas<H: Handle>(
+    workspaces: &[Workspace],
+    windows: &[Window<H>],
+    handle: &WindowHandle<H>,
+) -> Vec<Xyhw> {
+    let workspaces = workspaces.iter().flat_map(|ws| [ws.xyhw, ws.xyhw_avoided]);
+    let windows = windows
+        .iter()
+        .filter(|w| w.handle != *handle && w.visible() && w.floating())
+        .map(outer_xyhw);
+    workspaces.chain(windows).collect()
+}
+
+// The area taken by a window along with its border.
+fn outer_xyhw<H: Handle>(window: &Window<H>) -> Xyhw {
+    let mut loc = window.calculated_xyhw();
+    loc.set_w(loc.w() + 2 * window.border);
+    loc.set_h(loc.h() + 2 * window.border);
+    loc
+}
+
+// Move a floating window so that its edges within `distance` of the edges of `areas` touch them.
+fn stick_to_edges<H: Handle>(window: &mut Window<H>, areas: &[Xyhw], distance: i32) {
+    let loc = outer_xyhw(window);
+    // The shortest move bringing an edge of the window onto an edge of an area beside it.
+    let pull = |start: i32, end: i32, edges: &mut dyn Iterator<Item = (i32, i32)>| {
+        edges
+            .flat_map(|(a, b)| [a - start, a - end, b - start, b - end])
+            .filter(|d| d.abs() <= distance)
+            .min_by_key(|d| d.abs())
+            .unwrap_or_default()
+    };
+    let beside = |a: i32, a_len: i32, b: i32, b_len: i32| a < b + b_len && b < a + a_len;
+    let dx = pull(
+        loc.x(),
+        loc.x() + loc.w(),
+        &mut areas
+            .iter()
+            .filter(|r| beside(r.y(), r.h(), loc.y(), loc.h()))
+            .map(|r| (r.x(), r.x() + r.w())),
+    );
+    let dy = pull(
+        loc.y(),
+        loc.y() + loc.h(),
+        &mut areas
+            .iter()
+            .filter(|r| beside(r.x(), r.w(), loc.x(), loc.w()))
+            .map(|r| (r.y(), r.y() + r.h())),
+    );
+    if dx != 0 || dy != 0 {
+        let mut offset = window.get_floating_offsets().unwrap_or_default();
+        offset.set_x(offset.x() + dx);
+        offset.set_y(offset.y() + dy);
+        window.set_floating_offsets(Some(offset));
+    }
+}
+
 // Update the window for the workspace it is currently on.
 fn snap_to_workspace<H: Handle>(window: &mut Window<H>, workspaces: &[Workspace]) -> bool {
     // Check that the workspace contains the window.
@@ -79,3 +146,63 @@ fn should_snap<H: Handle>(window: &mut Window<H>, workspace: &Workspace, loc: Xy
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::tests::TestConfig;
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::{BBox, MockHandle, Screen, Window, WindowHandle, XyhwBuilder};
+    use crate::Manager;
+
+    #[test]
+    fn dragged_floating_windows_should_stick_to_close_edges() {
+        let mut manager: Manager<MockHandle, TestConfig, MockDisplayServer<MockHandle>> =
+            Manager::new_test(vec!["1".to_string()]);
+        manager.config.floating_snap_distance = 20;
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        for i in 1..=2 {
+            let handle = WindowHandle::<MockHandle>(i);
+            manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+        }
+        manager.update_windows();
+        for (i, x) in [(1, 500), (2, 100)] {
+            let window = manager.state.windows.iter_mut().find(|w| w.handle.0 == i);
+            let window = window.unwrap();
+            window.set_floating(true);
+            window.set_floating_exact(
+                XyhwBuilder {
+                    x,
+                    y: 300,
+                    w: 200,
+                    h: 200,
+                    ..XyhwBuilder::default()
+                }
+                .into(),
+            );
+            window.start_loc = window.get_floating_offsets();
+        }
+        let position = |manager: &Manager<_, _, _>| {
+            let window = manager.state.windows.iter().find(|w| w.handle.0 == 2);
+            window.map(|w| (w.x(), w.y()))
+        };
+
+        // The right edge sticks to the left edge of the other window.
+        manager.window_move_handler(&WindowHandle(2), 195, 0);
+        assert_eq!(position(&manager), Some((300, 300)));
+        // The left and bottom edges stick to those of the workspace, further than windows are
+        // tiled when dragged near them.
+        manager.window_move_handler(&WindowHandle(2), -85, 285);
+        assert_eq!(position(&manager), Some((0, 600)));
+        // Edges further away are left alone.
+        manager.window_move_handler(&WindowHandle(2), -50, 20);
+        assert_eq!(position(&manager), Some((50, 320)));
+    }
+}
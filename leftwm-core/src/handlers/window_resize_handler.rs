@@ -1,7 +1,9 @@
 use super::{Manager, Window, WindowHandle};
-use crate::config::Config;
+use crate::config::{Config, Corner};
 use crate::display_servers::DisplayServer;
-use crate::models::Handle;
+use crate::models::{Handle, SplitResize};
+use crate::State;
+use leftwm_layouts::geometry::{Rotation, Size};
 
 impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     pub fn window_resize_handler(
@@ -10,20 +12,182 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
         offset_w: i32,
         offset_h: i32,
     ) -> bool {
+        if let Some(split) = self.state.split_resize {
+            resize_split(&mut self.state, &split, offset_w, offset_h);
+            return true;
+        }
         if let Some(w) = self.state.windows.iter_mut().find(|w| &w.handle == handle) {
-            process_window(w, offset_w, offset_h);
+            process_window(w, self.state.resize_corner, offset_w, offset_h);
             return true;
         }
         false
     }
 }
 
-fn process_window<H: Handle>(window: &mut Window<H>, offset_w: i32, offset_h: i32) {
+/// The corner of a window closest to a point, which follows the pointer when resizing from it.
+pub(crate) fn corner_at<H: Handle>(window: &Window<H>, x: i32, y: i32) -> Corner {
+    let (center_x, center_y) = window.calculated_xyhw().center();
+    match (x < center_x, y < center_y) {
+        (true, true) => Corner::TopLeft,
+        (false, true) => Corner::TopRight,
+        (true, false) => Corner::BottomLeft,
+        (false, false) => Corner::BottomRight,
+    }
+}
+
+fn process_window<H: Handle>(window: &mut Window<H>, corner: Corner, offset_w: i32, offset_h: i32) {
     window.set_floating(true);
     let mut offset = window.get_floating_offsets().unwrap_or_default();
     let start = window.start_loc.unwrap_or_default();
+    let from_left = matches!(corner, Corner::TopLeft | Corner::BottomLeft);
+    let from_top = matches!(corner, Corner::TopLeft | Corner::TopRight);
     // offset.clear_minmax();
-    offset.set_w(start.w() + offset_w);
-    offset.set_h(start.h() + offset_h);
+    let mut w = start.w() + if from_left { -offset_w } else { offset_w };
+    let mut h = start.h() + if from_top { -offset_h } else { offset_h };
+    // Keep the aspect ratios the window asked for, which are those of its inside.
+    if let Some(requested) = window.requested {
+        let base_w = window.normal.w() - window.border * 2;
+        let base_h = window.normal.h() - window.border * 2;
+        let (inner_w, inner_h) = requested.keep_aspect(base_w + w, base_h + h);
+        w = inner_w - base_w;
+        h = inner_h - base_h;
+    }
+    // The opposite corner stays where it is.
+    if from_left {
+        offset.set_x(start.x() + start.w() - w);
+    }
+    if from_top {
+        offset.set_y(start.y() + start.h() - h);
+    }
+    offset.set_w(w);
+    offset.set_h(h);
     window.set_floating_offsets(Some(offset));
 }
+
+/// Start resizing the main column of the layout of a tiled window, when enabled and the layout
+/// has one.
+pub(crate) fn start_split_resize<H: Handle>(
+    state: &mut State<H>,
+    handle: WindowHandle<H>,
+) -> Option<SplitResize> {
+    if !state.resize_tiled_splits {
+        return None;
+    }
+    let window = state.windows.iter().find(|w| w.handle == handle)?;
+    if window.floating() || window.is_fullscreen() || window.is_maximized() {
+        return None;
+    }
+    let tag = window.tag?;
+    let ws = state.workspaces.iter().find(|ws| ws.tag == Some(tag))?;
+
+    // Windows sharing a container share a slot of the layout.
+    let mut slots = vec![];
+    let mut slot = None;
+    for w in state
+        .windows
+        .iter()
+        .filter(|w| w.has_tag(&tag) && w.is_managed() && !w.floating())
+    {
+        let existing = w
+            .container
+            .and_then(|c| slots.iter().position(|s| *s == Some(c)));
+        let index = existing.unwrap_or_else(|| {
+            slots.push(w.container);
+            slots.len() - 1
+        });
+        if w.handle == handle {
+            slot = Some(index);
+        }
+    }
+
+    let layout = state.layout_manager.layout(ws.id, tag);
+    let start = layout.main_size()?;
+    let in_main = slot? < layout.main_window_count()?;
+    let vertical = matches!(layout.rotate, Rotation::East | Rotation::West);
+    Some(SplitResize {
+        workspace_id: ws.id,
+        tag,
+        start,
+        length: if vertical { ws.height() } else { ws.width() },
+        vertical,
+        in_main,
+    })
+}
+
+/// Resize the main column so the resized window grows by the drag offset.
+fn resize_split<H: Handle>(
+    state: &mut State<H>,
+    split: &SplitResize,
+    offset_w: i32,
+    offset_h: i32,
+) {
+    let mut delta = if split.vertical { offset_h } else { offset_w };
+    if !split.in_main {
+        delta = -delta;
+    }
+    let size = match split.start {
+        Size::Pixel(px) => Size::Pixel((px + delta).clamp(0, split.length)),
+        Size::Ratio(ratio) => {
+            let length = split.length.max(1) as f32;
+            Size::Ratio((ratio + delta as f32 / length).clamp(0.0, 1.0))
+        }
+    };
+    state
+        .layout_manager
+        .layout_mut(split.workspace_id, split.tag)
+        .set_main_size(size);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::tests::TestConfig;
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::{BBox, MockHandle, Screen, Window, WindowHandle, XyhwBuilder};
+    use crate::utils::modmask_lookup::{Button, ModMask};
+    use crate::{DisplayEvent, Manager};
+
+    #[test]
+    fn resizing_should_move_the_corner_clicked_closest_to() {
+        let mut manager: Manager<MockHandle, TestConfig, MockDisplayServer<MockHandle>> =
+            Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::new(
+            BBox {
+                x: 0,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            String::new(),
+        ));
+        let handle = WindowHandle::<MockHandle>(1);
+        manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+        manager.update_windows();
+        let window = &mut manager.state.windows[0];
+        window.set_floating(true);
+        window.set_floating_exact(
+            XyhwBuilder {
+                x: 100,
+                y: 100,
+                w: 200,
+                h: 200,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        let edges = |manager: &Manager<_, _, _>| {
+            let w = &manager.state.windows[0];
+            (w.x(), w.y(), w.x() + w.width(), w.y() + w.height())
+        };
+        let (_, _, right, bottom) = edges(&manager);
+        manager.state.reposition_cursor_on_resize = false;
+
+        manager.mouse_combo_handler(&ModMask::Super, Button::Secondary, handle, 120, 110);
+        manager.display_event_handler(DisplayEvent::ResizeWindow(handle, -20, -30));
+        assert_eq!(edges(&manager), (80, 70, right, bottom));
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode(80, 70));
+
+        manager.mouse_combo_handler(&ModMask::Super, Button::Secondary, handle, 280, 250);
+        manager.display_event_handler(DisplayEvent::ResizeWindow(handle, 10, 20));
+        assert_eq!(edges(&manager), (80, 70, right + 10, bottom + 20));
+    }
+}
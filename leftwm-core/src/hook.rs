@@ -0,0 +1,159 @@
+use crate::models::{Handle, TagId, WindowHandle, WorkspaceId};
+use crate::state::State;
+
+/// Something that changed in the state, for configs to react to with
+/// [`crate::Config::hook_handler`].
+///
+/// Hooks are found by comparing the state once the loop handled an event or command with the
+/// state the previous time, so changes made by a hook handler are reported the next time.
+/// Frequent events which changed nothing on screen, such as pointer motion, are not compared.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Hook<H: Handle> {
+    WindowCreated(WindowHandle<H>),
+    WindowDestroyed(WindowHandle<H>),
+    FocusChanged(Option<WindowHandle<H>>),
+    TagChanged {
+        workspace: WorkspaceId,
+        tag: Option<TagId>,
+    },
+    /// The last window of the tag shown on the workspace went away.
+    TagEmptied {
+        workspace: WorkspaceId,
+        tag: TagId,
+    },
+    /// The user did not touch the keyboard or mouse for the idle timeout.
+    UserIdle,
+    /// The user is back after being idle.
+    UserActive,
+}
+
+/// The parts of the state hooks are about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Snapshot<H: Handle> {
+    windows: Vec<WindowHandle<H>>,
+    focused: Option<WindowHandle<H>>,
+    tags: Vec<(WorkspaceId, Option<TagId>)>,
+    /// The shown tags without managed windows.
+    empty: Vec<(WorkspaceId, TagId)>,
+    idle: bool,
+}
+
+impl<H: Handle> Snapshot<H> {
+    pub fn capture(state: &State<H>) -> Self {
+        Self {
+            windows: state.windows.iter().map(|w| w.handle).collect(),
+            focused: state.focus_manager.window(&state.windows).map(|w| w.handle),
+            tags: state.workspaces.iter().map(|ws| (ws.id, ws.tag)).collect(),
+            empty: state
+                .workspaces
+                .iter()
+                .filter_map(|ws| Some((ws.id, ws.tag?)))
+                .filter(|(_, tag)| {
+                    !state
+                        .windows
+                        .iter()
+                        .any(|w| w.has_tag(tag) && w.is_managed())
+                })
+                .collect(),
+            idle: state.user_idle,
+        }
+    }
+
+    /// The hooks for the changes from `self` to `next`.
+    pub fn changes(&self, next: &Self) -> Vec<Hook<H>> {
+        let mut hooks: Vec<Hook<H>> = next
+            .windows
+            .iter()
+            .filter(|h| !self.windows.contains(h))
+            .map(|h| Hook::WindowCreated(*h))
+            .collect();
+        hooks.extend(
+            self.windows
+                .iter()
+                .filter(|h| !next.windows.contains(h))
+                .map(|h| Hook::WindowDestroyed(*h)),
+        );
+        hooks.extend(
+            next.tags
+                .iter()
+                .filter(|tag| !self.tags.contains(tag))
+                .map(|&(workspace, tag)| Hook::TagChanged { workspace, tag }),
+        );
+        hooks.extend(
+            next.empty
+                .iter()
+                .filter(|&&(workspace, tag)| {
+                    self.tags.contains(&(workspace, Some(tag)))
+                        && !self.empty.contains(&(workspace, tag))
+                })
+                .map(|&(workspace, tag)| Hook::TagEmptied { workspace, tag }),
+        );
+        if next.focused != self.focused {
+            hooks.push(Hook::FocusChanged(next.focused));
+        }
+        if next.idle != self.idle {
+            hooks.push(if next.idle {
+                Hook::UserIdle
+            } else {
+                Hook::UserActive
+            });
+        }
+        hooks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MockHandle;
+    use crate::Manager;
+
+    #[test]
+    fn state_changes_should_be_reported_as_hooks() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Default::default());
+        let before: Snapshot<MockHandle> = Snapshot::capture(&manager.state);
+
+        manager.window_created_handler(
+            crate::Window::new(WindowHandle::<MockHandle>(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.handle_window_focus(&WindowHandle(1));
+        let after = Snapshot::capture(&manager.state);
+        assert_eq!(
+            before.changes(&after),
+            vec![
+                Hook::WindowCreated(WindowHandle(1)),
+                Hook::FocusChanged(Some(WindowHandle(1))),
+            ]
+        );
+
+        manager.state.goto_tag_handler(2);
+        let moved = Snapshot::capture(&manager.state);
+        assert!(after.changes(&moved).contains(&Hook::TagChanged {
+            workspace: 1,
+            tag: Some(2)
+        }));
+        assert!(after.changes(&after).is_empty());
+        // Showing a tag without windows does not empty it.
+        assert!(!after
+            .changes(&moved)
+            .iter()
+            .any(|hook| matches!(hook, Hook::TagEmptied { .. })));
+
+        manager.state.goto_tag_handler(1);
+        let shown = Snapshot::capture(&manager.state);
+        manager.window_destroyed_handler(&WindowHandle(1));
+        let emptied = Snapshot::capture(&manager.state);
+        assert!(shown.changes(&emptied).contains(&Hook::TagEmptied {
+            workspace: 1,
+            tag: 1
+        }));
+
+        manager.state.user_idle = true;
+        let idle = Snapshot::capture(&manager.state);
+        assert_eq!(emptied.changes(&idle), vec![Hook::UserIdle]);
+        assert_eq!(idle.changes(&emptied), vec![Hook::UserActive]);
+    }
+}
@@ -1,6 +1,8 @@
 mod layout_manager;
 mod layout_mode;
 
+use leftwm_layouts::geometry::Rect;
+use std::fmt::Debug;
 use thiserror::Error;
 
 pub use layout_manager::LayoutManager;
@@ -22,6 +24,16 @@ pub const CENTER_MAIN: &str = "CenterMain";
 pub const CENTER_MAIN_BALANCED: &str = "CenterMainBalanced";
 pub const CENTER_MAIN_FLUID: &str = "CenterMainFluid";
 
+/// Places windows for layouts implemented outside of `leftwm_layouts`, such as plugins.
+pub trait LayoutArranger: Debug + Send + Sync {
+    /// The names of the layouts this arranger places windows for.
+    fn layouts(&self) -> Vec<String>;
+
+    /// The tiles of `count` windows in `area` for the layout named `layout`. `None` falls back
+    /// to the definition of the layout.
+    fn arrange(&self, layout: &str, count: usize, area: &Rect) -> Option<Vec<Rect>>;
+}
+
 #[derive(Debug, Error)]
 #[error("Could not parse layout: {0}")]
 pub struct ParseLayoutError(String);
@@ -1,9 +1,11 @@
 use crate::{config::Config, utils::helpers::cycle_vec};
+use leftwm_layouts::geometry::Rect;
 use leftwm_layouts::Layout;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use super::LayoutMode;
+use super::{LayoutArranger, LayoutMode};
 
 /// The [`LayoutManager`] holds the actual set of [`Layout`].
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,6 +27,18 @@ pub struct LayoutManager {
     /// The actual, modifiable layouts grouped by either
     /// Workspace or Tag, depending on the configured [`LayoutMode`].
     layouts: HashMap<usize, Vec<Layout>>,
+
+    /// The name of the layout tags start with, by tag ID, when overridden for the tag.
+    #[serde(default)]
+    default_layouts: HashMap<usize, String>,
+
+    /// The most windows shown in the stack of a layout, by layout name, when limited.
+    #[serde(default)]
+    max_visible_in_stack: HashMap<String, usize>,
+
+    /// Places windows for the layouts it knows, instead of their definition.
+    #[serde(skip)]
+    arranger: Option<Arc<dyn LayoutArranger>>,
 }
 
 impl LayoutManager {
@@ -67,6 +81,19 @@ impl LayoutManager {
             }
         }
 
+        let default_layouts: HashMap<usize, String> = config
+            .create_list_of_tag_labels()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, label)| Some((i + 1, config.tag_overrides(label).layout?)))
+            .collect();
+
+        let max_visible_in_stack: HashMap<String, usize> = config
+            .layout_definitions()
+            .iter()
+            .filter_map(|def| Some((def.name.clone(), config.max_visible_in_stack(&def.name)?)))
+            .collect();
+
         if available_layouts.is_empty() {
             tracing::warn!(
                 "No Layouts were loaded from config - defaulting to a single default Layout"
@@ -85,6 +112,9 @@ impl LayoutManager {
             available_layouts,
             available_layouts_per_ws,
             layouts: HashMap::new(),
+            default_layouts,
+            max_visible_in_stack,
+            arranger: config.layout_arranger(),
         }
     }
 
@@ -105,6 +135,12 @@ impl LayoutManager {
             );
             return;
         }
+        if self.default_layouts != old.default_layouts {
+            tracing::debug!(
+                "The default Layouts per Tag have changed, layouts will not be restored"
+            );
+            return;
+        }
         self.layouts = old.layouts.clone();
     }
 
@@ -129,11 +165,22 @@ impl LayoutManager {
     ///
     /// If the layouts for the specific workspace / tag have not
     /// yet been set up, they will be initialized by copying
-    /// from the [`Self::available_layouts`] or [`Self::available_layouts_per_ws`].
+    /// from the [`Self::available_layouts`] or [`Self::available_layouts_per_ws`],
+    /// starting with the default layout of the tag if it has one.
     fn layouts_mut(&mut self, wsid: usize, tagid: usize) -> &mut Vec<Layout> {
         let id = self.id(wsid, tagid);
         self.layouts.entry(id).or_insert_with(|| match &self.mode {
-            LayoutMode::Tag => self.available_layouts.clone(),
+            LayoutMode::Tag => {
+                let mut layouts = self.available_layouts.clone();
+                if let Some(i) = self
+                    .default_layouts
+                    .get(&tagid)
+                    .and_then(|name| layouts.iter().position(|l| &l.name == name))
+                {
+                    layouts.rotate_left(i);
+                }
+                layouts
+            }
             LayoutMode::Workspace => self
                 .available_layouts_per_ws
                 .get(&wsid)
@@ -181,6 +228,35 @@ impl LayoutManager {
         layouts.first_mut().unwrap()
     }
 
+    /// The tiles of `count` windows in `area` with the current layout of the workspace / tag
+    /// context.
+    pub fn arrange(&mut self, wsid: usize, tagid: usize, count: usize, area: &Rect) -> Vec<Rect> {
+        let arranger = self.arranger.clone();
+        let def = self.layout(wsid, tagid);
+        arranger
+            .filter(|arranger| arranger.layouts().contains(&def.name))
+            .and_then(|arranger| arranger.arrange(&def.name, count, area))
+            .unwrap_or_else(|| leftwm_layouts::apply(def, count, area))
+    }
+
+    /// How many of `count` windows the current layout of the workspace / tag context shows, the
+    /// windows of its main column and at most `max_visible_in_stack` of the stack.
+    pub fn visible_count(&mut self, wsid: usize, tagid: usize, count: usize) -> usize {
+        let def = self.layout(wsid, tagid);
+        let (name, main) = (def.name.clone(), def.main_window_count().unwrap_or(0));
+        match self.max_visible_in_stack.get(&name) {
+            Some(max) => count.min((main + max).max(1)),
+            None => count,
+        }
+    }
+
+    /// Reset the layouts of the provided workspace / tag context to the configured ones, undoing
+    /// any changes made to them.
+    pub fn reset(&mut self, wsid: usize, tagid: usize) {
+        let id = self.id(wsid, tagid);
+        self.layouts.remove(&id);
+    }
+
     pub fn cycle_next_layout(&mut self, wsid: usize, tagid: usize) {
         cycle_vec(self.layouts_mut(wsid, tagid), -1);
     }
@@ -211,11 +287,13 @@ mod tests {
     use leftwm_layouts::layouts::Layouts;
 
     use crate::{
-        config::tests::TestConfig,
+        config::{tests::TestConfig, TagOverrides},
         layouts::{self, EVEN_VERTICAL, MONOCLE},
     };
 
-    use super::LayoutManager;
+    use super::{LayoutArranger, LayoutManager, LayoutMode, Rect};
+    use std::collections::HashMap;
+    use std::sync::Arc;
 
     fn layout_manager() -> LayoutManager {
         let config = TestConfig {
@@ -262,4 +340,54 @@ mod tests {
         layout_manager.set_layout(2, 1, EVEN_VERTICAL);
         assert_eq!(EVEN_VERTICAL, &layout_manager.layout(2, 1).name);
     }
+
+    #[derive(Debug)]
+    struct Stacked;
+
+    impl LayoutArranger for Stacked {
+        fn layouts(&self) -> Vec<String> {
+            vec![EVEN_VERTICAL.to_string()]
+        }
+
+        fn arrange(&self, _layout: &str, count: usize, area: &Rect) -> Option<Vec<Rect>> {
+            Some(vec![*area; count])
+        }
+    }
+
+    #[test]
+    fn arranger_should_place_windows_for_its_layouts() {
+        let mut layout_manager = layout_manager();
+        layout_manager.arranger = Some(Arc::new(Stacked));
+        let area = Rect::new(0, 0, 400, 200);
+
+        layout_manager.set_layout(2, 1, EVEN_VERTICAL);
+        assert_eq!(layout_manager.arrange(2, 1, 2, &area), vec![area, area]);
+
+        layout_manager.set_layout(2, 1, MONOCLE);
+        assert_eq!(layout_manager.arrange(2, 1, 2, &area), vec![area]);
+    }
+
+    #[test]
+    fn tags_should_start_with_their_default_layout() {
+        let config = TestConfig {
+            tags: vec!["1".to_string(), "video".to_string()],
+            layouts: vec![MONOCLE.to_string(), EVEN_VERTICAL.to_string()],
+            layout_definitions: Layouts::default().layouts,
+            tag_overrides: HashMap::from([(
+                "video".to_string(),
+                TagOverrides {
+                    layout: Some(EVEN_VERTICAL.to_string()),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let mut layout_manager = LayoutManager::new(&config);
+        layout_manager.mode = LayoutMode::Tag;
+
+        assert_eq!(MONOCLE, &layout_manager.layout(1, 1).name);
+        assert_eq!(EVEN_VERTICAL, &layout_manager.layout(1, 2).name);
+        layout_manager.cycle_next_layout(1, 2);
+        assert_eq!(MONOCLE, &layout_manager.layout(1, 2).name);
+    }
 }
@@ -22,6 +22,7 @@ pub mod display_servers;
 pub mod errors;
 mod event_loop;
 mod handlers;
+mod hook;
 pub mod layouts;
 pub mod models;
 pub mod state;
@@ -30,11 +31,13 @@ pub mod utils;
 use utils::modmask_lookup::Button;
 use utils::modmask_lookup::ModMask;
 
-pub use command::{Command, ReleaseScratchPadOption};
+pub use command::{Command, ReleaseScratchPadOption, WindowSelector};
 pub use config::Config;
 pub use display_action::DisplayAction;
 pub use display_event::DisplayEvent;
 pub use display_servers::DisplayServer;
+pub use event_loop::Error as EventLoopError;
+pub use hook::Hook;
 pub use models::Manager;
 pub use models::Mode;
 pub use models::Window;
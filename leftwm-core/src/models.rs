@@ -1,5 +1,6 @@
 //! Objects (such as windows) used to develop `LeftWM`.
 mod dock_area;
+mod floating_memory;
 mod focus_manager;
 mod gutter;
 mod manager;
@@ -20,19 +21,27 @@ mod xyhw_change;
 pub mod dto;
 
 pub use dock_area::DockArea;
+pub use floating_memory::FloatingMemory;
 pub use focus_manager::FocusBehaviour;
+pub use focus_manager::FocusFollowsWorkspace;
 pub use focus_manager::FocusManager;
+pub use focus_manager::FOCUS_HISTORY_LENGTH;
 pub use gutter::Gutter;
 pub use gutter::Side;
 pub use manager::Manager;
+#[cfg(test)]
+pub(crate) use manager::TestManager;
 pub use margins::Margins;
-pub use mode::Mode;
+pub use mode::{Mode, SplitResize};
 pub use scratchpad::{ScratchPad, ScratchPadName};
-pub use screen::{BBox, Screen};
+pub use screen::{sort_screens, BBox, Screen};
 pub use size::Size;
+pub use window::BorderColors;
+pub use window::FloatingPlacement;
 pub use window::Handle;
 #[cfg(test)]
 pub(crate) use window::MockHandle;
+pub use window::PinnedFrom;
 pub use window::Window;
 pub use window::WindowHandle;
 pub use window_change::WindowChange;
@@ -47,5 +56,6 @@ pub use tag::Tag;
 pub use tag::Tags;
 
 pub type TagId = usize;
+pub type ContainerId = usize;
 pub type WorkspaceId = usize;
 type MaybeWindowHandle<H> = Option<WindowHandle<H>>;
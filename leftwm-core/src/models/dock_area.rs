@@ -1,6 +1,7 @@
 use crate::models::Xyhw;
 use crate::models::XyhwBuilder;
 
+use super::BBox;
 use super::Handle;
 use super::Screen;
 
@@ -24,6 +25,11 @@ pub struct DockArea {
 }
 
 impl DockArea {
+    /// Returns the area the strut reserves on `screen`, or `None` if it reserves nothing there.
+    ///
+    /// Struts are measured from the edges of the whole screens area, but the start and end
+    /// fields of `_NET_WM_STRUT_PARTIAL` limit them to the monitor the dock sits on, so the
+    /// reserved area is clipped to `screen`.
     #[must_use]
     pub fn as_xyhw<H: Handle>(
         &self,
@@ -31,69 +37,128 @@ impl DockArea {
         screens_width: i32,
         screen: &Screen<H>,
     ) -> Option<Xyhw> {
+        let bbox = &screen.bbox;
         if self.top > 0 {
-            return Some(self.xyhw_from_top(screen.bbox.y));
+            return self.xyhw_from_top(bbox);
         }
         if self.bottom > 0 {
-            return Some(self.xyhw_from_bottom(screens_height, screen.bbox.y + screen.bbox.height));
+            return self.xyhw_from_bottom(screens_height, bbox);
         }
         if self.left > 0 {
-            return Some(self.xyhw_from_left(screen.bbox.x));
+            return self.xyhw_from_left(bbox);
         }
         if self.right > 0 {
-            return Some(self.xyhw_from_right(screens_width, screen.bbox.x + screen.bbox.width));
+            return self.xyhw_from_right(screens_width, bbox);
         }
         None
     }
 
-    fn xyhw_from_top(&self, screen_y: i32) -> Xyhw {
-        XyhwBuilder {
-            x: self.top_start_x,
-            y: screen_y,
-            h: self.top - screen_y,
-            w: self.top_end_x - self.top_start_x,
-            ..XyhwBuilder::default()
+    fn xyhw_from_top(&self, bbox: &BBox) -> Option<Xyhw> {
+        if self.top <= bbox.y || self.top > bbox.y + bbox.height {
+            return None;
         }
-        .into()
+        let (x, w) = clip_span(self.top_start_x, self.top_end_x, bbox.x, bbox.width)?;
+        Some(
+            XyhwBuilder {
+                x,
+                y: bbox.y,
+                h: self.top - bbox.y,
+                w,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        )
     }
 
-    fn xyhw_from_bottom(&self, screens_height: i32, screen_bottom: i32) -> Xyhw {
-        XyhwBuilder {
-            x: self.bottom_start_x,
-            y: screens_height - self.bottom,
-            h: self.bottom - (screens_height - screen_bottom),
-            w: self.bottom_end_x - self.bottom_start_x,
-            ..XyhwBuilder::default()
+    fn xyhw_from_bottom(&self, screens_height: i32, bbox: &BBox) -> Option<Xyhw> {
+        let y = screens_height - self.bottom;
+        if y < bbox.y || y >= bbox.y + bbox.height {
+            return None;
         }
-        .into()
+        let (x, w) = clip_span(self.bottom_start_x, self.bottom_end_x, bbox.x, bbox.width)?;
+        Some(
+            XyhwBuilder {
+                x,
+                y,
+                h: bbox.y + bbox.height - y,
+                w,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        )
     }
 
-    fn xyhw_from_left(&self, screen_x: i32) -> Xyhw {
-        XyhwBuilder {
-            x: screen_x,
-            y: self.left_start_y,
-            h: self.left_end_y - self.left_start_y,
-            w: self.left - screen_x,
-            ..XyhwBuilder::default()
+    fn xyhw_from_left(&self, bbox: &BBox) -> Option<Xyhw> {
+        if self.left <= bbox.x || self.left > bbox.x + bbox.width {
+            return None;
         }
-        .into()
+        let (y, h) = clip_span(self.left_start_y, self.left_end_y, bbox.y, bbox.height)?;
+        Some(
+            XyhwBuilder {
+                x: bbox.x,
+                y,
+                h,
+                w: self.left - bbox.x,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        )
     }
 
-    fn xyhw_from_right(&self, screens_width: i32, screen_right: i32) -> Xyhw {
-        XyhwBuilder {
-            x: screens_width - self.right,
-            y: self.right_start_y,
-            h: self.right_end_y - self.right_start_y,
-            w: self.right - (screens_width - screen_right),
-            ..XyhwBuilder::default()
+    fn xyhw_from_right(&self, screens_width: i32, bbox: &BBox) -> Option<Xyhw> {
+        let x = screens_width - self.right;
+        if x < bbox.x || x >= bbox.x + bbox.width {
+            return None;
         }
-        .into()
+        let (y, h) = clip_span(self.right_start_y, self.right_end_y, bbox.y, bbox.height)?;
+        Some(
+            XyhwBuilder {
+                x,
+                y,
+                h,
+                w: bbox.x + bbox.width - x,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        )
     }
 }
 
+/// Clips the inclusive strut range `start..=end` to the screen range starting at `screen_start`,
+/// returning the start and length of what is left. A range left at zero, as `_NET_WM_STRUT`
+/// gives, covers the whole screen.
+fn clip_span(start: i32, end: i32, screen_start: i32, screen_len: i32) -> Option<(i32, i32)> {
+    if start == 0 && end == 0 {
+        return Some((screen_start, screen_len));
+    }
+    let from = start.max(screen_start);
+    let to = (end + 1).min(screen_start + screen_len);
+    (from < to).then_some((from, to - from))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::MockHandle;
+
+    const SCREEN: BBox = BBox {
+        x: 0,
+        y: 0,
+        width: 2000,
+        height: 1000,
+    };
+
+    fn screen(x: i32, y: i32, width: i32, height: i32) -> Screen<MockHandle> {
+        Screen::new(
+            BBox {
+                x,
+                y,
+                width,
+                height,
+            },
+            String::new(),
+        )
+    }
 
     #[test]
     fn should_be_able_to_build_from_top() {
@@ -105,13 +170,13 @@ mod tests {
         };
         let expected: Xyhw = XyhwBuilder {
             h: 2,
-            w: 190,
+            w: 191,
             x: 10,
             y: 0,
             ..XyhwBuilder::default()
         }
         .into();
-        assert_eq!(area.xyhw_from_top(0), expected);
+        assert_eq!(area.xyhw_from_top(&SCREEN), Some(expected));
     }
 
     #[test]
@@ -124,13 +189,13 @@ mod tests {
         };
         let expected: Xyhw = XyhwBuilder {
             h: 2,
-            w: 190,
+            w: 191,
             x: 10,
             y: 998,
             ..XyhwBuilder::default()
         }
         .into();
-        assert_eq!(area.xyhw_from_bottom(1000, 1000), expected);
+        assert_eq!(area.xyhw_from_bottom(1000, &SCREEN), Some(expected));
     }
 
     #[test]
@@ -142,14 +207,14 @@ mod tests {
             ..DockArea::default()
         };
         let expected: Xyhw = XyhwBuilder {
-            h: 190,
+            h: 191,
             w: 2,
             x: 0,
             y: 10,
             ..XyhwBuilder::default()
         }
         .into();
-        assert_eq!(area.xyhw_from_left(0), expected);
+        assert_eq!(area.xyhw_from_left(&SCREEN), Some(expected));
     }
 
     #[test]
@@ -161,13 +226,68 @@ mod tests {
             ..DockArea::default()
         };
         let expected: Xyhw = XyhwBuilder {
-            h: 190,
+            h: 191,
             w: 2,
             x: 1998,
             y: 10,
             ..XyhwBuilder::default()
         }
         .into();
-        assert_eq!(area.xyhw_from_right(2000, 2000), expected);
+        assert_eq!(area.xyhw_from_right(2000, &SCREEN), Some(expected));
+    }
+
+    #[test]
+    fn a_strut_without_a_range_should_span_the_whole_screen() {
+        let area = DockArea {
+            top: 20,
+            ..DockArea::default()
+        };
+        let expected: Xyhw = XyhwBuilder {
+            h: 20,
+            w: 2000,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert_eq!(area.xyhw_from_top(&SCREEN), Some(expected));
+    }
+
+    #[test]
+    fn a_partial_strut_should_only_reserve_space_on_its_own_monitor() {
+        let left = screen(0, 0, 1920, 1080);
+        let right = screen(1920, 0, 1920, 1200);
+        let top_of_right = DockArea {
+            top: 20,
+            top_start_x: 1920,
+            top_end_x: 3839,
+            ..DockArea::default()
+        };
+        assert!(!left.contains_dock_area(top_of_right, (1200, 3840)));
+        assert!(right.contains_dock_area(top_of_right, (1200, 3840)));
+        let expected: Xyhw = XyhwBuilder {
+            h: 20,
+            w: 1920,
+            x: 1920,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert_eq!(top_of_right.as_xyhw(1200, 3840, &right), Some(expected));
+
+        // Struts are measured from the bottom of the whole screens area, which the shorter
+        // left monitor does not reach.
+        let bottom_of_left = DockArea {
+            bottom: 150,
+            bottom_start_x: 0,
+            bottom_end_x: 1919,
+            ..DockArea::default()
+        };
+        assert_eq!(bottom_of_left.as_xyhw(1200, 3840, &right), None);
+        let expected: Xyhw = XyhwBuilder {
+            h: 30,
+            w: 1920,
+            y: 1050,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert_eq!(bottom_of_left.as_xyhw(1200, 3840, &left), Some(expected));
     }
 }
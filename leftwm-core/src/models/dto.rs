@@ -1,18 +1,25 @@
 use crate::state::State;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-use super::Handle;
+use super::{Handle, Margins, TagId, WindowHandle, Xyhw};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Viewport {
     pub id: usize,
     pub output: String,
     pub tag: String,
+    /// Tags shown along with `tag`.
+    #[serde(default)]
+    pub extra_tags: Vec<String>,
     pub h: u32,
     pub w: u32,
     pub x: i32,
     pub y: i32,
     pub layout: String,
+    /// Number of windows hidden behind the last tile of the stack.
+    #[serde(default)]
+    pub hidden_in_stack: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -23,6 +30,15 @@ pub struct ManagerState {
     pub active_desktop: Vec<String>,
     pub working_tags: Vec<String>,
     pub urgent_tags: Vec<String>,
+    /// Ids of the recently focused windows on the focused tag, most recent first.
+    #[serde(default)]
+    pub focus_history: Vec<u64>,
+    /// Titles of the minimized windows, most recently minimized last.
+    #[serde(default)]
+    pub minimized: Vec<String>,
+    /// Number of errors the display server ran into, by category.
+    #[serde(default)]
+    pub display_server_errors: BTreeMap<String, usize>,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -47,16 +63,26 @@ pub struct DisplayWorkspace {
     pub layout: String,
     pub index: usize,
     pub tags: Vec<TagsForWorkspace>,
+    /// Number of windows hidden behind the last tile of the stack.
+    pub hidden_in_stack: usize,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DisplayState {
     pub window_title: String,
     pub workspaces: Vec<DisplayWorkspace>,
+    pub focus_history: Vec<u64>,
+    pub minimized: Vec<String>,
+    pub display_server_errors: BTreeMap<String, usize>,
 }
 
 impl From<ManagerState> for DisplayState {
     fn from(m: ManagerState) -> Self {
-        let visible: Vec<String> = m.viewports.iter().map(|vp| vp.tag.clone()).collect();
+        let visible: Vec<String> = m
+            .viewports
+            .iter()
+            .flat_map(|vp| std::iter::once(&vp.tag).chain(&vp.extra_tags))
+            .cloned()
+            .collect();
         let workspaces = m
             .viewports
             .iter()
@@ -76,6 +102,9 @@ impl From<ManagerState> for DisplayState {
         Self {
             workspaces,
             window_title: m.window_title.unwrap_or_default(),
+            focus_history: m.focus_history,
+            minimized: m.minimized,
+            display_server_errors: m.display_server_errors,
         }
     }
 }
@@ -95,7 +124,7 @@ fn viewport_into_display_workspace(
         .map(|(index, t)| TagsForWorkspace {
             name: t.clone(),
             index,
-            mine: viewport.tag == *t,
+            mine: viewport.tag == *t || viewport.extra_tags.contains(t),
             visible: visible.contains(t),
             focused: focused.contains(t),
             urgent: urgent_tags.contains(t),
@@ -112,6 +141,7 @@ fn viewport_into_display_workspace(
         y: viewport.y,
         index: ws_index,
         layout: viewport.layout.clone(),
+        hidden_in_stack: viewport.hidden_in_stack,
     }
 }
 
@@ -130,7 +160,12 @@ impl<H: Handle> From<&State<H>> for ManagerState {
             .tags
             .all()
             .iter()
-            .filter(|tag| state.windows.iter().any(|w| w.has_tag(&tag.id) && w.urgent))
+            .filter(|tag| {
+                state
+                    .windows
+                    .iter()
+                    .any(|w| w.has_tag(&tag.id) && w.shows_urgency())
+            })
             .map(|t| t.label.clone())
             .collect();
         for ws in &state.workspaces {
@@ -154,21 +189,30 @@ impl<H: Handle> From<&State<H>> for ManagerState {
                     |s| s.output.clone(),
                 );
 
+            let extra_tags = ws
+                .extra_tags
+                .iter()
+                .filter_map(|&tag_id| state.tags.get(tag_id).map(|tag| tag.label.clone()))
+                .collect();
+
             viewports.push(Viewport {
                 id: ws.id,
                 output,
                 tag: tag_label,
+                extra_tags,
                 x: ws.xyhw.x(),
                 y: ws.xyhw.y(),
                 h: ws.xyhw.h() as u32,
                 w: ws.xyhw.w() as u32,
                 layout: layout_name,
+                hidden_in_stack: ws.hidden_in_stack,
             });
         }
         let active_desktop = match state.focus_manager.workspace(&state.workspaces) {
             Some(ws) => ws
                 .tag
                 .iter()
+                .chain(&ws.extra_tags)
                 .map(|&tag_id| state.tags.get(tag_id).unwrap().label.clone())
                 .collect(),
             None => vec![], // todo ??
@@ -177,6 +221,18 @@ impl<H: Handle> From<&State<H>> for ManagerState {
             Some(win) => win.name.clone(),
             None => None,
         };
+        let focus_history = state
+            .focus_manager
+            .window_history_for_tag(state.focus_manager.tag(0), &state.windows)
+            .iter()
+            .filter_map(|handle| serde_json::to_value(handle).ok()?.as_u64())
+            .collect();
+        let minimized = state
+            .minimized
+            .iter()
+            .filter_map(|(handle, _)| state.windows.iter().find(|w| &w.handle == handle))
+            .map(|w| w.name.clone().unwrap_or_default())
+            .collect();
         Self {
             window_title,
             desktop_names: state
@@ -189,6 +245,90 @@ impl<H: Handle> From<&State<H>> for ManagerState {
             active_desktop,
             urgent_tags,
             working_tags,
+            focus_history,
+            minimized,
+            display_server_errors: state.display_server_errors.clone(),
         }
     }
 }
+
+/// An area of the screen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+/// A window to draw decorations around, see [`Decorations`].
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DecorationFrame<H: Handle> {
+    #[serde(bound = "")]
+    pub window: WindowHandle<H>,
+    pub title: Option<String>,
+    pub class: Option<String>,
+    pub tag: Option<TagId>,
+    pub focused: bool,
+    pub urgent: bool,
+    pub floating: bool,
+    pub selected: bool,
+    pub border: i32,
+    /// The area to decorate, the window included.
+    pub frame: Geometry,
+    /// The area of the window, its border included.
+    pub client: Geometry,
+}
+
+/// The windows external decorators draw frames around. Decorators connect to
+/// `current_decorations.sock` and get the frames as a JSON line whenever they change.
+///
+/// Frames are the space reserved by the `decoration_frame` theme option: tiled windows are shrunk
+/// to leave it free in their tile, floating windows get it around them. Decorators should draw in
+/// override-redirect windows, which leftwm leaves alone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Decorations<H: Handle> {
+    #[serde(bound = "")]
+    pub frames: Vec<DecorationFrame<H>>,
+}
+
+impl<H: Handle> From<&State<H>> for Decorations<H> {
+    fn from(state: &State<H>) -> Self {
+        let focused = state.focus_manager.window(&state.windows).map(|w| w.handle);
+        let frames = state
+            .windows
+            .iter()
+            .filter(|w| {
+                w.visible() && w.is_managed() && !w.is_fullscreen() && w.frame != Margins::new(0)
+            })
+            .map(|w| {
+                let client = w.calculated_xyhw();
+                DecorationFrame {
+                    window: w.handle,
+                    title: w.name.clone(),
+                    class: w.res_class.clone(),
+                    tag: w.tag,
+                    focused: Some(w.handle) == focused,
+                    urgent: w.urgent,
+                    floating: w.floating(),
+                    selected: w.selected,
+                    border: w.border(),
+                    frame: frame_geometry(&client, w.border(), &w.frame),
+                    client: frame_geometry(&client, w.border(), &Margins::new(0)),
+                }
+            })
+            .collect();
+        Self { frames }
+    }
+}
+
+/// The area of a window with its border, grown by `frame`.
+fn frame_geometry(window: &Xyhw, border: i32, frame: &Margins) -> Geometry {
+    Geometry {
+        x: window.x() - frame.left as i32,
+        y: window.y() - frame.top as i32,
+        w: window.w() + border * 2 + (frame.left + frame.right) as i32,
+        h: window.h() + border * 2 + (frame.top + frame.bottom) as i32,
+    }
+}
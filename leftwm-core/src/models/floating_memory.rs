@@ -0,0 +1,129 @@
+use super::{Handle, Window, Workspace, Xyhw, XyhwBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Position relative to the workspace, and size of a floating window.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct Geometry {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+}
+
+/// The last floating geometry of windows, by `WM_CLASS` and instance, so a window floated again
+/// appears where it was.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FloatingMemory {
+    geometries: HashMap<String, Geometry>,
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl FloatingMemory {
+    /// Remember the floating geometry of `window`, on the workspace `ws`.
+    pub fn remember<H: Handle>(&mut self, window: &Window<H>, ws: &Workspace) {
+        let Some(key) = key(window) else {
+            return;
+        };
+        let xyhw = window.exact_xyhw();
+        let geometry = Geometry {
+            x: xyhw.x() - ws.xyhw.x(),
+            y: xyhw.y() - ws.xyhw.y(),
+            w: xyhw.w(),
+            h: xyhw.h(),
+        };
+        if self.geometries.insert(key, geometry) != Some(geometry) {
+            self.changed = true;
+        }
+    }
+
+    /// The remembered floating geometry of `window` on the workspace `ws`. Windows which would
+    /// not fit in the workspace anymore are centered.
+    #[must_use]
+    pub fn recall<H: Handle>(&self, window: &Window<H>, ws: &Workspace) -> Option<Xyhw> {
+        let geometry = self.geometries.get(&key(window)?)?;
+        let mut xyhw: Xyhw = XyhwBuilder {
+            x: ws.xyhw.x() + geometry.x,
+            y: ws.xyhw.y() + geometry.y,
+            w: geometry.w,
+            h: geometry.h,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        if !ws.xyhw_avoided.contains_xyhw(&xyhw) {
+            xyhw.center_relative(ws.xyhw_avoided, window.border);
+        }
+        Some(xyhw)
+    }
+
+    /// Whether geometries changed since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+fn key<H: Handle>(window: &Window<H>) -> Option<String> {
+    if window.res_class.is_none() && window.res_name.is_none() {
+        return None;
+    }
+    Some(format!(
+        "{}/{}",
+        window.res_class.as_deref().unwrap_or_default(),
+        window.res_name.as_deref().unwrap_or_default()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BBox, MockHandle, WindowHandle};
+
+    fn workspace(x: i32) -> Workspace {
+        let mut ws = Workspace::new(
+            BBox {
+                x,
+                y: 0,
+                width: 1000,
+                height: 800,
+            },
+            0,
+        );
+        ws.update_avoided_areas();
+        ws
+    }
+
+    #[test]
+    fn windows_should_float_where_they_were_on_their_workspace() {
+        let mut window = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        window.res_class = Some("Pavucontrol".to_string());
+        window.res_name = Some("pavucontrol".to_string());
+        window.set_floating(true);
+        window.normal = workspace(0).xyhw;
+        window.set_floating_exact(
+            XyhwBuilder {
+                x: 100,
+                y: 50,
+                w: 400,
+                h: 300,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+
+        let mut memory = FloatingMemory::default();
+        memory.remember(&window, &workspace(0));
+        assert!(memory.take_changed());
+        memory.remember(&window, &workspace(0));
+        assert!(!memory.take_changed());
+
+        let xyhw = memory.recall(&window, &workspace(1000)).unwrap();
+        assert_eq!(
+            (xyhw.x(), xyhw.y(), xyhw.w(), xyhw.h()),
+            (1100, 50, 400, 300)
+        );
+
+        window.res_name = Some("other".to_string());
+        assert!(memory.recall(&window, &workspace(0)).is_none());
+    }
+}
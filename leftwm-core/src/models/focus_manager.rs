@@ -34,6 +34,21 @@ impl FocusBehaviour {
     }
 }
 
+/// What moves along with the focus when a command focuses another workspace.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FocusFollowsWorkspace {
+    /// Only the window last focused on the workspace's tag gets focus, if any.
+    #[default]
+    Nothing,
+    /// Keyboard focus moves to the workspace, on its last focused or else its top window.
+    Keyboard,
+    /// Like `Keyboard`, and the cursor moves onto that window too.
+    KeyboardAndCursor,
+}
+
+/// Maximum number of entries kept in each of the focus histories.
+pub const FOCUS_HISTORY_LENGTH: usize = 10;
+
 /// `FocusManager` stores the history of which workspaces, tags, and windows had focus.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FocusManager<H: Handle> {
@@ -43,12 +58,17 @@ pub struct FocusManager<H: Handle> {
     pub tag_history: VecDeque<TagId>,
     #[serde(bound = "")]
     pub tags_last_window: HashMap<TagId, WindowHandle<H>>,
+    /// The recently focused windows of each tag, most recent first.
+    #[serde(bound = "", default)]
+    pub tags_window_history: HashMap<TagId, VecDeque<WindowHandle<H>>>,
     pub last_mouse_position: Option<(i32, i32)>,
     // entries below are configuration variables and are never changed
     pub behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
     pub sloppy_mouse_follows_focus: bool,
     pub create_follows_cursor: bool,
+    #[serde(default)]
+    pub focus_follows_workspace: FocusFollowsWorkspace,
 }
 
 impl<H: Handle> FocusManager<H> {
@@ -58,11 +78,13 @@ impl<H: Handle> FocusManager<H> {
             window_history: Default::default(),
             tag_history: Default::default(),
             tags_last_window: Default::default(),
+            tags_window_history: Default::default(),
             last_mouse_position: None,
             behaviour: config.focus_behaviour(),
             focus_new_windows: config.focus_new_windows(),
             sloppy_mouse_follows_focus: config.sloppy_mouse_follows_focus(),
             create_follows_cursor: config.create_follows_cursor(),
+            focus_follows_workspace: config.focus_follows_workspace(),
         }
     }
 
@@ -119,6 +141,58 @@ impl<H: Handle> FocusManager<H> {
         None
     }
 
+    /// Record a window focus change at the front of the history.
+    /// Older entries for the same window are dropped so the history only holds distinct windows,
+    /// and the history is kept at `FOCUS_HISTORY_LENGTH` entries.
+    pub fn push_window_history(&mut self, handle: MaybeWindowHandle<H>) {
+        if handle.is_some() {
+            self.window_history.retain(|h| h != &handle);
+        }
+        self.window_history.push_front(handle);
+        self.window_history.truncate(FOCUS_HISTORY_LENGTH);
+    }
+
+    /// Record a window focus change at the front of the history of `tag`, kept at
+    /// `FOCUS_HISTORY_LENGTH` distinct windows.
+    pub fn push_tag_window_history(&mut self, tag: TagId, handle: WindowHandle<H>) {
+        let history = self.tags_window_history.entry(tag).or_default();
+        history.retain(|h| h != &handle);
+        history.push_front(handle);
+        history.truncate(FOCUS_HISTORY_LENGTH);
+    }
+
+    /// Return the handles of the recently focused windows that still exist, most recent first.
+    /// If a tag is given, the history of that tag is used, so focusing windows elsewhere does
+    /// not push its windows out, and only windows still on the tag are returned.
+    pub fn window_history_for_tag(
+        &self,
+        tag: Option<TagId>,
+        windows: &[Window<H>],
+    ) -> Vec<WindowHandle<H>> {
+        let handles: Vec<&WindowHandle<H>> = match tag {
+            Some(tag) => self
+                .tags_window_history
+                .get(&tag)
+                .into_iter()
+                .flatten()
+                .collect(),
+            None => self.window_history.iter().flatten().collect(),
+        };
+        let mut history: Vec<WindowHandle<H>> = vec![];
+        for handle in handles {
+            if history.contains(handle) {
+                continue;
+            }
+            let found = windows
+                .iter()
+                .any(|w| &w.handle == handle && tag.map_or(true, |t| w.has_tag(&t)));
+            if found {
+                history.push(*handle);
+            }
+        }
+        history
+    }
+
     // seems like duplicate code
     pub fn create_follows_cursor(&self) -> bool {
         self.create_follows_cursor
@@ -3,11 +3,25 @@ use leftwm_layouts::layouts::Layouts;
 
 use crate::config::Config;
 use crate::display_servers::DisplayServer;
+use crate::handlers::ProcessLookup;
+use crate::hook;
 use crate::state::State;
 use crate::utils::child_process::Children;
-use std::sync::{atomic::AtomicBool, Arc};
+use crate::utils::i3_ipc;
+use crate::utils::metrics::Metrics;
+use crate::utils::scheduler::Scheduler;
+use crate::DisplayAction;
+use std::sync::{atomic::AtomicBool, Arc, Mutex};
 
-use super::Handle;
+use super::{Handle, Window};
+
+/// The manager the tests run, on mock windows and display server.
+#[cfg(test)]
+pub(crate) type TestManager = Manager<
+    crate::models::window::MockHandle,
+    crate::config::tests::TestConfig,
+    crate::display_servers::MockDisplayServer<crate::models::window::MockHandle>,
+>;
 
 /// Maintains current program state.
 #[derive(Debug)]
@@ -18,6 +32,12 @@ pub struct Manager<H: Handle, C, SERVER> {
     pub(crate) children: Children,
     pub(crate) reap_requested: Arc<AtomicBool>,
     pub(crate) reload_requested: bool,
+    pub(crate) replaced: bool,
+    pub(crate) metrics: Arc<Mutex<Metrics>>,
+    pub(crate) hook_snapshot: Option<hook::Snapshot<H>>,
+    pub(crate) i3_ipc: Option<i3_ipc::Server>,
+    pub(crate) scheduler: Scheduler<H>,
+    pub(crate) process_lookup: ProcessLookup,
     pub display_server: SERVER,
 }
 
@@ -34,6 +54,12 @@ where
             children: Default::default(),
             reap_requested: Default::default(),
             reload_requested: false,
+            replaced: false,
+            metrics: Default::default(),
+            hook_snapshot: None,
+            i3_ipc: None,
+            scheduler: Scheduler::default(),
+            process_lookup: ProcessLookup::default(),
         }
     }
 }
@@ -43,7 +69,8 @@ impl<H: Handle, C, SERVER> Manager<H, C, SERVER> {
         crate::child_process::register_child_hook(self.reap_requested.clone());
     }
 
-    /// Soft reload the worker without saving state.
+    /// Restart the worker. The state is saved beforehand by the callers, for the new worker to
+    /// restore it.
     pub fn hard_reload(&mut self) {
         self.reload_requested = true;
     }
@@ -61,18 +88,54 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
         self.display_server
             .reload_config(&self.config, focused, &self.state.windows);
         self.state.load_theme_config(&self.config);
+        // Borders and title bars may have changed, so all windows are sent again.
+        self.state
+            .windows
+            .iter_mut()
+            .for_each(Window::forget_applied);
+        // The mousebinds may have changed along with the modkey.
+        self.state.actions.push_back(DisplayAction::ReloadKeyGrabs);
         true
     }
+
+    /// Read the config file again and apply it without restarting, returning `false` when the
+    /// config cannot be reloaded in place. That is also the case when it changed what is only
+    /// set up as the worker starts: workspaces, timers, signals or the metrics endpoint.
+    pub fn reload_config(&mut self) -> bool {
+        let started_with = |config: &C| {
+            (
+                config.workspaces(),
+                config.auto_derive_workspaces(),
+                config.timers(),
+                config.signals(),
+                config.metrics_endpoint(),
+            )
+        };
+        let before = started_with(&self.config);
+        if !self.config.reload() {
+            return false;
+        }
+        if started_with(&self.config) != before {
+            tracing::info!("The config changed what needs a restart, reloading it all");
+            return false;
+        }
+        self.state.load_config(&self.config);
+        self.load_theme_config()
+    }
+
+    /// Switch to another modkey without reading the config again, see `Config::set_modkey`.
+    pub fn set_modkey(&mut self, modkey: &str) -> bool {
+        if !self.config.set_modkey(modkey) {
+            tracing::warn!("Unable to use {} as the modkey", modkey);
+            return false;
+        }
+        self.state.mousebinds = self.config.mousebinds();
+        self.load_theme_config()
+    }
 }
 
 #[cfg(test)]
-impl
-    Manager<
-        crate::models::window::MockHandle,
-        crate::config::tests::TestConfig,
-        crate::display_servers::MockDisplayServer<crate::models::window::MockHandle>,
-    >
-{
+impl TestManager {
     pub fn new_test(tags: Vec<String>) -> Self {
         use crate::config::tests::TestConfig;
         let defs = Layouts::default().layouts;
@@ -85,6 +148,18 @@ impl
         })
     }
 
+    /// A test manager with a screen and `count` windows, whose handles go from 1 to `count`.
+    pub fn new_test_with_windows(tags: Vec<String>, count: i32) -> Self {
+        use crate::models::{MockHandle, Screen, WindowHandle};
+        let mut manager = Self::new_test(tags);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=count {
+            let window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        manager
+    }
+
     pub fn new_test_with_border(tags: Vec<String>, border_width: i32) -> Self {
         use crate::config::tests::TestConfig;
         let defs = Layouts::default().layouts;
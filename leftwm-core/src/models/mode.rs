@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
-use crate::models::WindowHandle;
+use crate::models::{TagId, WindowHandle};
+use leftwm_layouts::geometry::Size;
 use serde::{Deserialize, Serialize};
 
 use super::window::Handle;
@@ -23,3 +24,17 @@ impl<H: Handle> Default for Mode<H> {
         Self::Normal
     }
 }
+
+/// A mouse resize of a tiled window, resizing the main column of the layout of its tag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SplitResize {
+    pub(crate) workspace_id: usize,
+    pub(crate) tag: TagId,
+    /// Size of the main column when the resize started.
+    pub(crate) start: Size,
+    /// Width, or height for layouts rotated to the side, of the workspace.
+    pub(crate) length: i32,
+    pub(crate) vertical: bool,
+    /// Whether the resized window is in the main column, which grows as the window grows.
+    pub(crate) in_main: bool,
+}
@@ -13,7 +13,7 @@ pub struct Screen<H: Handle> {
 }
 
 /// Screen Bounding Box
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BBox {
     pub x: i32,
     pub y: i32,
@@ -40,25 +40,31 @@ impl<H: Handle> Screen<H> {
         (bbox.x <= x && x <= max_x) && (bbox.y <= y && y <= max_y)
     }
 
+    /// Whether the strut of `dock_area` reserves space on this screen. `screens_area` is the
+    /// height and width of all the screens together.
     #[must_use]
-    pub const fn contains_dock_area(&self, dock_area: DockArea, screens_area: (i32, i32)) -> bool {
-        if dock_area.top > 0 {
-            return self.contains_point(dock_area.top_start_x, dock_area.top);
-        }
-        if dock_area.bottom > 0 {
-            return self
-                .contains_point(dock_area.bottom_start_x, screens_area.0 - dock_area.bottom);
-        }
-        if dock_area.left > 0 {
-            return self.contains_point(dock_area.left, dock_area.left_start_y);
-        }
-        if dock_area.right > 0 {
-            return self.contains_point(screens_area.1 - dock_area.right, dock_area.right_start_y);
-        }
-        false
+    pub fn contains_dock_area(&self, dock_area: DockArea, screens_area: (i32, i32)) -> bool {
+        dock_area
+            .as_xyhw(screens_area.0, screens_area.1, self)
+            .is_some()
     }
 }
 
+/// Sort screens left to right, then top to bottom, and drop the clones of an earlier screen
+/// showing the same area, so workspaces go to the same monitors whatever order the server
+/// lists them in.
+pub fn sort_screens<H: Handle>(screens: &mut Vec<Screen<H>>) {
+    let mut seen = vec![];
+    screens.retain(|screen| {
+        if seen.contains(&screen.bbox) {
+            return false;
+        }
+        seen.push(screen.bbox);
+        true
+    });
+    screens.sort_by_key(|screen| (screen.bbox.x, screen.bbox.y));
+}
+
 impl BBox {
     pub fn add(&mut self, bbox: BBox) {
         self.x += bbox.x;
@@ -98,3 +104,32 @@ impl<H: Handle> Default for Screen<H> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MockHandle;
+
+    fn screen(output: &str, x: i32, y: i32) -> Screen<MockHandle> {
+        let bbox = BBox {
+            x,
+            y,
+            width: 1920,
+            height: 1080,
+        };
+        Screen::new(bbox, output.to_string())
+    }
+
+    #[test]
+    fn screens_should_be_sorted_and_clones_dropped() {
+        let mut screens = vec![
+            screen("HDMI-1", 1920, 0),
+            screen("DP-1", 0, 1080),
+            screen("eDP-1", 0, 0),
+            screen("HDMI-2", 1920, 0),
+        ];
+        sort_screens(&mut screens);
+        let outputs: Vec<&str> = screens.iter().map(|s| s.output.as_str()).collect();
+        assert_eq!(outputs, vec!["eDP-1", "DP-1", "HDMI-1"]);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Handle, TagId, Xyhw};
+use super::{ContainerId, Handle, TagId, Xyhw};
 use crate::{layouts::LayoutManager, Window, Workspace};
 use serde::{Deserialize, Serialize};
 
@@ -224,10 +224,10 @@ impl Tag {
         windows: &mut [Window<H>],
         workspace: &Workspace,
         layout_manager: &mut LayoutManager,
-    ) {
+    ) -> usize {
         if let Some(window) = windows
             .iter_mut()
-            .find(|w| w.has_tag(&self.id) && w.is_fullscreen())
+            .find(|w| workspace.is_displaying(w) && w.is_fullscreen())
         {
             window.set_visible(true);
             window.normal = workspace.xyhw;
@@ -236,7 +236,7 @@ impl Tag {
             windows
                 .iter_mut()
                 .filter(|w| {
-                    w.has_tag(&self.id)
+                    workspace.is_displaying(w)
                         && (w.transient == Some(handle)
                             || w.states.contains(&super::WindowState::Above) && w.floating())
                         && w.is_managed()
@@ -244,51 +244,98 @@ impl Tag {
                 .for_each(|w| {
                     w.set_visible(true);
                 });
+            0
         } else if let Some(window) = windows
             .iter_mut()
-            .find(|w| w.has_tag(&self.id) && w.is_maximized())
+            .find(|w| workspace.is_displaying(w) && w.is_maximized())
         {
             window.set_visible(true);
             window.normal = workspace.rect().into();
 
             windows
                 .iter_mut()
-                .filter(|w| w.has_tag(&self.id) && w.floating())
+                .filter(|w| workspace.is_displaying(w) && w.floating())
                 .for_each(|w| {
                     w.set_visible(true);
                 });
+            0
         } else {
             // Don't bother updating the other windows when a window is fullscreen.
             // Mark all windows for this workspace as visible.
-            let mut all_mine: Vec<&mut Window<H>> =
-                windows.iter_mut().filter(|w| w.has_tag(&self.id)).collect();
+            let mut all_mine: Vec<&mut Window<H>> = windows
+                .iter_mut()
+                .filter(|w| workspace.is_displaying(w))
+                .collect();
             all_mine.iter_mut().for_each(|w| w.set_visible(true));
 
-            // Update the location / visibility of all non-floating windows.
-            let mut managed_nonfloat: Vec<&mut Window<H>> = windows
+            let hidden = self.tile_windows(windows, workspace, layout_manager);
+
+            // Update the location of all floating windows.
+            windows
                 .iter_mut()
-                .filter(|w| w.has_tag(&self.id) && w.is_managed() && !w.floating())
-                .collect();
-            let def = layout_manager.layout(workspace.id, workspace.tag.unwrap_or(1));
-            let rects = leftwm_layouts::apply(def, managed_nonfloat.len(), &workspace.rect());
-            for (i, window) in managed_nonfloat.iter_mut().enumerate() {
-                match rects.get(i) {
+                .filter(|w| workspace.is_displaying(w) && w.is_managed() && w.floating())
+                .for_each(|w| w.normal = workspace.xyhw);
+            hidden
+        }
+    }
+
+    /// Assign a tile of the workspace to every managed, non-floating window of this tag, and of
+    /// the other tags the workspace shows.
+    ///
+    /// The tiles are computed by the active layout inside `workspace.rect()`, which already
+    /// excludes struts (avoided areas), gutters and the workspace margin. Windows the layout
+    /// has no room for are hidden.
+    ///
+    /// All windows of a tabbed container share a single tile, only the first of them (the
+    /// active tab) is shown.
+    ///
+    /// When the layout limits the windows visible in its stack, the slots past the limit are
+    /// hidden behind the last visible one. Returns how many slots were hidden that way.
+    fn tile_windows<H: Handle>(
+        &self,
+        windows: &mut [Window<H>],
+        workspace: &Workspace,
+        layout_manager: &mut LayoutManager,
+    ) -> usize {
+        let managed_nonfloat: Vec<&mut Window<H>> = windows
+            .iter_mut()
+            .filter(|w| workspace.is_displaying(w) && w.is_managed() && !w.floating())
+            .collect();
+
+        // Group the windows into the slots the layout has to place.
+        let mut slots: Vec<Vec<&mut Window<H>>> = vec![];
+        let mut slot_containers: Vec<Option<ContainerId>> = vec![];
+        for window in managed_nonfloat {
+            let existing = window
+                .container
+                .and_then(|c| slot_containers.iter().position(|s| *s == Some(c)));
+            if let Some(i) = existing {
+                slots[i].push(window);
+            } else {
+                slot_containers.push(window.container);
+                slots.push(vec![window]);
+            }
+        }
+
+        let visible = layout_manager.visible_count(workspace.id, self.id, slots.len());
+        let rects = layout_manager.arrange(workspace.id, self.id, visible, &workspace.rect());
+        for (i, slot) in slots.iter_mut().enumerate() {
+            for (tab, window) in slot.iter_mut().enumerate() {
+                match rects.get(i.min(visible.saturating_sub(1))) {
                     Some(rect) => {
                         window.normal = Xyhw::from(*rect);
                         window.container_size = Some(workspace.xyhw);
+                        if tab > 0 || i >= visible {
+                            window.set_visible(false);
+                        }
                     }
                     None => {
                         window.set_visible(false);
                     }
                 }
             }
-
-            // Update the location of all floating windows.
-            windows
-                .iter_mut()
-                .filter(|w| w.has_tag(&self.id) && w.is_managed() && w.floating())
-                .for_each(|w| w.normal = workspace.xyhw);
         }
+        slots.len() - visible
     }
 }
 
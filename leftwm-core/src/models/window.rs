@@ -5,7 +5,9 @@ use std::fmt::Debug;
 
 use super::WindowState;
 use super::WindowType;
+use crate::models::ContainerId;
 use crate::models::Margins;
+use crate::models::Size;
 use crate::models::TagId;
 use crate::models::Xyhw;
 use crate::models::XyhwBuilder;
@@ -55,6 +57,24 @@ pub struct Window<H: Handle> {
     floating: Option<Xyhw>,
     pub never_focus: bool,
     pub urgent: bool,
+    /// Whether the urgency of the window is not shown for now, see `TogglePresentationMode`.
+    #[serde(skip)]
+    urgency_hidden: bool,
+    /// Whether the client did not answer a ping in time, and is probably hung.
+    #[serde(default)]
+    pub unresponsive: bool,
+    /// Whether the window is part of the selection batch commands act on.
+    #[serde(default)]
+    pub selected: bool,
+    /// The tabbed container the window is grouped in, if any.
+    #[serde(default)]
+    pub container: Option<ContainerId>,
+    /// Whether the client is told it is fullscreen while it stays in its tile.
+    #[serde(default)]
+    pub fake_fullscreen: bool,
+    /// How the window was before it was pinned picture-in-picture, while it is.
+    #[serde(default)]
+    pub pinned_from: Option<PinnedFrom>,
     pub debugging: bool,
     pub name: Option<String>,
     pub legacy_name: Option<String>,
@@ -64,7 +84,26 @@ pub struct Window<H: Handle> {
     pub border: i32,
     pub margin: Margins,
     pub margin_multiplier: f32,
+    /// Space reserved around the window for decorations drawn by an external decorator.
+    #[serde(default = "no_frame")]
+    pub frame: Margins,
+    /// Border colors replacing the configured ones, from the overrides of the tag of the window.
+    #[serde(default)]
+    pub border_colors: BorderColors,
+    /// Border width replacing the configured one, set by a window rule.
+    #[serde(default)]
+    pub rule_border_width: Option<i32>,
+    /// Whether the client asked for no decorations through `_MOTIF_WM_HINTS`. It then has no
+    /// border, unless a window rule sets its border width.
+    #[serde(default)]
+    pub undecorated: bool,
+    /// Border colors replacing the configured ones and those of the tag, set by a window rule.
+    #[serde(default)]
+    pub rule_border_colors: BorderColors,
     pub states: Vec<WindowState>,
+    /// Where the window is placed when it spawns floating, set by a window rule.
+    #[serde(skip)]
+    pub placement: Option<FloatingPlacement>,
     pub requested: Option<Xyhw>,
     pub normal: Xyhw,
     pub start_loc: Option<Xyhw>,
@@ -73,6 +112,89 @@ pub struct Window<H: Handle> {
     // Two strings that are within a XClassHint, kept separate for simpler comparing.
     pub res_name: Option<String>,
     pub res_class: Option<String>,
+    /// What the display server was last sent of the window, so unchanged windows are left out
+    /// of the next update.
+    #[serde(skip)]
+    applied: Option<Applied>,
+}
+
+/// The parts of a window the display server shows it with.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Applied {
+    geometry: (i32, i32, i32, i32),
+    border: i32,
+    visible: bool,
+    fullscreen: bool,
+    floating: bool,
+    selected: bool,
+    urgent: bool,
+    border_colors: BorderColors,
+}
+
+/// The floating state of a window before it was pinned picture-in-picture.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PinnedFrom {
+    pub floating: bool,
+    pub offsets: Option<Xyhw>,
+    pub sticky: bool,
+    pub above: bool,
+}
+
+/// Border colors of a window, each replacing the configured one when set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct BorderColors {
+    pub default: Option<String>,
+    pub floating: Option<String>,
+    pub focused: Option<String>,
+    pub selected: Option<String>,
+}
+
+impl BorderColors {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.default.is_none()
+            && self.floating.is_none()
+            && self.focused.is_none()
+            && self.selected.is_none()
+    }
+
+    /// The colors set here, each falling back to the one of `fallback`.
+    #[must_use]
+    pub fn or(&self, fallback: &Self) -> Self {
+        Self {
+            default: self.default.clone().or_else(|| fallback.default.clone()),
+            floating: self.floating.clone().or_else(|| fallback.floating.clone()),
+            focused: self.focused.clone().or_else(|| fallback.focused.clone()),
+            selected: self.selected.clone().or_else(|| fallback.selected.clone()),
+        }
+    }
+}
+
+/// The position and size of a floating window, in pixels or as a ratio of the workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct FloatingPlacement {
+    /// `(x, y)` from the top left corner of the workspace.
+    pub position: Option<(Size, Size)>,
+    /// `(width, height)`
+    pub size: Option<(Size, Size)>,
+}
+
+impl FloatingPlacement {
+    /// Place `xyhw` within `area`. Without a position the window is centered.
+    pub fn apply(&self, xyhw: &mut Xyhw, area: &Xyhw) {
+        if let Some((w, h)) = self.size {
+            xyhw.set_w(w.into_absolute(area.w()));
+            xyhw.set_h(h.into_absolute(area.h()));
+        }
+        let (x, y) = self.position.map_or_else(
+            || ((area.w() - xyhw.w()) / 2, (area.h() - xyhw.h()) / 2),
+            |(x, y)| (x.into_absolute(area.w()), y.into_absolute(area.h())),
+        );
+        xyhw.set_x(area.x() + x);
+        xyhw.set_y(area.y() + y);
+    }
 }
 
 impl<H: Handle> Window<H> {
@@ -88,6 +210,12 @@ impl<H: Handle> Window<H> {
             debugging: false,
             never_focus: false,
             urgent: false,
+            urgency_hidden: false,
+            unresponsive: false,
+            selected: false,
+            container: None,
+            fake_fullscreen: false,
+            pinned_from: None,
             name,
             pid,
             legacy_name: None,
@@ -96,7 +224,13 @@ impl<H: Handle> Window<H> {
             border: 1,
             margin: Margins::new(10),
             margin_multiplier: 1.0,
+            frame: Margins::new(0),
+            border_colors: BorderColors::default(),
+            rule_border_width: None,
+            undecorated: false,
+            rule_border_colors: BorderColors::default(),
             states: vec![],
+            placement: None,
             normal: XyhwBuilder::default().into(),
             requested: None,
             floating: None,
@@ -105,19 +239,56 @@ impl<H: Handle> Window<H> {
             strut: None,
             res_name: None,
             res_class: None,
+            applied: None,
         }
     }
 
+    /// Records that the display server is sent the window as it is now, returning whether this
+    /// differs from what it was sent last.
+    pub(crate) fn mark_applied(&mut self) -> bool {
+        let applied = Some(Applied {
+            geometry: (self.x(), self.y(), self.width(), self.height()),
+            border: self.border(),
+            visible: self.visible(),
+            fullscreen: self.is_fullscreen(),
+            floating: self.floating(),
+            selected: self.selected,
+            urgent: self.shows_urgency(),
+            border_colors: self.border_colors.clone(),
+        });
+        if self.applied == applied {
+            return false;
+        }
+        self.applied = applied;
+        true
+    }
+
+    /// Forgets what the display server was sent of the window, so the next update sends it.
+    pub(crate) fn forget_applied(&mut self) {
+        self.applied = None;
+    }
+
     pub fn set_visible(&mut self, value: bool) {
         self.visible = value;
     }
 
+    pub(crate) fn set_urgency_hidden(&mut self, value: bool) {
+        self.urgency_hidden = value;
+    }
+
+    /// Whether the window is shown as urgent. It can be urgent without, in presentation mode.
+    #[must_use]
+    pub const fn shows_urgency(&self) -> bool {
+        self.urgent && !self.urgency_hidden
+    }
+
     #[must_use]
     pub fn visible(&self) -> bool {
         self.visible
             || self.r#type == WindowType::Menu
             || self.r#type == WindowType::Splash
             || self.r#type == WindowType::Toolbar
+            || self.is_popup()
     }
 
     pub fn set_floating(&mut self, value: bool) {
@@ -159,7 +330,7 @@ impl<H: Handle> Window<H> {
 
     #[must_use]
     pub fn is_fullscreen(&self) -> bool {
-        self.states.contains(&WindowState::Fullscreen)
+        self.states.contains(&WindowState::Fullscreen) && !self.fake_fullscreen
     }
 
     #[must_use]
@@ -227,16 +398,22 @@ impl<H: Handle> Window<H> {
         } else {
             value = self.normal.w()
                 - (((self.margin.left + self.margin.right) as f32) * self.margin_multiplier) as i32
+                - (self.frame.left + self.frame.right) as i32
                 - (self.border * 2);
         }
-        let limit = match self.requested {
+        if value < self.min_width() && self.is_managed() {
+            value = self.min_width();
+        }
+        value
+    }
+
+    /// The width a managed window is never made smaller than, its own minimum when floating.
+    #[must_use]
+    pub fn min_width(&self) -> i32 {
+        match self.requested {
             Some(requested) if requested.minw() > 0 && self.floating() => requested.minw(),
             _ => 100,
-        };
-        if value < limit && self.is_managed() {
-            value = limit;
         }
-        value
     }
 
     #[must_use]
@@ -250,16 +427,22 @@ impl<H: Handle> Window<H> {
         } else {
             value = self.normal.h()
                 - (((self.margin.top + self.margin.bottom) as f32) * self.margin_multiplier) as i32
+                - (self.frame.top + self.frame.bottom) as i32
                 - (self.border * 2);
         }
-        let limit = match self.requested {
+        if value < self.min_height() && self.is_managed() {
+            value = self.min_height();
+        }
+        value
+    }
+
+    /// The height a managed window is never made smaller than, its own minimum when floating.
+    #[must_use]
+    pub fn min_height(&self) -> i32 {
+        match self.requested {
             Some(requested) if requested.minh() > 0 && self.floating() => requested.minh(),
             _ => 100,
-        };
-        if value < limit && self.is_managed() {
-            value = limit;
         }
-        value
     }
 
     pub fn set_x(&mut self, x: i32) {
@@ -286,7 +469,9 @@ impl<H: Handle> Window<H> {
             let relative = self.normal + self.floating.unwrap_or_default();
             relative.x()
         } else {
-            self.normal.x() + (self.margin.left as f32 * self.margin_multiplier) as i32
+            self.normal.x()
+                + (self.margin.left as f32 * self.margin_multiplier) as i32
+                + self.frame.left as i32
         }
     }
 
@@ -298,7 +483,9 @@ impl<H: Handle> Window<H> {
             let relative = self.normal + self.floating.unwrap_or_default();
             relative.y()
         } else {
-            self.normal.y() + (self.margin.top as f32 * self.margin_multiplier) as i32
+            self.normal.y()
+                + (self.margin.top as f32 * self.margin_multiplier) as i32
+                + self.frame.top as i32
         }
     }
 
@@ -343,7 +530,14 @@ impl<H: Handle> Window<H> {
 
     #[must_use]
     pub fn is_managed(&self) -> bool {
-        self.r#type != WindowType::Desktop && self.r#type != WindowType::Dock
+        self.r#type != WindowType::Desktop && self.r#type != WindowType::Dock && !self.is_popup()
+    }
+
+    /// Notifications, popup and dropdown menus and tooltips, which stay where the client put them
+    /// above all other windows and never take the focus.
+    #[must_use]
+    pub fn is_popup(&self) -> bool {
+        self.r#type.is_popup()
     }
 
     #[must_use]
@@ -375,6 +569,10 @@ impl<H: Handle> Window<H> {
     }
 }
 
+const fn no_frame() -> Margins {
+    Margins::new(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,4 +591,40 @@ mod tests {
         subject.untag();
         assert!(!subject.has_tag(&1), "was unable to untag the window");
     }
+
+    #[test]
+    fn tiled_windows_should_leave_room_for_their_frame() {
+        let mut subject = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        subject.normal = XyhwBuilder {
+            x: 0,
+            y: 0,
+            w: 800,
+            h: 600,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        subject.margin = Margins::new(0);
+        subject.border = 0;
+        subject.frame = Margins::new_from_triple(20, 2, 4);
+        assert_eq!(
+            (subject.x(), subject.y(), subject.width(), subject.height()),
+            (2, 20, 796, 576)
+        );
+    }
+
+    #[test]
+    fn only_changed_windows_should_be_sent_again() {
+        let mut subject = Window::new(WindowHandle::<MockHandle>(1), None, None);
+        subject.set_visible(true);
+        assert!(subject.mark_applied(), "a new window was not sent");
+        assert!(!subject.mark_applied(), "an unchanged window was sent");
+
+        subject.normal.set_x(100);
+        assert!(subject.mark_applied(), "a moved window was not sent");
+        subject.set_visible(false);
+        assert!(subject.mark_applied(), "a hidden window was not sent");
+
+        subject.forget_applied();
+        assert!(subject.mark_applied(), "a forgotten window was not sent");
+    }
 }
@@ -21,6 +21,7 @@ pub struct WindowChange<H: Handle> {
     pub strut: Option<XyhwChange>,
     pub requested: Option<Xyhw>,
     pub states: Option<Vec<WindowState>>,
+    pub undecorated: Option<bool>,
 }
 
 impl<H: Handle> WindowChange<H> {
@@ -37,9 +38,29 @@ impl<H: Handle> WindowChange<H> {
             strut: None,
             requested: None,
             states: None,
+            undecorated: None,
         }
     }
 
+    /// Fold a later change to the same window into this one, so both can be applied at once.
+    pub fn merge(&mut self, newer: Self) {
+        let merge_xyhw = |older: Option<XyhwChange>, newer: Option<XyhwChange>| match (older, newer)
+        {
+            (Some(older), Some(newer)) => Some(older.merge(newer)),
+            (older, newer) => newer.or(older),
+        };
+        self.transient = newer.transient.or(self.transient);
+        self.never_focus = newer.never_focus.or(self.never_focus);
+        self.urgent = newer.urgent.or(self.urgent);
+        self.name = newer.name.or(self.name.take());
+        self.r#type = newer.r#type.or(self.r#type.take());
+        self.floating = merge_xyhw(self.floating, newer.floating);
+        self.strut = merge_xyhw(self.strut, newer.strut);
+        self.requested = newer.requested.or(self.requested);
+        self.states = newer.states.or(self.states.take());
+        self.undecorated = newer.undecorated.or(self.undecorated);
+    }
+
     pub fn update(self, window: &mut Window<H>, container: Option<Xyhw>) -> bool {
         let mut changed = false;
         if let Some(trans) = &self.transient {
@@ -88,12 +109,17 @@ impl<H: Handle> WindowChange<H> {
             if !window.is_managed() {
                 window.border = 0;
                 window.margin = Margins::new(0);
+                window.frame = Margins::new(0);
             }
         }
         if let Some(states) = self.states {
             changed = true;
             window.states = states;
         }
+        if let Some(undecorated) = self.undecorated {
+            changed = changed || window.undecorated != undecorated;
+            window.undecorated = undecorated;
+        }
         changed
     }
 }
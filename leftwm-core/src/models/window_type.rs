@@ -9,5 +9,21 @@ pub enum WindowType {
     Utility,
     Splash,
     Dialog,
+    Notification,
+    PopupMenu,
+    DropdownMenu,
+    Tooltip,
     Normal,
 }
+
+impl WindowType {
+    /// Notifications, popup and dropdown menus and tooltips, which are left undecorated where
+    /// the client put them.
+    #[must_use]
+    pub const fn is_popup(&self) -> bool {
+        matches!(
+            self,
+            Self::Notification | Self::PopupMenu | Self::DropdownMenu | Self::Tooltip
+        )
+    }
+}
@@ -10,7 +10,13 @@ use super::{Handle, WorkspaceId};
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Workspace {
     // tag represents the currently visible tag
-    pub tag: Option<TagId>, // TODO: Make this a list.
+    pub tag: Option<TagId>,
+    /// Tags shown along with `tag`, their windows arranged by its layout, see `ToggleTag`.
+    #[serde(default)]
+    pub extra_tags: Vec<TagId>,
+    /// Tags shown on this workspace before `tag`, the last one first.
+    #[serde(default)]
+    pub tag_history: Vec<TagId>,
     pub margin: Margins,
     pub margin_multiplier: f32,
     pub gutters: Vec<Gutter>,
@@ -20,6 +26,10 @@ pub struct Workspace {
     pub xyhw_avoided: Xyhw,
     /// ID of workspace. Starts with 1.
     pub id: WorkspaceId,
+    /// Number of windows of the shown tag hidden behind the last tile of the stack, when the
+    /// layout limits the windows visible in it.
+    #[serde(default)]
+    pub hidden_in_stack: usize,
 }
 
 impl fmt::Debug for Workspace {
@@ -46,6 +56,8 @@ impl Workspace {
     pub fn new(bbox: BBox, id: usize) -> Self {
         Self {
             tag: None,
+            extra_tags: vec![],
+            tag_history: vec![],
             margin: Margins::new(10),
             margin_multiplier: 1.0,
             gutters: vec![],
@@ -67,6 +79,7 @@ impl Workspace {
             }
             .into(),
             id,
+            hidden_in_stack: 0,
         }
     }
 
@@ -93,10 +106,25 @@ impl Workspace {
             })
     }
 
+    /// Show `tag`, remembering the tag shown until now in `tag_history`.
     pub fn show_tag(&mut self, tag: &TagId) {
+        self.tag_history.retain(|t| t != tag);
+        if let Some(old_tag) = self.tag.filter(|t| t != tag) {
+            self.tag_history.retain(|&t| t != old_tag);
+            self.tag_history.insert(0, old_tag);
+        }
         self.tag = Some(*tag);
     }
 
+    /// The tag shown on this workspace before the current one.
+    #[must_use]
+    pub fn last_tag(&self) -> Option<TagId> {
+        self.tag_history
+            .iter()
+            .copied()
+            .find(|&t| Some(t) != self.tag)
+    }
+
     #[must_use]
     pub const fn contains_point(&self, x: i32, y: i32) -> bool {
         self.xyhw.contains_point(x, y)
@@ -104,7 +132,7 @@ impl Workspace {
 
     #[must_use]
     pub fn has_tag(&self, tag: &TagId) -> bool {
-        self.tag == Some(*tag)
+        self.tag == Some(*tag) || self.extra_tags.contains(tag)
     }
 
     /// Returns true if the workspace is displays a given window.
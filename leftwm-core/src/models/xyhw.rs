@@ -17,6 +17,11 @@ pub struct Xyhw {
     maxw: i32,
     minh: i32,
     maxh: i32,
+    /// Smallest and largest ratios of width to height, as `(width, height)`.
+    #[serde(default)]
+    min_aspect: Option<(i32, i32)>,
+    #[serde(default)]
+    max_aspect: Option<(i32, i32)>,
 }
 
 /// Modifiable struct that can be used to generate an Xyhw struct. Contains min/max width and
@@ -59,6 +64,8 @@ impl Default for Xyhw {
             maxw: 999_999_999,
             minh: -999_999_999,
             maxh: 999_999_999,
+            min_aspect: None,
+            max_aspect: None,
         }
     }
 }
@@ -75,6 +82,8 @@ impl Add for Xyhw {
             maxw: cmp::min(self.maxw, other.maxw),
             minh: cmp::max(self.minh, other.minh),
             maxh: cmp::min(self.maxh, other.maxh),
+            min_aspect: self.min_aspect.or(other.min_aspect),
+            max_aspect: self.max_aspect.or(other.max_aspect),
         }
     }
 }
@@ -91,6 +100,8 @@ impl Sub for Xyhw {
             maxw: cmp::min(self.maxw, other.maxw),
             minh: cmp::max(self.minh, other.minh),
             maxh: cmp::min(self.maxh, other.maxh),
+            min_aspect: self.min_aspect.or(other.min_aspect),
+            max_aspect: self.max_aspect.or(other.max_aspect),
         }
     }
 }
@@ -106,6 +117,8 @@ impl From<XyhwBuilder> for Xyhw {
             maxw: xywh.maxw,
             minh: xywh.minh,
             maxh: xywh.maxh,
+            min_aspect: None,
+            max_aspect: None,
         };
         b.update_limits();
         b
@@ -170,6 +183,15 @@ impl Xyhw {
         self.maxh
     }
 
+    #[must_use]
+    pub const fn min_aspect(&self) -> Option<(i32, i32)> {
+        self.min_aspect
+    }
+    #[must_use]
+    pub const fn max_aspect(&self) -> Option<(i32, i32)> {
+        self.max_aspect
+    }
+
     pub fn clear_minmax(&mut self) {
         self.minw = -999_999_999;
         self.maxw = 999_999_999;
@@ -212,6 +234,37 @@ impl Xyhw {
         self.update_limits();
     }
 
+    pub fn set_min_aspect(&mut self, value: Option<(i32, i32)>) {
+        self.min_aspect = value;
+    }
+    pub fn set_max_aspect(&mut self, value: Option<(i32, i32)>) {
+        self.max_aspect = value;
+    }
+
+    /// Shrink a `w` by `h` size to the aspect ratios of this one, as a window resized to it
+    /// would by the rules of ICCCM 4.1.2.3: a size too wide is narrowed, one too tall is
+    /// shortened.
+    #[must_use]
+    pub fn keep_aspect(&self, w: i32, h: i32) -> (i32, i32) {
+        if w <= 0 || h <= 0 {
+            return (w, h);
+        }
+        let (width, height) = (i64::from(w), i64::from(h));
+        if let Some((aw, ah)) = self.max_aspect {
+            let (aw, ah) = (i64::from(aw), i64::from(ah));
+            if width * ah > height * aw {
+                return (i32::try_from(height * aw / ah).unwrap_or(w), h);
+            }
+        }
+        if let Some((aw, ah)) = self.min_aspect {
+            let (aw, ah) = (i64::from(aw), i64::from(ah));
+            if width * ah < height * aw {
+                return (w, i32::try_from(width * ah / aw).unwrap_or(h));
+            }
+        }
+        (w, h)
+    }
+
     fn update_limits(&mut self) {
         if self.h > self.maxh {
             self.h = self.maxh;
@@ -305,6 +358,13 @@ impl Xyhw {
         self.y = outer.y() + outer.h() / 2 - self.h / 2 - border;
     }
 
+    /// Move the rectangle the least needed to be within `outer`, or to its top left corner when
+    /// it is larger.
+    pub fn clamp_within(&mut self, outer: Self) {
+        self.x = self.x.min(outer.x + outer.w - self.w).max(outer.x);
+        self.y = self.y.min(outer.y + outer.h - self.h).max(outer.y);
+    }
+
     pub const fn center(&self) -> (i32, i32) {
         let x = self.x + (self.w / 2);
         let y = self.y + (self.h / 2);
@@ -503,4 +563,40 @@ mod tests {
         };
         assert!(!a.contains_xyhw(&b));
     }
+
+    #[test]
+    fn clamp_within_should_move_the_least_needed() {
+        let outer = Xyhw {
+            x: 0,
+            y: 0,
+            w: 1000,
+            h: 800,
+            ..Xyhw::default()
+        };
+        let mut xyhw = Xyhw {
+            x: 900,
+            y: -50,
+            w: 200,
+            h: 100,
+            ..Xyhw::default()
+        };
+        xyhw.clamp_within(outer);
+        assert_eq!((xyhw.x, xyhw.y), (800, 0));
+
+        xyhw.w = 2000;
+        xyhw.clamp_within(outer);
+        assert_eq!(xyhw.x, 0);
+    }
+    #[test]
+    fn keep_aspect_should_narrow_or_shorten_to_the_ratios() {
+        let xyhw = Xyhw {
+            min_aspect: Some((1, 2)),
+            max_aspect: Some((16, 9)),
+            ..Xyhw::default()
+        };
+        assert_eq!(xyhw.keep_aspect(1000, 900), (1000, 900));
+        assert_eq!(xyhw.keep_aspect(3200, 900), (1600, 900));
+        assert_eq!(xyhw.keep_aspect(400, 1000), (400, 800));
+        assert_eq!(Xyhw::default().keep_aspect(3200, 900), (3200, 900));
+    }
 }
@@ -14,6 +14,10 @@ pub struct XyhwChange {
     pub maxw: Option<i32>,
     pub minh: Option<i32>,
     pub maxh: Option<i32>,
+    #[serde(default)]
+    pub min_aspect: Option<(i32, i32)>,
+    #[serde(default)]
+    pub max_aspect: Option<(i32, i32)>,
 }
 
 impl From<Xyhw> for XyhwChange {
@@ -27,11 +31,30 @@ impl From<Xyhw> for XyhwChange {
             maxw: Some(xywh.maxw()),
             minh: Some(xywh.minh()),
             maxh: Some(xywh.maxh()),
+            min_aspect: xywh.min_aspect(),
+            max_aspect: xywh.max_aspect(),
         }
     }
 }
 
 impl XyhwChange {
+    /// Combine with a later change, the values of `newer` win where both are set.
+    #[must_use]
+    pub fn merge(self, newer: Self) -> Self {
+        Self {
+            x: newer.x.or(self.x),
+            y: newer.y.or(self.y),
+            h: newer.h.or(self.h),
+            w: newer.w.or(self.w),
+            minw: newer.minw.or(self.minw),
+            maxw: newer.maxw.or(self.maxw),
+            minh: newer.minh.or(self.minh),
+            maxh: newer.maxh.or(self.maxh),
+            min_aspect: newer.min_aspect.or(self.min_aspect),
+            max_aspect: newer.max_aspect.or(self.max_aspect),
+        }
+    }
+
     pub fn update(&self, xyhw: &mut Xyhw) -> bool {
         let mut changed = false;
         if let Some(x) = self.x {
@@ -82,6 +105,14 @@ impl XyhwChange {
                 changed = true;
             }
         }
+        if self.min_aspect.is_some() && xyhw.min_aspect() != self.min_aspect {
+            xyhw.set_min_aspect(self.min_aspect);
+            changed = true;
+        }
+        if self.max_aspect.is_some() && xyhw.max_aspect() != self.max_aspect {
+            xyhw.set_max_aspect(self.max_aspect);
+            changed = true;
+        }
         changed
     }
 
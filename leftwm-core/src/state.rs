@@ -1,18 +1,24 @@
 //! Save and restore manager state.
 
 use crate::child_process::ChildID;
-use crate::config::{Config, InsertBehavior, ScratchPad};
+use crate::config::{
+    Config, Corner, FloatingPlacementBehavior, InsertBehavior, Mousebind, PictureInPicture,
+    ScratchPad, TagOverflowBehavior, TagOverrides, UrgencyBehavior,
+};
+use crate::handlers::command_handler::{MINIMIZED_TAG, STASHED_TAG};
+use crate::handlers::SWALLOWED_TAG;
 use crate::layouts::LayoutManager;
 use crate::models::{
-    FocusManager, Handle, Mode, ScratchPadName, Screen, Tags, Window, WindowHandle, WindowState,
-    WindowType, Workspace,
+    FloatingMemory, FocusManager, Handle, Mode, ScratchPadName, Screen, SplitResize, TagId, Tags,
+    Window, WindowHandle, WindowState, WindowType, Workspace, Xyhw,
 };
 use crate::DisplayAction;
 use leftwm_layouts::Layout;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 #[derive(Serialize, Deserialize, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct State<H: Handle> {
     #[serde(bound = "")]
     pub screens: Vec<Screen<H>>,
@@ -24,20 +30,99 @@ pub struct State<H: Handle> {
     pub layout_manager: LayoutManager,
     #[serde(bound = "")]
     pub mode: Mode<H>,
+    /// The main column being resized with the mouse, while resizing a tiled window.
+    #[serde(skip)]
+    pub split_resize: Option<SplitResize>,
+    /// The corner following the pointer, while resizing a floating window with the mouse.
+    #[serde(skip)]
+    pub resize_corner: Corner,
+    /// Whether the user has not touched the keyboard or mouse for the idle timeout.
+    #[serde(skip)]
+    pub user_idle: bool,
     pub active_scratchpads: HashMap<ScratchPadName, VecDeque<ChildID>>,
     #[serde(bound = "")]
     pub actions: VecDeque<DisplayAction<H>>,
     pub tags: Tags, // List of all known tags.
-    // entries below are loaded from config and are never changed
+    /// Index each window had among the windows of the tags it was moved away from. Tags keep
+    /// no order of their own: their stack is the order of their windows in `windows`.
+    #[serde(bound = "", default)]
+    pub tag_stack_positions: HashMap<TagId, Vec<(WindowHandle<H>, usize)>>,
+    /// Minimized windows and the tag they were minimized from, most recent last.
+    #[serde(bound = "", default)]
+    pub minimized: Vec<(WindowHandle<H>, TagId)>,
+    /// Stashed windows and the tag they were stashed from.
+    #[serde(bound = "", default)]
+    pub stashed: Vec<(WindowHandle<H>, TagId)>,
+    /// Windows launched from a terminal and the terminal they hide, see `swallow`.
+    #[serde(bound = "", default)]
+    pub swallowed: Vec<(WindowHandle<H>, WindowHandle<H>)>,
+    /// Windows in the order they became urgent, most recent last, see `FocusNextUrgent`.
+    #[serde(skip)]
+    pub urgent_order: Vec<WindowHandle<H>>,
+    /// Errors the display server ran into, by category. Refreshed before each state update
+    /// written to the state socket.
+    #[serde(skip)]
+    pub display_server_errors: BTreeMap<String, usize>,
+    /// The last floating geometry of windows by `WM_CLASS`, when remembered. Kept in a file of
+    /// its own by the config.
+    #[serde(skip)]
+    pub floating_memory: Option<FloatingMemory>,
+    // entries below are loaded from config and only change when it is reloaded
     pub scratchpads: Vec<ScratchPad>,
     pub layout_definitions: Vec<Layout>,
-    pub mousekey: Vec<String>,
+    #[serde(default)]
+    pub mousebinds: Vec<Mousebind>,
+    #[serde(default)]
+    pub stash_classes: Vec<String>,
     pub default_width: i32,
     pub default_height: i32,
+    /// The border width of windows, and that of urgent ones when it differs.
+    #[serde(skip)]
+    pub border_width: i32,
+    #[serde(skip)]
+    pub urgent_border_width: Option<i32>,
+    /// The overrides of each tag by label, resolved as the config is loaded.
+    #[serde(skip)]
+    pub tag_overrides: HashMap<String, TagOverrides>,
+    /// Whether windows leave room for an external decorator, which window frames are served to.
+    #[serde(skip)]
+    pub decorated: bool,
     pub disable_tile_drag: bool,
     pub reposition_cursor_on_resize: bool,
     pub insert_behavior: InsertBehavior,
+    #[serde(default)]
+    pub max_windows_per_tag: Option<usize>,
+    #[serde(default)]
+    pub tag_overflow_behavior: TagOverflowBehavior,
+    #[serde(default)]
+    pub floating_placement_behavior: FloatingPlacementBehavior,
+    #[serde(default)]
+    pub picture_in_picture: PictureInPicture,
+    pub auto_float_max_width: Option<i32>,
+    pub auto_float_max_height: Option<i32>,
+    #[serde(default)]
+    pub resize_tiled_splits: bool,
     pub single_window_border: bool,
+    #[serde(default)]
+    pub urgency_behavior: UrgencyBehavior,
+    /// Borders, gaps and the urgency of shown windows are left out, see `TogglePresentationMode`.
+    #[serde(default)]
+    pub presentation_mode: bool,
+    #[serde(default)]
+    pub presentation_mode_hides_docks: bool,
+    /// Pixels floating windows are moved or resized by with `MoveFloatingWindow` and the
+    /// `ResizeWindow*` commands.
+    #[serde(default)]
+    pub floating_step: i32,
+    /// Gaps are left out when a workspace shows a single tiled window.
+    #[serde(default)]
+    pub smart_gaps: bool,
+    /// Windows launched from a terminal hide it until they close.
+    #[serde(default)]
+    pub swallow: bool,
+    /// Gaps are left out, see `ToggleGaps`.
+    #[serde(default)]
+    pub gaps_hidden: bool,
 }
 
 impl<H: Handle> State<H> {
@@ -47,6 +132,10 @@ impl<H: Handle> State<H> {
             tags.add_new(label.as_str());
         });
         tags.add_new_hidden("NSP");
+        tags.add_new_hidden(MINIMIZED_TAG);
+        tags.add_new_hidden(STASHED_TAG);
+        tags.add_new_hidden(SWALLOWED_TAG);
+        let tag_overrides = resolve_tag_overrides(&tags, config);
 
         Self {
             focus_manager: FocusManager::new(config),
@@ -55,24 +144,88 @@ impl<H: Handle> State<H> {
             windows: Default::default(),
             workspaces: Default::default(),
             mode: Default::default(),
+            split_resize: None,
+            resize_corner: Corner::default(),
+            user_idle: false,
             active_scratchpads: Default::default(),
             actions: Default::default(),
             tags,
+            tag_stack_positions: Default::default(),
+            minimized: Default::default(),
+            stashed: Default::default(),
+            swallowed: Default::default(),
+            urgent_order: Default::default(),
+            display_server_errors: Default::default(),
+            floating_memory: config.load_floating_memory(),
             scratchpads: config.create_list_of_scratchpads(),
             layout_definitions: config.layout_definitions(),
-            mousekey: config.mousekey(),
+            mousebinds: config.mousebinds(),
+            stash_classes: config.stash_classes(),
             default_width: config.default_width(),
             default_height: config.default_height(),
+            border_width: config.border_width(),
+            urgent_border_width: config.urgent_border_width(),
+            tag_overrides,
+            decorated: config.decoration_frame().is_some(),
             disable_tile_drag: config.disable_tile_drag(),
             reposition_cursor_on_resize: config.reposition_cursor_on_resize(),
             insert_behavior: config.insert_behavior(),
+            max_windows_per_tag: config.max_windows_per_tag(),
+            tag_overflow_behavior: config.tag_overflow_behavior(),
+            floating_placement_behavior: config.floating_placement_behavior(),
+            picture_in_picture: config.picture_in_picture(),
+            auto_float_max_width: config.auto_float_max_width(),
+            auto_float_max_height: config.auto_float_max_height(),
+            resize_tiled_splits: config.resize_tiled_splits(),
             single_window_border: config.single_window_border(),
+            urgency_behavior: config.urgency_behavior(),
+            presentation_mode: false,
+            presentation_mode_hides_docks: config.presentation_mode_hides_docks(),
+            floating_step: config.floating_step(),
+            smart_gaps: config.smart_gaps(),
+            swallow: config.swallow(),
+            gaps_hidden: false,
+        }
+    }
+
+    /// Remember the floating geometry of a window, on the workspace showing it.
+    pub(crate) fn remember_floating(&mut self, handle: &WindowHandle<H>) {
+        let Some(memory) = &mut self.floating_memory else {
+            return;
+        };
+        let Some(window) = self.windows.iter().find(|w| &w.handle == handle) else {
+            return;
+        };
+        if !window.floating() || !window.is_managed() {
+            return;
+        }
+        if let Some(ws) = self.workspaces.iter().find(|ws| ws.tag == window.tag) {
+            memory.remember(window, ws);
         }
     }
 
+    /// The remembered floating geometry of `window`, placed on the workspace showing its tag, or
+    /// else `ws`.
+    pub(crate) fn remembered_floating(&self, window: &Window<H>, ws: &Workspace) -> Option<Xyhw> {
+        let ws = self
+            .workspaces
+            .iter()
+            .find(|ws| ws.tag == window.tag)
+            .unwrap_or(ws);
+        self.floating_memory.as_ref()?.recall(window, ws)
+    }
+
     /// Sorts the windows and puts them in order of importance.
     pub fn sort_windows(&mut self) {
-        let mut sorter = WindowSorter::new(self.windows.iter().collect());
+        // Windows asking to be kept below the others, which only stay above desktop windows.
+        let (below, others) = self
+            .windows
+            .iter()
+            .partition(|w| w.states.contains(&WindowState::Below) && w.is_managed());
+        let mut sorter = WindowSorter::new(others);
+
+        // Notifications, popups and tooltips go above everything.
+        sorter.sort(Window::is_popup);
 
         // Windows explicitly marked as on top
         sorter.sort(|w| w.states.contains(&WindowState::Above) && w.floating());
@@ -108,6 +261,7 @@ impl<H: Handle> State<H> {
 
         // Last docks.
         sorter.sort(|w| w.r#type == WindowType::Dock);
+        sorter.push(below);
 
         // Finish and put all unsorted at the end.
         let windows = sorter.finish();
@@ -118,43 +272,103 @@ impl<H: Handle> State<H> {
         self.actions.push_back(act);
     }
 
-    /// Removes border if there is a single visible window.
-    /// Only will run if `single_window_border` is set to `false` in the configuration file.
-    pub fn handle_single_border(&mut self, border_width: i32) {
-        if self.single_window_border {
+    /// Remember the position of a window in the stack of the tag it is about to leave.
+    pub fn remember_tag_position(&mut self, handle: &WindowHandle<H>, tag: TagId) {
+        let Some(position) = self
+            .windows
+            .iter()
+            .filter(|w| w.has_tag(&tag))
+            .position(|w| &w.handle == handle)
+        else {
             return;
-        }
+        };
+        let positions = self.tag_stack_positions.entry(tag).or_default();
+        positions.retain(|(h, _)| h != handle);
+        positions.push((*handle, position));
+    }
+
+    /// Move a window back to the position it had in the stack of `tag` when it was moved away.
+    /// Returns `None` if the window never left that tag.
+    pub fn restore_tag_position(&mut self, handle: &WindowHandle<H>, tag: TagId) -> Option<()> {
+        let positions = self.tag_stack_positions.get_mut(&tag)?;
+        let index = positions.iter().position(|(h, _)| h == handle)?;
+        let (_, position) = positions.remove(index);
+
+        let current = self.windows.iter().position(|w| &w.handle == handle)?;
+        let window = self.windows.remove(current);
+        let on_tag: Vec<usize> = self
+            .windows
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.has_tag(&tag))
+            .map(|(i, _)| i)
+            .collect();
+        let target = match (on_tag.get(position), on_tag.last()) {
+            (Some(&i), _) => i,
+            (None, Some(&last)) => last + 1,
+            (None, None) => current,
+        };
+        self.windows.insert(target, window);
+        Some(())
+    }
 
+    /// Sets the border width of the windows of every tag, as overridden for the tag.
+    /// Removes border if there is a single visible window, when `single_window_border` is set to
+    /// `false` in the configuration file, and from windows asking for no decorations.
+    pub fn handle_single_border(&mut self) {
+        if self.presentation_mode {
+            self.windows
+                .iter_mut()
+                .filter(|w| w.is_managed())
+                .for_each(|w| w.border = 0);
+            return;
+        }
         for tag in self.tags.normal() {
-            let mut windows_on_tag: Vec<&mut Window<H>> = self
+            let border_width = self
+                .tag_overrides
+                .get(&tag.label)
+                .and_then(|overrides| overrides.border_width)
+                .unwrap_or(self.border_width);
+            let (mut windows_on_tag, others): (Vec<&mut Window<H>>, Vec<_>) = self
                 .windows
                 .iter_mut()
-                .filter(|w| w.tag.unwrap_or(0) == tag.id && w.r#type == WindowType::Normal)
-                .collect();
-
-            let wsid = self
-                .workspaces
-                .iter()
-                .find(|ws| ws.has_tag(&tag.id))
-                .map(|w| w.id);
-            let layout = self.layout_manager.layout(wsid.unwrap_or(1), tag.id);
-
-            // TODO: hardcoded layout name.
-            if layout.is_monocle() {
-                windows_on_tag.iter_mut().for_each(|w| w.border = 0);
-                continue;
+                .filter(|w| w.tag.unwrap_or(0) == tag.id && w.is_managed())
+                .partition(|w| w.r#type == WindowType::Normal);
+            // Only normal windows get a border from the theme, the others only from their rule.
+            for w in others {
+                w.border = w.rule_border_width.unwrap_or(0);
             }
 
-            if windows_on_tag.len() == 1 {
-                if let Some(w) = windows_on_tag.first_mut() {
-                    w.border = 0;
+            if !self.single_window_border {
+                let wsid = self
+                    .workspaces
+                    .iter()
+                    .find(|ws| ws.has_tag(&tag.id))
+                    .map(|w| w.id);
+                let layout = self.layout_manager.layout(wsid.unwrap_or(1), tag.id);
+
+                // TODO: hardcoded layout name.
+                if layout.is_monocle() {
+                    windows_on_tag.iter_mut().for_each(|w| w.border = 0);
+                    continue;
+                }
+
+                if windows_on_tag.len() == 1 {
+                    if let Some(w) = windows_on_tag.first_mut() {
+                        w.border = 0;
+                    }
+                    continue;
                 }
-                continue;
             }
 
-            windows_on_tag
-                .iter_mut()
-                .for_each(|w| w.border = border_width);
+            for w in windows_on_tag {
+                let border_width = match self.urgent_border_width {
+                    _ if w.undecorated => 0,
+                    Some(urgent_border_width) if w.urgent => urgent_border_width,
+                    _ => border_width,
+                };
+                w.border = w.rule_border_width.unwrap_or(border_width);
+            }
         }
     }
 
@@ -192,6 +406,66 @@ impl<H: Handle> State<H> {
         }
         self.default_height = config.default_height();
         self.default_width = config.default_width();
+        self.border_width = config.border_width();
+        self.urgent_border_width = config.urgent_border_width();
+        self.tag_overrides = resolve_tag_overrides(&self.tags, config);
+        self.decorated = config.decoration_frame().is_some();
+        self.handle_single_border();
+    }
+
+    /// Apply a reloaded config to the running state, keeping the windows, workspaces and focus.
+    ///
+    /// Tags are relabeled in place, and tags the config no longer lists are kept for the windows
+    /// that may be on them.
+    pub(crate) fn load_config(&mut self, config: &impl Config) {
+        let fresh = Self::new(config);
+
+        for (i, label) in config.create_list_of_tag_labels().iter().enumerate() {
+            match self.tags.get_mut(i + 1) {
+                Some(tag) => tag.label.clone_from(label),
+                None => {
+                    self.tags.add_new(label);
+                }
+            }
+        }
+        let labels = self.tags.normal().iter().map(|t| t.label.clone());
+        let act = DisplayAction::SetDesktopNames(labels.collect());
+        self.actions.push_back(act);
+
+        let focus = &mut self.focus_manager;
+        focus.behaviour = fresh.focus_manager.behaviour;
+        focus.focus_new_windows = fresh.focus_manager.focus_new_windows;
+        focus.sloppy_mouse_follows_focus = fresh.focus_manager.sloppy_mouse_follows_focus;
+        focus.create_follows_cursor = fresh.focus_manager.create_follows_cursor;
+        focus.focus_follows_workspace = fresh.focus_manager.focus_follows_workspace;
+
+        let old_layouts = std::mem::replace(&mut self.layout_manager, fresh.layout_manager);
+        self.layout_manager.restore(&old_layouts);
+
+        self.scratchpads = fresh.scratchpads;
+        self.layout_definitions = fresh.layout_definitions;
+        self.mousebinds = fresh.mousebinds;
+        self.stash_classes = fresh.stash_classes;
+        self.disable_tile_drag = fresh.disable_tile_drag;
+        self.reposition_cursor_on_resize = fresh.reposition_cursor_on_resize;
+        self.insert_behavior = fresh.insert_behavior;
+        self.max_windows_per_tag = fresh.max_windows_per_tag;
+        self.tag_overflow_behavior = fresh.tag_overflow_behavior;
+        self.floating_placement_behavior = fresh.floating_placement_behavior;
+        self.picture_in_picture = fresh.picture_in_picture;
+        self.auto_float_max_width = fresh.auto_float_max_width;
+        self.auto_float_max_height = fresh.auto_float_max_height;
+        self.resize_tiled_splits = fresh.resize_tiled_splits;
+        self.single_window_border = fresh.single_window_border;
+        self.urgency_behavior = fresh.urgency_behavior;
+        self.presentation_mode_hides_docks = fresh.presentation_mode_hides_docks;
+        self.floating_step = fresh.floating_step;
+        self.smart_gaps = fresh.smart_gaps;
+        self.swallow = fresh.swallow;
+        // Geometries remembered since the file was read are kept, unless remembering was turned off.
+        if self.floating_memory.is_some() != fresh.floating_memory.is_some() {
+            self.floating_memory = fresh.floating_memory;
+        }
     }
 
     /// Apply saved state to a running manager.
@@ -260,6 +534,18 @@ impl<H: Handle> State<H> {
             if let Some(old_workspace) = old_state.workspaces.iter().find(|w| w.id == workspace.id)
             {
                 workspace.margin_multiplier = old_workspace.margin_multiplier;
+                workspace.extra_tags = old_workspace
+                    .extra_tags
+                    .iter()
+                    .copied()
+                    .filter(|&tag| all_tags.get(tag).is_some())
+                    .collect();
+                workspace.tag_history = old_workspace
+                    .tag_history
+                    .iter()
+                    .copied()
+                    .filter(|&tag| all_tags.get(tag).is_some())
+                    .collect();
                 if are_tags_equal {
                     workspace.tag = old_workspace.tag;
                 } else {
@@ -282,7 +568,39 @@ impl<H: Handle> State<H> {
                 .insert(scratchpad.clone(), id.clone());
         }
 
-        // Restore focus.
+        self.restore_hidden_windows(old_state);
+        self.restore_focus(old_state);
+
+        // Restore layout manager
+        self.layout_manager.restore(&old_state.layout_manager);
+    }
+
+    /// Remember the minimized, stashed and swallowed windows which are still there.
+    fn restore_hidden_windows(&mut self, old_state: &Self) {
+        let exists = |handle: &WindowHandle<H>| self.windows.iter().any(|w| &w.handle == handle);
+        self.minimized = old_state
+            .minimized
+            .iter()
+            .filter(|(handle, _)| exists(handle))
+            .copied()
+            .collect();
+        self.stashed = old_state
+            .stashed
+            .iter()
+            .filter(|(handle, _)| exists(handle))
+            .copied()
+            .collect();
+        self.swallowed = old_state
+            .swallowed
+            .iter()
+            .filter(|(handle, terminal)| exists(handle) && exists(terminal))
+            .copied()
+            .collect();
+    }
+
+    /// Focus the tag and window that were focused, keeping the focus history.
+    fn restore_focus(&mut self, old_state: &Self) {
+        let all_tags = &self.tags;
         self.focus_manager.tags_last_window = old_state.focus_manager.tags_last_window.clone();
         self.focus_manager
             .tags_last_window
@@ -300,9 +618,19 @@ impl<H: Handle> State<H> {
             },
         };
         self.focus_tag(&tag_id);
-
-        // Restore layout manager
-        self.layout_manager.restore(&old_state.layout_manager);
+        // Keep the tag history for `ReturnToLastTag`, with the focused tag first.
+        let tags = &self.tags;
+        let mut tag_history: VecDeque<TagId> = old_state
+            .focus_manager
+            .tag_history
+            .iter()
+            .copied()
+            .filter(|&id| tags.get(id).is_some())
+            .collect();
+        if tag_history.front() != Some(&tag_id) {
+            tag_history.push_front(tag_id);
+        }
+        self.focus_manager.tag_history = tag_history;
     }
 }
 
@@ -333,8 +661,101 @@ impl<'a, H: Handle> WindowSorter<'a, H> {
         });
     }
 
+    pub fn push(&mut self, mut windows: Vec<&'a Window<H>>) {
+        self.stack.append(&mut windows);
+    }
+
     pub fn finish(mut self) -> Vec<&'a Window<H>> {
         self.stack.append(&mut self.unsorted);
         self.stack
     }
 }
+
+/// The overrides of the normal tags by label, leaving out tags without any.
+fn resolve_tag_overrides(tags: &Tags, config: &impl Config) -> HashMap<String, TagOverrides> {
+    tags.normal()
+        .iter()
+        .map(|tag| (tag.label.clone(), config.tag_overrides(&tag.label)))
+        .filter(|(_, overrides)| overrides != &TagOverrides::default())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{MockHandle, WindowHandle, WindowState, WindowType};
+    use crate::{Command, DisplayAction, Manager, State, Window};
+
+    fn tags() -> Vec<String> {
+        (1..=4).map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn saved_state_should_be_restored_by_a_new_manager() {
+        let mut manager = Manager::new_test_with_windows(tags(), 3);
+        manager.state.focus_window(&WindowHandle::<MockHandle>(2));
+        manager.command_handler(&Command::SendWindowToTag {
+            window: None,
+            tag: 3,
+        });
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        manager.command_handler(&Command::ToggleTag(3));
+        let saved = serde_json::to_string(&manager.state).unwrap();
+
+        let mut restarted = Manager::new_test_with_windows(tags(), 3);
+        let old_state: State<MockHandle> = serde_json::from_str(&saved).unwrap();
+        restarted.state.restore_state(&old_state);
+
+        let tag_of = |handle| {
+            let window = restarted
+                .state
+                .windows
+                .iter()
+                .find(|w| w.handle.0 == handle);
+            window.and_then(|w| w.tag)
+        };
+        assert_eq!(tag_of(1), Some(1));
+        assert_eq!(tag_of(2), Some(3));
+        assert_eq!(tag_of(3), Some(1));
+        let workspace = &restarted.state.workspaces[0];
+        assert_eq!(workspace.tag, Some(2));
+        assert_eq!(workspace.extra_tags, vec![3]);
+        assert_eq!(
+            restarted.state.focus_manager.tag_history,
+            manager.state.focus_manager.tag_history
+        );
+    }
+
+    #[test]
+    fn state_saved_by_leftwm_0_5_should_still_be_restored() {
+        let saved = include_str!("../tests/data/state-0.5.0.ron");
+        let old_state: State<MockHandle> = ron::from_str(saved).unwrap();
+
+        let mut restarted = Manager::new_test_with_windows(tags(), 3);
+        restarted.state.restore_state(&old_state);
+        let tags: Vec<_> = restarted.state.windows.iter().map(|w| w.tag).collect();
+        assert_eq!(tags, vec![Some(1), Some(3), Some(1)]);
+        assert_eq!(restarted.state.workspaces[0].tag, Some(2));
+    }
+
+    #[test]
+    fn windows_kept_below_should_only_stay_above_desktops() {
+        let mut manager = Manager::new_test_with_windows(tags(), 3);
+        manager.state.windows[1].states.push(WindowState::Below);
+        for (i, r#type) in [(4, WindowType::Desktop), (5, WindowType::Dock)] {
+            let mut window = Window::new(WindowHandle::<MockHandle>(i), None, None);
+            window.r#type = r#type;
+            manager.state.windows.push(window);
+        }
+        let below = manager.state.windows[1].handle.0;
+
+        manager.state.sort_windows();
+        let Some(DisplayAction::SetWindowOrder(order)) = manager.state.actions.back() else {
+            panic!("sorting windows should restack them");
+        };
+        let order: Vec<_> = order.iter().map(|h| h.0).collect();
+        assert_eq!(order[order.len() - 3..], [5, below, 4]);
+    }
+}
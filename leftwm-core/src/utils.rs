@@ -1,8 +1,22 @@
 //! Various shared functions that `LeftWM` uses.
 pub mod child_process;
+pub mod clipboard_manager;
+pub mod color;
 pub mod command_pipe;
+pub mod focus_stealing;
 pub mod helpers;
+pub mod i3_ipc;
+pub mod idle_watch;
+pub mod loop_watchdog;
+pub mod metrics;
 pub mod modmask_lookup;
+pub mod motif_hints;
+pub mod ping_watch;
+pub mod property_throttle;
 pub mod return_pipe;
+pub mod scheduler;
+pub mod sd_notify;
+pub mod signals;
 pub mod state_socket;
+pub mod text_property;
 pub mod window_updater;
@@ -3,19 +3,50 @@
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
-use std::ffi::OsStr;
-use std::fs;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
 use std::iter::{Extend, FromIterator};
+use std::os::unix::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::sync::{atomic::AtomicBool, Arc};
+use std::sync::{atomic::AtomicBool, Arc, Mutex, PoisonError};
+use std::thread;
 
+use nix::fcntl::OFlag;
 use xdg::BaseDirectories;
 
 use crate::errors::Result;
 
 pub type ChildID = u32;
 
+/// Variables set in the environment of the programs leftwm runs, or left out of it when `None`.
+static CHILD_ENV: Mutex<Vec<(&str, Option<OsString>)>> = Mutex::new(Vec::new());
+
+/// Set `key` to `value` in the environment of the programs leftwm runs from now on, or leave it
+/// out when `None`. The environment of leftwm itself is left as it is.
+pub fn set_child_env(key: &'static str, value: Option<OsString>) {
+    let mut env = CHILD_ENV.lock().unwrap_or_else(PoisonError::into_inner);
+    env.retain(|(k, _)| *k != key);
+    env.push((key, value));
+}
+
+/// A command running `program` with the environment set by [`set_child_env`].
+fn child_command(program: impl AsRef<OsStr>) -> Command {
+    let mut command = Command::new(program);
+    for (key, value) in CHILD_ENV
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+    {
+        match value {
+            Some(value) => command.env(key, value),
+            None => command.env_remove(key),
+        };
+    }
+    command
+}
+
 #[derive(Default)]
 pub struct Nanny {}
 
@@ -34,7 +65,7 @@ impl Nanny {
 
     /// Runs a script if it exits
     fn run_script(path: &Path) -> Result<Child> {
-        Command::new(path)
+        child_command(path)
             .stdin(Stdio::null())
             .stdout(Stdio::null())
             .stderr(Stdio::null())
@@ -165,10 +196,126 @@ pub fn register_child_hook(flag: Arc<AtomicBool>) {
         .map_err(|err| tracing::error!("Cannot register SIGCHLD signal handler: {:?}", err));
 }
 
-/// Sends command to shell for execution
-/// Assumes STDIN/STDERR/STDOUT unwanted.
+/// Sends command to shell for execution.
+/// The command is split into words the way a shell would, and runs in its own session so it
+/// survives the window manager. Its STDOUT and STDERR are forwarded to the log.
 pub fn exec_shell(command: &str, children: &mut Children) -> Option<ChildID> {
-    exec_shell_with_args(command, Vec::new(), children)
+    let Some(words) = split_shell_words(command) else {
+        tracing::error!("Unable to parse command {:?}: unterminated quote", command);
+        return None;
+    };
+    let (program, args) = words.split_first()?;
+    let mut cmd = child_command(program);
+    cmd.args(args).stdin(Stdio::null());
+    // STDOUT and STDERR share a pipe, for a single thread to read them.
+    let output = match output_pipe() {
+        Ok((reader, stdout, stderr)) => {
+            cmd.stdout(stdout).stderr(stderr);
+            Some(reader)
+        }
+        Err(err) => {
+            tracing::warn!("Unable to log the output of {:?}: {}", command, err);
+            cmd.stdout(Stdio::null()).stderr(Stdio::null());
+            None
+        }
+    };
+    // SAFETY: `setsid` is async-signal-safe and does not touch the parent's memory.
+    unsafe {
+        cmd.pre_exec(|| nix::unistd::setsid().map(|_| ()).map_err(Into::into));
+    }
+    let spawned = cmd.spawn();
+    // Only the child is left with the writing ends, for the pipe to close along with it.
+    drop(cmd);
+    let child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::error!("Unable to run command {:?}: {}", command, err);
+            return None;
+        }
+    };
+    let pid = child.id();
+    if let Some(output) = output {
+        forward_to_log(pid, program.clone(), output);
+    }
+    children.insert(child);
+    Some(pid)
+}
+
+/// A pipe to read the output of a child from, and its writing end for STDOUT and STDERR.
+fn output_pipe() -> std::io::Result<(File, Stdio, Stdio)> {
+    let (reader, writer) = nix::unistd::pipe2(OFlag::O_CLOEXEC)?;
+    let stderr = writer.try_clone()?;
+    Ok((reader.into(), writer.into(), stderr.into()))
+}
+
+/// Logs every line `output` produces until the child, and whatever it started, closes it.
+fn forward_to_log(pid: ChildID, program: String, output: impl Read + Send + 'static) {
+    thread::spawn(move || {
+        for line in BufReader::new(output)
+            .lines()
+            .map_while(std::result::Result::ok)
+        {
+            tracing::info!(pid, "{}: {}", program, line);
+        }
+    });
+}
+
+/// Split a command line into words, honouring single quotes, double quotes and backslash escapes.
+/// Returns `None` if a quote is left open.
+fn split_shell_words(command: &str) -> Option<Vec<String>> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\' | '$' | '`') => word.push(c),
+                            c => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Some(words)
 }
 
 /// Sends command to shell for execution including arguments.
@@ -178,7 +325,7 @@ pub fn exec_shell_with_args(
     args: Vec<String>,
     children: &mut Children,
 ) -> Option<ChildID> {
-    let child = Command::new(command)
+    let child = child_command(command)
         .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::null())
@@ -189,3 +336,47 @@ pub fn exec_shell_with_args(
     children.insert(child);
     Some(pid)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{output_pipe, split_shell_words};
+    use std::io::Read;
+    use std::process::Command;
+
+    #[test]
+    fn split_shell_words_should_split_on_whitespace() {
+        assert_eq!(
+            split_shell_words("  notify-send  hello   world ").unwrap(),
+            vec!["notify-send", "hello", "world"]
+        );
+    }
+
+    #[test]
+    fn split_shell_words_should_honour_quotes_and_escapes() {
+        assert_eq!(
+            split_shell_words(r#"sh -c 'echo "$HOME"' "a \"b\" c" d\ e ''"#).unwrap(),
+            vec!["sh", "-c", r#"echo "$HOME""#, r#"a "b" c"#, "d e", ""]
+        );
+    }
+
+    #[test]
+    fn split_shell_words_should_reject_unterminated_quotes() {
+        assert!(split_shell_words("echo 'oops").is_none());
+        assert!(split_shell_words("echo \"oops").is_none());
+    }
+
+    #[test]
+    fn output_pipe_should_close_once_the_child_is_done() {
+        let (mut reader, stdout, stderr) = output_pipe().unwrap();
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2"]);
+        command.stdout(stdout).stderr(stderr);
+        let mut child = command.spawn().unwrap();
+        drop(command);
+        child.wait().unwrap();
+
+        let mut output = String::new();
+        reader.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "out\nerr\n");
+    }
+}
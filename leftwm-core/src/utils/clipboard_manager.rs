@@ -0,0 +1,164 @@
+//! Clipboard persistence, so the clipboard survives the window it was copied from.
+//!
+//! The display servers own the `CLIPBOARD_MANAGER` selection. When the owner of the `CLIPBOARD`
+//! exits it asks for the clipboard to be saved by converting `CLIPBOARD_MANAGER` to
+//! `SAVE_TARGETS`, the contents are then fetched in every target offered and served until another
+//! window takes the clipboard. See the freedesktop clipboard manager specification.
+//!
+//! This keeps track of the saved contents and of the save in progress, generic over the atom and
+//! window types of the backends.
+
+/// The contents of the clipboard converted to one target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardData<A> {
+    pub target: A,
+    pub r#type: A,
+    pub format: u8,
+    pub value: Vec<u8>,
+}
+
+/// A client asking for the clipboard to be saved, answered once it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveRequest<A, W> {
+    pub requestor: W,
+    pub property: A,
+    pub time: u32,
+}
+
+#[derive(Debug)]
+struct Save<A, W> {
+    request: SaveRequest<A, W>,
+    remaining: Vec<A>,
+    fetched: Vec<ClipboardData<A>>,
+}
+
+/// The saved clipboard and the save in progress.
+#[derive(Debug)]
+pub struct ClipboardStore<A, W> {
+    saved: Vec<ClipboardData<A>>,
+    save: Option<Save<A, W>>,
+}
+
+impl<A, W> Default for ClipboardStore<A, W> {
+    fn default() -> Self {
+        Self {
+            saved: vec![],
+            save: None,
+        }
+    }
+}
+
+impl<A: Copy + PartialEq, W: Copy> ClipboardStore<A, W> {
+    /// Start saving the clipboard, abandoning the save in progress if any.
+    pub fn begin_save(&mut self, request: SaveRequest<A, W>) {
+        self.save = Some(Save {
+            request,
+            remaining: vec![],
+            fetched: vec![],
+        });
+    }
+
+    #[must_use]
+    pub const fn is_saving(&self) -> bool {
+        self.save.is_some()
+    }
+
+    /// The clipboard owner listed the targets it can convert the clipboard to. Returns the first
+    /// one to fetch, `None` if there is nothing to fetch.
+    pub fn targets_offered(&mut self, targets: impl IntoIterator<Item = A>) -> Option<A> {
+        let save = self.save.as_mut()?;
+        save.remaining = targets.into_iter().collect();
+        save.remaining.reverse();
+        save.remaining.pop()
+    }
+
+    /// The clipboard owner converted the clipboard to the last requested target, `None` if it
+    /// failed to. Returns the next target to fetch, `None` once all were.
+    pub fn target_fetched(&mut self, data: Option<ClipboardData<A>>) -> Option<A> {
+        let save = self.save.as_mut()?;
+        save.fetched.extend(data);
+        save.remaining.pop()
+    }
+
+    /// End the save in progress, keeping what was fetched if anything was. Returns the request to
+    /// answer and whether the clipboard was saved.
+    pub fn finish_save(&mut self) -> Option<(SaveRequest<A, W>, bool)> {
+        let save = self.save.take()?;
+        let saved = !save.fetched.is_empty();
+        if saved {
+            self.saved = save.fetched;
+        }
+        Some((save.request, saved))
+    }
+
+    /// The saved contents converted to `target`.
+    #[must_use]
+    pub fn get(&self, target: A) -> Option<&ClipboardData<A>> {
+        self.saved.iter().find(|data| data.target == target)
+    }
+
+    /// The targets the saved contents are available in.
+    #[must_use]
+    pub fn targets(&self) -> Vec<A> {
+        self.saved.iter().map(|data| data.target).collect()
+    }
+
+    /// Forget the saved contents, as another window took the clipboard.
+    pub fn clear(&mut self) {
+        self.saved.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: SaveRequest<u32, u32> = SaveRequest {
+        requestor: 1,
+        property: 2,
+        time: 0,
+    };
+
+    fn data(target: u32) -> ClipboardData<u32> {
+        ClipboardData {
+            target,
+            r#type: target,
+            format: 8,
+            value: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn every_offered_target_should_be_fetched_and_saved() {
+        let mut store = ClipboardStore::default();
+        store.begin_save(REQUEST);
+        assert_eq!(store.targets_offered([10, 11, 12]), Some(10));
+        assert_eq!(store.target_fetched(Some(data(10))), Some(11));
+        assert_eq!(store.target_fetched(None), Some(12));
+        assert_eq!(store.target_fetched(Some(data(12))), None);
+        assert_eq!(store.finish_save(), Some((REQUEST, true)));
+
+        assert!(!store.is_saving());
+        assert_eq!(store.targets(), vec![10, 12]);
+        assert_eq!(store.get(12), Some(&data(12)));
+        assert_eq!(store.get(11), None);
+    }
+
+    #[test]
+    fn a_failed_save_should_keep_the_previous_contents() {
+        let mut store = ClipboardStore::default();
+        store.begin_save(REQUEST);
+        store.targets_offered([10]);
+        store.target_fetched(Some(data(10)));
+        store.finish_save();
+
+        store.begin_save(REQUEST);
+        assert_eq!(store.targets_offered([11]), Some(11));
+        assert_eq!(store.target_fetched(None), None);
+        assert_eq!(store.finish_save(), Some((REQUEST, false)));
+        assert_eq!(store.targets(), vec![10]);
+
+        store.clear();
+        assert!(store.targets().is_empty());
+    }
+}
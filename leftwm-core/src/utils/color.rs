@@ -0,0 +1,161 @@
+//! Parsing of the colors from the config, and their pixel values.
+//!
+//! Hex colors are parsed here, with or without an alpha channel. Other colors, such as X11 color
+//! names, are left to the X server to look up. The pixel of a color is computed from the channel
+//! masks of a visual, so no color cells need to be allocated for true color visuals.
+
+/// A color with 8 bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+/// The layout of the pixels of a true color visual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisualFormat {
+    pub red_mask: u32,
+    pub green_mask: u32,
+    pub blue_mask: u32,
+    pub depth: u8,
+}
+
+impl Rgba {
+    #[must_use]
+    pub const fn opaque(red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha: u8::MAX,
+        }
+    }
+
+    /// A color from the 16 bit channels X uses, such as in the reply of a color lookup.
+    #[must_use]
+    pub fn from_x_channels(red: u16, green: u16, blue: u16) -> Self {
+        let high = |channel: u16| channel.to_be_bytes()[0];
+        Self::opaque(high(red), high(green), high(blue))
+    }
+
+    /// Parses `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`. Returns `None` for anything else.
+    #[must_use]
+    pub fn from_hex(color: &str) -> Option<Self> {
+        let digits = color.trim().strip_prefix('#')?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).ok()?;
+            // `#f00` is the same as `#ff0000`.
+            Some(if width == 1 { value * 0x11 } else { value })
+        };
+        let width = match digits.len() {
+            3 | 4 => 1,
+            6 | 8 => 2,
+            _ => return None,
+        };
+        let alpha = if digits.len() % 3 == 0 {
+            u8::MAX
+        } else {
+            channel(3, width)?
+        };
+        Some(Self {
+            red: channel(0, width)?,
+            green: channel(1, width)?,
+            blue: channel(2, width)?,
+            alpha,
+        })
+    }
+
+    /// The pixel value of the color in a visual. With a depth of 32 the bits outside of the
+    /// channel masks hold the alpha, and the channels are premultiplied as compositors expect.
+    /// Visuals without an alpha channel get the color as if it was opaque.
+    #[must_use]
+    pub fn pixel(self, format: VisualFormat) -> u32 {
+        let rgb_mask = format.red_mask | format.green_mask | format.blue_mask;
+        let alpha_mask = if format.depth == 32 { !rgb_mask } else { 0 };
+        let alpha = if alpha_mask == 0 { u8::MAX } else { self.alpha };
+        let premultiply = |channel: u8| {
+            let value = u16::from(channel) * u16::from(alpha) / u16::from(u8::MAX);
+            u8::try_from(value).unwrap_or(channel)
+        };
+        scale_to_mask(premultiply(self.red), format.red_mask)
+            | scale_to_mask(premultiply(self.green), format.green_mask)
+            | scale_to_mask(premultiply(self.blue), format.blue_mask)
+            | scale_to_mask(alpha, alpha_mask)
+    }
+}
+
+/// Scales an 8 bit channel to the bits of `mask`.
+fn scale_to_mask(channel: u8, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+    let value = u64::from(channel) * max / u64::from(u8::MAX);
+    u32::try_from(value).unwrap_or(u32::MAX) << shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RGB24: VisualFormat = VisualFormat {
+        red_mask: 0x00ff_0000,
+        green_mask: 0x0000_ff00,
+        blue_mask: 0x0000_00ff,
+        depth: 24,
+    };
+
+    const ARGB32: VisualFormat = VisualFormat { depth: 32, ..RGB24 };
+
+    #[test]
+    fn hex_colors_should_be_parsed_with_and_without_alpha() {
+        assert_eq!(
+            Rgba::from_hex("#ff8000"),
+            Some(Rgba::opaque(0xff, 0x80, 0x00))
+        );
+        assert_eq!(Rgba::from_hex("#f80"), Some(Rgba::opaque(0xff, 0x88, 0x00)));
+        let translucent = Rgba {
+            alpha: 0x80,
+            ..Rgba::opaque(0x11, 0x22, 0x33)
+        };
+        assert_eq!(Rgba::from_hex("#11223380"), Some(translucent));
+        assert_eq!(
+            Rgba::from_hex("#1238"),
+            Some(Rgba {
+                alpha: 0x88,
+                ..Rgba::opaque(0x11, 0x22, 0x33)
+            })
+        );
+    }
+
+    #[test]
+    fn other_colors_should_not_be_parsed_as_hex() {
+        assert_eq!(Rgba::from_hex("red"), None);
+        assert_eq!(Rgba::from_hex("#12345"), None);
+        assert_eq!(Rgba::from_hex("#gg0000"), None);
+        assert_eq!(Rgba::from_hex("#ééé"), None);
+    }
+
+    #[test]
+    fn pixel_should_follow_the_visual() {
+        let color = Rgba {
+            alpha: 0x80,
+            ..Rgba::opaque(0xff, 0x80, 0x00)
+        };
+        assert_eq!(color.pixel(RGB24), 0x00ff_8000);
+        assert_eq!(color.pixel(ARGB32), 0x8080_4000);
+        let rgb16 = VisualFormat {
+            red_mask: 0xf800,
+            green_mask: 0x07e0,
+            blue_mask: 0x001f,
+            depth: 16,
+        };
+        assert_eq!(Rgba::opaque(0xff, 0xff, 0).pixel(rgb16), 0xffe0);
+    }
+}
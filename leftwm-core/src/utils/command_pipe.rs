@@ -1,7 +1,9 @@
 //! Creates a pipe to listen for external commands.
+use crate::config::Schedule;
+use crate::models::WindowHandle;
 use crate::models::{Handle, TagId};
 use crate::utils::return_pipe::ReturnPipe;
-use crate::{command, Command, ReleaseScratchPadOption};
+use crate::{command, Command, ReleaseScratchPadOption, WindowSelector};
 use leftwm_layouts::geometry::Direction as FocusDirection;
 use std::error::Error;
 use std::fs::OpenOptions;
@@ -129,7 +131,12 @@ async fn read_from_pipe<H: Handle>(
     Some(())
 }
 
-fn parse_command<H: Handle>(s: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+/// Parse a command written as for `leftwm-command`.
+///
+/// # Errors
+///
+/// Will error if the arguments of a known command are invalid.
+pub fn parse_command<H: Handle>(s: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
     let (head, rest) = s.split_once(' ').unwrap_or((s, ""));
     match head {
         // Move Window
@@ -140,8 +147,8 @@ fn parse_command<H: Handle>(s: &str) -> Result<Command<H>, Box<dyn std::error::E
         "MoveWindowToNextTag" => build_move_window_to_next_tag(rest),
         "MoveWindowToPreviousTag" => build_move_window_to_previous_tag(rest),
         "MoveWindowToLastWorkspace" => Ok(Command::MoveWindowToLastWorkspace),
-        "MoveWindowToNextWorkspace" => Ok(Command::MoveWindowToNextWorkspace),
-        "MoveWindowToPreviousWorkspace" => Ok(Command::MoveWindowToPreviousWorkspace),
+        "MoveWindowToNextWorkspace" => build_move_window_to_next_workspace(rest),
+        "MoveWindowToPreviousWorkspace" => build_move_window_to_previous_workspace(rest),
         "MoveWindowAt" => build_move_window_dir(rest),
         "SendWindowToTag" => build_send_window_to_tag(rest),
         // Focus Navigation
@@ -149,10 +156,12 @@ fn parse_command<H: Handle>(s: &str) -> Result<Command<H>, Box<dyn std::error::E
         "FocusWindowTop" => build_focus_window_top(rest),
         "FocusWindowUp" => Ok(Command::FocusWindowUp),
         "FocusWindowAt" => build_focus_window_dir(rest),
+        "FocusPrevious" => build_focus_previous(rest),
         "FocusNextTag" => build_focus_next_tag(rest),
         "FocusPreviousTag" => build_focus_previous_tag(rest),
         "FocusWorkspaceNext" => Ok(Command::FocusWorkspaceNext),
         "FocusWorkspacePrevious" => Ok(Command::FocusWorkspacePrevious),
+        "ActivateWindow" => build_activate_window(rest),
         // Layout
         "DecreaseMainWidth" | "DecreaseMainSize" => build_decrease_main_size(rest), // 'DecreaseMainWidth' deprecated
         "IncreaseMainWidth" | "IncreaseMainSize" => build_increase_main_size(rest), // 'IncreaseMainWidth' deprecated
@@ -163,6 +172,9 @@ fn parse_command<H: Handle>(s: &str) -> Result<Command<H>, Box<dyn std::error::E
         "RotateTag" => Ok(Command::RotateTag),
         "SetLayout" => build_set_layout(rest),
         "SetMarginMultiplier" => build_set_margin_multiplier(rest),
+        "SetModKey" => build_set_modkey(rest),
+        "SetInsertBehavior" => build_set_insert_behavior(rest),
+        "ToggleGaps" => Ok(Command::ToggleGaps),
         // Scratchpad
         "ToggleScratchPad" => build_toggle_scratchpad(rest),
         "AttachScratchPad" => build_attach_scratchpad(rest),
@@ -180,16 +192,53 @@ fn parse_command<H: Handle>(s: &str) -> Result<Command<H>, Box<dyn std::error::E
         // Workspace/Tag
         "GoToTag" => build_go_to_tag(rest),
         "ReturnToLastTag" => Ok(Command::ReturnToLastTag),
+        "ToggleTag" => build_toggle_tag(rest),
         "SendWorkspaceToTag" => build_send_workspace_to_tag(rest),
         "SwapScreens" => Ok(Command::SwapScreens),
         "ToggleFullScreen" => Ok(Command::ToggleFullScreen),
+        "ToggleFakeFullScreen" => Ok(Command::ToggleFakeFullScreen),
+        "TogglePictureInPicture" => Ok(Command::TogglePictureInPicture),
         "ToggleMaximized" => Ok(Command::ToggleMaximized),
         "ToggleSticky" => Ok(Command::ToggleSticky),
         "ToggleAbove" => Ok(Command::ToggleAbove),
+        // Selection
+        "ToggleSelectWindow" => Ok(Command::ToggleSelectWindow),
+        "ClearSelection" => Ok(Command::ClearSelection),
+        "SendSelectionToTag" => build_send_selection_to_tag(rest),
+        "FloatSelection" => Ok(Command::FloatSelection),
+        "CloseSelection" => Ok(Command::CloseSelection),
+        // Tabbed containers
+        "GroupWindows" => Ok(Command::GroupWindows),
+        "UngroupWindow" => Ok(Command::UngroupWindow),
+        "NextTab" => Ok(Command::NextTab),
+        "PreviousTab" => Ok(Command::PreviousTab),
+        // Minimized windows
+        "MinimizeWindow" => Ok(Command::MinimizeWindow),
+        "RestoreLastMinimized" => Ok(Command::RestoreLastMinimized),
+        "RestoreAll" => Ok(Command::RestoreAll),
+        // Stashed windows
+        "StashWindows" => Ok(Command::StashWindows),
+        "UnstashWindows" => Ok(Command::UnstashWindows),
+        // Scheduled commands
+        "Schedule" => build_schedule(rest),
+        "CancelSchedules" => Ok(Command::CancelSchedules),
+        // Urgency
+        "ClearUrgent" => Ok(Command::ClearUrgent { window: None }),
+        "FocusNextUrgent" => Ok(Command::FocusNextUrgent),
+        // Presentation mode
+        "TogglePresentationMode" => Ok(Command::TogglePresentationMode),
+        // Floating windows
+        "MoveFloatingWindow" => build_move_floating_window(rest),
+        "ResizeWindowWider" => Ok(Command::ResizeWindowWider),
+        "ResizeWindowNarrower" => Ok(Command::ResizeWindowNarrower),
+        "ResizeWindowTaller" => Ok(Command::ResizeWindowTaller),
+        "ResizeWindowShorter" => Ok(Command::ResizeWindowShorter),
         // General
         "CloseWindow" => Ok(Command::CloseWindow),
         "CloseAllOtherWindows" => Ok(Command::CloseAllOtherWindows),
+        "ForceCloseWindow" => Ok(Command::ForceCloseWindow),
         "SoftReload" => Ok(Command::SoftReload),
+        "Execute" => build_execute(rest),
         _ => Ok(Command::Other(s.into())),
     }
 }
@@ -266,6 +315,27 @@ fn build_send_window_to_tag<H: Handle>(
     })
 }
 
+fn build_send_selection_to_tag<H: Handle>(
+    raw: &str,
+) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let tag_id = if raw.is_empty() {
+        return Err("missing argument tag_id".into());
+    } else {
+        match TagId::from_str(raw) {
+            Ok(tag) => tag,
+            Err(_) => Err("argument tag_id was not a valid tag number")?,
+        }
+    };
+    Ok(Command::SendSelectionToTag(tag_id))
+}
+
+fn build_toggle_tag<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    match TagId::from_str(raw) {
+        Ok(tag) => Ok(Command::ToggleTag(tag)),
+        Err(_) => Err("argument tag_id was missing or not a valid tag number")?,
+    }
+}
+
 fn build_send_workspace_to_tag<H: Handle>(
     raw: &str,
 ) -> Result<Command<H>, Box<dyn std::error::Error>> {
@@ -288,6 +358,48 @@ fn build_send_workspace_to_tag<H: Handle>(
     Ok(Command::SendWorkspaceToTag(ws_index, tag_index))
 }
 
+fn build_execute<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument command".into());
+    }
+    Ok(Command::Execute(raw.to_owned()))
+}
+
+fn build_schedule<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let (kind, rest) = raw
+        .split_once(' ')
+        .ok_or("missing arguments schedule and command")?;
+    let (schedule, command) = match kind {
+        "after" | "every" => {
+            let (secs, command) = rest.split_once(' ').ok_or("missing argument command")?;
+            let Ok(secs) = secs.parse() else {
+                return Err("argument seconds was not a valid number".into());
+            };
+            if kind == "after" {
+                (Schedule::After(secs), command)
+            } else {
+                (Schedule::Every(secs), command)
+            }
+        }
+        "cron" => {
+            let fields: Vec<&str> = rest.splitn(6, ' ').collect();
+            let [.., command] = fields[..] else {
+                return Err("missing argument command".into());
+            };
+            (
+                Schedule::Cron(fields[..fields.len() - 1].join(" ")),
+                command,
+            )
+        }
+        _ => return Err("schedule was not one of `after`, `every` or `cron`".into()),
+    };
+    schedule.validate()?;
+    Ok(Command::Schedule {
+        schedule,
+        command: Box::new(parse_command(command)?),
+    })
+}
+
 fn build_set_layout<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
     let layout_name = if raw.is_empty() {
         return Err("missing layout name".into());
@@ -308,6 +420,22 @@ fn build_set_margin_multiplier<H: Handle>(
     Ok(Command::SetMarginMultiplier(margin_multiplier))
 }
 
+fn build_set_modkey<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing modifier".into());
+    }
+    Ok(Command::SetModKey(raw.to_owned()))
+}
+
+fn build_set_insert_behavior<H: Handle>(
+    raw: &str,
+) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let behavior = serde_json::from_value(raw.into()).or(Err(
+        "argument was not Top, Bottom, BeforeCurrent, AfterCurrent or AfterSmallest",
+    ))?;
+    Ok(Command::SetInsertBehavior(behavior))
+}
+
 fn build_focus_window_top<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
     let swap = if raw.is_empty() {
         false
@@ -332,6 +460,41 @@ fn build_focus_window_dir<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn st
     Ok(Command::FocusWindowAt(dir))
 }
 
+fn build_activate_window<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let (kind, value) = raw.split_once(' ').unwrap_or((raw, ""));
+    if value.is_empty() {
+        return Err("missing argument handle, class or title".into());
+    }
+    let selector = match kind {
+        "handle" => {
+            let handle = match value.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => value.parse(),
+            }
+            .or(Err("argument handle was not a valid window id"))?;
+            let handle: WindowHandle<H> = serde_json::from_value(handle.into())
+                .or(Err("argument handle was not a valid window id"))?;
+            WindowSelector::Handle(handle)
+        }
+        "class" => WindowSelector::Class(value.to_owned()),
+        "title" => WindowSelector::Title(value.to_owned()),
+        _ => Err("first argument was not handle, class or title")?,
+    };
+    Ok(Command::ActivateWindow(selector))
+}
+
+fn build_focus_previous<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let steps = if raw.is_empty() {
+        1
+    } else {
+        match usize::from_str(raw) {
+            Ok(steps) => steps,
+            Err(_) => Err("Argument steps was not a positive number")?,
+        }
+    };
+    Ok(Command::FocusPrevious(steps))
+}
+
 fn build_move_window_dir<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
     let dir = if raw.is_empty() {
         FocusDirection::North
@@ -344,6 +507,15 @@ fn build_move_window_dir<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std
     Ok(Command::MoveWindowAt(dir))
 }
 
+fn build_move_floating_window<H: Handle>(
+    raw: &str,
+) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    match FocusDirection::from_str(raw) {
+        Ok(d) => Ok(Command::MoveFloatingWindow(d)),
+        Err(()) => Err("Argument direction was missing or invalid")?,
+    }
+}
+
 fn build_move_window_top<H: Handle>(raw: &str) -> Result<Command<H>, Box<dyn std::error::Error>> {
     let swap = if raw.is_empty() {
         true
@@ -396,6 +568,34 @@ fn build_move_window_to_previous_tag<H: Handle>(
     Ok(Command::MoveWindowToPreviousTag { follow })
 }
 
+fn build_move_window_to_next_workspace<H: Handle>(
+    raw: &str,
+) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let follow = if raw.is_empty() {
+        false
+    } else {
+        match bool::from_str(raw) {
+            Ok(bl) => bl,
+            Err(_) => Err("Argument follow was not true or false")?,
+        }
+    };
+    Ok(Command::MoveWindowToNextWorkspace { follow })
+}
+
+fn build_move_window_to_previous_workspace<H: Handle>(
+    raw: &str,
+) -> Result<Command<H>, Box<dyn std::error::Error>> {
+    let follow = if raw.is_empty() {
+        false
+    } else {
+        match bool::from_str(raw) {
+            Ok(bl) => bl,
+            Err(_) => Err("Argument follow was not true or false")?,
+        }
+    };
+    Ok(Command::MoveWindowToPreviousWorkspace { follow })
+}
+
 fn build_increase_main_size<H: Handle>(
     raw: &str,
 ) -> Result<Command<H>, Box<dyn std::error::Error>> {
@@ -497,6 +697,7 @@ impl<H: Handle> fmt::Display for InvalidFocusDeltaBehaviorError<H> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::config::InsertBehavior;
     use crate::models::MockHandle;
     use crate::utils::helpers::test::temp_path;
     use tokio::io::AsyncWriteExt;
@@ -588,11 +789,26 @@ mod test {
         assert!(build_toggle_scratchpad::<MockHandle>("").is_err());
     }
 
+    #[test]
+    fn build_send_selection_to_tag_without_parameter() {
+        assert!(build_send_selection_to_tag::<MockHandle>("").is_err());
+    }
+
+    #[test]
+    fn build_toggle_tag_without_parameter() {
+        assert!(build_toggle_tag::<MockHandle>("").is_err());
+    }
+
     #[test]
     fn build_send_workspace_to_tag_without_parameter() {
         assert!(build_send_workspace_to_tag::<MockHandle>("").is_err());
     }
 
+    #[test]
+    fn build_execute_without_parameter() {
+        assert!(build_execute::<MockHandle>("").is_err());
+    }
+
     #[test]
     fn build_set_layout_without_parameter() {
         assert!(build_set_layout::<MockHandle>("").is_err());
@@ -603,6 +819,21 @@ mod test {
         assert!(build_set_margin_multiplier::<MockHandle>("").is_err());
     }
 
+    #[test]
+    fn build_set_modkey_without_parameter() {
+        assert!(build_set_modkey::<MockHandle>("").is_err());
+    }
+
+    #[test]
+    fn build_set_insert_behavior_with_and_without_a_behavior() {
+        assert_eq!(
+            build_set_insert_behavior::<MockHandle>("AfterCurrent").unwrap(),
+            Command::SetInsertBehavior(InsertBehavior::AfterCurrent)
+        );
+        assert!(build_set_insert_behavior::<MockHandle>("").is_err());
+        assert!(build_set_insert_behavior::<MockHandle>("Middle").is_err());
+    }
+
     #[test]
     fn build_move_window_top_without_parameter() {
         assert_eq!(
@@ -627,6 +858,58 @@ mod test {
         );
     }
 
+    #[test]
+    fn build_focus_previous_without_parameter() {
+        assert_eq!(
+            build_focus_previous::<MockHandle>("").unwrap(),
+            Command::FocusPrevious(1)
+        );
+    }
+
+    #[test]
+    fn build_activate_window_should_parse_the_selector() {
+        assert_eq!(
+            build_activate_window::<MockHandle>("handle 0x1a").unwrap(),
+            Command::ActivateWindow(WindowSelector::Handle(WindowHandle(26)))
+        );
+        assert_eq!(
+            build_activate_window::<MockHandle>("handle 26").unwrap(),
+            Command::ActivateWindow(WindowSelector::Handle(WindowHandle(26)))
+        );
+        assert_eq!(
+            build_activate_window::<MockHandle>("title Some title").unwrap(),
+            Command::ActivateWindow(WindowSelector::Title("Some title".to_string()))
+        );
+        assert!(build_activate_window::<MockHandle>("class").is_err());
+        assert!(build_activate_window::<MockHandle>("handle firefox").is_err());
+        assert!(build_activate_window::<MockHandle>("name firefox").is_err());
+    }
+
+    #[test]
+    fn build_schedule_should_parse_the_schedule_and_command() {
+        assert_eq!(
+            build_schedule::<MockHandle>("every 3600 GoToTag 9 false").unwrap(),
+            Command::Schedule {
+                schedule: Schedule::Every(3600),
+                command: Box::new(Command::GoToTag {
+                    tag: 9,
+                    swap: false
+                }),
+            }
+        );
+        assert_eq!(
+            build_schedule::<MockHandle>("cron 0 19 * * * SetMarginMultiplier 2").unwrap(),
+            Command::Schedule {
+                schedule: Schedule::Cron("0 19 * * *".to_string()),
+                command: Box::new(Command::SetMarginMultiplier(2.0)),
+            }
+        );
+        assert!(build_schedule::<MockHandle>("after 60").is_err());
+        assert!(build_schedule::<MockHandle>("after soon GoToTag 1").is_err());
+        assert!(build_schedule::<MockHandle>("cron 0 25 * * * GoToTag 1").is_err());
+        assert!(build_schedule::<MockHandle>("at 12:00 GoToTag 1").is_err());
+    }
+
     #[test]
     fn build_move_window_dir_without_parameter() {
         assert_eq!(
@@ -651,6 +934,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn build_move_window_to_next_workspace_without_parameter() {
+        assert_eq!(
+            build_move_window_to_next_workspace::<MockHandle>("").unwrap(),
+            Command::MoveWindowToNextWorkspace { follow: false }
+        );
+        assert_eq!(
+            build_move_window_to_next_workspace::<MockHandle>("true").unwrap(),
+            Command::MoveWindowToNextWorkspace { follow: true }
+        );
+    }
+
     #[test]
     fn build_focus_next_tag_without_parameter() {
         assert_eq!(
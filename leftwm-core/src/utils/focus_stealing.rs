@@ -0,0 +1,137 @@
+//! Detects focus ping-pong with clients that keep taking the focus back after it is reasserted.
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Reasserting the focus against the same window this many times ...
+pub const FOCUS_LOOP_LIMIT: usize = 5;
+/// ... within this period is considered a loop.
+pub const FOCUS_LOOP_PERIOD: Duration = Duration::from_secs(1);
+/// How long the focus is no longer reasserted once a loop is detected.
+pub const FOCUS_LOOP_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusLoopVerdict {
+    /// Reassert the focus on the focused window.
+    Reassert,
+    /// A loop was just detected, stop reasserting the focus for a while.
+    LoopDetected,
+    /// A loop was detected recently between the same windows, leave the focus where it is.
+    BackingOff,
+}
+
+/// Keeps track of the focus being reasserted when another window takes it.
+#[derive(Debug)]
+pub struct FocusLoopDetector<W> {
+    pair: Option<(W, W)>,
+    reasserts: VecDeque<Instant>,
+    /// The windows a loop was detected between, and until when the focus is left to them.
+    backoff: Option<((W, W), Instant)>,
+}
+
+impl<W> Default for FocusLoopDetector<W> {
+    fn default() -> Self {
+        Self {
+            pair: None,
+            reasserts: VecDeque::new(),
+            backoff: None,
+        }
+    }
+}
+
+impl<W: PartialEq + Copy> FocusLoopDetector<W> {
+    /// Called when `intruder` took the focus from `focused`, decides whether to take it back.
+    pub fn check(&mut self, focused: W, intruder: W) -> FocusLoopVerdict {
+        self.check_at(Instant::now(), focused, intruder)
+    }
+
+    fn check_at(&mut self, now: Instant, focused: W, intruder: W) -> FocusLoopVerdict {
+        match self.backoff {
+            Some((pair, until)) if now < until => {
+                if pair == (focused, intruder) {
+                    return FocusLoopVerdict::BackingOff;
+                }
+            }
+            _ => self.backoff = None,
+        }
+        if self.pair != Some((focused, intruder)) {
+            self.pair = Some((focused, intruder));
+            self.reasserts.clear();
+        }
+        while self
+            .reasserts
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > FOCUS_LOOP_PERIOD)
+        {
+            self.reasserts.pop_front();
+        }
+        self.reasserts.push_back(now);
+        if self.reasserts.len() > FOCUS_LOOP_LIMIT {
+            self.reasserts.clear();
+            self.backoff = Some(((focused, intruder), now + FOCUS_LOOP_BACKOFF));
+            return FocusLoopVerdict::LoopDetected;
+        }
+        FocusLoopVerdict::Reassert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flipping_focus_quickly_should_be_detected_and_backed_off() {
+        let mut detector = FocusLoopDetector::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        for i in 0..FOCUS_LOOP_LIMIT as u64 {
+            assert_eq!(
+                detector.check_at(at(i * 10), 1, 2),
+                FocusLoopVerdict::Reassert
+            );
+        }
+        assert_eq!(
+            detector.check_at(at(60), 1, 2),
+            FocusLoopVerdict::LoopDetected
+        );
+        assert_eq!(
+            detector.check_at(at(70), 1, 2),
+            FocusLoopVerdict::BackingOff
+        );
+        let after_backoff = 60 + FOCUS_LOOP_BACKOFF.as_millis() as u64;
+        assert_eq!(
+            detector.check_at(at(after_backoff), 1, 2),
+            FocusLoopVerdict::Reassert
+        );
+    }
+
+    #[test]
+    fn other_windows_should_still_lose_the_focus_during_a_backoff() {
+        let mut detector = FocusLoopDetector::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        for i in 0..=FOCUS_LOOP_LIMIT as u64 {
+            detector.check_at(at(i * 10), 1, 2);
+        }
+        assert_eq!(detector.check_at(at(70), 1, 3), FocusLoopVerdict::Reassert);
+        assert_eq!(detector.check_at(at(80), 4, 2), FocusLoopVerdict::Reassert);
+        assert_eq!(
+            detector.check_at(at(90), 1, 2),
+            FocusLoopVerdict::BackingOff
+        );
+    }
+
+    #[test]
+    fn slow_or_different_focus_changes_should_not_be_a_loop() {
+        let mut detector = FocusLoopDetector::default();
+        let start = Instant::now();
+        for i in 0..20 {
+            let now = start + FOCUS_LOOP_PERIOD * i;
+            assert_eq!(detector.check_at(now, 1, 2), FocusLoopVerdict::Reassert);
+        }
+        for i in 0..20 {
+            let now = start + FOCUS_LOOP_PERIOD * 20 + Duration::from_millis(i);
+            let verdict = detector.check_at(now, 1, 2 + i % 2);
+            assert_eq!(verdict, FocusLoopVerdict::Reassert);
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! A socket speaking a subset of the i3 IPC protocol, so tools written for i3 work with leftwm.
+//!
+//! Messages are `i3-ipc`, the payload length and the message type as native endian `u32`s, then
+//! the JSON payload. Requests are handed to the event loop, which answers them, while events are
+//! sent to the clients that subscribed to them. Clients find the socket through `I3SOCK`.
+use crate::errors::Result;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+
+/// Payloads larger than this are refused, and the client disconnected.
+const MAX_PAYLOAD_LEN: u32 = 1024 * 1024;
+
+pub const RUN_COMMAND: u32 = 0;
+pub const GET_WORKSPACES: u32 = 1;
+pub const SUBSCRIBE: u32 = 2;
+pub const GET_OUTPUTS: u32 = 3;
+pub const GET_TREE: u32 = 4;
+pub const GET_VERSION: u32 = 7;
+
+pub const EVENT_WORKSPACE: u32 = 0x8000_0000;
+pub const EVENT_WINDOW: u32 = 0x8000_0003;
+
+/// A request for the event loop to answer.
+#[derive(Debug)]
+pub struct Request {
+    pub kind: u32,
+    pub payload: String,
+    reply: oneshot::Sender<String>,
+}
+
+impl Request {
+    pub fn reply(self, payload: String) {
+        self.reply.send(payload).ok();
+    }
+}
+
+/// Serves i3 IPC clients until shut down.
+#[derive(Debug)]
+pub struct Server {
+    listener: Option<tokio::task::JoinHandle<()>>,
+    socket_file: PathBuf,
+    requests: mpsc::Receiver<Request>,
+    events: broadcast::Sender<(u32, String)>,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        assert!(
+            std::thread::panicking() || self.listener.is_none(),
+            "i3_ipc::Server has to be shutdown explicitly before drop"
+        );
+    }
+}
+
+impl Server {
+    /// Listen on the unix socket `socket_file`.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the socket file cannot be created.
+    pub async fn listen(socket_file: PathBuf) -> Result<Self> {
+        let listener = if let Ok(m) = UnixListener::bind(&socket_file) {
+            m
+        } else {
+            fs::remove_file(&socket_file).await?;
+            UnixListener::bind(&socket_file)?
+        };
+        let (request_sender, requests) = mpsc::channel(16);
+        let (events, _) = broadcast::channel(64);
+        let event_sender = events.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((peer, _)) => {
+                        tokio::spawn(serve(peer, request_sender.clone(), event_sender.clone()));
+                    }
+                    Err(e) => tracing::error!("Accept failed = {:?}", e),
+                }
+            }
+        });
+        Ok(Self {
+            listener: Some(task),
+            socket_file,
+            requests,
+            events,
+        })
+    }
+
+    #[must_use]
+    pub fn socket_file(&self) -> &Path {
+        &self.socket_file
+    }
+
+    /// The next request of a client.
+    pub async fn next_request(&mut self) -> Option<Request> {
+        self.requests.recv().await
+    }
+
+    /// Send an event to the clients subscribed to its kind.
+    pub fn send_event(&self, kind: u32, payload: String) {
+        // Fails when no client is connected.
+        self.events.send((kind, payload)).ok();
+    }
+
+    /// Explicitly shutdown the `Server` to perform cleanup.
+    pub async fn shutdown(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.abort();
+            listener.await.ok();
+            fs::remove_file(&self.socket_file).await.ok();
+        }
+    }
+}
+
+/// The event kind of a `SUBSCRIBE` event name.
+fn event_kind(name: &str) -> Option<u32> {
+    match name {
+        "workspace" => Some(EVENT_WORKSPACE),
+        "window" => Some(EVENT_WINDOW),
+        _ => None,
+    }
+}
+
+/// Answer the requests of a client, and send it the events it subscribed to.
+async fn serve(
+    peer: UnixStream,
+    requests: mpsc::Sender<Request>,
+    events: broadcast::Sender<(u32, String)>,
+) {
+    let (mut reader, mut writer) = peer.into_split();
+    // Reading is not cancel safe, so it gets a task of its own.
+    let (message_sender, mut messages) = mpsc::channel(1);
+    let read_task = tokio::spawn(async move {
+        while let Ok(message) = read_message(&mut reader).await {
+            if message_sender.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut events = events.subscribe();
+    let mut subscribed: Vec<u32> = vec![];
+    loop {
+        let (kind, payload) = tokio::select! {
+            message = messages.recv() => {
+                let Some((kind, payload)) = message else {
+                    break;
+                };
+                if kind == SUBSCRIBE {
+                    let reply = match serde_json::from_str::<Vec<String>>(&payload) {
+                        Ok(names) => {
+                            subscribed.extend(names.iter().filter_map(|name| event_kind(name)));
+                            r#"{"success":true}"#
+                        }
+                        Err(_) => r#"{"success":false}"#,
+                    };
+                    (kind, reply.to_string())
+                } else {
+                    let (reply, answer) = oneshot::channel();
+                    if requests.send(Request { kind, payload, reply }).await.is_err() {
+                        break;
+                    }
+                    let Ok(answer) = answer.await else {
+                        break;
+                    };
+                    (kind, answer)
+                }
+            }
+            event = events.recv() => match event {
+                Ok((kind, payload)) if subscribed.contains(&kind) => (kind, payload),
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+        };
+        if write_message(&mut writer, kind, &payload).await.is_err() {
+            break;
+        }
+    }
+    read_task.abort();
+}
+
+async fn read_message<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<(u32, String)> {
+    let mut frame = [0; 14];
+    reader.read_exact(&mut frame).await?;
+    let invalid = |msg| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    if &frame[..6] != MAGIC {
+        return Err(invalid("not an i3 IPC message"));
+    }
+    let len = u32::from_ne_bytes([frame[6], frame[7], frame[8], frame[9]]);
+    let kind = u32::from_ne_bytes([frame[10], frame[11], frame[12], frame[13]]);
+    if len > MAX_PAYLOAD_LEN {
+        return Err(invalid("payload too large"));
+    }
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload).await?;
+    let payload = String::from_utf8(payload).map_err(|_| invalid("payload is not UTF-8"))?;
+    Ok((kind, payload))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    kind: u32,
+    payload: &str,
+) -> std::io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "payload too large"))?;
+    let mut message = Vec::with_capacity(14 + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&len.to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    writer.write_all(&message).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::helpers::test::temp_path;
+
+    #[tokio::test]
+    async fn clients_should_get_replies_and_subscribed_events() {
+        let socket_file = temp_path().await.unwrap();
+        let mut server = Server::listen(socket_file.clone()).await.unwrap();
+        let mut client = UnixStream::connect(&socket_file).await.unwrap();
+
+        write_message(&mut client, SUBSCRIBE, r#"["window"]"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            read_message(&mut client).await.unwrap(),
+            (SUBSCRIBE, r#"{"success":true}"#.to_string())
+        );
+
+        write_message(&mut client, GET_VERSION, "").await.unwrap();
+        let request = server.next_request().await.unwrap();
+        assert_eq!(request.kind, GET_VERSION);
+        request.reply(r#"{"major":4}"#.to_string());
+        assert_eq!(
+            read_message(&mut client).await.unwrap(),
+            (GET_VERSION, r#"{"major":4}"#.to_string())
+        );
+
+        server.send_event(EVENT_WORKSPACE, "{}".to_string());
+        server.send_event(EVENT_WINDOW, r#"{"change":"new"}"#.to_string());
+        assert_eq!(
+            read_message(&mut client).await.unwrap(),
+            (EVENT_WINDOW, r#"{"change":"new"}"#.to_string())
+        );
+
+        server.shutdown().await;
+        assert!(!socket_file.exists());
+    }
+}
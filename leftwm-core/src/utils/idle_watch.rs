@@ -0,0 +1,109 @@
+//! Idle detection for display servers, from the time since the last user input.
+//!
+//! Display servers read how long the user has been idle once [`IdleWatch::is_due`], and hand it
+//! to [`IdleWatch::update`]. They wake up after the delay [`IdleWatch::schedule_wakeup`] returns
+//! to look again, right when the user would become idle, and every [`ACTIVITY_POLL_INTERVAL`]
+//! while the user is idle to notice them coming back.
+use std::time::{Duration, Instant};
+
+/// How often to look for input while the user is idle.
+pub const ACTIVITY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether the user is idle, and when to look again.
+#[derive(Debug, Default)]
+pub struct IdleWatch {
+    timeout: Option<Duration>,
+    idle: bool,
+    wakeup: Option<Instant>,
+}
+
+impl IdleWatch {
+    /// Watch for the user being idle for `timeout`, or not at all without one.
+    #[must_use]
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.timeout.is_some()
+    }
+
+    /// Whether it is time to read the idle time again: the wakeup asked for is due, or none was
+    /// asked for yet.
+    #[must_use]
+    pub fn is_due(&self) -> bool {
+        self.is_due_at(Instant::now())
+    }
+
+    fn is_due_at(&self, now: Instant) -> bool {
+        self.wakeup.map_or(true, |wakeup| wakeup <= now)
+    }
+
+    /// Whether the user became idle (`Some(true)`) or active again (`Some(false)`), given they
+    /// have been idle for `idle_for`.
+    pub fn update(&mut self, idle_for: Duration) -> Option<bool> {
+        let idle = idle_for >= self.timeout?;
+        if idle == self.idle {
+            return None;
+        }
+        self.idle = idle;
+        Some(idle)
+    }
+
+    /// How long to wait before looking again, if no wakeup for it was requested yet.
+    pub fn schedule_wakeup(&mut self, idle_for: Duration) -> Option<Duration> {
+        self.schedule_wakeup_at(Instant::now(), idle_for)
+    }
+
+    fn schedule_wakeup_at(&mut self, now: Instant, idle_for: Duration) -> Option<Duration> {
+        let delay = if self.idle {
+            ACTIVITY_POLL_INTERVAL
+        } else {
+            self.timeout?.saturating_sub(idle_for)
+        };
+        let next = now + delay;
+        if self
+            .wakeup
+            .is_some_and(|wakeup| wakeup > now && wakeup <= next)
+        {
+            return None;
+        }
+        self.wakeup = Some(next);
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_user_should_become_idle_after_the_timeout() {
+        let secs = Duration::from_secs;
+        let mut watch = IdleWatch::new(Some(secs(60)));
+        let start = Instant::now();
+        assert!(watch.is_due_at(start));
+        assert_eq!(watch.update(secs(10)), None);
+        assert_eq!(watch.schedule_wakeup_at(start, secs(10)), Some(secs(50)));
+        assert!(!watch.is_due_at(start + secs(5)));
+        // Input in between does not bring the wakeup forward.
+        assert_eq!(watch.schedule_wakeup_at(start + secs(5), secs(0)), None);
+        assert!(watch.is_due_at(start + secs(50)));
+
+        assert_eq!(watch.update(secs(60)), Some(true));
+        assert_eq!(watch.update(secs(61)), None);
+        assert_eq!(
+            watch.schedule_wakeup_at(start + secs(60), secs(60)),
+            Some(ACTIVITY_POLL_INTERVAL)
+        );
+        assert_eq!(watch.update(secs(0)), Some(false));
+
+        let mut disabled = IdleWatch::new(None);
+        assert_eq!(disabled.update(secs(3600)), None);
+        assert_eq!(disabled.schedule_wakeup_at(start, secs(3600)), None);
+    }
+}
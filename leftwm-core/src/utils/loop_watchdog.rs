@@ -0,0 +1,180 @@
+//! Watches the event loop for work that takes too long, so freezes can be diagnosed from the log.
+use crate::models::{Handle, Mode, WindowHandle};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Handling a single event, command or action for longer than this is reported once done.
+pub const SLOW_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// Being stuck on a single piece of work for longer than this is reported while still stuck.
+pub const STALL_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// A piece of work of the event loop, only formatted once it is reported.
+#[derive(Debug, Clone)]
+pub struct Task<H: Handle> {
+    /// What is handled, such as `event` or `action`.
+    pub what: &'static str,
+    /// The name of the event, action or command.
+    pub kind: Cow<'static, str>,
+    /// The window the work is about.
+    pub window: Option<WindowHandle<H>>,
+}
+
+impl<H: Handle> fmt::Display for Task<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.what, self.kind)?;
+        if let Some(window) = &self.window {
+            write!(f, " for window {window:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A short description of the manager state, included in reports.
+#[derive(Debug, Clone, Copy)]
+pub struct Summary<H: Handle> {
+    pub windows: usize,
+    pub workspaces: usize,
+    pub focused: Option<WindowHandle<H>>,
+    pub mode: Mode<H>,
+}
+
+impl<H: Handle> fmt::Display for Summary<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} windows, {} workspaces, focused window: {:?}, mode: {:?}",
+            self.windows, self.workspaces, self.focused, self.mode
+        )
+    }
+}
+
+struct Work<H: Handle> {
+    started: Option<Instant>,
+    task: Option<Task<H>>,
+    summary: Option<Summary<H>>,
+    reported: bool,
+}
+
+/// Keeps track of the work the event loop is busy with. A background thread reports the work
+/// once it has been running for longer than the stall threshold.
+///
+/// The thread stops once the watchdog is dropped.
+pub struct LoopWatchdog<H: Handle> {
+    work: Arc<Mutex<Work<H>>>,
+}
+
+impl<H: Handle> LoopWatchdog<H> {
+    #[must_use]
+    pub fn spawn(stall_threshold: Duration) -> Self {
+        let work = Arc::new(Mutex::new(Work {
+            started: None,
+            task: None,
+            summary: None,
+            reported: false,
+        }));
+        let watched = Arc::downgrade(&work);
+        let spawned = thread::Builder::new()
+            .name("leftwm-loop-watchdog".to_string())
+            .spawn(move || watch(&watched, stall_threshold));
+        if let Err(err) = spawned {
+            tracing::warn!("Unable to start the event loop watchdog: {}", err);
+        }
+        Self { work }
+    }
+
+    /// Set a short description of the manager state, included in stall reports.
+    pub fn set_summary(&self, summary: Summary<H>) {
+        if let Ok(mut work) = self.work.lock() {
+            work.summary = Some(summary);
+        }
+    }
+
+    /// Mark the start of a piece of work.
+    pub fn begin(&self, task: Task<H>) {
+        if let Ok(mut work) = self.work.lock() {
+            work.started = Some(Instant::now());
+            work.task = Some(task);
+            work.reported = false;
+        }
+    }
+
+    /// Mark the end of the current piece of work and return how long it took.
+    pub fn end(&self) -> Duration {
+        self.work
+            .lock()
+            .ok()
+            .and_then(|mut work| work.started.take())
+            .map_or(Duration::ZERO, |started| started.elapsed())
+    }
+
+    /// Whether the current piece of work has been reported as stalled.
+    #[cfg(test)]
+    fn stalled(&self) -> bool {
+        self.work.lock().is_ok_and(|work| work.reported)
+    }
+}
+
+fn watch<H: Handle>(work: &Weak<Mutex<Work<H>>>, stall_threshold: Duration) {
+    loop {
+        thread::sleep(stall_threshold / 5);
+        let Some(work) = work.upgrade() else {
+            return;
+        };
+        let Ok(mut work) = work.lock() else {
+            return;
+        };
+        let Some(elapsed) = work.started.map(|started| started.elapsed()) else {
+            continue;
+        };
+        if elapsed >= stall_threshold && !work.reported {
+            if let (Some(task), Some(summary)) = (&work.task, &work.summary) {
+                tracing::error!(
+                    "Event loop stalled for {:?} while handling {} ({})",
+                    elapsed,
+                    task,
+                    summary
+                );
+            } else {
+                tracing::error!("Event loop stalled for {:?}", elapsed);
+            }
+            work.reported = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MockHandle;
+
+    fn task() -> Task<MockHandle> {
+        Task {
+            what: "event",
+            kind: "test".into(),
+            window: None,
+        }
+    }
+
+    #[test]
+    fn end_should_return_the_duration_of_the_work() {
+        let watchdog = LoopWatchdog::spawn(STALL_THRESHOLD);
+        watchdog.begin(task());
+        thread::sleep(Duration::from_millis(20));
+        assert!(watchdog.end() >= Duration::from_millis(20));
+        assert_eq!(watchdog.end(), Duration::ZERO);
+    }
+
+    #[test]
+    fn work_running_past_the_threshold_should_be_reported_as_stalled() {
+        let watchdog = LoopWatchdog::spawn(Duration::from_millis(50));
+        watchdog.begin(task());
+        assert!(!watchdog.stalled());
+        thread::sleep(Duration::from_millis(200));
+        assert!(watchdog.stalled());
+        watchdog.end();
+    }
+}
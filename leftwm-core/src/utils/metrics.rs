@@ -0,0 +1,367 @@
+//! Runtime statistics, served in the Prometheus text format for debugging performance.
+//!
+//! The endpoint is only started when `metrics_endpoint` is set, either to a TCP address such as
+//! `127.0.0.1:9184` or to a unix socket, `unix:<path>` or an absolute path. Both speak just enough HTTP for Prometheus
+//! and `curl --unix-socket`: every request gets the current metrics.
+use crate::errors::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::fs::FileTypeExt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener};
+
+/// Upper bounds of the command duration histogram buckets, in seconds.
+const COMMAND_DURATION_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Requests are read up to the end of their headers, at most this many bytes.
+const MAX_REQUEST_LEN: usize = 8192;
+
+/// Clients not done sending their request by then are disconnected.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The statistics collected by the event loop.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    managed_windows: usize,
+    events: BTreeMap<&'static str, u64>,
+    relayouts: u64,
+    display_server_errors: BTreeMap<String, usize>,
+    command_buckets: [u64; COMMAND_DURATION_BUCKETS.len()],
+    command_count: u64,
+    command_seconds: f64,
+}
+
+impl Metrics {
+    pub fn record_event(&mut self, kind: &'static str) {
+        *self.events.entry(kind).or_default() += 1;
+    }
+
+    pub fn record_relayout(&mut self) {
+        self.relayouts += 1;
+    }
+
+    pub fn record_command(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self
+            .command_buckets
+            .iter_mut()
+            .zip(COMMAND_DURATION_BUCKETS)
+        {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.command_count += 1;
+        self.command_seconds += seconds;
+    }
+
+    pub fn set_state(&mut self, managed_windows: usize, errors: &BTreeMap<String, usize>) {
+        self.managed_windows = managed_windows;
+        self.display_server_errors.clone_from(errors);
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        header(
+            &mut out,
+            "leftwm_managed_windows",
+            "gauge",
+            "Windows managed by leftwm.",
+        );
+        writeln!(out, "leftwm_managed_windows {}", self.managed_windows).ok();
+
+        header(
+            &mut out,
+            "leftwm_display_events_total",
+            "counter",
+            "Display events handled, by kind.",
+        );
+        for (kind, count) in &self.events {
+            writeln!(
+                out,
+                "leftwm_display_events_total{{kind=\"{}\"}} {count}",
+                escape(kind)
+            )
+            .ok();
+        }
+
+        header(
+            &mut out,
+            "leftwm_relayouts_total",
+            "counter",
+            "Times the windows were laid out and sent to the display server.",
+        );
+        writeln!(out, "leftwm_relayouts_total {}", self.relayouts).ok();
+
+        header(
+            &mut out,
+            "leftwm_display_server_errors_total",
+            "counter",
+            "Errors the display server ran into, by category.",
+        );
+        for (kind, count) in &self.display_server_errors {
+            writeln!(
+                out,
+                "leftwm_display_server_errors_total{{kind=\"{}\"}} {count}",
+                escape(kind)
+            )
+            .ok();
+        }
+
+        header(
+            &mut out,
+            "leftwm_command_duration_seconds",
+            "histogram",
+            "Time taken to handle commands.",
+        );
+        for (count, bound) in self.command_buckets.iter().zip(COMMAND_DURATION_BUCKETS) {
+            writeln!(
+                out,
+                "leftwm_command_duration_seconds_bucket{{le=\"{bound}\"}} {count}"
+            )
+            .ok();
+        }
+        writeln!(
+            out,
+            "leftwm_command_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+            self.command_count
+        )
+        .ok();
+        writeln!(
+            out,
+            "leftwm_command_duration_seconds_sum {}",
+            self.command_seconds
+        )
+        .ok();
+        writeln!(
+            out,
+            "leftwm_command_duration_seconds_count {}",
+            self.command_count
+        )
+        .ok();
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {name} {help}").ok();
+    writeln!(out, "# TYPE {name} {kind}").ok();
+}
+
+/// Escape a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves the metrics until shut down.
+#[derive(Debug, Default)]
+pub struct Endpoint {
+    listener: Option<tokio::task::JoinHandle<()>>,
+    socket_file: Option<PathBuf>,
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        assert!(
+            std::thread::panicking() || self.listener.is_none(),
+            "metrics::Endpoint has to be shutdown explicitly before drop"
+        );
+    }
+}
+
+impl Endpoint {
+    /// Listen on `address`: a TCP address, or a unix socket given as `unix:<path>` or as an
+    /// absolute path.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the address is invalid or in use, or the socket file cannot be created.
+    pub async fn listen(address: &str, metrics: Arc<Mutex<Metrics>>) -> Result<Self> {
+        let (listener, socket_file) = match address.strip_prefix("unix:") {
+            Some(path) => bind_unix(PathBuf::from(path)).await?,
+            None if address.starts_with('/') => bind_unix(PathBuf::from(address)).await?,
+            None => {
+                let addr = address.parse::<SocketAddr>().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "not a TCP or unix address")
+                })?;
+                (Listener::Tcp(TcpListener::bind(addr).await?), None)
+            }
+        };
+        let task = tokio::spawn(async move {
+            loop {
+                if let Err(e) = listener.accept(&metrics).await {
+                    tracing::error!("Accept failed = {:?}", e);
+                }
+            }
+        });
+        Ok(Self {
+            listener: Some(task),
+            socket_file,
+        })
+    }
+
+    /// Explicitly shutdown the `Endpoint` to perform cleanup.
+    pub async fn shutdown(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.abort();
+            listener.await.ok();
+            if let Some(socket_file) = self.socket_file.take() {
+                fs::remove_file(socket_file).await.ok();
+            }
+        }
+    }
+}
+
+/// A listener for either kind of endpoint.
+enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl Listener {
+    /// Accept a client, and answer it on a task of its own.
+    async fn accept(&self, metrics: &Arc<Mutex<Metrics>>) -> io::Result<()> {
+        match self {
+            Self::Tcp(listener) => {
+                let (peer, _) = listener.accept().await?;
+                tokio::spawn(respond(peer, metrics.clone()));
+            }
+            Self::Unix(listener) => {
+                let (peer, _) = listener.accept().await?;
+                tokio::spawn(respond(peer, metrics.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Listen on the unix socket `socket_file`, replacing a stale socket left there but no other
+/// kind of file.
+async fn bind_unix(socket_file: PathBuf) -> io::Result<(Listener, Option<PathBuf>)> {
+    let stale = fs::symlink_metadata(&socket_file)
+        .await
+        .is_ok_and(|meta| meta.file_type().is_socket());
+    if stale {
+        fs::remove_file(&socket_file).await?;
+    }
+    let listener = UnixListener::bind(&socket_file)?;
+    Ok((Listener::Unix(listener), Some(socket_file)))
+}
+
+/// Answer a single HTTP request with the metrics, whatever was asked for.
+async fn respond<S: AsyncRead + AsyncWrite + Unpin>(mut peer: S, metrics: Arc<Mutex<Metrics>>) {
+    let read_request = async {
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_LEN {
+            match peer.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(len) => request.extend_from_slice(&buf[..len]),
+            }
+        }
+    };
+    if tokio::time::timeout(REQUEST_TIMEOUT, read_request)
+        .await
+        .is_err()
+    {
+        return;
+    }
+    let Ok(body) = metrics.lock().map(|metrics| metrics.render()) else {
+        return;
+    };
+    let response = format!(
+        "HTTP/1.0 200 OK\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n\
+         {body}",
+        body.len()
+    );
+    if peer.write_all(response.as_bytes()).await.is_ok() {
+        peer.shutdown().await.ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::helpers::test::temp_path;
+    use tokio::net::UnixStream;
+
+    #[test]
+    fn metrics_should_render_in_the_text_format() {
+        let mut metrics = Metrics::default();
+        metrics.record_event("WindowCreate");
+        metrics.record_event("WindowCreate");
+        metrics.record_relayout();
+        metrics.record_command(Duration::from_millis(3));
+        let errors = BTreeMap::from([("Bad\"Window\"".to_string(), 2)]);
+        metrics.set_state(4, &errors);
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE leftwm_managed_windows gauge\nleftwm_managed_windows 4\n"));
+        assert!(text.contains("leftwm_display_events_total{kind=\"WindowCreate\"} 2\n"));
+        assert!(text.contains("leftwm_relayouts_total 1\n"));
+        assert!(text.contains("leftwm_display_server_errors_total{kind=\"Bad\\\"Window\\\"\"} 2\n"));
+        assert!(text.contains("leftwm_command_duration_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("leftwm_command_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("leftwm_command_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("leftwm_command_duration_seconds_count 1\n"));
+    }
+
+    #[tokio::test]
+    async fn unix_socket_should_answer_http_requests() {
+        let socket_file = temp_path().await.unwrap();
+        fs::remove_file(&socket_file).await.unwrap();
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        metrics.lock().unwrap().record_relayout();
+        let mut endpoint = Endpoint::listen(socket_file.to_str().unwrap(), metrics)
+            .await
+            .unwrap();
+
+        let mut peer = UnixStream::connect(&socket_file).await.unwrap();
+        peer.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        peer.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(response.contains("\r\n\r\n# HELP leftwm_managed_windows"));
+        assert!(response.contains("leftwm_relayouts_total 1\n"));
+
+        endpoint.shutdown().await;
+        assert!(!socket_file.exists());
+    }
+
+    #[tokio::test]
+    async fn only_sockets_should_be_replaced_by_the_endpoint() {
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        let file = temp_path().await.unwrap();
+        fs::write(&file, "keep me").await.unwrap();
+        let address = format!("unix:{}", file.display());
+        assert!(Endpoint::listen(&address, metrics.clone()).await.is_err());
+        assert_eq!(fs::read_to_string(&file).await.unwrap(), "keep me");
+        fs::remove_file(&file).await.unwrap();
+
+        assert!(Endpoint::listen("metrics.sock", metrics.clone())
+            .await
+            .is_err());
+
+        let stale = UnixListener::bind(&file).unwrap();
+        drop(stale);
+        let mut endpoint = Endpoint::listen(&address, metrics).await.unwrap();
+        endpoint.shutdown().await;
+        assert!(!file.exists());
+    }
+}
@@ -2,13 +2,13 @@ use bitflags::bitflags;
 use serde::{de::Visitor, Deserialize, Serialize};
 
 bitflags! {
-    /// Represents the state of modifier keys
+    /// Represents the state of modifier keys, with the bits of X modifier masks
     #[derive(Clone, Debug, PartialEq, Eq)]
     pub struct ModMask: u16 {
         /// Used as the zero value
         const Zero = 0;
-        const Any = 1;
-        const Shift = 1 << 1;
+        const Any = 1 << 15;
+        const Shift = 1;
         const Control = 1 << 2;
         /// Mod1
         const Alt = 1 << 3;
@@ -69,6 +69,23 @@ impl From<Button> for u8 {
     }
 }
 
+/// `modifiers` with each combination of the lock modifiers in `locks` added, so grabbing all of
+/// them matches whichever locks are on.
+#[must_use]
+pub fn with_lock_combinations(modifiers: u16, locks: u16) -> Vec<u16> {
+    // Grabs with any modifier already match every lock.
+    if modifiers & ModMask::Any.bits() != 0 {
+        return vec![modifiers];
+    }
+    let mut combinations = vec![modifiers];
+    let mut subset = locks;
+    while subset != 0 {
+        combinations.push(modifiers | subset);
+        subset = (subset - 1) & locks;
+    }
+    combinations
+}
+
 #[must_use]
 pub fn into_modmask(keys: &[String]) -> ModMask {
     let mut mask = ModMask::Zero;
@@ -147,3 +164,22 @@ impl<'de> Deserialize<'de> for ModMask {
         deserializer.deserialize_u16(ModmaskVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_combination_of_the_locks_should_be_grabbed() {
+        let mut combinations = with_lock_combinations(0b1000, 0b1_0010);
+        combinations.sort_unstable();
+        assert_eq!(combinations, [0b1000, 0b1010, 0b1_1000, 0b1_1010]);
+        assert_eq!(with_lock_combinations(0b1000, 0), [0b1000]);
+    }
+
+    #[test]
+    fn grabs_with_any_modifier_should_not_be_repeated() {
+        let any = ModMask::Any.bits();
+        assert_eq!(with_lock_combinations(any, 0b1_0010), [any]);
+    }
+}
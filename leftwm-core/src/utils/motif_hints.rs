@@ -0,0 +1,63 @@
+//! Decoding of `_MOTIF_WM_HINTS`, through which clients ask to be shown without decorations.
+//!
+//! The property holds the flags, functions, decorations, input mode and status of the window,
+//! in that order. Only the decorations are of interest here.
+
+/// The decorations field is set.
+const HINTS_DECORATIONS: u32 = 1 << 1;
+/// All decorations, the other decoration bits then being those left out.
+const DECORATION_ALL: u32 = 1 << 0;
+const DECORATION_BORDER: u32 = 1 << 1;
+
+/// Whether the hints ask for the window to have no border.
+#[must_use]
+pub fn wants_no_border(hints: &[u32]) -> bool {
+    let [flags, _functions, decorations, ..] = *hints else {
+        return false;
+    };
+    if flags & HINTS_DECORATIONS == 0 {
+        return false;
+    }
+    if decorations & DECORATION_ALL == 0 {
+        decorations & DECORATION_BORDER == 0
+    } else {
+        decorations & DECORATION_BORDER != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_decorations_should_mean_no_border() {
+        assert!(wants_no_border(&[HINTS_DECORATIONS, 0, 0, 0, 0]));
+        assert!(wants_no_border(&[
+            HINTS_DECORATIONS,
+            0,
+            DECORATION_ALL | DECORATION_BORDER,
+            0,
+            0
+        ]));
+    }
+
+    #[test]
+    fn borders_should_be_kept_unless_left_out() {
+        assert!(!wants_no_border(&[0, 0, 0, 0, 0]));
+        assert!(!wants_no_border(&[
+            HINTS_DECORATIONS,
+            0,
+            DECORATION_ALL,
+            0,
+            0
+        ]));
+        assert!(!wants_no_border(&[
+            HINTS_DECORATIONS,
+            0,
+            DECORATION_BORDER,
+            0,
+            0
+        ]));
+        assert!(!wants_no_border(&[HINTS_DECORATIONS]));
+    }
+}
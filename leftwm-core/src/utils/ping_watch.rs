@@ -0,0 +1,117 @@
+//! Detection of hung clients, from the `_NET_WM_PING` protocol.
+//!
+//! Display servers ping windows they ask to close, and hand the replies to [`PingWatch::pong`].
+//! Windows that did not reply within [`PING_TIMEOUT`] are returned once by
+//! [`PingWatch::take_unresponsive`], and a late reply reports them as responsive again.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// How long a client has to answer a ping before it is considered hung.
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Keeps track of the windows that were pinged and did not reply yet.
+#[derive(Debug)]
+pub struct PingWatch<W> {
+    /// When each window was pinged, or `None` once it was reported unresponsive.
+    pinged: HashMap<W, Option<Instant>>,
+    wakeup: Option<Instant>,
+}
+
+impl<W> Default for PingWatch<W> {
+    fn default() -> Self {
+        Self {
+            pinged: HashMap::new(),
+            wakeup: None,
+        }
+    }
+}
+
+impl<W: Hash + Eq + Copy> PingWatch<W> {
+    /// Record a ping sent to `window`. A window already waiting keeps its first ping.
+    pub fn ping(&mut self, window: W) {
+        self.ping_at(Instant::now(), window);
+    }
+
+    /// Record a reply from `window`, returning whether it had been reported unresponsive.
+    pub fn pong(&mut self, window: &W) -> bool {
+        matches!(self.pinged.remove(window), Some(None))
+    }
+
+    /// Stop waiting for a reply from a window that is gone.
+    pub fn forget(&mut self, window: &W) {
+        self.pinged.remove(window);
+    }
+
+    /// The windows that did not reply in time and were not reported yet.
+    pub fn take_unresponsive(&mut self) -> Vec<W> {
+        self.take_unresponsive_at(Instant::now())
+    }
+
+    /// How long to wait before the next ping times out, if no wakeup for it was requested yet.
+    pub fn schedule_wakeup(&mut self) -> Option<Duration> {
+        self.schedule_wakeup_at(Instant::now())
+    }
+
+    fn ping_at(&mut self, now: Instant, window: W) {
+        self.pinged.entry(window).or_insert(Some(now));
+    }
+
+    fn take_unresponsive_at(&mut self, now: Instant) -> Vec<W> {
+        let mut unresponsive = vec![];
+        for (window, pinged) in &mut self.pinged {
+            if pinged.is_some_and(|at| now.duration_since(at) >= PING_TIMEOUT) {
+                *pinged = None;
+                unresponsive.push(*window);
+            }
+        }
+        unresponsive
+    }
+
+    fn schedule_wakeup_at(&mut self, now: Instant) -> Option<Duration> {
+        let next_due = self
+            .pinged
+            .values()
+            .flatten()
+            .min()
+            .map(|at| *at + PING_TIMEOUT)?;
+        if self
+            .wakeup
+            .is_some_and(|wakeup| wakeup > now && wakeup <= next_due)
+        {
+            return None;
+        }
+        self.wakeup = Some(next_due);
+        Some(next_due.saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_should_be_reported_once_when_they_do_not_reply_in_time() {
+        let mut watch = PingWatch::default();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        watch.ping_at(at(0), 1);
+        watch.ping_at(at(2), 2);
+        watch.ping_at(at(3), 1);
+        assert_eq!(
+            watch.schedule_wakeup_at(at(3)),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(watch.schedule_wakeup_at(at(4)), None);
+
+        assert!(watch.take_unresponsive_at(at(4)).is_empty());
+        assert_eq!(watch.take_unresponsive_at(at(5)), vec![1]);
+        assert!(watch.take_unresponsive_at(at(6)).is_empty());
+        assert!(!watch.pong(&2));
+        assert!(watch.take_unresponsive_at(at(10)).is_empty());
+
+        // A late reply means the window is responsive again.
+        assert!(watch.pong(&1));
+        assert!(!watch.pong(&1));
+    }
+}
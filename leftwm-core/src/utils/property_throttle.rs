@@ -0,0 +1,138 @@
+//! Rate limiting of window property changes, for clients updating a property many times a second.
+//!
+//! The first change of a property is handled right away. Further changes within
+//! [`PROPERTY_CHANGE_INTERVAL`] are coalesced into a single change handled once the interval has
+//! passed, which reads the final value of the property.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Changes to the same property of the same window are handled at most once per interval.
+pub const PROPERTY_CHANGE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Keeps track of the properties that changed recently, keyed by window and property.
+#[derive(Debug)]
+pub struct PropertyThrottle<K> {
+    last_handled: HashMap<K, Instant>,
+    pending: Vec<K>,
+    wakeup: Option<Instant>,
+}
+
+impl<K> Default for PropertyThrottle<K> {
+    fn default() -> Self {
+        Self {
+            last_handled: HashMap::new(),
+            pending: vec![],
+            wakeup: None,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Copy> PropertyThrottle<K> {
+    /// Whether a property change should be handled now. Otherwise it is returned by
+    /// [`Self::take_due`] once the interval has passed.
+    pub fn allow(&mut self, key: K) -> bool {
+        self.allow_at(Instant::now(), key)
+    }
+
+    /// The coalesced property changes that are due to be handled.
+    pub fn take_due(&mut self) -> Vec<K> {
+        self.take_due_at(Instant::now())
+    }
+
+    /// How long to wait before the next coalesced change is due, if no wakeup for it was
+    /// requested yet.
+    pub fn schedule_wakeup(&mut self) -> Option<Duration> {
+        self.schedule_wakeup_at(Instant::now())
+    }
+
+    fn allow_at(&mut self, now: Instant, key: K) -> bool {
+        match self.last_handled.get(&key) {
+            Some(last) if now.duration_since(*last) < PROPERTY_CHANGE_INTERVAL => {
+                if !self.pending.contains(&key) {
+                    self.pending.push(key);
+                }
+                false
+            }
+            _ => {
+                self.last_handled.insert(key, now);
+                true
+            }
+        }
+    }
+
+    fn take_due_at(&mut self, now: Instant) -> Vec<K> {
+        let is_due = |last: Option<&Instant>| {
+            last.map_or(true, |last| {
+                now.duration_since(*last) >= PROPERTY_CHANGE_INTERVAL
+            })
+        };
+        let (due, waiting): (Vec<K>, Vec<K>) = self
+            .pending
+            .drain(..)
+            .partition(|key| is_due(self.last_handled.get(key)));
+        self.pending = waiting;
+        for key in &due {
+            self.last_handled.insert(*key, now);
+        }
+        let pending = &self.pending;
+        self.last_handled
+            .retain(|key, last| !is_due(Some(last)) || pending.contains(key));
+        due
+    }
+
+    fn schedule_wakeup_at(&mut self, now: Instant) -> Option<Duration> {
+        let next_due = self
+            .pending
+            .iter()
+            .filter_map(|key| self.last_handled.get(key))
+            .min()
+            .map(|last| *last + PROPERTY_CHANGE_INTERVAL)?;
+        if self
+            .wakeup
+            .is_some_and(|wakeup| wakeup > now && wakeup <= next_due)
+        {
+            return None;
+        }
+        self.wakeup = Some(next_due);
+        Some(next_due.saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_within_the_interval_should_be_coalesced() {
+        let mut throttle = PropertyThrottle::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        assert!(throttle.allow_at(at(0), (1, 1)));
+        assert!(!throttle.allow_at(at(10), (1, 1)));
+        assert!(!throttle.allow_at(at(20), (1, 1)));
+        // Other properties and windows are not affected.
+        assert!(throttle.allow_at(at(20), (1, 2)));
+        assert!(throttle.allow_at(at(20), (2, 1)));
+
+        assert!(throttle.take_due_at(at(50)).is_empty());
+        assert_eq!(throttle.take_due_at(at(100)), vec![(1, 1)]);
+        assert!(throttle.take_due_at(at(300)).is_empty());
+        assert!(throttle.allow_at(at(300), (1, 1)));
+    }
+
+    #[test]
+    fn a_wakeup_should_be_requested_once_per_due_change() {
+        let mut throttle = PropertyThrottle::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        assert_eq!(throttle.schedule_wakeup_at(at(0)), None);
+        throttle.allow_at(at(0), (1, 1));
+        throttle.allow_at(at(30), (1, 1));
+        assert_eq!(
+            throttle.schedule_wakeup_at(at(30)),
+            Some(Duration::from_millis(70))
+        );
+        assert_eq!(throttle.schedule_wakeup_at(at(40)), None);
+    }
+}
@@ -0,0 +1,315 @@
+//! Runs commands after a delay or on a schedule.
+//!
+//! Every scheduled command gets a tokio task of its own, sending the command to the event loop
+//! when it is due, where it is handled like one read from the command pipe. Commands scheduled
+//! from the config live as long as the scheduler, while those scheduled by commands can be
+//! cancelled.
+use crate::config::Schedule;
+use crate::models::Handle;
+use crate::Command;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+
+/// Sends scheduled commands to the event loop when they are due.
+#[derive(Debug)]
+pub struct Scheduler<H: Handle> {
+    sender: mpsc::UnboundedSender<Command<H>>,
+    receiver: mpsc::UnboundedReceiver<Command<H>>,
+    configured: Vec<JoinHandle<()>>,
+    scheduled: Vec<JoinHandle<()>>,
+}
+
+impl<H: Handle> Default for Scheduler<H> {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver,
+            configured: vec![],
+            scheduled: vec![],
+        }
+    }
+}
+
+impl<H: Handle> Drop for Scheduler<H> {
+    fn drop(&mut self) {
+        for task in self.configured.iter().chain(&self.scheduled) {
+            task.abort();
+        }
+    }
+}
+
+impl<H: Handle> Scheduler<H> {
+    /// Run `command` on `schedule`, for as long as the scheduler lives, or until cancelled when
+    /// not `configured`.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the schedule is invalid, see [`Schedule::validate`].
+    pub fn schedule(
+        &mut self,
+        schedule: &Schedule,
+        command: Command<H>,
+        configured: bool,
+    ) -> Result<(), String> {
+        schedule.validate()?;
+        let sender = self.sender.clone();
+        let task = match schedule {
+            Schedule::After(secs) => {
+                let delay = Duration::from_secs(*secs);
+                tokio::spawn(async move {
+                    time::sleep(delay).await;
+                    sender.send(command).ok();
+                })
+            }
+            Schedule::Every(secs) => {
+                let period = Duration::from_secs(*secs);
+                tokio::spawn(async move {
+                    let mut interval = time::interval_at(Instant::now() + period, period);
+                    loop {
+                        interval.tick().await;
+                        if sender.send(command.clone()).is_err() {
+                            return;
+                        }
+                    }
+                })
+            }
+            Schedule::Cron(expression) => {
+                let cron = Cron::from_str(expression)?;
+                tokio::spawn(async move {
+                    loop {
+                        let now = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default();
+                        let next_minute = Duration::from_secs((now.as_secs() / 60 + 1) * 60);
+                        time::sleep(next_minute.saturating_sub(now)).await;
+                        let due =
+                            local_time(next_minute.as_secs()).is_some_and(|t| cron.matches(&t));
+                        if due && sender.send(command.clone()).is_err() {
+                            return;
+                        }
+                    }
+                })
+            }
+        };
+        if configured {
+            self.configured.push(task);
+        } else {
+            self.scheduled.retain(|task| !task.is_finished());
+            self.scheduled.push(task);
+        }
+        Ok(())
+    }
+
+    /// Send `command` once after `delay`, for the manager itself. It is not cancelled by
+    /// [`Self::cancel`].
+    pub fn send_after(&self, delay: Duration, command: Command<H>) {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            time::sleep(delay).await;
+            sender.send(command).ok();
+        });
+    }
+
+    /// Cancel the commands scheduled by commands.
+    pub fn cancel(&mut self) {
+        for task in self.scheduled.drain(..) {
+            task.abort();
+        }
+    }
+
+    /// The next command which is due.
+    pub async fn next_command(&mut self) -> Option<Command<H>> {
+        self.receiver.recv().await
+    }
+}
+
+/// A local time, as matched by cron expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub minute: u32,
+    pub hour: u32,
+    pub day: u32,
+    pub month: u32,
+    /// From 0 for Sunday to 6.
+    pub weekday: u32,
+}
+
+/// The local time `secs` seconds after the epoch.
+fn local_time(secs: u64) -> Option<LocalTime> {
+    let time = libc::time_t::try_from(secs).ok()?;
+    // SAFETY: `localtime_r` only writes to `tm`, which lives until it returns.
+    let tm = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return None;
+        }
+        tm
+    };
+    let field = |value: libc::c_int| u32::try_from(value).ok();
+    Some(LocalTime {
+        minute: field(tm.tm_min)?,
+        hour: field(tm.tm_hour)?,
+        day: field(tm.tm_mday)?,
+        month: field(tm.tm_mon)? + 1,
+        weekday: field(tm.tm_wday)?,
+    })
+}
+
+/// A parsed cron expression, the values allowed for each field as bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for Cron {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!("cron expression `{s}` does not have 5 fields"));
+        };
+        let weekday_bits = parse_field(weekdays, 0, 7)?;
+        Ok(Self {
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            // Both 0 and 7 are Sunday.
+            weekdays: (weekday_bits | weekday_bits >> 7) & 0x7f,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+}
+
+impl Cron {
+    /// Whether the command is due at `time`. As with cron, when both the day of the month and
+    /// the day of the week are restricted, either of them matching is enough.
+    #[must_use]
+    pub fn matches(&self, time: &LocalTime) -> bool {
+        let has = |bits: u64, value: u32| bits & (1 << value) != 0;
+        let day = has(self.days, time.day);
+        let weekday = has(self.weekdays, time.weekday);
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        has(self.minutes, time.minute)
+            && has(self.hours, time.hour)
+            && has(self.months, time.month)
+            && day_matches
+    }
+}
+
+/// The values allowed by a field made of `*`, values, ranges and steps, e.g. `1-5,*/15`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("invalid cron field `{field}`");
+    let value = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|v| (min..=max).contains(v))
+            .ok_or_else(invalid)
+    };
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (value(first)?, value(last)?),
+            // A single value with a step runs up to the end.
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if first > last {
+            return Err(invalid());
+        }
+        for v in (first..=last).step_by(step) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minute: u32, hour: u32, day: u32, month: u32, weekday: u32) -> LocalTime {
+        LocalTime {
+            minute,
+            hour,
+            day,
+            month,
+            weekday,
+        }
+    }
+
+    #[test]
+    fn cron_expressions_should_match_like_cron() {
+        let hourly_on_weekdays = Cron::from_str("0 9-17/2 * * 1-5").unwrap();
+        assert!(hourly_on_weekdays.matches(&at(0, 11, 14, 3, 5)));
+        assert!(!hourly_on_weekdays.matches(&at(0, 12, 14, 3, 5)));
+        assert!(!hourly_on_weekdays.matches(&at(0, 11, 15, 3, 6)));
+        assert!(!hourly_on_weekdays.matches(&at(30, 11, 14, 3, 5)));
+
+        // Either the day of the month or the day of the week.
+        let firsts_and_sundays = Cron::from_str("*/30 0 1 * 7").unwrap();
+        assert!(firsts_and_sundays.matches(&at(30, 0, 1, 6, 3)));
+        assert!(firsts_and_sundays.matches(&at(0, 0, 9, 6, 0)));
+        assert!(!firsts_and_sundays.matches(&at(0, 0, 10, 6, 1)));
+
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(Cron::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn scheduled_commands_should_be_sent_when_due_until_cancelled() {
+        let mut scheduler = Scheduler::<crate::models::MockHandle>::default();
+        scheduler
+            .schedule(&Schedule::After(5), Command::SwapScreens, false)
+            .unwrap();
+        scheduler
+            .schedule(&Schedule::Every(2), Command::RotateTag, false)
+            .unwrap();
+        assert!(scheduler
+            .schedule(&Schedule::Every(0), Command::RotateTag, false)
+            .is_err());
+
+        let mut due = vec![];
+        for _ in 0..3 {
+            due.push(scheduler.next_command().await.unwrap());
+        }
+        assert_eq!(
+            due,
+            vec![Command::RotateTag, Command::RotateTag, Command::SwapScreens]
+        );
+
+        scheduler.cancel();
+        time::sleep(Duration::from_secs(10)).await;
+        assert!(scheduler.receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Status notifications to systemd, for running leftwm as a `Type=notify` user service.
+//!
+//! Notifications are only sent when systemd asked for them by setting `NOTIFY_SOCKET`, see
+//! `sd_notify(3)`. As they come from `leftwm-worker`, the unit needs `NotifyAccess=all`.
+use std::env;
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+/// Send a notification like `READY=1` to systemd, if it expects notifications.
+pub fn notify(state: &str) {
+    let Some(socket) = env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    if let Err(err) = notify_to(&socket.to_string_lossy(), state) {
+        tracing::warn!("Unable to notify systemd of {:?}: {}", state, err);
+    }
+}
+
+/// How often to send `WATCHDOG=1`, if the service has a watchdog: half its timeout, as
+/// recommended by `sd_watchdog_enabled(3)`. `WATCHDOG_PID` is ignored, it names the `leftwm`
+/// process that started the worker.
+#[must_use]
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec / 2))
+}
+
+fn notify_to(socket: &str, state: &str) -> io::Result<()> {
+    let datagram = UnixDatagram::unbound()?;
+    match socket.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        _ => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifications_should_reach_the_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let systemd = UnixDatagram::bind(&path).unwrap();
+
+        notify_to(path.to_str().unwrap(), "READY=1").unwrap();
+        let mut buf = [0; 16];
+        let len = systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+    }
+}
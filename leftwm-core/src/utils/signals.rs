@@ -0,0 +1,75 @@
+//! Runs the commands configured for signals, so scripts can poke the window manager with `kill`
+//! instead of going through the command pipe.
+use crate::config::Signal;
+use crate::models::Handle;
+use crate::utils::command_pipe;
+use crate::Command;
+use futures::future::{self, FutureExt};
+use std::collections::HashMap;
+use tokio::signal::unix;
+
+/// Receives the signals commands are configured for.
+#[derive(Debug)]
+pub struct Signals<H: Handle> {
+    listeners: Vec<(unix::Signal, Command<H>)>,
+}
+
+impl<H: Handle> Signals<H> {
+    /// Listen to the signals of `commands`, and to `SIGHUP` for `SoftReload` unless configured
+    /// otherwise. Commands which cannot be parsed or signals which cannot be listened to are
+    /// logged and left out.
+    #[must_use]
+    pub fn new(mut commands: HashMap<Signal, String>) -> Self {
+        commands
+            .entry(Signal::Hup)
+            .or_insert_with(|| "SoftReload".to_string());
+        let mut listeners = vec![];
+        for (signal, command) in commands {
+            let parsed = match command_pipe::parse_command(&command) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    tracing::error!("Unable to parse `{}` for {:?}: {}", command, signal, err);
+                    continue;
+                }
+            };
+            match unix::signal(signal.kind()) {
+                Ok(listener) => listeners.push((listener, parsed)),
+                Err(err) => tracing::error!("Unable to listen to {:?}: {}", signal, err),
+            }
+        }
+        Self { listeners }
+    }
+
+    /// The command of the next signal received.
+    pub async fn next_command(&mut self) -> Option<Command<H>> {
+        if self.listeners.is_empty() {
+            return future::pending().await;
+        }
+        let received = self.listeners.iter_mut().map(|(listener, command)| {
+            listener
+                .recv()
+                .map(|r| r.map(|()| command.clone()))
+                .boxed_local()
+        });
+        future::select_all(received).await.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MockHandle;
+
+    #[tokio::test]
+    async fn signals_should_run_their_commands() {
+        let commands = HashMap::from([
+            (Signal::Usr2, "SwapScreens".to_string()),
+            (Signal::Usr1, "GoToTag nine".to_string()),
+        ]);
+        let mut signals = Signals::<MockHandle>::new(commands);
+        assert_eq!(signals.listeners.len(), 2);
+
+        nix::sys::signal::raise(nix::sys::signal::SIGUSR2).unwrap();
+        assert_eq!(signals.next_command().await, Some(Command::SwapScreens));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::errors::{LeftError, Result};
 use crate::models::dto::ManagerState;
 use crate::models::Handle;
+use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::fs;
@@ -60,9 +61,18 @@ impl StateSocket {
         &mut self,
         raw_state: &crate::state::State<H>,
     ) -> Result<()> {
+        let state: ManagerState = raw_state.into();
+        self.write(&state).await
+    }
+
+    /// Send `value` as a JSON line to the peers, if it changed since the last time.
+    ///
+    /// # Errors
+    /// Will return Err if a mut ref to the peer is unavailable.
+    /// Will return error if `value` cannot be serialized
+    pub async fn write(&mut self, value: &impl Serialize) -> Result<()> {
         if self.listener.is_some() {
-            let state: ManagerState = raw_state.into();
-            let mut json = serde_json::to_string(&state)?;
+            let mut json = serde_json::to_string(value)?;
             json.push('\n');
             let mut state = self.state.lock().await;
 
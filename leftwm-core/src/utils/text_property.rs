@@ -0,0 +1,111 @@
+//! Decoding of X11 text properties, such as window titles.
+//!
+//! Clients set titles as `UTF8_STRING`, as Latin-1 `STRING` or as `COMPOUND_TEXT`. Invalid bytes
+//! are replaced instead of failing, so every window gets a usable title.
+
+/// The encoding of a text property, from its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Latin1,
+    CompoundText,
+}
+
+const ESC: u8 = 0x1b;
+
+/// Decode the value of a text property. A trailing null byte is ignored.
+#[must_use]
+pub fn decode(bytes: &[u8], encoding: TextEncoding) -> String {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    match encoding {
+        TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        TextEncoding::Latin1 => decode_latin1(bytes),
+        TextEncoding::CompoundText => decode_compound_text(bytes),
+    }
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| char::from(b)).collect()
+}
+
+/// Decode the parts of `COMPOUND_TEXT` that can be: ASCII, Latin-1 and embedded UTF-8 segments.
+/// Text in other character sets is replaced by `U+FFFD`.
+fn decode_compound_text(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    // Latin-1 is designated to the right half by default.
+    let mut latin1_right_half = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != ESC {
+            match bytes[i] {
+                b @ 0xa0..=0xff if latin1_right_half => text.push(char::from(b)),
+                0xa0..=0xff => text.push(char::REPLACEMENT_CHARACTER),
+                b => text.push(char::from(b)),
+            }
+            i += 1;
+            continue;
+        }
+        let sequence = escape_sequence(&bytes[i..]);
+        match sequence {
+            // Start of a UTF-8 segment, up to `ESC % @`.
+            [ESC, b'%', b'G'] => {
+                let rest = &bytes[i + sequence.len()..];
+                let end = rest
+                    .windows(3)
+                    .position(|w| w == [ESC, b'%', b'@'])
+                    .unwrap_or(rest.len());
+                text.push_str(&String::from_utf8_lossy(&rest[..end]));
+                i += sequence.len() + (end + 3).min(rest.len());
+                continue;
+            }
+            [ESC, b'-', b'A'] => latin1_right_half = true,
+            [ESC, b'-' | b')' | b'$', ..] => latin1_right_half = false,
+            _ => {}
+        }
+        i += sequence.len();
+    }
+    text
+}
+
+/// The escape sequence at the start of `bytes`: `ESC`, intermediate bytes and a final byte.
+fn escape_sequence(bytes: &[u8]) -> &[u8] {
+    let intermediates = bytes[1..]
+        .iter()
+        .take_while(|b| (0x20..=0x2f).contains(*b))
+        .count();
+    let len = (2 + intermediates).min(bytes.len());
+    &bytes[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_utf8_should_be_replaced() {
+        assert_eq!(
+            decode(b"caf\xc3\xa9 \xff", TextEncoding::Utf8),
+            "café \u{fffd}"
+        );
+    }
+
+    #[test]
+    fn latin1_should_be_decoded() {
+        assert_eq!(decode(b"caf\xe9\0", TextEncoding::Latin1), "café");
+    }
+
+    #[test]
+    fn compound_text_should_decode_latin1_and_utf8_segments() {
+        let bytes = b"caf\xe9 \x1b%G\xe2\x9c\x93\x1b%@ ok";
+        assert_eq!(decode(bytes, TextEncoding::CompoundText), "café ✓ ok");
+    }
+
+    #[test]
+    fn compound_text_in_other_charsets_should_be_replaced() {
+        let bytes = b"a\x1b$)B\xb0\xa1\x1b-Ab\xe9";
+        assert_eq!(
+            decode(bytes, TextEncoding::CompoundText),
+            "a\u{fffd}\u{fffd}b\u{e9}"
+        );
+    }
+}
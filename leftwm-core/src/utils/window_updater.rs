@@ -1,6 +1,8 @@
-use crate::config::Config;
+use crate::config::{Config, TagOverrides};
 use crate::display_servers::DisplayServer;
-use crate::models::{Handle, Manager};
+use crate::handlers::command_handler::prune_containers;
+use crate::models::{Handle, Manager, Margins, WindowType, Workspace};
+use crate::DisplayAction;
 
 impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
     /*
@@ -9,17 +11,382 @@ impl<H: Handle, C: Config, SERVER: DisplayServer<H>> Manager<H, C, SERVER> {
      */
     pub fn update_windows(&mut self) {
         // set all tagged windows as visible
-        self.state
-            .windows
-            .iter_mut()
-            .for_each(|w| w.set_visible(w.tag.is_none()));
+        let presenting = self.state.presentation_mode;
+        let hide_docks = presenting && self.state.presentation_mode_hides_docks;
+        let gaps_hidden = self.state.gaps_hidden;
+        let smart_gaps = self.state.smart_gaps;
+        prune_containers(&mut self.state.windows);
+        self.state.windows.iter_mut().for_each(|w| {
+            w.set_visible(w.tag.is_none() && !(hide_docks && w.r#type == WindowType::Dock));
+        });
+        let no_overrides = TagOverrides::default();
 
-        for ws in &self.state.workspaces {
+        for ws in &mut self.state.workspaces {
             let windows = &mut self.state.windows;
             let all_tags = &self.state.tags;
+            ws.hidden_in_stack = 0;
             if let Some(Some(tag)) = ws.tag.map(|tag_id| all_tags.get(tag_id)) {
-                tag.update_windows(windows, ws, &mut self.state.layout_manager);
+                let overrides = self
+                    .state
+                    .tag_overrides
+                    .get(&tag.label)
+                    .unwrap_or(&no_overrides);
+                // With smart gaps, a single tiled window takes the whole workspace.
+                let single = smart_gaps
+                    && windows
+                        .iter()
+                        .filter(|w| ws.is_managed(w) && !w.floating())
+                        .count()
+                        == 1;
+                let gapless = presenting || gaps_hidden || single;
+                let margin = match overrides.margin {
+                    _ if gapless => Margins::new(0),
+                    Some(margin) => margin,
+                    None => self.config.margin(),
+                };
+                for window in windows
+                    .iter_mut()
+                    .filter(|w| ws.is_displaying(w) && w.is_managed())
+                {
+                    window.margin = margin;
+                    let border_colors = window.rule_border_colors.or(&overrides.border_colors);
+                    if window.border_colors != border_colors {
+                        window.border_colors = border_colors.clone();
+                        let act =
+                            DisplayAction::SetWindowBorderColors(window.handle, border_colors);
+                        self.state.actions.push_back(act);
+                    }
+                }
+
+                let overridden;
+                let shown = match overrides.workspace_margin {
+                    _ if presenting => {
+                        overridden = Workspace {
+                            margin: Margins::new(0),
+                            gutters: vec![],
+                            xyhw_avoided: if hide_docks { ws.xyhw } else { ws.xyhw_avoided },
+                            ..ws.clone()
+                        };
+                        &overridden
+                    }
+                    _ if gapless => {
+                        overridden = Workspace {
+                            margin: Margins::new(0),
+                            ..ws.clone()
+                        };
+                        &overridden
+                    }
+                    Some(margin) => {
+                        overridden = Workspace {
+                            margin,
+                            ..ws.clone()
+                        };
+                        &overridden
+                    }
+                    None => &*ws,
+                };
+                ws.hidden_in_stack =
+                    tag.update_windows(windows, shown, &mut self.state.layout_manager);
             }
         }
+        // Urgency is not shown on the windows being presented.
+        for window in &mut self.state.windows {
+            let hidden = presenting && window.visible();
+            window.set_urgency_hidden(hidden);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TagOverrides;
+    use crate::layouts::LayoutManager;
+    use crate::models::{
+        BorderColors, Margins, MockHandle, TestManager, WindowChange, WindowHandle, WindowType,
+        Xyhw, XyhwBuilder,
+    };
+    use crate::{Command, DisplayAction, Manager, Window};
+
+    fn tiled(manager: &TestManager) -> Vec<Xyhw> {
+        manager
+            .state
+            .windows
+            .iter()
+            .filter(|w| !w.floating() && w.visible())
+            .map(|w| w.normal)
+            .collect()
+    }
+
+    fn assert_tiles_fill(tiles: &[Xyhw], x: i32, y: i32, w: i32, h: i32) {
+        for tile in tiles {
+            assert!(
+                tile.x() >= x && tile.y() >= y,
+                "{tile:?} starts outside the area"
+            );
+            assert!(
+                tile.x() + tile.w() <= x + w && tile.y() + tile.h() <= y + h,
+                "{tile:?} ends outside the area"
+            );
+        }
+        let area: i32 = tiles.iter().map(|t| t.w() * t.h()).sum();
+        assert_eq!(
+            area,
+            w * h,
+            "tiles should cover the area without overlapping"
+        );
+    }
+
+    #[test]
+    fn tiled_windows_should_fill_the_workspace() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 3);
+        manager.update_windows();
+        let tiles = tiled(&manager);
+        assert_eq!(tiles.len(), 3);
+        assert_tiles_fill(&tiles, 0, 0, 800, 600);
+    }
+
+    #[test]
+    fn tiled_windows_should_respect_struts() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 2);
+        let workspace = &mut manager.state.workspaces[0];
+        workspace.avoid.push(
+            XyhwBuilder {
+                x: 0,
+                y: 0,
+                w: 800,
+                h: 30,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        workspace.update_avoided_areas();
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 0, 30, 800, 570);
+    }
+
+    #[test]
+    fn tiled_windows_should_respect_workspace_margins() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 2);
+        manager.state.workspaces[0].margin = Margins::new(10);
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 10, 10, 780, 580);
+    }
+
+    #[test]
+    fn floating_windows_should_be_excluded_from_tiling() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 3);
+        manager.state.windows[1].set_floating(true);
+        manager.update_windows();
+        let tiles = tiled(&manager);
+        assert_eq!(tiles.len(), 2);
+        assert_tiles_fill(&tiles, 0, 0, 800, 600);
+        let floating = &manager.state.windows[1];
+        assert!(floating.visible());
+        assert_eq!(floating.normal, manager.state.workspaces[0].xyhw);
+    }
+
+    #[test]
+    fn tag_overrides_should_apply_to_the_windows_of_the_tag() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 2);
+        let overrides = TagOverrides {
+            margin: Some(Margins::new(0)),
+            workspace_margin: Some(Margins::new(5)),
+            border_width: Some(3),
+            border_colors: BorderColors {
+                default: Some("#00FF00".to_string()),
+                ..BorderColors::default()
+            },
+            layout: None,
+        };
+        manager
+            .config
+            .tag_overrides
+            .insert("1".to_string(), overrides.clone());
+        manager.load_theme_config();
+        manager.state.actions.clear();
+        manager.update_windows();
+
+        assert_tiles_fill(&tiled(&manager), 5, 5, 790, 590);
+        for window in &manager.state.windows {
+            assert_eq!(window.border(), 3);
+            assert_eq!(window.margin, Margins::new(0));
+            assert_eq!(window.border_colors, overrides.border_colors);
+        }
+        let pushed_colors = |manager: &TestManager| {
+            manager
+                .state
+                .actions
+                .iter()
+                .filter(|act| matches!(act, DisplayAction::SetWindowBorderColors(..)))
+                .count()
+        };
+        assert_eq!(pushed_colors(&manager), 2);
+
+        // The colors are only pushed when they change.
+        manager.state.actions.clear();
+        manager.update_windows();
+        assert_eq!(pushed_colors(&manager), 0);
+    }
+
+    #[test]
+    fn window_rule_borders_should_replace_those_of_the_tag() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 2);
+        let overrides = TagOverrides {
+            border_width: Some(3),
+            border_colors: BorderColors {
+                default: Some("#00FF00".to_string()),
+                ..BorderColors::default()
+            },
+            ..TagOverrides::default()
+        };
+        manager
+            .config
+            .tag_overrides
+            .insert("1".to_string(), overrides);
+        manager.load_theme_config();
+        let window = &mut manager.state.windows[0];
+        window.rule_border_width = Some(0);
+        window.rule_border_colors.focused = Some("#FF0000".to_string());
+        manager.state.handle_single_border();
+        manager.update_windows();
+
+        let [ruled, other] = &manager.state.windows[..] else {
+            panic!("expected two windows");
+        };
+        assert_eq!(ruled.border(), 0);
+        assert_eq!(
+            ruled.border_colors,
+            BorderColors {
+                default: Some("#00FF00".to_string()),
+                focused: Some("#FF0000".to_string()),
+                ..BorderColors::default()
+            }
+        );
+        assert_eq!(other.border(), 3);
+        assert_eq!(other.border_colors.focused, None);
+    }
+
+    #[test]
+    fn undecorated_windows_should_have_no_border_unless_a_rule_sets_one() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 3);
+        manager.config.border_width = 2;
+        manager.load_theme_config();
+        manager.state.windows[0].undecorated = true;
+        manager.state.windows[1].undecorated = true;
+        manager.state.windows[1].rule_border_width = Some(4);
+        manager.state.handle_single_border();
+        manager.update_windows();
+
+        let borders: Vec<i32> = manager.state.windows.iter().map(Window::border).collect();
+        assert_eq!(borders, [0, 4, 2]);
+    }
+
+    #[test]
+    fn rules_and_urgency_should_set_the_border_of_windows_of_any_type() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 4);
+        manager.config.border_width = 2;
+        manager.config.urgent_border_width = Some(5);
+        manager.load_theme_config();
+        let windows = &mut manager.state.windows;
+        windows[0].urgent = true;
+        windows[1].r#type = WindowType::Dialog;
+        windows[2].r#type = WindowType::Utility;
+        windows[2].rule_border_width = Some(3);
+        windows[2].rule_border_colors.default = Some("#00FF00".to_string());
+        manager.state.handle_single_border();
+        manager.update_windows();
+
+        let borders: Vec<i32> = manager.state.windows.iter().map(Window::border).collect();
+        assert_eq!(borders, [5, 0, 3, 2]);
+        let colors = &manager.state.windows[2].border_colors;
+        assert_eq!(colors.default.as_deref(), Some("#00FF00"));
+    }
+
+    #[test]
+    fn windows_past_the_stack_limit_should_hide_behind_the_last_stack_tile() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 5);
+        manager
+            .config
+            .max_visible_in_stack
+            .insert("MainAndVertStack".to_string(), 2);
+        manager.state.layout_manager = LayoutManager::new(&manager.config);
+        manager.command_handler(&Command::SetLayout("MainAndVertStack".to_string()));
+        manager.update_windows();
+
+        let tiles = tiled(&manager);
+        assert_eq!(tiles.len(), 3);
+        assert_tiles_fill(&tiles, 0, 0, 800, 600);
+        let windows = &manager.state.windows;
+        for hidden in &windows[3..] {
+            assert!(!hidden.visible());
+            assert_eq!(hidden.normal, windows[2].normal);
+        }
+        assert_eq!(manager.state.workspaces[0].hidden_in_stack, 2);
+    }
+    #[test]
+    fn presentation_mode_should_leave_out_borders_gaps_and_struts_until_toggled_again() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 2);
+        manager.config.border_width = 2;
+        manager.load_theme_config();
+        manager.state.presentation_mode_hides_docks = true;
+        let workspace = &mut manager.state.workspaces[0];
+        workspace.margin = Margins::new(10);
+        workspace.avoid.push(
+            XyhwBuilder {
+                x: 0,
+                y: 0,
+                w: 800,
+                h: 30,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        workspace.update_avoided_areas();
+
+        manager.command_handler(&Command::TogglePresentationMode);
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 0, 0, 800, 600);
+        assert!(manager.state.windows.iter().all(|w| w.border() == 0));
+
+        // The client becomes urgent, and its hint is reported again as the property changes.
+        manager.state.actions.clear();
+        for _ in 0..2 {
+            let mut change = WindowChange::new(WindowHandle(1));
+            change.urgent = Some(true);
+            manager.window_changed_handler(change);
+            manager.update_windows();
+        }
+        let rewrites_hint =
+            |act: &DisplayAction<MockHandle>| matches!(act, DisplayAction::SetWindowUrgency(..));
+        assert!(!manager.state.actions.iter().any(rewrites_hint));
+        assert!(manager.state.windows[0].urgent);
+        assert!(!manager.state.windows[0].shows_urgency());
+
+        manager.command_handler(&Command::TogglePresentationMode);
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 10, 40, 780, 550);
+        assert!(manager.state.windows.iter().all(|w| w.border() == 2));
+        assert!(manager.state.windows[0].shows_urgency());
+    }
+
+    #[test]
+    fn gaps_should_be_left_out_for_single_windows_with_smart_gaps_or_when_toggled() {
+        let mut manager = Manager::new_test_with_windows(vec!["1".to_string()], 1);
+        manager.state.smart_gaps = true;
+        manager.state.workspaces[0].margin = Margins::new(10);
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 0, 0, 800, 600);
+
+        manager.window_created_handler(
+            Window::new(WindowHandle::<MockHandle>(2), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 10, 10, 780, 580);
+
+        manager.command_handler(&Command::ToggleGaps);
+        manager.update_windows();
+        assert_tiles_fill(&tiled(&manager), 0, 0, 800, 600);
     }
 }
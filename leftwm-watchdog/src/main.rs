@@ -20,6 +20,9 @@ type LeftwmArgs = Vec<String>;
 
 const SUBCOMMAND_PREFIX: &str = "leftwm-";
 
+/// Exit code of `leftwm-worker` when another window manager took over.
+const REPLACED_EXIT_CODE: i32 = 3;
+
 const SUBCOMMAND_NAME_INDEX: usize = 0;
 const SUBCOMMAND_DESCRIPTION_INDEX: usize = 1;
 const AVAILABLE_SUBCOMMANDS: [[&str; 2]; 6] = [
@@ -185,6 +188,10 @@ fn start_leftwm() {
         kill_lefthk_session(&mut lefthk_session);
 
         session_exit_status = get_exit_status(&mut leftwm_session);
+        if was_replaced(session_exit_status) {
+            println!("LeftWM was replaced by another window manager.");
+            exit(0);
+        }
         error_occured = check_error_occured(session_exit_status);
 
         // TODO: either add more details or find a better workaround.
@@ -278,6 +285,10 @@ fn check_error_occured(session_exit_status: Option<ExitStatus>) -> bool {
     }
 }
 
+fn was_replaced(session_exit_status: Option<ExitStatus>) -> bool {
+    session_exit_status.and_then(|status| status.code()) == Some(REPLACED_EXIT_CODE)
+}
+
 fn print_crash_message() {
     println!(concat!(
         "Leftwm crashed due to an unexpected error.\n",
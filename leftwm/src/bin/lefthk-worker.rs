@@ -1,4 +1,7 @@
-use lefthk_core::{config::Config, worker::Worker};
+use lefthk_core::{
+    config::Config,
+    worker::{Status, Worker},
+};
 use leftwm::utils;
 use tracing_subscriber::EnvFilter;
 use xdg::BaseDirectories;
@@ -14,11 +17,22 @@ fn main() {
     let exit_status = std::panic::catch_unwind(|| {
         let rt = tokio::runtime::Runtime::new().expect("ERROR: couldn't init Tokio runtime");
         let _rt_guard = rt.enter();
-        let config = leftwm::load();
-        let path = BaseDirectories::with_prefix("leftwm-lefthk")
-            .expect("ERROR: could not find base directory");
+        // The keys are grabbed again from a freshly read config on `Reload`, with the modkey of
+        // `SetModKey` when it asked for the reload.
+        loop {
+            let mut config = leftwm::load();
+            if let Some(modkey) = leftwm::take_replaced_modkey() {
+                config.modkey = modkey;
+            }
+            let path = BaseDirectories::with_prefix("leftwm-lefthk")
+                .expect("ERROR: could not find base directory");
 
-        rt.block_on(Worker::new(config.mapped_bindings(), path).event_loop());
+            let status = rt.block_on(Worker::new(config.mapped_bindings(), path).event_loop());
+            if status != Status::Reload {
+                break;
+            }
+            tracing::info!("Reloading keybinds");
+        }
     });
 
     match exit_status {
@@ -1,6 +1,6 @@
 use anyhow::{bail, Result};
 use clap::{arg, command};
-use leftwm::{Config, ThemeConfig};
+use leftwm::{Config, KeyWarning, ThemeConfig};
 use ron::{
     extensions::Extensions,
     ser::{to_string_pretty, PrettyConfig},
@@ -74,6 +74,10 @@ async fn main() -> Result<()> {
             }
             config.check_mousekey(verbose);
             config.check_log_level(verbose);
+            config.check_script(verbose);
+            config.check_plugins(verbose);
+            config.check_timers(verbose);
+            config.check_tag_overrides(verbose);
             #[cfg(not(feature = "lefthk"))]
             println!("\x1b[1;93mWARN: Ignoring checks on keybinds as you compiled for an external hot key daemon.\x1b[0m");
             #[cfg(feature = "lefthk")]
@@ -130,13 +134,21 @@ pub fn load_from_file(fspath: Option<&str>, verbose: bool) -> Result<Config> {
         let ron = Options::default()
             .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES);
         let config: Config = ron.from_str(&contents)?;
+        print_key_warnings(&KeyWarning::check_ron(&contents));
         Ok(config)
     } else {
         let config = toml::from_str(&contents)?;
+        print_key_warnings(&KeyWarning::check_toml(&contents));
         Ok(config)
     }
 }
 
+fn print_key_warnings(warnings: &[KeyWarning]) {
+    for warning in warnings {
+        println!("\x1b[1;93mWARN: Configuration {warning}\x1b[0m");
+    }
+}
+
 fn write_to_file(ron_file: &Path, config: &Config) -> Result<(), anyhow::Error> {
     let ron_pretty_conf = PrettyConfig::new()
         .depth_limit(2)
@@ -154,7 +166,7 @@ fn write_to_file(ron_file: &Path, config: &Config) -> Result<(), anyhow::Error>
 
 ",
     );
-    let ron_with_header = comment_header + &ron;
+    let ron_with_header = comment_header + ron.as_str();
     let mut file = File::create(ron_file)?;
     file.write_all(ron_with_header.as_bytes())?;
     Ok(())
@@ -1,5 +1,5 @@
 use leftwm::utils;
-use leftwm_core::Manager;
+use leftwm_core::{EventLoopError, Manager};
 use std::panic;
 use tracing_subscriber::EnvFilter;
 
@@ -13,6 +13,10 @@ use xlib_display_server::XlibDisplayServer;
 #[cfg(feature = "xlib")]
 use xlib_display_server::XlibWindowHandle;
 
+/// Exit code telling the `leftwm` session not to restart the worker, as another window manager
+/// took over. Must match `REPLACED_EXIT_CODE` of `leftwm-watchdog`.
+const REPLACED_EXIT_CODE: i32 = 3;
+
 fn main() {
     // INFO: This is used when attaching to leftwm-worker with lldb using `--waitfor` to ensure
     //       the process don't run further.
@@ -77,6 +81,10 @@ fn main() {
 
     match exit_status {
         Ok(Ok(())) => tracing::info!("Completed"),
+        Ok(Err(EventLoopError::Replaced)) => {
+            tracing::info!("Completed, another window manager took over");
+            std::process::exit(REPLACED_EXIT_CODE);
+        }
         Ok(Err(err)) => tracing::info!("Completed with event loop error: {}", err),
         Err(err) => tracing::info!("Completed with error: {:?}", err),
     }
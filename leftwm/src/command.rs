@@ -12,9 +12,14 @@ use serde::{Deserialize, Serialize};
 #[allow(clippy::module_name_repetitions)]
 #[derive(EnumDocs, Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum BaseCommand {
+    /// Args: `command` (string)
     Execute,
+    /// Waits for one of the keybinds listed as `children`, for `chord_timeout` milliseconds
+    Chord,
     CloseWindow,
     CloseAllOtherWindows,
+    /// Kill the focused window without asking it to close, e.g. when it hangs
+    ForceCloseWindow,
     SwapTags,
     SoftReload,
     HardReload,
@@ -29,11 +34,19 @@ pub enum BaseCommand {
     /// Args: `ScratchpadName`
     ToggleScratchPad,
     ToggleFullScreen,
+    /// Tell the window it is fullscreen while keeping it in its tile
+    ToggleFakeFullScreen,
+    /// Float the window in a corner, sticky and above other windows, or restore it
+    TogglePictureInPicture,
+    /// Fill the workspace, leaving struts and margins free, without going fullscreen
     ToggleMaximized,
     ToggleSticky,
     ToggleAbove,
     GotoTag,
     ReturnToLastTag,
+    /// Args: `tag_index` (int)
+    /// Show the tag along with the tag of the workspace, or stop showing it there
+    ToggleTag,
     FloatingToTile,
     TileToFloating,
     ToggleFloating,
@@ -47,11 +60,15 @@ pub enum BaseCommand {
     FocusPreviousTag,
     /// Args: `WindowClass` or `visible-window-index` (int)
     FocusWindow,
+    /// Args: `handle`, `class` or `title`, followed by the value to match
+    ActivateWindow,
     FocusWindowUp,
     FocusWindowDown,
     FocusWindowTop,
     /// Args: `direction` (string, optional)
     FocusWindowAt,
+    /// Args: `steps` (int, optional)
+    FocusPrevious,
     FocusWorkspaceNext,
     FocusWorkspacePrevious,
     /// Args: `tag_index` (int)
@@ -60,7 +77,9 @@ pub enum BaseCommand {
     MoveWindowToNextTag,
     MoveWindowToPreviousTag,
     MoveToLastWorkspace,
+    /// Args: `follow` (bool, optional)
     MoveWindowToNextWorkspace,
+    /// Args: `follow` (bool, optional)
     MoveWindowToPreviousWorkspace,
     /// Args: `direction` (string, optional)
     MoveWindowAt,
@@ -79,10 +98,58 @@ pub enum BaseCommand {
     DecreaseMainCount,
     /// Args: `multiplier-value` (float)
     SetMarginMultiplier,
+    /// Args: `modifier` (string)
+    /// Use another modifier as `modkey`, for keybinds and mousebinds, until the config is reloaded
+    SetModKey,
+    /// Args: `Top`, `Bottom`, `BeforeCurrent`, `AfterCurrent` or `AfterSmallest`
+    /// Insert new windows the given way until the config is reloaded
+    SetInsertBehavior,
+    /// Leave out the gaps of all workspaces until toggled again
+    ToggleGaps,
+    ToggleSelectWindow,
+    ClearSelection,
+    /// Args: `tag_index` (int)
+    SendSelectionToTag,
+    FloatSelection,
+    CloseSelection,
+    GroupWindows,
+    UngroupWindow,
+    NextTab,
+    PreviousTab,
+    MinimizeWindow,
+    RestoreLastMinimized,
+    RestoreAll,
+    /// Hide the selected windows, or else those of `stash_classes`, until `UnstashWindows`
+    StashWindows,
+    /// Bring stashed windows back exactly where they were
+    UnstashWindows,
+    /// Args: `after <seconds>`, `every <seconds>` or `cron <minute> <hour> <day> <month> <weekday>`,
+    /// followed by the command as written for `leftwm-command`
+    Schedule,
+    /// Cancel the commands scheduled by `Schedule`
+    CancelSchedules,
+    /// Clear the urgency of the focused window
+    ClearUrgent,
+    /// Show and focus the window which most recently became urgent
+    FocusNextUrgent,
+    /// Leave out borders, gaps and urgency, and docks with `presentation_mode_hides_docks`,
+    /// until toggled again
+    TogglePresentationMode,
+    /// Args: `direction` (string)
+    MoveFloatingWindow,
+    ResizeWindowWider,
+    ResizeWindowNarrower,
+    ResizeWindowTaller,
+    ResizeWindowShorter,
+    /// Writes the most recent log records to `log-dump.log` in the leftwm state directory
+    DumpLog,
     UnloadTheme,
     /// Args: `Path_to/theme.ron`
     /// Note: `theme.toml` will be deprecated but stays for backwards compatibility for a while
     LoadTheme,
+    /// Args: `function` of the script at `script_path`, followed by its arguments (optional)
+    /// Note: Needs leftwm built with the `scripting` feature
+    RunScript,
 }
 
 impl std::convert::From<BaseCommand> for String {
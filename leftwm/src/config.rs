@@ -1,21 +1,46 @@
 //! `LeftWM` general configuration
 
 mod checks;
+#[cfg(feature = "lefthk")]
+mod chord;
 mod default;
+mod key_warnings;
 mod keybind;
+#[cfg(feature = "lefthk")]
+mod modkey;
+#[cfg(feature = "plugins")]
+mod plugins;
+#[cfg(feature = "scripting")]
+mod scripting;
 
+pub use self::key_warnings::KeyWarning;
 use self::keybind::Modifier;
+#[cfg(feature = "lefthk")]
+pub use self::modkey::take_replaced_modkey;
 
 #[cfg(feature = "lefthk")]
 use super::BaseCommand;
-use super::ThemeConfig;
+use super::{CustomMargins, ThemeConfig};
 #[cfg(feature = "lefthk")]
 use crate::config::keybind::Keybind;
 use anyhow::Result;
+#[cfg(feature = "plugins")]
+use leftwm_core::layouts::LayoutArranger;
+#[cfg(feature = "plugins")]
+use leftwm_core::DisplayEvent;
+#[cfg(feature = "scripting")]
+use leftwm_core::Hook;
 use leftwm_core::{
-    config::{InsertBehavior, ScratchPad, Workspace},
+    config::{
+        EmptyTagBehavior, FloatingPlacementBehavior, Idle, InsertBehavior, Mousebind,
+        PictureInPicture, ScratchPad, Signal, TagOverflowBehavior, TagOverrides, Timer, TitleBar,
+        UrgencyBehavior, WmReplacedBehavior, Workspace,
+    },
     layouts::LayoutMode,
-    models::{FocusBehaviour, Gutter, Handle, Margins, Window, WindowState, WindowType},
+    models::{
+        BorderColors, FloatingMemory, FloatingPlacement, FocusBehaviour, FocusFollowsWorkspace,
+        Gutter, Handle, Margins, Size, Window, WindowState, WindowType,
+    },
     state::State,
     DisplayAction, DisplayServer, Manager, ReturnPipe,
 };
@@ -28,11 +53,14 @@ use ron::{
     Options,
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::Write;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "plugins")]
+use std::sync::Arc;
 use std::{convert::TryInto, fs::OpenOptions};
 use std::{default::Default, error::Error};
 use xdg::BaseDirectories;
@@ -64,6 +92,35 @@ const STATE_FILE: &str = "/tmp/leftwm.state";
 /// ```
 ///
 /// windows whose `WM_CLASS` is "krita" will spawn on tag 3 (1-indexed) and not floating.
+///
+/// `window_type` restricts a rule to windows of a `_NET_WM_WINDOW_TYPE`, alone or together with
+/// the class and title, and `never_focus` keeps matched windows from taking the focus:
+///
+/// ```ron
+/// window_rules: [
+///     (window_class: "gimp", window_type: Dialog, spawn_floating: true),
+///     (window_type: Splash, never_focus: true),
+/// ]
+/// ```
+///
+/// When a matched window spawns floating, `position` and `size` place it, in pixels or as a ratio
+/// of the workspace:
+///
+/// ```ron
+/// window_rules: [
+///     (window_class: "pavucontrol", spawn_floating: true, position: (0.6, 40), size: (0.4, 500)),
+/// ]
+/// ```
+///
+/// `border_width` and the `*_border_color` settings replace those of the theme and the tag for
+/// matched windows:
+///
+/// ```ron
+/// window_rules: [
+///     (window_class: "Alacritty", spawn_floating: true, border_width: 0),
+///     (window_class: "firefox", focused_border_color: "#FF8800"),
+/// ]
+/// ```
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct WindowHook {
     // Use serde default field attribute to fallback to None option in case of missing field in
@@ -83,6 +140,9 @@ pub struct WindowHook {
         serialize_with = "to_config_string"
     )]
     pub window_title: Option<Regex>,
+    /// `_NET_WM_WINDOW_TYPE` in X11, which matched windows must have
+    #[serde(default)]
+    pub window_type: Option<WindowType>,
     pub spawn_on_tag: Option<usize>,
     pub spawn_on_workspace: Option<usize>,
     pub spawn_floating: Option<bool>,
@@ -90,14 +150,43 @@ pub struct WindowHook {
     pub spawn_fullscreen: Option<bool>,
     /// Handle the window as if it was of this `_NET_WM_WINDOW_TYPE`
     pub spawn_as_type: Option<WindowType>,
+    /// `(x, y)` of the window when it spawns floating, from the top left of the workspace
+    #[serde(default)]
+    pub position: Option<(Size, Size)>,
+    /// `(width, height)` of the window when it spawns floating
+    #[serde(default)]
+    pub size: Option<(Size, Size)>,
+    /// Whether the window never takes the focus
+    #[serde(default)]
+    pub never_focus: Option<bool>,
+    /// Border width of the window, replacing the one of the theme and the tag
+    #[serde(default)]
+    pub border_width: Option<i32>,
+    /// Border colors of the window, replacing the ones of the theme and the tag
+    #[serde(default)]
+    pub default_border_color: Option<String>,
+    #[serde(default)]
+    pub floating_border_color: Option<String>,
+    #[serde(default)]
+    pub focused_border_color: Option<String>,
+    #[serde(default)]
+    pub selected_border_color: Option<String>,
 }
 
 impl WindowHook {
     /// Score the similarity between a [`leftwm_core::models::Window`] and a [`WindowHook`].
     ///
     /// Multiple [`WindowHook`]s might match a `WM_CLASS` but we want the most
-    /// specific one to apply: matches by title are scored greater than by `WM_CLASS`.
+    /// specific one to apply: matches by title are scored greater than by `WM_CLASS`, which are
+    /// scored greater than by window type. A hook never matches if any of what it is set to
+    /// match does not.
     fn score_window<H: Handle>(&self, window: &Window<H>) -> u8 {
+        let type_score = match &self.window_type {
+            Some(r#type) if r#type != &window.r#type => return 0,
+            Some(_) => 1,
+            None => 0,
+        };
+
         // returns true if any of the items in the provided `Vec<&Option<String>>` is Some and matches the `&Regex`
         let matches_any = |re: &Regex, strs: Vec<&Option<String>>| {
             strs.iter().any(|str| {
@@ -112,15 +201,19 @@ impl WindowHook {
             })
         };
 
-        let class_score = self.window_class.as_ref().map_or(0, |re| {
-            u8::from(matches_any(re, vec![&window.res_class, &window.res_name]))
-        });
+        let class_score = match &self.window_class {
+            Some(re) if !matches_any(re, vec![&window.res_class, &window.res_name]) => return 0,
+            Some(_) => 1,
+            None => 0,
+        };
 
-        let title_score = self.window_title.as_ref().map_or(0, |re| {
-            u8::from(matches_any(re, vec![&window.legacy_name, &window.name]))
-        });
+        let title_score = match &self.window_title {
+            Some(re) if !matches_any(re, vec![&window.legacy_name, &window.name]) => return 0,
+            Some(_) => 1,
+            None => 0,
+        };
 
-        class_score + 2 * title_score
+        type_score + 2 * class_score + 4 * title_score
     }
 
     fn apply<H: Handle>(&self, state: &mut State<H>, window: &mut Window<H>) {
@@ -171,6 +264,63 @@ impl WindowHook {
         if let Some(w_type) = self.spawn_as_type.clone() {
             window.r#type = w_type;
         }
+        if let Some(never_focus) = self.never_focus {
+            window.never_focus = never_focus;
+        }
+        window.rule_border_width = self.border_width;
+        window.rule_border_colors = BorderColors {
+            default: self.default_border_color.clone(),
+            floating: self.floating_border_color.clone(),
+            focused: self.focused_border_color.clone(),
+            selected: self.selected_border_color.clone(),
+        };
+        if self.position.is_some() || self.size.is_some() {
+            window.placement = Some(FloatingPlacement {
+                position: self.position,
+                size: self.size,
+            });
+        }
+    }
+}
+
+/// Settings replacing the ones of the theme and the layout on a tag, see [`TagOverrides`].
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct TagSettings {
+    pub margin: Option<CustomMargins>,
+    pub workspace_margin: Option<CustomMargins>,
+    pub border_width: Option<i32>,
+    pub default_border_color: Option<String>,
+    pub floating_border_color: Option<String>,
+    pub focused_border_color: Option<String>,
+    pub selected_border_color: Option<String>,
+    /// The layout the tag starts with, when `layout_mode` is `Tag`.
+    pub layout: Option<String>,
+}
+
+impl TagSettings {
+    fn to_overrides(&self) -> TagOverrides {
+        let margins = |margin: &Option<CustomMargins>| {
+            margin.clone().and_then(|margin| match margin.try_into() {
+                Ok(margins) => Some(margins),
+                Err(err) => {
+                    tracing::warn!("Could not read margin: {}", err);
+                    None
+                }
+            })
+        };
+        TagOverrides {
+            margin: margins(&self.margin),
+            workspace_margin: margins(&self.workspace_margin),
+            border_width: self.border_width,
+            border_colors: BorderColors {
+                default: self.default_border_color.clone(),
+                floating: self.floating_border_color.clone(),
+                focused: self.focused_border_color.clone(),
+                selected: self.selected_border_color.clone(),
+            },
+            layout: self.layout.clone(),
+        }
     }
 }
 
@@ -200,25 +350,90 @@ pub struct Config {
     pub log_level: String,
     pub modkey: String,
     pub mousekey: Option<Modifier>,
+    /// Mouse buttons pressed on windows with modifiers, `mousekey` and `modkey` included.
+    pub mousebind: Vec<Mousebind>,
     pub workspaces: Option<Vec<Workspace>>,
     pub tags: Option<Vec<String>>,
     pub layouts: Vec<String>,
     pub layout_definitions: Vec<Layout>,
     pub layout_mode: LayoutMode,
+    /// The most windows shown in the stack of layouts, by name, the others hiding behind the last
+    /// of them.
+    pub max_visible_in_stack: HashMap<String, usize>,
     pub insert_behavior: InsertBehavior,
+    /// Maximum number of tiled windows on a tag before `tag_overflow_behavior` applies.
+    pub max_windows_per_tag: Option<usize>,
+    pub tag_overflow_behavior: TagOverflowBehavior,
+    /// Where new floating windows are placed: `Center` or `Smart`.
+    pub floating_placement_behavior: FloatingPlacementBehavior,
+    /// Corner and size of windows pinned by `TogglePictureInPicture`.
+    pub picture_in_picture: PictureInPicture,
+    /// New windows requesting a size within these are floated and centered.
+    pub auto_float_max_width: Option<i32>,
+    pub auto_float_max_height: Option<i32>,
+    /// Resizing a tiled window with the mouse resizes the main column of its layout.
+    pub resize_tiled_splits: bool,
+    /// Classes of the windows `StashWindows` hides when no window is selected.
+    pub stash_classes: Vec<String>,
+    /// What to do when the last window of a shown tag goes away.
+    pub empty_tag_behavior: EmptyTagBehavior,
+    /// `empty_tag_behavior` for specific tags, by label.
+    pub empty_tag_behavior_by_tag: HashMap<String, EmptyTagBehavior>,
+    /// Gaps, borders and layout replacing the configured ones on specific tags, by label.
+    pub tag_overrides: HashMap<String, TagSettings>,
+    /// When the user is idle, and what to do then.
+    pub idle: Idle,
+    /// Commands run after a delay or on a schedule.
+    pub timers: Vec<Timer>,
+    /// How the urgency of windows is cleared.
+    pub urgency_behavior: UrgencyBehavior,
+    /// Commands run when the window manager receives `SIGHUP`, `SIGUSR1` or `SIGUSR2`.
+    pub signals: HashMap<Signal, String>,
+    /// `TogglePresentationMode` also hides docks and lets windows use the space they reserve.
+    pub presentation_mode_hides_docks: bool,
+    /// Pixels `MoveFloatingWindow` and the `ResizeWindow*` commands move or resize windows by.
+    pub floating_step: i32,
+    /// Leave out gaps when a workspace shows a single tiled window.
+    pub smart_gaps: bool,
+    /// Hide terminals while the windows launched from them are open.
+    pub swallow: bool,
     pub scratchpad: Option<Vec<ScratchPad>>,
     pub window_rules: Option<Vec<WindowHook>>,
     // If you are on tag "1" and you goto tag "1" this takes you to the previous tag
     pub disable_current_tag_swap: bool,
     pub disable_tile_drag: bool,
     pub disable_window_snap: bool,
+    /// Pixels within which dragged floating windows stick to workspace and window edges.
+    pub floating_snap_distance: i32,
     pub focus_behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
+    /// Mark windows that keep taking the focus back as never taking focus.
+    pub mark_focus_stealers_never_focus: bool,
+    /// Wrap windows in a frame of our own drawing their border, instead of drawing it on them.
+    pub reparent_windows: bool,
+    /// Whether to exit or stay when another window manager asks to take over.
+    pub wm_replaced_behavior: WmReplacedBehavior,
+    /// TCP address, or unix socket as `unix:<path>` or an absolute path, to serve metrics on in
+    /// the Prometheus text format.
+    pub metrics_endpoint: Option<String>,
+    /// Serve an i3 IPC compatible socket, found by tools written for i3 through `I3SOCK`.
+    pub i3_ipc: bool,
+    /// Remember where windows floated by `WM_CLASS`, and float them there again.
+    pub remember_floating_geometry: bool,
     pub single_window_border: bool,
     pub sloppy_mouse_follows_focus: bool,
     pub create_follows_cursor: Option<bool>,
+    /// What moves along with the focus when a command focuses another workspace.
+    pub focus_follows_workspace: FocusFollowsWorkspace,
     pub auto_derive_workspaces: bool,
     pub disable_cursor_reposition_on_resize: bool,
+    /// Rhai script defining custom commands and hooks, needs the `scripting` feature.
+    pub script_path: Option<PathBuf>,
+    /// WebAssembly plugins providing commands, layouts and event filters, needs the `plugins`
+    /// feature.
+    pub plugins: Vec<PathBuf>,
+    /// Milliseconds a `Chord` keybind waits for one of its children, 0 to wait for `Escape`.
+    pub chord_timeout: u64,
     #[cfg(feature = "lefthk")]
     pub keybind: Vec<Keybind>,
     pub state_path: Option<PathBuf>,
@@ -226,13 +441,22 @@ pub struct Config {
     //       at least when `TOML` is used as config language
     #[serde(skip)]
     pub theme_setting: ThemeConfig,
+    #[cfg(feature = "scripting")]
+    #[serde(skip)]
+    pub script: Option<scripting::Script>,
+    #[cfg(feature = "plugins")]
+    #[serde(skip)]
+    pub plugin_host: Option<Arc<plugins::Host>>,
 }
 
 #[must_use]
 pub fn load() -> Config {
-    load_from_file()
+    let mut config = load_from_file()
         .map_err(|err| eprintln!("ERROR LOADING CONFIG: {err:?}"))
-        .unwrap_or_default()
+        .unwrap_or_default();
+    config.load_script();
+    config.load_plugins();
+    config
 }
 
 /// # Panics
@@ -262,6 +486,9 @@ fn load_from_file() -> Result<Config> {
             .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES);
         let contents = fs::read_to_string(config_file_ron)?;
         let config: Config = ron.from_str(&contents)?;
+        for warning in KeyWarning::check_ron(&contents) {
+            tracing::warn!("Config: {}", warning);
+        }
         Ok(config)
     } else if Path::new(&config_file_toml).exists() {
         tracing::debug!(
@@ -270,6 +497,9 @@ fn load_from_file() -> Result<Config> {
         );
         let contents = fs::read_to_string(config_file_toml)?;
         let config = toml::from_str(&contents)?;
+        for warning in KeyWarning::check_toml(&contents) {
+            tracing::warn!("Config: {}", warning);
+        }
         tracing::info!("You are using TOML as config language which will be deprecated in the future.\nPlease consider migrating you config to RON. For further info visit the leftwm wiki.");
         Ok(config)
     } else {
@@ -292,7 +522,7 @@ fn load_from_file() -> Result<Config> {
 
 ",
         );
-        let ron_with_header = comment_header + &ron;
+        let ron_with_header = comment_header + ron.as_str();
 
         let mut file = File::create(&config_file_ron)?;
         file.write_all(ron_with_header.as_bytes())?;
@@ -373,20 +603,7 @@ impl lefthk_core::config::Config for Config {
             .clone()
             .into_iter()
             .map(|mut keybind| {
-                if let Some(ref mut modifier) = keybind.modifier {
-                    match modifier {
-                        Modifier::Single(m) if m == "modkey" => *m = self.modkey.clone(),
-                        Modifier::List(ms) => {
-                            for m in ms {
-                                if m == "modkey" {
-                                    *m = self.modkey.clone();
-                                }
-                            }
-                        }
-                        Modifier::Single(_) => {}
-                    }
-                }
-
+                keybind.replace_modkey(&self.modkey);
                 keybind
             })
             .filter_map(
@@ -428,6 +645,22 @@ impl leftwm_core::Config for Config {
             .into()
     }
 
+    fn mousebinds(&self) -> Vec<Mousebind> {
+        let mousekey = leftwm_core::Config::mousekey(self);
+        let mut binds = self.mousebind.clone();
+        for bind in &mut binds {
+            bind.modifier = std::mem::take(&mut bind.modifier)
+                .into_iter()
+                .flat_map(|m| match m.as_str() {
+                    "mousekey" => mousekey.clone(),
+                    "modkey" => vec![self.modkey.clone()],
+                    _ => vec![m],
+                })
+                .collect();
+        }
+        binds
+    }
+
     fn create_list_of_scratchpads(&self) -> Vec<ScratchPad> {
         if let Some(scratchpads) = &self.scratchpad {
             return scratchpads.clone();
@@ -444,6 +677,17 @@ impl leftwm_core::Config for Config {
         for custom_layout in &self.layout_definitions {
             layouts.push(custom_layout.clone());
         }
+        #[cfg(feature = "plugins")]
+        if let Some(host) = &self.plugin_host {
+            for name in host.layouts() {
+                if !layouts.iter().any(|layout| layout.name == name) {
+                    layouts.push(Layout {
+                        name,
+                        ..Layout::default()
+                    });
+                }
+            }
+        }
         layouts
     }
 
@@ -451,10 +695,106 @@ impl leftwm_core::Config for Config {
         self.layout_mode
     }
 
+    fn max_visible_in_stack(&self, layout: &str) -> Option<usize> {
+        self.max_visible_in_stack.get(layout).copied()
+    }
+
+    #[cfg(feature = "plugins")]
+    fn layout_arranger(&self) -> Option<Arc<dyn LayoutArranger>> {
+        self.plugin_host
+            .clone()
+            .map(|host| host as Arc<dyn LayoutArranger>)
+    }
+
+    #[cfg(feature = "plugins")]
+    fn filter_event<H: Handle>(&self, event: &DisplayEvent<H>) -> bool {
+        self.plugin_host
+            .as_ref()
+            .map_or(true, |host| host.filter_event(event))
+    }
+
     fn insert_behavior(&self) -> InsertBehavior {
         self.insert_behavior
     }
 
+    fn max_windows_per_tag(&self) -> Option<usize> {
+        self.max_windows_per_tag
+    }
+
+    fn tag_overflow_behavior(&self) -> TagOverflowBehavior {
+        self.tag_overflow_behavior
+    }
+
+    fn floating_placement_behavior(&self) -> FloatingPlacementBehavior {
+        self.floating_placement_behavior
+    }
+
+    fn picture_in_picture(&self) -> PictureInPicture {
+        self.picture_in_picture
+    }
+
+    fn auto_float_max_width(&self) -> Option<i32> {
+        self.auto_float_max_width
+    }
+
+    fn auto_float_max_height(&self) -> Option<i32> {
+        self.auto_float_max_height
+    }
+
+    fn resize_tiled_splits(&self) -> bool {
+        self.resize_tiled_splits
+    }
+
+    fn stash_classes(&self) -> Vec<String> {
+        self.stash_classes.clone()
+    }
+
+    fn empty_tag_behavior(&self, tag: &str) -> EmptyTagBehavior {
+        self.empty_tag_behavior_by_tag
+            .get(tag)
+            .unwrap_or(&self.empty_tag_behavior)
+            .clone()
+    }
+
+    fn tag_overrides(&self, tag: &str) -> TagOverrides {
+        self.tag_overrides
+            .get(tag)
+            .map(TagSettings::to_overrides)
+            .unwrap_or_default()
+    }
+
+    fn idle(&self) -> Idle {
+        self.idle.clone()
+    }
+
+    fn timers(&self) -> Vec<Timer> {
+        self.timers.clone()
+    }
+
+    fn urgency_behavior(&self) -> UrgencyBehavior {
+        self.urgency_behavior
+    }
+
+    fn signals(&self) -> HashMap<Signal, String> {
+        self.signals.clone()
+    }
+
+    fn presentation_mode_hides_docks(&self) -> bool {
+        self.presentation_mode_hides_docks
+    }
+
+    fn floating_step(&self) -> i32 {
+        self.floating_step
+    }
+
+    fn smart_gaps(&self) -> bool {
+        self.smart_gaps
+    }
+
+    fn swallow(&self) -> bool {
+        self.swallow
+    }
+
     fn single_window_border(&self) -> bool {
         self.single_window_border
     }
@@ -463,6 +803,50 @@ impl leftwm_core::Config for Config {
         self.focus_new_windows
     }
 
+    fn mark_focus_stealers_never_focus(&self) -> bool {
+        self.mark_focus_stealers_never_focus
+    }
+
+    fn reparent_windows(&self) -> bool {
+        self.reparent_windows
+    }
+
+    fn title_bar(&self) -> Option<TitleBar> {
+        let theme = &self.theme_setting;
+        let height = theme.title_bar_height.filter(|height| *height > 0)?;
+        Some(TitleBar {
+            height,
+            font: theme
+                .title_bar_font
+                .clone()
+                .unwrap_or_else(|| "fixed".to_string()),
+            color: theme
+                .title_bar_color
+                .clone()
+                .unwrap_or_else(|| "#222222".to_string()),
+            focused_color: theme
+                .title_bar_focused_color
+                .clone()
+                .unwrap_or_else(|| "#005577".to_string()),
+            text_color: theme
+                .title_bar_text_color
+                .clone()
+                .unwrap_or_else(|| "#EEEEEE".to_string()),
+        })
+    }
+
+    fn wm_replaced_behavior(&self) -> WmReplacedBehavior {
+        self.wm_replaced_behavior
+    }
+
+    fn metrics_endpoint(&self) -> Option<String> {
+        self.metrics_endpoint.clone()
+    }
+
+    fn i3_ipc(&self) -> bool {
+        self.i3_ipc
+    }
+
     fn command_handler<H: Handle, SERVER: DisplayServer<H>>(
         command: &str,
         manager: &mut Manager<H, Self, SERVER>,
@@ -485,7 +869,13 @@ impl leftwm_core::Config for Config {
                     write_to_pipe(&mut return_pipe, "OK: Command executed successfully");
                     manager.load_theme_config()
                 }
+                "RunScript" => run_script(value.trim(), manager, &mut return_pipe),
                 _ => {
+                    if let Some(needs_refresh) = run_plugin_command(command, value.trim(), manager)
+                    {
+                        write_to_pipe(&mut return_pipe, "OK: Command executed successfully");
+                        return needs_refresh;
+                    }
                     tracing::warn!("Command not recognized: {}", command);
                     write_to_pipe(&mut return_pipe, "ERROR: Command not recognized");
                     false
@@ -498,12 +888,35 @@ impl leftwm_core::Config for Config {
                     write_to_pipe(&mut return_pipe, "ERROR: Missing parameter theme_path");
                     false
                 }
+                "RunScript" => {
+                    tracing::warn!("Missing parameter function");
+                    write_to_pipe(&mut return_pipe, "ERROR: Missing parameter function");
+                    false
+                }
                 "UnloadTheme" => {
                     manager.config.theme_setting = ThemeConfig::default();
                     write_to_pipe(&mut return_pipe, "OK: Command executed successfully");
                     manager.load_theme_config()
                 }
+                "DumpLog" => {
+                    match crate::utils::log::ring::dump() {
+                        Ok(path) => {
+                            let msg = format!("OK: Log written to {}", path.display());
+                            write_to_pipe(&mut return_pipe, &msg);
+                        }
+                        Err(err) => {
+                            tracing::warn!("Unable to dump the log: {}", err);
+                            let msg = format!("ERROR: Unable to dump the log: {err}");
+                            write_to_pipe(&mut return_pipe, &msg);
+                        }
+                    }
+                    false
+                }
                 _ => {
+                    if let Some(needs_refresh) = run_plugin_command(command, "", manager) {
+                        write_to_pipe(&mut return_pipe, "OK: Command executed successfully");
+                        return needs_refresh;
+                    }
                     tracing::warn!("Command not recognized: {}", command);
                     write_to_pipe(&mut return_pipe, "ERROR: Command not recognized");
                     false
@@ -512,10 +925,22 @@ impl leftwm_core::Config for Config {
         }
     }
 
+    #[cfg(feature = "scripting")]
+    fn hook_handler<H: Handle, SERVER: DisplayServer<H>>(
+        hook: &Hook<H>,
+        manager: &mut Manager<H, Self, SERVER>,
+    ) -> bool {
+        scripting::run_hook(hook, manager)
+    }
+
     fn border_width(&self) -> i32 {
         self.theme_setting.border_width.unwrap_or(1)
     }
 
+    fn urgent_border_width(&self) -> Option<i32> {
+        self.theme_setting.urgent_border_width
+    }
+
     fn margin(&self) -> Margins {
         match self
             .theme_setting
@@ -545,6 +970,19 @@ impl leftwm_core::Config for Config {
             })
     }
 
+    fn decoration_frame(&self) -> Option<Margins> {
+        self.theme_setting
+            .decoration_frame
+            .clone()
+            .and_then(|custom_margin| match custom_margin.try_into() {
+                Ok(margins) => Some(margins),
+                Err(err) => {
+                    tracing::warn!("Could not read decoration frame: {}", err);
+                    None
+                }
+            })
+    }
+
     fn gutter(&self) -> Option<Vec<Gutter>> {
         self.theme_setting.gutter.clone()
     }
@@ -563,6 +1001,20 @@ impl leftwm_core::Config for Config {
             .unwrap_or_else(|| "#000000".to_string())
     }
 
+    fn selected_border_color(&self) -> String {
+        self.theme_setting
+            .selected_border_color
+            .clone()
+            .unwrap_or_else(|| "#FFFF00".to_string())
+    }
+
+    fn urgent_border_color(&self) -> String {
+        self.theme_setting
+            .urgent_border_color
+            .clone()
+            .unwrap_or_else(|| "#FF8800".to_string())
+    }
+
     fn background_color(&self) -> String {
         self.theme_setting
             .background_color
@@ -574,6 +1026,10 @@ impl leftwm_core::Config for Config {
         self.disable_window_snap
     }
 
+    fn floating_snap_distance(&self) -> i32 {
+        self.floating_snap_distance
+    }
+
     fn always_float(&self) -> bool {
         self.theme_setting.always_float.unwrap_or(false)
     }
@@ -619,6 +1075,44 @@ impl leftwm_core::Config for Config {
         }
     }
 
+    fn reload(&mut self) -> bool {
+        let mut config = match load_from_file() {
+            Ok(config) => config,
+            Err(err) => {
+                tracing::error!("Keeping the current config, unable to reload it: {:?}", err);
+                return true;
+            }
+        };
+        config.theme_setting = std::mem::take(&mut self.theme_setting);
+        config.load_script();
+        config.load_plugins();
+        #[cfg(feature = "lefthk")]
+        {
+            config.clear_keybinds();
+            reload_lefthk();
+        }
+        *self = config;
+        true
+    }
+
+    fn set_modkey(&mut self, modkey: &str) -> bool {
+        if leftwm_core::utils::modmask_lookup::into_modmask(&[modkey.to_owned()]).is_empty() {
+            return false;
+        }
+        // The mousekey follows the modkey when they were the same.
+        if leftwm_core::Config::mousekey(self) == [self.modkey.as_str()] {
+            self.mousekey = Some(Modifier::Single(modkey.to_owned()));
+        }
+        modkey.clone_into(&mut self.modkey);
+        #[cfg(feature = "lefthk")]
+        {
+            use lefthk_core::config::Command;
+            let replace = modkey::ReplaceModkey(modkey.to_owned());
+            send_to_lefthk(replace.normalize(), std::time::Duration::ZERO);
+        }
+        true
+    }
+
     fn load_state<H: Handle>(&self, state: &mut State<H>) {
         let path = self.state_file().to_owned();
         match File::open(&path) {
@@ -637,6 +1131,39 @@ impl leftwm_core::Config for Config {
         }
     }
 
+    fn load_floating_memory(&self) -> Option<FloatingMemory> {
+        if !self.remember_floating_geometry {
+            return None;
+        }
+        let path = self.floating_memory_file();
+        let memory = match File::open(&path) {
+            Ok(file) => ron::de::from_reader(file).unwrap_or_else(|err| {
+                tracing::error!("Cannot load floating geometries: {}", err);
+                FloatingMemory::default()
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => FloatingMemory::default(),
+            Err(err) => {
+                tracing::error!("Cannot open {}: {}", path.display(), err);
+                FloatingMemory::default()
+            }
+        };
+        Some(memory)
+    }
+
+    fn save_floating_memory(&self, memory: &FloatingMemory) {
+        let path = self.floating_memory_file();
+        let file = match File::create(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                tracing::error!("Cannot create file at path {}: {}", path.display(), err);
+                return;
+            }
+        };
+        if let Err(err) = ron::ser::to_writer(file, memory) {
+            tracing::error!("Cannot save floating geometries: {}", err);
+        }
+    }
+
     /// Pick the best matching [`WindowHook`], if any, and apply its config.
     fn setup_predefined_window<H: Handle>(
         &self,
@@ -692,6 +1219,10 @@ impl leftwm_core::Config for Config {
         self.create_follows_cursor
             .unwrap_or(self.focus_behaviour == FocusBehaviour::Sloppy)
     }
+
+    fn focus_follows_workspace(&self) -> FocusFollowsWorkspace {
+        self.focus_follows_workspace
+    }
 }
 
 impl Config {
@@ -700,11 +1231,62 @@ impl Config {
         self.keybind.clear();
     }
 
+    /// Compile the script at `script_path`, if any. Errors are logged, leaving the script out.
+    pub fn load_script(&mut self) {
+        let Some(path) = &self.script_path else {
+            return;
+        };
+        #[cfg(feature = "scripting")]
+        {
+            let loaded = absolute_path(&path.to_string_lossy())
+                .ok_or_else(|| format!("{} does not exist", path.display()))
+                .and_then(|path| scripting::Script::load(&path).map_err(|err| err.to_string()));
+            match loaded {
+                Ok(script) => self.script = Some(script),
+                Err(err) => tracing::error!("Unable to load script: {}", err),
+            }
+        }
+        #[cfg(not(feature = "scripting"))]
+        tracing::warn!(
+            "Ignoring script {}: leftwm was built without the `scripting` feature",
+            path.display()
+        );
+    }
+
+    /// Load the plugins listed in `plugins`. Errors are logged, leaving the plugin out.
+    pub fn load_plugins(&mut self) {
+        if self.plugins.is_empty() {
+            return;
+        }
+        #[cfg(feature = "plugins")]
+        {
+            let paths: Vec<PathBuf> = self
+                .plugins
+                .iter()
+                .filter_map(|path| {
+                    let absolute = absolute_path(&path.to_string_lossy());
+                    if absolute.is_none() {
+                        tracing::error!("Unable to load plugin: {} does not exist", path.display());
+                    }
+                    absolute
+                })
+                .collect();
+            self.plugin_host = Some(Arc::new(plugins::Host::load(&paths)));
+        }
+        #[cfg(not(feature = "plugins"))]
+        tracing::warn!("Ignoring plugins: leftwm was built without the `plugins` feature");
+    }
+
     fn state_file(&self) -> &Path {
         self.state_path
             .as_deref()
             .unwrap_or_else(|| Path::new(STATE_FILE))
     }
+
+    /// The remembered floating geometries are kept next to the state file.
+    fn floating_memory_file(&self) -> PathBuf {
+        self.state_file().with_extension("floating")
+    }
 }
 
 // Regular expression in leftwm config should correspond to RE2 syntax, described here:
@@ -732,6 +1314,87 @@ fn get_return_pipe() -> Result<File, Box<dyn std::error::Error>> {
     Ok(OpenOptions::new().append(true).open(file_path)?)
 }
 
+/// Have lefthk read the config again and grab its keys.
+#[cfg(feature = "lefthk")]
+fn reload_lefthk() {
+    use lefthk_core::config::{command::Reload, Command};
+    send_to_lefthk(Reload::new().normalize(), std::time::Duration::ZERO);
+}
+
+/// Send a command to lefthk through its command pipe, once `delay` passed.
+///
+/// The pipe is opened without waiting for lefthk to read it, failing when it does not.
+#[cfg(feature = "lefthk")]
+fn send_to_lefthk(
+    command: lefthk_core::config::command::utils::normalized_command::NormalizedCommand,
+    delay: std::time::Duration,
+) {
+    use lefthk_core::ipc::Pipe;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::unix::pipe;
+
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let path = BaseDirectories::with_prefix("leftwm-lefthk")
+            .ok()
+            .and_then(|dirs| dirs.find_runtime_file(Pipe::pipe_name()));
+        let Some(path) = path else {
+            tracing::warn!("Unable to find the lefthk pipe to send {}", command);
+            return;
+        };
+        let line = format!("{command}\n");
+        let written = match pipe::OpenOptions::new().open_sender(path) {
+            Ok(mut pipe) => pipe.write_all(line.as_bytes()).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = written {
+            tracing::error!("Unable to send {} to lefthk: {}", command, err);
+        }
+    });
+}
+
+/// Run the script function named by a `RunScript` command, passing it the rest of the command.
+fn run_script<H: Handle, SERVER: DisplayServer<H>>(
+    value: &str,
+    manager: &mut Manager<H, Config, SERVER>,
+    return_pipe: &mut Result<File, Box<dyn Error>>,
+) -> bool {
+    let (function, args) = value.split_once(' ').unwrap_or((value, ""));
+    #[cfg(feature = "scripting")]
+    if let Some(needs_refresh) = scripting::run_function(function, args.trim(), manager) {
+        write_to_pipe(return_pipe, "OK: Command executed successfully");
+        return needs_refresh;
+    }
+    #[cfg(not(feature = "scripting"))]
+    let _ = (args, manager);
+    tracing::warn!("Script function not found: {}", function);
+    write_to_pipe(return_pipe, "ERROR: Script function not found");
+    false
+}
+
+/// Run the commands a plugin gives for a command leftwm does not know. Returns `None` if no
+/// plugin handles it.
+fn run_plugin_command<H: Handle, SERVER: DisplayServer<H>>(
+    command: &str,
+    args: &str,
+    manager: &mut Manager<H, Config, SERVER>,
+) -> Option<bool> {
+    #[cfg(feature = "plugins")]
+    {
+        let commands = manager.config.plugin_host.clone()?.command(command, args)?;
+        let mut refresh = false;
+        for command in &commands {
+            refresh = manager.command_handler(command) || refresh;
+        }
+        Some(refresh)
+    }
+    #[cfg(not(feature = "plugins"))]
+    {
+        let _ = (command, args, manager);
+        None
+    }
+}
+
 fn write_to_pipe(return_pipe: &mut Result<File, Box<dyn Error>>, msg: &str) {
     if let Ok(pipefile) = return_pipe {
         if let Err(e) = writeln!(pipefile, "{msg}") {
@@ -743,6 +1406,11 @@ fn write_to_pipe(return_pipe: &mut Result<File, Box<dyn Error>>, msg: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use leftwm_core::models::WindowHandle;
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+    struct TestHandle(i32);
+    impl Handle for TestHandle {}
 
     #[test]
     fn config_serializes_to_valid_ron_test() {
@@ -758,4 +1426,52 @@ mod tests {
         let ron_config = ron::from_str::<'_, Config>(ron.unwrap().as_str());
         assert!(ron_config.is_ok(), "Could not deserialize default config");
     }
+
+    #[test]
+    fn window_rules_should_parse_the_floating_placement() {
+        let ron = Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES);
+        let hook: WindowHook = ron
+            .from_str(r#"(window_class: "pavucontrol", position: (0.5, 40), size: (300, 0.25))"#)
+            .unwrap();
+        assert_eq!(hook.position, Some((Size::Ratio(0.5), Size::Pixel(40))));
+        assert_eq!(hook.size, Some((Size::Pixel(300), Size::Ratio(0.25))));
+    }
+
+    #[test]
+    fn window_rules_should_only_match_their_window_type() {
+        let ron = Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES);
+        let dialogs: WindowHook = ron
+            .from_str(r#"(window_class: "gimp", window_type: Dialog, never_focus: true)"#)
+            .unwrap();
+        let gimp: WindowHook = ron.from_str(r#"(window_class: "gimp")"#).unwrap();
+
+        let mut window = Window::new(WindowHandle(TestHandle(1)), None, None);
+        window.res_class = Some("gimp".to_string());
+        assert_eq!(dialogs.score_window(&window), 0);
+        window.r#type = WindowType::Dialog;
+        assert!(dialogs.score_window(&window) > gimp.score_window(&window));
+        assert_eq!(dialogs.never_focus, Some(true));
+    }
+
+    #[test]
+    fn window_rules_should_not_match_when_any_of_their_criteria_does_not() {
+        let ron = Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES);
+        let hook: WindowHook = ron
+            .from_str(r#"(window_class: "gimp", window_type: Dialog, window_title: "Export")"#)
+            .unwrap();
+
+        let mut window = Window::new(WindowHandle(TestHandle(1)), None, None);
+        window.r#type = WindowType::Dialog;
+        window.res_class = Some("firefox".to_string());
+        window.name = Some("Export".to_string());
+        assert_eq!(hook.score_window(&window), 0);
+        window.res_class = Some("gimp".to_string());
+        window.name = Some("Open".to_string());
+        assert_eq!(hook.score_window(&window), 0);
+        window.name = Some("Export".to_string());
+        assert_eq!(hook.score_window(&window), 7);
+    }
 }
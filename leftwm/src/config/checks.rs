@@ -35,6 +35,82 @@ impl Config {
         }
     }
 
+    pub fn check_script(&self, verbose: bool) {
+        let Some(path) = &self.script_path else {
+            return;
+        };
+        if verbose {
+            println!("Trying to compile script {}.", path.display());
+        }
+        #[cfg(feature = "scripting")]
+        match super::absolute_path(&path.to_string_lossy()) {
+            Some(absolute) => match super::scripting::Script::load(&absolute) {
+                Ok(_) if verbose => println!("Script is ok."),
+                Ok(_) => {}
+                Err(err) => println!("Script is invalid: {err}"),
+            },
+            None => println!("Script {} does not exist.", path.display()),
+        }
+        #[cfg(not(feature = "scripting"))]
+        println!("\x1b[1;93mWARN: Ignoring `script_path` as you compiled without the `scripting` feature.\x1b[0m");
+    }
+
+    pub fn check_plugins(&self, verbose: bool) {
+        for path in &self.plugins {
+            if verbose {
+                println!("Trying to load plugin {}.", path.display());
+            }
+            let Some(absolute) = super::absolute_path(&path.to_string_lossy()) else {
+                println!("Plugin {} does not exist.", path.display());
+                continue;
+            };
+            #[cfg(feature = "plugins")]
+            match super::plugins::check(&absolute) {
+                Ok(()) if verbose => println!("Plugin is ok."),
+                Ok(()) => {}
+                Err(err) => println!("Plugin {} is invalid: {err}", path.display()),
+            }
+            #[cfg(not(feature = "plugins"))]
+            let _ = absolute;
+        }
+        #[cfg(not(feature = "plugins"))]
+        if !self.plugins.is_empty() {
+            println!("\x1b[1;93mWARN: Ignoring `plugins` as you compiled without the `plugins` feature.\x1b[0m");
+        }
+    }
+
+    pub fn check_timers(&self, verbose: bool) {
+        for timer in &self.timers {
+            if verbose {
+                println!("Checking the schedule of `{}`.", timer.command);
+            }
+            match timer.schedule.validate() {
+                Ok(()) if verbose => println!("Schedule is ok."),
+                Ok(()) => {}
+                Err(err) => println!("Schedule of `{}` is invalid: {err}", timer.command),
+            }
+        }
+    }
+
+    pub fn check_tag_overrides(&self, verbose: bool) {
+        let tags = leftwm_core::Config::create_list_of_tag_labels(self);
+        for (tag, settings) in &self.tag_overrides {
+            if verbose {
+                println!("Checking the overrides of tag `{tag}`.");
+            }
+            if !tags.contains(tag) {
+                println!("Overrides of tag `{tag}` are unused: there is no such tag.");
+            }
+            match &settings.layout {
+                Some(layout) if !self.layouts.contains(layout) => {
+                    println!("Layout `{layout}` of tag `{tag}` is not one of `layouts`.");
+                }
+                _ if verbose => println!("Overrides are ok."),
+                _ => {}
+            }
+        }
+    }
+
     /// Check all keybinds to ensure that required values are provided
     /// Checks to see if value is provided (if required)
     /// Checks to see if keys are valid against Xkeysym
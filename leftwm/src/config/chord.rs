@@ -0,0 +1,112 @@
+//! Chords, keybinds waiting for one more key among their children, e.g. `modkey+t` then `r`.
+//!
+//! lefthk grabs the keys of a chord until one of them is pressed or `Escape` cancels it. The
+//! chords written here are cancelled as well when no key was pressed within `chord_timeout`: a
+//! [`ChordTimeout`] is sent back to lefthk, which only ends the chord it was sent for.
+use super::send_to_lefthk;
+use lefthk_core::config::command::utils::denormalize_function::DenormalizeCommandFunction;
+use lefthk_core::config::command::utils::normalized_command::NormalizedCommand;
+use lefthk_core::config::{Command, Keybind};
+use lefthk_core::errors::Error;
+use lefthk_core::worker::Worker;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+inventory::submit! {DenormalizeCommandFunction::new::<TimedChord>()}
+inventory::submit! {DenormalizeCommandFunction::new::<ChordTimeout>()}
+
+/// Number of chords started by this lefthk worker, the last one being the pending chord.
+static CHORDS_STARTED: AtomicUsize = AtomicUsize::new(0);
+
+/// Grab the keys of `keybinds` until one is pressed, or `timeout` milliseconds passed unless 0.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimedChord {
+    pub keybinds: Vec<Keybind>,
+    pub timeout: u64,
+}
+
+impl Command for TimedChord {
+    fn normalize(&self) -> NormalizedCommand {
+        normalize(self)
+    }
+
+    fn denormalize(generalized: &NormalizedCommand) -> Option<Box<Self>> {
+        ron::from_str(&generalized.0).ok()
+    }
+
+    fn execute(&self, worker: &mut Worker) -> Error {
+        worker.xwrap.grab_keys(&self.keybinds);
+        worker.chord_ctx.keybinds = Some(self.keybinds.clone());
+        let chord = CHORDS_STARTED.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.timeout > 0 {
+            let delay = Duration::from_millis(self.timeout);
+            send_to_lefthk(ChordTimeout(chord).normalize(), delay);
+        }
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        "TimedChord"
+    }
+}
+
+/// Cancel the chord started as the given number, if it is still pending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChordTimeout(pub usize);
+
+impl Command for ChordTimeout {
+    fn normalize(&self) -> NormalizedCommand {
+        normalize(self)
+    }
+
+    fn denormalize(generalized: &NormalizedCommand) -> Option<Box<Self>> {
+        ron::from_str(&generalized.0).ok()
+    }
+
+    fn execute(&self, worker: &mut Worker) -> Error {
+        if self.0 == CHORDS_STARTED.load(Ordering::SeqCst) && worker.chord_ctx.keybinds.is_some() {
+            worker.chord_ctx.elapsed = true;
+        }
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        "ChordTimeout"
+    }
+}
+
+/// Commands are sent to lefthk one per line, with the name of their type to tell them apart.
+pub(super) fn normalize<T: Serialize>(command: &T) -> NormalizedCommand {
+    let config = PrettyConfig::new()
+        .struct_names(true)
+        .new_line(" ".to_owned());
+    NormalizedCommand(ron::ser::to_string_pretty(command, config).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lefthk_core::config::command::{self, Execute};
+
+    #[test]
+    fn chords_should_be_told_apart_from_other_commands() {
+        let chord = TimedChord {
+            keybinds: vec![Keybind {
+                command: Execute::new(&"leftwm-command 'RotateTag'").normalize(),
+                modifier: vec![],
+                key: "r".to_owned(),
+            }],
+            timeout: 1500,
+        };
+        let normalized = chord.normalize();
+        assert!(!normalized.0.contains('\n'));
+        let denormalized = command::denormalize(&normalized).unwrap();
+        assert_eq!(denormalized.get_name(), "TimedChord");
+        assert_eq!(*TimedChord::denormalize(&normalized).unwrap(), chord);
+
+        let timeout = command::denormalize(&ChordTimeout(3).normalize()).unwrap();
+        assert_eq!(timeout.get_name(), "ChordTimeout");
+    }
+}
@@ -1,10 +1,11 @@
 use leftwm_core::models::{ScratchPad, Size};
+use std::collections::HashMap;
 
 use crate::Backend;
 
 #[cfg(feature = "lefthk")]
-use super::{default_terminal, exit_strategy, BaseCommand, Keybind};
-use super::{Config, Default, FocusBehaviour, LayoutMode, ThemeConfig};
+use super::{default_terminal, exit_strategy, BaseCommand, Keybind, Mousebind};
+use super::{Config, Default, FocusBehaviour, FocusFollowsWorkspace, LayoutMode, ThemeConfig};
 
 impl Default for Config {
     // We allow this because this function would be difficult to reduce. If someone would like to
@@ -22,6 +23,7 @@ impl Default for Config {
                 value: "dmenu_run".to_owned(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "p".to_owned(),
+                children: vec![],
             },
             // Mod + Shift + Enter => Open A Shell
             Keybind {
@@ -29,6 +31,7 @@ impl Default for Config {
                 value: default_terminal().to_owned(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "Return".to_owned(),
+                children: vec![],
             },
             // Mod + Shift + q => kill focused window
             Keybind {
@@ -36,6 +39,7 @@ impl Default for Config {
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "q".to_owned(),
+                children: vec![],
             },
             // Mod + Shift + r => soft reload leftwm
             Keybind {
@@ -43,6 +47,7 @@ impl Default for Config {
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "r".to_owned(),
+                children: vec![],
             },
             // Mod + Shift + x => exit leftwm
             Keybind {
@@ -50,6 +55,7 @@ impl Default for Config {
                 value: exit_strategy().to_owned(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "x".to_owned(),
+                children: vec![],
             },
             // Mod + Ctrl + l => lock the screen
             Keybind {
@@ -57,6 +63,7 @@ impl Default for Config {
                 value: "slock".to_owned(),
                 modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
                 key: "l".to_owned(),
+                children: vec![],
             },
             // Mod + Shift + w => swap the tags on the last to active workspaces
             Keybind {
@@ -64,6 +71,7 @@ impl Default for Config {
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "w".to_owned(),
+                children: vec![],
             },
             // Mod + w => move the active window to the previous workspace
             Keybind {
@@ -71,108 +79,126 @@ impl Default for Config {
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "w".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::MoveWindowUp,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "k".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::MoveWindowDown,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "j".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::MoveWindowTop,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "Return".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWindowUp,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "k".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWindowDown,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "j".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::NextLayout,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
                 key: "k".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::PreviousLayout,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
                 key: "j".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWorkspaceNext,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "l".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWorkspacePrevious,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "h".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::MoveWindowUp,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "Up".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::MoveWindowDown,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: "Down".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWindowUp,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "Up".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWindowDown,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "Down".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::NextLayout,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
                 key: "Up".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::PreviousLayout,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
                 key: "Down".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWorkspaceNext,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "Right".to_owned(),
+                children: vec![],
             },
             Keybind {
                 command: BaseCommand::FocusWorkspacePrevious,
                 value: String::default(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: "Left".to_owned(),
+                children: vec![],
             },
         ];
 
@@ -184,6 +210,7 @@ impl Default for Config {
                 value: i.to_string(),
                 modifier: Some(vec!["modkey".to_owned()].into()),
                 key: i.to_string(),
+                children: vec![],
             });
         }
 
@@ -195,6 +222,7 @@ impl Default for Config {
                 value: i.to_string(),
                 modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
                 key: i.to_string(),
+                children: vec![],
             });
         }
 
@@ -224,6 +252,7 @@ impl Default for Config {
             layouts: layouts.names(),
             layout_definitions: layouts.layouts,
             layout_mode: LayoutMode::Tag,
+            max_visible_in_stack: HashMap::new(),
             // TODO: add sane default for scratchpad config.
             // Currently default values are set in sane_dimension fn.
             scratchpad: Some(vec![scratchpad]),
@@ -231,18 +260,53 @@ impl Default for Config {
             disable_current_tag_swap: false,
             disable_tile_drag: false,
             disable_window_snap: true,
+            floating_snap_distance: 0,
             focus_behaviour: FocusBehaviour::Sloppy, // default behaviour: mouse move auto-focuses window
             focus_new_windows: true, // default behaviour: focuses windows on creation
+            mark_focus_stealers_never_focus: false,
+            reparent_windows: false,
+            wm_replaced_behavior: leftwm_core::config::WmReplacedBehavior::Exit,
+            metrics_endpoint: None,
+            i3_ipc: false,
+            remember_floating_geometry: false,
             single_window_border: true,
             insert_behavior: leftwm_core::config::InsertBehavior::Bottom,
+            max_windows_per_tag: None,
+            tag_overflow_behavior: leftwm_core::config::TagOverflowBehavior::SpillToNextTag,
+            floating_placement_behavior: leftwm_core::config::FloatingPlacementBehavior::Center,
+            picture_in_picture: leftwm_core::config::PictureInPicture::default(),
+            auto_float_max_width: None,
+            auto_float_max_height: None,
+            resize_tiled_splits: false,
+            stash_classes: vec![],
+            empty_tag_behavior: leftwm_core::config::EmptyTagBehavior::Nothing,
+            empty_tag_behavior_by_tag: HashMap::new(),
+            tag_overrides: HashMap::new(),
+            idle: leftwm_core::config::Idle::default(),
+            timers: vec![],
+            urgency_behavior: leftwm_core::config::UrgencyBehavior::ClearOnFocus,
+            signals: HashMap::new(),
+            presentation_mode_hides_docks: false,
+            floating_step: 20,
+            smart_gaps: false,
+            swallow: false,
             modkey: "Mod4".to_owned(),     // win key
             mousekey: Some("Mod4".into()), // win key
+            mousebind: Mousebind::defaults(&["mousekey".to_owned()]),
             #[cfg(feature = "lefthk")]
             keybind: commands,
             theme_setting: ThemeConfig::default(),
             state_path: None,
+            script_path: None,
+            #[cfg(feature = "scripting")]
+            script: None,
+            plugins: vec![],
+            chord_timeout: 2000,
+            #[cfg(feature = "plugins")]
+            plugin_host: None,
             sloppy_mouse_follows_focus: true,
             create_follows_cursor: None,
+            focus_follows_workspace: FocusFollowsWorkspace::Nothing,
             disable_cursor_reposition_on_resize: false,
             auto_derive_workspaces: true,
         }
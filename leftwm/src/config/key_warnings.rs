@@ -0,0 +1,143 @@
+//! Detection of unknown and deprecated keys in the config file, which serde would otherwise
+//! silently ignore.
+use std::fmt;
+
+use ron::{extensions::Extensions, Options, Value};
+
+use super::Config;
+
+/// Keys that are no longer read from the config, with what to do instead.
+const DEPRECATED_KEYS: &[(&str, &str)] = &[(
+    "max_window_width",
+    "it was removed, create a custom layout with a `Reserve` property instead",
+)];
+
+/// Unknown keys are only matched to known keys this similar.
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// A top level key of the config file that does not do what the user likely expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyWarning {
+    Unknown {
+        key: String,
+        suggestion: Option<String>,
+    },
+    Deprecated {
+        key: String,
+        hint: &'static str,
+    },
+}
+
+impl KeyWarning {
+    /// Check the keys of a `config.ron`. Contents that can't be parsed yield no warnings, the
+    /// parse error is reported when loading the config.
+    #[must_use]
+    pub fn check_ron(contents: &str) -> Vec<Self> {
+        let ron = Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES);
+        let keys = match ron.from_str::<Value>(contents) {
+            Ok(Value::Map(map)) => map
+                .keys()
+                .filter_map(|key| match key {
+                    Value::String(key) => Some(key.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        Self::check(&keys)
+    }
+
+    /// Check the keys of a `config.toml`.
+    #[must_use]
+    pub fn check_toml(contents: &str) -> Vec<Self> {
+        let keys: Vec<String> = toml::from_str::<toml::Table>(contents)
+            .map(|table| table.keys().cloned().collect())
+            .unwrap_or_default();
+        Self::check(&keys)
+    }
+
+    fn check(keys: &[String]) -> Vec<Self> {
+        let known = known_keys();
+        keys.iter()
+            .filter(|key| !known.contains(key))
+            .map(|key| {
+                if let Some((_, hint)) = DEPRECATED_KEYS.iter().find(|(k, _)| k == key) {
+                    return Self::Deprecated {
+                        key: key.clone(),
+                        hint,
+                    };
+                }
+                let suggestion = known
+                    .iter()
+                    .map(|k| (strsim::jaro_winkler(key, k), k))
+                    .filter(|(similarity, _)| *similarity >= SUGGESTION_THRESHOLD)
+                    .max_by(|(a, _), (b, _)| a.total_cmp(b))
+                    .map(|(_, k)| k.clone());
+                Self::Unknown {
+                    key: key.clone(),
+                    suggestion,
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for KeyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown {
+                key,
+                suggestion: Some(suggestion),
+            } => write!(f, "key `{key}` is unknown, did you mean `{suggestion}`?"),
+            Self::Unknown { key, .. } => write!(f, "key `{key}` is unknown and will be ignored"),
+            Self::Deprecated { key, hint } => write!(f, "key `{key}` is deprecated, {hint}"),
+        }
+    }
+}
+
+/// The keys the config is read from, taken from the serialized default config.
+fn known_keys() -> Vec<String> {
+    match serde_json::to_value(Config::default()) {
+        Ok(serde_json::Value::Object(map)) => map.keys().cloned().collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_keys_should_get_a_suggestion() {
+        let warnings = KeyWarning::check_ron("(modkey: \"Mod4\", focus_behavior: Sloppy)");
+        assert_eq!(
+            warnings,
+            vec![KeyWarning::Unknown {
+                key: "focus_behavior".to_string(),
+                suggestion: Some("focus_behaviour".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn deprecated_keys_should_be_reported_as_such() {
+        let warnings = KeyWarning::check_toml("modkey = \"Mod4\"\nmax_window_width = 1000");
+        assert!(matches!(
+            warnings.as_slice(),
+            [KeyWarning::Deprecated { key, .. }] if key == "max_window_width"
+        ));
+    }
+
+    #[test]
+    fn unrelated_keys_should_not_get_a_suggestion() {
+        let warnings = KeyWarning::check_ron("(zzz: 1)");
+        assert_eq!(
+            warnings,
+            vec![KeyWarning::Unknown {
+                key: "zzz".to_string(),
+                suggestion: None,
+            }]
+        );
+    }
+}
@@ -5,10 +5,12 @@ use super::BaseCommand;
 #[cfg(feature = "lefthk")]
 use crate::Config;
 #[cfg(feature = "lefthk")]
-use anyhow::{ensure, Context, Result};
+use anyhow::{anyhow, ensure, Context, Result};
 #[cfg(feature = "lefthk")]
 use lefthk_core::config::Command;
 #[cfg(feature = "lefthk")]
+use leftwm_core::utils::modmask_lookup::into_modmask;
+#[cfg(feature = "lefthk")]
 use std::fmt::Write;
 #[cfg(feature = "lefthk")]
 use std::str::FromStr;
@@ -21,6 +23,9 @@ pub struct Keybind {
     pub value: String,
     pub modifier: Option<Modifier>,
     pub key: String,
+    /// The keybinds waited for after this one, when its command is `Chord`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Keybind>,
 }
 
 #[cfg(feature = "lefthk")]
@@ -31,7 +36,10 @@ impl Keybind {
     ) -> Result<lefthk_core::config::Keybind> {
         let value_is_some = !self.value.is_empty();
         match &self.command {
-            BaseCommand::Execute | BaseCommand::LoadTheme => {
+            BaseCommand::Execute
+            | BaseCommand::LoadTheme
+            | BaseCommand::RunScript
+            | BaseCommand::Schedule => {
                 ensure!(value_is_some, "value must not be empty");
             }
             BaseCommand::ToggleScratchPad
@@ -54,12 +62,33 @@ impl Keybind {
             BaseCommand::GotoTag => {
                 usize::from_str(&self.value).context("invalid index value for GotoTag")?;
             }
+            BaseCommand::ActivateWindow => {
+                ensure!(
+                    matches!(
+                        self.value.split_once(' '),
+                        Some(("handle" | "class" | "title", value)) if !value.is_empty()
+                    ),
+                    "Value should be `handle`, `class` or `title` followed by the value to match"
+                );
+            }
             BaseCommand::FocusWindowTop if value_is_some => {
                 bool::from_str(&self.value).context("invalid boolean value for FocusWindowTop")?;
             }
+            BaseCommand::FocusPrevious if value_is_some => {
+                usize::from_str(&self.value).context("invalid steps value for FocusPrevious")?;
+            }
+            BaseCommand::MoveWindowToNextWorkspace | BaseCommand::MoveWindowToPreviousWorkspace
+                if value_is_some =>
+            {
+                bool::from_str(&self.value).context("invalid boolean value for follow")?;
+            }
             BaseCommand::SwapWindowTop if value_is_some => {
                 bool::from_str(&self.value).context("invalid boolean value for SwapWindowTop")?;
             }
+            BaseCommand::SendSelectionToTag => {
+                usize::from_str(&self.value)
+                    .context("invalid index value for SendSelectionToTag")?;
+            }
             BaseCommand::MoveToTag => {
                 usize::from_str(&self.value).context("invalid index value for SendWindowToTag")?;
             }
@@ -79,6 +108,21 @@ impl Keybind {
                 f32::from_str(&self.value)
                     .context("invalid margin multiplier for SetMarginMultiplier")?;
             }
+            BaseCommand::SetModKey => {
+                ensure!(
+                    !into_modmask(std::slice::from_ref(&self.value)).is_empty(),
+                    "Value should be a modifier, such as `Mod1` or `Mod4`"
+                );
+            }
+            BaseCommand::SetInsertBehavior => {
+                ensure!(
+                    matches!(
+                        self.value.as_str(),
+                        "Top" | "Bottom" | "BeforeCurrent" | "AfterCurrent" | "AfterSmallest"
+                    ),
+                    "Value should be `Top`, `Bottom`, `BeforeCurrent`, `AfterCurrent` or `AfterSmallest`"
+                );
+            }
             BaseCommand::FocusNextTag | BaseCommand::FocusPreviousTag if value_is_some => {
                 ensure!(
                 usize::from_str(&self.value).is_ok()
@@ -86,6 +130,7 @@ impl Keybind {
             "Value should be empty, or one of 'default', 'goto_empty', 'ignore_empty', 'goto_used', 'ignore_used'"
                 );
             }
+            BaseCommand::Chord => return self.try_convert_chord(config),
             _ => {}
         }
 
@@ -107,15 +152,77 @@ impl Keybind {
         };
         Ok(lefthk_core::config::Keybind {
             command: lefthk_core::config::command::Execute::new(&command).normalize(),
-            modifier: self
-                .modifier
-                .as_ref()
-                .unwrap_or(&"None".into())
-                .clone()
-                .into(),
+            modifier: self.lefthk_modifier(),
             key: self.key.clone(),
         })
     }
+
+    /// A chord grabbing the keys of its children once pressed, and `Escape` to cancel it.
+    fn try_convert_chord(&self, config: &Config) -> Result<lefthk_core::config::Keybind> {
+        ensure!(!self.children.is_empty(), "children must not be empty");
+        let mut keybinds = self
+            .children
+            .iter()
+            .map(|child| {
+                ensure!(
+                    lefthk_core::xkeysym_lookup::into_keysym(&child.key).is_some(),
+                    "Key `{}` of a chord child is not valid",
+                    child.key
+                );
+                child
+                    .try_convert_to_lefthk_keybind(config)
+                    .map_err(|err| anyhow!("{} for chord child {}", err, child.key))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let no_modifier = |modifier: &[String]| modifier.iter().all(|m| m == "None");
+        if !keybinds
+            .iter()
+            .any(|kb| kb.key == "Escape" && no_modifier(&kb.modifier))
+        {
+            keybinds.push(lefthk_core::config::Keybind {
+                command: lefthk_core::config::command::ExitChord::new().normalize(),
+                modifier: vec!["None".to_owned()],
+                key: "Escape".to_owned(),
+            });
+        }
+        let chord = super::chord::TimedChord {
+            keybinds,
+            timeout: config.chord_timeout,
+        };
+        Ok(lefthk_core::config::Keybind {
+            command: chord.normalize(),
+            modifier: self.lefthk_modifier(),
+            key: self.key.clone(),
+        })
+    }
+
+    /// Substitute `modkey` in the modifiers of this keybind and of its children.
+    pub fn replace_modkey(&mut self, modkey: &str) {
+        if let Some(ref mut modifier) = self.modifier {
+            match modifier {
+                Modifier::Single(m) if m == "modkey" => *m = modkey.to_owned(),
+                Modifier::List(ms) => {
+                    for m in ms {
+                        if m == "modkey" {
+                            *m = modkey.to_owned();
+                        }
+                    }
+                }
+                Modifier::Single(_) => {}
+            }
+        }
+        for child in &mut self.children {
+            child.replace_modkey(modkey);
+        }
+    }
+
+    fn lefthk_modifier(&self) -> Vec<String> {
+        self.modifier
+            .as_ref()
+            .unwrap_or(&"None".into())
+            .clone()
+            .into()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
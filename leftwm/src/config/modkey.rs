@@ -0,0 +1,66 @@
+//! Switching the modkey of lefthk without editing the config, for `SetModKey`.
+//!
+//! lefthk grabs the keys of the config it reads from the file, so a [`ReplaceModkey`] sent to it
+//! keeps the new modkey here and reloads the keys. The modkey is only used by the reload it asked
+//! for: a later `Reload` goes back to the modkey of the file.
+use super::chord::normalize;
+use lefthk_core::config::command::utils::denormalize_function::DenormalizeCommandFunction;
+use lefthk_core::config::command::utils::normalized_command::NormalizedCommand;
+use lefthk_core::config::Command;
+use lefthk_core::errors::Error;
+use lefthk_core::worker::{Status, Worker};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+inventory::submit! {DenormalizeCommandFunction::new::<ReplaceModkey>()}
+
+/// The modkey the keys are to be grabbed with on the next reload of the worker.
+static MODKEY: Mutex<Option<String>> = Mutex::new(None);
+
+/// Grab the keys again with the given modkey in place of the configured one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaceModkey(pub String);
+
+impl Command for ReplaceModkey {
+    fn normalize(&self) -> NormalizedCommand {
+        normalize(self)
+    }
+
+    fn denormalize(generalized: &NormalizedCommand) -> Option<Box<Self>> {
+        ron::from_str(&generalized.0).ok()
+    }
+
+    fn execute(&self, worker: &mut Worker) -> Error {
+        if let Ok(mut modkey) = MODKEY.lock() {
+            *modkey = Some(self.0.clone());
+        }
+        worker.status = Status::Reload;
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        "ReplaceModkey"
+    }
+}
+
+/// The modkey sent by a [`ReplaceModkey`] since the keys were last grabbed, if any.
+pub fn take_replaced_modkey() -> Option<String> {
+    MODKEY.lock().ok().and_then(|mut modkey| modkey.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lefthk_core::config::command;
+
+    #[test]
+    fn replaced_modkeys_should_be_told_apart_from_other_commands() {
+        let replace = ReplaceModkey("Mod1".to_owned());
+        let denormalized = command::denormalize(&replace.normalize()).unwrap();
+        assert_eq!(denormalized.get_name(), "ReplaceModkey");
+        assert_eq!(
+            *ReplaceModkey::denormalize(&replace.normalize()).unwrap(),
+            replace
+        );
+    }
+}
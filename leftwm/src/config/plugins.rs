@@ -0,0 +1,562 @@
+//! WebAssembly plugins, loaded from the files listed in `plugins`.
+//!
+//! Plugins add custom commands, layouts and event filters without depending on the internals
+//! of leftwm. They run sandboxed: they cannot import anything, get a limited amount of memory
+//! and are stopped after a fixed amount of work per call. A plugin file that changed is loaded
+//! again the next time plugins are used; the names of its layouts are only picked up by
+//! reloading the config.
+//!
+//! # ABI, version 1
+//!
+//! Values are passed as UTF-8 JSON in the memory of the plugin. leftwm writes its input to a
+//! buffer returned by `leftwm_alloc` and hands it back to `leftwm_free` after the call, and
+//! plugins return their output as `(ptr << 32) | len`, or `0` for none. A plugin exports:
+//!
+//! - `memory` and `leftwm_alloc(len: i32) -> i32`, returning a buffer of `len` bytes,
+//! - `leftwm_free(ptr: i32, len: i32)`, releasing a buffer of `leftwm_alloc`,
+//! - optionally `leftwm_layouts() -> i64`: the names of the layouts it arranges, such as
+//!   `["Spiral"]`,
+//! - optionally `leftwm_arrange(ptr: i32, len: i32) -> i64`: gets
+//!   `{"layout": "Spiral", "windows": 2, "area": {"x": 0, "y": 0, "w": 1920, "h": 1080}}` and
+//!   returns the tile of each window, such as `[{"x": 0, "y": 0, "w": 960, "h": 1080}, ...]`,
+//! - optionally `leftwm_command(ptr: i32, len: i32) -> i64`: gets the commands leftwm does not
+//!   know, as `{"command": "Name", "args": "rest of the line"}`, and returns `null` if it does
+//!   not know them either, or else the commands to run, such as `["SendWindowToTag 3"]`,
+//! - optionally `leftwm_filter_event(ptr: i32, len: i32) -> i32`: gets display events, as
+//!   `{"kind": "WindowChange", "window": 4194305}`, and returns `0` to drop them. Only key and
+//!   mouse bindings and changes to the properties of windows are sent; other events, such as
+//!   windows and screens coming and going, always reach leftwm.
+use anyhow::{anyhow, Result};
+use leftwm_core::layouts::LayoutArranger;
+use leftwm_core::models::{Handle, WindowHandle};
+use leftwm_core::utils::command_pipe::parse_command;
+use leftwm_core::{Command, DisplayEvent};
+use leftwm_layouts::geometry::Rect;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+use wasmi::{Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Plugins are stopped after using this much fuel, about as many instructions, in a call.
+const FUEL: u64 = 10_000_000;
+
+/// The most memory a plugin can grow to.
+const MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Plugin files are checked for changes at most this often.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A window tile, as plugins see it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct Tile {
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+}
+
+impl From<&Rect> for Tile {
+    fn from(rect: &Rect) -> Self {
+        Self {
+            x: rect.x,
+            y: rect.y,
+            w: rect.w,
+            h: rect.h,
+        }
+    }
+}
+
+impl From<Tile> for Rect {
+    fn from(tile: Tile) -> Self {
+        Rect::new(tile.x, tile.y, tile.w, tile.h)
+    }
+}
+
+#[derive(Serialize)]
+struct ArrangeRequest<'a> {
+    layout: &'a str,
+    windows: usize,
+    area: Tile,
+}
+
+#[derive(Serialize)]
+struct CommandRequest<'a> {
+    command: &'a str,
+    args: &'a str,
+}
+
+#[derive(Serialize)]
+struct EventRequest<'a, H: Handle> {
+    kind: &'a str,
+    #[serde(bound = "")]
+    window: Option<&'a WindowHandle<H>>,
+}
+
+/// The instance of a plugin module.
+struct Loaded {
+    store: Store<StoreLimits>,
+    instance: Instance,
+    memory: Memory,
+    layouts: Vec<String>,
+    filters_events: bool,
+}
+
+impl Loaded {
+    fn new(engine: &Engine, path: &Path) -> Result<Self> {
+        let module = Module::new(engine, std::fs::read(path)?.as_slice())?;
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY)
+            .instances(1)
+            .memories(1)
+            .tables(1)
+            .build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+        store.add_fuel(FUEL).map_err(wasmi::Error::from)?;
+        // Nothing is linked, plugins have no way out of their sandbox.
+        let instance = Linker::new(engine)
+            .instantiate(&mut store, &module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| anyhow!("no `memory` export"))?;
+        if instance.get_export(&store, "leftwm_free").is_none() {
+            return Err(anyhow!("no `leftwm_free` export"));
+        }
+        let mut loaded = Self {
+            store,
+            instance,
+            memory,
+            layouts: vec![],
+            filters_events: false,
+        };
+        loaded.filters_events = loaded.exports("leftwm_filter_event");
+        if loaded.exports("leftwm_layouts") {
+            loaded.refuel()?;
+            let output = loaded
+                .instance
+                .get_typed_func::<(), i64>(&loaded.store, "leftwm_layouts")?
+                .call(&mut loaded.store, ())?;
+            loaded.layouts = loaded.read(output)?.unwrap_or_default();
+        }
+        Ok(loaded)
+    }
+
+    fn exports(&self, name: &str) -> bool {
+        self.instance.get_export(&self.store, name).is_some()
+    }
+
+    /// Give the plugin its fuel for a call.
+    fn refuel(&mut self) -> Result<()> {
+        let remaining = self.store.consume_fuel(0).map_err(wasmi::Error::from)?;
+        self.store
+            .add_fuel(FUEL - remaining)
+            .map_err(wasmi::Error::from)?;
+        Ok(())
+    }
+
+    /// Write JSON to a buffer of the plugin and run `call` with its address and length, then
+    /// free the buffer, whether the call failed or not.
+    fn with_input<T>(
+        &mut self,
+        input: &[u8],
+        call: impl FnOnce(&mut Self, i32, i32) -> Result<T>,
+    ) -> Result<T> {
+        self.refuel()?;
+        let len = i32::try_from(input.len())?;
+        let ptr = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "leftwm_alloc")?
+            .call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, usize::try_from(ptr)?, input)
+            .map_err(wasmi::Error::from)?;
+        let output = call(self, ptr, len);
+        self.refuel()?;
+        self.instance
+            .get_typed_func::<(i32, i32), ()>(&self.store, "leftwm_free")?
+            .call(&mut self.store, (ptr, len))?;
+        output
+    }
+
+    /// Read the output a plugin returned as `(ptr << 32) | len`.
+    fn read<T: DeserializeOwned>(&self, output: i64) -> Result<Option<T>> {
+        if output == 0 {
+            return Ok(None);
+        }
+        let output = output as u64;
+        let ptr = usize::try_from(output >> 32)?;
+        let len = usize::try_from(output & 0xffff_ffff)?;
+        let bytes = self
+            .memory
+            .data(&self.store)
+            .get(ptr..ptr + len)
+            .ok_or_else(|| anyhow!("output out of bounds"))?;
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Call an export taking JSON input and returning JSON output.
+    fn call<T: DeserializeOwned>(
+        &mut self,
+        name: &str,
+        input: &impl Serialize,
+    ) -> Result<Option<T>> {
+        self.with_input(&serde_json::to_vec(input)?, |loaded, ptr, len| {
+            let output = loaded
+                .instance
+                .get_typed_func::<(i32, i32), i64>(&loaded.store, name)?
+                .call(&mut loaded.store, (ptr, len))?;
+            loaded.read(output)
+        })
+    }
+
+    fn filter_event(&mut self, input: &[u8]) -> Result<bool> {
+        self.with_input(input, |loaded, ptr, len| {
+            let keep = loaded
+                .instance
+                .get_typed_func::<(i32, i32), i32>(&loaded.store, "leftwm_filter_event")?
+                .call(&mut loaded.store, (ptr, len))?;
+            Ok(keep != 0)
+        })
+    }
+}
+
+struct Plugin {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    loaded: Option<Loaded>,
+}
+
+impl Plugin {
+    fn load(&mut self, engine: &Engine) {
+        self.modified = modified(&self.path);
+        self.loaded = Loaded::new(engine, &self.path)
+            .map_err(|err| {
+                tracing::error!("Unable to load plugin {}: {}", self.path.display(), err);
+            })
+            .ok();
+    }
+
+    fn loaded(&mut self, export: &str) -> Option<&mut Loaded> {
+        self.loaded.as_mut().filter(|loaded| loaded.exports(export))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+struct Plugins {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+    checked: Instant,
+}
+
+impl Plugins {
+    /// Load the plugins whose file changed since they were loaded.
+    fn reload_changed(&mut self) {
+        if self.checked.elapsed() < RELOAD_INTERVAL {
+            return;
+        }
+        self.checked = Instant::now();
+        for plugin in &mut self.plugins {
+            if modified(&plugin.path) != plugin.modified {
+                tracing::info!("Reloading plugin {}", plugin.path.display());
+                plugin.load(&self.engine);
+            }
+        }
+    }
+}
+
+/// The loaded plugins.
+pub struct Host {
+    plugins: Mutex<Plugins>,
+}
+
+impl fmt::Debug for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Host").finish_non_exhaustive()
+    }
+}
+
+impl Host {
+    /// Load the plugins at `paths`. Plugins that fail to load are logged and left out until
+    /// their file changes.
+    #[must_use]
+    pub fn load(paths: &[PathBuf]) -> Self {
+        let engine = engine();
+        let plugins = paths
+            .iter()
+            .map(|path| {
+                let mut plugin = Plugin {
+                    path: path.clone(),
+                    modified: None,
+                    loaded: None,
+                };
+                plugin.load(&engine);
+                plugin
+            })
+            .collect();
+        Self {
+            plugins: Mutex::new(Plugins {
+                engine,
+                plugins,
+                checked: Instant::now(),
+            }),
+        }
+    }
+
+    fn plugins(&self) -> MutexGuard<'_, Plugins> {
+        let mut plugins = self.plugins.lock().unwrap_or_else(PoisonError::into_inner);
+        plugins.reload_changed();
+        plugins
+    }
+
+    /// Ask the plugins for the commands to run for a command leftwm does not know. Returns
+    /// `None` if no plugin handles it.
+    pub fn command<H: Handle>(&self, command: &str, args: &str) -> Option<Vec<Command<H>>> {
+        let request = CommandRequest { command, args };
+        let mut plugins = self.plugins();
+        let commands: Vec<String> = plugins.plugins.iter_mut().find_map(|plugin| {
+            let path = plugin.path.clone();
+            plugin
+                .loaded("leftwm_command")?
+                .call("leftwm_command", &request)
+                .map_err(|err| warn_failed(&path, "leftwm_command", &err))
+                .ok()?
+        })?;
+        Some(
+            commands
+                .iter()
+                .filter_map(|text| match parse_command(text) {
+                    // Custom commands could call the plugin again.
+                    Ok(Command::Other(_)) | Err(_) => {
+                        tracing::warn!("Plugin issued an invalid command: {}", text);
+                        None
+                    }
+                    Ok(command) => Some(command),
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether the plugins let the event loop handle `event`.
+    pub fn filter_event<H: Handle>(&self, event: &DisplayEvent<H>) -> bool {
+        // Dropping other events would leave the state out of sync with the display server.
+        if !event.is_droppable() {
+            return true;
+        }
+        let mut plugins = self.plugins();
+        let mut filters = plugins.plugins.iter_mut().filter(|plugin| {
+            plugin
+                .loaded
+                .as_ref()
+                .is_some_and(|loaded| loaded.filters_events)
+        });
+        let Some(first) = filters.next() else {
+            return true;
+        };
+        let request = EventRequest {
+            kind: event.name(),
+            window: event.window(),
+        };
+        // Serialized once for all the plugins.
+        let Ok(input) = serde_json::to_vec(&request) else {
+            return true;
+        };
+        std::iter::once(first).chain(filters).all(|plugin| {
+            let path = &plugin.path;
+            plugin.loaded.as_mut().map_or(true, |loaded| {
+                loaded
+                    .filter_event(&input)
+                    .map_err(|err| warn_failed(path, "leftwm_filter_event", &err))
+                    .unwrap_or(true)
+            })
+        })
+    }
+}
+
+impl LayoutArranger for Host {
+    fn layouts(&self) -> Vec<String> {
+        self.plugins()
+            .plugins
+            .iter()
+            .filter_map(|plugin| plugin.loaded.as_ref())
+            .flat_map(|loaded| loaded.layouts.clone())
+            .collect()
+    }
+
+    fn arrange(&self, layout: &str, count: usize, area: &Rect) -> Option<Vec<Rect>> {
+        let request = ArrangeRequest {
+            layout,
+            windows: count,
+            area: Tile::from(area),
+        };
+        let mut plugins = self.plugins();
+        let plugin = plugins.plugins.iter_mut().find(|plugin| {
+            plugin
+                .loaded
+                .as_ref()
+                .is_some_and(|loaded| loaded.layouts.iter().any(|name| name == layout))
+        })?;
+        let path = plugin.path.clone();
+        let tiles: Vec<Tile> = plugin
+            .loaded("leftwm_arrange")?
+            .call("leftwm_arrange", &request)
+            .map_err(|err| warn_failed(&path, "leftwm_arrange", &err))
+            .ok()??;
+        Some(tiles.into_iter().map(Rect::from).collect())
+    }
+}
+
+fn engine() -> Engine {
+    let mut config = wasmi::Config::default();
+    config.consume_fuel(true);
+    Engine::new(&config)
+}
+
+/// Load the plugin at `path` on its own, to check it.
+///
+/// # Errors
+///
+/// Will error if the plugin cannot be read, is not a valid module or does not follow the ABI.
+pub fn check(path: &Path) -> Result<()> {
+    Loaded::new(&engine(), path).map(|_| ())
+}
+
+fn warn_failed(path: &Path, export: &str, err: &anyhow::Error) {
+    tracing::warn!("Plugin {} failed in `{}`: {}", path.display(), export, err);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leftwm_core::models::{Screen, Window};
+    use std::io::Write;
+
+    /// A plugin with a bump allocator, freeing only its last buffer, and `exports`, with
+    /// functions returning the JSON of `outputs`.
+    fn plugin(exports: &str, outputs: &[(&str, &str)]) -> tempfile::NamedTempFile {
+        let mut source = String::from(
+            r#"(module
+                (memory (export "memory") 1)
+                (global $next (mut i32) (i32.const 4096))
+                (func (export "leftwm_alloc") (param $len i32) (result i32)
+                    (local $ptr i32)
+                    (local.set $ptr (global.get $next))
+                    (global.set $next (i32.add (global.get $next) (local.get $len)))
+                    (local.get $ptr))
+                (func (export "leftwm_free") (param $ptr i32) (param $len i32)
+                    (if (i32.eq (i32.add (local.get $ptr) (local.get $len)) (global.get $next))
+                        (then (global.set $next (local.get $ptr)))))"#,
+        );
+        source.push_str(exports);
+        for (i, (name, json)) in outputs.iter().enumerate() {
+            let offset = i * 256;
+            let params = if *name == "leftwm_layouts" {
+                ""
+            } else {
+                "(param i32 i32)"
+            };
+            source.push_str(&format!(
+                r#"(data (i32.const {offset}) "{}")
+                (func (export "{name}") {params} (result i64) (i64.const {}))"#,
+                json.replace('"', "\\\""),
+                (offset << 32) | json.len(),
+            ));
+        }
+        source.push(')');
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&wat::parse_str(source).unwrap()).unwrap();
+        file
+    }
+
+    #[test]
+    fn plugins_should_arrange_layouts_and_handle_commands() {
+        let file = plugin(
+            "",
+            &[
+                ("leftwm_layouts", r#"["Spiral"]"#),
+                ("leftwm_arrange", r#"[{"x":1,"y":2,"w":3,"h":4}]"#),
+                ("leftwm_command", r#"["FocusWindowUp","NotACommand"]"#),
+            ],
+        );
+        let host = Host::load(&[file.path().to_path_buf()]);
+
+        assert_eq!(host.layouts(), vec!["Spiral".to_string()]);
+        let area = Rect::new(0, 0, 100, 100);
+        assert_eq!(
+            host.arrange("Spiral", 1, &area),
+            Some(vec![Rect::new(1, 2, 3, 4)])
+        );
+        assert_eq!(host.arrange("Monocle", 1, &area), None);
+        assert_eq!(
+            host.command::<i32>("Custom", "args"),
+            Some(vec![Command::FocusWindowUp])
+        );
+    }
+
+    #[test]
+    fn runaway_plugins_should_be_stopped() {
+        let file = plugin(
+            r#"(func (export "leftwm_filter_event") (param i32 i32) (result i32)
+                (loop $spin (br $spin))
+                (i32.const 0))"#,
+            &[],
+        );
+        let host = Host::load(&[file.path().to_path_buf()]);
+        let event = DisplayEvent::<i32>::TitleBarPressed(WindowHandle(1));
+        assert!(host.filter_event(&event));
+    }
+
+    #[test]
+    fn plugins_should_only_filter_events_safe_to_drop() {
+        let file = plugin(
+            r#"(func (export "leftwm_filter_event") (param i32 i32) (result i32)
+                (i32.const 0))"#,
+            &[],
+        );
+        let host = Host::load(&[file.path().to_path_buf()]);
+        assert!(!host.filter_event(&DisplayEvent::<i32>::TitleBarPressed(WindowHandle(1))));
+        let window = Window::new(WindowHandle(1), None, None);
+        assert!(host.filter_event(&DisplayEvent::<i32>::WindowCreate(window, 0, 0)));
+        assert!(host.filter_event(&DisplayEvent::<i32>::WindowDestroy(WindowHandle(1))));
+        assert!(host.filter_event(&DisplayEvent::<i32>::ScreenCreate(Screen::default())));
+        assert!(host.filter_event(&DisplayEvent::<i32>::ScreenDestroy("HDMI-1".to_string())));
+        assert!(host.filter_event(&DisplayEvent::<i32>::Movement(WindowHandle(1), 0, 0)));
+    }
+
+    #[test]
+    fn plugins_should_get_their_input_buffers_back() {
+        let file = plugin(
+            r#"(func (export "leftwm_filter_event") (param i32 i32) (result i32)
+                (i32.const 0))"#,
+            &[],
+        );
+        let host = Host::load(&[file.path().to_path_buf()]);
+        // Without freeing, the inputs would overflow the single page of memory of the plugin.
+        for i in 0..10_000 {
+            assert!(!host.filter_event(&DisplayEvent::<i32>::TitleBarPressed(WindowHandle(i))));
+        }
+    }
+
+    #[test]
+    fn plugins_should_not_import_anything() {
+        let module = wat::parse_str(
+            r#"(module
+                (import "env" "exit" (func $exit))
+                (memory (export "memory") 1)
+                (func (export "leftwm_alloc") (param i32) (result i32) (i32.const 0)))"#,
+        )
+        .unwrap();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&module).unwrap();
+        let host = Host::load(&[file.path().to_path_buf()]);
+        assert!(host.plugins().plugins[0].loaded.is_none());
+    }
+}
@@ -0,0 +1,339 @@
+//! User scripts written in [Rhai](https://rhai.rs), loaded from `script_path`.
+//!
+//! A script defines functions run by the `RunScript <function> [args]` command, and may define
+//! hook functions run when the state changes:
+//!
+//! ```rhai
+//! fn on_window_created(window) {}  // `window` is a map, see `windows()`
+//! fn on_window_destroyed(handle) {}
+//! fn on_focus_changed(window) {}   // `()` when no window is focused
+//! fn on_tag_changed(workspace, tag) {}
+//! fn on_tag_emptied(workspace, tag) {}  // the last window of the shown tag went away
+//! fn on_user_idle() {}                  // no input for the `idle` timeout
+//! fn on_user_active() {}
+//! ```
+//!
+//! Scripts only see a copy of the state and change it through the same commands as
+//! `leftwm-command`, which are run once the script function returns:
+//!
+//! - `windows()`: the windows, as maps with `handle`, `class`, `title`, `tag`, `floating` and
+//!   `focused` keys,
+//! - `focused_window()`: the focused window, or `()`,
+//! - `current_tag()`: the tag of the focused workspace, or `()`,
+//! - `command(text)`: run a command, e.g. `command("SendWindowToTag 3")`,
+//! - `activate_window(handle)`: switch to the tag of a window and focus it.
+use leftwm_core::models::{Handle, WindowHandle};
+use leftwm_core::utils::command_pipe::parse_command;
+use leftwm_core::{Command, DisplayServer, Hook, Manager, State, Window};
+use rhai::{Array, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use std::fmt;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::Config;
+
+/// Scripts are stopped after this many operations, so a runaway loop cannot hang the window
+/// manager.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// What script functions can see of the state, and the commands they issued.
+#[derive(Debug, Default)]
+struct Context {
+    windows: Array,
+    focused: Dynamic,
+    tag: Dynamic,
+    commands: Vec<String>,
+}
+
+/// A compiled user script.
+#[derive(Clone)]
+pub struct Script {
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+    context: Arc<Mutex<Context>>,
+}
+
+impl fmt::Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Script").finish_non_exhaustive()
+    }
+}
+
+// The engine holds callbacks behind trait objects. A panic ends the worker, so no script is
+// used after one.
+impl UnwindSafe for Script {}
+impl RefUnwindSafe for Script {}
+
+impl Script {
+    /// Compile the script at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the file cannot be read or has a syntax error.
+    pub fn load(path: &Path) -> Result<Self, Box<rhai::EvalAltResult>> {
+        let context = Arc::new(Mutex::new(Context::default()));
+        let engine = build_engine(&context);
+        let ast = engine.compile_file(path.to_path_buf())?;
+        Ok(Self {
+            engine: Arc::new(engine),
+            ast: Arc::new(ast),
+            context,
+        })
+    }
+
+    /// Whether the script defines `name` taking `arity` arguments.
+    fn defines(&self, name: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == arity)
+    }
+
+    /// Call a script function and return the commands it issued.
+    fn call<H: Handle>(
+        &self,
+        state: &State<H>,
+        name: &str,
+        args: impl FuncArgs,
+    ) -> Vec<Command<H>> {
+        {
+            let mut context = lock(&self.context);
+            context.windows = windows(state);
+            context.focused = focused_window(state);
+            context.tag = state
+                .focus_manager
+                .workspace(&state.workspaces)
+                .and_then(|ws| ws.tag)
+                .map_or(Dynamic::UNIT, |tag| Dynamic::from(tag as i64));
+        }
+        parse_commands(&self.run(name, args))
+    }
+
+    /// Call a script function with the current context and return the commands it issued.
+    fn run(&self, name: &str, args: impl FuncArgs) -> Vec<String> {
+        lock(&self.context).commands.clear();
+        if let Err(err) = self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+        {
+            tracing::warn!("Script function `{}` failed: {}", name, err);
+        }
+        std::mem::take(&mut lock(&self.context).commands)
+    }
+}
+
+fn parse_commands<H: Handle>(commands: &[String]) -> Vec<Command<H>> {
+    commands
+        .iter()
+        .filter_map(|text| match parse_command(text) {
+            // Custom commands could run scripts again.
+            Ok(Command::Other(_)) | Err(_) => {
+                tracing::warn!("Script issued an invalid command: {}", text);
+                None
+            }
+            Ok(command) => Some(command),
+        })
+        .collect()
+}
+
+fn build_engine(context: &Arc<Mutex<Context>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.disable_symbol("eval");
+    engine.on_print(|text| tracing::info!("Script: {}", text));
+    engine.on_debug(|text, _, pos| tracing::debug!("Script ({}): {}", pos, text));
+
+    let ctx = context.clone();
+    engine.register_fn("windows", move || lock(&ctx).windows.clone());
+    let ctx = context.clone();
+    engine.register_fn("focused_window", move || lock(&ctx).focused.clone());
+    let ctx = context.clone();
+    engine.register_fn("current_tag", move || lock(&ctx).tag.clone());
+    let ctx = context.clone();
+    engine.register_fn("command", move |text: &str| {
+        lock(&ctx).commands.push(text.to_string());
+    });
+    let ctx = context.clone();
+    engine.register_fn("activate_window", move |handle: i64| {
+        lock(&ctx)
+            .commands
+            .push(format!("ActivateWindow handle {handle}"));
+    });
+    engine
+}
+
+fn lock(context: &Mutex<Context>) -> std::sync::MutexGuard<'_, Context> {
+    context.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn handle_to_script<H: Handle>(handle: WindowHandle<H>) -> Dynamic {
+    serde_json::to_value(handle)
+        .ok()
+        .and_then(|value| value.as_i64())
+        .map_or(Dynamic::UNIT, Dynamic::from)
+}
+
+fn window_to_script<H: Handle>(window: &Window<H>, focused: bool) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("handle".into(), handle_to_script(window.handle));
+    map.insert(
+        "class".into(),
+        window
+            .res_class
+            .clone()
+            .map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map.insert(
+        "title".into(),
+        window.name.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map.insert(
+        "tag".into(),
+        window
+            .tag
+            .map_or(Dynamic::UNIT, |tag| Dynamic::from(tag as i64)),
+    );
+    map.insert("floating".into(), window.floating().into());
+    map.insert("focused".into(), focused.into());
+    map.into()
+}
+
+fn windows<H: Handle>(state: &State<H>) -> Array {
+    let focused = state.focus_manager.window(&state.windows).map(|w| w.handle);
+    state
+        .windows
+        .iter()
+        .map(|w| window_to_script(w, Some(w.handle) == focused))
+        .collect()
+}
+
+fn focused_window<H: Handle>(state: &State<H>) -> Dynamic {
+    state
+        .focus_manager
+        .window(&state.windows)
+        .map_or(Dynamic::UNIT, |w| window_to_script(w, true))
+}
+
+/// Run the commands a script issued. Returns whether the display needs a refresh.
+fn run_commands<H: Handle, SERVER: DisplayServer<H>>(
+    commands: Vec<Command<H>>,
+    manager: &mut Manager<H, Config, SERVER>,
+) -> bool {
+    let mut refresh = false;
+    for command in &commands {
+        refresh = manager.command_handler(command) || refresh;
+    }
+    refresh
+}
+
+/// Run the script function of a `RunScript` command, with its arguments if it takes them.
+/// Returns `None` if the script has no such function.
+pub fn run_function<H: Handle, SERVER: DisplayServer<H>>(
+    name: &str,
+    args: &str,
+    manager: &mut Manager<H, Config, SERVER>,
+) -> Option<bool> {
+    let script = manager.config.script.clone()?;
+    let commands = if script.defines(name, 1) {
+        script.call(&manager.state, name, (args.to_string(),))
+    } else if script.defines(name, 0) {
+        script.call(&manager.state, name, ())
+    } else {
+        return None;
+    };
+    Some(run_commands(commands, manager))
+}
+
+/// Run the hook function of the script for `hook`, if it has one.
+pub fn run_hook<H: Handle, SERVER: DisplayServer<H>>(
+    hook: &Hook<H>,
+    manager: &mut Manager<H, Config, SERVER>,
+) -> bool {
+    let Some(script) = manager.config.script.clone() else {
+        return false;
+    };
+    let state = &manager.state;
+    let commands = match hook {
+        Hook::WindowCreated(handle) if script.defines("on_window_created", 1) => {
+            let Some(window) = state.windows.iter().find(|w| &w.handle == handle) else {
+                return false;
+            };
+            let focused = state.focus_manager.window(&state.windows).map(|w| w.handle);
+            let window = window_to_script(window, focused == Some(*handle));
+            script.call(state, "on_window_created", (window,))
+        }
+        Hook::WindowDestroyed(handle) if script.defines("on_window_destroyed", 1) => {
+            script.call(state, "on_window_destroyed", (handle_to_script(*handle),))
+        }
+        Hook::FocusChanged(_) if script.defines("on_focus_changed", 1) => {
+            script.call(state, "on_focus_changed", (focused_window(state),))
+        }
+        Hook::TagChanged { workspace, tag } if script.defines("on_tag_changed", 2) => {
+            let tag = tag.map_or(Dynamic::UNIT, |tag| Dynamic::from(tag as i64));
+            let args = (Dynamic::from(*workspace as i64), tag);
+            script.call(state, "on_tag_changed", args)
+        }
+        Hook::TagEmptied { workspace, tag } if script.defines("on_tag_emptied", 2) => {
+            let args = (Dynamic::from(*workspace as i64), Dynamic::from(*tag as i64));
+            script.call(state, "on_tag_emptied", args)
+        }
+        Hook::UserIdle if script.defines("on_user_idle", 0) => {
+            script.call(state, "on_user_idle", ())
+        }
+        Hook::UserActive if script.defines("on_user_active", 0) => {
+            script.call(state, "on_user_active", ())
+        }
+        _ => return false,
+    };
+    run_commands(commands, manager)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn script(source: &str) -> Script {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+        Script::load(file.path()).unwrap()
+    }
+
+    #[test]
+    fn script_functions_should_issue_commands() {
+        let script = script(
+            r#"
+            fn send_all(tag) {
+                for window in windows() {
+                    command(`SendWindowToTag ${tag}`);
+                }
+                command("NotACommand");
+                activate_window(3);
+            }
+            "#,
+        );
+        let window = Window::new(WindowHandle(3), None, None);
+        lock(&script.context).windows = vec![window_to_script(&window, true)];
+
+        assert!(script.defines("send_all", 1));
+        assert!(!script.defines("send_all", 0));
+        let commands: Vec<Command<i32>> =
+            parse_commands(&script.run("send_all", ("2".to_string(),)));
+        assert_eq!(
+            commands,
+            vec![
+                Command::SendWindowToTag {
+                    window: None,
+                    tag: 2
+                },
+                Command::ActivateWindow(leftwm_core::WindowSelector::Handle(WindowHandle(3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn runaway_scripts_should_be_stopped() {
+        let script = script("fn spin() { loop {} }");
+        assert!(script.run("spin", ()).is_empty());
+    }
+}
@@ -10,6 +10,8 @@ pub struct ThemeConfig {
     pub border_width: Option<i32>,
     pub margin: Option<CustomMargins>,
     pub workspace_margin: Option<CustomMargins>,
+    /// Space reserved around windows for an external decorator to draw in.
+    pub decoration_frame: Option<CustomMargins>,
     pub default_width: Option<i32>,
     pub default_height: Option<i32>,
     pub always_float: Option<bool>,
@@ -17,7 +19,18 @@ pub struct ThemeConfig {
     pub default_border_color: Option<String>,
     pub floating_border_color: Option<String>,
     pub focused_border_color: Option<String>,
+    pub selected_border_color: Option<String>,
+    pub urgent_border_color: Option<String>,
+    /// Border width of urgent windows, `border_width` without it.
+    pub urgent_border_width: Option<i32>,
     pub background_color: Option<String>,
+    /// Height of the title bars drawn on frames when `reparent_windows` is on, none without it.
+    pub title_bar_height: Option<u16>,
+    /// Core X font of the title bars.
+    pub title_bar_font: Option<String>,
+    pub title_bar_color: Option<String>,
+    pub title_bar_focused_color: Option<String>,
+    pub title_bar_text_color: Option<String>,
     #[serde(rename = "on_new_window")]
     pub on_new_window_cmd: Option<String>,
 }
@@ -40,6 +53,7 @@ impl Default for ThemeConfig {
             border_width: Some(1),
             margin: Some(CustomMargins::Int(10)),
             workspace_margin: Some(CustomMargins::Int(10)),
+            decoration_frame: None,
             default_width: Some(1000),
             default_height: Some(700),
             always_float: Some(false),
@@ -47,7 +61,15 @@ impl Default for ThemeConfig {
             default_border_color: Some("#000000".to_owned()),
             floating_border_color: Some("#000000".to_owned()),
             focused_border_color: Some("#FF0000".to_owned()),
+            selected_border_color: Some("#FFFF00".to_owned()),
+            urgent_border_color: Some("#FF8800".to_owned()),
+            urgent_border_width: None,
             background_color: Some("#333333".to_owned()),
+            title_bar_height: None,
+            title_bar_font: Some("fixed".to_owned()),
+            title_bar_color: Some("#222222".to_owned()),
+            title_bar_focused_color: Some("#005577".to_owned()),
+            title_bar_text_color: Some("#EEEEEE".to_owned()),
             on_new_window_cmd: None,
         }
     }
@@ -114,7 +136,13 @@ workspace_margin = 5
 default_border_color = '#222222'
 floating_border_color = '#005500'
 focused_border_color = '#FFB53A'
+urgent_border_color = '#FF00FF'
 background_color = '#333333'
+title_bar_height = 18
+title_bar_font = 'fixed'
+title_bar_color = '#222222'
+title_bar_focused_color = '#005577'
+title_bar_text_color = '#EEEEEE'
 on_new_window = 'echo Hello World'
 
 [[gutter]]
@@ -129,6 +157,7 @@ value = 0
                 border_width: Some(0),
                 margin: Some(CustomMargins::Int(5)),
                 workspace_margin: Some(CustomMargins::Int(5)),
+                decoration_frame: None,
                 default_width: Some(400),
                 default_height: Some(400),
                 always_float: Some(true),
@@ -140,7 +169,15 @@ value = 0
                 default_border_color: Some("#222222".to_string()),
                 floating_border_color: Some("#005500".to_string()),
                 focused_border_color: Some("#FFB53A".to_string()),
+                selected_border_color: None,
+                urgent_border_color: Some("#FF00FF".to_string()),
+                urgent_border_width: None,
                 background_color: Some("#333333".to_owned()),
+                title_bar_height: Some(18),
+                title_bar_font: Some("fixed".to_string()),
+                title_bar_color: Some("#222222".to_string()),
+                title_bar_focused_color: Some("#005577".to_string()),
+                title_bar_text_color: Some("#EEEEEE".to_string()),
                 on_new_window_cmd: Some("echo Hello World".to_string()),
             }
         );
@@ -159,7 +196,13 @@ value = 0
     default_border_color: Some("#222222"),
     floating_border_color: Some("#005500"),
     focused_border_color: Some("#FFB53A"),
+    urgent_border_color: Some("#FF00FF"),
     background_color: Some("#333333"),
+    title_bar_height: Some(18),
+    title_bar_font: Some("fixed"),
+    title_bar_color: Some("#222222"),
+    title_bar_focused_color: Some("#005577"),
+    title_bar_text_color: Some("#EEEEEE"),
     on_new_window: Some("echo Hello World"),
 
     gutter: Some([Gutter (
@@ -176,6 +219,7 @@ value = 0
                 border_width: Some(0),
                 margin: Some(CustomMargins::Int(5)),
                 workspace_margin: Some(CustomMargins::Int(5)),
+                decoration_frame: None,
                 default_width: Some(400),
                 default_height: Some(400),
                 always_float: Some(true),
@@ -187,7 +231,15 @@ value = 0
                 default_border_color: Some("#222222".to_string()),
                 floating_border_color: Some("#005500".to_string()),
                 focused_border_color: Some("#FFB53A".to_string()),
+                selected_border_color: None,
+                urgent_border_color: Some("#FF00FF".to_string()),
+                urgent_border_width: None,
                 background_color: Some("#333333".to_owned()),
+                title_bar_height: Some(18),
+                title_bar_font: Some("fixed".to_string()),
+                title_bar_color: Some("#222222".to_string()),
+                title_bar_focused_color: Some("#005577".to_string()),
+                title_bar_text_color: Some("#EEEEEE".to_string()),
                 on_new_window_cmd: Some("echo Hello World".to_string()),
             }
         );
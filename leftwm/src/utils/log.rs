@@ -10,6 +10,8 @@ pub mod file;
 #[cfg(feature = "sys-log")]
 mod sys;
 
+pub mod ring;
+
 #[must_use]
 #[allow(clippy::missing_panics_doc)]
 pub fn parse_log_level(level_regex: &str) -> (impl Subscriber, Option<ParseError>) {
@@ -28,6 +30,8 @@ pub fn parse_log_level(level_regex: &str) -> (impl Subscriber, Option<ParseError
 pub fn get_subscribers(filter: EnvFilter) -> impl Subscriber {
     let subscriber = tracing_subscriber::registry().with(filter);
 
+    let subscriber = ring::add_layer(subscriber);
+
     #[cfg(feature = "journald-log")]
     let subscriber = journald::add_layer(subscriber);
 
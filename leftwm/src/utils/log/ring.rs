@@ -0,0 +1,101 @@
+//! Keeps the most recent log records in memory, so they can be dumped with the `DumpLog` command
+//! after a glitch, whether or not a log output was being watched.
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tracing::Subscriber;
+use tracing_subscriber::{layer::SubscriberExt, registry::LookupSpan};
+use xdg::BaseDirectories;
+
+/// How many log records are kept in memory.
+pub const CAPACITY: usize = 1000;
+
+const DUMP_FILE_NAME: &str = "log-dump.log";
+
+static RECORDS: Mutex<LogRing> = Mutex::new(LogRing::new(CAPACITY));
+
+struct LogRing {
+    records: VecDeque<String>,
+    capacity: usize,
+}
+
+impl LogRing {
+    const fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, record: String) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+}
+
+/// Receives one formatted record per write from the fmt layer.
+struct RingWriter;
+
+impl Write for RingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let record = String::from_utf8_lossy(buf).trim_end().to_string();
+        if let Ok(mut ring) = RECORDS.lock() {
+            ring.push(record);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn add_layer<S>(subscriber: S) -> impl Subscriber + for<'span> LookupSpan<'span>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_writer(|| RingWriter);
+    subscriber.with(layer)
+}
+
+/// Returns the log records kept in memory, oldest first.
+#[must_use]
+pub fn records() -> Vec<String> {
+    RECORDS
+        .lock()
+        .map(|ring| ring.records.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Writes the log records kept in memory to `log-dump.log` in the leftwm state directory.
+///
+/// # Errors
+///
+/// Will error if the state directory can't be created or the file can't be written.
+pub fn dump() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = BaseDirectories::with_prefix("leftwm")?.place_state_file(DUMP_FILE_NAME)?;
+    let mut contents = records().join("\n");
+    contents.push('\n');
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_should_drop_the_oldest_records_once_full() {
+        let mut ring = LogRing::new(3);
+        for i in 0..5 {
+            ring.push(i.to_string());
+        }
+        assert_eq!(ring.records, ["2", "3", "4"]);
+    }
+}